@@ -12,10 +12,14 @@ use tracing as log;
 
 use crate::expr::str;
 use crate::Expr::*;
-use crate::{eval, Env, Expr, Policy};
+use crate::{
+    eval, policy_hash, Action, Env, Expr, Policy, PolicyAuditRepository, PolicyDecision, Resource,
+};
 use ockam_core::compat::format;
-use ockam_core::compat::string::ToString;
+use ockam_core::compat::string::{String, ToString};
 use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_identity::utils::now;
 use ockam_identity::{Identifier, IdentityAttributesRepository, IdentitySecureChannelLocalInfo};
 
 /// This AccessControl uses a storage for authenticated attributes in order
@@ -26,6 +30,66 @@ pub struct AbacAccessControl {
     identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
     policy: Policy,
     environment: Env,
+    audit: Option<PolicyAudit>,
+}
+
+/// Repository where the decisions taken by an access control are recorded,
+/// together with the resource and action being accessed
+#[derive(Clone)]
+pub(crate) struct PolicyAudit {
+    repository: Arc<dyn PolicyAuditRepository>,
+    resource: Resource,
+    action: Action,
+}
+
+impl PolicyAudit {
+    pub(crate) fn new(
+        repository: Arc<dyn PolicyAuditRepository>,
+        resource: Resource,
+        action: Action,
+    ) -> Self {
+        Self {
+            repository,
+            resource,
+            action,
+        }
+    }
+
+    /// Record a decision. A failure to record a decision does not change the decision itself
+    pub(crate) async fn record(
+        &self,
+        policy: &Policy,
+        subject: Option<Identifier>,
+        environment_keys: Vec<String>,
+        is_authorized: bool,
+    ) {
+        let decision = match (now(), policy_hash(policy.expression())) {
+            (Ok(decided_at), Ok(policy_hash)) => PolicyDecision::new(
+                decided_at,
+                subject,
+                Some(self.resource.clone()),
+                Some(self.action.clone()),
+                policy_hash,
+                is_authorized,
+                environment_keys,
+            ),
+            (Err(e), _) | (_, Err(e)) => {
+                log::warn! {
+                    policy = %policy,
+                    err    = %e,
+                    "cannot create a policy decision"
+                }
+                return;
+            }
+        };
+        if let Err(e) = self.repository.store_decision(&decision).await {
+            log::warn! {
+                policy = %policy,
+                err    = %e,
+                "cannot store a policy decision"
+            }
+        }
+    }
 }
 
 /// Debug implementation printing out the policy expression only
@@ -47,9 +111,26 @@ impl AbacAccessControl {
             identity_attributes_repository,
             policy,
             environment,
+            audit: None,
         }
     }
 
+    /// Record all the decisions taken by this access control for a given resource and action
+    pub fn with_audit_repository(
+        mut self,
+        repository: Arc<dyn PolicyAuditRepository>,
+        resource: Resource,
+        action: Action,
+    ) -> Self {
+        self.audit = Some(PolicyAudit::new(repository, resource, action));
+        self
+    }
+
+    pub(crate) fn with_audit(mut self, audit: Option<PolicyAudit>) -> Self {
+        self.audit = audit;
+        self
+    }
+
     /// Create an AccessControl which will verify that the sender of
     /// a message has an authenticated attribute with the correct name and value
     pub fn create(
@@ -74,12 +155,25 @@ where {
 impl AbacAccessControl {
     /// Returns true if the identity is authorized
     pub async fn is_identity_authorized(&self, id: Identifier) -> Result<bool> {
+        let environment = self.make_environment(&id).await?;
+        let is_authorized = self.evaluate(&id, &environment);
+        if let Some(audit) = &self.audit {
+            let keys = environment.entries().map(|(k, _)| k.to_string()).collect();
+            audit
+                .record(&self.policy, Some(id), keys, is_authorized)
+                .await
+        }
+        Ok(is_authorized)
+    }
+
+    /// Populate the evaluation environment with the attributes of an identity
+    async fn make_environment(&self, id: &Identifier) -> Result<Env> {
         let mut environment = self.environment.clone();
 
         // Get identity attributes and populate the environment:
         if let Some(attrs) = self
             .identity_attributes_repository
-            .get_attributes(&id)
+            .get_attributes(id)
             .await?
         {
             for (key, value) in attrs.attrs() {
@@ -130,9 +224,12 @@ impl AbacAccessControl {
 
         // add the identifier itself as a subject parameter
         environment.put("subject.identifier", str(id.to_string()));
+        Ok(environment)
+    }
 
-        // Finally, evaluate the expression and return the result:
-        match eval(self.policy.expression(), &environment) {
+    /// Evaluate the policy expression and return the result
+    fn evaluate(&self, id: &Identifier, environment: &Env) -> bool {
+        match eval(self.policy.expression(), environment) {
             Ok(Expr::Bool(b)) => {
                 log::debug! {
                     policy        = %self.policy,
@@ -140,7 +237,7 @@ impl AbacAccessControl {
                     is_authorized = %b,
                     "policy evaluated"
                 }
-                Ok(b)
+                b
             }
             Ok(x) => {
                 log::warn! {
@@ -149,7 +246,7 @@ impl AbacAccessControl {
                     expr   = %x,
                     "evaluation did not yield a boolean result"
                 }
-                Ok(false)
+                false
             }
             Err(e) => {
                 log::warn! {
@@ -158,7 +255,7 @@ impl AbacAccessControl {
                     err    = %e,
                     "policy evaluation failed"
                 }
-                Ok(false)
+                false
             }
         }
     }
@@ -176,9 +273,54 @@ impl IncomingAccessControl for AbacAccessControl {
                 policy = %self.policy,
                 "identity identifier not found; access denied"
             }
+            if let Some(audit) = &self.audit {
+                let keys = self
+                    .environment
+                    .entries()
+                    .map(|(k, _)| k.to_string())
+                    .collect();
+                audit.record(&self.policy, None, keys, false).await
+            }
             return Ok(false);
         };
 
         self.is_identity_authorized(id).await
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parse;
+    use ockam_core::compat::collections::BTreeMap;
+    use ockam_identity::{identities, AttributesEntry, IdentityAttributesSqlxDatabase};
+
+    #[tokio::test]
+    async fn numeric_attributes_are_compared_after_conversion() -> Result<()> {
+        let identities = identities().await?;
+        let repository = IdentityAttributesSqlxDatabase::create().await?;
+        let now = now()?.0;
+
+        let valid = identities.identities_creation().create_identity().await?;
+        let expired = identities.identities_creation().create_identity().await?;
+        for (subject, valid_until) in [(&valid, now + 3600), (&expired, now - 3600)] {
+            let attributes = BTreeMap::from([(
+                b"valid_until".to_vec(),
+                valid_until.to_string().into_bytes(),
+            )]);
+            repository
+                .put_attributes(
+                    subject,
+                    AttributesEntry::new(attributes, now.into(), None, None),
+                )
+                .await?;
+        }
+
+        let expression = parse("(> (int subject.valid_until) (now))")?.unwrap();
+        let access_control =
+            AbacAccessControl::new(repository, Policy::new(expression), Env::new());
+        assert!(access_control.is_identity_authorized(valid).await?);
+        assert!(!access_control.is_identity_authorized(expired).await?);
+        Ok(())
+    }
+}
@@ -1,6 +1,7 @@
-use ockam_abac::{eval, parse, Env, Expr};
+use ockam_abac::{eval, parse, Env, EvalError, Expr};
 use rustyline::error::ReadlineError;
 use rustyline::highlight::MatchingBracketHighlighter;
+use rustyline::history::DefaultHistory;
 use rustyline::validate::MatchingBracketValidator;
 use rustyline::{Config, EditMode, Editor, Result};
 use rustyline_derive::{Completer, Helper, Highlighter, Hinter, Validator};
@@ -9,7 +10,14 @@ const HELP: &str = r#"Available commands:
   :def <id> <expression>  -- Add an expression to the environment.
   :env                    -- Show all current environment entries.
   :clear                  -- Remove all bindings from the environment.
-  :help | :h | :?         -- Show this help message."#;
+  :help | :h | :?         -- Show this help message.
+
+Available operators:
+  and, or, not, if, <, >, =, !=, member?, exists?
+  starts-with?, ends-with?, contains?, matches?  -- String predicates.
+  +, -                                           -- Integer arithmetic.
+  int                                            -- Convert a string to an integer.
+  now                                            -- Current time in seconds since the Unix epoch."#;
 
 #[derive(Completer, Helper, Highlighter, Hinter, Validator)]
 struct ReplHelper {
@@ -27,7 +35,7 @@ fn main() -> Result<()> {
         .build();
 
     let mut env = Env::new();
-    let mut repl = Editor::<ReplHelper>::with_config(c)?;
+    let mut repl = Editor::<ReplHelper, DefaultHistory>::with_config(c)?;
     repl.set_helper(Some(ReplHelper {
         highlighter: MatchingBracketHighlighter::new(),
         validator: MatchingBracketValidator::new(),
@@ -44,7 +52,7 @@ fn main() -> Result<()> {
                         Ok(None) => continue,
                         Ok(Some(e)) => match eval(&e, &env) {
                             Ok(x) => println!("{x}"),
-                            Err(e) => eprintln!("error: {e}"),
+                            Err(e) => report(&e),
                         },
                         Err(e) => eprintln!("error: {e}"),
                     }
@@ -71,7 +79,7 @@ fn on_command(line: &str, env: &mut Env) {
                         Ok(x) => {
                             env.put(name, x);
                         }
-                        Err(e) => eprintln!("error: {e}"),
+                        Err(e) => report(&e),
                     }
                 } else {
                     eprintln!("invalid :def command")
@@ -90,3 +98,11 @@ fn on_command(line: &str, env: &mut Env) {
         (cmd, _) => eprintln!("unknown command {cmd}"),
     }
 }
+
+fn report(e: &EvalError) {
+    if e.is_type_error() {
+        eprintln!("type error: {e}")
+    } else {
+        eprintln!("error: {e}")
+    }
+}
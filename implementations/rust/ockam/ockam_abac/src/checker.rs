@@ -0,0 +1,563 @@
+use core::fmt;
+
+use crate::env::Env;
+use crate::eval::eval;
+use crate::expr::Expr;
+use ockam_core::compat::format;
+use ockam_core::compat::string::{String, ToString};
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+
+/// Prefixes of the identifiers which are bound when a policy is evaluated.
+///
+/// Subject attributes come from credentials, resource and action attributes
+/// are set by the node. They are all strings, numeric attributes are converted
+/// with the `int` operator, as in `(> (int subject.valid_until) (now))`.
+const KNOWN_PREFIXES: [&str; 3] = ["subject.", "resource.", "action."];
+
+/// Type of an expression, as inferred by [`check`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Type {
+    Str,
+    Int,
+    Float,
+    Bool,
+    Seq,
+    Unit,
+    /// The type could not be inferred, for example because of a previous error.
+    Unknown,
+}
+
+impl Type {
+    fn of(expr: &Expr) -> Self {
+        match expr {
+            Expr::Str(_) => Type::Str,
+            Expr::Int(_) => Type::Int,
+            Expr::Float(_) => Type::Float,
+            Expr::Bool(_) => Type::Bool,
+            Expr::Seq(_) => Type::Seq,
+            Expr::List(xs) if xs.is_empty() => Type::Unit,
+            Expr::List(_) | Expr::Ident(_) => Type::Unknown,
+        }
+    }
+
+    /// Two types are compatible if they are equal or if one of them is unknown.
+    fn is_compatible(self, other: Type) -> bool {
+        self == other || self == Type::Unknown || other == Type::Unknown
+    }
+}
+
+impl fmt::Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Type::Str => f.write_str("string"),
+            Type::Int => f.write_str("integer"),
+            Type::Float => f.write_str("float"),
+            Type::Bool => f.write_str("boolean"),
+            Type::Seq => f.write_str("sequence"),
+            Type::Unit => f.write_str("unit"),
+            Type::Unknown => f.write_str("unknown"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// The expression is guaranteed to fail when evaluated.
+    Error,
+    /// The expression can be evaluated but is most likely not what was intended.
+    Warning,
+}
+
+/// A problem found in a policy expression.
+#[derive(Debug, Clone)]
+pub struct Diagnostic {
+    severity: Severity,
+    expr: Expr,
+    message: String,
+}
+
+impl Diagnostic {
+    fn error<S: Into<String>>(expr: &Expr, message: S) -> Self {
+        Diagnostic {
+            severity: Severity::Error,
+            expr: expr.clone(),
+            message: message.into(),
+        }
+    }
+
+    fn warning<S: Into<String>>(expr: &Expr, message: S) -> Self {
+        Diagnostic {
+            severity: Severity::Warning,
+            expr: expr.clone(),
+            message: message.into(),
+        }
+    }
+
+    pub fn severity(&self) -> Severity {
+        self.severity
+    }
+
+    pub fn is_error(&self) -> bool {
+        self.severity == Severity::Error
+    }
+
+    /// The sub-expression this diagnostic is about.
+    pub fn expr(&self) -> &Expr {
+        &self.expr
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let severity = match self.severity {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+        };
+        write!(f, "{severity}: {} in {}", self.message, self.expr)
+    }
+}
+
+/// The errors found by [`check_policy`].
+#[derive(Debug)]
+pub struct CheckError(Vec<Diagnostic>);
+
+impl CheckError {
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.0
+    }
+}
+
+impl fmt::Display for CheckError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("invalid policy expression")?;
+        for d in &self.0 {
+            write!(f, "\n  {d}")?
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for CheckError {}
+
+impl From<CheckError> for ockam_core::Error {
+    fn from(e: CheckError) -> Self {
+        ockam_core::Error::new(Origin::Application, Kind::Invalid, e.to_string())
+    }
+}
+
+/// Check a policy expression before it is stored.
+///
+/// Returns the warnings if the expression has no errors.
+pub fn check_policy(expr: &Expr) -> Result<Vec<Diagnostic>, CheckError> {
+    let (errors, warnings): (Vec<_>, Vec<_>) =
+        check(expr).into_iter().partition(Diagnostic::is_error);
+    if errors.is_empty() {
+        Ok(warnings)
+    } else {
+        Err(CheckError(errors))
+    }
+}
+
+/// Statically check a policy expression.
+///
+/// The type of every sub-expression is inferred from literals and from the
+/// identifiers known to be bound at evaluation time (`subject.*`, `resource.*`
+/// and `action.*`, which are all strings, and can be converted to integers with
+/// `int`). The following problems are reported:
+///
+///  - errors: unknown operators and identifiers, wrong number of arguments,
+///    arguments of the wrong type, invalid regular expressions, and constant
+///    sub-expressions whose evaluation fails (e.g. integer overflows).
+///  - warnings: boolean sub-expressions which are always true or always false,
+///    including `if` branches which can never be taken.
+#[rustfmt::skip]
+pub fn check(expr: &Expr) -> Vec<Diagnostic> {
+    /// A stack operation.
+    enum Op<'a> {
+        Visit(&'a Expr),
+        /// Check the operator application or sequence, using the
+        /// information inferred for its arguments.
+        Apply(&'a Expr),
+    }
+
+    /// What is known about an expression after checking it.
+    struct Info {
+        typ: Type,
+        /// The value of the expression if it can be computed statically.
+        value: Option<Expr>,
+        /// Whether the value was computed from an operator application.
+        folded: bool,
+    }
+
+    impl Info {
+        fn new(typ: Type) -> Self {
+            Info { typ, value: None, folded: false }
+        }
+
+        fn constant(value: Expr) -> Self {
+            Info { typ: Type::of(&value), value: Some(value), folded: false }
+        }
+
+        fn folded(value: Expr) -> Self {
+            Info { typ: Type::of(&value), value: Some(value), folded: true }
+        }
+    }
+
+    let mut diagnostics = Vec::new();
+
+    // Control stack.
+    let mut ctrl: Vec<Op> = Vec::new();
+    // Information about the checked expressions.
+    let mut infos: Vec<Info> = Vec::new();
+
+    ctrl.push(Op::Visit(expr));
+
+    while let Some(x) = ctrl.pop() {
+        match x {
+            Op::Visit(e @ Expr::Ident(id)) => {
+                if !is_known_identifier(id) {
+                    diagnostics.push(Diagnostic::error(e, format!("unbound identifier: {id}")))
+                }
+                infos.push(Info::new(Type::Str))
+            }
+            Op::Visit(e @ Expr::List(xs)) => match &xs[..] {
+                [] => infos.push(Info::constant(e.clone())),
+                [Expr::Ident(id), args @ ..] if id == "exists?" => {
+                    // The arguments of 'exists?' are not evaluated.
+                    for a in args {
+                        if !a.is_ident() {
+                            let msg = "'exists?' expects identifiers as arguments";
+                            diagnostics.push(Diagnostic::error(a, msg))
+                        }
+                    }
+                    infos.push(Info::new(Type::Bool))
+                }
+                [Expr::Ident(_), args @ ..] => {
+                    ctrl.push(Op::Apply(e));
+                    for a in args.iter().rev() {
+                        ctrl.push(Op::Visit(a))
+                    }
+                }
+                [other, ..] => {
+                    diagnostics.push(Diagnostic::error(other, "expected (op ...)"));
+                    infos.push(Info::new(Type::Unknown))
+                }
+            },
+            Op::Visit(e @ Expr::Seq(xs)) => {
+                ctrl.push(Op::Apply(e));
+                for x in xs.iter().rev() {
+                    ctrl.push(Op::Visit(x))
+                }
+            }
+            Op::Visit(e) => infos.push(Info::constant(e.clone())),
+            Op::Apply(Expr::Seq(xs)) => {
+                let args = infos.split_off(infos.len() - xs.len());
+                let info = if args.iter().all(|a| a.value.is_some()) {
+                    Info::constant(Expr::Seq(args.into_iter().filter_map(|a| a.value).collect()))
+                } else {
+                    for (x, (a, b)) in xs.iter().skip(1).zip(args.iter().zip(args.iter().skip(1))) {
+                        if !a.typ.is_compatible(b.typ) {
+                            let msg = format!("sequence elements must have the same type, found {} and {}", a.typ, b.typ);
+                            diagnostics.push(Diagnostic::error(x, msg));
+                            break
+                        }
+                    }
+                    Info::new(Type::Seq)
+                };
+                infos.push(info)
+            }
+            Op::Apply(e @ Expr::List(xs)) => {
+                let op = match &xs[0] {
+                    Expr::Ident(op) => op.as_str(),
+                    _ => unreachable!("only operator applications are applied"),
+                };
+                let exprs = &xs[1 ..];
+                let args = infos.split_off(infos.len() - exprs.len());
+                let errors = diagnostics.iter().filter(|d| d.is_error()).count();
+                let typ = check_operator(e, op, exprs, &args.iter().map(|a| a.typ).collect::<Vec<_>>(), &mut diagnostics);
+
+                // Try to compute the value of the expression if there were no errors
+                // so far and if all the arguments are known. Otherwise, check if the
+                // result of 'and' / 'or' is determined by one of its arguments.
+                let has_errors = diagnostics.iter().filter(|d| d.is_error()).count() > errors;
+                let value = if has_errors || op == "now" {
+                    None
+                } else if args.iter().all(|a| a.value.is_some()) {
+                    let mut v = Vec::with_capacity(xs.len());
+                    v.push(xs[0].clone());
+                    v.extend(args.iter().filter_map(|a| a.value.clone()));
+                    match eval(&Expr::List(v), &Env::new()) {
+                        Ok(x) => Some(x),
+                        Err(err) => {
+                            diagnostics.push(Diagnostic::error(e, err.to_string()));
+                            None
+                        }
+                    }
+                } else {
+                    let short_circuit = match op {
+                        "and" => Some(false),
+                        "or" => Some(true),
+                        _ => None,
+                    };
+                    short_circuit.and_then(|b| {
+                        args.iter()
+                            .any(|a| matches!(a.value, Some(Expr::Bool(x)) if x == b))
+                            .then_some(Expr::Bool(b))
+                    })
+                };
+
+                if op == "if" {
+                    if let Some(Expr::Bool(b)) = &args[0].value {
+                        let never = if *b { "else" } else { "then" };
+                        let msg = format!("condition is always {b}, the '{never}' branch is never taken");
+                        diagnostics.push(Diagnostic::warning(&exprs[0], msg))
+                    }
+                }
+
+                match value {
+                    Some(x) => {
+                        // Only report the innermost constant boolean expression.
+                        if let (Expr::Bool(b), false) = (&x, args.iter().any(|a| a.folded)) {
+                            diagnostics.push(Diagnostic::warning(e, format!("expression is always {b}")))
+                        }
+                        infos.push(Info::folded(x))
+                    }
+                    None => infos.push(Info::new(typ)),
+                }
+            }
+            Op::Apply(_) => unreachable!("only lists and sequences are applied"),
+        }
+    }
+
+    diagnostics
+}
+
+/// Check the arity and argument types of an operator application and return its type.
+#[rustfmt::skip]
+fn check_operator(
+    e: &Expr,
+    op: &str,
+    exprs: &[Expr],
+    args: &[Type],
+    diagnostics: &mut Vec<Diagnostic>,
+) -> Type {
+    let mut arity = |ok: bool, msg: &str| {
+        if !ok {
+            diagnostics.push(Diagnostic::error(e, format!("'{op}' requires {msg}")))
+        }
+        ok
+    };
+
+    let ok = match op {
+        "and" | "or" | "+"                => true,
+        "not" | "int"                     => arity(args.len() == 1, "one argument"),
+        "if"                              => arity(args.len() == 3, "three arguments"),
+        "<" | ">" | "=" | "!="            => arity(args.len() >= 2, "at least two arguments"),
+        "member?" | "starts-with?" | "ends-with?" | "contains?" | "matches?"
+                                          => arity(args.len() == 2, "two arguments"),
+        "-"                               => arity(!args.is_empty(), "at least one argument"),
+        "now"                             => arity(args.is_empty(), "no arguments"),
+        _ => {
+            diagnostics.push(Diagnostic::error(e, format!("unknown operator: {op}")));
+            return Type::Unknown
+        }
+    };
+    if !ok {
+        return Type::Unknown
+    }
+
+    let mut expect = |i: usize, t: Type| {
+        if !args[i].is_compatible(t) {
+            let msg = format!("'{op}' expects {t} arguments, found {}", args[i]);
+            diagnostics.push(Diagnostic::error(&exprs[i], msg))
+        }
+    };
+
+    match op {
+        "and" | "or" | "not" => {
+            (0 .. args.len()).for_each(|i| expect(i, Type::Bool));
+            Type::Bool
+        }
+        "if" => {
+            expect(0, Type::Bool);
+            if args[1] == args[2] { args[1] } else { Type::Unknown }
+        }
+        "<" | ">" | "=" | "!=" => {
+            for i in 1 .. args.len() {
+                if !args[i - 1].is_compatible(args[i]) {
+                    let msg = format!("'{op}' expects arguments of the same type, found {} and {}", args[i - 1], args[i]);
+                    diagnostics.push(Diagnostic::error(&exprs[i], msg));
+                    break
+                }
+            }
+            Type::Bool
+        }
+        "member?" => {
+            expect(1, Type::Seq);
+            Type::Bool
+        }
+        "starts-with?" | "ends-with?" | "contains?" => {
+            expect(0, Type::Str);
+            expect(1, Type::Str);
+            Type::Bool
+        }
+        "matches?" => {
+            expect(0, Type::Str);
+            expect(1, Type::Str);
+            #[cfg(feature = "std")]
+            if let Expr::Str(pattern) = &exprs[1] {
+                if let Err(err) = regex::Regex::new(pattern) {
+                    let msg = format!("invalid regular expression: {err}");
+                    diagnostics.push(Diagnostic::error(&exprs[1], msg))
+                }
+            }
+            Type::Bool
+        }
+        "+" | "-" => {
+            (0 .. args.len()).for_each(|i| expect(i, Type::Int));
+            Type::Int
+        }
+        "int" => {
+            if !args[0].is_compatible(Type::Str) && !args[0].is_compatible(Type::Int) {
+                let msg = format!("'int' expects a string or an integer argument, found {}", args[0]);
+                diagnostics.push(Diagnostic::error(&exprs[0], msg))
+            }
+            Type::Int
+        }
+        "now" => Type::Int,
+        _ => unreachable!("unknown operators are reported above"),
+    }
+}
+
+fn is_known_identifier(id: &str) -> bool {
+    KNOWN_PREFIXES
+        .iter()
+        .any(|p| id.len() > p.len() && id.starts_with(p))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{check, check_policy, Diagnostic, Severity};
+    use crate::parse;
+
+    fn diagnostics(s: &str) -> Vec<Diagnostic> {
+        check(&parse(s).unwrap().unwrap())
+    }
+
+    fn errors(s: &str) -> Vec<String> {
+        diagnostics(s)
+            .into_iter()
+            .filter(|d| d.is_error())
+            .map(|d| d.message().to_string())
+            .collect()
+    }
+
+    fn warnings(s: &str) -> Vec<String> {
+        diagnostics(s)
+            .into_iter()
+            .filter(|d| d.severity() == Severity::Warning)
+            .map(|d| d.message().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn valid_policies() {
+        for p in [
+            "true",
+            r#"(= subject.trust_context_id resource.trust_context_id)"#,
+            r#"(and (= subject.ockam-role "enroller") (ends-with? subject.email "@ourcorp.com"))"#,
+            r#"(or (exists? subject.admin) (member? subject.role ["a" "b"]))"#,
+            r#"(matches? subject.email "^[a-z]+@ourcorp\\.com$")"#,
+            r#"(if (exists? subject.group) (= subject.group "dev") false)"#,
+            "(> (- (now) 60) 0)",
+            "(> (int subject.valid_until) (now))",
+            r#"(< (+ (int subject.age) 1) (int resource.max_age))"#,
+        ] {
+            assert!(diagnostics(p).is_empty(), "{p}: {:?}", diagnostics(p))
+        }
+    }
+
+    #[test]
+    fn unknown_operators_and_identifiers() {
+        assert_eq!(errors("(foo subject.a)"), ["unknown operator: foo"]);
+        assert_eq!(errors("(= x subject.a)"), ["unbound identifier: x"]);
+        assert_eq!(errors("(= 1 2 (3))"), ["expected (op ...)"]);
+    }
+
+    #[test]
+    fn wrong_arity() {
+        assert_eq!(errors("(not true false)"), ["'not' requires one argument"]);
+        assert_eq!(
+            errors("(= subject.a)"),
+            ["'=' requires at least two arguments"]
+        );
+        assert_eq!(
+            errors(r#"(starts-with? subject.a)"#),
+            ["'starts-with?' requires two arguments"]
+        );
+        assert_eq!(errors("(now 1)"), ["'now' requires no arguments"]);
+    }
+
+    #[test]
+    fn type_errors() {
+        assert_eq!(
+            errors("(= subject.a 1)"),
+            ["'=' expects arguments of the same type, found string and integer"]
+        );
+        assert_eq!(
+            errors(r#"(and subject.a true)"#),
+            ["'and' expects boolean arguments, found string"]
+        );
+        assert_eq!(
+            errors("(+ 1 subject.a)"),
+            ["'+' expects integer arguments, found string"]
+        );
+        assert_eq!(
+            errors(r#"(member? subject.a "b")"#),
+            ["'member?' expects sequence arguments, found string"]
+        );
+        assert!(errors(r#"(matches? subject.a "(")"#)[0].starts_with("invalid regular expression"));
+        assert_eq!(
+            errors("(> subject.valid_until (now))"),
+            ["'>' expects arguments of the same type, found string and integer"]
+        );
+        assert_eq!(
+            errors("(int true)"),
+            ["'int' expects a string or an integer argument, found boolean"]
+        );
+        assert_eq!(errors(r#"(int "1.5")"#).len(), 1);
+        assert_eq!(
+            errors("(+ 9223372036854775807 1)"),
+            ["integer overflow in '+'"]
+        );
+    }
+
+    #[test]
+    fn constant_expressions() {
+        assert_eq!(warnings("(= 1 1)"), ["expression is always true"]);
+        assert_eq!(warnings("(not (= 1 1))"), ["expression is always true"]);
+        assert_eq!(
+            warnings(r#"(and (= subject.a "b") false)"#),
+            ["expression is always false"]
+        );
+        assert_eq!(
+            warnings(r#"(if true (= subject.a "b") false)"#),
+            ["condition is always true, the 'else' branch is never taken"]
+        );
+        assert!(warnings(r#"(or (= subject.a "b") false)"#).is_empty());
+    }
+
+    #[test]
+    fn check_policy_separates_errors_from_warnings() {
+        let warnings = check_policy(&parse("(= 1 1)").unwrap().unwrap()).unwrap();
+        assert_eq!(warnings.len(), 1);
+        let err = check_policy(&parse("(foo 1)").unwrap().unwrap()).unwrap_err();
+        assert_eq!(err.diagnostics().len(), 1);
+    }
+}
@@ -22,6 +22,8 @@ pub enum EvalError {
     InvalidType(Expr, &'static str),
     TypeMismatch(Expr, Expr),
     Malformed(String),
+    Overflow(String),
+    Unsupported(String),
 }
 
 #[derive(Debug)]
@@ -40,9 +42,25 @@ impl EvalError {
         EvalError::Malformed(s.into())
     }
 
+    pub fn overflow<S: Into<String>>(op: S) -> Self {
+        EvalError::Overflow(op.into())
+    }
+
+    pub fn unsupported<S: Into<String>>(op: S) -> Self {
+        EvalError::Unsupported(op.into())
+    }
+
     pub fn is_unbound(&self) -> bool {
         matches!(self, EvalError::Unbound(_))
     }
+
+    /// Is this error caused by an operator being applied to values of the wrong type?
+    pub fn is_type_error(&self) -> bool {
+        matches!(
+            self,
+            EvalError::InvalidType(..) | EvalError::TypeMismatch(..)
+        )
+    }
 }
 
 impl From<Utf8Error> for ParseError {
@@ -91,6 +109,10 @@ impl fmt::Display for EvalError {
             EvalError::InvalidType(e, m) => write!(f, "invalid type of expression {e}: {m}"),
             EvalError::Malformed(m) => write!(f, "malformed expression: {m}"),
             EvalError::TypeMismatch(a, b) => write!(f, "{a} and {b} are not of the same type"),
+            EvalError::Overflow(op) => write!(f, "integer overflow in '{op}'"),
+            EvalError::Unsupported(op) => {
+                write!(f, "operator not supported on this platform: {op}")
+            }
         }
     }
 }
@@ -3,7 +3,7 @@ use core::cmp::Ordering;
 use crate::env::Env;
 use crate::error::EvalError;
 use crate::expr::{unit, Expr};
-use ockam_core::compat::string::ToString;
+use ockam_core::compat::string::{String, ToString};
 use ockam_core::compat::vec::Vec;
 
 #[rustfmt::skip]
@@ -23,6 +23,13 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
         Gt(usize),
         Lt(usize),
         Member,
+        StartsWith,
+        EndsWith,
+        Contains,
+        Matches,
+        ToInt,
+        Add(usize),
+        Sub(usize),
         Seq(usize),
     }
 
@@ -132,6 +139,57 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
                             }
                             ctrl.push(Op::Member)
                         }
+                        "starts-with?" => {
+                            if nargs != 2 {
+                                let msg = "'starts-with?' requires two arguments";
+                                return Err(EvalError::malformed(msg))
+                            }
+                            ctrl.push(Op::StartsWith)
+                        }
+                        "ends-with?" => {
+                            if nargs != 2 {
+                                let msg = "'ends-with?' requires two arguments";
+                                return Err(EvalError::malformed(msg))
+                            }
+                            ctrl.push(Op::EndsWith)
+                        }
+                        "contains?" => {
+                            if nargs != 2 {
+                                let msg = "'contains?' requires two arguments";
+                                return Err(EvalError::malformed(msg))
+                            }
+                            ctrl.push(Op::Contains)
+                        }
+                        "matches?" => {
+                            if nargs != 2 {
+                                let msg = "'matches?' requires two arguments";
+                                return Err(EvalError::malformed(msg))
+                            }
+                            ctrl.push(Op::Matches)
+                        }
+                        "int" => {
+                            if nargs != 1 {
+                                return Err(EvalError::malformed("'int' requires one argument"))
+                            }
+                            ctrl.push(Op::ToInt)
+                        }
+                        "+" => {
+                            ctrl.push(Op::Add(nargs))
+                        }
+                        "-" => {
+                            if nargs < 1 {
+                                let msg = "'-' requires at least one argument";
+                                return Err(EvalError::malformed(msg))
+                            }
+                            ctrl.push(Op::Sub(nargs))
+                        }
+                        "now" => {
+                            if nargs != 0 {
+                                return Err(EvalError::malformed("'now' takes no arguments"))
+                            }
+                            args.push(now()?);
+                            continue
+                        }
                         "exists?" => {
                             let mut b = true;
                             for x in &xs[1 ..] {
@@ -255,6 +313,59 @@ pub fn eval(expr: &Expr, env: &Env) -> Result<Expr, EvalError> {
                     }
                 }
             }
+            Op::StartsWith => {
+                let (s, p) = pop_strings(&mut args, "'starts-with?' expects string arguments")?;
+                args.push(Expr::Bool(s.starts_with(p.as_str())))
+            }
+            Op::EndsWith => {
+                let (s, p) = pop_strings(&mut args, "'ends-with?' expects string arguments")?;
+                args.push(Expr::Bool(s.ends_with(p.as_str())))
+            }
+            Op::Contains => {
+                let (s, p) = pop_strings(&mut args, "'contains?' expects string arguments")?;
+                args.push(Expr::Bool(s.contains(p.as_str())))
+            }
+            Op::Matches => {
+                let (s, p) = pop_strings(&mut args, "'matches?' expects string arguments")?;
+                args.push(Expr::Bool(is_match(&s, &p)?))
+            }
+            Op::ToInt => {
+                match pop(&mut args) {
+                    Expr::Int(i) => args.push(Expr::Int(i)),
+                    Expr::Str(s) => match s.trim().parse() {
+                        Ok(i) => args.push(Expr::Int(i)),
+                        Err(_) => {
+                            let msg = "'int' expects a string containing an integer";
+                            return Err(EvalError::InvalidType(Expr::Str(s), msg))
+                        }
+                    }
+                    other => {
+                        let msg = "'int' expects a string or an integer argument";
+                        return Err(EvalError::InvalidType(other, msg))
+                    }
+                }
+            }
+            Op::Add(n) => {
+                let xs = pop_ints(n, &mut args, "'+' expects integer arguments")?;
+                let mut r: i64 = 0;
+                for x in xs {
+                    r = r.checked_add(x).ok_or_else(|| EvalError::overflow("+"))?
+                }
+                args.push(Expr::Int(r))
+            }
+            Op::Sub(n) => {
+                let xs = pop_ints(n, &mut args, "'-' expects integer arguments")?;
+                let r = if let [x] = xs[..] {
+                    x.checked_neg().ok_or_else(|| EvalError::overflow("-"))?
+                } else {
+                    let mut r = xs[0];
+                    for x in &xs[1 ..] {
+                        r = r.checked_sub(*x).ok_or_else(|| EvalError::overflow("-"))?
+                    }
+                    r
+                };
+                args.push(Expr::Int(r))
+            }
             Op::Seq(n) => {
                 let s = args.split_off(args.len() - n);
                 args.push(Expr::Seq(s))
@@ -275,6 +386,77 @@ fn pop<T>(s: &mut Vec<T>) -> T {
     s.pop().expect("stack is not empty")
 }
 
+/// Pop off the two topmost arguments which must both be strings.
+fn pop_strings(args: &mut Vec<Expr>, msg: &'static str) -> Result<(String, String), EvalError> {
+    let b = pop(args);
+    let a = pop(args);
+    match (a, b) {
+        (Expr::Str(a), Expr::Str(b)) => Ok((a, b)),
+        (Expr::Str(_), other) | (other, _) => Err(EvalError::InvalidType(other, msg)),
+    }
+}
+
+/// Pop off the `n` topmost arguments which must all be integers.
+fn pop_ints(n: usize, args: &mut Vec<Expr>, msg: &'static str) -> Result<Vec<i64>, EvalError> {
+    let mut xs = Vec::with_capacity(n);
+    for x in args.split_off(args.len() - n) {
+        match x {
+            Expr::Int(i) => xs.push(i),
+            other => return Err(EvalError::InvalidType(other, msg)),
+        }
+    }
+    Ok(xs)
+}
+
+/// Maximum number of compiled regular expressions kept by [`is_match`].
+#[cfg(feature = "std")]
+const MAX_CACHED_REGEXES: usize = 256;
+
+/// Check if a string matches a regular expression.
+///
+/// Policies are evaluated for every message, so the compiled regular
+/// expressions are cached. The cache is cleared when it is full, in case
+/// the patterns are not literals but come from the environment.
+#[cfg(feature = "std")]
+fn is_match(s: &str, pattern: &str) -> Result<bool, EvalError> {
+    use once_cell::sync::Lazy;
+    use std::collections::BTreeMap;
+    use std::sync::Mutex;
+
+    static REGEXES: Lazy<Mutex<BTreeMap<String, regex::Regex>>> =
+        Lazy::new(|| Mutex::new(BTreeMap::new()));
+
+    let cached = REGEXES.lock().ok().and_then(|r| r.get(pattern).cloned());
+    let r = match cached {
+        Some(r) => r,
+        None => {
+            let r = regex::Regex::new(pattern)
+                .map_err(|e| EvalError::malformed(format!("invalid regular expression: {e}")))?;
+            if let Ok(mut regexes) = REGEXES.lock() {
+                if regexes.len() >= MAX_CACHED_REGEXES {
+                    regexes.clear()
+                }
+                regexes.insert(pattern.to_string(), r.clone());
+            }
+            r
+        }
+    };
+    Ok(r.is_match(s))
+}
+
+#[cfg(not(feature = "std"))]
+fn is_match(_: &str, _: &str) -> Result<bool, EvalError> {
+    Err(EvalError::unsupported("matches?"))
+}
+
+/// The current time as the number of seconds since the Unix epoch.
+fn now() -> Result<Expr, EvalError> {
+    let now = ockam_identity::utils::now().map_err(|_| EvalError::unsupported("now"))?;
+    i64::try_from(now.0)
+        .map(Expr::Int)
+        .map_err(|_| EvalError::overflow("now"))
+}
+
 /// Evaluate a predicate against the `n` topmost arguments.
 fn eval_predicate<F>(n: usize, args: &mut Vec<Expr>, f: F) -> Result<(), EvalError>
 where
@@ -292,3 +474,81 @@ where
     args.push(Expr::Bool(b));
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{eval, parse, Env, EvalError, Expr};
+
+    fn run(s: &str) -> Result<Expr, EvalError> {
+        let mut env = Env::new();
+        env.put("subject.email", Expr::Str("alice@example.com".into()));
+        env.put("subject.valid_until", Expr::Int(i64::MAX));
+        // attributes are strings when they come from credentials
+        env.put("subject.expires", Expr::Str(i64::MAX.to_string()));
+        eval(&parse(s).unwrap().unwrap(), &env)
+    }
+
+    #[test]
+    fn string_operators() {
+        assert!(run(r#"(starts-with? subject.email "alice")"#)
+            .unwrap()
+            .is_true());
+        assert!(run(r#"(ends-with? subject.email "@example.com")"#)
+            .unwrap()
+            .is_true());
+        assert!(run(r#"(ends-with? subject.email "@example.org")"#)
+            .unwrap()
+            .is_false());
+        assert!(run(r#"(contains? subject.email "@")"#).unwrap().is_true());
+        assert!(run(r#"(matches? subject.email "^[a-z]+@example\\.com$")"#)
+            .unwrap()
+            .is_true());
+        assert!(run(r#"(matches? subject.email "^bob@")"#)
+            .unwrap()
+            .is_false());
+    }
+
+    #[test]
+    fn arithmetic_operators() {
+        assert!(run("(= (+ 1 2 3) 6)").unwrap().is_true());
+        assert!(run("(= (+) 0)").unwrap().is_true());
+        assert!(run("(= (- 10 3 2) 5)").unwrap().is_true());
+        assert!(run("(= (- 4) -4)").unwrap().is_true());
+        assert!(matches!(
+            run("(+ subject.valid_until 1)"),
+            Err(EvalError::Overflow(_))
+        ));
+    }
+
+    #[test]
+    fn int_operator() {
+        assert!(run(r#"(= (int "42") 42)"#).unwrap().is_true());
+        assert!(run(r#"(= (int " -7 ") -7)"#).unwrap().is_true());
+        assert!(run("(= (int 3) 3)").unwrap().is_true());
+        assert!(run(r#"(int "4.2")"#).unwrap_err().is_type_error());
+        assert!(run("(int subject.email)").unwrap_err().is_type_error());
+        assert!(run("(int true)").unwrap_err().is_type_error());
+        assert!(run("(int 1 2)").is_err());
+    }
+
+    #[test]
+    fn now_operator() {
+        assert!(run("(> subject.valid_until (now))").unwrap().is_true());
+        assert!(run("(< (- (now) 60) (now))").unwrap().is_true());
+        assert!(run("(> (int subject.expires) (now))").unwrap().is_true());
+        assert!(run("(now 1)").is_err());
+    }
+
+    #[test]
+    fn type_errors() {
+        let e = run(r#"(starts-with? subject.email 1)"#).unwrap_err();
+        assert!(e.is_type_error());
+        let e = run(r#"(+ 1 "2")"#).unwrap_err();
+        assert!(e.is_type_error());
+        let e = run(r#"(- subject.email)"#).unwrap_err();
+        assert!(e.is_type_error());
+        assert!(!run(r#"(matches? subject.email "(")"#)
+            .unwrap_err()
+            .is_type_error());
+    }
+}
@@ -7,6 +7,7 @@
 extern crate alloc;
 extern crate core;
 
+mod checker;
 mod env;
 mod error;
 mod eval;
@@ -21,6 +22,7 @@ pub mod expr;
 mod storage;
 
 pub use attribute_access_control::AbacAccessControl;
+pub use checker::{check, check_policy, CheckError, Diagnostic, Severity, Type};
 pub use env::Env;
 pub use error::{EvalError, ParseError};
 pub use eval::eval;
@@ -1,14 +1,16 @@
+use crate::attribute_access_control::PolicyAudit;
 use crate::types::{Action, Resource};
-use crate::{AbacAccessControl, PoliciesRepository};
+use crate::{AbacAccessControl, PoliciesRepository, PolicyAuditRepository};
 use crate::{Env, Expr};
 use core::fmt;
 use core::fmt::{Debug, Formatter};
 use ockam_core::compat::boxed::Box;
 use ockam_core::compat::format;
 use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
 use ockam_core::{async_trait, RelayMessage};
 use ockam_core::{IncomingAccessControl, Result};
-use ockam_identity::IdentityAttributesRepository;
+use ockam_identity::{IdentityAttributesRepository, IdentitySecureChannelLocalInfo};
 use tracing as log;
 
 /// Evaluates a policy expression against an environment of attributes.
@@ -21,6 +23,7 @@ pub struct PolicyAccessControl {
     policies: Arc<dyn PoliciesRepository>,
     identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
     environment: Env,
+    audit_repository: Option<Arc<dyn PolicyAuditRepository>>,
 }
 
 /// Debug implementation writing out the resource, action and initial environment
@@ -54,8 +57,21 @@ impl PolicyAccessControl {
             policies,
             identity_attributes_repository,
             environment: env,
+            audit_repository: None,
         }
     }
+
+    /// Record all the decisions taken by this access control
+    pub fn with_audit_repository(mut self, repository: Arc<dyn PolicyAuditRepository>) -> Self {
+        self.audit_repository = Some(repository);
+        self
+    }
+
+    fn audit(&self) -> Option<PolicyAudit> {
+        self.audit_repository
+            .clone()
+            .map(|r| PolicyAudit::new(r, self.resource.clone(), self.action.clone()))
+    }
 }
 
 #[async_trait]
@@ -70,6 +86,12 @@ impl IncomingAccessControl for PolicyAccessControl {
             if let Expr::Bool(b) = policy.expression() {
                 // If the policy is a constant there is no need to populate
                 // the environment or look for message metadata.
+                if let Some(audit) = self.audit() {
+                    let subject = IdentitySecureChannelLocalInfo::find_info(msg.local_message())
+                        .ok()
+                        .map(|i| i.their_identity_id());
+                    audit.record(&policy, subject, Vec::new(), *b).await
+                }
                 return Ok(*b);
             } else {
                 policy
@@ -89,6 +111,7 @@ impl IncomingAccessControl for PolicyAccessControl {
             policy,
             self.environment.clone(),
         )
+        .with_audit(self.audit())
         .is_authorized(msg)
         .await
     }
@@ -1,7 +1,17 @@
+mod policy_audit_repository;
+#[cfg(feature = "std")]
+pub mod policy_audit_repository_sql;
+#[cfg(feature = "std")]
+mod policy_audit_writer;
 mod policy_repository;
 #[cfg(feature = "std")]
 pub mod policy_repository_sql;
 
+pub use policy_audit_repository::*;
+#[cfg(feature = "std")]
+pub use policy_audit_repository_sql::*;
+#[cfg(feature = "std")]
+pub use policy_audit_writer::*;
 pub use policy_repository::*;
 #[cfg(feature = "std")]
 pub use policy_repository_sql::*;
@@ -0,0 +1,162 @@
+use crate::{Action, Expr, Resource};
+use core::time::Duration;
+use minicbor::{Decode, Encode};
+use ockam_core::async_trait;
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::string::String;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use ockam_identity::models::TimestampInSeconds;
+use ockam_identity::Identifier;
+use sha2::{Digest, Sha256};
+
+/// This repository stores an audit trail of the decisions taken when evaluating policies.
+#[async_trait]
+pub trait PolicyAuditRepository: Send + Sync + 'static {
+    /// Store a policy decision.
+    /// Older decisions can be removed according to the retention limits of the repository
+    async fn store_decision(&self, decision: &PolicyDecision) -> Result<()>;
+
+    /// Store several policy decisions at once
+    async fn store_decisions(&self, decisions: &[PolicyDecision]) -> Result<()> {
+        for decision in decisions {
+            self.store_decision(decision).await?
+        }
+        Ok(())
+    }
+
+    /// Return the decisions matching a filter, most recent first
+    async fn get_decisions(&self, filter: &PolicyDecisionFilter) -> Result<Vec<PolicyDecision>>;
+
+    /// Delete the decisions which are beyond the retention limits of the repository
+    async fn delete_expired_decisions(&self) -> Result<()>;
+}
+
+/// A decision taken when evaluating a policy for a given subject
+#[derive(Debug, Clone, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PolicyDecision {
+    #[n(1)] decided_at: TimestampInSeconds,
+    #[n(2)] subject: Option<Identifier>,
+    #[n(3)] resource: Option<Resource>,
+    #[n(4)] action: Option<Action>,
+    #[n(5)] policy_hash: String,
+    #[n(6)] is_authorized: bool,
+    #[n(7)] environment_keys: Vec<String>,
+}
+
+impl PolicyDecision {
+    pub fn new(
+        decided_at: TimestampInSeconds,
+        subject: Option<Identifier>,
+        resource: Option<Resource>,
+        action: Option<Action>,
+        policy_hash: String,
+        is_authorized: bool,
+        environment_keys: Vec<String>,
+    ) -> Self {
+        Self {
+            decided_at,
+            subject,
+            resource,
+            action,
+            policy_hash,
+            is_authorized,
+            environment_keys,
+        }
+    }
+
+    pub fn decided_at(&self) -> TimestampInSeconds {
+        self.decided_at
+    }
+
+    pub fn subject(&self) -> Option<&Identifier> {
+        self.subject.as_ref()
+    }
+
+    pub fn resource(&self) -> Option<&Resource> {
+        self.resource.as_ref()
+    }
+
+    pub fn action(&self) -> Option<&Action> {
+        self.action.as_ref()
+    }
+
+    /// Hex-encoded SHA256 hash of the encoded policy expression
+    pub fn policy_hash(&self) -> &str {
+        &self.policy_hash
+    }
+
+    pub fn is_authorized(&self) -> bool {
+        self.is_authorized
+    }
+
+    /// Names of the attributes which were available when evaluating the policy
+    pub fn environment_keys(&self) -> &[String] {
+        &self.environment_keys
+    }
+}
+
+/// Hash a policy expression so that decisions can be related to the policy which was evaluated
+/// without storing the full expression with each decision
+pub fn policy_hash(expr: &Expr) -> Result<String> {
+    Ok(hex::encode(Sha256::digest(minicbor::to_vec(expr)?)))
+}
+
+/// Criteria used to select policy decisions
+#[derive(Debug, Clone, Default, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PolicyDecisionFilter {
+    #[n(1)] pub resource: Option<Resource>,
+    #[n(2)] pub subject: Option<Identifier>,
+    #[n(3)] pub since: Option<TimestampInSeconds>,
+    #[n(4)] pub until: Option<TimestampInSeconds>,
+    #[n(5)] pub limit: Option<u32>,
+}
+
+impl PolicyDecisionFilter {
+    pub fn with_resource(mut self, resource: Resource) -> Self {
+        self.resource = Some(resource);
+        self
+    }
+
+    pub fn with_subject(mut self, subject: Identifier) -> Self {
+        self.subject = Some(subject);
+        self
+    }
+
+    pub fn with_since(mut self, since: TimestampInSeconds) -> Self {
+        self.since = Some(since);
+        self
+    }
+
+    pub fn with_until(mut self, until: TimestampInSeconds) -> Self {
+        self.until = Some(until);
+        self
+    }
+
+    pub fn with_limit(mut self, limit: u32) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+}
+
+/// Limits on the number and age of the policy decisions kept in a [`PolicyAuditRepository`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PolicyAuditRetention {
+    /// Maximum number of decisions to keep
+    pub max_entries: u64,
+    /// Maximum age of the decisions to keep
+    pub max_age: Duration,
+}
+
+impl Default for PolicyAuditRetention {
+    fn default() -> Self {
+        Self {
+            max_entries: 100_000,
+            max_age: Duration::from_secs(30 * 24 * 60 * 60),
+        }
+    }
+}
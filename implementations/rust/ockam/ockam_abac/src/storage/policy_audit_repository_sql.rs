@@ -0,0 +1,280 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+use sqlx::*;
+use tracing::debug;
+
+use ockam_core::async_trait;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use ockam_identity::utils::now;
+use ockam_identity::{Identifier, TimestampInSeconds};
+use ockam_node::database::{Boolean, FromSqlxError, Nullable, SqlxDatabase, ToSqlxType, ToVoid};
+
+use crate::{
+    Action, PolicyAuditRepository, PolicyAuditRetention, PolicyDecision, PolicyDecisionFilter,
+    Resource,
+};
+
+/// Number of stored decisions after which the retention limits are enforced
+const RETENTION_CHECK_INTERVAL: u64 = 100;
+
+pub struct PolicyAuditSqlxDatabase {
+    database: Arc<SqlxDatabase>,
+    retention: PolicyAuditRetention,
+    stored_decisions: AtomicU64,
+}
+
+impl PolicyAuditSqlxDatabase {
+    /// Create a new database for policy decisions, using the default retention limits
+    pub fn new(database: Arc<SqlxDatabase>) -> Self {
+        debug!("create a repository for policy decisions");
+        Self {
+            database,
+            retention: PolicyAuditRetention::default(),
+            stored_decisions: AtomicU64::new(0),
+        }
+    }
+
+    /// Set the retention limits for this repository
+    pub fn with_retention(mut self, retention: PolicyAuditRetention) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Create a new in-memory database for policy decisions
+    pub async fn create() -> Result<Arc<Self>> {
+        Ok(Arc::new(Self::new(
+            SqlxDatabase::in_memory("policy decisions").await?,
+        )))
+    }
+}
+
+#[async_trait]
+impl PolicyAuditRepository for PolicyAuditSqlxDatabase {
+    async fn store_decision(&self, decision: &PolicyDecision) -> Result<()> {
+        self.store_decisions(core::slice::from_ref(decision)).await
+    }
+
+    async fn store_decisions(&self, decisions: &[PolicyDecision]) -> Result<()> {
+        if decisions.is_empty() {
+            return Ok(());
+        }
+        let mut transaction = self.database.begin().await.into_core()?;
+        for decision in decisions {
+            query("INSERT INTO policy_decision VALUES ($1, $2, $3, $4, $5, $6, $7)")
+                .bind(decision.decided_at().to_sql())
+                .bind(decision.subject().map(|s| s.to_sql()))
+                .bind(decision.resource().map(|r| r.to_sql()))
+                .bind(decision.action().map(|a| a.to_sql()))
+                .bind(decision.policy_hash().to_sql())
+                .bind(decision.is_authorized().to_sql())
+                .bind(decision.environment_keys().join(",").to_sql())
+                .execute(&mut *transaction)
+                .await
+                .void()?;
+        }
+        transaction.commit().await.void()?;
+
+        // Enforcing the retention limits on each insertion would be too costly
+        let count = decisions.len() as u64;
+        let stored = self.stored_decisions.fetch_add(count, Ordering::Relaxed);
+        if stored == 0
+            || stored / RETENTION_CHECK_INTERVAL != (stored + count) / RETENTION_CHECK_INTERVAL
+        {
+            self.delete_expired_decisions().await?;
+        }
+        Ok(())
+    }
+
+    async fn get_decisions(&self, filter: &PolicyDecisionFilter) -> Result<Vec<PolicyDecision>> {
+        // The parameters are cast because a missing filter value is sent as an integer NULL
+        // and Postgres would then refuse to compare it with a TEXT column
+        let query = query_as(
+            "SELECT decided_at, subject, resource, action, policy_hash, is_authorized, environment_keys
+             FROM policy_decision
+             WHERE (CAST($1 AS TEXT) IS NULL OR resource = CAST($1 AS TEXT))
+             AND (CAST($2 AS TEXT) IS NULL OR subject = CAST($2 AS TEXT))
+             AND (CAST($3 AS BIGINT) IS NULL OR decided_at >= CAST($3 AS BIGINT))
+             AND (CAST($4 AS BIGINT) IS NULL OR decided_at <= CAST($4 AS BIGINT))
+             ORDER BY decided_at DESC, rowid DESC
+             LIMIT $5",
+        )
+        .bind(filter.resource.as_ref().map(|r| r.to_sql()))
+        .bind(filter.subject.as_ref().map(|s| s.to_sql()))
+        .bind(filter.since.map(|t| t.to_sql()))
+        .bind(filter.until.map(|t| t.to_sql()))
+        // Postgres does not accept a negative limit to mean 'no limit'
+        .bind(filter.limit.map(|l| l as i64).unwrap_or(i64::MAX));
+        let rows: Vec<PolicyDecisionRow> =
+            query.fetch_all(&self.database.pool).await.into_core()?;
+        rows.into_iter().map(|r| r.decision()).collect()
+    }
+
+    async fn delete_expired_decisions(&self) -> Result<()> {
+        let oldest = now()?.saturating_sub(self.retention.max_age.as_secs());
+        let mut transaction = self.database.begin().await.into_core()?;
+
+        query("DELETE FROM policy_decision WHERE decided_at < $1")
+            .bind(oldest.to_sql())
+            .execute(&mut *transaction)
+            .await
+            .void()?;
+
+        query(
+            "DELETE FROM policy_decision WHERE rowid NOT IN
+             (SELECT rowid FROM policy_decision ORDER BY decided_at DESC, rowid DESC LIMIT $1)",
+        )
+        .bind(self.retention.max_entries.to_sql())
+        .execute(&mut *transaction)
+        .await
+        .void()?;
+
+        transaction.commit().await.void()
+    }
+}
+
+/// Low-level representation of a row in the policy_decision table
+#[derive(FromRow)]
+struct PolicyDecisionRow {
+    decided_at: i64,
+    subject: Nullable<String>,
+    resource: Nullable<String>,
+    action: Nullable<String>,
+    policy_hash: String,
+    is_authorized: Boolean,
+    environment_keys: String,
+}
+
+impl PolicyDecisionRow {
+    fn decision(&self) -> Result<PolicyDecision> {
+        let subject = self
+            .subject
+            .to_option()
+            .map(|s| Identifier::try_from(s.as_str()))
+            .transpose()?;
+        let environment_keys = if self.environment_keys.is_empty() {
+            vec![]
+        } else {
+            self.environment_keys
+                .split(',')
+                .map(|k| k.to_string())
+                .collect()
+        };
+        Ok(PolicyDecision::new(
+            TimestampInSeconds(self.decided_at as u64),
+            subject,
+            self.resource.to_option().map(Resource::from),
+            self.action.to_option().map(Action::from),
+            self.policy_hash.clone(),
+            self.is_authorized.to_bool(),
+            environment_keys,
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use core::time::Duration;
+    use ockam_identity::identities;
+    use ockam_node::database::with_dbs;
+
+    use super::*;
+
+    #[tokio::test]
+    async fn test_repository() -> Result<()> {
+        with_dbs(|db| async move {
+            let repository = PolicyAuditSqlxDatabase::new(db);
+            let identities = identities().await?;
+            let subject1 = identities.identities_creation().create_identity().await?;
+            let subject2 = identities.identities_creation().create_identity().await?;
+            let now = now()?.0;
+
+            let d1 = decision(now - 20, Some(&subject1), "outlet", true);
+            let d2 = decision(now - 10, Some(&subject2), "outlet", false);
+            let d3 = decision(now, None, "inlet", false);
+            for d in [&d1, &d2, &d3] {
+                repository.store_decision(d).await?;
+            }
+
+            // all the decisions are returned, most recent first
+            let decisions = repository
+                .get_decisions(&PolicyDecisionFilter::default())
+                .await?;
+            assert_eq!(decisions, vec![d3.clone(), d2.clone(), d1.clone()]);
+
+            // decisions can be filtered by resource, subject and time range
+            let filter = PolicyDecisionFilter::default().with_resource("outlet".into());
+            assert_eq!(
+                repository.get_decisions(&filter).await?,
+                vec![d2.clone(), d1.clone()]
+            );
+
+            let filter = PolicyDecisionFilter::default().with_subject(subject1.clone());
+            assert_eq!(repository.get_decisions(&filter).await?, vec![d1.clone()]);
+
+            let filter = PolicyDecisionFilter::default()
+                .with_since((now - 15).into())
+                .with_until((now - 5).into());
+            assert_eq!(repository.get_decisions(&filter).await?, vec![d2.clone()]);
+
+            let filter = PolicyDecisionFilter::default().with_limit(1);
+            assert_eq!(repository.get_decisions(&filter).await?, vec![d3]);
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_retention() -> Result<()> {
+        with_dbs(|db| async move {
+            let repository =
+                PolicyAuditSqlxDatabase::new(db).with_retention(PolicyAuditRetention {
+                    max_entries: 2,
+                    max_age: Duration::from_secs(60),
+                });
+            let now = now()?.0;
+
+            // this decision is too old
+            repository
+                .store_decision(&decision(now - 120, None, "outlet", true))
+                .await?;
+            for i in 0..3 {
+                repository
+                    .store_decision(&decision(now - i, None, "outlet", true))
+                    .await?;
+            }
+            repository.delete_expired_decisions().await?;
+
+            // only the 2 most recent decisions are kept
+            let decisions = repository
+                .get_decisions(&PolicyDecisionFilter::default())
+                .await?;
+            assert_eq!(
+                decisions.iter().map(|d| d.decided_at()).collect::<Vec<_>>(),
+                vec![now.into(), (now - 1).into()]
+            );
+
+            Ok(())
+        })
+        .await
+    }
+
+    /// HELPERS
+    fn decision(
+        decided_at: u64,
+        subject: Option<&Identifier>,
+        resource: &str,
+        is_authorized: bool,
+    ) -> PolicyDecision {
+        PolicyDecision::new(
+            decided_at.into(),
+            subject.cloned(),
+            Some(resource.into()),
+            Some("handle_message".into()),
+            "hash".to_string(),
+            is_authorized,
+            vec!["resource.id".to_string(), "subject.identifier".to_string()],
+        )
+    }
+}
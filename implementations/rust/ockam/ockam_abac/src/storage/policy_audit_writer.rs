@@ -0,0 +1,136 @@
+use core::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::mpsc::error::TrySendError;
+use tokio::sync::mpsc::{channel, Receiver, Sender};
+use tracing::{debug, warn};
+
+use ockam_core::async_trait;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+
+use crate::{PolicyAuditRepository, PolicyDecision, PolicyDecisionFilter};
+
+/// Maximum number of decisions waiting to be stored
+const MAX_PENDING_DECISIONS: usize = 10_000;
+
+/// Maximum number of decisions stored in a single transaction
+const MAX_BATCH_SIZE: usize = 500;
+
+/// A [`PolicyAuditRepository`] storing the decisions in the background.
+///
+/// Access controls are evaluated for every message, so the decisions are queued and
+/// written in batches by a separate task instead of delaying the messages. When the
+/// queue is full, because the database can't keep up, new decisions are dropped.
+pub struct PolicyAuditWriter {
+    repository: Arc<dyn PolicyAuditRepository>,
+    sender: Sender<PolicyDecision>,
+    dropped_decisions: AtomicU64,
+}
+
+impl PolicyAuditWriter {
+    /// Start writing decisions to a repository. This must be called from a tokio runtime
+    pub fn start(repository: Arc<dyn PolicyAuditRepository>) -> Self {
+        let (sender, receiver) = channel(MAX_PENDING_DECISIONS);
+        tokio::spawn(Self::write_decisions(repository.clone(), receiver));
+        Self {
+            repository,
+            sender,
+            dropped_decisions: AtomicU64::new(0),
+        }
+    }
+
+    /// Number of decisions which were dropped because the queue was full
+    pub fn dropped_decisions(&self) -> u64 {
+        self.dropped_decisions.load(Ordering::Relaxed)
+    }
+
+    /// Store the queued decisions until the writer is dropped
+    async fn write_decisions(
+        repository: Arc<dyn PolicyAuditRepository>,
+        mut receiver: Receiver<PolicyDecision>,
+    ) {
+        while let Some(decision) = receiver.recv().await {
+            let mut batch = vec![decision];
+            while batch.len() < MAX_BATCH_SIZE {
+                match receiver.try_recv() {
+                    Ok(decision) => batch.push(decision),
+                    Err(_) => break,
+                }
+            }
+            if let Err(e) = repository.store_decisions(&batch).await {
+                warn!(%e, "cannot store {} policy decisions", batch.len());
+            }
+        }
+        debug!("stopped writing policy decisions");
+    }
+}
+
+#[async_trait]
+impl PolicyAuditRepository for PolicyAuditWriter {
+    async fn store_decision(&self, decision: &PolicyDecision) -> Result<()> {
+        match self.sender.try_send(decision.clone()) {
+            Ok(()) => {}
+            Err(TrySendError::Full(_)) => {
+                // Only log the first dropped decision of each series
+                if self.dropped_decisions.fetch_add(1, Ordering::Relaxed) % 1000 == 0 {
+                    warn!("too many pending policy decisions, some decisions are not recorded");
+                }
+            }
+            Err(TrySendError::Closed(_)) => {
+                warn!("the policy decisions writer is stopped, the decision is not recorded");
+            }
+        }
+        Ok(())
+    }
+
+    async fn get_decisions(&self, filter: &PolicyDecisionFilter) -> Result<Vec<PolicyDecision>> {
+        self.repository.get_decisions(filter).await
+    }
+
+    async fn delete_expired_decisions(&self) -> Result<()> {
+        self.repository.delete_expired_decisions().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use core::time::Duration;
+
+    use super::*;
+    use crate::PolicyAuditSqlxDatabase;
+    use ockam_identity::utils::now;
+
+    #[tokio::test]
+    async fn decisions_are_written_in_the_background() -> Result<()> {
+        let repository = PolicyAuditSqlxDatabase::create().await?;
+        let writer = PolicyAuditWriter::start(repository.clone());
+        let now = now()?;
+        for i in 0..10 {
+            let decision = PolicyDecision::new(
+                now,
+                None,
+                Some("outlet".into()),
+                Some("handle_message".into()),
+                "hash".to_string(),
+                i % 2 == 0,
+                vec![],
+            );
+            writer.store_decision(&decision).await?;
+        }
+
+        let mut stored = 0;
+        for _ in 0..50 {
+            stored = repository
+                .get_decisions(&PolicyDecisionFilter::default())
+                .await?
+                .len();
+            if stored == 10 {
+                break;
+            }
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+        assert_eq!(stored, 10);
+        assert_eq!(writer.dropped_decisions(), 0);
+        Ok(())
+    }
+}
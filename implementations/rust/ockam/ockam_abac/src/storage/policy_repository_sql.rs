@@ -50,7 +50,7 @@ impl PoliciesRepository for PolicySqlxDatabase {
         action: &Action,
         policy: &Policy,
     ) -> Result<()> {
-        let query = query("INSERT OR REPLACE INTO policy VALUES (?, ?, ?)")
+        let query = query("INSERT INTO policy VALUES ($1, $2, $3) ON CONFLICT (resource, action) DO UPDATE SET expression = $3")
             .bind(resource.to_sql())
             .bind(action.to_sql())
             .bind(minicbor::to_vec(policy.expression())?.to_sql());
@@ -58,7 +58,7 @@ impl PoliciesRepository for PolicySqlxDatabase {
     }
 
     async fn delete_policy(&self, resource: &Resource, action: &Action) -> Result<()> {
-        let query = query("DELETE FROM policy WHERE resource = ? and action = ?")
+        let query = query("DELETE FROM policy WHERE resource = $1 and action = $2")
             .bind(resource.to_sql())
             .bind(action.to_sql());
         query.execute(&self.database.pool).await.void()
@@ -118,41 +118,45 @@ impl PolicyRow {
 #[cfg(test)]
 mod test {
     use crate::expr::*;
+    use ockam_node::database::with_dbs;
 
     use super::*;
 
     #[tokio::test]
     async fn test_repository() -> Result<()> {
-        let repository = create_repository().await?;
-
-        // a policy can be associated to a resource and an action
-        let r = Resource::from("outlet");
-        let a = Action::from("create");
-        let p = Policy::new(eq([ident("name"), str("me")]));
-        repository.set_policy(&r, &a, &p).await?;
-        assert_eq!(repository.get_policy(&r, &a).await?.unwrap(), p);
-
-        // we can retrieve all the policies associated to a given resource
-        let policies = repository.get_policies_by_resource(&r).await?;
-        assert_eq!(policies.len(), 1);
-
-        let a = Action::from("delete");
-        repository.set_policy(&r, &a, &p).await?;
-        let policies = repository.get_policies_by_resource(&r).await?;
-        assert_eq!(policies.len(), 2);
-
-        // we can delete a given policy
-        // here we delete the policy for outlet/delete
-        repository.delete_policy(&r, &a).await?;
-        let policies = repository.get_policies_by_resource(&r).await?;
-        assert_eq!(policies.len(), 1);
-        assert_eq!(policies.first().unwrap().0, Action::from("create"));
-
-        Ok(())
+        with_dbs(|db| async move {
+            let repository = create_repository(db);
+
+            // a policy can be associated to a resource and an action
+            let r = Resource::from("outlet");
+            let a = Action::from("create");
+            let p = Policy::new(eq([ident("name"), str("me")]));
+            repository.set_policy(&r, &a, &p).await?;
+            assert_eq!(repository.get_policy(&r, &a).await?.unwrap(), p);
+
+            // we can retrieve all the policies associated to a given resource
+            let policies = repository.get_policies_by_resource(&r).await?;
+            assert_eq!(policies.len(), 1);
+
+            let a = Action::from("delete");
+            repository.set_policy(&r, &a, &p).await?;
+            let policies = repository.get_policies_by_resource(&r).await?;
+            assert_eq!(policies.len(), 2);
+
+            // we can delete a given policy
+            // here we delete the policy for outlet/delete
+            repository.delete_policy(&r, &a).await?;
+            let policies = repository.get_policies_by_resource(&r).await?;
+            assert_eq!(policies.len(), 1);
+            assert_eq!(policies.first().unwrap().0, Action::from("create"));
+
+            Ok(())
+        })
+        .await
     }
 
     /// HELPERS
-    async fn create_repository() -> Result<Arc<dyn PoliciesRepository>> {
-        Ok(PolicySqlxDatabase::create().await?)
+    fn create_repository(db: Arc<SqlxDatabase>) -> Arc<dyn PoliciesRepository> {
+        Arc::new(PolicySqlxDatabase::new(db))
     }
 }
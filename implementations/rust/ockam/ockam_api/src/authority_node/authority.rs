@@ -21,7 +21,7 @@ use ockam_transport_tcp::{TcpListenerOptions, TcpTransport};
 
 use crate::authenticator::enrollment_tokens::EnrollmentTokenAuthenticator;
 use crate::authority_node::authority::EnrollerCheck::{AnyMember, EnrollerOnly};
-use crate::authority_node::Configuration;
+use crate::authority_node::{Configuration, RevocationsService};
 use crate::bootstrapped_identities_store::BootstrapedIdentityAttributesStore;
 use crate::echoer::Echoer;
 use crate::nodes::service::actions;
@@ -34,6 +34,7 @@ use crate::nodes::service::default_address::DefaultAddress;
 //   - a credential issuer
 //   - an enrollment token issuer
 //   - an enrollment token acceptor
+//   - a revocations service
 pub struct Authority {
     identifier: Identifier,
     secure_channels: Arc<SecureChannels>,
@@ -60,9 +61,15 @@ impl Authority {
         debug!(?configuration, "creating the authority");
 
         // create the database
-        let database_path = &configuration.database_path;
-        Self::create_ockam_directory_if_necessary(database_path)?;
-        let database = Arc::new(SqlxDatabase::create(database_path).await?);
+        let database = match &configuration.database_url {
+            Some(database_url) => SqlxDatabase::create_from_url(database_url).await?,
+            None => {
+                let database_path = &configuration.database_path;
+                Self::create_ockam_directory_if_necessary(database_path)?;
+                SqlxDatabase::create(database_path).await?
+            }
+        };
+        let database = Arc::new(database);
 
         // create the repositories
         let vault = Vault::create_with_database(database.clone());
@@ -231,6 +238,31 @@ impl Authority {
         Ok(())
     }
 
+    /// Start the revocations service, maintaining and publishing the list of credentials
+    /// revoked by the authority
+    pub async fn start_revocations_service(
+        &self,
+        ctx: &Context,
+        secure_channel_flow_control_id: &FlowControlId,
+        configuration: &Configuration,
+    ) -> Result<()> {
+        let revocations = RevocationsService::new(
+            self.secure_channels.identities().credentials(),
+            self.identity_attributes_repository(),
+            &self.identifier,
+        );
+
+        let address = DefaultAddress::REVOCATIONS_SERVICE.to_string();
+        ctx.flow_controls()
+            .add_consumer(address.clone(), secure_channel_flow_control_id);
+
+        self.start(ctx, configuration, address.clone(), AnyMember, revocations)
+            .await?;
+
+        info!("started a revocations service at '{address}'");
+        Ok(())
+    }
+
     /// Start the Okta service to retrieve attributes authenticated by Okta
     pub async fn start_okta(
         &self,
@@ -361,12 +393,11 @@ impl Authority {
             "resource.trust_context_id",
             str(configuration.project_identifier.clone()),
         );
-        let abac = Arc::new(AbacAccessControl::new(
+        Arc::new(AbacAccessControl::new(
             self.identity_attributes_repository(),
             Policy::new(rule),
             env,
-        ));
-        abac
+        ))
     }
 }
 
@@ -22,6 +22,11 @@ pub struct Configuration {
     /// path where the database should be stored
     pub database_path: PathBuf,
 
+    /// URL of the database, for example a Postgres database shared by several authority replicas.
+    /// When it is set, `database_path` is not used
+    #[serde(default)]
+    pub database_url: Option<String>,
+
     /// Project identifier on the Orchestrator node
     pub project_identifier: String,
 
@@ -35,6 +35,11 @@ pub async fn start_node(ctx: &Context, configuration: &Configuration) -> Result<
         .await?;
     debug!("credential issuer started");
 
+    authority
+        .start_revocations_service(ctx, &secure_channel_flow_control_id, configuration)
+        .await?;
+    debug!("revocations service started");
+
     // start the Okta service (if the optional configuration has been provided)
     authority
         .start_okta(ctx, &secure_channel_flow_control_id, configuration)
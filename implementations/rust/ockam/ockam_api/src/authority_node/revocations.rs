@@ -0,0 +1,211 @@
+use miette::IntoDiagnostic;
+use minicbor::{Decode, Decoder, Encode};
+use tracing::trace;
+
+use ockam::identity::models::{CredentialHash, RevocationListAndPurposeKey};
+use ockam::identity::{
+    secure_channel_required, Credentials, Identifier, IdentityAttributesRepository,
+    IdentitySecureChannelLocalInfo,
+};
+use ockam_core::api::{Method, Request, RequestHeader, Response};
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, Result, Routed, Worker};
+use ockam_node::Context;
+
+use crate::cloud::AuthorityNode;
+use crate::nodes::service::default_address::DefaultAddress;
+
+/// Name of the attribute that an identity must have to be allowed to revoke credentials
+const OCKAM_ROLE: &[u8] = b"ockam-role";
+const ENROLLER: &[u8] = b"enroller";
+
+/// This worker maintains the revocation list of an authority:
+///
+///  - `GET /` returns the current revocation list, signed by the authority
+///  - `POST /credentials` revokes a single credential, identified by its hash
+///  - `POST /subjects` revokes all the credentials issued to a given identity
+///
+/// Revocations can only be requested by enrollers. A revoked subject also loses its attributes
+/// so that no new credential can be issued for it.
+pub struct RevocationsService {
+    credentials: Arc<Credentials>,
+    identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+    issuer: Identifier,
+}
+
+impl RevocationsService {
+    /// Create a new revocations service for the given authority
+    pub fn new(
+        credentials: Arc<Credentials>,
+        identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+        issuer: &Identifier,
+    ) -> Self {
+        Self {
+            credentials,
+            identity_attributes_repository,
+            issuer: issuer.clone(),
+        }
+    }
+
+    async fn is_enroller(&self, identifier: &Identifier) -> Result<bool> {
+        Ok(self
+            .identity_attributes_repository
+            .get_attributes(identifier)
+            .await?
+            .map(|entry| entry.attrs().get(OCKAM_ROLE).map(|r| r.as_slice()) == Some(ENROLLER))
+            .unwrap_or(false))
+    }
+
+    async fn revoke_subject(&self, subject: &Identifier) -> Result<()> {
+        self.credentials
+            .revocations_repository()
+            .revoke_subject(&self.issuer, subject)
+            .await?;
+        self.identity_attributes_repository.delete(subject).await
+    }
+}
+
+#[ockam_core::worker]
+impl Worker for RevocationsService {
+    type Context = Context;
+    type Message = Vec<u8>;
+
+    async fn handle_message(&mut self, c: &mut Context, m: Routed<Self::Message>) -> Result<()> {
+        if let Ok(i) = IdentitySecureChannelLocalInfo::find_info(m.local_message()) {
+            let from = i.their_identity_id();
+            let mut dec = Decoder::new(m.as_body());
+            let req: RequestHeader = dec.decode()?;
+            trace! {
+                target: "ockam_api::authority_node::revocations",
+                from   = %from,
+                id     = %req.id(),
+                method = ?req.method(),
+                path   = %req.path(),
+                body   = %req.has_body(),
+                "request"
+            }
+            let path_segments = req.path_segments::<5>();
+            let res = match (req.method(), path_segments.as_slice()) {
+                (Some(Method::Get), [""]) => {
+                    let repository = self.credentials.revocations_repository();
+                    let revocation_list = self
+                        .credentials
+                        .credentials_creation()
+                        .issue_revocation_list(
+                            &self.issuer,
+                            repository.get_revoked_credentials(&self.issuer).await?,
+                            repository.get_revoked_subjects(&self.issuer).await?,
+                        )
+                        .await?;
+                    Response::ok()
+                        .with_headers(&req)
+                        .body(revocation_list)
+                        .to_vec()?
+                }
+                (Some(Method::Post), _) if !self.is_enroller(&from).await? => {
+                    Response::forbidden(&req, "only enrollers can revoke credentials").to_vec()?
+                }
+                (Some(Method::Post), ["credentials"]) => {
+                    let revoke: RevokeCredential = dec.decode()?;
+                    self.credentials
+                        .revocations_repository()
+                        .revoke_credential(&self.issuer, &revoke.credential_hash)
+                        .await?;
+                    Response::ok().with_headers(&req).to_vec()?
+                }
+                (Some(Method::Post), ["subjects"]) => {
+                    let revoke: RevokeSubject = dec.decode()?;
+                    self.revoke_subject(&revoke.subject).await?;
+                    Response::ok().with_headers(&req).to_vec()?
+                }
+                _ => Response::unknown_path(&req).to_vec()?,
+            };
+            c.send(m.return_route(), res).await
+        } else {
+            secure_channel_required(c, m).await
+        }
+    }
+}
+
+/// Request to revoke a single credential
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RevokeCredential {
+    #[n(1)] pub credential_hash: CredentialHash,
+}
+
+impl RevokeCredential {
+    pub fn new(credential_hash: CredentialHash) -> Self {
+        Self { credential_hash }
+    }
+}
+
+/// Request to revoke all the credentials of a subject
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct RevokeSubject {
+    #[n(1)] pub subject: Identifier,
+}
+
+impl RevokeSubject {
+    pub fn new(subject: Identifier) -> Self {
+        Self { subject }
+    }
+}
+
+#[async_trait]
+pub trait Revocations {
+    async fn revoke_credential(
+        &self,
+        ctx: &Context,
+        credential_hash: CredentialHash,
+    ) -> miette::Result<()>;
+
+    async fn revoke_subject(&self, ctx: &Context, subject: Identifier) -> miette::Result<()>;
+
+    async fn get_revocation_list(
+        &self,
+        ctx: &Context,
+    ) -> miette::Result<RevocationListAndPurposeKey>;
+}
+
+#[async_trait]
+impl Revocations for AuthorityNode {
+    async fn revoke_credential(
+        &self,
+        ctx: &Context,
+        credential_hash: CredentialHash,
+    ) -> miette::Result<()> {
+        let req = Request::post("/credentials").body(RevokeCredential::new(credential_hash));
+        self.secure_client
+            .tell(ctx, DefaultAddress::REVOCATIONS_SERVICE, req)
+            .await
+            .into_diagnostic()?
+            .success()
+            .into_diagnostic()
+    }
+
+    async fn revoke_subject(&self, ctx: &Context, subject: Identifier) -> miette::Result<()> {
+        let req = Request::post("/subjects").body(RevokeSubject::new(subject));
+        self.secure_client
+            .tell(ctx, DefaultAddress::REVOCATIONS_SERVICE, req)
+            .await
+            .into_diagnostic()?
+            .success()
+            .into_diagnostic()
+    }
+
+    async fn get_revocation_list(
+        &self,
+        ctx: &Context,
+    ) -> miette::Result<RevocationListAndPurposeKey> {
+        self.secure_client
+            .ask(ctx, DefaultAddress::REVOCATIONS_SERVICE, Request::get("/"))
+            .await
+            .into_diagnostic()?
+            .success()
+            .into_diagnostic()
+    }
+}
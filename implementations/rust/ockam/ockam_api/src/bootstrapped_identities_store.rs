@@ -4,6 +4,7 @@ use serde::{Deserialize, Serialize};
 use serde_json as json;
 use tracing::trace;
 
+use ockam::identity::models::CredentialHash;
 use ockam::identity::utils::now;
 use ockam::identity::{AttributesEntry, Identifier, IdentityAttributesRepository};
 use ockam_core::async_trait;
@@ -71,6 +72,16 @@ impl IdentityAttributesRepository for BootstrapedIdentityAttributesStore {
     async fn delete(&self, identity: &Identifier) -> Result<()> {
         self.repository.delete(identity).await
     }
+
+    async fn delete_by_credential_hash(
+        &self,
+        attested_by: &Identifier,
+        credential_hash: &CredentialHash,
+    ) -> Result<()> {
+        self.repository
+            .delete_by_credential_hash(attested_by, credential_hash)
+            .await
+    }
 }
 
 #[derive(Clone, Debug, Serialize, Deserialize)]
@@ -156,4 +167,12 @@ impl IdentityAttributesRepository for PreTrustedIdentities {
     async fn delete(&self, _identity: &Identifier) -> Result<()> {
         Ok(())
     }
+
+    async fn delete_by_credential_hash(
+        &self,
+        _attested_by: &Identifier,
+        _credential_hash: &CredentialHash,
+    ) -> Result<()> {
+        Ok(())
+    }
 }
@@ -1,8 +1,11 @@
-use ockam::identity::models::ChangeHistory;
-use ockam::identity::{Identifier, Identity};
+use ockam::identity::models::{Change, ChangeHistory, RecoverySignature};
+use ockam::identity::{Identifier, Identities, IdentitiesKeys, Identity};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::Error;
-use ockam_vault::{HandleToSecret, SigningSecretKeyHandle};
+use ockam_vault::{
+    HandleToSecret, SigningSecret, SigningSecretKeyHandle, SoftwareVaultForSigning,
+    VerifyingPublicKey,
+};
 
 use crate::cli_state::{random_name, CliState, Result};
 
@@ -35,7 +38,7 @@ impl CliState {
         };
 
         let vault = self.get_named_vault(vault_name).await?;
-        let identities = self.make_identities(vault.vault().await?).await?;
+        let identities = self.make_identities(self.make_vault(&vault).await?).await?;
         let identity = identities.identities_creation().create_identity().await?;
 
         self.store_named_identity(&identity, name, &vault.name())
@@ -76,7 +79,7 @@ impl CliState {
         ));
 
         // create the identity
-        let identities = self.make_identities(vault.vault().await?).await?;
+        let identities = self.make_identities(self.make_vault(&vault).await?).await?;
         let identifier = identities
             .identities_creation()
             .identity_builder()
@@ -90,6 +93,148 @@ impl CliState {
     }
 }
 
+/// The methods below allow the rotation of the primary key of a named identity.
+///
+/// The new key is generated in the vault used to create the identity, and the new change
+/// is added to the identity change history.
+///
+/// If the primary key is lost, an identity which committed to recovery keys can be recovered
+/// with a new key, authorized by signatures of those recovery keys.
+///
+impl CliState {
+    /// Rotate the primary key of a named identity, or of the default identity if no name is given.
+    /// The purpose keys of the identity are attested with the new key, or replaced by new
+    /// purpose keys if `revoke_all_purpose_keys` is true
+    pub async fn rotate_identity_key(
+        &self,
+        name: &Option<String>,
+        revoke_all_purpose_keys: bool,
+    ) -> Result<Identity> {
+        let named_identity = self.get_named_identity_or_default(name).await?;
+        let vault = self.get_named_vault(&named_identity.vault_name()).await?;
+        let identities = self.make_identities(self.make_vault(&vault).await?).await?;
+        Ok(identities
+            .rotate_identity_key(&named_identity.identifier(), revoke_all_purpose_keys)
+            .await?)
+    }
+
+    /// Rotate the primary key of a named identity, or of the default identity if no name is given,
+    /// with a new key committing to a set of recovery keys.
+    /// `threshold` of those keys can then authorize a new primary key if the current one is lost.
+    /// If the identity already has recovery keys, enough of their secrets must be given, with their
+    /// index in the recovery keys of the identity, to approve their replacement
+    pub async fn set_identity_recovery_keys(
+        &self,
+        name: &Option<String>,
+        threshold: u8,
+        recovery_keys: Vec<VerifyingPublicKey>,
+        current_recovery_secrets: Vec<(u8, SigningSecret)>,
+    ) -> Result<Identity> {
+        let named_identity = self.get_named_identity_or_default(name).await?;
+        let vault = self.get_named_vault(&named_identity.vault_name()).await?;
+        let identities = self.make_identities(self.make_vault(&vault).await?).await?;
+        let identifier = named_identity.identifier();
+        let change = identities
+            .create_recovery_keys_change(&identifier, threshold, recovery_keys)
+            .await?;
+        let recovery_signatures =
+            Self::sign_with_recovery_secrets(&identities, &change, current_recovery_secrets)
+                .await?;
+        Ok(identities
+            .replace_recovery_keys(&identifier, change, recovery_signatures)
+            .await?)
+    }
+
+    /// Recover an identity whose primary key was lost and store it with a name. If the identity
+    /// was already stored, that name must be the same as before.
+    /// The new primary key is created in the given vault, or in the default vault, and is
+    /// authorized by signatures made with the secrets of the identity recovery keys, given with
+    /// their index in the recovery keys of the identity
+    pub async fn recover_identity(
+        &self,
+        name: &str,
+        vault_name: &Option<String>,
+        identity: Identity,
+        recovery_secrets: Vec<(u8, SigningSecret)>,
+    ) -> Result<NamedIdentity> {
+        let repository = self.identities_repository().await?;
+        if let Some(named_identity) = repository.get_named_identity(name).await? {
+            if &named_identity.identifier() != identity.identifier() {
+                return Err(Error::new(
+                    Origin::Api,
+                    Kind::AlreadyExists,
+                    format!("an identity named {name} already exists"),
+                )
+                .into());
+            }
+        }
+        if let Some(named_identity) = repository
+            .get_named_identity_by_identifier(identity.identifier())
+            .await?
+        {
+            if named_identity.name() != name {
+                return Err(Error::new(
+                    Origin::Api,
+                    Kind::AlreadyExists,
+                    format!(
+                        "the identity {} is already named {}",
+                        identity.identifier(),
+                        named_identity.name()
+                    ),
+                )
+                .into());
+            }
+        }
+
+        let vault = match vault_name {
+            Some(vault_name) => self.get_named_vault(vault_name).await?,
+            None => self.get_or_create_default_named_vault().await?,
+        };
+        let identities = self.make_identities(self.make_vault(&vault).await?).await?;
+        let options = identities
+            .identities_creation()
+            .identity_builder()
+            .build_options()
+            .await?;
+        let change = identities
+            .identities_keys()
+            .create_recovery_change(&identity, options)
+            .await?;
+
+        let recovery_signatures =
+            Self::sign_with_recovery_secrets(&identities, &change, recovery_secrets).await?;
+
+        let identity = identities
+            .identities_creation()
+            .recover_identity(identity, change, recovery_signatures)
+            .await?;
+        self.store_named_identity(identity.identifier(), name, &vault.name())
+            .await
+    }
+
+    /// Sign a change with recovery secrets, given with their index in the recovery keys of an identity
+    async fn sign_with_recovery_secrets(
+        identities: &Identities,
+        change: &Change,
+        recovery_secrets: Vec<(u8, SigningSecret)>,
+    ) -> Result<Vec<RecoverySignature>> {
+        // the recovery secrets are only kept in memory while signing
+        let recovery_vault = SoftwareVaultForSigning::create().await?;
+        let recovery_keys =
+            IdentitiesKeys::new(recovery_vault.clone(), identities.vault().verifying_vault);
+        let mut recovery_signatures = vec![];
+        for (key_index, secret) in recovery_secrets {
+            let handle = recovery_vault.import_key(secret).await?;
+            recovery_signatures.push(
+                recovery_keys
+                    .sign_recovery_change(change, key_index, &handle)
+                    .await?,
+            );
+        }
+        Ok(recovery_signatures)
+    }
+}
+
 /// The methods below allow to query identities:
 ///
 ///  - all of them
@@ -182,9 +327,7 @@ impl CliState {
                 Ok(Identity::import_from_change_history(
                     Some(&identity.identifier()),
                     change_history,
-                    self.get_or_create_default_named_vault()
-                        .await?
-                        .vault()
+                    self.make_vault(&self.get_or_create_default_named_vault().await?)
                         .await?
                         .verifying_vault,
                 )
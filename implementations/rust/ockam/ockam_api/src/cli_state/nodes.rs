@@ -119,7 +119,7 @@ impl CliState {
     ///  - remove the node log files
     pub async fn remove_node(&self, node_name: &str) -> Result<()> {
         // don't try to remove a node on a non-existent database
-        if !self.database_path().exists() {
+        if self.database_url().is_none() && !self.database_path().exists() {
             return Ok(());
         };
 
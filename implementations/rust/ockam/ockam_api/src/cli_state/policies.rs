@@ -1,6 +1,11 @@
 use crate::cli_state::CliState;
 use crate::cli_state::Result;
-use ockam_abac::{Action, Env, Policy, PolicyAccessControl, Resource};
+use crate::http::HttpPathAccessControl;
+use crate::kafka::KafkaTopicAccessControl;
+use ockam::identity::Identifier;
+use ockam_abac::{
+    Action, Env, Policy, PolicyAccessControl, PolicyDecision, PolicyDecisionFilter, Resource,
+};
 
 impl CliState {
     pub async fn get_policy(&self, resource: &Resource, action: &Action) -> Result<Option<Policy>> {
@@ -63,4 +68,42 @@ impl CliState {
             env,
         ))
     }
+
+    /// Create an access control evaluating the policies of kafka topics for a given identity
+    pub(crate) async fn make_kafka_topic_access_control(
+        &self,
+        subject: Identifier,
+        env: Env,
+    ) -> Result<KafkaTopicAccessControl> {
+        Ok(KafkaTopicAccessControl::new(
+            self.policies_repository().await?,
+            self.identity_attributes_repository().await?,
+            subject,
+            env,
+        ))
+    }
+
+    /// Create an access control evaluating the policies of HTTP paths for the callers of an outlet
+    pub(crate) async fn make_http_path_access_control(
+        &self,
+        env: Env,
+    ) -> Result<HttpPathAccessControl> {
+        Ok(HttpPathAccessControl::new(
+            self.policies_repository().await?,
+            self.identity_attributes_repository().await?,
+            env,
+        ))
+    }
+
+    /// Return the decisions taken when evaluating policies, most recent first
+    pub async fn get_policy_decisions(
+        &self,
+        filter: &PolicyDecisionFilter,
+    ) -> Result<Vec<PolicyDecision>> {
+        Ok(self
+            .policy_audit_repository()
+            .await?
+            .get_decisions(filter)
+            .await?)
+    }
 }
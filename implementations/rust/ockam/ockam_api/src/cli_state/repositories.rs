@@ -3,7 +3,9 @@ use ockam::identity::{
     ChangeHistoryRepository, ChangeHistorySqlxDatabase, IdentityAttributesRepository,
     IdentityAttributesSqlxDatabase,
 };
-use ockam_abac::{PoliciesRepository, PolicySqlxDatabase};
+use ockam_abac::{
+    PoliciesRepository, PolicyAuditRepository, PolicyAuditSqlxDatabase, PolicySqlxDatabase,
+};
 use ockam_core::compat::sync::Arc;
 
 use crate::cli_state::error::Result;
@@ -56,6 +58,10 @@ impl CliState {
         Ok(Arc::new(PolicySqlxDatabase::new(self.database())))
     }
 
+    pub(crate) async fn policy_audit_repository(&self) -> Result<Arc<dyn PolicyAuditRepository>> {
+        Ok(Arc::new(PolicyAuditSqlxDatabase::new(self.database())))
+    }
+
     pub(super) async fn projects_repository(&self) -> Result<Arc<dyn ProjectsRepository>> {
         Ok(Arc::new(ProjectsSqlxDatabase::new(self.database())))
     }
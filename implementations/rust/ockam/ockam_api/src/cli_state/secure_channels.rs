@@ -21,7 +21,9 @@ impl CliState {
 
         //TODO: fix this.  Either don't require it to be a bootstrappedidentitystore (and use the
         //trait instead),  or pass it from the general_options always.
-        let vault = self.get_node_vault(node_name).await?.vault().await?;
+        let vault = self
+            .make_vault(&self.get_node_vault(node_name).await?)
+            .await?;
         let identity_attributes_repository: Arc<dyn IdentityAttributesRepository> =
             Arc::new(match pre_trusted_identities {
                 None => BootstrapedIdentityAttributesStore::new(
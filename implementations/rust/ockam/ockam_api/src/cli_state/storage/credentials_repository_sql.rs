@@ -38,7 +38,7 @@ impl CredentialsRepository for CredentialsSqlxDatabase {
         issuer: &Identity,
         credential: CredentialAndPurposeKey,
     ) -> Result<NamedCredential> {
-        let query = query("INSERT OR REPLACE INTO credential VALUES (?, ?, ?, ?)")
+        let query = query("INSERT INTO credential VALUES ($1, $2, $3, $4) ON CONFLICT (name) DO UPDATE SET issuer_identifier = $2, issuer_change_history = $3, credential = $4")
             .bind(name.to_sql())
             .bind(issuer.identifier().to_sql())
             .bind(issuer.change_history().to_sql())
@@ -112,43 +112,47 @@ mod tests {
     use ockam::identity::models::CredentialSchemaIdentifier;
     use ockam::identity::utils::AttributesBuilder;
     use ockam::identity::{identities, Identities};
+    use ockam::with_dbs;
     use std::time::Duration;
 
     use super::*;
 
     #[tokio::test]
     async fn test_credentials_repository() -> Result<()> {
-        let repository = create_repository().await?;
-        let identities = identities().await?;
-
-        // a credential can be stored under a name
-        let issuer_identity = identities.identities_creation().create_identity().await?;
-        let issuer = identities.get_identity(&issuer_identity).await?;
-        let credential = create_credential(identities.clone(), &issuer_identity).await?;
-        let named_credential1 = repository
-            .store_credential("name", &issuer, credential.clone())
-            .await?;
-
-        // That credential can be retrieved by name
-        let result = repository.get_credential("name").await?;
-        assert_eq!(
-            result,
-            Some(NamedCredential::new("name", &issuer, credential))
-        );
-
-        // All the credentials can be retrieved at once
-        let credential = create_credential(identities, &issuer_identity).await?;
-        let named_credential2 = repository
-            .store_credential("name2", &issuer, credential.clone())
-            .await?;
-        let result = repository.get_credentials().await?;
-        assert_eq!(result, vec![named_credential1, named_credential2]);
-        Ok(())
+        with_dbs(|db| async move {
+            let repository = create_repository(db);
+            let identities = identities().await?;
+
+            // a credential can be stored under a name
+            let issuer_identity = identities.identities_creation().create_identity().await?;
+            let issuer = identities.get_identity(&issuer_identity).await?;
+            let credential = create_credential(identities.clone(), &issuer_identity).await?;
+            let named_credential1 = repository
+                .store_credential("name", &issuer, credential.clone())
+                .await?;
+
+            // That credential can be retrieved by name
+            let result = repository.get_credential("name").await?;
+            assert_eq!(
+                result,
+                Some(NamedCredential::new("name", &issuer, credential))
+            );
+
+            // All the credentials can be retrieved at once
+            let credential = create_credential(identities, &issuer_identity).await?;
+            let named_credential2 = repository
+                .store_credential("name2", &issuer, credential.clone())
+                .await?;
+            let result = repository.get_credentials().await?;
+            assert_eq!(result, vec![named_credential1, named_credential2]);
+            Ok(())
+        })
+        .await
     }
 
     /// HELPERS
-    async fn create_repository() -> Result<Arc<dyn CredentialsRepository>> {
-        Ok(CredentialsSqlxDatabase::create().await?)
+    fn create_repository(db: Arc<SqlxDatabase>) -> Arc<dyn CredentialsRepository> {
+        Arc::new(CredentialsSqlxDatabase::new(db))
     }
 
     async fn create_credential(
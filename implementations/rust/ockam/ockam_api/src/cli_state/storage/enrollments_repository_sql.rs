@@ -1,13 +1,13 @@
 use std::str::FromStr;
 use std::sync::Arc;
 
-use sqlx::sqlite::SqliteRow;
+use sqlx::any::AnyRow;
 use sqlx::FromRow;
 use sqlx::*;
 use time::OffsetDateTime;
 
 use ockam::identity::Identifier;
-use ockam::{FromSqlxError, SqlxDatabase, ToSqlxType, ToVoid};
+use ockam::{Boolean, FromSqlxError, Nullable, SqlxDatabase, ToSqlxType, ToVoid};
 use ockam_core::async_trait;
 use ockam_core::Result;
 
@@ -36,7 +36,7 @@ impl EnrollmentsSqlxDatabase {
 #[async_trait]
 impl EnrollmentsRepository for EnrollmentsSqlxDatabase {
     async fn set_as_enrolled(&self, identifier: &Identifier) -> Result<()> {
-        let query = query("INSERT OR REPLACE INTO identity_enrollment VALUES (?, ?)")
+        let query = query("INSERT INTO identity_enrollment VALUES ($1, $2) ON CONFLICT (identifier) DO UPDATE SET enrolled_at = $2")
             .bind(identifier.to_sql())
             .bind(OffsetDateTime::now_utc().to_sql());
         Ok(query.execute(&self.database.pool).await.void()?)
@@ -94,11 +94,11 @@ impl EnrollmentsRepository for EnrollmentsSqlxDatabase {
             INNER JOIN named_identity ON
               identity.identifier = named_identity.identifier
             WHERE
-              named_identity.is_default = ?
+              named_identity.is_default = $1
             "#,
         )
         .bind(true.to_sql());
-        let result: Option<SqliteRow> = query
+        let result: Option<AnyRow> = query
             .fetch_optional(&self.database.pool)
             .await
             .into_core()?;
@@ -118,11 +118,11 @@ impl EnrollmentsRepository for EnrollmentsSqlxDatabase {
             INNER JOIN named_identity ON
               identity.identifier = named_identity.identifier
             WHERE
-              named_identity.name = ?
+              named_identity.name = $1
             "#,
         )
         .bind(name.to_sql());
-        let result: Option<SqliteRow> = query
+        let result: Option<AnyRow> = query
             .fetch_optional(&self.database.pool)
             .await
             .into_core()?;
@@ -133,9 +133,9 @@ impl EnrollmentsRepository for EnrollmentsSqlxDatabase {
 #[derive(FromRow)]
 pub struct EnrollmentRow {
     identifier: String,
-    name: Option<String>,
-    is_default: bool,
-    enrolled_at: Option<i64>,
+    name: Nullable<String>,
+    is_default: Boolean,
+    enrolled_at: Nullable<i64>,
 }
 
 impl EnrollmentRow {
@@ -143,14 +143,15 @@ impl EnrollmentRow {
         let identifier = Identifier::from_str(self.identifier.as_str())?;
         Ok(IdentityEnrollment::new(
             identifier,
-            self.name.clone(),
-            self.is_default,
+            self.name.to_option(),
+            self.is_default.to_bool(),
             self.enrolled_at(),
         ))
     }
 
     fn enrolled_at(&self) -> Option<OffsetDateTime> {
         self.enrolled_at
+            .to_option()
             .map(|at| OffsetDateTime::from_unix_timestamp(at).unwrap_or(OffsetDateTime::now_utc()))
     }
 }
@@ -161,35 +162,38 @@ mod tests {
     use ockam::identity::{
         identities, ChangeHistoryRepository, ChangeHistorySqlxDatabase, Identity,
     };
+    use ockam::with_dbs;
 
     use super::*;
 
     #[tokio::test]
     async fn test_identities_enrollment_repository() -> Result<()> {
-        let db = create_database().await?;
-        let repository = create_repository(db.clone());
+        with_dbs(|db| async move {
+            let repository = create_repository(db.clone());
 
-        // create some identities
-        let identity1 = create_identity(db.clone(), "identity1").await?;
-        create_identity(db.clone(), "identity2").await?;
+            // create some identities
+            let identity1 = create_identity(db.clone(), "identity1").await?;
+            create_identity(db.clone(), "identity2").await?;
 
-        // an identity can be enrolled
-        repository.set_as_enrolled(identity1.identifier()).await?;
+            // an identity can be enrolled
+            repository.set_as_enrolled(identity1.identifier()).await?;
 
-        // retrieve the identities and their enrollment status
-        let result = repository.get_all_identities_enrollments().await?;
-        assert_eq!(result.len(), 2);
+            // retrieve the identities and their enrollment status
+            let result = repository.get_all_identities_enrollments().await?;
+            assert_eq!(result.len(), 2);
 
-        // retrieve only the enrolled identities
-        let result = repository.get_enrolled_identities().await?;
-        assert_eq!(result.len(), 1);
+            // retrieve only the enrolled identities
+            let result = repository.get_enrolled_identities().await?;
+            assert_eq!(result.len(), 1);
 
-        // the first identity has been set as the default one when it has been created
-        // so we should retrieve this information via is_default_identity_enrolled
-        let result = repository.is_default_identity_enrolled().await?;
-        assert!(result);
+            // the first identity has been set as the default one when it has been created
+            // so we should retrieve this information via is_default_identity_enrolled
+            let result = repository.is_default_identity_enrolled().await?;
+            assert!(result);
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
     /// HELPERS
@@ -226,10 +230,6 @@ mod tests {
         Arc::new(EnrollmentsSqlxDatabase::new(db))
     }
 
-    async fn create_database() -> Result<Arc<SqlxDatabase>> {
-        SqlxDatabase::in_memory("enrollments-test").await
-    }
-
     async fn create_change_history_repository(
         db: Arc<SqlxDatabase>,
     ) -> Result<Arc<dyn ChangeHistoryRepository>> {
@@ -6,12 +6,12 @@ use ockam::identity::Identifier;
 use ockam_core::async_trait;
 use ockam_core::compat::sync::Arc;
 use ockam_core::Result;
-use ockam_node::database::{FromSqlxError, SqlxDatabase, ToSqlxType, ToVoid};
+use ockam_node::database::{Boolean, FromSqlxError, SqlxDatabase, ToSqlxType, ToVoid};
 
 use crate::cli_state::{IdentitiesRepository, NamedIdentity};
 
 /// Implementation of `IdentitiesRepository` trait based on an underlying database
-/// using sqlx as its API, and Sqlite as its driver
+/// using sqlx as its API, and Sqlite or Postgres as its driver
 #[derive(Clone)]
 pub struct IdentitiesSqlxDatabase {
     database: Arc<SqlxDatabase>,
@@ -43,13 +43,14 @@ impl IdentitiesRepository for IdentitiesSqlxDatabase {
         let mut transaction = self.database.begin().await.into_core()?;
 
         let query1 = query_scalar(
-            "SELECT EXISTS(SELECT 1 FROM named_identity WHERE is_default=$1 AND name=$2)",
+            "SELECT CAST(EXISTS(SELECT 1 FROM named_identity WHERE is_default=$1 AND name=$2) AS INTEGER)",
         )
         .bind(true.to_sql())
         .bind(name.to_sql());
-        let is_already_default: bool = query1.fetch_one(&mut *transaction).await.into_core()?;
+        let is_already_default: Boolean = query1.fetch_one(&mut *transaction).await.into_core()?;
+        let is_already_default = is_already_default.to_bool();
 
-        let query2 = query("INSERT OR REPLACE INTO named_identity VALUES (?, ?, ?, ?)")
+        let query2 = query("INSERT INTO named_identity VALUES ($1, $2, $3, $4) ON CONFLICT (name) DO UPDATE SET identifier = $1, vault_name = $3, is_default = $4")
             .bind(identifier.to_sql())
             .bind(name.to_sql())
             .bind(vault_name.to_sql())
@@ -84,7 +85,7 @@ impl IdentitiesRepository for IdentitiesSqlxDatabase {
 
             // otherwise delete it and set another identity as the default
             Some(named_identity) => {
-                let query2 = query("DELETE FROM named_identity WHERE name=?").bind(name.to_sql());
+                let query2 = query("DELETE FROM named_identity WHERE name=$1").bind(name.to_sql());
                 query2.execute(&mut *transaction).await.void()?;
 
                 // if the deleted identity was the default one, select another identity to be the default one
@@ -96,7 +97,7 @@ impl IdentitiesRepository for IdentitiesSqlxDatabase {
                             .into_core()?
                     {
                         let query3 =
-                            query("UPDATE named_identity SET is_default = ? WHERE name = ?")
+                            query("UPDATE named_identity SET is_default = $1 WHERE name = $2")
                                 .bind(true.to_sql())
                                 .bind(other_name.to_sql());
                         query3.execute(&mut *transaction).await.void()?
@@ -180,13 +181,13 @@ impl IdentitiesRepository for IdentitiesSqlxDatabase {
     async fn set_as_default(&self, name: &str) -> Result<()> {
         let mut transaction = self.database.begin().await.into_core()?;
         // set the identifier as the default one
-        let query1 = query("UPDATE named_identity SET is_default = ? WHERE name = ?")
+        let query1 = query("UPDATE named_identity SET is_default = $1 WHERE name = $2")
             .bind(true.to_sql())
             .bind(name.to_sql());
         query1.execute(&mut *transaction).await.void()?;
 
         // set all the others as non-default
-        let query2 = query("UPDATE named_identity SET is_default = ? WHERE name <> ?")
+        let query2 = query("UPDATE named_identity SET is_default = $1 WHERE name <> $2")
             .bind(false.to_sql())
             .bind(name.to_sql());
         query2.execute(&mut *transaction).await.void()?;
@@ -196,13 +197,13 @@ impl IdentitiesRepository for IdentitiesSqlxDatabase {
     async fn set_as_default_by_identifier(&self, identifier: &Identifier) -> Result<()> {
         let mut transaction = self.database.begin().await.into_core()?;
         // set the identifier as the default one
-        let query1 = query("UPDATE named_identity SET is_default = ? WHERE identifier = ?")
+        let query1 = query("UPDATE named_identity SET is_default = $1 WHERE identifier = $2")
             .bind(true.to_sql())
             .bind(identifier.to_sql());
         query1.execute(&mut *transaction).await.void()?;
 
         // set all the others as non-default
-        let query2 = query("UPDATE named_identity SET is_default = ? WHERE identifier <> ?")
+        let query2 = query("UPDATE named_identity SET is_default = $1 WHERE identifier <> $2")
             .bind(false.to_sql())
             .bind(identifier.to_sql());
         query2.execute(&mut *transaction).await.void()?;
@@ -225,7 +226,7 @@ pub(crate) struct NamedIdentityRow {
     identifier: String,
     name: String,
     vault_name: String,
-    is_default: bool,
+    is_default: Boolean,
 }
 
 impl NamedIdentityRow {
@@ -247,7 +248,7 @@ impl NamedIdentityRow {
             self.identifier()?,
             self.name.clone(),
             self.vault_name.clone(),
-            self.is_default,
+            self.is_default.to_bool(),
         ))
     }
 }
@@ -255,90 +256,97 @@ impl NamedIdentityRow {
 #[cfg(test)]
 mod tests {
     use ockam::identity::identities;
+    use ockam::with_dbs;
 
     use super::*;
 
     #[tokio::test]
     async fn test_identities_repository_named_identities() -> Result<()> {
-        let repository = create_repository().await?;
-
-        // A name can be associated to an identity
-        let identifier1 = create_identity().await?;
-        repository
-            .store_named_identity(&identifier1, "name1", "vault")
-            .await?;
-
-        let identifier2 = create_identity().await?;
-        repository
-            .store_named_identity(&identifier2, "name2", "vault")
-            .await?;
-
-        let result = repository.get_identifier("name1").await?;
-        assert_eq!(result, Some(identifier1.clone()));
-
-        let result = repository
-            .get_identity_name_by_identifier(&identifier1)
-            .await?;
-        assert_eq!(result, Some("name1".into()));
-
-        let result = repository.get_named_identity("name2").await?;
-        assert_eq!(result.map(|n| n.identifier()), Some(identifier2.clone()));
-
-        let result = repository.get_named_identities().await?;
-        assert_eq!(
-            result.iter().map(|n| n.identifier()).collect::<Vec<_>>(),
-            vec![identifier1.clone(), identifier2.clone()]
-        );
-
-        repository.delete_identity("name1").await?;
-        let result = repository.get_named_identities().await?;
-        assert_eq!(
-            result.iter().map(|n| n.identifier()).collect::<Vec<_>>(),
-            vec![identifier2.clone()]
-        );
-
-        Ok(())
+        with_dbs(|db| async move {
+            let repository = create_repository(db);
+
+            // A name can be associated to an identity
+            let identifier1 = create_identity().await?;
+            repository
+                .store_named_identity(&identifier1, "name1", "vault")
+                .await?;
+
+            let identifier2 = create_identity().await?;
+            repository
+                .store_named_identity(&identifier2, "name2", "vault")
+                .await?;
+
+            let result = repository.get_identifier("name1").await?;
+            assert_eq!(result, Some(identifier1.clone()));
+
+            let result = repository
+                .get_identity_name_by_identifier(&identifier1)
+                .await?;
+            assert_eq!(result, Some("name1".into()));
+
+            let result = repository.get_named_identity("name2").await?;
+            assert_eq!(result.map(|n| n.identifier()), Some(identifier2.clone()));
+
+            let result = repository.get_named_identities().await?;
+            assert_eq!(
+                result.iter().map(|n| n.identifier()).collect::<Vec<_>>(),
+                vec![identifier1.clone(), identifier2.clone()]
+            );
+
+            repository.delete_identity("name1").await?;
+            let result = repository.get_named_identities().await?;
+            assert_eq!(
+                result.iter().map(|n| n.identifier()).collect::<Vec<_>>(),
+                vec![identifier2.clone()]
+            );
+
+            Ok(())
+        })
+        .await
     }
 
     #[tokio::test]
     async fn test_identities_repository_default_identities() -> Result<()> {
-        let repository = create_repository().await?;
-
-        // A name can be associated to an identity
-        let identifier1 = create_identity().await?;
-        let named_identity1 = repository
-            .store_named_identity(&identifier1, "name1", "vault")
-            .await?;
-
-        let identifier2 = create_identity().await?;
-        let named_identity2 = repository
-            .store_named_identity(&identifier2, "name2", "vault")
-            .await?;
-
-        // An identity can be marked as being the default one
-        repository
-            .set_as_default_by_identifier(&identifier1)
-            .await?;
-        let result = repository.get_default_named_identity().await?;
-        assert_eq!(result, Some(named_identity1.set_as_default()));
-
-        // An identity can be marked as being the default one by passing its name
-        repository.set_as_default("name2").await?;
-        let result = repository.get_default_named_identity().await?;
-        assert_eq!(result, Some(named_identity2.set_as_default()));
-
-        let result = repository.get_named_identity("name1").await?;
-        assert!(!result.unwrap().is_default());
-
-        let result = repository.get_default_named_identity().await?;
-        assert_eq!(result.map(|i| i.name()), Some("name2".to_string()));
-
-        Ok(())
+        with_dbs(|db| async move {
+            let repository = create_repository(db);
+
+            // A name can be associated to an identity
+            let identifier1 = create_identity().await?;
+            let named_identity1 = repository
+                .store_named_identity(&identifier1, "name1", "vault")
+                .await?;
+
+            let identifier2 = create_identity().await?;
+            let named_identity2 = repository
+                .store_named_identity(&identifier2, "name2", "vault")
+                .await?;
+
+            // An identity can be marked as being the default one
+            repository
+                .set_as_default_by_identifier(&identifier1)
+                .await?;
+            let result = repository.get_default_named_identity().await?;
+            assert_eq!(result, Some(named_identity1.set_as_default()));
+
+            // An identity can be marked as being the default one by passing its name
+            repository.set_as_default("name2").await?;
+            let result = repository.get_default_named_identity().await?;
+            assert_eq!(result, Some(named_identity2.set_as_default()));
+
+            let result = repository.get_named_identity("name1").await?;
+            assert!(!result.unwrap().is_default());
+
+            let result = repository.get_default_named_identity().await?;
+            assert_eq!(result.map(|i| i.name()), Some("name2".to_string()));
+
+            Ok(())
+        })
+        .await
     }
 
     /// HELPERS
-    async fn create_repository() -> Result<Arc<dyn IdentitiesRepository>> {
-        Ok(IdentitiesSqlxDatabase::create().await?)
+    fn create_repository(db: Arc<SqlxDatabase>) -> Arc<dyn IdentitiesRepository> {
+        Arc::new(IdentitiesSqlxDatabase::new(db))
     }
 
     async fn create_identity() -> Result<Identifier> {
@@ -1,7 +1,7 @@
 use std::str::FromStr;
 use std::sync::Arc;
 
-use sqlx::sqlite::SqliteRow;
+use sqlx::any::AnyRow;
 use sqlx::*;
 
 use ockam::identity::Identifier;
@@ -9,7 +9,7 @@ use ockam_core::async_trait;
 use ockam_core::env::FromString;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
-use ockam_node::database::{FromSqlxError, SqlxDatabase, ToSqlxType, ToVoid};
+use ockam_node::database::{Boolean, FromSqlxError, Nullable, SqlxDatabase, ToSqlxType, ToVoid};
 
 use crate::cloud::addon::ConfluentConfig;
 use crate::cloud::project::{OktaConfig, Project, ProjectUserRole};
@@ -52,14 +52,15 @@ impl ProjectsRepository for ProjectsSqlxDatabase {
         let mut transaction = self.database.begin().await.into_core()?;
 
         let query1 = query_scalar(
-            "SELECT EXISTS(SELECT 1 FROM project WHERE is_default=$1 AND project_id=$2)",
+            "SELECT CAST(EXISTS(SELECT 1 FROM project WHERE is_default=$1 AND project_id=$2) AS INTEGER)",
         )
         .bind(true.to_sql())
         .bind(project.id.to_sql());
-        let is_already_default: bool = query1.fetch_one(&mut *transaction).await.into_core()?;
+        let is_already_default: Boolean = query1.fetch_one(&mut *transaction).await.into_core()?;
+        let is_already_default = is_already_default.to_bool();
 
         let query2 = query(
-            "INSERT OR REPLACE INTO project VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+            "INSERT INTO project VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) ON CONFLICT (project_id) DO UPDATE SET project_name = $2, is_default = $3, space_id = $4, space_name = $5, identifier = $6, access_route = $7, authority_identity = $8, authority_access_route = $9, version = $10, running = $11, operation_id = $12",
         )
             .bind(project.id.to_sql())
             .bind(project.name.to_sql())
@@ -82,7 +83,7 @@ impl ProjectsRepository for ProjectsSqlxDatabase {
 
         // store the users associated to that project
         for user_email in &project.users {
-            let query = query("INSERT OR REPLACE INTO user_project VALUES (?, ?)")
+            let query = query("INSERT INTO user_project VALUES ($1, $2)")
                 .bind(user_email.to_sql())
                 .bind(project.id.to_sql());
             query.execute(&mut *transaction).await.void()?;
@@ -94,7 +95,7 @@ impl ProjectsRepository for ProjectsSqlxDatabase {
 
         // store the user roles associated to that project
         for user_role in &project.user_roles {
-            let query = query("INSERT OR REPLACE INTO user_role VALUES (?, ?, ?, ?, ?)")
+            let query = query("INSERT INTO user_role VALUES ($1, $2, $3, $4, $5)")
                 .bind(user_role.id.to_sql())
                 .bind(project.id.to_sql())
                 .bind(user_role.email.to_sql())
@@ -104,7 +105,7 @@ impl ProjectsRepository for ProjectsSqlxDatabase {
         }
 
         // make sure that the project space is also saved
-        let query5 = query("INSERT OR IGNORE INTO space VALUES ($1, $2, $3)")
+        let query5 = query("INSERT INTO space VALUES ($1, $2, $3) ON CONFLICT DO NOTHING")
             .bind(project.space_id.to_sql())
             .bind(project.space_name.to_sql())
             .bind(true.to_sql());
@@ -112,7 +113,7 @@ impl ProjectsRepository for ProjectsSqlxDatabase {
 
         // store the okta configuration if any
         for okta_config in &project.okta_config {
-            let query = query("INSERT OR REPLACE INTO okta_config VALUES (?, ?, ?, ?, ?)")
+            let query = query("INSERT INTO okta_config VALUES ($1, $2, $3, $4, $5)")
                 .bind(project.id.to_sql())
                 .bind(okta_config.tenant_base_url.to_string().to_sql())
                 .bind(okta_config.client_id.to_sql())
@@ -123,7 +124,7 @@ impl ProjectsRepository for ProjectsSqlxDatabase {
 
         // store the confluent configuration if any
         for confluent_config in &project.confluent_config {
-            let query = query("INSERT OR REPLACE INTO confluent_config VALUES (?, ?)")
+            let query = query("INSERT INTO confluent_config VALUES ($1, $2)")
                 .bind(project.id.to_sql())
                 .bind(confluent_config.bootstrap_server.to_sql());
             query.execute(&mut *transaction).await.void()?;
@@ -135,7 +136,7 @@ impl ProjectsRepository for ProjectsSqlxDatabase {
     async fn get_project(&self, project_id: &str) -> Result<Option<Project>> {
         let query =
             query("SELECT project_name FROM project WHERE project_id=$1").bind(project_id.to_sql());
-        let row: Option<SqliteRow> = query
+        let row: Option<AnyRow> = query
             .fetch_optional(&self.database.pool)
             .await
             .into_core()?;
@@ -202,7 +203,7 @@ impl ProjectsRepository for ProjectsSqlxDatabase {
 
     async fn get_projects(&self) -> Result<Vec<Project>> {
         let query = query("SELECT project_name FROM project");
-        let rows: Vec<SqliteRow> = query.fetch_all(&self.database.pool).await.into_core()?;
+        let rows: Vec<AnyRow> = query.fetch_all(&self.database.pool).await.into_core()?;
         let project_names: Vec<String> = rows.iter().map(|r| r.get(0)).collect();
         let mut projects = vec![];
         for project_name in project_names {
@@ -217,7 +218,7 @@ impl ProjectsRepository for ProjectsSqlxDatabase {
     async fn get_default_project(&self) -> Result<Option<Project>> {
         let query =
             query("SELECT project_name FROM project WHERE is_default=$1").bind(true.to_sql());
-        let row: Option<SqliteRow> = query
+        let row: Option<AnyRow> = query
             .fetch_optional(&self.database.pool)
             .await
             .into_core()?;
@@ -233,13 +234,13 @@ impl ProjectsRepository for ProjectsSqlxDatabase {
     async fn set_default_project(&self, project_id: &str) -> Result<()> {
         let mut transaction = self.database.begin().await.into_core()?;
         // set the project as the default one
-        let query1 = query("UPDATE project SET is_default = ? WHERE project_id = ?")
+        let query1 = query("UPDATE project SET is_default = $1 WHERE project_id = $2")
             .bind(true.to_sql())
             .bind(project_id.to_sql());
         query1.execute(&mut *transaction).await.void()?;
 
         // set all the others as non-default
-        let query2 = query("UPDATE project SET is_default = ? WHERE project_id <> ?")
+        let query2 = query("UPDATE project SET is_default = $1 WHERE project_id <> $2")
             .bind(false.to_sql())
             .bind(project_id.to_sql());
         query2.execute(&mut *transaction).await.void()?;
@@ -249,20 +250,21 @@ impl ProjectsRepository for ProjectsSqlxDatabase {
     async fn delete_project(&self, project_id: &str) -> Result<()> {
         let mut transaction = self.database.begin().await.into_core()?;
 
-        let query1 = query("DELETE FROM project WHERE project_id=?").bind(project_id.to_sql());
+        let query1 = query("DELETE FROM project WHERE project_id=$1").bind(project_id.to_sql());
         query1.execute(&mut *transaction).await.void()?;
 
-        let query2 = query("DELETE FROM user_project WHERE project_id=?").bind(project_id.to_sql());
+        let query2 =
+            query("DELETE FROM user_project WHERE project_id=$1").bind(project_id.to_sql());
         query2.execute(&mut *transaction).await.void()?;
 
-        let query3 = query("DELETE FROM user_role WHERE project_id=?").bind(project_id.to_sql());
+        let query3 = query("DELETE FROM user_role WHERE project_id=$1").bind(project_id.to_sql());
         query3.execute(&mut *transaction).await.void()?;
 
-        let query4 = query("DELETE FROM okta_config WHERE project_id=?").bind(project_id.to_sql());
+        let query4 = query("DELETE FROM okta_config WHERE project_id=$1").bind(project_id.to_sql());
         query4.execute(&mut *transaction).await.void()?;
 
         let query5 =
-            query("DELETE FROM confluent_config WHERE project_id=?").bind(project_id.to_sql());
+            query("DELETE FROM confluent_config WHERE project_id=$1").bind(project_id.to_sql());
         query5.execute(&mut *transaction).await.void()?;
 
         transaction.commit().await.void()?;
@@ -278,16 +280,16 @@ struct ProjectRow {
     project_id: String,
     project_name: String,
     #[allow(unused)]
-    is_default: bool,
+    is_default: Boolean,
     space_id: String,
     space_name: String,
-    identifier: Option<String>,
+    identifier: Nullable<String>,
     access_route: String,
-    authority_identity: Option<String>,
-    authority_access_route: Option<String>,
-    version: Option<String>,
-    running: Option<bool>,
-    operation_id: Option<String>,
+    authority_identity: Nullable<String>,
+    authority_access_route: Nullable<String>,
+    version: Nullable<String>,
+    running: Nullable<Boolean>,
+    operation_id: Nullable<String>,
 }
 
 impl ProjectRow {
@@ -304,8 +306,8 @@ impl ProjectRow {
     ) -> Result<Project> {
         let identifier = self
             .identifier
-            .as_ref()
-            .map(|i| Identifier::from_string(i))
+            .to_option()
+            .map(|i| Identifier::from_string(&i))
             .transpose()?;
         Ok(Project {
             id: self.project_id.clone(),
@@ -314,11 +316,11 @@ impl ProjectRow {
             space_name: self.space_name.clone(),
             identity: identifier,
             access_route: self.access_route.clone(),
-            authority_access_route: self.authority_access_route.clone(),
-            authority_identity: self.authority_identity.clone(),
-            version: self.version.clone(),
-            running: self.running,
-            operation_id: self.operation_id.clone(),
+            authority_access_route: self.authority_access_route.to_option(),
+            authority_identity: self.authority_identity.to_option(),
+            version: self.version.to_option(),
+            running: self.running.to_option().map(|r| r.to_bool()),
+            operation_id: self.operation_id.to_option(),
             users: user_emails,
             user_roles,
             okta_config,
@@ -405,84 +407,91 @@ impl ConfluentConfigRow {
 mod test {
     use super::*;
     use crate::{SpacesRepository, SpacesSqlxDatabase};
+    use ockam::with_dbs;
 
     #[tokio::test]
     async fn test_repository() -> Result<()> {
-        let repository = create_repository().await?;
-
-        // create and store 2 projects
-        let project1 = create_project(
-            "1",
-            "name1",
-            vec!["me@ockam.io", "you@ockam.io"],
-            vec![
-                create_project_user_role(1, RoleInShare::Admin),
-                create_project_user_role(2, RoleInShare::Guest),
-            ],
-        );
-        let mut project2 = create_project(
-            "2",
-            "name2",
-            vec!["me@ockam.io", "him@ockam.io", "her@ockam.io"],
-            vec![
-                create_project_user_role(1, RoleInShare::Admin),
-                create_project_user_role(2, RoleInShare::Guest),
-            ],
-        );
-        repository.store_project(&project1).await?;
-        repository.store_project(&project2).await?;
-
-        // retrieve them as a list or by name
-        let result = repository.get_projects().await?;
-        assert_eq!(result, vec![project1.clone(), project2.clone()]);
-
-        let result = repository.get_project_by_name("name1").await?;
-        assert_eq!(result, Some(project1.clone()));
-
-        // a project can be marked as the default project
-        repository.set_default_project("1").await?;
-        let result = repository.get_default_project().await?;
-        assert_eq!(result, Some(project1.clone()));
-
-        repository.set_default_project("2").await?;
-        let result = repository.get_default_project().await?;
-        assert_eq!(result, Some(project2.clone()));
-
-        // updating a project which was already the default should keep it the default
-        project2.users = vec!["someone@ockam.io".into()];
-        repository.store_project(&project2).await?;
-        let result = repository.get_default_project().await?;
-        assert_eq!(result, Some(project2.clone()));
-
-        // a project can be deleted
-        repository.delete_project("2").await?;
-        let result = repository.get_default_project().await?;
-        assert_eq!(result, None);
-
-        let result = repository.get_projects().await?;
-        assert_eq!(result, vec![project1.clone()]);
-        Ok(())
+        with_dbs(|db| async move {
+            let repository = create_repository(db);
+
+            // create and store 2 projects
+            let project1 = create_project(
+                "1",
+                "name1",
+                vec!["me@ockam.io", "you@ockam.io"],
+                vec![
+                    create_project_user_role(1, RoleInShare::Admin),
+                    create_project_user_role(2, RoleInShare::Guest),
+                ],
+            );
+            let mut project2 = create_project(
+                "2",
+                "name2",
+                vec!["me@ockam.io", "him@ockam.io", "her@ockam.io"],
+                vec![
+                    create_project_user_role(1, RoleInShare::Admin),
+                    create_project_user_role(2, RoleInShare::Guest),
+                ],
+            );
+            repository.store_project(&project1).await?;
+            repository.store_project(&project2).await?;
+
+            // retrieve them as a list or by name
+            let result = repository.get_projects().await?;
+            assert_eq!(result, vec![project1.clone(), project2.clone()]);
+
+            let result = repository.get_project_by_name("name1").await?;
+            assert_eq!(result, Some(project1.clone()));
+
+            // a project can be marked as the default project
+            repository.set_default_project("1").await?;
+            let result = repository.get_default_project().await?;
+            assert_eq!(result, Some(project1.clone()));
+
+            repository.set_default_project("2").await?;
+            let result = repository.get_default_project().await?;
+            assert_eq!(result, Some(project2.clone()));
+
+            // updating a project which was already the default should keep it the default
+            project2.users = vec!["someone@ockam.io".into()];
+            repository.store_project(&project2).await?;
+            let result = repository.get_default_project().await?;
+            assert_eq!(result, Some(project2.clone()));
+
+            // a project can be deleted
+            repository.delete_project("2").await?;
+            let result = repository.get_default_project().await?;
+            assert_eq!(result, None);
+
+            let result = repository.get_projects().await?;
+            assert_eq!(result, vec![project1.clone()]);
+            Ok(())
+        })
+        .await
     }
 
     #[tokio::test]
     async fn test_store_project_space() -> Result<()> {
-        let db = SqlxDatabase::in_memory("projects").await?;
-        let projects_repository = ProjectsSqlxDatabase::new(db.clone());
-        let project = create_project("1", "name1", vec![], vec![]);
-        projects_repository.store_project(&project).await?;
-
-        // the space information coming from the project must also be stored in the spaces table
-        let spaces_repository: Arc<dyn SpacesRepository> = Arc::new(SpacesSqlxDatabase::new(db));
-        let space = spaces_repository.get_default_space().await?.unwrap();
-        assert_eq!(project.space_id, space.id);
-        assert_eq!(project.space_name, space.name);
-
-        Ok(())
+        with_dbs(|db| async move {
+            let projects_repository = ProjectsSqlxDatabase::new(db.clone());
+            let project = create_project("1", "name1", vec![], vec![]);
+            projects_repository.store_project(&project).await?;
+
+            // the space information coming from the project must also be stored in the spaces table
+            let spaces_repository: Arc<dyn SpacesRepository> =
+                Arc::new(SpacesSqlxDatabase::new(db));
+            let space = spaces_repository.get_default_space().await?.unwrap();
+            assert_eq!(project.space_id, space.id);
+            assert_eq!(project.space_name, space.name);
+
+            Ok(())
+        })
+        .await
     }
 
     /// HELPERS
-    async fn create_repository() -> Result<Arc<dyn ProjectsRepository>> {
-        Ok(ProjectsSqlxDatabase::create().await?)
+    fn create_repository(db: Arc<SqlxDatabase>) -> Arc<dyn ProjectsRepository> {
+        Arc::new(ProjectsSqlxDatabase::new(db))
     }
 
     fn create_project(
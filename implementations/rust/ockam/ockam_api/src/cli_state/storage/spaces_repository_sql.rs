@@ -1,11 +1,11 @@
 use std::sync::Arc;
 
-use sqlx::sqlite::SqliteRow;
+use sqlx::any::AnyRow;
 use sqlx::*;
 
 use ockam_core::async_trait;
 use ockam_core::Result;
-use ockam_node::database::{FromSqlxError, SqlxDatabase, ToSqlxType, ToVoid};
+use ockam_node::database::{Boolean, FromSqlxError, SqlxDatabase, ToSqlxType, ToVoid};
 
 use crate::cloud::space::Space;
 
@@ -37,12 +37,13 @@ impl SpacesRepository for SpacesSqlxDatabase {
         let mut transaction = self.database.begin().await.into_core()?;
 
         let query1 =
-            query_scalar("SELECT EXISTS (SELECT 1 FROM space WHERE is_default=$1 AND space_id=$2)")
+            query_scalar("SELECT CAST(EXISTS(SELECT 1 FROM space WHERE is_default=$1 AND space_id=$2) AS INTEGER)")
                 .bind(true.to_sql())
                 .bind(space.id.to_sql());
-        let is_already_default: bool = query1.fetch_one(&mut *transaction).await.into_core()?;
+        let is_already_default: Boolean = query1.fetch_one(&mut *transaction).await.into_core()?;
+        let is_already_default = is_already_default.to_bool();
 
-        let query2 = query("INSERT OR REPLACE INTO space VALUES (?, ?, ?)")
+        let query2 = query("INSERT INTO space VALUES ($1, $2, $3) ON CONFLICT (space_id) DO UPDATE SET space_name = $2, is_default = $3")
             .bind(space.id.to_sql())
             .bind(space.name.to_sql())
             .bind(is_already_default.to_sql());
@@ -54,7 +55,7 @@ impl SpacesRepository for SpacesSqlxDatabase {
 
         // store the users associated to that space
         for user_email in &space.users {
-            let query4 = query("INSERT OR REPLACE INTO user_space VALUES (?, ?)")
+            let query4 = query("INSERT INTO user_space VALUES ($1, $2)")
                 .bind(user_email.to_sql())
                 .bind(space.id.to_sql());
             query4.execute(&mut *transaction).await.void()?;
@@ -65,7 +66,7 @@ impl SpacesRepository for SpacesSqlxDatabase {
 
     async fn get_space(&self, space_id: &str) -> Result<Option<Space>> {
         let query = query("SELECT space_name FROM space WHERE space_id=$1").bind(space_id.to_sql());
-        let row: Option<SqliteRow> = query
+        let row: Option<AnyRow> = query
             .fetch_optional(&self.database.pool)
             .await
             .into_core()?;
@@ -123,7 +124,7 @@ impl SpacesRepository for SpacesSqlxDatabase {
 
     async fn get_default_space(&self) -> Result<Option<Space>> {
         let query = query("SELECT space_name FROM space WHERE is_default=$1").bind(true.to_sql());
-        let row: Option<SqliteRow> = query
+        let row: Option<AnyRow> = query
             .fetch_optional(&self.database.pool)
             .await
             .into_core()?;
@@ -137,13 +138,13 @@ impl SpacesRepository for SpacesSqlxDatabase {
     async fn set_default_space(&self, space_id: &str) -> Result<()> {
         let mut transaction = self.database.begin().await.into_core()?;
         // set the space as the default one
-        let query1 = query("UPDATE space SET is_default = ? WHERE space_id = ?")
+        let query1 = query("UPDATE space SET is_default = $1 WHERE space_id = $2")
             .bind(true.to_sql())
             .bind(space_id.to_sql());
         query1.execute(&mut *transaction).await.void()?;
 
         // set all the others as non-default
-        let query2 = query("UPDATE space SET is_default = ? WHERE space_id <> ?")
+        let query2 = query("UPDATE space SET is_default = $1 WHERE space_id <> $2")
             .bind(false.to_sql())
             .bind(space_id.to_sql());
         query2.execute(&mut *transaction).await.void()?;
@@ -153,10 +154,10 @@ impl SpacesRepository for SpacesSqlxDatabase {
     async fn delete_space(&self, space_id: &str) -> Result<()> {
         let mut transaction = self.database.begin().await.into_core()?;
 
-        let query1 = query("DELETE FROM space WHERE space_id=?").bind(space_id.to_sql());
+        let query1 = query("DELETE FROM space WHERE space_id=$1").bind(space_id.to_sql());
         query1.execute(&mut *transaction).await.void()?;
 
-        let query2 = query("DELETE FROM user_space WHERE space_id=?").bind(space_id.to_sql());
+        let query2 = query("DELETE FROM user_space WHERE space_id=$1").bind(space_id.to_sql());
         query2.execute(&mut *transaction).await.void()?;
 
         transaction.commit().await.void()
@@ -197,64 +198,68 @@ struct UserSpaceRow {
 #[cfg(test)]
 mod test {
     use super::*;
+    use ockam::with_dbs;
 
     #[tokio::test]
     async fn test_repository() -> Result<()> {
-        let repository = create_repository().await?;
-
-        // create and store 2 spaces
-        let space1 = Space {
-            id: "1".to_string(),
-            name: "name1".to_string(),
-            users: vec!["me@ockam.io".to_string(), "you@ockam.io".to_string()],
-        };
-        let mut space2 = Space {
-            id: "2".to_string(),
-            name: "name2".to_string(),
-            users: vec![
-                "me@ockam.io".to_string(),
-                "him@ockam.io".to_string(),
-                "her@ockam.io".to_string(),
-            ],
-        };
-
-        repository.store_space(&space1).await?;
-        repository.store_space(&space2).await?;
-
-        // retrieve them as a vector or by name
-        let result = repository.get_spaces().await?;
-        assert_eq!(result, vec![space1.clone(), space2.clone()]);
-
-        let result = repository.get_space_by_name("name1").await?;
-        assert_eq!(result, Some(space1.clone()));
-
-        // a space can be marked as the default space
-        repository.set_default_space("1").await?;
-        let result = repository.get_default_space().await?;
-        assert_eq!(result, Some(space1.clone()));
-
-        repository.set_default_space("2").await?;
-        let result = repository.get_default_space().await?;
-        assert_eq!(result, Some(space2.clone()));
-
-        // updating a space which was already the default should keep it the default
-        space2.users = vec!["someone@ockam.io".to_string()];
-        repository.store_space(&space2).await?;
-        let result = repository.get_default_space().await?;
-        assert_eq!(result, Some(space2.clone()));
-
-        // a space can be deleted
-        repository.delete_space("2").await?;
-        let result = repository.get_default_space().await?;
-        assert_eq!(result, None);
-
-        let result = repository.get_spaces().await?;
-        assert_eq!(result, vec![space1.clone()]);
-        Ok(())
+        with_dbs(|db| async move {
+            let repository = create_repository(db);
+
+            // create and store 2 spaces
+            let space1 = Space {
+                id: "1".to_string(),
+                name: "name1".to_string(),
+                users: vec!["me@ockam.io".to_string(), "you@ockam.io".to_string()],
+            };
+            let mut space2 = Space {
+                id: "2".to_string(),
+                name: "name2".to_string(),
+                users: vec![
+                    "me@ockam.io".to_string(),
+                    "him@ockam.io".to_string(),
+                    "her@ockam.io".to_string(),
+                ],
+            };
+
+            repository.store_space(&space1).await?;
+            repository.store_space(&space2).await?;
+
+            // retrieve them as a vector or by name
+            let result = repository.get_spaces().await?;
+            assert_eq!(result, vec![space1.clone(), space2.clone()]);
+
+            let result = repository.get_space_by_name("name1").await?;
+            assert_eq!(result, Some(space1.clone()));
+
+            // a space can be marked as the default space
+            repository.set_default_space("1").await?;
+            let result = repository.get_default_space().await?;
+            assert_eq!(result, Some(space1.clone()));
+
+            repository.set_default_space("2").await?;
+            let result = repository.get_default_space().await?;
+            assert_eq!(result, Some(space2.clone()));
+
+            // updating a space which was already the default should keep it the default
+            space2.users = vec!["someone@ockam.io".to_string()];
+            repository.store_space(&space2).await?;
+            let result = repository.get_default_space().await?;
+            assert_eq!(result, Some(space2.clone()));
+
+            // a space can be deleted
+            repository.delete_space("2").await?;
+            let result = repository.get_default_space().await?;
+            assert_eq!(result, None);
+
+            let result = repository.get_spaces().await?;
+            assert_eq!(result, vec![space1.clone()]);
+            Ok(())
+        })
+        .await
     }
 
     /// HELPERS
-    async fn create_repository() -> Result<Arc<dyn SpacesRepository>> {
-        Ok(SpacesSqlxDatabase::create().await?)
+    fn create_repository(db: Arc<SqlxDatabase>) -> Arc<dyn SpacesRepository> {
+        Arc::new(SpacesSqlxDatabase::new(db))
     }
 }
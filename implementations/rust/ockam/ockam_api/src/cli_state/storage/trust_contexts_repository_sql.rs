@@ -9,7 +9,7 @@ use ockam_core::async_trait;
 use ockam_core::env::FromString;
 use ockam_core::Result;
 use ockam_multiaddr::MultiAddr;
-use ockam_node::database::{FromSqlxError, SqlxDatabase, ToSqlxType, ToVoid};
+use ockam_node::database::{Boolean, FromSqlxError, Nullable, SqlxDatabase, ToSqlxType, ToVoid};
 use ockam_transport_tcp::TcpTransport;
 
 use crate::cli_state::storage::CredentialAndPurposeKeySql;
@@ -41,13 +41,14 @@ impl TrustContextsRepository for TrustContextsSqlxDatabase {
     async fn store_trust_context(&self, trust_context: &NamedTrustContext) -> Result<()> {
         let mut transaction = self.database.begin().await.into_core()?;
         let query1 = query_scalar(
-            "SELECT EXISTS(SELECT 1 FROM trust_context WHERE is_default=$1 AND name=$2)",
+            "SELECT CAST(EXISTS(SELECT 1 FROM trust_context WHERE is_default=$1 AND name=$2) AS INTEGER)",
         )
         .bind(true.to_sql())
         .bind(trust_context.name().to_sql());
-        let is_already_default: bool = query1.fetch_one(&mut *transaction).await.into_core()?;
+        let is_already_default: Boolean = query1.fetch_one(&mut *transaction).await.into_core()?;
+        let is_already_default = is_already_default.to_bool();
 
-        let query2 = query("INSERT OR REPLACE INTO trust_context VALUES ($1, $2, $3, $4, $5, $6)")
+        let query2 = query("INSERT INTO trust_context VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (name) DO UPDATE SET trust_context_id = $2, is_default = $3, credential = $4, authority_change_history = $5, authority_route = $6")
             .bind(trust_context.name().to_sql())
             .bind(trust_context.trust_context_id().to_sql())
             .bind(is_already_default.to_sql())
@@ -86,13 +87,13 @@ impl TrustContextsRepository for TrustContextsSqlxDatabase {
     async fn set_default_trust_context(&self, name: &str) -> Result<()> {
         let mut transaction = self.database.begin().await.into_core()?;
         // set the trust context as the default one
-        let query1 = query("UPDATE trust_context SET is_default = ? WHERE name = ?")
+        let query1 = query("UPDATE trust_context SET is_default = $1 WHERE name = $2")
             .bind(true.to_sql())
             .bind(name.to_sql());
         query1.execute(&mut *transaction).await.void()?;
 
         // set all the others as non-default
-        let query2 = query("UPDATE trust_context SET is_default = ? WHERE name <> ?")
+        let query2 = query("UPDATE trust_context SET is_default = $1 WHERE name <> $2")
             .bind(false.to_sql())
             .bind(name.to_sql());
         query2.execute(&mut *transaction).await.void()?;
@@ -116,7 +117,7 @@ impl TrustContextsRepository for TrustContextsSqlxDatabase {
     }
 
     async fn delete_trust_context(&self, name: &str) -> Result<()> {
-        let query1 = query("DELETE FROM trust_context WHERE name=?").bind(name.to_sql());
+        let query1 = query("DELETE FROM trust_context WHERE name=$1").bind(name.to_sql());
         query1.execute(&self.database.pool).await.void()
     }
 }
@@ -128,28 +129,28 @@ struct NamedTrustContextRow {
     name: String,
     trust_context_id: String,
     #[allow(unused)]
-    is_default: bool,
-    credential: Option<String>,
-    authority_change_history: Option<String>,
-    authority_route: Option<String>,
+    is_default: Boolean,
+    credential: Nullable<String>,
+    authority_change_history: Nullable<String>,
+    authority_route: Nullable<String>,
 }
 
 impl NamedTrustContextRow {
     fn named_trust_context(&self) -> Result<NamedTrustContext> {
         let credential: Option<CredentialAndPurposeKey> = self
             .credential
-            .as_ref()
-            .map(|c| CredentialAndPurposeKey::decode_from_string(c))
+            .to_option()
+            .map(|c| CredentialAndPurposeKey::decode_from_string(&c))
             .transpose()?;
         let authority_change_history = self
             .authority_change_history
-            .as_ref()
-            .map(|i| ChangeHistory::import_from_string(i))
+            .to_option()
+            .map(|i| ChangeHistory::import_from_string(&i))
             .transpose()?;
         let authority_route = self
             .authority_route
-            .as_ref()
-            .map(|r| MultiAddr::from_string(r))
+            .to_option()
+            .map(|r| MultiAddr::from_string(&r))
             .transpose()?;
         Ok(NamedTrustContext::new(
             &self.name,
@@ -176,6 +177,7 @@ impl NamedTrustContextRow {
 #[cfg(test)]
 mod test {
     use core::time::Duration;
+    use ockam::with_dbs;
 
     use ockam::identity::models::CredentialSchemaIdentifier;
     use ockam::identity::utils::AttributesBuilder;
@@ -185,51 +187,55 @@ mod test {
 
     #[tokio::test]
     async fn test_repository() -> Result<()> {
-        let repository = create_repository().await?;
-
-        // create 2 trust contexts
-        let identities = identities().await?;
-        let issuer_identifier = identities.identities_creation().create_identity().await?;
-        let issuer = identities.get_identity(&issuer_identifier).await?;
-
-        let trust_context1 =
-            create_trust_context("trust-context-1", identities.clone(), &issuer).await?;
-        let trust_context2 = create_trust_context("trust-context-2", identities, &issuer).await?;
-        repository.store_trust_context(&trust_context1).await?;
-        repository.store_trust_context(&trust_context2).await?;
-
-        // get a trust context by name
-        let result = repository.get_trust_context("trust-context-1").await?;
-        assert_eq!(result, Some(trust_context1.clone()));
-
-        // get all the trust contexts
-        let result = repository.get_trust_contexts().await?;
-        assert_eq!(result, vec![trust_context1.clone(), trust_context2.clone()]);
-
-        // set the first trust context as the default trust context
-        repository
-            .set_default_trust_context("trust-context-1")
-            .await?;
-        let result = repository.get_default_trust_context().await?;
-        assert_eq!(result, Some(trust_context1));
-
-        // then set the second one
-        repository
-            .set_default_trust_context("trust-context-2")
-            .await?;
-        let result = repository.get_default_trust_context().await?;
-        assert_eq!(result, Some(trust_context2.clone()));
-
-        // a trust context can be deleted
-        repository.delete_trust_context("trust-context-1").await?;
-        let result = repository.get_trust_contexts().await?;
-        assert_eq!(result, vec![trust_context2]);
-        Ok(())
+        with_dbs(|db| async move {
+            let repository = create_repository(db);
+
+            // create 2 trust contexts
+            let identities = identities().await?;
+            let issuer_identifier = identities.identities_creation().create_identity().await?;
+            let issuer = identities.get_identity(&issuer_identifier).await?;
+
+            let trust_context1 =
+                create_trust_context("trust-context-1", identities.clone(), &issuer).await?;
+            let trust_context2 =
+                create_trust_context("trust-context-2", identities, &issuer).await?;
+            repository.store_trust_context(&trust_context1).await?;
+            repository.store_trust_context(&trust_context2).await?;
+
+            // get a trust context by name
+            let result = repository.get_trust_context("trust-context-1").await?;
+            assert_eq!(result, Some(trust_context1.clone()));
+
+            // get all the trust contexts
+            let result = repository.get_trust_contexts().await?;
+            assert_eq!(result, vec![trust_context1.clone(), trust_context2.clone()]);
+
+            // set the first trust context as the default trust context
+            repository
+                .set_default_trust_context("trust-context-1")
+                .await?;
+            let result = repository.get_default_trust_context().await?;
+            assert_eq!(result, Some(trust_context1));
+
+            // then set the second one
+            repository
+                .set_default_trust_context("trust-context-2")
+                .await?;
+            let result = repository.get_default_trust_context().await?;
+            assert_eq!(result, Some(trust_context2.clone()));
+
+            // a trust context can be deleted
+            repository.delete_trust_context("trust-context-1").await?;
+            let result = repository.get_trust_contexts().await?;
+            assert_eq!(result, vec![trust_context2]);
+            Ok(())
+        })
+        .await
     }
 
     /// HELPERS
-    async fn create_repository() -> Result<Arc<dyn TrustContextsRepository>> {
-        Ok(TrustContextsSqlxDatabase::create().await?)
+    fn create_repository(db: Arc<SqlxDatabase>) -> Arc<dyn TrustContextsRepository> {
+        Arc::new(TrustContextsSqlxDatabase::new(db))
     }
 
     async fn create_trust_context(
@@ -1,11 +1,11 @@
 use std::sync::Arc;
 
-use sqlx::sqlite::SqliteRow;
+use sqlx::any::AnyRow;
 use sqlx::*;
 
 use ockam_core::async_trait;
 use ockam_core::Result;
-use ockam_node::database::{FromSqlxError, SqlxDatabase, ToSqlxType, ToVoid};
+use ockam_node::database::{Boolean, FromSqlxError, SqlxDatabase, ToSqlxType, ToVoid};
 
 use crate::cloud::enroll::auth0::UserInfo;
 
@@ -34,13 +34,15 @@ impl UsersRepository for UsersSqlxDatabase {
     async fn store_user(&self, user: &UserInfo) -> Result<()> {
         let mut transaction = self.database.begin().await.into_core()?;
 
-        let query1 =
-            query_scalar("SELECT EXISTS(SELECT email FROM user WHERE is_default=$1 AND email=$2)")
-                .bind(true.to_sql())
-                .bind(user.email.to_sql());
-        let is_already_default: bool = query1.fetch_one(&mut *transaction).await.into_core()?;
+        let query1 = query_scalar(
+            "SELECT CAST(EXISTS(SELECT email FROM \"user\" WHERE is_default=$1 AND email=$2) AS INTEGER)",
+        )
+        .bind(true.to_sql())
+        .bind(user.email.to_sql());
+        let is_already_default: Boolean = query1.fetch_one(&mut *transaction).await.into_core()?;
+        let is_already_default = is_already_default.to_bool();
 
-        let query2 = query("INSERT OR REPLACE INTO user VALUES ($1, $2, $3, $4, $5, $6, $7, $8)")
+        let query2 = query("INSERT INTO \"user\" VALUES ($1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT (email) DO UPDATE SET sub = $2, nickname = $3, name = $4, picture = $5, updated_at = $6, email_verified = $7, is_default = $8")
             .bind(user.email.to_sql())
             .bind(user.sub.to_sql())
             .bind(user.nickname.to_sql())
@@ -55,8 +57,8 @@ impl UsersRepository for UsersSqlxDatabase {
     }
 
     async fn get_default_user(&self) -> Result<Option<UserInfo>> {
-        let query = query("SELECT email FROM user WHERE is_default=$1").bind(true.to_sql());
-        let row: Option<SqliteRow> = query
+        let query = query("SELECT email FROM \"user\" WHERE is_default=$1").bind(true.to_sql());
+        let row: Option<AnyRow> = query
             .fetch_optional(&self.database.pool)
             .await
             .into_core()?;
@@ -68,14 +70,14 @@ impl UsersRepository for UsersSqlxDatabase {
     }
 
     async fn set_default_user(&self, email: &str) -> Result<()> {
-        let query = query("UPDATE user SET is_default = ? WHERE email = ?")
+        let query = query("UPDATE \"user\" SET is_default = $1 WHERE email = $2")
             .bind(true.to_sql())
             .bind(email.to_sql());
         query.execute(&self.database.pool).await.void()
     }
 
     async fn get_user(&self, email: &str) -> Result<Option<UserInfo>> {
-        let query = query_as("SELECT email, sub, nickname, name, picture, updated_at, email_verified, is_default FROM user WHERE email=$1").bind(email.to_sql());
+        let query = query_as("SELECT email, sub, nickname, name, picture, updated_at, email_verified, is_default FROM \"user\" WHERE email=$1").bind(email.to_sql());
         let row: Option<UserRow> = query
             .fetch_optional(&self.database.pool)
             .await
@@ -84,13 +86,13 @@ impl UsersRepository for UsersSqlxDatabase {
     }
 
     async fn get_users(&self) -> Result<Vec<UserInfo>> {
-        let query = query_as("SELECT email, sub, nickname, name, picture, updated_at, email_verified, is_default FROM user");
+        let query = query_as("SELECT email, sub, nickname, name, picture, updated_at, email_verified, is_default FROM \"user\"");
         let rows: Vec<UserRow> = query.fetch_all(&self.database.pool).await.into_core()?;
         Ok(rows.iter().map(|u| u.user()).collect())
     }
 
     async fn delete_user(&self, email: &str) -> Result<()> {
-        let query1 = query("DELETE FROM user WHERE email=?").bind(email.to_sql());
+        let query1 = query("DELETE FROM \"user\" WHERE email=$1").bind(email.to_sql());
         query1.execute(&self.database.pool).await.void()
     }
 }
@@ -106,9 +108,9 @@ struct UserRow {
     name: String,
     picture: String,
     updated_at: String,
-    email_verified: bool,
+    email_verified: Boolean,
     #[allow(unused)]
-    is_default: bool,
+    is_default: Boolean,
 }
 
 impl UserRow {
@@ -120,7 +122,7 @@ impl UserRow {
             name: self.name.clone(),
             picture: self.picture.clone(),
             updated_at: self.updated_at.clone(),
-            email_verified: self.email_verified,
+            email_verified: self.email_verified.to_bool(),
         }
     }
 }
@@ -128,58 +130,62 @@ impl UserRow {
 #[cfg(test)]
 mod test {
     use super::*;
+    use ockam::with_dbs;
 
     #[tokio::test]
     async fn test_repository() -> Result<()> {
-        let repository = create_repository().await?;
-
-        // create and store 2 users
-        let user1 = UserInfo {
-            sub: "sub".into(),
-            nickname: "me".to_string(),
-            name: "me".to_string(),
-            picture: "me".to_string(),
-            updated_at: "today".to_string(),
-            email: "me@ockam.io".into(),
-            email_verified: false,
-        };
-        let user2 = UserInfo {
-            sub: "sub".into(),
-            nickname: "you".to_string(),
-            name: "you".to_string(),
-            picture: "you".to_string(),
-            updated_at: "today".to_string(),
-            email: "you@ockam.io".into(),
-            email_verified: false,
-        };
-
-        repository.store_user(&user1).await?;
-        repository.store_user(&user2).await?;
-
-        // retrieve them as a vector or by name
-        let result = repository.get_users().await?;
-        assert_eq!(result, vec![user1.clone(), user2.clone()]);
-
-        let result = repository.get_user("me@ockam.io").await?;
-        assert_eq!(result, Some(user1.clone()));
-
-        // a user can be set created as the default user
-        repository.set_default_user("me@ockam.io").await?;
-        let result = repository.get_default_user().await?;
-        assert_eq!(result, Some(user1.clone()));
-
-        // a user can be deleted
-        repository.delete_user("you@ockam.io").await?;
-        let result = repository.get_user("you@ockam.io").await?;
-        assert_eq!(result, None);
-
-        let result = repository.get_users().await?;
-        assert_eq!(result, vec![user1.clone()]);
-        Ok(())
+        with_dbs(|db| async move {
+            let repository = create_repository(db);
+
+            // create and store 2 users
+            let user1 = UserInfo {
+                sub: "sub".into(),
+                nickname: "me".to_string(),
+                name: "me".to_string(),
+                picture: "me".to_string(),
+                updated_at: "today".to_string(),
+                email: "me@ockam.io".into(),
+                email_verified: false,
+            };
+            let user2 = UserInfo {
+                sub: "sub".into(),
+                nickname: "you".to_string(),
+                name: "you".to_string(),
+                picture: "you".to_string(),
+                updated_at: "today".to_string(),
+                email: "you@ockam.io".into(),
+                email_verified: false,
+            };
+
+            repository.store_user(&user1).await?;
+            repository.store_user(&user2).await?;
+
+            // retrieve them as a vector or by name
+            let result = repository.get_users().await?;
+            assert_eq!(result, vec![user1.clone(), user2.clone()]);
+
+            let result = repository.get_user("me@ockam.io").await?;
+            assert_eq!(result, Some(user1.clone()));
+
+            // a user can be set created as the default user
+            repository.set_default_user("me@ockam.io").await?;
+            let result = repository.get_default_user().await?;
+            assert_eq!(result, Some(user1.clone()));
+
+            // a user can be deleted
+            repository.delete_user("you@ockam.io").await?;
+            let result = repository.get_user("you@ockam.io").await?;
+            assert_eq!(result, None);
+
+            let result = repository.get_users().await?;
+            assert_eq!(result, vec![user1.clone()]);
+            Ok(())
+        })
+        .await
     }
 
     /// HELPERS
-    async fn create_repository() -> Result<Arc<dyn UsersRepository>> {
-        Ok(UsersSqlxDatabase::create().await?)
+    fn create_repository(db: Arc<SqlxDatabase>) -> Arc<dyn UsersRepository> {
+        Arc::new(UsersSqlxDatabase::new(db))
     }
 }
@@ -3,7 +3,7 @@ use std::path::PathBuf;
 use ockam_core::async_trait;
 use ockam_core::Result;
 
-use crate::NamedVault;
+use crate::cli_state::{NamedVault, Pkcs11Token};
 
 /// This trait allows vaults to be defined with a name and a path
 /// in order to make it possible to store identity keys in different databases on disk (or in a KMS)
@@ -12,6 +12,14 @@ pub trait VaultsRepository: Send + Sync + 'static {
     /// Store a new vault path with an associated name
     async fn store_vault(&self, name: &str, path: PathBuf, is_kms: bool) -> Result<NamedVault>;
 
+    /// Store a new KMS vault using a PKCS#11 token, with an associated name
+    async fn store_pkcs11_vault(
+        &self,
+        name: &str,
+        path: PathBuf,
+        pkcs11_token: Pkcs11Token,
+    ) -> Result<NamedVault>;
+
     /// Delete a vault given its name
     async fn delete_vault(&self, name: &str) -> Result<()>;
 
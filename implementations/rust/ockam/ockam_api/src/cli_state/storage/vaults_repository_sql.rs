@@ -4,8 +4,8 @@ use std::sync::Arc;
 
 use sqlx::*;
 
-use crate::cli_state::{NamedVault, VaultsRepository};
-use ockam::{FromSqlxError, SqlxDatabase, ToSqlxType, ToVoid};
+use crate::cli_state::{NamedVault, Pkcs11Token, VaultsRepository};
+use ockam::{Boolean, FromSqlxError, Nullable, SqlxDatabase, ToSqlxType, ToVoid};
 use ockam_core::async_trait;
 use ockam_core::Result;
 
@@ -27,31 +27,52 @@ impl VaultsSqlxDatabase {
     }
 }
 
-#[async_trait]
-impl VaultsRepository for VaultsSqlxDatabase {
-    async fn store_vault(&self, name: &str, path: PathBuf, is_kms: bool) -> Result<NamedVault> {
+impl VaultsSqlxDatabase {
+    /// Store a vault, keeping its default status if it already exists
+    async fn store(&self, vault: NamedVault) -> Result<NamedVault> {
         let mut transaction = self.database.begin().await.into_core()?;
 
-        let query1 =
-            query_scalar("SELECT EXISTS(SELECT 1 FROM vault WHERE is_default=$1 AND name=$2)")
-                .bind(true.to_sql())
-                .bind(name.to_sql());
-        let is_already_default: bool = query1.fetch_one(&mut *transaction).await.into_core()?;
-
-        let query2 = query("INSERT OR REPLACE INTO vault VALUES (?1, ?2, ?3, ?4)")
-            .bind(name.to_sql())
-            .bind(path.to_sql())
+        let query1 = query_scalar(
+            "SELECT CAST(EXISTS(SELECT 1 FROM vault WHERE is_default=$1 AND name=$2) AS INTEGER)",
+        )
+        .bind(true.to_sql())
+        .bind(vault.name().to_sql());
+        let is_already_default: Boolean = query1.fetch_one(&mut *transaction).await.into_core()?;
+        let is_already_default = is_already_default.to_bool();
+
+        let pkcs11_token = vault.pkcs11_token();
+        let query2 = query("INSERT INTO vault (name, path, is_default, is_kms, pkcs11_module, pkcs11_slot) VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (name) DO UPDATE SET path = $2, is_default = $3, is_kms = $4, pkcs11_module = $5, pkcs11_slot = $6")
+            .bind(vault.name().to_sql())
+            .bind(vault.path().to_sql())
             .bind(is_already_default.to_sql())
-            .bind(is_kms.to_sql());
+            .bind(vault.is_kms().to_sql())
+            .bind(pkcs11_token.map(|t| t.module().to_sql()))
+            .bind(pkcs11_token.map(|t| t.slot().to_sql()));
         query2.execute(&mut *transaction).await.void()?;
         transaction.commit().await.void()?;
 
-        Ok(NamedVault::new(
-            name,
-            path.clone(),
-            is_already_default,
-            is_kms,
-        ))
+        Ok(if is_already_default {
+            vault.set_as_default()
+        } else {
+            vault
+        })
+    }
+}
+
+#[async_trait]
+impl VaultsRepository for VaultsSqlxDatabase {
+    async fn store_vault(&self, name: &str, path: PathBuf, is_kms: bool) -> Result<NamedVault> {
+        self.store(NamedVault::new(name, path, false, is_kms)).await
+    }
+
+    async fn store_pkcs11_vault(
+        &self,
+        name: &str,
+        path: PathBuf,
+        pkcs11_token: Pkcs11Token,
+    ) -> Result<NamedVault> {
+        self.store(NamedVault::new(name, path, false, true).with_pkcs11_token(pkcs11_token))
+            .await
     }
 
     /// Delete a vault by name
@@ -59,7 +80,7 @@ impl VaultsRepository for VaultsSqlxDatabase {
         let mut transaction = self.database.begin().await.into_core()?;
 
         // get the named vault
-        let query1 = query_as("SELECT name, path, is_default, is_kms FROM vault WHERE name=$1")
+        let query1 = query_as("SELECT name, path, is_default, is_kms, pkcs11_module, pkcs11_slot FROM vault WHERE name=$1")
             .bind(name.to_sql());
         let row: Option<VaultRow> = query1.fetch_optional(&mut *transaction).await.into_core()?;
         let named_vault = row.map(|r| r.named_vault()).transpose()?;
@@ -70,7 +91,7 @@ impl VaultsRepository for VaultsSqlxDatabase {
 
             // otherwise delete it and set another vault as the default
             Some(named_vault) => {
-                let query2 = query("DELETE FROM vault WHERE name=?").bind(name.to_sql());
+                let query2 = query("DELETE FROM vault WHERE name=$1").bind(name.to_sql());
                 query2.execute(&mut *transaction).await.void()?;
 
                 // if the deleted vault was the default one, select another vault to be the default one
@@ -80,7 +101,7 @@ impl VaultsRepository for VaultsSqlxDatabase {
                         .await
                         .into_core()?
                     {
-                        let query3 = query("UPDATE vault SET is_default = ? WHERE name = ?")
+                        let query3 = query("UPDATE vault SET is_default = $1 WHERE name = $2")
                             .bind(true.to_sql())
                             .bind(other_name.to_sql());
                         query3.execute(&mut *transaction).await.void()?
@@ -94,13 +115,13 @@ impl VaultsRepository for VaultsSqlxDatabase {
     async fn set_as_default(&self, name: &str) -> Result<()> {
         let mut transaction = self.database.begin().await.into_core()?;
         // set the identifier as the default one
-        let query1 = query("UPDATE vault SET is_default = ? WHERE name = ?")
+        let query1 = query("UPDATE vault SET is_default = $1 WHERE name = $2")
             .bind(true.to_sql())
             .bind(name.to_sql());
         query1.execute(&mut *transaction).await.void()?;
 
         // set all the others as non-default
-        let query2 = query("UPDATE vault SET is_default = ? WHERE name <> ?")
+        let query2 = query("UPDATE vault SET is_default = $1 WHERE name <> $2")
             .bind(false.to_sql())
             .bind(name.to_sql());
         query2.execute(&mut *transaction).await.void()?;
@@ -108,7 +129,7 @@ impl VaultsRepository for VaultsSqlxDatabase {
     }
 
     async fn is_default(&self, name: &str) -> Result<bool> {
-        let query = query_as("SELECT name, path, is_default, is_kms FROM vault WHERE name = $1")
+        let query = query_as("SELECT name, path, is_default, is_kms, pkcs11_module, pkcs11_slot FROM vault WHERE name = $1")
             .bind(name.to_sql());
         let row: Option<VaultRow> = query
             .fetch_optional(&self.database.pool)
@@ -118,13 +139,15 @@ impl VaultsRepository for VaultsSqlxDatabase {
     }
 
     async fn get_named_vaults(&self) -> Result<Vec<NamedVault>> {
-        let query = query_as("SELECT name, path, is_default, is_kms FROM vault");
+        let query = query_as(
+            "SELECT name, path, is_default, is_kms, pkcs11_module, pkcs11_slot FROM vault",
+        );
         let rows: Vec<VaultRow> = query.fetch_all(&self.database.pool).await.into_core()?;
         rows.iter().map(|r| r.named_vault()).collect()
     }
 
     async fn get_named_vault(&self, name: &str) -> Result<Option<NamedVault>> {
-        let query = query_as("SELECT name, path, is_default, is_kms FROM vault WHERE name = $1")
+        let query = query_as("SELECT name, path, is_default, is_kms, pkcs11_module, pkcs11_slot FROM vault WHERE name = $1")
             .bind(name.to_sql());
         let row: Option<VaultRow> = query
             .fetch_optional(&self.database.pool)
@@ -135,7 +158,7 @@ impl VaultsRepository for VaultsSqlxDatabase {
 
     async fn get_default_vault(&self) -> Result<Option<NamedVault>> {
         let query =
-            query_as("SELECT name, path, is_default, is_kms FROM vault WHERE is_default = $1")
+            query_as("SELECT name, path, is_default, is_kms, pkcs11_module, pkcs11_slot FROM vault WHERE is_default = $1")
                 .bind(true.to_sql());
         let row: Option<VaultRow> = query
             .fetch_optional(&self.database.pool)
@@ -151,72 +174,100 @@ impl VaultsRepository for VaultsSqlxDatabase {
 pub(crate) struct VaultRow {
     name: String,
     path: String,
-    is_default: bool,
-    is_kms: bool,
+    is_default: Boolean,
+    is_kms: Boolean,
+    pkcs11_module: Nullable<String>,
+    pkcs11_slot: Nullable<i64>,
 }
 
 impl VaultRow {
     pub(crate) fn named_vault(&self) -> Result<NamedVault> {
-        Ok(NamedVault::new(
+        let named_vault = NamedVault::new(
             &self.name,
             PathBuf::from_str(self.path.as_str()).unwrap(),
-            self.is_default,
-            self.is_kms,
-        ))
+            self.is_default.to_bool(),
+            self.is_kms.to_bool(),
+        );
+        Ok(
+            match (self.pkcs11_module.to_option(), self.pkcs11_slot.to_option()) {
+                (Some(module), Some(slot)) => named_vault
+                    .with_pkcs11_token(Pkcs11Token::new(PathBuf::from(module), slot as u64)),
+                _ => named_vault,
+            },
+        )
     }
 
     pub(crate) fn is_default(&self) -> bool {
-        self.is_default
+        self.is_default.to_bool()
     }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use ockam::with_dbs;
 
     #[tokio::test]
     async fn test_repository() -> Result<()> {
-        let repository = create_repository().await?;
-
-        // A vault can be defined with a path and stored under a specific name
-        let named_vault1 = repository
-            .store_vault("vault1", "path".into(), false)
-            .await?;
-        let expected = NamedVault::new("vault1", "path".into(), false, false);
-        assert_eq!(named_vault1, expected);
-
-        // The vault can then be retrieved with its name
-        let result = repository.get_named_vault("vault1").await?;
-        assert_eq!(result, Some(named_vault1.clone()));
-
-        // A default vault can be set
-        repository.set_as_default("vault1").await?;
-        let result = repository.get_default_vault().await?;
-        assert_eq!(result, Some(named_vault1.set_as_default()));
-
-        let named_vault2 = repository
-            .store_vault("vault2", "path2".into(), false)
-            .await?;
-        repository.set_as_default("vault2").await?;
-        let result = repository.get_default_vault().await?;
-        assert_eq!(result, Some(named_vault2.set_as_default()));
-
-        Ok(())
+        with_dbs(|db| async move {
+            let repository = create_repository(db);
+
+            // A vault can be defined with a path and stored under a specific name
+            let named_vault1 = repository
+                .store_vault("vault1", "path".into(), false)
+                .await?;
+            let expected = NamedVault::new("vault1", "path".into(), false, false);
+            assert_eq!(named_vault1, expected);
+
+            // The vault can then be retrieved with its name
+            let result = repository.get_named_vault("vault1").await?;
+            assert_eq!(result, Some(named_vault1.clone()));
+
+            // A default vault can be set
+            repository.set_as_default("vault1").await?;
+            let result = repository.get_default_vault().await?;
+            assert_eq!(result, Some(named_vault1.set_as_default()));
+
+            let named_vault2 = repository
+                .store_vault("vault2", "path2".into(), false)
+                .await?;
+            repository.set_as_default("vault2").await?;
+            let result = repository.get_default_vault().await?;
+            assert_eq!(result, Some(named_vault2.set_as_default()));
+
+            Ok(())
+        })
+        .await
     }
 
     #[tokio::test]
     async fn test_store_kms_vault() -> Result<()> {
-        let repository = create_repository().await?;
-
-        // A KMS vault can be created by setting the kms flag to true
-        let kms = repository.store_vault("kms", "path".into(), true).await?;
-        let expected = NamedVault::new("kms", "path".into(), false, true);
-        assert_eq!(kms, expected);
-        Ok(())
+        with_dbs(|db| async move {
+            let repository = create_repository(db);
+
+            // A KMS vault can be created by setting the kms flag to true
+            let kms = repository.store_vault("kms", "path".into(), true).await?;
+            let expected = NamedVault::new("kms", "path".into(), false, true);
+            assert_eq!(kms, expected);
+
+            // A KMS vault can use a PKCS#11 token
+            let pkcs11_token = Pkcs11Token::new("/usr/lib/softhsm/libsofthsm2.so".into(), 42);
+            let pkcs11 = repository
+                .store_pkcs11_vault("pkcs11", "path2".into(), pkcs11_token.clone())
+                .await?;
+            let expected = NamedVault::new("pkcs11", "path2".into(), false, true)
+                .with_pkcs11_token(pkcs11_token);
+            assert_eq!(pkcs11, expected);
+
+            let result = repository.get_named_vault("pkcs11").await?;
+            assert_eq!(result, Some(expected));
+            Ok(())
+        })
+        .await
     }
 
     /// HELPERS
-    async fn create_repository() -> Result<Arc<dyn VaultsRepository>> {
-        Ok(VaultsSqlxDatabase::create().await?)
+    fn create_repository(db: Arc<SqlxDatabase>) -> Arc<dyn VaultsRepository> {
+        Arc::new(VaultsSqlxDatabase::new(db))
     }
 }
@@ -3,11 +3,13 @@ use std::sync::Arc;
 
 use ockam::identity::models::{ChangeHistory, CredentialAndPurposeKey};
 use ockam::identity::{
-    AuthorityService, CredentialsMemoryRetriever, Identifier, Identity, RemoteCredentialsRetriever,
-    RemoteCredentialsRetrieverInfo, SecureChannels, TrustContext,
+    AuthorityService, CredentialsMemoryRetriever, Identifier, Identity, IdentityKeyRotationPolicy,
+    IdentityKeyRotator, RemoteCredentialsRetriever, RemoteCredentialsRetrieverInfo,
+    RevocationListRefresher, SecureChannels, TrustContext,
+    DEFAULT_REVOCATION_LIST_REFRESH_INTERVAL,
 };
 use ockam_core::errcode::{Kind, Origin};
-use ockam_core::Error;
+use ockam_core::{Error, Route};
 use ockam_multiaddr::MultiAddr;
 use ockam_transport_tcp::TcpTransport;
 
@@ -315,6 +317,70 @@ impl NamedTrustContext {
         ))
     }
 
+    /// Make a processor periodically retrieving the revocation list of the trust context authority.
+    /// This is only possible if the authority identity and route are configured
+    pub async fn revocation_list_refresher(
+        &self,
+        tcp_transport: &TcpTransport,
+        secure_channels: Arc<SecureChannels>,
+        caller: &Identifier,
+    ) -> Result<Option<RevocationListRefresher>> {
+        let (authority, route) = match self.authority_identifier_and_route(tcp_transport).await? {
+            Some(authority) => authority,
+            None => return Ok(None),
+        };
+        Ok(Some(RevocationListRefresher::new(
+            secure_channels,
+            caller.clone(),
+            authority,
+            route,
+            DefaultAddress::REVOCATIONS_SERVICE,
+            DEFAULT_REVOCATION_LIST_REFRESH_INTERVAL,
+        )))
+    }
+
+    /// Make a processor rotating the key of an identity according to a rotation policy.
+    /// The new change history of the identity is published to the trust context authority
+    /// if the authority identity and route are configured
+    pub async fn identity_key_rotator(
+        &self,
+        tcp_transport: &TcpTransport,
+        secure_channels: Arc<SecureChannels>,
+        identifier: &Identifier,
+        policy: IdentityKeyRotationPolicy,
+    ) -> Result<IdentityKeyRotator> {
+        let rotator = IdentityKeyRotator::new(secure_channels, identifier.clone(), policy);
+        Ok(
+            match self.authority_identifier_and_route(tcp_transport).await? {
+                Some((authority, route)) => rotator.with_authority(authority, route),
+                None => rotator,
+            },
+        )
+    }
+
+    /// Return the identifier of the authority and a route to it, if they are configured
+    async fn authority_identifier_and_route(
+        &self,
+        tcp_transport: &TcpTransport,
+    ) -> Result<Option<(Identifier, Route)>> {
+        let (authority, route) = match (self.authority_identifier().await?, self.authority_route())
+        {
+            (Some(authority), Some(route)) => (authority, route),
+            _ => return Ok(None),
+        };
+        let route = multiaddr_to_route(&route, tcp_transport)
+            .await
+            .ok_or_else(|| {
+                Error::new(
+                    Origin::Api,
+                    Kind::Internal,
+                    format!("cannot create a route from the address {route}"),
+                )
+            })?
+            .route;
+        Ok(Some((authority, route)))
+    }
+
     /// Return access data for an authority in order to be able to create
     /// a RPC client to that authority and obtain credentials
     pub async fn authority(&self) -> Result<Option<Authority>> {
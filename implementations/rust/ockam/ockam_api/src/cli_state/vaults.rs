@@ -3,17 +3,31 @@ use std::path::PathBuf;
 use std::sync::Arc;
 
 use ockam::identity::{Identities, Vault};
+use ockam_core::env::get_env;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_node::database::SqlxDatabase;
+use ockam_vault::storage::{SecretsSqlxDatabase, WrappingKeySource};
 use ockam_vault_aws::AwsSigningVault;
+use ockam_vault_pkcs11::{Pkcs11Config, Pkcs11SigningVault};
 
-use crate::cli_state::{random_name, CliState, Result};
+use crate::cli_state::{random_name, CliState, CliStateError, Result};
+
+/// Name of the environment variable containing the passphrase used to encrypt the local vault secrets
+pub const OCKAM_VAULT_PASSPHRASE: &str = "OCKAM_VAULT_PASSPHRASE";
+
+/// Name of the environment variable containing the new passphrase of a vault, when its passphrase is rotated
+pub const OCKAM_VAULT_NEW_PASSPHRASE: &str = "OCKAM_VAULT_NEW_PASSPHRASE";
+
+/// Name of the environment variable containing the user PIN of the PKCS#11 tokens
+pub const OCKAM_PKCS11_PIN: &str = "OCKAM_PKCS11_PIN";
 
 /// The methods below support the creation and update of local vaults
 ///
 ///  - by default private keys are stored locally but they can also be stored in a KMS
+///  - a KMS is either an AWS KMS or a token (an HSM for example) accessed via a PKCS#11 module
 ///  - keys stored locally are stored with other application data in the local database if the default vault is used
 ///  - any additional vault stores its keys in a separate file
+///  - local keys are encrypted with a key derived from the OCKAM_VAULT_PASSPHRASE environment variable when it is set
 ///
 impl CliState {
     /// Create a vault with a given name
@@ -31,6 +45,21 @@ impl CliState {
         self.create_a_vault(vault_name, true).await
     }
 
+    /// Create a KMS vault with a given name, storing its keys in a token accessed via a PKCS#11 module.
+    /// The token user PIN is read from the OCKAM_PKCS11_PIN environment variable when the vault is used.
+    pub async fn create_pkcs11_vault(
+        &self,
+        vault_name: &str,
+        pkcs11_token: Pkcs11Token,
+    ) -> Result<NamedVault> {
+        let path = self.vault_path(vault_name).await?;
+        let vaults_repository = self.vaults_repository().await?;
+        let vault = vaults_repository
+            .store_pkcs11_vault(vault_name, path, pkcs11_token)
+            .await?;
+        self.set_as_default_if_first(vault).await
+    }
+
     /// Select a different vault to be the default vault
     pub async fn set_default_vault(&self, vault_name: &str) -> Result<()> {
         Ok(self
@@ -116,6 +145,32 @@ impl CliState {
 
 /// Builder functions
 impl CliState {
+    /// Return a Vault accessing the keys of a named vault
+    pub async fn make_vault(&self, named_vault: &NamedVault) -> Result<Vault> {
+        if let Some(pkcs11_token) = &named_vault.pkcs11_token {
+            let mut config = Pkcs11Config::new(pkcs11_token.module(), pkcs11_token.slot());
+            if let Some(pin) = get_env::<String>(OCKAM_PKCS11_PIN)? {
+                config = config.with_pin(pin);
+            }
+            let mut vault = Vault::create().await?;
+            let pkcs11_vault = Arc::new(Pkcs11SigningVault::create(config).await?);
+            vault.identity_vault = pkcs11_vault.clone();
+            vault.credential_vault = pkcs11_vault;
+            Ok(vault)
+        } else if named_vault.is_kms {
+            let mut vault = Vault::create().await?;
+            let aws_vault = Arc::new(AwsSigningVault::create().await?);
+            vault.identity_vault = aws_vault.clone();
+            vault.credential_vault = aws_vault;
+            Ok(vault)
+        } else {
+            let repository = self
+                .secrets_repository(named_vault, get_env::<String>(OCKAM_VAULT_PASSPHRASE)?)
+                .await?;
+            Ok(Vault::create_with_secrets_repository(Arc::new(repository)))
+        }
+    }
+
     /// Return an Identities struct using a specific Vault
     pub async fn make_identities(&self, vault: Vault) -> Result<Arc<Identities>> {
         Ok(Identities::builder()
@@ -128,34 +183,103 @@ impl CliState {
     }
 }
 
+/// Passphrase management
+impl CliState {
+    /// Encrypt the secrets of a vault with a key derived from a new passphrase.
+    /// If the secrets are already encrypted, `current_passphrase` must be their current passphrase
+    pub async fn rotate_vault_passphrase(
+        &self,
+        named_vault: &NamedVault,
+        current_passphrase: Option<String>,
+        new_passphrase: String,
+    ) -> Result<()> {
+        if named_vault.pkcs11_token.is_some() || named_vault.is_kms {
+            return Err(CliStateError::InvalidOperation(format!(
+                "The keys of the vault {} are stored in a KMS, they are not protected by a passphrase",
+                named_vault.name
+            )));
+        }
+        self.secrets_repository(named_vault, current_passphrase)
+            .await?
+            .rotate_wrapping_key(WrappingKeySource::passphrase(new_passphrase))
+            .await?;
+        Ok(())
+    }
+}
+
 /// Private functions
 impl CliState {
+    /// Return the repository storing the local secrets of a vault
+    async fn secrets_repository(
+        &self,
+        named_vault: &NamedVault,
+        passphrase: Option<String>,
+    ) -> Result<SecretsSqlxDatabase> {
+        let database = self.vault_database(named_vault).await?;
+        Ok(match passphrase {
+            Some(passphrase) => {
+                SecretsSqlxDatabase::open_with_wrapping_key(
+                    database,
+                    WrappingKeySource::passphrase(passphrase),
+                )
+                .await?
+            }
+            None => SecretsSqlxDatabase::new(database),
+        })
+    }
+
+    /// Return the database storing the local secrets of a vault:
+    /// the main database for the default vault, a separate SQLite file otherwise
+    async fn vault_database(&self, named_vault: &NamedVault) -> Result<Arc<SqlxDatabase>> {
+        if named_vault.path == self.database_path() {
+            Ok(self.database())
+        } else {
+            Ok(Arc::new(
+                SqlxDatabase::create(named_vault.path.as_path()).await?,
+            ))
+        }
+    }
+
     /// Create a vault with the given name and indicate if it is going to be used as a KMS vault
-    /// The vault path is either
-    /// - the database path if this is the first created vault (it is set as the default vault)
-    /// - a file next to the database file, named 'vault_name'
     async fn create_a_vault(&self, vault_name: &str, is_kms: bool) -> Result<NamedVault> {
-        let vaults_repository = self.vaults_repository().await?;
+        let path = self.vault_path(vault_name).await?;
+        let vault = self
+            .vaults_repository()
+            .await?
+            .store_vault(vault_name, path, is_kms)
+            .await?;
+        self.set_as_default_if_first(vault).await
+    }
 
-        // the first created vault is the default one
-        let is_default_vault = vaults_repository.get_default_vault().await?.is_none();
+    /// Return the path of a new vault:
+    /// - the database path if this is the first created vault (it is set as the default vault)
+    /// - a file next to the database file, named 'vault_name'
+    async fn vault_path(&self, vault_name: &str) -> Result<PathBuf> {
+        let is_default_vault = self
+            .vaults_repository()
+            .await?
+            .get_default_vault()
+            .await?
+            .is_none();
 
         // if the vault is the default vault we store the data directly in the main database
         // otherwise we open a new file with the vault name
-        let path = if is_default_vault {
+        Ok(if is_default_vault {
             self.database_path()
         } else {
             self.dir().join(vault_name)
-        };
+        })
+    }
 
-        let mut vault = vaults_repository
-            .store_vault(vault_name, path, is_kms)
-            .await?;
-        if is_default_vault {
-            vaults_repository.set_as_default(vault_name).await?;
-            vault = vault.set_as_default();
+    /// The first created vault is the default one
+    async fn set_as_default_if_first(&self, vault: NamedVault) -> Result<NamedVault> {
+        let vaults_repository = self.vaults_repository().await?;
+        if vaults_repository.get_default_vault().await?.is_none() {
+            vaults_repository.set_as_default(&vault.name()).await?;
+            Ok(vault.set_as_default())
+        } else {
+            Ok(vault)
         }
-        Ok(vault)
     }
 }
 
@@ -165,6 +289,7 @@ pub struct NamedVault {
     path: PathBuf,
     is_default: bool,
     is_kms: bool,
+    pkcs11_token: Option<Pkcs11Token>,
 }
 
 impl NamedVault {
@@ -175,6 +300,15 @@ impl NamedVault {
             path,
             is_default,
             is_kms,
+            pkcs11_token: None,
+        }
+    }
+
+    /// Return a copy of this vault using a PKCS#11 token to store its keys
+    pub fn with_pkcs11_token(self, pkcs11_token: Pkcs11Token) -> NamedVault {
+        Self {
+            pkcs11_token: Some(pkcs11_token),
+            ..self
         }
     }
 
@@ -205,38 +339,57 @@ impl NamedVault {
         self.is_kms
     }
 
-    pub async fn vault(&self) -> Result<Vault> {
-        if self.is_kms {
-            let mut vault = Vault::create().await?;
-            let aws_vault = Arc::new(AwsSigningVault::create().await?);
-            vault.identity_vault = aws_vault.clone();
-            vault.credential_vault = aws_vault;
-            Ok(vault)
-        } else {
-            Ok(Vault::create_with_database(self.database().await?))
+    /// Return a description of the type of vault
+    pub fn vault_type(&self) -> &'static str {
+        match (&self.pkcs11_token, self.is_kms) {
+            (Some(_), _) => "PKCS#11",
+            (None, true) => "AWS KMS",
+            (None, false) => "OCKAM",
         }
     }
 
-    async fn database(&self) -> Result<Arc<SqlxDatabase>> {
-        Ok(Arc::new(SqlxDatabase::create(self.path.as_path()).await?))
+    /// Return the PKCS#11 token storing the keys of this vault, if there is one
+    pub fn pkcs11_token(&self) -> Option<&Pkcs11Token> {
+        self.pkcs11_token.as_ref()
     }
 }
 
 impl Display for NamedVault {
     fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
         writeln!(f, "Name: {}", self.name)?;
-        writeln!(
-            f,
-            "Type: {}",
-            match self.is_kms {
-                true => "AWS KMS",
-                false => "OCKAM",
-            }
-        )?;
+        writeln!(f, "Type: {}", self.vault_type())?;
+        if let Some(pkcs11_token) = &self.pkcs11_token {
+            writeln!(f, "Module: {}", pkcs11_token.module.display())?;
+            writeln!(f, "Slot: {}", pkcs11_token.slot)?;
+        }
         Ok(())
     }
 }
 
+/// Token accessed via a PKCS#11 module
+#[derive(Debug, PartialEq, Eq, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Pkcs11Token {
+    module: PathBuf,
+    slot: u64,
+}
+
+impl Pkcs11Token {
+    /// Create a new token description from the path of a PKCS#11 module and a slot number
+    pub fn new(module: PathBuf, slot: u64) -> Self {
+        Self { module, slot }
+    }
+
+    /// Return the path of the PKCS#11 module
+    pub fn module(&self) -> PathBuf {
+        self.module.clone()
+    }
+
+    /// Return the slot of the token
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -279,6 +432,39 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_rotate_vault_passphrase() -> Result<()> {
+        let cli = CliState::test().await?;
+        let vault = cli.create_named_vault("vault").await?;
+        let database = cli.vault_database(&vault).await?;
+
+        // the secrets are encrypted the first time a passphrase is set
+        cli.rotate_vault_passphrase(&vault, None, "passphrase1".into())
+            .await?;
+        assert!(SecretsSqlxDatabase::has_wrapping_key(database.clone()).await?);
+        assert!(cli
+            .secrets_repository(&vault, Some("passphrase1".into()))
+            .await
+            .is_ok());
+
+        // the current passphrase is required to change it
+        assert!(cli
+            .rotate_vault_passphrase(&vault, Some("wrong".into()), "passphrase2".into())
+            .await
+            .is_err());
+        cli.rotate_vault_passphrase(&vault, Some("passphrase1".into()), "passphrase2".into())
+            .await?;
+        assert!(cli
+            .secrets_repository(&vault, Some("passphrase1".into()))
+            .await
+            .is_err());
+        assert!(cli
+            .secrets_repository(&vault, Some("passphrase2".into()))
+            .await
+            .is_ok());
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_get_default_named_vault() -> Result<()> {
         let cli = CliState::test().await?;
@@ -0,0 +1,190 @@
+use ockam::identity::{Identifier, IdentityAttributesRepository};
+use ockam_abac::expr::str;
+use ockam_abac::{
+    AbacAccessControl, Action, Env, PoliciesRepository, PolicyAuditRepository, Resource,
+};
+use ockam_core::compat::sync::Arc;
+use ockam_core::Result;
+
+/// Prefix of the resource name of an HTTP path, the resource of the path
+/// `/admin` is `http-path/admin`
+pub const HTTP_PATH_RESOURCE_PREFIX: &str = "http-path";
+
+/// Action of sending a request to a path
+pub const REQUEST: Action = Action::assert_inline("request");
+
+/// Return the resource used to set the policies of an HTTP path and the paths below it
+pub fn http_path_resource(path: &str) -> Resource {
+    let path = path.trim_matches('/');
+    Resource::new(&format!("{HTTP_PATH_RESOURCE_PREFIX}/{path}"))
+}
+
+/// Evaluates the policies set on HTTP paths for the identity calling an HTTP outlet.
+///
+/// The policy of the longest path containing the requested path applies, so that
+/// a policy on `/admin` also protects `/admin/users`. A path without any policy
+/// can be accessed.
+///
+/// Besides the attributes of the caller, a policy can use the `resource.path` of the
+/// policy and the `action.method` of the request, as in `(= action.method "GET")`.
+pub(crate) struct HttpPathAccessControl {
+    policies: Arc<dyn PoliciesRepository>,
+    identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+    audit_repository: Option<Arc<dyn PolicyAuditRepository>>,
+    environment: Env,
+}
+
+impl HttpPathAccessControl {
+    pub(crate) fn new(
+        policies: Arc<dyn PoliciesRepository>,
+        identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+        environment: Env,
+    ) -> Self {
+        Self {
+            policies,
+            identity_attributes_repository,
+            audit_repository: None,
+            environment,
+        }
+    }
+
+    /// Record all the decisions taken for the paths
+    pub(crate) fn with_audit_repository(
+        mut self,
+        repository: Arc<dyn PolicyAuditRepository>,
+    ) -> Self {
+        self.audit_repository = Some(repository);
+        self
+    }
+
+    /// Return true if the caller can send a request to the path made of the given segments.
+    /// A caller without identity is only authorized on paths without policy
+    pub(crate) async fn is_authorized(
+        &self,
+        caller: Option<&Identifier>,
+        segments: &[String],
+        method: &str,
+    ) -> Result<bool> {
+        for length in (0..=segments.len()).rev() {
+            let path = format!("/{}", segments[..length].join("/"));
+            let resource = http_path_resource(&path);
+            let policy = match self.policies.get_policy(&resource, &REQUEST).await? {
+                Some(policy) => policy,
+                None => continue,
+            };
+
+            let caller = match caller {
+                Some(caller) => caller,
+                None => {
+                    debug!("request to {path} denied to a caller without identity");
+                    return Ok(false);
+                }
+            };
+
+            let mut environment = self.environment.clone();
+            environment.put("resource.id", str(resource.as_str()));
+            environment.put("resource.path", str(path.as_str()));
+            environment.put("action.id", str(REQUEST.as_str()));
+            environment.put("action.method", str(method));
+
+            let mut access_control = AbacAccessControl::new(
+                self.identity_attributes_repository.clone(),
+                policy,
+                environment,
+            );
+            if let Some(repository) = &self.audit_repository {
+                access_control =
+                    access_control.with_audit_repository(repository.clone(), resource, REQUEST);
+            }
+
+            let is_authorized = access_control
+                .is_identity_authorized(caller.clone())
+                .await?;
+            if !is_authorized {
+                debug!("{method} request denied on the path {path} to {caller}");
+            }
+            return Ok(is_authorized);
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::identity::{AttributesEntry, IdentityAttributesSqlxDatabase};
+    use ockam_abac::expr::{eq, ident};
+    use ockam_abac::{Policy, PolicySqlxDatabase};
+    use std::collections::BTreeMap;
+
+    fn segments(path: &str) -> Vec<String> {
+        crate::http::request::path_segments(path).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_path_policies() -> Result<()> {
+        let policies = PolicySqlxDatabase::create().await?;
+        let identity_attributes = IdentityAttributesSqlxDatabase::create().await?;
+        let caller = Identifier::try_from(
+            "Iabababababababababababababababababababababababababababababababab",
+        )?;
+        identity_attributes
+            .put_attributes(
+                &caller,
+                AttributesEntry::new(
+                    BTreeMap::from([(b"role".to_vec(), b"support".to_vec())]),
+                    ockam::identity::utils::now()?,
+                    None,
+                    None,
+                ),
+            )
+            .await?;
+
+        let access_control =
+            HttpPathAccessControl::new(policies.clone(), identity_attributes, Env::new());
+
+        // without policy all the paths can be accessed
+        assert!(
+            access_control
+                .is_authorized(None, &segments("/admin"), "GET")
+                .await?
+        );
+
+        policies
+            .set_policy(
+                &http_path_resource("/admin"),
+                &REQUEST,
+                &Policy::new(eq([ident("subject.role"), str("admin")])),
+            )
+            .await?;
+        policies
+            .set_policy(
+                &http_path_resource("/admin/tickets"),
+                &REQUEST,
+                &Policy::new(eq([ident("subject.role"), str("support")])),
+            )
+            .await?;
+
+        assert!(
+            !access_control
+                .is_authorized(Some(&caller), &segments("/admin/users/1"), "GET")
+                .await?
+        );
+        assert!(
+            access_control
+                .is_authorized(Some(&caller), &segments("/admin/tickets/12"), "POST")
+                .await?
+        );
+        assert!(
+            !access_control
+                .is_authorized(None, &segments("/admin/tickets"), "GET")
+                .await?
+        );
+        assert!(
+            access_control
+                .is_authorized(Some(&caller), &segments("/public"), "GET")
+                .await?
+        );
+        Ok(())
+    }
+}
@@ -0,0 +1,201 @@
+#[cfg(test)]
+mod test {
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::sync::mpsc;
+
+    use ockam::identity::{AttributesEntry, SecureChannelListenerOptions, SecureChannelOptions};
+    use ockam::Context;
+    use ockam_abac::expr::{eq, ident, str};
+    use ockam_abac::{Env, Policy};
+    use ockam_core::compat::sync::Arc;
+    use ockam_core::{route, AllowAll, Result};
+    use ockam_node::compat::tokio;
+    use ockam_transport_tcp::{LoadBalancing, TcpInletOptions};
+
+    use crate::http::{
+        http_path_resource, HttpOutletRules, HttpOutletService, HttpRouter, REQUEST,
+    };
+    use crate::nodes::models::portal::HttpRoute;
+
+    /// Start a backend answering each request with its name, and sending the
+    /// received requests to the returned channel
+    async fn start_backend(name: &'static str) -> (String, mpsc::UnboundedReceiver<String>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap().to_string();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((mut stream, _)) = listener.accept().await {
+                let mut request = vec![];
+                let mut buffer = [0; 1024];
+                while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+                    match stream.read(&mut buffer).await {
+                        Ok(0) | Err(_) => break,
+                        Ok(n) => request.extend_from_slice(&buffer[..n]),
+                    }
+                }
+                let _ = sender.send(String::from_utf8_lossy(&request).to_string());
+                let response = format!(
+                    "HTTP/1.1 200 OK\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{name}",
+                    name.len()
+                );
+                let _ = stream.write_all(response.as_bytes()).await;
+            }
+        });
+        (address, receiver)
+    }
+
+    async fn send_request(port: u16, request: &str) -> String {
+        let mut stream = TcpStream::connect(("127.0.0.1", port)).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        tokio::time::timeout(
+            Duration::from_secs(10),
+            stream.read_to_string(&mut response),
+        )
+        .await
+        .unwrap()
+        .unwrap();
+        response
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 60_000)]
+    async fn http_outlet__requests_are_routed_authorized_and_identified(
+        context: &mut Context,
+    ) -> Result<()> {
+        let handler = crate::util::test_utils::start_manager_for_tests(context).await?;
+        let secure_channels = handler.secure_channels.clone();
+        let identities_creation = secure_channels.identities().identities_creation();
+        let caller = identities_creation.create_identity().await?;
+        let outlet_identity = identities_creation.create_identity().await?;
+
+        let identity_attributes = secure_channels
+            .identities()
+            .identity_attributes_repository();
+        identity_attributes
+            .put_attributes(
+                &caller,
+                AttributesEntry::new(
+                    BTreeMap::from([(b"role".to_vec(), b"support".to_vec())]),
+                    ockam::identity::utils::now()?,
+                    None,
+                    None,
+                ),
+            )
+            .await?;
+
+        let listener = secure_channels
+            .create_secure_channel_listener(
+                context,
+                &outlet_identity,
+                "http_listener",
+                SecureChannelListenerOptions::new(),
+            )
+            .await?;
+        let channel = secure_channels
+            .create_secure_channel(
+                context,
+                &caller,
+                route!["http_listener"],
+                SecureChannelOptions::new(),
+            )
+            .await?;
+
+        let (default_backend, mut default_requests) = start_backend("default").await;
+        let (api_backend, mut api_requests) = start_backend("api").await;
+
+        handler
+            .cli_state
+            .set_policy(
+                &http_path_resource("/api/admin"),
+                &REQUEST,
+                &Policy::new(eq([ident("subject.role"), str("admin")])),
+            )
+            .await?;
+
+        let router = HttpRouter::create(
+            context,
+            &handler.tcp,
+            vec![default_backend],
+            vec![HttpRoute::new(None, "/api", vec![api_backend])],
+            LoadBalancing::default(),
+        )
+        .await?;
+        let rules = HttpOutletRules::new(
+            router,
+            handler
+                .cli_state
+                .make_http_path_access_control(Env::new())
+                .await?,
+            identity_attributes,
+            vec!["role".to_string()],
+        );
+        HttpOutletService::create(
+            context,
+            "http_outlet".into(),
+            rules,
+            Arc::new(AllowAll),
+            &[listener.flow_control_id().clone()],
+        )
+        .await?;
+
+        let (inlet_address, _) = handler
+            .tcp
+            .create_inlet(
+                "127.0.0.1:0",
+                route![channel.encryptor_address().clone(), "http_outlet"],
+                TcpInletOptions::new(),
+            )
+            .await?;
+        let port = inlet_address.port();
+
+        // the identity of the caller replaces the one sent by the client
+        let response = send_request(
+            port,
+            "GET /index.html HTTP/1.1\r\nHost: app\r\nX-Ockam-Identity: Iforged\r\n\r\n",
+        )
+        .await;
+        assert!(response.ends_with("default"), "{response}");
+        let request = default_requests.recv().await.unwrap();
+        assert!(
+            request.contains(&format!("X-Ockam-Identity: {caller}\r\n")),
+            "{request}"
+        );
+        assert!(
+            request.contains("X-Ockam-Attribute-role: support\r\n"),
+            "{request}"
+        );
+        assert!(!request.contains("Iforged"), "{request}");
+
+        // requests are routed by path
+        let response = send_request(
+            port,
+            "POST /api/tickets HTTP/1.1\r\nHost: app\r\nContent-Length: 2\r\n\r\n{}",
+        )
+        .await;
+        assert!(response.ends_with("api"), "{response}");
+        let request = api_requests.recv().await.unwrap();
+        assert!(request.starts_with("POST /api/tickets HTTP/1.1\r\n"));
+
+        // the policies of the paths are enforced
+        let response =
+            send_request(port, "GET /api/admin/users HTTP/1.1\r\nHost: app\r\n\r\n").await;
+        assert!(response.starts_with("HTTP/1.1 403 Forbidden"), "{response}");
+
+        // a request following the first one on a connection is never forwarded
+        let response = send_request(
+            port,
+            "GET /api HTTP/1.1\r\nHost: app\r\n\r\nGET /api/admin HTTP/1.1\r\nHost: app\r\n\r\n",
+        )
+        .await;
+        assert!(response.ends_with("api"), "{response}");
+        let request = api_requests.recv().await.unwrap();
+        assert!(!request.contains("/api/admin"), "{request}");
+
+        context.stop().await
+    }
+}
@@ -0,0 +1,25 @@
+//! An HTTP/1.1 aware outlet.
+//!
+//! The outlet reads the requests going through its portals, routes them to a
+//! backend by host and path, checks the policies set on their path, and adds the
+//! identity of the caller as headers, so that the backends can trust who is
+//! calling without authenticating the callers themselves.
+
+mod access_control;
+mod integration_test;
+mod outlet_service;
+mod portal_worker;
+mod request;
+mod routes;
+
+pub(crate) use access_control::HttpPathAccessControl;
+pub use access_control::{http_path_resource, HTTP_PATH_RESOURCE_PREFIX, REQUEST};
+pub(crate) use outlet_service::{is_header_name, HttpOutletRules, HttpOutletService};
+pub(crate) use routes::HttpRouter;
+
+/// Header carrying the identifier of the caller
+pub const IDENTITY_HEADER: &str = "X-Ockam-Identity";
+
+/// Prefix of the headers carrying the attributes of the caller, the attribute
+/// `role` is sent in the `X-Ockam-Attribute-role` header
+pub const ATTRIBUTE_HEADER_PREFIX: &str = "X-Ockam-Attribute-";
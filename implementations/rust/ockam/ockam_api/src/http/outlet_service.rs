@@ -0,0 +1,191 @@
+use crate::error::ApiError;
+use crate::http::access_control::HttpPathAccessControl;
+use crate::http::portal_worker::HttpPortalWorker;
+use crate::http::request::{HttpError, RequestHead};
+use crate::http::routes::HttpRouter;
+use crate::http::{ATTRIBUTE_HEADER_PREFIX, IDENTITY_HEADER};
+use ockam::identity::{Identifier, IdentityAttributesRepository, IdentitySecureChannelLocalInfo};
+use ockam_core::compat::sync::Arc;
+use ockam_core::flow_control::FlowControlId;
+use ockam_core::{async_trait, Address, IncomingAccessControl, Result, Routed, Worker};
+use ockam_node::{Context, WorkerBuilder};
+use ockam_transport_tcp::PortalMessage;
+
+/// Rules applied by an HTTP outlet to the requests of its callers
+pub(crate) struct HttpOutletRules {
+    router: HttpRouter,
+    access_control: HttpPathAccessControl,
+    identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+    /// Attributes of the callers sent to the backends
+    attributes: Vec<String>,
+}
+
+impl HttpOutletRules {
+    pub(crate) fn new(
+        router: HttpRouter,
+        access_control: HttpPathAccessControl,
+        identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+        attributes: Vec<String>,
+    ) -> Self {
+        Self {
+            router,
+            access_control,
+            identity_attributes_repository,
+            attributes,
+        }
+    }
+
+    /// Authorize a request, then return the outlet of its backend and the head to send to it
+    pub(crate) async fn forward(
+        &self,
+        head: &RequestHead,
+        caller: Option<&Identifier>,
+    ) -> core::result::Result<(Address, Vec<u8>), HttpError> {
+        let host = head.host()?;
+        let segments = head.path_segments()?;
+
+        match self
+            .access_control
+            .is_authorized(caller, &segments, head.method())
+            .await
+        {
+            Ok(true) => {}
+            Ok(false) => return Err(HttpError::Forbidden),
+            Err(e) => {
+                warn!("Could not evaluate the policies of an HTTP request: {e}");
+                return Err(HttpError::Internal);
+            }
+        }
+
+        let headers = self.identity_headers(caller).await?;
+        let outlet = self.router.route(host.as_deref(), &segments).clone();
+        Ok((outlet, head.encode(&headers)))
+    }
+
+    /// Headers carrying the identifier of the caller and its selected attributes
+    async fn identity_headers(
+        &self,
+        caller: Option<&Identifier>,
+    ) -> core::result::Result<Vec<(String, String)>, HttpError> {
+        let caller = match caller {
+            Some(caller) => caller,
+            None => return Ok(vec![]),
+        };
+        let mut headers = vec![(IDENTITY_HEADER.to_string(), caller.to_string())];
+        if self.attributes.is_empty() {
+            return Ok(headers);
+        }
+
+        let entry = self
+            .identity_attributes_repository
+            .get_attributes(caller)
+            .await
+            .map_err(|e| {
+                warn!("Could not get the attributes of {caller}: {e}");
+                HttpError::Internal
+            })?;
+        let entry = match entry {
+            Some(entry) => entry,
+            None => return Ok(headers),
+        };
+        for name in &self.attributes {
+            let value = match entry.attrs().get(name.as_bytes()) {
+                Some(value) => value,
+                None => continue,
+            };
+            match core::str::from_utf8(value) {
+                Ok(value) if is_header_value(value) => headers.push((
+                    format!("{ATTRIBUTE_HEADER_PREFIX}{name}"),
+                    value.to_string(),
+                )),
+                _ => warn!("The attribute {name} of {caller} can't be sent in a header"),
+            }
+        }
+        Ok(headers)
+    }
+}
+
+/// Listens to the pings of the inlets connecting to an HTTP outlet, and starts
+/// an [`HttpPortalWorker`] for each of them
+pub(crate) struct HttpOutletService {
+    rules: Arc<HttpOutletRules>,
+    access_control: Arc<dyn IncomingAccessControl>,
+}
+
+impl HttpOutletService {
+    pub(crate) async fn create(
+        ctx: &Context,
+        address: Address,
+        rules: HttpOutletRules,
+        access_control: Arc<dyn IncomingAccessControl>,
+        consumers: &[FlowControlId],
+    ) -> Result<()> {
+        for flow_control_id in consumers {
+            ctx.flow_controls()
+                .add_consumer(address.clone(), flow_control_id);
+        }
+
+        let worker = Self {
+            rules: Arc::new(rules),
+            access_control: access_control.clone(),
+        };
+        WorkerBuilder::new(worker)
+            .with_address(address)
+            .with_incoming_access_control_arc(access_control)
+            .start(ctx)
+            .await?;
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Worker for HttpOutletService {
+    type Message = PortalMessage;
+    type Context = Context;
+
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.rules.router.stop(ctx).await;
+        Ok(())
+    }
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Self::Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        let return_route = msg.return_route();
+        let src_addr = msg.src_addr();
+        let caller = IdentitySecureChannelLocalInfo::find_info(msg.local_message())
+            .map(|info| info.their_identity_id())
+            .ok();
+
+        if let PortalMessage::Ping = msg.body() {
+        } else {
+            return Err(ApiError::core("An HTTP portal must be opened with a ping"));
+        }
+
+        HttpPortalWorker::start(
+            ctx,
+            self.rules.clone(),
+            caller,
+            return_route,
+            &src_addr,
+            self.access_control.clone(),
+        )
+        .await
+    }
+}
+
+/// Return true if the name can be used in a header name
+pub(crate) fn is_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&b))
+}
+
+fn is_header_value(value: &str) -> bool {
+    value
+        .bytes()
+        .all(|b| b == b'\t' || (b' '..=b'~').contains(&b))
+}
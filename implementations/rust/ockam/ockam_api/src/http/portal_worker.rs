@@ -0,0 +1,245 @@
+use crate::http::outlet_service::HttpOutletRules;
+use crate::http::request::{BodyReader, HttpError, RequestHeadReader};
+use ockam::identity::Identifier;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{
+    async_trait, route, Address, AllowAll, Any, Decodable, IncomingAccessControl, Mailbox,
+    Mailboxes, Result, Route, Routed, Worker,
+};
+use ockam_node::{Context, WorkerBuilder};
+use ockam_transport_tcp::{PortalMessage, MAX_PAYLOAD_SIZE};
+
+/// Progress of the request of a portal
+enum RequestState {
+    /// Waiting for the request line and the headers
+    Head(RequestHeadReader),
+    /// Forwarding the body to the backend
+    Body(BodyReader),
+    /// The request was forwarded or answered, any further data is dropped
+    Complete,
+}
+
+/// A worker standing between the inlet of a portal and the TCP outlet of a backend.
+///
+/// It answers the ping of the inlet, reads the request of the client, and only
+/// connects to the backend selected for it once the request is authorized. The
+/// request is forwarded with the identity headers of the caller, then the
+/// response of the backend is relayed as is.
+///
+/// A single request is sent over each portal: the backend is asked to close the
+/// connection after its response, so that the next request of the client goes
+/// through a new portal, and is routed and authorized again.
+pub(crate) struct HttpPortalWorker {
+    rules: Arc<HttpOutletRules>,
+    caller: Option<Identifier>,
+    /// Address receiving the messages of the inlet
+    inlet_address: Address,
+    /// Address receiving the messages of the TCP outlet of the backend
+    outlet_address: Address,
+    inlet_route: Route,
+    /// Route to the backend, known once its TCP outlet answered our ping
+    outlet_route: Option<Route>,
+    state: RequestState,
+    /// Data waiting for the connection to the backend
+    pending: Vec<u8>,
+    is_connecting: bool,
+    is_disconnecting: bool,
+}
+
+impl HttpPortalWorker {
+    pub(crate) async fn start(
+        ctx: &Context,
+        rules: Arc<HttpOutletRules>,
+        caller: Option<Identifier>,
+        inlet_route: Route,
+        src_addr: &Address,
+        access_control: Arc<dyn IncomingAccessControl>,
+    ) -> Result<()> {
+        let inlet_address = Address::random_tagged("HttpPortalWorker.inlet");
+        let outlet_address = Address::random_tagged("HttpPortalWorker.outlet");
+
+        // Receive the further messages of the secure channel which sent the ping
+        let flow_controls = ctx.flow_controls();
+        if let Some(producer_flow_control_id) = flow_controls
+            .get_flow_control_with_producer(src_addr)
+            .map(|x| x.flow_control_id().clone())
+        {
+            flow_controls.add_consumer(inlet_address.clone(), &producer_flow_control_id);
+        }
+
+        let worker = Self {
+            rules,
+            caller,
+            inlet_address: inlet_address.clone(),
+            outlet_address: outlet_address.clone(),
+            inlet_route,
+            outlet_route: None,
+            state: RequestState::Head(RequestHeadReader::default()),
+            pending: vec![],
+            is_connecting: false,
+            is_disconnecting: false,
+        };
+
+        let inlet_mailbox = Mailbox::new(inlet_address, access_control, Arc::new(AllowAll));
+        let outlet_mailbox = Mailbox::new(outlet_address, Arc::new(AllowAll), Arc::new(AllowAll));
+        WorkerBuilder::new(worker)
+            .with_mailboxes(Mailboxes::new(inlet_mailbox, vec![outlet_mailbox]))
+            .start(ctx)
+            .await?;
+        Ok(())
+    }
+
+    /// Read the data sent by the client
+    async fn handle_request_data(&mut self, ctx: &Context, data: Vec<u8>) -> Result<()> {
+        let mut data = data;
+
+        if let RequestState::Head(reader) = &mut self.state {
+            let (head, rest) = match reader.read(&data) {
+                Ok(Some(read)) => read,
+                Ok(None) => return Ok(()),
+                Err(e) => return self.respond_error(ctx, e).await,
+            };
+            let body = match head.body_reader() {
+                Ok(body) => body,
+                Err(e) => return self.respond_error(ctx, e).await,
+            };
+            let (outlet, head) = match self.rules.forward(&head, self.caller.as_ref()).await {
+                Ok(forward) => forward,
+                Err(e) => return self.respond_error(ctx, e).await,
+            };
+
+            debug!("Forwarding an HTTP request to {outlet}");
+            self.pending = head;
+            self.is_connecting = true;
+            self.state = RequestState::Body(body);
+            ctx.send_from_address(
+                route![outlet],
+                PortalMessage::Ping,
+                self.outlet_address.clone(),
+            )
+            .await?;
+            data = rest;
+        }
+
+        if let RequestState::Body(body) = &mut self.state {
+            let length = match body.read(&data) {
+                Ok(length) => length,
+                Err(_) => {
+                    // The head was already forwarded, the connection can only be closed
+                    debug!("Invalid body in an HTTP request");
+                    return self.disconnect(ctx).await;
+                }
+            };
+            if body.is_complete() {
+                self.state = RequestState::Complete;
+            }
+            data.truncate(length);
+            self.send_to_backend(ctx, data).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn send_to_backend(&mut self, ctx: &Context, data: Vec<u8>) -> Result<()> {
+        match &self.outlet_route {
+            Some(outlet_route) if !data.is_empty() => {
+                for chunk in data.chunks(MAX_PAYLOAD_SIZE) {
+                    ctx.send_from_address(
+                        outlet_route.clone(),
+                        PortalMessage::Payload(chunk.to_vec()),
+                        self.outlet_address.clone(),
+                    )
+                    .await?;
+                }
+            }
+            Some(_) => {}
+            None => self.pending.extend(data),
+        }
+        Ok(())
+    }
+
+    /// Answer the client directly then close the portal
+    async fn respond_error(&mut self, ctx: &Context, error: HttpError) -> Result<()> {
+        debug!("Answering an HTTP request with {error:?}");
+        self.state = RequestState::Complete;
+        ctx.send(
+            self.inlet_route.clone(),
+            PortalMessage::Payload(error.response()),
+        )
+        .await?;
+        self.disconnect(ctx).await
+    }
+
+    /// Close both sides of the portal
+    async fn disconnect(&mut self, ctx: &Context) -> Result<()> {
+        self.is_disconnecting = true;
+        self.disconnect_backend(ctx).await?;
+        ctx.send(self.inlet_route.clone(), PortalMessage::Disconnect)
+            .await?;
+        ctx.stop_worker(self.inlet_address.clone()).await
+    }
+
+    async fn disconnect_backend(&mut self, ctx: &Context) -> Result<()> {
+        if let Some(outlet_route) = self.outlet_route.take() {
+            ctx.send_from_address(
+                outlet_route,
+                PortalMessage::Disconnect,
+                self.outlet_address.clone(),
+            )
+            .await?;
+        }
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl Worker for HttpPortalWorker {
+    type Message = Any;
+    type Context = Context;
+
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        // Let the inlet send the request of the client
+        ctx.send(self.inlet_route.clone(), PortalMessage::Pong)
+            .await
+    }
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        if self.is_disconnecting {
+            return Ok(());
+        }
+
+        let from_inlet = msg.msg_addr() == self.inlet_address;
+        let return_route = msg.return_route();
+        let message = PortalMessage::decode(msg.payload())?;
+
+        match (from_inlet, message) {
+            (true, PortalMessage::Payload(data)) => {
+                self.handle_request_data(ctx, data).await?;
+            }
+            (true, PortalMessage::Disconnect) => {
+                self.is_disconnecting = true;
+                self.disconnect_backend(ctx).await?;
+                ctx.stop_worker(self.inlet_address.clone()).await?;
+            }
+            (false, PortalMessage::Pong) if self.is_connecting => {
+                self.is_connecting = false;
+                self.outlet_route = Some(return_route);
+                let pending = core::mem::take(&mut self.pending);
+                self.send_to_backend(ctx, pending).await?;
+            }
+            (false, PortalMessage::Payload(data)) => {
+                ctx.send(self.inlet_route.clone(), PortalMessage::Payload(data))
+                    .await?;
+            }
+            (false, PortalMessage::Disconnect) => {
+                self.outlet_route = None;
+                self.disconnect(ctx).await?;
+            }
+            (_, message) => {
+                warn!("Unexpected message in an HTTP portal: {message:?}");
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,558 @@
+use tracing::debug;
+
+/// Maximum size of the head of a request: request line and headers
+const MAX_HEAD_SIZE: usize = 64 * 1024;
+/// Maximum number of headers of a request
+const MAX_HEADERS: usize = 128;
+/// Maximum size of a chunk size line or a trailer line
+const MAX_LINE_SIZE: usize = 4 * 1024;
+
+/// Headers which only apply to the connection between the client and the outlet
+const HOP_BY_HOP_HEADERS: &[&str] = &[
+    "connection",
+    "keep-alive",
+    "proxy-authorization",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "upgrade",
+];
+
+/// Headers which define the request and can't be removed by the `Connection` header
+const END_TO_END_HEADERS: &[&str] = &["content-length", "host", "transfer-encoding"];
+
+/// Prefix of the headers set by the outlet, which can't be sent by the clients
+const OCKAM_HEADERS_PREFIX: &str = "x-ockam-";
+
+/// Reasons to answer a request from the outlet instead of forwarding it to a backend
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum HttpError {
+    BadRequest,
+    Forbidden,
+    MethodNotAllowed,
+    HeadersTooLarge,
+    Internal,
+}
+
+impl HttpError {
+    /// Complete response to send to the client before closing the connection
+    pub(crate) fn response(&self) -> Vec<u8> {
+        let (status, reason) = match self {
+            Self::BadRequest => (400, "Bad Request"),
+            Self::Forbidden => (403, "Forbidden"),
+            Self::MethodNotAllowed => (405, "Method Not Allowed"),
+            Self::HeadersTooLarge => (431, "Request Header Fields Too Large"),
+            Self::Internal => (500, "Internal Server Error"),
+        };
+        format!(
+            "HTTP/1.1 {status} {reason}\r\ncontent-type: text/plain\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{reason}\n",
+            reason.len() + 1
+        )
+        .into_bytes()
+    }
+}
+
+/// Accumulates the data received from the client until the head of the request is complete
+#[derive(Default)]
+pub(crate) struct RequestHeadReader {
+    buffer: Vec<u8>,
+}
+
+impl RequestHeadReader {
+    /// Return the head of the request, and the data following it, once the head is complete
+    pub(crate) fn read(
+        &mut self,
+        data: &[u8],
+    ) -> Result<Option<(RequestHead, Vec<u8>)>, HttpError> {
+        self.buffer.extend_from_slice(data);
+
+        let (head, length) = {
+            let mut headers = [httparse::EMPTY_HEADER; MAX_HEADERS];
+            let mut request = httparse::Request::new(&mut headers);
+            let length = match request.parse(&self.buffer) {
+                Ok(httparse::Status::Complete(length)) => length,
+                Ok(httparse::Status::Partial) if self.buffer.len() > MAX_HEAD_SIZE => {
+                    return Err(HttpError::HeadersTooLarge)
+                }
+                Ok(httparse::Status::Partial) => return Ok(None),
+                Err(httparse::Error::TooManyHeaders) => return Err(HttpError::HeadersTooLarge),
+                Err(e) => {
+                    debug!("Invalid HTTP request: {e}");
+                    return Err(HttpError::BadRequest);
+                }
+            };
+            if length > MAX_HEAD_SIZE {
+                return Err(HttpError::HeadersTooLarge);
+            }
+
+            let head = RequestHead {
+                method: request.method.unwrap_or_default().to_string(),
+                target: request.path.unwrap_or_default().to_string(),
+                minor_version: request.version.unwrap_or(1),
+                headers: request
+                    .headers
+                    .iter()
+                    .map(|h| (h.name.to_string(), h.value.to_vec()))
+                    .collect(),
+            };
+            (head, length)
+        };
+
+        let rest = self.buffer.split_off(length);
+        Ok(Some((head, rest)))
+    }
+}
+
+/// Request line and headers of a request
+#[derive(Debug)]
+pub(crate) struct RequestHead {
+    method: String,
+    target: String,
+    minor_version: u8,
+    headers: Vec<(String, Vec<u8>)>,
+}
+
+impl RequestHead {
+    pub(crate) fn method(&self) -> &str {
+        &self.method
+    }
+
+    /// Host targeted by the request, in lower case and without port.
+    /// Only HTTP/1.0 requests can omit it
+    pub(crate) fn host(&self) -> Result<Option<String>, HttpError> {
+        if let Some((authority, _)) = split_absolute_form(&self.target) {
+            return Ok(Some(host_without_port(authority)));
+        }
+
+        let mut hosts = self.header_values("host");
+        match (hosts.next(), hosts.next()) {
+            (Some(host), None) => {
+                let host = core::str::from_utf8(host).map_err(|_| HttpError::BadRequest)?;
+                Ok(Some(host_without_port(host)))
+            }
+            (None, _) if self.minor_version == 0 => Ok(None),
+            _ => Err(HttpError::BadRequest),
+        }
+    }
+
+    /// Segments of the requested path, used to route the request and check its policies
+    pub(crate) fn path_segments(&self) -> Result<Vec<String>, HttpError> {
+        if self.method.eq_ignore_ascii_case("CONNECT") {
+            return Err(HttpError::MethodNotAllowed);
+        }
+        let path = match split_absolute_form(&self.target) {
+            Some((_, "")) => "/",
+            Some((_, path)) => path,
+            None if self.target == "*" => "/",
+            None => &self.target,
+        };
+        if !path.starts_with('/') {
+            return Err(HttpError::BadRequest);
+        }
+        let path = path.split(['?', '#']).next().unwrap_or_default();
+        path_segments(path)
+    }
+
+    /// Return how the end of the body of the request is found
+    pub(crate) fn body_reader(&self) -> Result<BodyReader, HttpError> {
+        let transfer_encodings = self.header_tokens("transfer-encoding");
+        let content_lengths = self.header_tokens("content-length");
+
+        if !transfer_encodings.is_empty() {
+            // A request with both headers could be read differently by the backend
+            if !content_lengths.is_empty()
+                || self.minor_version == 0
+                || transfer_encodings.last().map(String::as_str) != Some("chunked")
+            {
+                return Err(HttpError::BadRequest);
+            }
+            return Ok(BodyReader::Chunked(ChunkState::Size(vec![])));
+        }
+
+        match content_lengths.split_first() {
+            None => Ok(BodyReader::Length(0)),
+            Some((length, others)) => {
+                if others.iter().any(|l| l != length) || !length.bytes().all(|b| b.is_ascii_digit())
+                {
+                    return Err(HttpError::BadRequest);
+                }
+                let length = length.parse().map_err(|_| HttpError::BadRequest)?;
+                Ok(BodyReader::Length(length))
+            }
+        }
+    }
+
+    /// Encode the head forwarded to the backend.
+    ///
+    /// The hop-by-hop headers and the headers reserved to the outlet are removed,
+    /// the given headers are added, and the backend is asked to close the
+    /// connection after its response.
+    pub(crate) fn encode(&self, headers: &[(String, String)]) -> Vec<u8> {
+        let connection_options = self.header_tokens("connection");
+        let is_removed = |name: &str| {
+            let name = name.to_ascii_lowercase();
+            HOP_BY_HOP_HEADERS.contains(&name.as_str())
+                || (connection_options.contains(&name)
+                    && !END_TO_END_HEADERS.contains(&name.as_str()))
+                // Some servers read underscores as dashes
+                || name.replace('_', "-").starts_with(OCKAM_HEADERS_PREFIX)
+        };
+
+        let mut head = format!(
+            "{} {} HTTP/1.{}\r\n",
+            self.method, self.target, self.minor_version
+        )
+        .into_bytes();
+        for (name, value) in self.headers.iter().filter(|(name, _)| !is_removed(name)) {
+            head.extend_from_slice(name.as_bytes());
+            head.extend_from_slice(b": ");
+            head.extend_from_slice(value);
+            head.extend_from_slice(b"\r\n");
+        }
+        for (name, value) in headers {
+            head.extend_from_slice(format!("{name}: {value}\r\n").as_bytes());
+        }
+        head.extend_from_slice(b"connection: close\r\n\r\n");
+        head
+    }
+
+    fn header_values<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a [u8]> + 'a {
+        self.headers
+            .iter()
+            .filter(move |(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_slice())
+    }
+
+    /// Comma separated values of all the headers with the given name, in lower case
+    fn header_tokens(&self, name: &str) -> Vec<String> {
+        self.header_values(name)
+            .flat_map(|value| {
+                String::from_utf8_lossy(value)
+                    .split(',')
+                    .map(|t| t.trim().to_ascii_lowercase())
+                    .filter(|t| !t.is_empty())
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+}
+
+/// Split the percent-decoded path on slashes, rejecting the `.` and `..` segments
+/// which could be used to reach another path than the authorized one
+pub(crate) fn path_segments(path: &str) -> Result<Vec<String>, HttpError> {
+    let mut segments = vec![];
+    for segment in percent_decode(path).split(['/', '\\']) {
+        match segment {
+            "" => continue,
+            "." | ".." => return Err(HttpError::BadRequest),
+            segment => segments.push(segment.to_string()),
+        }
+    }
+    Ok(segments)
+}
+
+/// Split a request target of the form `http://host:port/path` in an authority and a path
+fn split_absolute_form(target: &str) -> Option<(&str, &str)> {
+    let (scheme, rest) = target.split_once("://")?;
+    if !scheme.eq_ignore_ascii_case("http") && !scheme.eq_ignore_ascii_case("https") {
+        return None;
+    }
+    Some(match rest.find(['/', '?']) {
+        Some(index) => rest.split_at(index),
+        None => (rest, ""),
+    })
+}
+
+fn host_without_port(authority: &str) -> String {
+    let authority = authority.rsplit_once('@').map_or(authority, |(_, a)| a);
+    let host = match authority.strip_prefix('[') {
+        Some(ipv6) => ipv6.split(']').next().unwrap_or_default(),
+        None => authority.split(':').next().unwrap_or_default(),
+    };
+    host.to_ascii_lowercase()
+}
+
+fn percent_decode(s: &str) -> String {
+    let bytes = s.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|h| core::str::from_utf8(h).ok())
+            .and_then(|h| u8::from_str_radix(h, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            (byte, _) => {
+                decoded.push(byte);
+                i += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+/// Finds the end of the body of a request, so that nothing following it reaches the backend
+#[derive(Debug)]
+pub(crate) enum BodyReader {
+    /// Number of bytes remaining
+    Length(u64),
+    Chunked(ChunkState),
+}
+
+#[derive(Debug)]
+pub(crate) enum ChunkState {
+    /// Reading the line with the size of the next chunk
+    Size(Vec<u8>),
+    /// Reading the data of a chunk, with the number of bytes remaining
+    Data(u64),
+    /// Reading the end of line following the data of a chunk
+    DataEnd(Vec<u8>),
+    /// Reading the trailer lines, until an empty line
+    Trailers(Vec<u8>),
+    Done,
+}
+
+impl BodyReader {
+    /// Return the number of bytes at the start of `data` which belong to the body
+    pub(crate) fn read(&mut self, data: &[u8]) -> Result<usize, HttpError> {
+        let mut read = 0;
+        while read < data.len() && !self.is_complete() {
+            let data = &data[read..];
+            match self {
+                Self::Length(remaining) => {
+                    let n = (*remaining).min(data.len() as u64);
+                    *remaining -= n;
+                    read += n as usize;
+                }
+                Self::Chunked(ChunkState::Data(remaining)) => {
+                    let n = (*remaining).min(data.len() as u64);
+                    *remaining -= n;
+                    read += n as usize;
+                    if *remaining == 0 {
+                        *self = Self::Chunked(ChunkState::DataEnd(vec![]));
+                    }
+                }
+                Self::Chunked(ChunkState::Size(line)) => {
+                    read += 1;
+                    if let Some(line) = read_line(line, data[0])? {
+                        *self = Self::Chunked(match parse_chunk_size(&line)? {
+                            0 => ChunkState::Trailers(vec![]),
+                            size => ChunkState::Data(size),
+                        });
+                    }
+                }
+                Self::Chunked(ChunkState::DataEnd(line)) => {
+                    read += 1;
+                    if let Some(line) = read_line(line, data[0])? {
+                        if !line.is_empty() {
+                            return Err(HttpError::BadRequest);
+                        }
+                        *self = Self::Chunked(ChunkState::Size(vec![]));
+                    }
+                }
+                Self::Chunked(ChunkState::Trailers(line)) => {
+                    read += 1;
+                    if let Some(line) = read_line(line, data[0])? {
+                        if line.is_empty() {
+                            *self = Self::Chunked(ChunkState::Done);
+                        }
+                    }
+                }
+                Self::Chunked(ChunkState::Done) => break,
+            }
+        }
+        Ok(read)
+    }
+
+    pub(crate) fn is_complete(&self) -> bool {
+        matches!(self, Self::Length(0) | Self::Chunked(ChunkState::Done))
+    }
+}
+
+/// Add a byte to a line, returning the line, without its end, once it is complete
+fn read_line(line: &mut Vec<u8>, byte: u8) -> Result<Option<Vec<u8>>, HttpError> {
+    if byte == b'\n' {
+        let mut line = core::mem::take(line);
+        if line.last() == Some(&b'\r') {
+            line.pop();
+        }
+        return Ok(Some(line));
+    }
+    line.push(byte);
+    if line.len() > MAX_LINE_SIZE {
+        return Err(HttpError::BadRequest);
+    }
+    Ok(None)
+}
+
+/// Parse the hexadecimal size of a chunk, ignoring the chunk extensions
+fn parse_chunk_size(line: &[u8]) -> Result<u64, HttpError> {
+    let line = core::str::from_utf8(line).map_err(|_| HttpError::BadRequest)?;
+    let size = line
+        .split(';')
+        .next()
+        .unwrap_or_default()
+        .trim_end_matches([' ', '\t']);
+    if size.is_empty() || !size.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Err(HttpError::BadRequest);
+    }
+    u64::from_str_radix(size, 16).map_err(|_| HttpError::BadRequest)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn head(request: &str) -> Result<RequestHead, HttpError> {
+        let (head, _) = RequestHeadReader::default()
+            .read(request.as_bytes())?
+            .expect("complete head");
+        Ok(head)
+    }
+
+    #[test]
+    fn the_head_is_read_over_several_payloads() {
+        let mut reader = RequestHeadReader::default();
+        assert!(reader
+            .read(b"GET /index.html HTTP/1.1\r\nHo")
+            .unwrap()
+            .is_none());
+        let (head, rest) = reader
+            .read(b"st: example.com\r\n\r\nGET /next")
+            .unwrap()
+            .unwrap();
+        assert_eq!(head.method(), "GET");
+        assert_eq!(head.host().unwrap().as_deref(), Some("example.com"));
+        assert_eq!(rest, b"GET /next");
+
+        let mut reader = RequestHeadReader::default();
+        assert_eq!(
+            reader.read(b"GET / HTTP/1.1\r\nHost\r\n\r\n").unwrap_err(),
+            HttpError::BadRequest
+        );
+        let mut reader = RequestHeadReader::default();
+        let long_header = format!("GET / HTTP/1.1\r\nX-Long: {}", "a".repeat(MAX_HEAD_SIZE));
+        assert_eq!(
+            reader.read(long_header.as_bytes()).unwrap_err(),
+            HttpError::HeadersTooLarge
+        );
+    }
+
+    #[test]
+    fn the_host_is_taken_from_the_target_or_the_host_header() {
+        let host = |request: &str| head(request).unwrap().host();
+        assert_eq!(
+            host("GET / HTTP/1.1\r\nHost: Example.COM:8080\r\n\r\n").unwrap(),
+            Some("example.com".to_string())
+        );
+        assert_eq!(
+            host("GET http://api.example.com/users HTTP/1.1\r\nHost: other.com\r\n\r\n").unwrap(),
+            Some("api.example.com".to_string())
+        );
+        assert_eq!(
+            host("GET / HTTP/1.1\r\nHost: [::1]:80\r\n\r\n").unwrap(),
+            Some("::1".to_string())
+        );
+        assert_eq!(host("GET / HTTP/1.0\r\n\r\n").unwrap(), None);
+        assert!(host("GET / HTTP/1.1\r\n\r\n").is_err());
+        assert!(host("GET / HTTP/1.1\r\nHost: a.com\r\nHost: b.com\r\n\r\n").is_err());
+    }
+
+    #[test]
+    fn the_path_is_normalized_before_being_authorized() {
+        let segments = |target: &str| {
+            head(&format!("GET {target} HTTP/1.1\r\nHost: a.com\r\n\r\n"))
+                .unwrap()
+                .path_segments()
+        };
+        assert_eq!(segments("/").unwrap(), Vec::<String>::new());
+        assert_eq!(segments("/api//users/?id=1").unwrap(), vec!["api", "users"]);
+        assert_eq!(segments("/api%2Fadmin").unwrap(), vec!["api", "admin"]);
+        assert_eq!(segments("http://a.com/api").unwrap(), vec!["api"]);
+        assert_eq!(segments("/public/../admin"), Err(HttpError::BadRequest));
+        assert_eq!(segments("/public/%2e%2e/admin"), Err(HttpError::BadRequest));
+        assert_eq!(segments("api"), Err(HttpError::BadRequest));
+        assert_eq!(
+            head("CONNECT a.com:443 HTTP/1.1\r\nHost: a.com\r\n\r\n")
+                .unwrap()
+                .path_segments(),
+            Err(HttpError::MethodNotAllowed)
+        );
+    }
+
+    #[test]
+    fn the_forwarded_head_only_contains_trusted_headers() {
+        let head = head(
+            "POST /api HTTP/1.1\r\n\
+             Host: a.com\r\n\
+             Connection: keep-alive, X-Secret, Content-Length\r\n\
+             X-Secret: 1\r\n\
+             Keep-Alive: timeout=5\r\n\
+             X-Ockam-Identity: Iforged\r\n\
+             x_ockam_attribute_role: admin\r\n\
+             Content-Length: 2\r\n\
+             Accept: */*\r\n\r\n",
+        )
+        .unwrap();
+        let encoded = head.encode(&[(crate::http::IDENTITY_HEADER.into(), "Iabcd".into())]);
+        assert_eq!(
+            String::from_utf8(encoded).unwrap(),
+            "POST /api HTTP/1.1\r\n\
+             Host: a.com\r\n\
+             Content-Length: 2\r\n\
+             Accept: */*\r\n\
+             X-Ockam-Identity: Iabcd\r\n\
+             connection: close\r\n\r\n"
+        );
+    }
+
+    #[test]
+    fn ambiguous_bodies_are_rejected() {
+        let body = |headers: &str| {
+            head(&format!("POST / HTTP/1.1\r\nHost: a.com\r\n{headers}\r\n"))
+                .unwrap()
+                .body_reader()
+                .map(|b| b.is_complete())
+        };
+        assert_eq!(body(""), Ok(true));
+        assert_eq!(body("Content-Length: 3\r\n"), Ok(false));
+        assert_eq!(
+            body("Content-Length: 3\r\nContent-Length: 3\r\n"),
+            Ok(false)
+        );
+        assert!(body("Content-Length: 3\r\nContent-Length: 4\r\n").is_err());
+        assert!(body("Content-Length: +3\r\n").is_err());
+        assert_eq!(body("Transfer-Encoding: gzip, chunked\r\n"), Ok(false));
+        assert!(body("Transfer-Encoding: chunked, gzip\r\n").is_err());
+        assert!(body("Transfer-Encoding: chunked\r\nContent-Length: 3\r\n").is_err());
+    }
+
+    #[test]
+    fn the_end_of_the_body_is_found() {
+        let mut body = BodyReader::Length(5);
+        assert_eq!(body.read(b"abc").unwrap(), 3);
+        assert_eq!(body.read(b"deGET /").unwrap(), 2);
+        assert!(body.is_complete());
+
+        let chunked = b"4;ext=1\r\nWiki\r\n5\r\npedia\r\n0\r\nTrailer: 1\r\n\r\nGET /admin";
+        let end = chunked.len() - b"GET /admin".len();
+        let mut body = BodyReader::Chunked(ChunkState::Size(vec![]));
+        let mut read = 0;
+        for part in chunked.chunks(3) {
+            read += body.read(part).unwrap();
+            if body.is_complete() {
+                break;
+            }
+        }
+        assert_eq!(read, end);
+
+        let mut body = BodyReader::Chunked(ChunkState::Size(vec![]));
+        assert!(body.read(b"4\r\nWikiX\r\n").is_err());
+        let mut body = BodyReader::Chunked(ChunkState::Size(vec![]));
+        assert!(body.read(b"zz\r\n").is_err());
+    }
+}
@@ -0,0 +1,136 @@
+use crate::error::ApiError;
+use crate::http::request::path_segments;
+use crate::nodes::models::portal::HttpRoute;
+use ockam_core::{Address, Result};
+use ockam_node::Context;
+use ockam_transport_tcp::{LoadBalancing, TcpOutletOptions, TcpTransport};
+
+/// Routes the requests of an HTTP outlet to the TCP outlets connecting to its backends
+pub(crate) struct HttpRouter {
+    routes: Vec<RouterEntry>,
+    default_outlet: Address,
+}
+
+struct RouterEntry {
+    host: Option<String>,
+    segments: Vec<String>,
+    outlet: Address,
+}
+
+impl HttpRouter {
+    /// Start a TCP outlet for the default targets and for the targets of each route
+    pub(crate) async fn create(
+        ctx: &Context,
+        tcp_transport: &TcpTransport,
+        targets: Vec<String>,
+        routes: Vec<HttpRoute>,
+        load_balancing: LoadBalancing,
+    ) -> Result<Self> {
+        let mut entries = vec![];
+        for route in routes {
+            let segments = path_segments(&route.path)
+                .map_err(|_| ApiError::core(format!("Invalid path in the HTTP route {route}")))?;
+            if !route.path.starts_with('/') || route.targets.is_empty() {
+                return Err(ApiError::core(format!("Invalid HTTP route {route}")));
+            }
+            entries.push((route, segments));
+        }
+
+        let mut router = Self {
+            routes: vec![],
+            default_outlet: create_backend_outlet(tcp_transport, targets, load_balancing).await?,
+        };
+        for (route, segments) in entries {
+            match create_backend_outlet(tcp_transport, route.targets, load_balancing).await {
+                Ok(outlet) => router.routes.push(RouterEntry {
+                    host: route.host.map(|h| h.to_ascii_lowercase()),
+                    segments,
+                    outlet,
+                }),
+                Err(e) => {
+                    router.stop(ctx).await;
+                    return Err(e);
+                }
+            }
+        }
+        Ok(router)
+    }
+
+    /// Return the outlet of the most specific route matching the host and the path of a
+    /// request. Routes for a host take precedence over the routes for any host.
+    pub(crate) fn route(&self, host: Option<&str>, segments: &[String]) -> &Address {
+        self.routes
+            .iter()
+            .filter(|r| r.host.is_none() || r.host.as_deref() == host)
+            .filter(|r| segments.starts_with(&r.segments))
+            .max_by_key(|r| (r.host.is_some(), r.segments.len()))
+            .map(|r| &r.outlet)
+            .unwrap_or(&self.default_outlet)
+    }
+
+    /// Stop the TCP outlets of the backends
+    pub(crate) async fn stop(&self, ctx: &Context) {
+        let outlets = self.routes.iter().map(|r| &r.outlet);
+        for outlet in outlets.chain([&self.default_outlet]) {
+            if let Err(e) = ctx.stop_worker(outlet.clone()).await {
+                warn!(%outlet, %e, "Failed to stop the outlet of an HTTP backend");
+            }
+        }
+    }
+}
+
+async fn create_backend_outlet(
+    tcp_transport: &TcpTransport,
+    targets: Vec<String>,
+    load_balancing: LoadBalancing,
+) -> Result<Address> {
+    let address = Address::random_tagged("HttpOutlet.backend");
+    tcp_transport
+        .create_outlet_with_targets(
+            address.clone(),
+            targets,
+            TcpOutletOptions::new().with_load_balancing(load_balancing),
+        )
+        .await?;
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(host: Option<&str>, path: &str, outlet: &str) -> RouterEntry {
+        RouterEntry {
+            host: host.map(|h| h.to_string()),
+            segments: path_segments(path).unwrap(),
+            outlet: outlet.into(),
+        }
+    }
+
+    #[test]
+    fn requests_are_sent_to_the_most_specific_route() {
+        let router = HttpRouter {
+            routes: vec![
+                entry(None, "/api", "api"),
+                entry(None, "/api/admin", "admin"),
+                entry(Some("docs.example.com"), "/", "docs"),
+                entry(Some("docs.example.com"), "/api", "docs_api"),
+            ],
+            default_outlet: "default".into(),
+        };
+        let route = |host: Option<&str>, path: &str| {
+            router
+                .route(host, &path_segments(path).unwrap())
+                .address()
+                .to_string()
+        };
+
+        assert_eq!(route(Some("example.com"), "/"), "default");
+        assert_eq!(route(Some("example.com"), "/apis"), "default");
+        assert_eq!(route(Some("example.com"), "/api"), "api");
+        assert_eq!(route(None, "/api/users"), "api");
+        assert_eq!(route(None, "/api/admin/users"), "admin");
+        assert_eq!(route(Some("docs.example.com"), "/guide"), "docs");
+        assert_eq!(route(Some("docs.example.com"), "/api/admin"), "docs_api");
+    }
+}
@@ -0,0 +1,411 @@
+use aes_gcm_siv::aead::{Aead, KeyInit};
+use aes_gcm_siv::{Aes256GcmSiv, Nonce};
+use hmac::{Hmac, Mac};
+use minicbor::{Decode, Encode};
+use ockam_core::compat::collections::HashMap;
+use ockam_core::compat::sync::Mutex;
+use rand::RngCore;
+use sha2::Sha256;
+use std::fmt::{Debug, Display, Formatter};
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+
+/// Topic name used to configure the protection of every topic which is not
+/// explicitly configured
+pub const ANY_TOPIC: &str = "*";
+
+/// Name of the environment variable containing the secret used to derive the data keys of the topics
+pub const OCKAM_KAFKA_PROTECTION_SECRET: &str = "OCKAM_KAFKA_PROTECTION_SECRET";
+
+/// Minimum length of a field protection secret, in bytes
+const MIN_SECRET_LENGTH: usize = 16;
+const DATA_KEY_LENGTH: usize = 32;
+
+/// Nonce used for the deterministic encryption of all the fields
+const DETERMINISTIC_NONCE: [u8; 12] = [0; 12];
+
+/// Describe which fields of the records of a topic are encrypted by a producer.
+///
+/// The record value is encrypted for each consumer with the secure channels.
+/// The record key and the selected headers are encrypted with a data key specific to the topic,
+/// the data key is then encrypted for each consumer with the secure channels.
+/// The encryption of the key and the headers is deterministic: the data key of a topic is derived
+/// from a [`FieldProtectionSecret`] shared by all the producers, so the same key is encrypted
+/// the same way by every producer, even after a restart, and the records of a compacted topic
+/// are still compacted.
+#[derive(Debug, Clone, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct TopicFieldProtection {
+    #[n(1)] topic: String,
+    #[n(2)] value: bool,
+    #[n(3)] key: bool,
+    #[n(4)] headers: Vec<String>,
+}
+
+impl TopicFieldProtection {
+    pub fn new(topic: impl Into<String>, value: bool, key: bool, headers: Vec<String>) -> Self {
+        Self {
+            topic: topic.into(),
+            value,
+            key,
+            headers,
+        }
+    }
+
+    /// Default protection: only the value of the records is encrypted
+    pub fn value_only(topic: impl Into<String>) -> Self {
+        Self::new(topic, true, false, vec![])
+    }
+
+    pub fn topic(&self) -> &str {
+        &self.topic
+    }
+
+    pub fn value(&self) -> bool {
+        self.value
+    }
+
+    pub fn key(&self) -> bool {
+        self.key
+    }
+
+    pub fn headers(&self) -> &Vec<String> {
+        &self.headers
+    }
+
+    /// Return true if fields other than the value need to be encrypted
+    pub(crate) fn protects_key_or_headers(&self) -> bool {
+        self.key || !self.headers.is_empty()
+    }
+}
+
+/// Parse a protection with the format `<topic>:<field>[,<field>...]`
+/// where a field is either `value`, `key` or `header:<name>`.
+/// The topic `*` configures every topic which is not explicitly configured.
+impl FromStr for TopicFieldProtection {
+    type Err = Error;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let (topic, fields) = text.split_once(':').ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                "invalid field protection, expected <topic>:<field>[,<field>...]",
+            )
+        })?;
+        if topic.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "missing topic name"));
+        }
+
+        let mut protection = TopicFieldProtection::new(topic, false, false, vec![]);
+        for field in fields.split(',') {
+            match field {
+                "value" => protection.value = true,
+                "key" => protection.key = true,
+                _ => match field.strip_prefix("header:") {
+                    Some(header) if !header.is_empty() => {
+                        protection.headers.push(header.to_string())
+                    }
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidInput,
+                            format!(
+                                "invalid field `{field}`, expected value, key or header:<name>"
+                            ),
+                        ))
+                    }
+                },
+            }
+        }
+        Ok(protection)
+    }
+}
+
+impl Display for TopicFieldProtection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let mut fields = vec![];
+        if self.value {
+            fields.push("value".to_string());
+        }
+        if self.key {
+            fields.push("key".to_string());
+        }
+        for header in self.headers.iter() {
+            fields.push(format!("header:{header}"));
+        }
+        write!(f, "{}:{}", self.topic, fields.join(","))
+    }
+}
+
+/// Secret shared by the producers of some topics, the data key of each topic is derived from it.
+///
+/// It must be distributed to the producer nodes out of band, for example with the
+/// OCKAM_KAFKA_PROTECTION_SECRET environment variable
+#[derive(Clone, Decode, Encode, PartialEq, Eq)]
+#[cbor(transparent)]
+pub struct FieldProtectionSecret(#[n(0)] String);
+
+impl FieldProtectionSecret {
+    pub fn new(secret: impl Into<String>) -> Result<Self, Error> {
+        let secret = secret.into();
+        if secret.len() < MIN_SECRET_LENGTH {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("the field protection secret must have at least {MIN_SECRET_LENGTH} bytes"),
+            ));
+        }
+        Ok(Self(secret))
+    }
+}
+
+impl Debug for FieldProtectionSecret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FieldProtectionSecret(...)")
+    }
+}
+
+/// Field protections of all the topics, shared by all the connections of a kafka service
+/// along with the data keys used to encrypt the keys and headers of each topic
+pub(crate) struct KafkaFieldProtection {
+    protections: Vec<TopicFieldProtection>,
+    secret: Option<FieldProtectionSecret>,
+    data_keys: Mutex<HashMap<String, DataKey>>,
+}
+
+impl KafkaFieldProtection {
+    pub(crate) fn new(protections: Vec<TopicFieldProtection>) -> Self {
+        Self {
+            protections,
+            secret: None,
+            data_keys: Default::default(),
+        }
+    }
+
+    /// Derive the data keys of the topics from a secret shared by all the producers
+    pub(crate) fn with_secret(mut self, secret: Option<FieldProtectionSecret>) -> Self {
+        self.secret = secret;
+        self
+    }
+
+    /// Return true if the keys or headers of some records must be encrypted with a data key
+    pub(crate) fn needs_data_keys(&self) -> bool {
+        self.protections.iter().any(|p| p.protects_key_or_headers())
+    }
+
+    /// Return the protection configured for a topic, or for any topic, or the
+    /// default protection which only encrypts the record value
+    pub(crate) fn for_topic(&self, topic: &str) -> TopicFieldProtection {
+        self.protections
+            .iter()
+            .find(|p| p.topic == topic)
+            .or_else(|| self.protections.iter().find(|p| p.topic == ANY_TOPIC))
+            .map(|p| TopicFieldProtection {
+                topic: topic.to_string(),
+                ..p.clone()
+            })
+            .unwrap_or_else(|| TopicFieldProtection::value_only(topic))
+    }
+
+    /// Return the data key of a topic. It is derived from the shared secret if there is one,
+    /// otherwise it is generated the first time and only lives as long as this service
+    pub(crate) fn data_key_for(&self, topic: &str) -> DataKey {
+        self.data_keys
+            .lock()
+            .unwrap()
+            .entry(topic.to_string())
+            .or_insert_with(|| match &self.secret {
+                Some(secret) => DataKey::derive_for_topic(secret, topic),
+                None => DataKey::generate(),
+            })
+            .clone()
+    }
+}
+
+impl Default for KafkaFieldProtection {
+    fn default() -> Self {
+        Self::new(vec![])
+    }
+}
+
+/// Symmetric key used to deterministically encrypt the keys and headers of a topic.
+///
+/// The fields are encrypted with AES-256-GCM-SIV (RFC 8452) and a fixed nonce. AES-GCM-SIV
+/// resists nonce reuse: equal plaintexts give equal ciphertexts, and nothing else is revealed
+/// about the plaintexts.
+#[derive(Clone)]
+pub(crate) struct DataKey([u8; DATA_KEY_LENGTH]);
+
+impl DataKey {
+    pub(crate) fn generate() -> Self {
+        let mut key = [0u8; DATA_KEY_LENGTH];
+        rand::thread_rng().fill_bytes(&mut key);
+        Self(key)
+    }
+
+    /// Derive the data key of a topic from a shared secret
+    pub(crate) fn derive_for_topic(secret: &FieldProtectionSecret, topic: &str) -> Self {
+        let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(secret.0.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(b"ockam kafka topic data key");
+        mac.update(topic.as_bytes());
+        Self(mac.finalize().into_bytes().into())
+    }
+
+    pub(crate) fn from_bytes(bytes: &[u8]) -> Result<Self, Error> {
+        let key = bytes
+            .try_into()
+            .map_err(|_| Error::new(ErrorKind::InvalidData, "invalid data key length"))?;
+        Ok(Self(key))
+    }
+
+    pub(crate) fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    fn cipher(&self) -> Aes256GcmSiv {
+        Aes256GcmSiv::new_from_slice(&self.0).expect("the data key has the AES-256 key length")
+    }
+
+    /// Encrypt the plaintext, the ciphertext is followed by the authentication tag
+    pub(crate) fn encrypt_deterministic(&self, plaintext: &[u8]) -> Vec<u8> {
+        self.cipher()
+            .encrypt(Nonce::from_slice(&DETERMINISTIC_NONCE), plaintext)
+            .expect("AES-GCM-SIV encryption only fails for very large plaintexts")
+    }
+
+    pub(crate) fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>, Error> {
+        self.cipher()
+            .decrypt(Nonce::from_slice(&DETERMINISTIC_NONCE), ciphertext)
+            .map_err(|_| Error::new(ErrorKind::InvalidData, "cannot decrypt the field"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_field_protection() {
+        let protection: TopicFieldProtection =
+            "orders:value,key,header:customer-id".parse().unwrap();
+        assert_eq!(
+            protection,
+            TopicFieldProtection::new("orders", true, true, vec!["customer-id".to_string()])
+        );
+        assert_eq!(
+            protection.to_string(),
+            "orders:value,key,header:customer-id"
+        );
+
+        assert!("orders".parse::<TopicFieldProtection>().is_err());
+        assert!(":key".parse::<TopicFieldProtection>().is_err());
+        assert!("orders:keys".parse::<TopicFieldProtection>().is_err());
+        assert!("orders:header:".parse::<TopicFieldProtection>().is_err());
+    }
+
+    #[test]
+    fn protection_for_topic() {
+        let protections = KafkaFieldProtection::new(vec![
+            TopicFieldProtection::new("orders", true, true, vec![]),
+            TopicFieldProtection::new(ANY_TOPIC, false, true, vec![]),
+        ]);
+        assert!(protections.for_topic("orders").value());
+        assert!(!protections.for_topic("payments").value());
+        assert_eq!(protections.for_topic("payments").topic(), "payments");
+
+        let protections = KafkaFieldProtection::default();
+        assert_eq!(
+            protections.for_topic("orders"),
+            TopicFieldProtection::value_only("orders")
+        );
+    }
+
+    #[test]
+    fn data_keys_derived_from_a_secret() {
+        assert!(FieldProtectionSecret::new("too short").is_err());
+        let secret = FieldProtectionSecret::new("a secret shared by the producers").unwrap();
+        assert_eq!(format!("{secret:?}"), "FieldProtectionSecret(...)");
+
+        let protections = || vec![TopicFieldProtection::new(ANY_TOPIC, false, true, vec![])];
+        let producer1 = KafkaFieldProtection::new(protections()).with_secret(Some(secret.clone()));
+        let producer2 = KafkaFieldProtection::new(protections()).with_secret(Some(secret));
+        assert!(producer1.needs_data_keys());
+        assert_eq!(
+            producer1.data_key_for("orders").as_bytes(),
+            producer2.data_key_for("orders").as_bytes()
+        );
+        assert_ne!(
+            producer1.data_key_for("orders").as_bytes(),
+            producer1.data_key_for("payments").as_bytes()
+        );
+
+        // without a secret each service has its own keys
+        let producer3 = KafkaFieldProtection::new(protections());
+        assert_ne!(
+            producer1.data_key_for("orders").as_bytes(),
+            producer3.data_key_for("orders").as_bytes()
+        );
+        assert!(!KafkaFieldProtection::default().needs_data_keys());
+    }
+
+    #[test]
+    fn deterministic_encryption() {
+        let data_key = DataKey::generate();
+        let ciphertext1 = data_key.encrypt_deterministic(b"customer-1");
+        let ciphertext2 = data_key.encrypt_deterministic(b"customer-1");
+        let ciphertext3 = data_key.encrypt_deterministic(b"customer-2");
+        assert_eq!(ciphertext1, ciphertext2);
+        assert_ne!(ciphertext1, ciphertext3);
+        assert_eq!(data_key.decrypt(&ciphertext1).unwrap(), b"customer-1");
+
+        let other_data_key = DataKey::generate();
+        assert_ne!(
+            other_data_key.encrypt_deterministic(b"customer-1"),
+            ciphertext1
+        );
+        assert!(other_data_key.decrypt(&ciphertext1).is_err());
+    }
+
+    #[test]
+    fn deterministic_encryption_known_vectors() {
+        // RFC 8452, Appendix C.2
+        let data_key =
+            DataKey::from_bytes(&hex::decode(format!("01{}", "00".repeat(31))).unwrap()).unwrap();
+        let nonce = hex::decode("030000000000000000000000").unwrap();
+        for (plaintext, ciphertext) in [
+            ("", "07f5f4169bbf55a8400cd47ea6fd400f"),
+            (
+                "0100000000000000",
+                "c2ef328e5c71c83b843122130f7364b761e0b97427e3df28",
+            ),
+            (
+                "01000000000000000000000000000000",
+                "85a01b63025ba19b7fd3ddfc033b3e76c9eac6fa700942702e90862383c6c366",
+            ),
+        ] {
+            assert_eq!(
+                data_key
+                    .cipher()
+                    .encrypt(
+                        Nonce::from_slice(&nonce),
+                        hex::decode(plaintext).unwrap().as_slice()
+                    )
+                    .unwrap(),
+                hex::decode(ciphertext).unwrap()
+            );
+        }
+
+        // The fields are encrypted with the fixed nonce, the ciphertext is not allowed to change
+        // since the records of a compacted topic must keep the same encrypted keys
+        let data_key = DataKey::from_bytes(&(0..32).collect::<Vec<u8>>()).unwrap();
+        let ciphertext =
+            hex::decode("38d9313ace5b3c5e656a9d4a1baa3ea321b62024d32327838133").unwrap();
+        assert_eq!(data_key.encrypt_deterministic(b"customer-1"), ciphertext);
+        assert_eq!(data_key.decrypt(&ciphertext).unwrap(), b"customer-1");
+
+        let mut tampered = ciphertext;
+        tampered[0] ^= 1;
+        assert!(data_key.decrypt(&tampered).is_err());
+        assert!(data_key.decrypt(&[]).is_err());
+    }
+}
@@ -39,12 +39,15 @@ mod test {
     use ockam_node::compat::tokio;
     use ockam_transport_tcp::{TcpInletOptions, TcpOutletOptions};
 
+    use ockam_abac::expr::{eq, ident, str};
+    use ockam_abac::Policy;
+
     use crate::hop::Hop;
     use crate::kafka::protocol_aware::utils::{encode_request, encode_response};
     use crate::kafka::secure_channel_map::RelayCreator;
     use crate::kafka::{
-        ConsumerNodeAddr, KafkaInletController, KafkaPortalListener,
-        KafkaSecureChannelControllerImpl,
+        kafka_topic_resource, ConsumerNodeAddr, KafkaInletController, KafkaPortalListener,
+        KafkaSecureChannelControllerImpl, KafkaTopicAccessControl, PRODUCE,
     };
     use crate::test_utils::NodeManagerHandle;
 
@@ -70,10 +73,15 @@ mod test {
         handler: &NodeManagerHandle,
         listener_address: Address,
         outlet_address: Address,
+        consumer_ids: Vec<String>,
+        topic_access_control: Option<KafkaTopicAccessControl>,
     ) -> ockam::Result<u16> {
         let secure_channel_controller = KafkaSecureChannelControllerImpl::new_extended(
             handler.secure_channels.clone(),
-            ConsumerNodeAddr::Relay(MultiAddr::try_from("/service/api")?),
+            ConsumerNodeAddr::Relay {
+                orchestrator_multiaddr: MultiAddr::try_from("/service/api")?,
+                consumer_ids,
+            },
             Some(HopRelayCreator {}),
             "test_trust_context_id".to_string(),
         );
@@ -102,6 +110,8 @@ mod test {
             context,
             inlet_controller,
             secure_channel_controller.into_trait(),
+            Default::default(),
+            topic_access_control,
             listener_address,
         )
         .await?;
@@ -121,6 +131,8 @@ mod test {
             &handler,
             "kafka_consumer_listener".into(),
             "kafka_consumer_outlet".into(),
+            vec![],
+            None,
         )
         .await?;
 
@@ -129,6 +141,8 @@ mod test {
             &handler,
             "kafka_producer_listener".into(),
             "kafka_producer_outlet".into(),
+            vec![],
+            None,
         )
         .await?;
 
@@ -239,6 +253,214 @@ mod test {
         Ok(())
     }
 
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 60_000)]
+    async fn producer__flow_with_several_consumers__content_encrypted_for_each_consumer(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let handler = crate::util::test_utils::start_manager_for_tests(context).await?;
+        let consumer_ids = ["consumer_a", "consumer_b"];
+
+        let mut consumer_bootstrap_ports = vec![];
+        for consumer_id in consumer_ids {
+            let consumer_bootstrap_port = create_kafka_service(
+                context,
+                &handler,
+                format!("kafka_{consumer_id}_listener").into(),
+                format!("kafka_{consumer_id}_outlet").into(),
+                vec![consumer_id.to_string()],
+                None,
+            )
+            .await?;
+
+            //each consumer creates its own relay for the partition 1 of 'my-topic'
+            let mut consumer_mock_kafka = TcpServerSimulator::start("127.0.0.1:0").await;
+            handler
+                .tcp
+                .create_outlet(
+                    format!("kafka_{consumer_id}_outlet"),
+                    format!("127.0.0.1:{}", consumer_mock_kafka.port),
+                    TcpOutletOptions::new(),
+                )
+                .await?;
+
+            simulate_first_kafka_consumer_empty_reply_and_ignore_result(
+                consumer_bootstrap_port,
+                &mut consumer_mock_kafka,
+            )
+            .await;
+            drop(consumer_mock_kafka);
+            context
+                .stop_worker(format!("kafka_{consumer_id}_outlet"))
+                .await?;
+            consumer_bootstrap_ports.push(consumer_bootstrap_port);
+        }
+
+        let producer_bootstrap_port = create_kafka_service(
+            context,
+            &handler,
+            "kafka_producer_listener".into(),
+            "kafka_producer_outlet".into(),
+            consumer_ids.iter().map(|id| id.to_string()).collect(),
+            None,
+        )
+        .await?;
+
+        let mut producer_mock_kafka = TcpServerSimulator::start("127.0.0.1:0").await;
+        handler
+            .tcp
+            .create_outlet(
+                "kafka_producer_outlet",
+                format!("127.0.0.1:{}", producer_mock_kafka.port),
+                TcpOutletOptions::new(),
+            )
+            .await?;
+        let request = simulate_kafka_producer_and_read_request(
+            producer_bootstrap_port,
+            &mut producer_mock_kafka,
+        )
+        .await;
+
+        let encrypted_body = request
+            .topic_data
+            .get(&TopicName::from(StrBytes::from_str("my-topic-name")))
+            .unwrap()
+            .partition_data
+            .get(0)
+            .unwrap()
+            .records
+            .as_ref()
+            .unwrap();
+        let mut encrypted_body = BytesMut::from(encrypted_body.as_ref());
+        let records = RecordBatchDecoder::decode(&mut encrypted_body).unwrap();
+
+        //the wrapper contains the other envelopes field only when there is more than
+        //one consumer
+        let wrapper = records.get(0).unwrap().value.as_ref().unwrap();
+        assert_eq!(
+            minicbor::Decoder::new(wrapper.as_ref()).map().unwrap(),
+            Some(3)
+        );
+
+        //both consumers run on the same node and share their secure channels, the record
+        //can then only be fetched once since decrypting it twice is detected as a replay
+        let mut consumer_mock_kafka = TcpServerSimulator::start("127.0.0.1:0").await;
+        handler
+            .tcp
+            .create_outlet(
+                "kafka_consumer_b_outlet",
+                format!("127.0.0.1:{}", consumer_mock_kafka.port),
+                TcpOutletOptions::new(),
+            )
+            .await?;
+
+        let plain_fetch_response = simulate_kafka_consumer_and_read_response(
+            consumer_bootstrap_ports[1],
+            &mut consumer_mock_kafka,
+            &request,
+        )
+        .await;
+
+        let plain_content = plain_fetch_response.responses[0].partitions[0]
+            .records
+            .as_ref()
+            .unwrap();
+        let mut plain_content = BytesMut::from(plain_content.as_ref());
+        let records = RecordBatchDecoder::decode(&mut plain_content).unwrap();
+
+        assert_eq!(
+            records.get(0).as_ref().unwrap().value.as_ref().unwrap(),
+            "hello world!".as_bytes()
+        );
+
+        context.stop().await?;
+        consumer_mock_kafka.destroy_and_wait().await;
+        producer_mock_kafka.destroy_and_wait().await;
+        Ok(())
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 60_000)]
+    async fn producer__topic_policy_on_credential_attribute__records_forwarded(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let handler = crate::util::test_utils::start_manager_for_tests(context).await?;
+
+        // the attribute is only attested by the credential of the node: the attributes
+        // stored with the named credential of the test node manager are removed, as for a
+        // node retrieving its credential from a remote authority
+        let identifier = handler.node_manager.identifier();
+        let identity_attributes = handler
+            .secure_channels
+            .identities()
+            .identity_attributes_repository();
+        identity_attributes.delete(&identifier).await?;
+        assert!(identity_attributes
+            .get_attributes(&identifier)
+            .await?
+            .is_none());
+        handler
+            .cli_state
+            .set_policy(
+                &kafka_topic_resource("my-topic-name"),
+                &PRODUCE,
+                &Policy::new(eq([
+                    ident("subject.trust_context_id"),
+                    str("test_trust_context_id"),
+                ])),
+            )
+            .await?;
+
+        let topic_access_control = handler
+            .node_manager
+            .kafka_topic_access_control("test_trust_context_id")
+            .await?;
+        let producer_bootstrap_port = create_kafka_service(
+            context,
+            &handler,
+            "kafka_producer_listener".into(),
+            "kafka_producer_outlet".into(),
+            vec![],
+            Some(topic_access_control),
+        )
+        .await?;
+
+        let mut producer_mock_kafka = TcpServerSimulator::start("127.0.0.1:0").await;
+        handler
+            .tcp
+            .create_outlet(
+                "kafka_producer_outlet",
+                format!("127.0.0.1:{}", producer_mock_kafka.port),
+                TcpOutletOptions::new(),
+            )
+            .await?;
+        let request = simulate_kafka_producer_and_read_request(
+            producer_bootstrap_port,
+            &mut producer_mock_kafka,
+        )
+        .await;
+
+        // a denied topic would have been removed from the request
+        let encrypted_body = request
+            .topic_data
+            .get(&TopicName::from(StrBytes::from_str("my-topic-name")))
+            .unwrap()
+            .partition_data[0]
+            .records
+            .as_ref()
+            .unwrap();
+        let mut encrypted_body = BytesMut::from(encrypted_body.as_ref());
+        let records = RecordBatchDecoder::decode(&mut encrypted_body).unwrap();
+        assert_ne!(
+            records[0].value.as_ref().unwrap(),
+            "hello world!".as_bytes()
+        );
+
+        context.stop().await?;
+        producer_mock_kafka.destroy_and_wait().await;
+        Ok(())
+    }
+
     async fn simulate_kafka_producer_and_read_request(
         producer_bootstrap_port: u16,
         producer_mock_kafka: &mut TcpServerSimulator,
@@ -1,6 +1,7 @@
 //!This service allows encrypted transparent communication from the kafka producer
 //! to the kafka consumer without any modification in the existing application.
 
+mod field_protection;
 mod inlet_controller;
 mod integration_test;
 mod length_delimited;
@@ -10,7 +11,12 @@ mod portal_listener;
 mod portal_worker;
 mod protocol_aware;
 mod secure_channel_map;
+mod topic_access_control;
 
+pub(crate) use field_protection::KafkaFieldProtection;
+pub use field_protection::{
+    FieldProtectionSecret, TopicFieldProtection, ANY_TOPIC, OCKAM_KAFKA_PROTECTION_SECRET,
+};
 pub(crate) use inlet_controller::KafkaInletController;
 use ockam_core::Address;
 pub(crate) use outlet_service::prefix_relay::PrefixRelayService;
@@ -18,6 +24,10 @@ pub(crate) use outlet_service::OutletManagerService;
 pub(crate) use portal_listener::KafkaPortalListener;
 pub(crate) use secure_channel_map::ConsumerNodeAddr;
 pub(crate) use secure_channel_map::KafkaSecureChannelControllerImpl;
+pub(crate) use topic_access_control::KafkaTopicAccessControl;
+pub use topic_access_control::{
+    kafka_topic_resource, CONSUME, KAFKA_TOPIC_RESOURCE_PREFIX, METADATA, PRODUCE,
+};
 
 pub const KAFKA_OUTLET_CONSUMERS: &str = "kafka_consumers";
 pub const KAFKA_OUTLET_INTERCEPTOR_ADDRESS: &str = "kafka_interceptor";
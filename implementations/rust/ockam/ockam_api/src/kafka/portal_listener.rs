@@ -7,6 +7,7 @@ use crate::kafka::inlet_controller::KafkaInletController;
 use crate::kafka::portal_worker::KafkaPortalWorker;
 use crate::kafka::protocol_aware::TopicUuidMap;
 use crate::kafka::secure_channel_map::KafkaSecureChannelController;
+use crate::kafka::{KafkaFieldProtection, KafkaTopicAccessControl};
 
 ///First point of ingress of kafka connections, at the first message it spawns new stateful workers
 /// to take care of the connection.
@@ -14,6 +15,8 @@ pub(crate) struct KafkaPortalListener {
     inlet_controller: KafkaInletController,
     secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
     uuid_to_name: TopicUuidMap,
+    field_protection: Arc<KafkaFieldProtection>,
+    topic_access_control: Option<Arc<KafkaTopicAccessControl>>,
 }
 
 #[ockam::worker]
@@ -48,6 +51,8 @@ impl Worker for KafkaPortalListener {
             self.secure_channel_controller.clone(),
             self.uuid_to_name.clone(),
             self.inlet_controller.clone(),
+            self.field_protection.clone(),
+            self.topic_access_control.clone(),
             None,
             flow_control_id,
             route![inlet_responder_address],
@@ -78,6 +83,8 @@ impl KafkaPortalListener {
         context: &Context,
         inlet_controller: KafkaInletController,
         secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
+        field_protection: KafkaFieldProtection,
+        topic_access_control: Option<KafkaTopicAccessControl>,
         listener_address: Address,
     ) -> ockam_core::Result<()> {
         context
@@ -87,6 +94,8 @@ impl KafkaPortalListener {
                     inlet_controller,
                     secure_channel_controller,
                     uuid_to_name: Default::default(),
+                    field_protection: Arc::new(field_protection),
+                    topic_access_control: topic_access_control.map(Arc::new),
                 },
             )
             .await
@@ -16,7 +16,7 @@ use crate::kafka::inlet_controller::KafkaInletController;
 use crate::kafka::length_delimited::{length_encode, KafkaMessageDecoder};
 use crate::kafka::protocol_aware::{InletInterceptorImpl, KafkaMessageInterceptor, TopicUuidMap};
 use crate::kafka::secure_channel_map::KafkaSecureChannelController;
-use crate::kafka::KAFKA_OUTLET_BOOTSTRAP_ADDRESS;
+use crate::kafka::{KafkaFieldProtection, KafkaTopicAccessControl, KAFKA_OUTLET_BOOTSTRAP_ADDRESS};
 
 ///by default kafka supports up to 1MB messages, 16MB is the maximum suggested
 pub(crate) const MAX_KAFKA_MESSAGE_SIZE: u32 = 16 * 1024 * 1024;
@@ -391,6 +391,8 @@ impl KafkaPortalWorker {
         secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
         uuid_to_name: TopicUuidMap,
         inlet_map: KafkaInletController,
+        field_protection: Arc<KafkaFieldProtection>,
+        topic_access_control: Option<Arc<KafkaTopicAccessControl>>,
         max_kafka_message_size: Option<u32>,
         flow_control_id: Option<FlowControlId>,
         inlet_responder_route: Route,
@@ -399,6 +401,8 @@ impl KafkaPortalWorker {
             secure_channel_controller,
             uuid_to_name,
             inlet_map,
+            field_protection,
+            topic_access_control,
         ));
 
         let requests_worker_address = Address::random_tagged("KafkaPortalWorker.requests");
@@ -744,7 +748,10 @@ mod test {
         let secure_channels = secure_channels().await.unwrap();
         let secure_channel_controller = KafkaSecureChannelControllerImpl::new(
             secure_channels,
-            ConsumerNodeAddr::Relay(MultiAddr::default()),
+            ConsumerNodeAddr::Relay {
+                orchestrator_multiaddr: MultiAddr::default(),
+                consumer_ids: vec![],
+            },
             "test_trust_context_id".to_string(),
         )
         .into_trait();
@@ -754,6 +761,8 @@ mod test {
             secure_channel_controller,
             Default::default(),
             inlet_map,
+            Default::default(),
+            None,
             Some(TEST_MAX_KAFKA_MESSAGE_SIZE),
             None,
             route![context.address()],
@@ -799,7 +808,10 @@ mod test {
 
         let secure_channel_controller = KafkaSecureChannelControllerImpl::new(
             handler.secure_channels.clone(),
-            ConsumerNodeAddr::Relay(MultiAddr::default()),
+            ConsumerNodeAddr::Relay {
+                orchestrator_multiaddr: MultiAddr::default(),
+                consumer_ids: vec![],
+            },
             "test_trust_context_id".to_string(),
         )
         .into_trait();
@@ -816,6 +828,8 @@ mod test {
             secure_channel_controller,
             Default::default(),
             inlet_map.clone(),
+            Default::default(),
+            None,
             None,
             None,
             route![context.address()],
@@ -100,6 +100,7 @@ impl KafkaMessageInterceptor for OutletInterceptorImpl {
                 RequestInfo {
                     request_api_key: ApiKey::MetadataKey,
                     request_api_version: header.request_api_version,
+                    denied_topics: vec![],
                 },
             );
         }
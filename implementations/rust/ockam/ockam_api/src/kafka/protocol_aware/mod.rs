@@ -1,9 +1,10 @@
 use crate::kafka::portal_worker::InterceptError;
-use crate::kafka::secure_channel_map::KafkaSecureChannelController;
-use crate::kafka::KafkaInletController;
+use crate::kafka::secure_channel_map::{KafkaEncryptedContent, KafkaSecureChannelController};
+use crate::kafka::{KafkaFieldProtection, KafkaInletController, KafkaTopicAccessControl};
 use bytes::BytesMut;
-use kafka_protocol::messages::ApiKey;
+use kafka_protocol::messages::{ApiKey, TopicName};
 use minicbor::{Decode, Encode};
+use ockam_abac::Action;
 use ockam_core::compat::{
     collections::HashMap,
     fmt::Debug,
@@ -11,8 +12,10 @@ use ockam_core::compat::{
 };
 use ockam_core::{async_trait, Address};
 use ockam_node::Context;
+use uuid::Uuid;
 
 mod metadata_interceptor;
+mod record_batches;
 mod request;
 mod response;
 mod tests;
@@ -24,6 +27,17 @@ pub(crate) use metadata_interceptor::OutletInterceptorImpl;
 struct RequestInfo {
     pub request_api_key: ApiKey,
     pub request_api_version: i16,
+    ///Topics removed from the request since their access was denied
+    pub denied_topics: Vec<DeniedTopic>,
+}
+
+///Topic removed from a request since the access to the topic was denied,
+///the response is completed with an authorization error for each of its partitions
+#[derive(Clone, Debug)]
+struct DeniedTopic {
+    pub name: TopicName,
+    pub topic_id: Uuid,
+    pub partitions: Vec<i32>,
 }
 
 type CorrelationId = i32;
@@ -53,6 +67,8 @@ pub(crate) struct InletInterceptorImpl {
     uuid_to_name: TopicUuidMap,
     secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
     inlet_map: KafkaInletController,
+    field_protection: Arc<KafkaFieldProtection>,
+    topic_access_control: Option<Arc<KafkaTopicAccessControl>>,
 }
 
 #[async_trait]
@@ -78,9 +94,70 @@ impl KafkaMessageInterceptor for InletInterceptorImpl {
 #[rustfmt::skip]
 #[cbor(map)]
 ///Wraps the content within every record batch
+///The first encrypted content is kept in the first two fields so that a wrapper
+///with a single consumer has the same encoding as before
 struct MessageWrapper {
     #[n(1)] consumer_decryptor_address: Address,
-    #[n(2)] content: Vec<u8>
+    #[n(2)] content: Vec<u8>,
+    ///The same content encrypted for the other consumers
+    #[n(3)] other_envelopes: Option<Vec<MessageEnvelope>>,
+}
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+///Content encrypted for one consumer
+struct MessageEnvelope {
+    #[n(1)] consumer_decryptor_address: Address,
+    #[n(2)] content: Vec<u8>,
+}
+
+impl MessageWrapper {
+    ///Create a wrapper from the content encrypted for each consumer
+    fn new(encrypted_contents: Vec<KafkaEncryptedContent>) -> Option<MessageWrapper> {
+        let mut envelopes = encrypted_contents.into_iter().map(|c| MessageEnvelope {
+            consumer_decryptor_address: c.consumer_decryptor_address,
+            content: c.content,
+        });
+        let first = envelopes.next()?;
+        let others: Vec<MessageEnvelope> = envelopes.collect();
+
+        Some(MessageWrapper {
+            consumer_decryptor_address: first.consumer_decryptor_address,
+            content: first.content,
+            other_envelopes: if others.is_empty() {
+                None
+            } else {
+                Some(others)
+            },
+        })
+    }
+
+    ///Return the content encrypted for every consumer
+    fn into_envelopes(self) -> Vec<MessageEnvelope> {
+        let mut envelopes = vec![MessageEnvelope {
+            consumer_decryptor_address: self.consumer_decryptor_address,
+            content: self.content,
+        }];
+        envelopes.extend(self.other_envelopes.unwrap_or_default());
+        envelopes
+    }
+}
+
+///Name of the record header containing the fields protected with a data key,
+///its value is a wrapper of the encoded `ProtectedFields`
+const PROTECTED_FIELDS_HEADER: &str = "ockam.protected_fields";
+
+#[derive(Debug, Clone, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+///Describes which fields of a record are encrypted, and the data key used
+///to encrypt the key and the headers
+struct ProtectedFields {
+    #[n(1)] data_key: Vec<u8>,
+    #[n(2)] value: bool,
+    #[n(3)] key: bool,
+    #[n(4)] headers: Vec<String>,
 }
 
 impl InletInterceptorImpl {
@@ -88,12 +165,33 @@ impl InletInterceptorImpl {
         secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
         uuid_to_name: TopicUuidMap,
         inlet_map: KafkaInletController,
+        field_protection: Arc<KafkaFieldProtection>,
+        topic_access_control: Option<Arc<KafkaTopicAccessControl>>,
     ) -> InletInterceptorImpl {
         Self {
             request_map: Arc::new(Mutex::new(Default::default())),
             uuid_to_name,
             secure_channel_controller,
             inlet_map,
+            field_protection,
+            topic_access_control,
+        }
+    }
+
+    ///Return true if the action is allowed on the topic,
+    ///every topic can be accessed when there is no access control
+    async fn is_topic_authorized(
+        &self,
+        context: &Context,
+        topic_name: &str,
+        action: &Action,
+    ) -> Result<bool, InterceptError> {
+        match &self.topic_access_control {
+            Some(access_control) => access_control
+                .is_authorized(context, topic_name, action)
+                .await
+                .map_err(InterceptError::Ockam),
+            None => Ok(true),
         }
     }
 }
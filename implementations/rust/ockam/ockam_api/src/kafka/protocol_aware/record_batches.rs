@@ -0,0 +1,357 @@
+use crate::kafka::portal_worker::InterceptError;
+use bytes::{BufMut, BytesMut};
+use kafka_protocol::records::{
+    Compression, Record, RecordBatchDecoder, RecordBatchEncoder, RecordEncodeOptions,
+};
+use std::io::{Error, ErrorKind, Read};
+
+//offsets within a record batch, or within a legacy message set entry
+const LENGTH_OFFSET: usize = 8;
+const MAGIC_BYTE_OFFSET: usize = 16;
+const LEGACY_ATTRIBUTES_OFFSET: usize = 17;
+//offsets within a record batch of version 2
+const CRC_OFFSET: usize = 17;
+const ATTRIBUTES_OFFSET: usize = 21;
+//attributes are two bytes in big endian, the codec is in the second one
+const ATTRIBUTES_LOW_BYTE_OFFSET: usize = 22;
+const RECORDS_OFFSET: usize = 61;
+const COMPRESSION_MASK: u8 = 0x7;
+
+/// The records of a record batch, with the codec used to compress them
+#[derive(Debug)]
+pub(crate) struct RecordBatch {
+    pub(crate) compression: Compression,
+    pub(crate) records: Vec<Record>,
+}
+
+/// Decode the record batches of a produce request or a fetch response.
+///
+/// Each batch keeps its own compression codec, so that it can be encoded again with the
+/// same codec: a fetch response can contain batches produced by clients using different codecs.
+/// The kafka protocol library only supports gzip and snappy, lz4 and zstd batches are
+/// decompressed here before being decoded.
+pub(crate) fn decode_record_batches(content: &[u8]) -> Result<Vec<RecordBatch>, InterceptError> {
+    let mut batches = vec![];
+    let mut offset = 0;
+    while offset < content.len() {
+        let batch = next_batch(&content[offset..])?;
+        offset += batch.len();
+
+        let compression = batch_compression(batch)?;
+        let records = match compression {
+            Compression::Lz4 | Compression::Zstd => {
+                decode_records(&mut decompress_batch(batch, compression)?)?
+            }
+            _ => decode_records(&mut BytesMut::from(batch))?,
+        };
+        batches.push(RecordBatch {
+            compression,
+            records,
+        });
+    }
+    Ok(batches)
+}
+
+/// Encode record batches, each one being compressed with its own codec
+pub(crate) fn encode_record_batches(batches: &[RecordBatch]) -> Result<BytesMut, InterceptError> {
+    let mut encoded = BytesMut::new();
+    for batch in batches {
+        let codec_supported_by_encoder =
+            !matches!(batch.compression, Compression::Lz4 | Compression::Zstd);
+        let mut encoded_batch = BytesMut::new();
+        RecordBatchEncoder::encode(
+            &mut encoded_batch,
+            batch.records.iter(),
+            &RecordEncodeOptions {
+                version: 2,
+                compression: if codec_supported_by_encoder {
+                    batch.compression
+                } else {
+                    Compression::None
+                },
+            },
+        )
+        .map_err(|_| invalid_data())?;
+
+        if codec_supported_by_encoder {
+            encoded.put(encoded_batch);
+        } else {
+            //the encoder can split the records in several batches
+            let mut offset = 0;
+            while offset < encoded_batch.len() {
+                let batch_bytes = next_batch(&encoded_batch[offset..])?;
+                offset += batch_bytes.len();
+                encoded.put(compress_batch(batch_bytes, batch.compression)?);
+            }
+        }
+    }
+    Ok(encoded)
+}
+
+/// Return the bytes of the first batch, or legacy message set entry, of the content
+fn next_batch(content: &[u8]) -> Result<&[u8], InterceptError> {
+    let length = match content.get(LENGTH_OFFSET..LENGTH_OFFSET + 4) {
+        Some(length) => i32::from_be_bytes([length[0], length[1], length[2], length[3]]),
+        None => {
+            warn!("truncated record batch");
+            return Err(invalid_data());
+        }
+    };
+    if length < 0 {
+        warn!("invalid record batch length: {length}");
+        return Err(invalid_data());
+    }
+    content
+        .get(..LENGTH_OFFSET + 4 + length as usize)
+        .ok_or_else(|| {
+            warn!("truncated record batch");
+            invalid_data()
+        })
+}
+
+/// Return the compression codec of a batch.
+/// Legacy message sets can only be decoded when they are compressed with gzip or snappy
+fn batch_compression(batch: &[u8]) -> Result<Compression, InterceptError> {
+    let (attributes, is_legacy) = match batch.get(MAGIC_BYTE_OFFSET) {
+        Some(0) | Some(1) => (batch.get(LEGACY_ATTRIBUTES_OFFSET), true),
+        Some(2) if batch.len() >= RECORDS_OFFSET => (batch.get(ATTRIBUTES_LOW_BYTE_OFFSET), false),
+        _ => {
+            warn!("cannot read the version of a record batch");
+            return Err(invalid_data());
+        }
+    };
+    let attributes = attributes.ok_or_else(|| {
+        warn!("truncated record batch");
+        invalid_data()
+    })?;
+
+    match (attributes & COMPRESSION_MASK, is_legacy) {
+        (0, _) => Ok(Compression::None),
+        (1, _) => Ok(Compression::Gzip),
+        (2, _) => Ok(Compression::Snappy),
+        (3, false) => Ok(Compression::Lz4),
+        (4, false) => Ok(Compression::Zstd),
+        (codec, _) => {
+            warn!("unsupported record batch compression codec: {codec}");
+            Err(invalid_data())
+        }
+    }
+}
+
+fn decode_records(batch: &mut BytesMut) -> Result<Vec<Record>, InterceptError> {
+    RecordBatchDecoder::decode(batch).map_err(|_| invalid_data())
+}
+
+/// Return a copy of a compressed batch where the records are decompressed
+fn decompress_batch(batch: &[u8], compression: Compression) -> Result<BytesMut, InterceptError> {
+    let (header, records) = batch.split_at(RECORDS_OFFSET);
+    let mut decompressed = vec![];
+    let result = match compression {
+        Compression::Lz4 => {
+            lz4_flex::frame::FrameDecoder::new(records).read_to_end(&mut decompressed)
+        }
+        Compression::Zstd => zstd::stream::Decoder::new(records)
+            .and_then(|mut decoder| decoder.read_to_end(&mut decompressed)),
+        _ => Ok(decompressed.len()),
+    };
+    if let Err(e) = result {
+        warn!("cannot decompress a {compression:?} record batch: {e}");
+        return Err(invalid_data());
+    }
+    Ok(rewrite_batch(header, &decompressed, Compression::None))
+}
+
+/// Return a copy of an uncompressed batch where the records are compressed with the given codec
+fn compress_batch(batch: &[u8], compression: Compression) -> Result<BytesMut, InterceptError> {
+    let (header, records) = batch.split_at(RECORDS_OFFSET);
+    let compressed = match compression {
+        Compression::Lz4 => {
+            let mut encoder = lz4_flex::frame::FrameEncoder::new(vec![]);
+            std::io::Write::write_all(&mut encoder, records)
+                .map_err(InterceptError::Io)
+                .and_then(|_| {
+                    encoder.finish().map_err(|e| {
+                        warn!("cannot compress a record batch with lz4: {e}");
+                        invalid_data()
+                    })
+                })?
+        }
+        Compression::Zstd => zstd::stream::encode_all(records, zstd::DEFAULT_COMPRESSION_LEVEL)
+            .map_err(InterceptError::Io)?,
+        _ => records.to_vec(),
+    };
+    Ok(rewrite_batch(header, &compressed, compression))
+}
+
+/// Create a batch from the header of another batch and new records.
+/// The codec, the batch length and the CRC are updated accordingly
+fn rewrite_batch(header: &[u8], records: &[u8], compression: Compression) -> BytesMut {
+    let mut batch = BytesMut::with_capacity(header.len() + records.len());
+    batch.put(header);
+    batch.put(records);
+
+    let length = (batch.len() - LENGTH_OFFSET - 4) as i32;
+    batch[LENGTH_OFFSET..LENGTH_OFFSET + 4].copy_from_slice(&length.to_be_bytes());
+    batch[ATTRIBUTES_LOW_BYTE_OFFSET] =
+        (batch[ATTRIBUTES_LOW_BYTE_OFFSET] & !COMPRESSION_MASK) | compression as u8;
+    let crc = crc32c::crc32c(&batch[ATTRIBUTES_OFFSET..]);
+    batch[CRC_OFFSET..CRC_OFFSET + 4].copy_from_slice(&crc.to_be_bytes());
+    batch
+}
+
+fn invalid_data() -> InterceptError {
+    InterceptError::Io(Error::from(ErrorKind::InvalidData))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bytes::Bytes;
+    use kafka_protocol::records::TimestampType;
+
+    fn records(values: &[&str]) -> Vec<Record> {
+        values
+            .iter()
+            .enumerate()
+            .map(|(offset, value)| Record {
+                transactional: false,
+                control: false,
+                partition_leader_epoch: 0,
+                producer_id: 0,
+                producer_epoch: 0,
+                timestamp_type: TimestampType::Creation,
+                offset: offset as i64,
+                sequence: offset as i32,
+                timestamp: 0,
+                key: None,
+                value: Some(Bytes::from(value.to_string())),
+                headers: Default::default(),
+            })
+            .collect()
+    }
+
+    fn values(batch: &RecordBatch) -> Vec<Bytes> {
+        batch
+            .records
+            .iter()
+            .map(|record| record.value.clone().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn record_batches_keep_their_codec() {
+        for compression in [
+            Compression::None,
+            Compression::Gzip,
+            Compression::Snappy,
+            Compression::Lz4,
+            Compression::Zstd,
+        ] {
+            let batch = RecordBatch {
+                compression,
+                records: records(&["first order", "second order"]),
+            };
+            let encoded = encode_record_batches(&[batch]).unwrap();
+            let decoded = decode_record_batches(&encoded).unwrap();
+
+            assert_eq!(decoded.len(), 1);
+            assert_eq!(decoded[0].compression, compression);
+            assert_eq!(
+                values(&decoded[0]),
+                vec![Bytes::from("first order"), Bytes::from("second order")]
+            );
+        }
+    }
+
+    #[test]
+    fn lz4_batch_uses_the_lz4_frame_format() {
+        let batch = RecordBatch {
+            compression: Compression::Lz4,
+            records: records(&["first order"]),
+        };
+        let encoded = encode_record_batches(&[batch]).unwrap();
+
+        assert_eq!(encoded[ATTRIBUTES_LOW_BYTE_OFFSET] & COMPRESSION_MASK, 3);
+        // magic number of an lz4 frame, in little endian
+        assert_eq!(
+            &encoded[RECORDS_OFFSET..RECORDS_OFFSET + 4],
+            &[0x04, 0x22, 0x4d, 0x18]
+        );
+    }
+
+    #[test]
+    fn zstd_batch_uses_the_zstd_frame_format() {
+        let batch = RecordBatch {
+            compression: Compression::Zstd,
+            records: records(&["first order"]),
+        };
+        let encoded = encode_record_batches(&[batch]).unwrap();
+
+        assert_eq!(encoded[ATTRIBUTES_LOW_BYTE_OFFSET] & COMPRESSION_MASK, 4);
+        // magic number of a zstd frame, in little endian
+        assert_eq!(
+            &encoded[RECORDS_OFFSET..RECORDS_OFFSET + 4],
+            &[0x28, 0xb5, 0x2f, 0xfd]
+        );
+    }
+
+    #[test]
+    fn mixed_codecs_are_decoded_batch_by_batch() {
+        let batches = vec![
+            RecordBatch {
+                compression: Compression::Gzip,
+                records: records(&["gzip"]),
+            },
+            RecordBatch {
+                compression: Compression::Lz4,
+                records: records(&["lz4"]),
+            },
+            RecordBatch {
+                compression: Compression::None,
+                records: records(&["none"]),
+            },
+            RecordBatch {
+                compression: Compression::Zstd,
+                records: records(&["zstd"]),
+            },
+        ];
+        let encoded = encode_record_batches(&batches).unwrap();
+        let decoded = decode_record_batches(&encoded).unwrap();
+
+        assert_eq!(
+            decoded
+                .iter()
+                .map(|batch| (batch.compression, values(batch)))
+                .collect::<Vec<_>>(),
+            vec![
+                (Compression::Gzip, vec![Bytes::from("gzip")]),
+                (Compression::Lz4, vec![Bytes::from("lz4")]),
+                (Compression::None, vec![Bytes::from("none")]),
+                (Compression::Zstd, vec![Bytes::from("zstd")]),
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_codec_is_rejected() {
+        let batch = RecordBatch {
+            compression: Compression::None,
+            records: records(&["hello world!"]),
+        };
+        let mut encoded = encode_record_batches(&[batch]).unwrap();
+        encoded[ATTRIBUTES_LOW_BYTE_OFFSET] |= 5;
+        assert!(decode_record_batches(&encoded).is_err());
+    }
+
+    #[test]
+    fn corrupted_lz4_batch_is_rejected() {
+        let batch = RecordBatch {
+            compression: Compression::Lz4,
+            records: records(&["hello world!"]),
+        };
+        let mut encoded = encode_record_batches(&[batch]).unwrap();
+        let last = encoded.len() - 1;
+        encoded[last] ^= 0xff;
+        assert!(decode_record_batches(&encoded).is_err());
+    }
+}
@@ -1,22 +1,26 @@
 use bytes::{Bytes, BytesMut};
 use kafka_protocol::messages::fetch_request::FetchRequest;
+use kafka_protocol::messages::metadata_request::MetadataRequest;
 use kafka_protocol::messages::produce_request::ProduceRequest;
 use kafka_protocol::messages::request_header::RequestHeader;
-use kafka_protocol::messages::ApiKey;
+use kafka_protocol::messages::{ApiKey, TopicName};
 use kafka_protocol::protocol::buf::ByteBuf;
-use kafka_protocol::protocol::Decodable;
-use kafka_protocol::records::{
-    Compression, RecordBatchDecoder, RecordBatchEncoder, RecordEncodeOptions,
-};
+use kafka_protocol::protocol::{Decodable, StrBytes};
 use minicbor::encode::Encoder;
 use ockam_node::Context;
 use std::convert::TryFrom;
 use std::io::{Error, ErrorKind};
 use tracing::warn;
+use uuid::Uuid;
 
 use crate::kafka::portal_worker::InterceptError;
-use crate::kafka::protocol_aware::utils::{decode_body, encode_request};
-use crate::kafka::protocol_aware::{InletInterceptorImpl, MessageWrapper, RequestInfo};
+use crate::kafka::protocol_aware::record_batches::{decode_record_batches, encode_record_batches};
+use crate::kafka::protocol_aware::utils::{decode_body, encode_request, string_to_str_bytes};
+use crate::kafka::protocol_aware::{
+    DeniedTopic, InletInterceptorImpl, MessageWrapper, ProtectedFields, RequestInfo,
+    PROTECTED_FIELDS_HEADER,
+};
+use crate::kafka::{CONSUME, METADATA, PRODUCE};
 
 impl InletInterceptorImpl {
     ///Parse request and map request <=> response
@@ -69,15 +73,28 @@ impl InletInterceptorImpl {
                     .await;
             }
             ApiKey::FetchKey => {
-                self.handle_fetch_request(context, &mut buffer, &header)
-                    .await?;
+                if let Some(request) = self
+                    .handle_fetch_request(context, &mut buffer, &header)
+                    .await?
+                {
+                    return Ok(request);
+                }
             }
-            ApiKey::MetadataKey | ApiKey::FindCoordinatorKey => {
+            ApiKey::MetadataKey => {
+                if let Some(request) = self
+                    .handle_metadata_request(context, &mut buffer, &header)
+                    .await?
+                {
+                    return Ok(request);
+                }
+            }
+            ApiKey::FindCoordinatorKey => {
                 self.request_map.lock().unwrap().insert(
                     header.correlation_id,
                     RequestInfo {
                         request_api_key: api_key,
                         request_api_version: header.request_api_version,
+                        denied_topics: vec![],
                     },
                 );
             }
@@ -99,13 +116,16 @@ impl InletInterceptorImpl {
         Ok(original)
     }
 
+    ///Returns the request without the denied topics when the access to some topics
+    ///was denied, otherwise the original request can be forwarded
     async fn handle_fetch_request(
         &self,
         context: &mut Context,
         buffer: &mut Bytes,
         header: &RequestHeader,
-    ) -> Result<(), InterceptError> {
-        let request: FetchRequest = decode_body(buffer, header.request_api_version)?;
+    ) -> Result<Option<BytesMut>, InterceptError> {
+        let mut request: FetchRequest = decode_body(buffer, header.request_api_version)?;
+        let mut denied_topics = vec![];
 
         //we intercept every partition interested by the kafka client
         //and create a relay for each
@@ -134,20 +154,118 @@ impl InletInterceptorImpl {
                 .map(|partition| partition.partition)
                 .collect();
 
+            if !self
+                .is_topic_authorized(context, &topic_id, &CONSUME)
+                .await?
+            {
+                denied_topics.push(DeniedTopic {
+                    name: topic.topic.clone(),
+                    topic_id: topic.topic_id,
+                    partitions,
+                });
+                continue;
+            }
+
             self.secure_channel_controller
                 .start_relays_for(context, &topic_id, partitions)
                 .await
                 .map_err(InterceptError::Ockam)?
         }
 
+        let modified_request = if denied_topics.is_empty() {
+            None
+        } else {
+            request.topics.retain(|topic| {
+                !denied_topics
+                    .iter()
+                    .any(|denied| denied.name == topic.topic && denied.topic_id == topic.topic_id)
+            });
+            Some(encode_request(
+                header,
+                &request,
+                header.request_api_version,
+                ApiKey::FetchKey,
+            )?)
+        };
+
         self.request_map.lock().unwrap().insert(
             header.correlation_id,
             RequestInfo {
                 request_api_key: ApiKey::FetchKey,
                 request_api_version: header.request_api_version,
+                denied_topics,
+            },
+        );
+        Ok(modified_request)
+    }
+
+    ///Removes the topics which cannot be described from the request.
+    ///Returns the modified request when some topics were removed
+    async fn handle_metadata_request(
+        &self,
+        context: &mut Context,
+        buffer: &mut Bytes,
+        header: &RequestHeader,
+    ) -> Result<Option<BytesMut>, InterceptError> {
+        let mut request: MetadataRequest = decode_body(buffer, header.request_api_version)?;
+        let mut denied_topics = vec![];
+
+        //an empty list of topics means every topic in the first version
+        //and the response is filtered instead
+        if header.request_api_version > 0 {
+            for topic in request.topics.iter().flatten() {
+                let topic_name = match &topic.name {
+                    Some(name) => Some(name.to_string()),
+                    None => self
+                        .uuid_to_name
+                        .lock()
+                        .unwrap()
+                        .get(&topic.topic_id.to_string())
+                        .cloned(),
+                };
+                if let Some(topic_name) = topic_name {
+                    if !self
+                        .is_topic_authorized(context, &topic_name, &METADATA)
+                        .await?
+                    {
+                        denied_topics.push(DeniedTopic {
+                            name: TopicName(string_to_str_bytes(topic_name.clone())),
+                            topic_id: topic.topic_id,
+                            partitions: vec![],
+                        });
+                    }
+                }
+            }
+        }
+
+        let modified_request = if denied_topics.is_empty() {
+            None
+        } else {
+            if let Some(topics) = request.topics.as_mut() {
+                topics.retain(|topic| {
+                    !denied_topics.iter().any(|denied| {
+                        topic.name.as_ref() == Some(&denied.name)
+                            || (!topic.topic_id.is_nil() && denied.topic_id == topic.topic_id)
+                    })
+                });
+            }
+            Some(encode_request(
+                header,
+                &request,
+                header.request_api_version,
+                ApiKey::MetadataKey,
+            )?)
+        };
+
+        self.request_map.lock().unwrap().insert(
+            header.correlation_id,
+            RequestInfo {
+                request_api_key: ApiKey::MetadataKey,
+                request_api_version: header.request_api_version,
+                denied_topics,
             },
         );
-        Ok(())
+        Ok(modified_request)
     }
 
     async fn handle_produce_request(
@@ -158,57 +276,109 @@ impl InletInterceptorImpl {
     ) -> Result<BytesMut, InterceptError> {
         let mut request: ProduceRequest = decode_body(buffer, header.request_api_version)?;
 
+        //the records of the denied topics are not sent to the broker,
+        //an error is returned for them once the broker responds
+        let mut denied_topics = vec![];
+        for (topic_name, topic) in request.topic_data.iter() {
+            if !self
+                .is_topic_authorized(context, topic_name, &PRODUCE)
+                .await?
+            {
+                denied_topics.push(DeniedTopic {
+                    name: topic_name.clone(),
+                    topic_id: Uuid::nil(),
+                    partitions: topic.partition_data.iter().map(|data| data.index).collect(),
+                });
+            }
+        }
+        if !denied_topics.is_empty() {
+            request
+                .topic_data
+                .retain(|topic_name, _| !denied_topics.iter().any(|d| &d.name == topic_name));
+            //there is no response when the producer doesn't wait for acknowledgements
+            if request.acks != 0 {
+                self.request_map.lock().unwrap().insert(
+                    header.correlation_id,
+                    RequestInfo {
+                        request_api_key: ApiKey::ProduceKey,
+                        request_api_version: header.request_api_version,
+                        denied_topics,
+                    },
+                );
+            }
+        }
+
         //the content can be set in multiple topics and partitions in a single message
         //for each we wrap the content and add the secure channel identifier of
         //the encrypted content
         for (topic_name, topic) in request.topic_data.iter_mut() {
+            let protection = self.field_protection.for_topic(topic_name);
             for data in &mut topic.partition_data {
                 if let Some(content) = data.records.take() {
-                    let mut content = BytesMut::from(content.as_ref());
-                    let mut records = RecordBatchDecoder::decode(&mut content)
-                        .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
-
-                    for record in records.iter_mut() {
-                        if let Some(record_value) = record.value.take() {
-                            let encrypted_content = self
-                                .secure_channel_controller
-                                .encrypt_content_for(
-                                    context,
-                                    topic_name,
-                                    data.index,
-                                    record_value.to_vec(),
-                                )
-                                .await
-                                .map_err(InterceptError::Ockam)?;
-
-                            //TODO: to target multiple consumers we could duplicate
-                            // the content with a dedicated encryption for each consumer
-                            let wrapper = MessageWrapper {
-                                consumer_decryptor_address: encrypted_content
-                                    .consumer_decryptor_address,
-                                content: encrypted_content.content,
+                    //each batch is compressed again with the codec chosen by the client
+                    let mut batches = decode_record_batches(&content)?;
+
+                    for record in batches
+                        .iter_mut()
+                        .flat_map(|batch| batch.records.iter_mut())
+                    {
+                        //the fields protected with a data key are wrapped first so that
+                        //the consumer decrypts the contents in the same order
+                        if protection.protects_key_or_headers() {
+                            let data_key = self.field_protection.data_key_for(topic_name);
+                            let mut protected_fields = ProtectedFields {
+                                data_key: data_key.as_bytes().to_vec(),
+                                value: protection.value(),
+                                key: false,
+                                headers: vec![],
                             };
 
-                            let mut write_buffer = Vec::with_capacity(1024);
-                            let mut encoder = Encoder::new(&mut write_buffer);
-                            encoder.encode(wrapper).map_err(|_err| {
-                                InterceptError::Io(Error::from(ErrorKind::InvalidData))
-                            })?;
+                            if protection.key() {
+                                if let Some(record_key) = record.key.take() {
+                                    record.key =
+                                        Some(data_key.encrypt_deterministic(&record_key).into());
+                                    protected_fields.key = true;
+                                }
+                            }
+
+                            for header_name in protection.headers() {
+                                if let Some(Some(header_value)) =
+                                    record.headers.get_mut(header_name.as_str())
+                                {
+                                    *header_value =
+                                        data_key.encrypt_deterministic(header_value).into();
+                                    protected_fields.headers.push(header_name.clone());
+                                }
+                            }
+
+                            let protected_fields =
+                                minicbor::to_vec(&protected_fields).map_err(|_| {
+                                    InterceptError::Io(Error::from(ErrorKind::InvalidData))
+                                })?;
+                            let wrapper = self
+                                .wrap_content(context, topic_name, data.index, protected_fields)
+                                .await?;
+                            record
+                                .headers
+                                .insert(StrBytes::from_str(PROTECTED_FIELDS_HEADER), Some(wrapper));
+                        }
 
-                            record.value = Some(write_buffer.into());
+                        if protection.value() {
+                            if let Some(record_value) = record.value.take() {
+                                let wrapper = self
+                                    .wrap_content(
+                                        context,
+                                        topic_name,
+                                        data.index,
+                                        record_value.to_vec(),
+                                    )
+                                    .await?;
+                                record.value = Some(wrapper);
+                            }
                         }
                     }
 
-                    let mut encoded = BytesMut::new();
-                    RecordBatchEncoder::encode(
-                        &mut encoded,
-                        records.iter(),
-                        &RecordEncodeOptions {
-                            version: 2,
-                            compression: Compression::None,
-                        },
-                    )
-                    .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+                    let encoded = encode_record_batches(&batches)?;
 
                     data.records = Some(encoded.freeze());
                 }
@@ -222,4 +392,35 @@ impl InletInterceptorImpl {
             ApiKey::ProduceKey,
         )
     }
+
+    ///Encrypts the content for each consumer of the topic partition
+    ///and returns the encoded wrapper
+    async fn wrap_content(
+        &self,
+        context: &mut Context,
+        topic_name: &str,
+        partition_id: i32,
+        content: Vec<u8>,
+    ) -> Result<Bytes, InterceptError> {
+        //the content is duplicated with a dedicated encryption
+        //for each consumer
+        let encrypted_contents = self
+            .secure_channel_controller
+            .encrypt_content_for(context, topic_name, partition_id, content)
+            .await
+            .map_err(InterceptError::Ockam)?;
+
+        let wrapper = MessageWrapper::new(encrypted_contents).ok_or_else(|| {
+            warn!("no consumer to encrypt the record for");
+            InterceptError::Io(Error::from(ErrorKind::InvalidData))
+        })?;
+
+        let mut write_buffer = Vec::with_capacity(1024);
+        let mut encoder = Encoder::new(&mut write_buffer);
+        encoder
+            .encode(wrapper)
+            .map_err(|_err| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+
+        Ok(write_buffer.into())
+    }
 }
@@ -2,24 +2,32 @@ use std::io::{Error, ErrorKind};
 use std::net::SocketAddr;
 
 use bytes::{Bytes, BytesMut};
-use kafka_protocol::messages::fetch_response::FetchResponse;
+use kafka_protocol::messages::fetch_response::{
+    FetchResponse, FetchableTopicResponse, PartitionData,
+};
 use kafka_protocol::messages::find_coordinator_response::FindCoordinatorResponse;
-use kafka_protocol::messages::metadata_response::MetadataResponse;
+use kafka_protocol::messages::metadata_response::{MetadataResponse, MetadataResponseTopic};
+use kafka_protocol::messages::produce_response::{
+    PartitionProduceResponse, ProduceResponse, TopicProduceResponse,
+};
 use kafka_protocol::messages::response_header::ResponseHeader;
 use kafka_protocol::messages::ApiKey;
 use kafka_protocol::protocol::buf::ByteBuf;
 use kafka_protocol::protocol::Decodable;
-use kafka_protocol::records::{
-    Compression, RecordBatchDecoder, RecordBatchEncoder, RecordEncodeOptions,
-};
+use kafka_protocol::ResponseError;
 use minicbor::decode::Decoder;
 use ockam_node::Context;
 use tracing::{trace, warn};
 
+use crate::kafka::field_protection::DataKey;
 use crate::kafka::inlet_controller::KafkaInletController;
 use crate::kafka::portal_worker::InterceptError;
+use crate::kafka::protocol_aware::record_batches::{decode_record_batches, encode_record_batches};
 use crate::kafka::protocol_aware::utils::{decode_body, encode_response, string_to_str_bytes};
-use crate::kafka::protocol_aware::{InletInterceptorImpl, MessageWrapper, RequestInfo};
+use crate::kafka::protocol_aware::{
+    InletInterceptorImpl, MessageWrapper, ProtectedFields, RequestInfo, PROTECTED_FIELDS_HEADER,
+};
+use crate::kafka::METADATA;
 
 impl InletInterceptorImpl {
     pub(crate) async fn intercept_response_impl(
@@ -68,6 +76,10 @@ impl InletInterceptorImpl {
             );
 
             match request_info.request_api_key {
+                ApiKey::ProduceKey => {
+                    return self.handle_produce_response(&mut buffer, &request_info, &header);
+                }
+
                 ApiKey::FetchKey => {
                     return self
                         .handle_fetch_response(context, &mut buffer, &request_info, &header)
@@ -122,6 +134,28 @@ impl InletInterceptorImpl {
     ) -> Result<BytesMut, InterceptError> {
         let mut response: MetadataResponse = decode_body(buffer, request_info.request_api_version)?;
 
+        //the topics which cannot be described are removed from the response, like a broker
+        //does when every topic is requested. The topics explicitly requested were removed
+        //from the request and get an authorization error instead
+        let mut hidden_topics = vec![];
+        for topic_name in response.topics.keys() {
+            if !self
+                .is_topic_authorized(context, topic_name, &METADATA)
+                .await?
+            {
+                hidden_topics.push(topic_name.clone());
+            }
+        }
+        response
+            .topics
+            .retain(|topic_name, _| !hidden_topics.contains(topic_name));
+        for denied_topic in request_info.denied_topics.iter() {
+            let mut topic = MetadataResponseTopic::default();
+            topic.error_code = ResponseError::TopicAuthorizationFailed.code();
+            topic.topic_id = denied_topic.topic_id;
+            response.topics.insert(denied_topic.name.clone(), topic);
+        }
+
         //we need to keep a map of topic uuid to topic name since fetch
         //operations only use uuid
         if request_info.request_api_version >= 10 {
@@ -224,46 +258,85 @@ impl InletInterceptorImpl {
         for response in response.responses.iter_mut() {
             for partition in response.partitions.iter_mut() {
                 if let Some(content) = partition.records.take() {
-                    let mut content = BytesMut::from(content.as_ref());
-                    let mut records = RecordBatchDecoder::decode(&mut content)
-                        .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
-
-                    for record in records.iter_mut() {
-                        if let Some(record_value) = record.value.take() {
-                            let message_wrapper: MessageWrapper =
-                                Decoder::new(record_value.as_ref()).decode().map_err(|_| {
-                                    InterceptError::Io(Error::from(ErrorKind::InvalidData))
-                                })?;
-
-                            let decrypted_content = self
-                                .secure_channel_controller
-                                .decrypt_content_for(
-                                    context,
-                                    &message_wrapper.consumer_decryptor_address,
-                                    message_wrapper.content,
-                                )
-                                .await
-                                .map_err(InterceptError::Ockam)?;
-
-                            record.value = Some(decrypted_content.into());
+                    //each batch is compressed again with its own codec, they can differ in a response
+                    let mut batches = decode_record_batches(&content)?;
+
+                    for record in batches
+                        .iter_mut()
+                        .flat_map(|batch| batch.records.iter_mut())
+                    {
+                        //records without protected fields only have an encrypted value
+                        let protected_fields =
+                            match record.headers.shift_remove(PROTECTED_FIELDS_HEADER) {
+                                Some(Some(wrapper)) => {
+                                    let protected_fields =
+                                        self.unwrap_content(context, &wrapper).await?;
+                                    let protected_fields: ProtectedFields =
+                                        minicbor::decode(&protected_fields).map_err(|_| {
+                                            InterceptError::Io(Error::from(ErrorKind::InvalidData))
+                                        })?;
+                                    Some(protected_fields)
+                                }
+                                _ => None,
+                            };
+
+                        if let Some(protected_fields) = &protected_fields {
+                            let data_key = DataKey::from_bytes(&protected_fields.data_key)
+                                .map_err(InterceptError::Io)?;
+
+                            if protected_fields.key {
+                                if let Some(record_key) = record.key.take() {
+                                    record.key = Some(
+                                        data_key
+                                            .decrypt(&record_key)
+                                            .map_err(InterceptError::Io)?
+                                            .into(),
+                                    );
+                                }
+                            }
+
+                            for header_name in protected_fields.headers.iter() {
+                                if let Some(Some(header_value)) =
+                                    record.headers.get_mut(header_name.as_str())
+                                {
+                                    *header_value = data_key
+                                        .decrypt(header_value)
+                                        .map_err(InterceptError::Io)?
+                                        .into();
+                                }
+                            }
+                        }
+
+                        let value_protected =
+                            protected_fields.as_ref().map(|p| p.value).unwrap_or(true);
+                        if value_protected {
+                            if let Some(record_value) = record.value.take() {
+                                let decrypted_content =
+                                    self.unwrap_content(context, &record_value).await?;
+                                record.value = Some(decrypted_content.into());
+                            }
                         }
                     }
 
-                    let mut encoded = BytesMut::new();
-                    RecordBatchEncoder::encode(
-                        &mut encoded,
-                        records.iter(),
-                        &RecordEncodeOptions {
-                            version: 2,
-                            compression: Compression::None,
-                        },
-                    )
-                    .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+                    let encoded = encode_record_batches(&batches)?;
                     partition.records = Some(encoded.freeze());
                 }
             }
         }
 
+        for denied_topic in request_info.denied_topics.iter() {
+            let mut topic = FetchableTopicResponse::default();
+            topic.topic = denied_topic.name.clone();
+            topic.topic_id = denied_topic.topic_id;
+            for partition_index in denied_topic.partitions.iter() {
+                let mut partition = PartitionData::default();
+                partition.partition_index = *partition_index;
+                partition.error_code = ResponseError::TopicAuthorizationFailed.code();
+                topic.partitions.push(partition);
+            }
+            response.responses.push(topic);
+        }
+
         encode_response(
             header,
             &response,
@@ -271,4 +344,72 @@ impl InletInterceptorImpl {
             ApiKey::FetchKey,
         )
     }
+
+    ///Adds an authorization error for the partitions of the topics which were
+    ///removed from the produce request
+    fn handle_produce_response(
+        &self,
+        buffer: &mut Bytes,
+        request_info: &RequestInfo,
+        header: &ResponseHeader,
+    ) -> Result<BytesMut, InterceptError> {
+        let mut response: ProduceResponse = decode_body(buffer, request_info.request_api_version)?;
+
+        for denied_topic in request_info.denied_topics.iter() {
+            let mut topic = TopicProduceResponse::default();
+            for index in denied_topic.partitions.iter() {
+                let mut partition = PartitionProduceResponse::default();
+                partition.index = *index;
+                partition.error_code = ResponseError::TopicAuthorizationFailed.code();
+                partition.base_offset = -1;
+                topic.partition_responses.push(partition);
+            }
+            response.responses.insert(denied_topic.name.clone(), topic);
+        }
+
+        encode_response(
+            header,
+            &response,
+            request_info.request_api_version,
+            ApiKey::ProduceKey,
+        )
+    }
+
+    ///Decodes the wrapper and decrypts the content encrypted for this consumer
+    async fn unwrap_content(
+        &self,
+        context: &mut Context,
+        wrapper: &[u8],
+    ) -> Result<Vec<u8>, InterceptError> {
+        let message_wrapper: MessageWrapper = Decoder::new(wrapper)
+            .decode()
+            .map_err(|_| InterceptError::Io(Error::from(ErrorKind::InvalidData)))?;
+
+        //the content is encrypted once for each consumer,
+        //we select the one we have a secure channel for
+        let mut envelopes = message_wrapper.into_envelopes();
+        let mut selected = None;
+        for (index, envelope) in envelopes.iter().enumerate() {
+            if self
+                .secure_channel_controller
+                .has_secure_channel_for(&envelope.consumer_decryptor_address)
+                .await
+            {
+                selected = Some(index);
+                break;
+            }
+        }
+        // when no secure channel is known the first envelope is used,
+        // so the decryption reports the missing secure channel
+        let envelope = envelopes.swap_remove(selected.unwrap_or(0));
+
+        self.secure_channel_controller
+            .decrypt_content_for(
+                context,
+                &envelope.consumer_decryptor_address,
+                envelope.content,
+            )
+            .await
+            .map_err(InterceptError::Ockam)
+    }
 }
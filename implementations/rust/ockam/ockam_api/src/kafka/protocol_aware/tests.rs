@@ -1,16 +1,36 @@
 #[cfg(test)]
 mod test {
     use crate::kafka::inlet_controller::KafkaInletController;
+    use crate::kafka::portal_worker::InterceptError;
+    use crate::kafka::protocol_aware::record_batches::{
+        decode_record_batches, encode_record_batches, RecordBatch,
+    };
     use crate::kafka::protocol_aware::utils::{encode_request, encode_response};
-    use crate::kafka::protocol_aware::InletInterceptorImpl;
     use crate::kafka::protocol_aware::KafkaMessageInterceptor;
+    use crate::kafka::protocol_aware::{InletInterceptorImpl, PROTECTED_FIELDS_HEADER};
     use crate::kafka::secure_channel_map::{KafkaEncryptedContent, KafkaSecureChannelController};
+    use crate::kafka::{
+        kafka_topic_resource, FieldProtectionSecret, KafkaFieldProtection, KafkaTopicAccessControl,
+        TopicFieldProtection, ANY_TOPIC, PRODUCE,
+    };
     use crate::port_range::PortRange;
+    use bytes::{Bytes, BytesMut};
+    use indexmap::IndexMap;
+    use kafka_protocol::messages::fetch_request::{FetchPartition, FetchTopic};
+    use kafka_protocol::messages::fetch_response::{FetchableTopicResponse, PartitionData};
+    use kafka_protocol::messages::produce_request::{PartitionProduceData, TopicProduceData};
     use kafka_protocol::messages::ApiKey;
     use kafka_protocol::messages::BrokerId;
+    use kafka_protocol::messages::ProduceResponse;
     use kafka_protocol::messages::{ApiVersionsRequest, MetadataRequest, MetadataResponse};
     use kafka_protocol::messages::{ApiVersionsResponse, RequestHeader, ResponseHeader};
-    use kafka_protocol::protocol::{Builder, StrBytes};
+    use kafka_protocol::messages::{FetchRequest, FetchResponse, ProduceRequest, TopicName};
+    use kafka_protocol::protocol::{Builder, Decodable, StrBytes};
+    use kafka_protocol::records::{Compression, Record, RecordBatchDecoder, TimestampType};
+    use kafka_protocol::ResponseError;
+    use ockam::identity::{Identifier, IdentityAttributesSqlxDatabase};
+    use ockam_abac::expr::{eq, ident, str};
+    use ockam_abac::{Env, PoliciesRepository, Policy, PolicySqlxDatabase};
     use ockam_core::compat::sync::Arc;
     use ockam_core::route;
     use ockam_core::{async_trait, Address};
@@ -27,11 +47,15 @@ mod test {
             _topic_name: &str,
             _partition_id: i32,
             content: Vec<u8>,
-        ) -> ockam_core::Result<KafkaEncryptedContent> {
-            Ok(KafkaEncryptedContent {
+        ) -> ockam_core::Result<Vec<KafkaEncryptedContent>> {
+            Ok(vec![KafkaEncryptedContent {
                 content,
                 consumer_decryptor_address: Address::from_string("arbitrary string"),
-            })
+            }])
+        }
+
+        async fn has_secure_channel_for(&self, _consumer_decryptor_address: &Address) -> bool {
+            true
         }
 
         async fn decrypt_content_for(
@@ -53,6 +77,786 @@ mod test {
         }
     }
 
+    /// Encrypts the content for two consumers, the encryption for a consumer only
+    /// prefixes the content with the consumer decryptor address
+    struct FanOutSecureChannelController {
+        local_consumer: Address,
+    }
+
+    const CONSUMERS: [&str; 2] = ["consumer_a", "consumer_b"];
+
+    #[async_trait]
+    impl KafkaSecureChannelController for FanOutSecureChannelController {
+        async fn encrypt_content_for(
+            &self,
+            _context: &mut Context,
+            _topic_name: &str,
+            _partition_id: i32,
+            content: Vec<u8>,
+        ) -> ockam_core::Result<Vec<KafkaEncryptedContent>> {
+            Ok(CONSUMERS
+                .iter()
+                .map(|consumer| KafkaEncryptedContent {
+                    content: [consumer.as_bytes(), content.as_slice()].concat(),
+                    consumer_decryptor_address: Address::from_string(*consumer),
+                })
+                .collect())
+        }
+
+        async fn has_secure_channel_for(&self, consumer_decryptor_address: &Address) -> bool {
+            consumer_decryptor_address == &self.local_consumer
+        }
+
+        async fn decrypt_content_for(
+            &self,
+            _context: &mut Context,
+            consumer_decryptor_address: &Address,
+            encrypted_content: Vec<u8>,
+        ) -> ockam_core::Result<Vec<u8>> {
+            assert_eq!(consumer_decryptor_address, &self.local_consumer);
+            let prefix = consumer_decryptor_address.address().as_bytes();
+            assert!(encrypted_content.starts_with(prefix));
+            Ok(encrypted_content[prefix.len()..].to_vec())
+        }
+
+        async fn start_relays_for(
+            &self,
+            _context: &mut Context,
+            _topic_id: &str,
+            _partitions: Vec<i32>,
+        ) -> ockam_core::Result<()> {
+            Ok(())
+        }
+    }
+
+    const TEST_KAFKA_API_VERSION: i16 = 12;
+    const TOPIC_NAME: &str = "my-topic-name";
+
+    fn create_interceptor(
+        secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
+        field_protections: Vec<TopicFieldProtection>,
+    ) -> InletInterceptorImpl {
+        create_interceptor_with_access_control(secure_channel_controller, field_protections, None)
+    }
+
+    fn create_interceptor_with_access_control(
+        secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
+        field_protections: Vec<TopicFieldProtection>,
+        topic_access_control: Option<KafkaTopicAccessControl>,
+    ) -> InletInterceptorImpl {
+        create_interceptor_with_field_protection(
+            secure_channel_controller,
+            KafkaFieldProtection::new(field_protections),
+            topic_access_control,
+        )
+    }
+
+    fn create_interceptor_with_field_protection(
+        secure_channel_controller: Arc<dyn KafkaSecureChannelController>,
+        field_protection: KafkaFieldProtection,
+        topic_access_control: Option<KafkaTopicAccessControl>,
+    ) -> InletInterceptorImpl {
+        let inlet_map = KafkaInletController::new(
+            MultiAddr::default(),
+            route![],
+            route![],
+            [127, 0, 0, 1].into(),
+            PortRange::new(0, 0).unwrap(),
+        );
+        InletInterceptorImpl::new(
+            secure_channel_controller,
+            Default::default(),
+            inlet_map,
+            Arc::new(field_protection),
+            topic_access_control.map(Arc::new),
+        )
+    }
+
+    fn create_record(key: Option<&str>, value: &str, headers: &[(&'static str, &str)]) -> Record {
+        Record {
+            transactional: false,
+            control: false,
+            partition_leader_epoch: 0,
+            producer_id: 0,
+            producer_epoch: 0,
+            timestamp_type: TimestampType::Creation,
+            offset: 0,
+            sequence: 0,
+            timestamp: 0,
+            key: key.map(|key| BytesMut::from(key).freeze()),
+            value: Some(BytesMut::from(value).freeze()),
+            headers: headers
+                .iter()
+                .map(|(name, value)| {
+                    (
+                        StrBytes::from_str(name),
+                        Some(BytesMut::from(*value).freeze()),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn encode_records(records: &[Record], compression: Compression) -> Bytes {
+        encode_record_batches(&[RecordBatch {
+            compression,
+            records: records.to_vec(),
+        }])
+        .unwrap()
+        .freeze()
+    }
+
+    /// Sends a produce request with the records through the interceptor and returns
+    /// the records as they would be received by the broker
+    async fn produce(
+        context: &mut Context,
+        interceptor: &InletInterceptorImpl,
+        records: Vec<Record>,
+    ) -> Vec<Record> {
+        let encrypted_records = produce_batch(
+            context,
+            interceptor,
+            encode_records(&records, Compression::None),
+        )
+        .await
+        .unwrap();
+        RecordBatchDecoder::decode(&mut BytesMut::from(encrypted_records.as_ref())).unwrap()
+    }
+
+    /// Sends a produce request with an encoded record batch through the interceptor
+    /// and returns the record batch as it would be received by the broker
+    async fn produce_batch(
+        context: &mut Context,
+        interceptor: &InletInterceptorImpl,
+        records: Bytes,
+    ) -> Result<Bytes, InterceptError> {
+        let topic_name = TopicName::from(StrBytes::from_str(TOPIC_NAME));
+        let encrypted_request = send_produce_request(context, interceptor, records).await?;
+        Ok(encrypted_request
+            .topic_data
+            .get(&topic_name)
+            .unwrap()
+            .partition_data
+            .get(0)
+            .unwrap()
+            .records
+            .clone()
+            .unwrap())
+    }
+
+    /// Sends a produce request with an encoded record batch through the interceptor
+    /// and returns the request as it would be received by the broker
+    async fn send_produce_request(
+        context: &mut Context,
+        interceptor: &InletInterceptorImpl,
+        records: Bytes,
+    ) -> Result<ProduceRequest, InterceptError> {
+        let topic_name = TopicName::from(StrBytes::from_str(TOPIC_NAME));
+        let mut topic_data = IndexMap::new();
+        topic_data.insert(
+            topic_name.clone(),
+            TopicProduceData::builder()
+                .partition_data(vec![PartitionProduceData::builder()
+                    .index(1)
+                    .records(Some(records))
+                    .unknown_tagged_fields(Default::default())
+                    .build()
+                    .unwrap()])
+                .unknown_tagged_fields(Default::default())
+                .build()
+                .unwrap(),
+        );
+
+        let mut encrypted_request = interceptor
+            .intercept_request(
+                context,
+                encode_request(
+                    &RequestHeader::builder()
+                        .request_api_version(TEST_KAFKA_API_VERSION)
+                        .correlation_id(1)
+                        .request_api_key(ApiKey::ProduceKey as i16)
+                        .unknown_tagged_fields(Default::default())
+                        .client_id(None)
+                        .build()
+                        .unwrap(),
+                    &ProduceRequest::builder()
+                        .transactional_id(None)
+                        .acks(1)
+                        .timeout_ms(0)
+                        .topic_data(topic_data)
+                        .unknown_tagged_fields(Default::default())
+                        .build()
+                        .unwrap(),
+                    TEST_KAFKA_API_VERSION,
+                    ApiKey::ProduceKey,
+                )
+                .unwrap(),
+            )
+            .await?;
+
+        RequestHeader::decode(
+            &mut encrypted_request,
+            ApiKey::ProduceKey.request_header_version(TEST_KAFKA_API_VERSION),
+        )
+        .unwrap();
+        Ok(ProduceRequest::decode(&mut encrypted_request, TEST_KAFKA_API_VERSION).unwrap())
+    }
+
+    /// Sends a fetch request through the interceptor, then returns the records of the
+    /// fetch response as they would be received by the consumer
+    async fn fetch(
+        context: &mut Context,
+        interceptor: &InletInterceptorImpl,
+        records: Vec<Record>,
+    ) -> Vec<Record> {
+        let decrypted_records = fetch_batch(
+            context,
+            interceptor,
+            encode_records(&records, Compression::None),
+        )
+        .await;
+        RecordBatchDecoder::decode(&mut BytesMut::from(decrypted_records.as_ref())).unwrap()
+    }
+
+    /// Sends a fetch request through the interceptor, then returns the record batches of
+    /// the fetch response as they would be received by the consumer
+    async fn fetch_batch(
+        context: &mut Context,
+        interceptor: &InletInterceptorImpl,
+        records: Bytes,
+    ) -> Bytes {
+        let topic_name = TopicName::from(StrBytes::from_str(TOPIC_NAME));
+        interceptor
+            .intercept_request(
+                context,
+                encode_request(
+                    &RequestHeader::builder()
+                        .request_api_version(TEST_KAFKA_API_VERSION)
+                        .correlation_id(2)
+                        .request_api_key(ApiKey::FetchKey as i16)
+                        .unknown_tagged_fields(Default::default())
+                        .client_id(None)
+                        .build()
+                        .unwrap(),
+                    &FetchRequest::builder()
+                        .cluster_id(None)
+                        .replica_id(BrokerId::default())
+                        .max_wait_ms(0)
+                        .min_bytes(0)
+                        .max_bytes(0)
+                        .isolation_level(0)
+                        .session_id(0)
+                        .session_epoch(0)
+                        .topics(vec![FetchTopic::builder()
+                            .topic(topic_name.clone())
+                            .topic_id(Default::default())
+                            .partitions(vec![FetchPartition::builder()
+                                .partition(1)
+                                .current_leader_epoch(0)
+                                .fetch_offset(0)
+                                .last_fetched_epoch(0)
+                                .log_start_offset(0)
+                                .partition_max_bytes(0)
+                                .unknown_tagged_fields(Default::default())
+                                .build()
+                                .unwrap()])
+                            .unknown_tagged_fields(Default::default())
+                            .build()
+                            .unwrap()])
+                        .forgotten_topics_data(Default::default())
+                        .rack_id(Default::default())
+                        .unknown_tagged_fields(Default::default())
+                        .build()
+                        .unwrap(),
+                    TEST_KAFKA_API_VERSION,
+                    ApiKey::FetchKey,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        let mut decrypted_response = interceptor
+            .intercept_response(
+                context,
+                encode_response(
+                    &ResponseHeader::builder()
+                        .correlation_id(2)
+                        .unknown_tagged_fields(Default::default())
+                        .build()
+                        .unwrap(),
+                    &FetchResponse::builder()
+                        .throttle_time_ms(Default::default())
+                        .error_code(Default::default())
+                        .session_id(Default::default())
+                        .responses(vec![FetchableTopicResponse::builder()
+                            .topic(topic_name)
+                            .topic_id(Default::default())
+                            .partitions(vec![PartitionData::builder()
+                                .partition_index(1)
+                                .error_code(Default::default())
+                                .high_watermark(Default::default())
+                                .last_stable_offset(Default::default())
+                                .log_start_offset(Default::default())
+                                .diverging_epoch(Default::default())
+                                .current_leader(Default::default())
+                                .snapshot_id(Default::default())
+                                .aborted_transactions(Default::default())
+                                .preferred_read_replica(Default::default())
+                                .records(Some(records))
+                                .unknown_tagged_fields(Default::default())
+                                .build()
+                                .unwrap()])
+                            .unknown_tagged_fields(Default::default())
+                            .build()
+                            .unwrap()])
+                        .unknown_tagged_fields(Default::default())
+                        .build()
+                        .unwrap(),
+                    TEST_KAFKA_API_VERSION,
+                    ApiKey::FetchKey,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+
+        ResponseHeader::decode(
+            &mut decrypted_response,
+            ApiKey::FetchKey.response_header_version(TEST_KAFKA_API_VERSION),
+        )
+        .unwrap();
+        let decrypted_response =
+            FetchResponse::decode(&mut decrypted_response, TEST_KAFKA_API_VERSION).unwrap();
+        decrypted_response.responses[0].partitions[0]
+            .records
+            .clone()
+            .unwrap()
+    }
+
+    fn header<'a>(record: &'a Record, name: &str) -> Option<&'a [u8]> {
+        record
+            .headers
+            .get(name)
+            .and_then(|value| value.as_ref())
+            .map(|value| value.as_ref())
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__produce_for_several_consumers__each_consumer_decrypts_its_envelope(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let producer_interceptor = create_interceptor(
+            Arc::new(FanOutSecureChannelController {
+                local_consumer: Address::from_string("producer"),
+            }),
+            vec![],
+        );
+
+        let encrypted_records = produce(
+            context,
+            &producer_interceptor,
+            vec![create_record(None, "hello world!", &[])],
+        )
+        .await;
+
+        for consumer in CONSUMERS {
+            let consumer_interceptor = create_interceptor(
+                Arc::new(FanOutSecureChannelController {
+                    local_consumer: Address::from_string(consumer),
+                }),
+                vec![],
+            );
+
+            let plain_records =
+                fetch(context, &consumer_interceptor, encrypted_records.clone()).await;
+            assert_eq!(
+                plain_records[0].value.as_ref().unwrap(),
+                "hello world!".as_bytes()
+            );
+        }
+
+        context.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__produce_with_protected_key_and_headers__round_trip(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let producer_interceptor = create_interceptor(
+            Arc::new(FanOutSecureChannelController {
+                local_consumer: Address::from_string("producer"),
+            }),
+            vec![format!("{TOPIC_NAME}:value,key,header:customer-id")
+                .parse()
+                .unwrap()],
+        );
+
+        let records = vec![
+            create_record(
+                Some("customer-1"),
+                "first order",
+                &[("customer-id", "customer-1"), ("source", "web")],
+            ),
+            create_record(
+                Some("customer-1"),
+                "second order",
+                &[("customer-id", "customer-1")],
+            ),
+            create_record(None, "anonymous order", &[]),
+        ];
+        let encrypted_records = produce(context, &producer_interceptor, records.clone()).await;
+
+        // the broker only sees encrypted keys, values and selected headers
+        for (record, encrypted_record) in records.iter().zip(encrypted_records.iter()) {
+            assert_ne!(encrypted_record.value, record.value);
+            if record.key.is_some() {
+                assert_ne!(encrypted_record.key, record.key);
+                assert_ne!(
+                    header(encrypted_record, "customer-id"),
+                    header(record, "customer-id")
+                );
+            }
+            assert_eq!(header(encrypted_record, "source"), header(record, "source"));
+            assert!(header(encrypted_record, PROTECTED_FIELDS_HEADER).is_some());
+        }
+        assert_eq!(encrypted_records[2].key, None);
+
+        // the key is encrypted deterministically to keep the same key for the same customer
+        assert_eq!(encrypted_records[0].key, encrypted_records[1].key);
+        assert_eq!(
+            header(&encrypted_records[0], "customer-id"),
+            header(&encrypted_records[1], "customer-id")
+        );
+
+        for consumer in CONSUMERS {
+            let consumer_interceptor = create_interceptor(
+                Arc::new(FanOutSecureChannelController {
+                    local_consumer: Address::from_string(consumer),
+                }),
+                vec![],
+            );
+
+            let plain_records =
+                fetch(context, &consumer_interceptor, encrypted_records.clone()).await;
+            assert_eq!(plain_records.len(), records.len());
+            for (record, plain_record) in records.iter().zip(plain_records.iter()) {
+                assert_eq!(plain_record.key, record.key);
+                assert_eq!(plain_record.value, record.value);
+                assert_eq!(plain_record.headers, record.headers);
+            }
+        }
+
+        context.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__produce_with_protected_key_only__value_in_plain_text(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let producer_interceptor = create_interceptor(
+            Arc::new(FanOutSecureChannelController {
+                local_consumer: Address::from_string("producer"),
+            }),
+            vec![TopicFieldProtection::new(ANY_TOPIC, false, true, vec![])],
+        );
+
+        let records = vec![create_record(Some("customer-1"), "public content", &[])];
+        let encrypted_records = produce(context, &producer_interceptor, records.clone()).await;
+        assert_eq!(encrypted_records[0].value, records[0].value);
+        assert_ne!(encrypted_records[0].key, records[0].key);
+
+        let consumer_interceptor = create_interceptor(
+            Arc::new(FanOutSecureChannelController {
+                local_consumer: Address::from_string(CONSUMERS[0]),
+            }),
+            vec![],
+        );
+        let plain_records = fetch(context, &consumer_interceptor, encrypted_records).await;
+        assert_eq!(plain_records[0].key, records[0].key);
+        assert_eq!(plain_records[0].value, records[0].value);
+        assert!(plain_records[0].headers.is_empty());
+
+        context.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__two_producers_with_shared_secret__same_encrypted_key(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let secret = FieldProtectionSecret::new("a secret shared by the producers").unwrap();
+        let create_producer = |secret: Option<FieldProtectionSecret>| {
+            create_interceptor_with_field_protection(
+                Arc::new(FanOutSecureChannelController {
+                    local_consumer: Address::from_string("producer"),
+                }),
+                KafkaFieldProtection::new(vec![TopicFieldProtection::new(
+                    ANY_TOPIC,
+                    true,
+                    true,
+                    vec![],
+                )])
+                .with_secret(secret),
+                None,
+            )
+        };
+        // for example two instances of the same application, or a producer which was restarted
+        let producer1 = create_producer(Some(secret.clone()));
+        let producer2 = create_producer(Some(secret));
+        let producer3 = create_producer(None);
+
+        let records = vec![create_record(Some("customer-1"), "first order", &[])];
+        let encrypted1 = produce(context, &producer1, records.clone()).await;
+        let encrypted2 = produce(context, &producer2, records.clone()).await;
+        let encrypted3 = produce(context, &producer3, records.clone()).await;
+
+        // the record keys are encrypted the same way, so a compacted topic stays compacted
+        assert_ne!(encrypted1[0].key, records[0].key);
+        assert_eq!(encrypted1[0].key, encrypted2[0].key);
+        assert_ne!(encrypted1[0].key, encrypted3[0].key);
+
+        let consumer_interceptor = create_interceptor(
+            Arc::new(FanOutSecureChannelController {
+                local_consumer: Address::from_string(CONSUMERS[0]),
+            }),
+            vec![],
+        );
+        for encrypted in [encrypted1, encrypted2] {
+            let plain_records = fetch(context, &consumer_interceptor, encrypted).await;
+            assert_eq!(plain_records[0].key, records[0].key);
+            assert_eq!(plain_records[0].value, records[0].value);
+        }
+
+        context.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__produce_compressed_batch__compression_preserved(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let producer_interceptor = create_interceptor(
+            Arc::new(FanOutSecureChannelController {
+                local_consumer: Address::from_string("producer"),
+            }),
+            vec![],
+        );
+        let consumer_interceptor = create_interceptor(
+            Arc::new(FanOutSecureChannelController {
+                local_consumer: Address::from_string(CONSUMERS[0]),
+            }),
+            vec![],
+        );
+
+        for compression in [
+            Compression::None,
+            Compression::Gzip,
+            Compression::Snappy,
+            Compression::Lz4,
+            Compression::Zstd,
+        ] {
+            let records = vec![
+                create_record(Some("customer-1"), "first order", &[]),
+                create_record(Some("customer-2"), "second order", &[]),
+            ];
+
+            let encrypted_batch = produce_batch(
+                context,
+                &producer_interceptor,
+                encode_records(&records, compression),
+            )
+            .await
+            .unwrap();
+            let encrypted_batches = decode_record_batches(&encrypted_batch).unwrap();
+            assert_eq!(encrypted_batches.len(), 1);
+            assert_eq!(encrypted_batches[0].compression, compression);
+            for (record, encrypted_record) in records.iter().zip(&encrypted_batches[0].records) {
+                assert_ne!(encrypted_record.value, record.value);
+            }
+
+            let plain_batch = fetch_batch(context, &consumer_interceptor, encrypted_batch).await;
+            let plain_batches = decode_record_batches(&plain_batch).unwrap();
+            assert_eq!(plain_batches.len(), 1);
+            assert_eq!(plain_batches[0].compression, compression);
+            for (record, plain_record) in records.iter().zip(&plain_batches[0].records) {
+                assert_eq!(plain_record.value, record.value);
+            }
+        }
+
+        context.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__produce_unsupported_compression__rejected(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let producer_interceptor = create_interceptor(
+            Arc::new(FanOutSecureChannelController {
+                local_consumer: Address::from_string("producer"),
+            }),
+            vec![],
+        );
+
+        // batches with an unknown codec cannot be decoded, the request must be rejected
+        // instead of letting the plain text content through
+        for codec in [5, 6, 7] {
+            let mut batch = BytesMut::from(
+                encode_records(
+                    &[create_record(None, "hello world!", &[])],
+                    Compression::None,
+                )
+                .as_ref(),
+            );
+            batch[22] |= codec;
+
+            assert!(decode_record_batches(&batch).is_err());
+            assert!(
+                produce_batch(context, &producer_interceptor, batch.freeze())
+                    .await
+                    .is_err()
+            );
+        }
+
+        context.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__fetch_batches_with_mixed_compression__each_codec_preserved(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let producer_interceptor = create_interceptor(
+            Arc::new(FanOutSecureChannelController {
+                local_consumer: Address::from_string("producer"),
+            }),
+            vec![],
+        );
+        let consumer_interceptor = create_interceptor(
+            Arc::new(FanOutSecureChannelController {
+                local_consumer: Address::from_string(CONSUMERS[0]),
+            }),
+            vec![],
+        );
+
+        // a fetch response can contain batches produced by clients using different codecs
+        let codecs = [Compression::Zstd, Compression::Gzip, Compression::Lz4];
+        let mut fetched_batches = BytesMut::new();
+        for compression in codecs {
+            let records = vec![create_record(None, &format!("{compression:?} order"), &[])];
+            let encrypted_batch = produce_batch(
+                context,
+                &producer_interceptor,
+                encode_records(&records, compression),
+            )
+            .await
+            .unwrap();
+            fetched_batches.extend_from_slice(&encrypted_batch);
+        }
+
+        let plain_batch =
+            fetch_batch(context, &consumer_interceptor, fetched_batches.freeze()).await;
+        let plain_batches = decode_record_batches(&plain_batch).unwrap();
+        assert_eq!(
+            plain_batches
+                .iter()
+                .map(|batch| (batch.compression, batch.records[0].value.clone().unwrap()))
+                .collect::<Vec<_>>(),
+            codecs
+                .iter()
+                .map(|compression| (*compression, Bytes::from(format!("{compression:?} order"))))
+                .collect::<Vec<_>>()
+        );
+
+        context.stop().await
+    }
+
+    #[allow(non_snake_case)]
+    #[ockam_macros::test(timeout = 5_000)]
+    async fn interceptor__produce_to_denied_topic__authorization_error_returned(
+        context: &mut Context,
+    ) -> ockam::Result<()> {
+        let policies = PolicySqlxDatabase::create().await?;
+        policies
+            .set_policy(
+                &kafka_topic_resource(TOPIC_NAME),
+                &PRODUCE,
+                &Policy::new(eq([ident("subject.team"), str("billing")])),
+            )
+            .await?;
+        let topic_access_control = KafkaTopicAccessControl::new(
+            policies,
+            IdentityAttributesSqlxDatabase::create().await?,
+            Identifier::try_from(
+                "Iabababababababababababababababababababababababababababababababab",
+            )?,
+            Env::new(),
+        );
+
+        let interceptor = create_interceptor_with_access_control(
+            Arc::new(FanOutSecureChannelController {
+                local_consumer: Address::from_string("producer"),
+            }),
+            vec![],
+            Some(topic_access_control),
+        );
+
+        // the records are not sent to the broker
+        let request = send_produce_request(
+            context,
+            &interceptor,
+            encode_records(
+                &[create_record(None, "hello world!", &[])],
+                Compression::None,
+            ),
+        )
+        .await
+        .unwrap();
+        assert!(request.topic_data.is_empty());
+
+        // the response of the broker is completed with an authorization error
+        let mut response = interceptor
+            .intercept_response(
+                context,
+                encode_response(
+                    &ResponseHeader::builder()
+                        .correlation_id(1)
+                        .unknown_tagged_fields(Default::default())
+                        .build()
+                        .unwrap(),
+                    &ProduceResponse::default(),
+                    TEST_KAFKA_API_VERSION,
+                    ApiKey::ProduceKey,
+                )
+                .unwrap(),
+            )
+            .await
+            .unwrap();
+        ResponseHeader::decode(
+            &mut response,
+            ApiKey::ProduceKey.response_header_version(TEST_KAFKA_API_VERSION),
+        )
+        .unwrap();
+        let response = ProduceResponse::decode(&mut response, TEST_KAFKA_API_VERSION).unwrap();
+
+        let topic_response = response
+            .responses
+            .get(&TopicName::from(StrBytes::from_str(TOPIC_NAME)))
+            .unwrap();
+        assert_eq!(topic_response.partition_responses.len(), 1);
+        assert_eq!(topic_response.partition_responses[0].index, 1);
+        assert_eq!(
+            topic_response.partition_responses[0].error_code,
+            ResponseError::TopicAuthorizationFailed.code()
+        );
+
+        context.stop().await
+    }
+
     #[allow(non_snake_case)]
     #[ockam_macros::test(timeout = 5_000)]
     async fn interceptor__basic_messages_with_several_api_versions__parsed_correctly(
@@ -70,6 +874,8 @@ mod test {
             Arc::new(DummySecureChannelController {}),
             Default::default(),
             inlet_map,
+            Default::default(),
+            None,
         );
 
         let mut correlation_id = 0;
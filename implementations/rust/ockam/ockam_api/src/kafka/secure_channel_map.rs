@@ -42,10 +42,11 @@ pub(crate) struct KafkaEncryptedContent {
 /// This is a proxy trait to avoid propagating the vault implementation.
 #[async_trait]
 pub(crate) trait KafkaSecureChannelController: Send + Sync {
-    /// Encrypts the content specifically for the consumer waiting for that topic name and
-    /// partition.
-    /// To do so it'll create a secure channel which will be used for key exchange only.
-    /// The secure channel will be created only once and then re-used, hence the first time will
+    /// Encrypts the content specifically for each consumer waiting for that topic name and
+    /// partition, the same content is returned encrypted once per consumer.
+    /// To do so it'll create a secure channel for each consumer which will be used for key
+    /// exchange only.
+    /// The secure channels will be created only once and then re-used, hence the first time will
     /// be slower, and may take up to few seconds.
     async fn encrypt_content_for(
         &self,
@@ -53,7 +54,11 @@ pub(crate) trait KafkaSecureChannelController: Send + Sync {
         topic_name: &str,
         partition_id: i32,
         content: Vec<u8>,
-    ) -> Result<KafkaEncryptedContent>;
+    ) -> Result<Vec<KafkaEncryptedContent>>;
+
+    /// Returns true if a secure channel with this consumer decryptor address exists locally,
+    /// meaning that the content encrypted for it can be decrypted by this node
+    async fn has_secure_channel_for(&self, consumer_decryptor_address: &Address) -> bool;
 
     /// Decrypts the content based on the consumer decryptor address
     /// the secure channel is expected to be already initialized.
@@ -64,7 +69,8 @@ pub(crate) trait KafkaSecureChannelController: Send + Sync {
         encrypted_content: Vec<u8>,
     ) -> Result<Vec<u8>>;
 
-    /// Starts relays in the orchestrator for each {topic_name}_{partition} combination
+    /// Starts relays in the orchestrator for each {topic_name}_{partition} combination,
+    /// suffixed by each consumer identifier when set.
     /// should be used only by the consumer.
     /// does nothing if they were already created, but fails it they already exist.
     async fn start_relays_for(
@@ -148,23 +154,40 @@ impl<F: RelayCreator> Clone for KafkaSecureChannelControllerImpl<F> {
     }
 }
 
-/// Describe to reach the consumer node:
-/// either directly or through a relay with a relay
+/// Describe to reach the consumer nodes:
+/// either directly or through a relay
 #[derive(Clone)]
 pub(crate) enum ConsumerNodeAddr {
-    Direct(Option<MultiAddr>),
-    Relay(MultiAddr),
+    /// Every record is encrypted for each of these consumer nodes
+    Direct(Vec<MultiAddr>),
+    /// The consumers are reached through the relays they create in the orchestrator.
+    /// Every record is encrypted for each of the consumer identifiers, a producer
+    /// encrypts for the default consumer when no identifier is given.
+    /// A consumer node creates its relays for each of its identifiers.
+    Relay {
+        orchestrator_multiaddr: MultiAddr,
+        consumer_ids: Vec<String>,
+    },
 }
 
-type TopicPartition = (String, i32);
+/// Return the alias of the relay created by a consumer for a topic partition.
+/// The orchestrator adds the `consumer__` prefix to the alias
+fn consumer_relay_alias(topic_name: &str, partition: i32, consumer_id: Option<&str>) -> String {
+    match consumer_id {
+        Some(consumer_id) => format!("{topic_name}_{partition}__{consumer_id}"),
+        None => format!("{topic_name}_{partition}"),
+    }
+}
 
 struct InnerSecureChannelControllerImpl<F: RelayCreator> {
     // we identity the secure channel instance by using the decryptor of the consumer
     // which is known to both parties
-    topic_encryptor_map: HashMap<TopicPartition, Address>,
-    // describes how to reach the consumer node
+    // the key is the route to the consumer secure channel listener
+    consumer_encryptor_map: HashMap<String, Address>,
+    // describes how to reach the consumer nodes
     consumer_node_multiaddr: ConsumerNodeAddr,
-    topic_relay_set: HashSet<TopicPartition>,
+    // aliases of the relays which were already created
+    topic_relay_set: HashSet<String>,
     relay_creator: Option<F>,
     secure_channels: Arc<SecureChannels>,
     access_control: AbacAccessControl,
@@ -178,7 +201,10 @@ impl KafkaSecureChannelControllerImpl<NodeManagerRelayCreator> {
     ) -> KafkaSecureChannelControllerImpl<NodeManagerRelayCreator> {
         let relay_creator = match consumer_node_multiaddr.clone() {
             ConsumerNodeAddr::Direct(_) => None,
-            ConsumerNodeAddr::Relay(mut orchestrator_multiaddr) => {
+            ConsumerNodeAddr::Relay {
+                mut orchestrator_multiaddr,
+                ..
+            } => {
                 orchestrator_multiaddr
                     .push_back(Service::new(KAFKA_OUTLET_CONSUMERS))
                     .unwrap();
@@ -214,7 +240,7 @@ impl<F: RelayCreator> KafkaSecureChannelControllerImpl<F> {
 
         Self {
             inner: Arc::new(Mutex::new(InnerSecureChannelControllerImpl {
-                topic_encryptor_map: Default::default(),
+                consumer_encryptor_map: Default::default(),
                 topic_relay_set: Default::default(),
                 secure_channels,
                 relay_creator,
@@ -307,93 +333,127 @@ impl<F: RelayCreator> KafkaSecureChannelControllerImpl<F> {
         }
     }
 
-    ///returns encryptor api address
-    async fn get_or_create_secure_channel_for(
-        &self,
-        context: &mut Context,
+    /// Return the routes to the secure channel listeners of every consumer of a topic partition
+    fn consumer_destinations(
+        consumer_node_multiaddr: &ConsumerNodeAddr,
         topic_name: &str,
         partition: i32,
-    ) -> Result<SecureChannelRegistryEntry> {
-        // here we should have the orchestrator address and expect relays to be
-        // present in the orchestrator with the format "consumer__{topic_name}_{partition}"
-
-        let mut inner = self.inner.lock().await;
-
-        // when we are using direct mode, there is only one consumer, and use the same secure
-        // channel for all topics
-        let topic_partition_key = match &inner.consumer_node_multiaddr {
-            ConsumerNodeAddr::Direct(_) => ("".to_string(), 0i32),
-            ConsumerNodeAddr::Relay(_) => (topic_name.to_string(), partition),
-        };
-
-        let encryptor_address = {
-            if let Some(encryptor_address) = inner.topic_encryptor_map.get(&topic_partition_key) {
-                encryptor_address.clone()
-            } else {
-                let destination = match inner.consumer_node_multiaddr.clone() {
-                    ConsumerNodeAddr::Direct(destination) => {
-                        if let Some(mut destination) = destination {
-                            debug!("creating new direct secure channel to consumer");
-                            destination
-                                .push_back(Service::new(DefaultAddress::SECURE_CHANNEL_LISTENER))?;
-                            destination
-                        } else {
-                            return Err(Error::new(
-                                Origin::Transport,
-                                Kind::Invalid,
-                                "cannot encrypt messages when consumer is not specified",
-                            ));
-                        }
-                    }
+    ) -> Result<Vec<MultiAddr>> {
+        match consumer_node_multiaddr {
+            // when we are using direct mode, we use the same secure channel for all topics
+            ConsumerNodeAddr::Direct(consumers) => {
+                if consumers.is_empty() {
+                    return Err(Error::new(
+                        Origin::Transport,
+                        Kind::Invalid,
+                        "cannot encrypt messages when consumer is not specified",
+                    ));
+                }
+                consumers
+                    .iter()
+                    .map(|consumer| {
+                        let mut destination = consumer.clone();
+                        destination
+                            .push_back(Service::new(DefaultAddress::SECURE_CHANNEL_LISTENER))?;
+                        Ok(destination)
+                    })
+                    .collect()
+            }
 
-                    ConsumerNodeAddr::Relay(mut destination) => {
+            // here we should have the orchestrator address and expect relays to be
+            // present in the orchestrator with the format "consumer__{topic_name}_{partition}"
+            // or "consumer__{topic_name}_{partition}__{consumer_id}"
+            ConsumerNodeAddr::Relay {
+                orchestrator_multiaddr,
+                consumer_ids,
+            } => {
+                let consumer_ids: Vec<Option<&str>> = if consumer_ids.is_empty() {
+                    vec![None]
+                } else {
+                    consumer_ids.iter().map(|id| Some(id.as_str())).collect()
+                };
+                consumer_ids
+                    .into_iter()
+                    .map(|consumer_id| {
                         //consumer__ prefix is added by the orchestrator
-                        let topic_partition_address = format!("consumer__{topic_name}_{partition}");
-
-                        debug!(
-                            "creating new secure channel via relay to {topic_partition_address}"
+                        let topic_partition_address = format!(
+                            "consumer__{}",
+                            consumer_relay_alias(topic_name, partition, consumer_id)
                         );
-
+                        let mut destination = orchestrator_multiaddr.clone();
                         destination.push_back(Service::new(topic_partition_address))?;
                         destination
                             .push_back(Service::new(DefaultAddress::SECURE_CHANNEL_LISTENER))?;
-                        destination
-                    }
-                };
+                        Ok(destination)
+                    })
+                    .collect()
+            }
+        }
+    }
 
-                let producer_encryptor_address =
-                    Self::request_secure_channel_creation(context, destination).await?;
+    ///returns the secure channels to every consumer of the topic partition
+    async fn get_or_create_secure_channels_for(
+        &self,
+        context: &mut Context,
+        topic_name: &str,
+        partition: i32,
+    ) -> Result<Vec<SecureChannelRegistryEntry>> {
+        let mut inner = self.inner.lock().await;
+
+        let destinations =
+            Self::consumer_destinations(&inner.consumer_node_multiaddr, topic_name, partition)?;
 
-                match Self::validate_consumer_credentials(&inner, &producer_encryptor_address).await
+        let mut entries = Vec::with_capacity(destinations.len());
+        for destination in destinations {
+            let destination_key = destination.to_string();
+            let encryptor_address = {
+                if let Some(encryptor_address) = inner.consumer_encryptor_map.get(&destination_key)
                 {
-                    Ok(producer_encryptor_address) => producer_encryptor_address,
-                    Err(error) => {
-                        Self::request_secure_channel_deletion(context, &producer_encryptor_address)
+                    encryptor_address.clone()
+                } else {
+                    debug!("creating new secure channel to {destination_key}");
+
+                    let producer_encryptor_address =
+                        Self::request_secure_channel_creation(context, destination).await?;
+
+                    match Self::validate_consumer_credentials(&inner, &producer_encryptor_address)
+                        .await
+                    {
+                        Ok(producer_encryptor_address) => producer_encryptor_address,
+                        Err(error) => {
+                            Self::request_secure_channel_deletion(
+                                context,
+                                &producer_encryptor_address,
+                            )
                             .await?;
-                        return Err(error);
-                    }
-                };
-
-                inner
-                    .topic_encryptor_map
-                    .insert(topic_partition_key, producer_encryptor_address.clone());
-
-                debug!("created secure channel");
-                producer_encryptor_address
-            }
-        };
+                            return Err(error);
+                        }
+                    };
+
+                    inner
+                        .consumer_encryptor_map
+                        .insert(destination_key, producer_encryptor_address.clone());
+
+                    debug!("created secure channel");
+                    producer_encryptor_address
+                }
+            };
+
+            let entry = inner
+                .secure_channels
+                .secure_channel_registry()
+                .get_channel_by_encryptor_address(&encryptor_address)
+                .ok_or_else(|| {
+                    Error::new(
+                        Origin::Channel,
+                        Kind::Unknown,
+                        format!("cannot find secure channel address `{encryptor_address}` in local registry"),
+                    )
+                })?;
+            entries.push(entry);
+        }
 
-        inner
-            .secure_channels
-            .secure_channel_registry()
-            .get_channel_by_encryptor_address(&encryptor_address)
-            .ok_or_else(|| {
-                Error::new(
-                    Origin::Channel,
-                    Kind::Unknown,
-                    format!("cannot find secure channel address `{encryptor_address}` in local registry"),
-                )
-            })
+        Ok(entries)
     }
 
     async fn validate_consumer_credentials(
@@ -478,34 +538,48 @@ impl<F: RelayCreator> KafkaSecureChannelController for KafkaSecureChannelControl
         topic_name: &str,
         partition_id: i32,
         content: Vec<u8>,
-    ) -> Result<KafkaEncryptedContent> {
-        let secure_channel_entry = self
-            .get_or_create_secure_channel_for(context, topic_name, partition_id)
+    ) -> Result<Vec<KafkaEncryptedContent>> {
+        let secure_channel_entries = self
+            .get_or_create_secure_channels_for(context, topic_name, partition_id)
             .await?;
 
-        let consumer_decryptor_address = secure_channel_entry.their_decryptor_address();
+        let mut encrypted_contents = Vec::with_capacity(secure_channel_entries.len());
+        for secure_channel_entry in secure_channel_entries {
+            let consumer_decryptor_address = secure_channel_entry.their_decryptor_address();
 
-        trace!("encrypting content with {consumer_decryptor_address}");
-        let encryption_response: EncryptionResponse = context
-            .send_and_receive(
-                route![secure_channel_entry.encryptor_api_address().clone()],
-                EncryptionRequest(content),
-            )
-            .await?;
+            trace!("encrypting content with {consumer_decryptor_address}");
+            let encryption_response: EncryptionResponse = context
+                .send_and_receive(
+                    route![secure_channel_entry.encryptor_api_address().clone()],
+                    EncryptionRequest(content.clone()),
+                )
+                .await?;
 
-        let encrypted_content = match encryption_response {
-            EncryptionResponse::Ok(p) => p,
-            EncryptionResponse::Err(cause) => {
-                warn!("cannot encrypt kafka message");
-                return Err(cause);
-            }
-        };
+            let encrypted_content = match encryption_response {
+                EncryptionResponse::Ok(p) => p,
+                EncryptionResponse::Err(cause) => {
+                    warn!("cannot encrypt kafka message");
+                    return Err(cause);
+                }
+            };
+
+            trace!("encrypted content with {consumer_decryptor_address}");
+            encrypted_contents.push(KafkaEncryptedContent {
+                content: encrypted_content,
+                consumer_decryptor_address,
+            });
+        }
 
-        trace!("encrypted content with {consumer_decryptor_address}");
-        Ok(KafkaEncryptedContent {
-            content: encrypted_content,
-            consumer_decryptor_address,
-        })
+        Ok(encrypted_contents)
+    }
+
+    async fn has_secure_channel_for(&self, consumer_decryptor_address: &Address) -> bool {
+        let inner = self.inner.lock().await;
+        inner
+            .secure_channels
+            .secure_channel_registry()
+            .get_channel_by_decryptor_address(consumer_decryptor_address)
+            .is_some()
     }
 
     async fn decrypt_content_for(
@@ -548,19 +622,27 @@ impl<F: RelayCreator> KafkaSecureChannelController for KafkaSecureChannelControl
             return Ok(());
         }
 
+        let consumer_ids: Vec<Option<String>> = match &inner.consumer_node_multiaddr {
+            ConsumerNodeAddr::Relay { consumer_ids, .. } if !consumer_ids.is_empty() => {
+                consumer_ids.iter().cloned().map(Some).collect()
+            }
+            _ => vec![None],
+        };
+
         for partition in partitions {
-            let topic_key: TopicPartition = (topic_name.to_string(), partition);
-            if inner.topic_relay_set.contains(&topic_key) {
-                continue;
+            for consumer_id in consumer_ids.iter() {
+                let alias = consumer_relay_alias(topic_name, partition, consumer_id.as_deref());
+                if inner.topic_relay_set.contains(&alias) {
+                    continue;
+                }
+                inner
+                    .relay_creator
+                    .as_ref()
+                    .unwrap()
+                    .create_relay(context, alias.clone())
+                    .await?;
+                inner.topic_relay_set.insert(alias);
             }
-            let alias = format!("{topic_name}_{partition}");
-            inner
-                .relay_creator
-                .as_ref()
-                .unwrap()
-                .create_relay(context, alias)
-                .await?;
-            inner.topic_relay_set.insert(topic_key);
         }
         Ok(())
     }
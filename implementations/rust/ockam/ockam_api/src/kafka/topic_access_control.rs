@@ -0,0 +1,340 @@
+use ockam::identity::models::CredentialAndPurposeKey;
+use ockam::identity::{Credentials, Identifier, IdentityAttributesRepository, TrustContext};
+use ockam_abac::expr::str;
+use ockam_abac::{
+    AbacAccessControl, Action, Env, PoliciesRepository, PolicyAuditRepository, Resource,
+};
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::Result;
+use ockam_node::Context;
+
+/// Prefix of the resource name of a kafka topic, the resource of the topic
+/// `orders` is `kafka-topic/orders`
+pub const KAFKA_TOPIC_RESOURCE_PREFIX: &str = "kafka-topic/";
+
+/// Action of producing records to a topic
+pub const PRODUCE: Action = Action::assert_inline("produce");
+/// Action of fetching records from a topic
+pub const CONSUME: Action = Action::assert_inline("consume");
+/// Action of reading the metadata of a topic
+pub const METADATA: Action = Action::assert_inline("metadata");
+
+/// Return the resource used to set the policies of a kafka topic
+pub fn kafka_topic_resource(topic: &str) -> Resource {
+    Resource::new(&format!("{KAFKA_TOPIC_RESOURCE_PREFIX}{topic}"))
+}
+
+/// Evaluates the policies set on kafka topics for the identity of the node running
+/// the kafka inlet, which is the identity authenticated by the secure channels to the
+/// kafka outlet.
+///
+/// The attributes of that identity are the ones attested by the credential issued to the node
+/// by the authority of its trust context.
+///
+/// A topic without any policy for an action can be accessed, so that existing deployments
+/// keep working until policies are set.
+pub(crate) struct KafkaTopicAccessControl {
+    policies: Arc<dyn PoliciesRepository>,
+    identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+    audit_repository: Option<Arc<dyn PolicyAuditRepository>>,
+    subject_credential: Option<SubjectCredential>,
+    subject: Identifier,
+    environment: Env,
+}
+
+/// Credential of the subject, retrieved from the authority of a trust context
+struct SubjectCredential {
+    trust_context: TrustContext,
+    credentials: Arc<Credentials>,
+    /// Last credential whose attributes have been stored
+    stored_credential: Mutex<Option<CredentialAndPurposeKey>>,
+}
+
+impl KafkaTopicAccessControl {
+    pub(crate) fn new(
+        policies: Arc<dyn PoliciesRepository>,
+        identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+        subject: Identifier,
+        environment: Env,
+    ) -> Self {
+        Self {
+            policies,
+            identity_attributes_repository,
+            audit_repository: None,
+            subject_credential: None,
+            subject,
+            environment,
+        }
+    }
+
+    /// Record all the decisions taken for the topics
+    pub(crate) fn with_audit_repository(
+        mut self,
+        repository: Arc<dyn PolicyAuditRepository>,
+    ) -> Self {
+        self.audit_repository = Some(repository);
+        self
+    }
+
+    /// Evaluate the policies with the attributes of the credential issued to the subject
+    /// by the authority of the trust context
+    pub(crate) fn with_trust_context(
+        mut self,
+        trust_context: TrustContext,
+        credentials: Arc<Credentials>,
+    ) -> Self {
+        self.subject_credential = Some(SubjectCredential {
+            trust_context,
+            credentials,
+            stored_credential: Mutex::new(None),
+        });
+        self
+    }
+
+    /// Return true if the action is allowed on the topic
+    pub(crate) async fn is_authorized(
+        &self,
+        ctx: &Context,
+        topic: &str,
+        action: &Action,
+    ) -> Result<bool> {
+        let resource = kafka_topic_resource(topic);
+        let policy = match self.policies.get_policy(&resource, action).await? {
+            Some(policy) => policy,
+            None => return Ok(true),
+        };
+        self.store_subject_attributes(ctx).await;
+
+        let mut environment = self.environment.clone();
+        environment.put("resource.id", str(resource.as_str()));
+        environment.put("resource.topic", str(topic));
+        environment.put("action.id", str(action.as_str()));
+
+        let mut access_control = AbacAccessControl::new(
+            self.identity_attributes_repository.clone(),
+            policy,
+            environment,
+        );
+        if let Some(repository) = &self.audit_repository {
+            access_control =
+                access_control.with_audit_repository(repository.clone(), resource, action.clone());
+        }
+
+        let is_authorized = access_control
+            .is_identity_authorized(self.subject.clone())
+            .await?;
+        if !is_authorized {
+            debug!("{action} denied on the kafka topic {topic}");
+        }
+        Ok(is_authorized)
+    }
+
+    /// Verify the current credential of the subject and store its attributes.
+    /// The credential is cached by the authority service and only verified again when it
+    /// has been renewed. When no credential can be retrieved the policies are evaluated with
+    /// the attributes already known for the subject.
+    async fn store_subject_attributes(&self, ctx: &Context) {
+        let subject_credential = match &self.subject_credential {
+            Some(subject_credential) => subject_credential,
+            None => return,
+        };
+        let credential = match subject_credential
+            .trust_context
+            .get_credential(ctx, &self.subject)
+            .await
+        {
+            Ok(Some(credential)) => credential,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("cannot retrieve the credential of {}: {e}", self.subject);
+                return;
+            }
+        };
+        if subject_credential
+            .stored_credential
+            .lock()
+            .unwrap()
+            .as_ref()
+            == Some(&credential)
+        {
+            return;
+        }
+
+        match subject_credential
+            .credentials
+            .credentials_verification()
+            .receive_presented_credential(
+                &self.subject,
+                &subject_credential.trust_context.authorities(),
+                &credential,
+            )
+            .await
+        {
+            Ok(()) => *subject_credential.stored_credential.lock().unwrap() = Some(credential),
+            Err(e) => warn!("the credential of {} cannot be verified: {e}", self.subject),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam::identity::models::CredentialSchemaIdentifier;
+    use ockam::identity::utils::AttributesBuilder;
+    use ockam::identity::{
+        identities, AttributesEntry, AuthorityService, CredentialsMemoryRetriever,
+        IdentityAttributesSqlxDatabase,
+    };
+    use ockam_abac::expr::{eq, ident};
+    use ockam_abac::{Policy, PolicySqlxDatabase};
+    use std::collections::BTreeMap;
+    use std::time::Duration;
+
+    #[ockam_macros::test]
+    async fn test_topic_policies(ctx: &mut Context) -> Result<()> {
+        let policies = PolicySqlxDatabase::create().await?;
+        let identity_attributes = IdentityAttributesSqlxDatabase::create().await?;
+        let subject = Identifier::try_from(
+            "Iabababababababababababababababababababababababababababababababab",
+        )?;
+        identity_attributes
+            .put_attributes(
+                &subject,
+                AttributesEntry::new(
+                    BTreeMap::from([(b"team".to_vec(), b"billing".to_vec())]),
+                    ockam::identity::utils::now()?,
+                    None,
+                    None,
+                ),
+            )
+            .await?;
+
+        let access_control = KafkaTopicAccessControl::new(
+            policies.clone(),
+            identity_attributes,
+            subject,
+            Env::new(),
+        );
+
+        // without policy the topic can be accessed
+        assert!(
+            access_control
+                .is_authorized(ctx, "orders", &PRODUCE)
+                .await?
+        );
+
+        policies
+            .set_policy(
+                &kafka_topic_resource("orders"),
+                &PRODUCE,
+                &Policy::new(eq([ident("subject.team"), str("billing")])),
+            )
+            .await?;
+        policies
+            .set_policy(
+                &kafka_topic_resource("orders"),
+                &CONSUME,
+                &Policy::new(eq([ident("subject.team"), str("shipping")])),
+            )
+            .await?;
+        policies
+            .set_policy(
+                &kafka_topic_resource("payments"),
+                &PRODUCE,
+                &Policy::new(eq([ident("resource.topic"), str("payments")])),
+            )
+            .await?;
+
+        assert!(
+            access_control
+                .is_authorized(ctx, "orders", &PRODUCE)
+                .await?
+        );
+        assert!(
+            !access_control
+                .is_authorized(ctx, "orders", &CONSUME)
+                .await?
+        );
+        assert!(
+            access_control
+                .is_authorized(ctx, "orders", &METADATA)
+                .await?
+        );
+        assert!(
+            access_control
+                .is_authorized(ctx, "payments", &PRODUCE)
+                .await?
+        );
+        ctx.stop().await
+    }
+
+    #[ockam_macros::test]
+    async fn test_topic_policies_with_the_subject_credential(ctx: &mut Context) -> Result<()> {
+        let identities = identities().await?;
+        let authority = identities.identities_creation().create_identity().await?;
+        let subject = identities.identities_creation().create_identity().await?;
+        let credential = identities
+            .credentials()
+            .credentials_creation()
+            .issue_credential(
+                &authority,
+                &subject,
+                AttributesBuilder::with_schema(CredentialSchemaIdentifier(1))
+                    .with_attribute("team", "billing")
+                    .build(),
+                Duration::from_secs(60),
+            )
+            .await?;
+        let trust_context = TrustContext::new(
+            "trust_context_id".to_string(),
+            Some(AuthorityService::new(
+                identities.credentials(),
+                authority,
+                Some(Arc::new(CredentialsMemoryRetriever::new(credential))),
+            )),
+        );
+
+        let policies = PolicySqlxDatabase::create().await?;
+        policies
+            .set_policy(
+                &kafka_topic_resource("orders"),
+                &PRODUCE,
+                &Policy::new(eq([ident("subject.team"), str("billing")])),
+            )
+            .await?;
+        policies
+            .set_policy(
+                &kafka_topic_resource("orders"),
+                &CONSUME,
+                &Policy::new(eq([ident("subject.team"), str("shipping")])),
+            )
+            .await?;
+
+        // the attributes of the subject are only known from its credential
+        let access_control = KafkaTopicAccessControl::new(
+            policies.clone(),
+            identities.identity_attributes_repository(),
+            subject.clone(),
+            Env::new(),
+        );
+        assert!(
+            !access_control
+                .is_authorized(ctx, "orders", &PRODUCE)
+                .await?
+        );
+
+        let access_control =
+            access_control.with_trust_context(trust_context, identities.credentials());
+        assert!(
+            access_control
+                .is_authorized(ctx, "orders", &PRODUCE)
+                .await?
+        );
+        assert!(
+            !access_control
+                .is_authorized(ctx, "orders", &CONSUME)
+                .await?
+        );
+        ctx.stop().await
+    }
+}
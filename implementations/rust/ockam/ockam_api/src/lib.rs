@@ -30,7 +30,9 @@ pub mod echoer;
 pub mod enroll;
 pub mod error;
 pub mod hop;
+pub mod http;
 pub mod kafka;
+pub mod metrics;
 pub mod minicbor_url;
 pub mod nodes;
 pub mod okta;
@@ -1,4 +1,5 @@
 use crate::logs::env::{log_format, log_max_files};
+use crate::logs::otlp::OtlpLayer;
 use ockam_core::env::FromString;
 use std::io::stdout;
 use std::path::PathBuf;
@@ -9,6 +10,7 @@ use tracing_subscriber::fmt::layer;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
 
 pub mod env;
+pub mod otlp;
 
 pub struct Logging;
 
@@ -18,6 +20,7 @@ impl Logging {
         color: bool,
         node_dir: Option<PathBuf>,
         crates: &[&str],
+        otlp: Option<OtlpLayer>,
     ) -> Option<WorkerGuard> {
         let filter = {
             let builder = EnvFilter::builder();
@@ -31,7 +34,8 @@ impl Logging {
         };
         let subscriber = tracing_subscriber::registry()
             .with(filter)
-            .with(tracing_error::ErrorLayer::default());
+            .with(tracing_error::ErrorLayer::default())
+            .with(otlp);
         let (appender, guard) = match node_dir {
             // If a node dir path is not provided, log to stdout.
             None => {
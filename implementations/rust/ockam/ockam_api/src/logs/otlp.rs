@@ -0,0 +1,545 @@
+//! Export the spans which are part of a distributed trace with the OTLP/JSON format,
+//! either to a file, one `ExportTraceServiceRequest` per line, or to the OTLP/HTTP endpoint
+//! of a collector (for example `http://127.0.0.1:4318`).
+//!
+//! The spans are exported by a background thread. When it can not keep up, for example
+//! when the collector is unreachable, the spans which don't fit in its queue are dropped
+//! so that the node is never slowed down by the export of its traces.
+//!
+//! A span is part of a distributed trace when it has `trace_id` and `span_id` fields,
+//! like the spans created by the node when a worker handles a message carrying a
+//! [`TraceContext`](ockam_core::TraceContext), or when its parent span is part of a trace.
+
+use std::collections::BTreeMap;
+use std::fmt::{Debug, Display, Formatter};
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{sync_channel, RecvTimeoutError, SyncSender, TrySendError};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use reqwest::blocking::Client;
+use reqwest::Url;
+use serde_json::{json, Value};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::Subscriber;
+use tracing_subscriber::layer::Context;
+use tracing_subscriber::registry::LookupSpan;
+use tracing_subscriber::Layer;
+
+use ockam_core::compat::rand::random;
+
+use crate::error::ApiError;
+
+/// Path of the traces endpoint of an OTLP/HTTP collector
+const OTLP_TRACES_PATH: &str = "/v1/traces";
+
+/// Maximum number of spans exported in one request
+const BATCH_SIZE: usize = 512;
+
+/// Maximum delay before exporting the closed spans
+const BATCH_DELAY: Duration = Duration::from_secs(1);
+
+/// Maximum number of closed spans waiting to be exported
+const QUEUE_SIZE: usize = 4 * BATCH_SIZE;
+
+/// Maximum duration to connect to a collector
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum duration of an export request to a collector
+const EXPORT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Destination of the exported traces
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OtlpDestination {
+    /// Append the traces to a file
+    File(PathBuf),
+    /// Send the traces to the traces endpoint of a collector with OTLP/HTTP
+    Collector(Url),
+}
+
+impl FromStr for OtlpDestination {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.starts_with("http://") || s.starts_with("https://") {
+            let mut url = Url::parse(s)
+                .map_err(|e| ApiError::message(format!("invalid collector url {s}: {e}")))?;
+            if url.host_str().unwrap_or_default().is_empty() {
+                return Err(ApiError::message(format!(
+                    "missing collector address in {s}"
+                )));
+            }
+            if url.path() == "/" {
+                url.set_path(OTLP_TRACES_PATH);
+            }
+            Ok(OtlpDestination::Collector(url))
+        } else if s.is_empty() {
+            Err(ApiError::message("the OTLP destination can not be empty"))
+        } else {
+            Ok(OtlpDestination::File(PathBuf::from(s)))
+        }
+    }
+}
+
+impl Display for OtlpDestination {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            OtlpDestination::File(path) => write!(f, "{}", path.display()),
+            OtlpDestination::Collector(url) => write!(f, "{url}"),
+        }
+    }
+}
+
+/// Span data kept while a span belonging to a trace is open
+struct OtlpSpan {
+    trace_id: String,
+    span_id: String,
+    parent_span_id: Option<String>,
+    start: SystemTime,
+    attributes: BTreeMap<String, String>,
+}
+
+/// A closed span, ready to be exported
+struct ClosedSpan {
+    name: &'static str,
+    target: &'static str,
+    span: OtlpSpan,
+    end: SystemTime,
+}
+
+/// Layer exporting the spans of distributed traces.
+/// Spans are batched and exported by a background thread.
+pub struct OtlpLayer {
+    sender: SyncSender<ClosedSpan>,
+    /// Number of spans dropped since the last export because the queue was full
+    dropped: Arc<AtomicU64>,
+}
+
+impl Debug for OtlpLayer {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OtlpLayer").finish()
+    }
+}
+
+impl OtlpLayer {
+    /// Create a layer exporting the traces of a node to the given destination
+    pub fn new(destination: OtlpDestination, node_name: &str) -> OtlpLayer {
+        let (sender, receiver) = sync_channel::<ClosedSpan>(QUEUE_SIZE);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let resource = json!({
+            "attributes": [
+                attribute("service.name", "ockam"),
+                attribute("ockam.node", node_name),
+            ]
+        });
+        let exporter_dropped = dropped.clone();
+        thread::Builder::new()
+            .name("otlp-exporter".to_string())
+            .spawn(move || {
+                // the blocking client must be created outside of an async runtime
+                let exporter = Exporter::new(destination, resource, EXPORT_TIMEOUT);
+                let dropped = exporter_dropped;
+                let mut batch = vec![];
+                loop {
+                    match receiver.recv_timeout(BATCH_DELAY) {
+                        Ok(span) => {
+                            batch.push(span);
+                            if batch.len() < BATCH_SIZE {
+                                continue;
+                            }
+                        }
+                        Err(RecvTimeoutError::Timeout) => {}
+                        Err(RecvTimeoutError::Disconnected) => {
+                            exporter.export(std::mem::take(&mut batch));
+                            break;
+                        }
+                    }
+                    let dropped = dropped.swap(0, Ordering::Relaxed);
+                    if dropped > 0 {
+                        warn!("dropped {dropped} spans, the OTLP exporter can not keep up");
+                    }
+                    exporter.export(std::mem::take(&mut batch));
+                }
+            })
+            .expect("Failed to start the OTLP exporter thread");
+        OtlpLayer { sender, dropped }
+    }
+}
+
+impl<S> Layer<S> for OtlpLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let span = match ctx.span(id) {
+            Some(span) => span,
+            None => return,
+        };
+        let mut visitor = AttributesVisitor::default();
+        attrs.record(&mut visitor);
+        let mut attributes = visitor.0;
+
+        let trace_id = attributes.remove("trace_id");
+        let span_id = attributes.remove("span_id");
+        let parent_span_id = attributes.remove("parent_span_id");
+
+        let otlp_span = match (trace_id, span_id) {
+            // The span was explicitly attached to a distributed trace
+            (Some(trace_id), Some(span_id)) => OtlpSpan {
+                trace_id,
+                span_id,
+                parent_span_id,
+                start: SystemTime::now(),
+                attributes,
+            },
+            // Otherwise the span is part of the trace of its parent, if any
+            _ => {
+                let parent = match span.parent() {
+                    Some(parent) => parent,
+                    None => return,
+                };
+                let extensions = parent.extensions();
+                let parent = match extensions.get::<OtlpSpan>() {
+                    Some(parent) => parent,
+                    None => return,
+                };
+                OtlpSpan {
+                    trace_id: parent.trace_id.clone(),
+                    span_id: hex::encode(random::<[u8; 8]>()),
+                    parent_span_id: Some(parent.span_id.clone()),
+                    start: SystemTime::now(),
+                    attributes,
+                }
+            }
+        };
+        span.extensions_mut().insert(otlp_span);
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(otlp_span) = span.extensions_mut().get_mut::<OtlpSpan>() {
+                let mut visitor = AttributesVisitor::default();
+                values.record(&mut visitor);
+                otlp_span.attributes.extend(visitor.0);
+            }
+        }
+    }
+
+    fn on_close(&self, id: Id, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(&id) {
+            if let Some(otlp_span) = span.extensions_mut().remove::<OtlpSpan>() {
+                let closed = ClosedSpan {
+                    name: span.name(),
+                    target: span.metadata().target(),
+                    span: otlp_span,
+                    end: SystemTime::now(),
+                };
+                // never wait for the exporter, a span is dropped if the queue is full
+                if let Err(TrySendError::Full(_)) = self.sender.try_send(closed) {
+                    self.dropped.fetch_add(1, Ordering::Relaxed);
+                }
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct AttributesVisitor(BTreeMap<String, String>);
+
+impl Visit for AttributesVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+struct Exporter {
+    destination: OtlpDestination,
+    resource: Value,
+    client: Option<Client>,
+}
+
+impl Exporter {
+    fn new(destination: OtlpDestination, resource: Value, timeout: Duration) -> Exporter {
+        let client = match &destination {
+            OtlpDestination::File(_) => None,
+            OtlpDestination::Collector(_) => {
+                match Client::builder()
+                    .connect_timeout(CONNECT_TIMEOUT.min(timeout))
+                    .timeout(timeout)
+                    .build()
+                {
+                    Ok(client) => Some(client),
+                    Err(e) => {
+                        warn!("failed to create the OTLP client, the traces are not exported: {e}");
+                        None
+                    }
+                }
+            }
+        };
+        Exporter {
+            destination,
+            resource,
+            client,
+        }
+    }
+
+    fn export(&self, spans: Vec<ClosedSpan>) {
+        if spans.is_empty() {
+            return;
+        }
+        let request = self.request(spans).to_string();
+        if let Err(e) = self.send(request) {
+            warn!("failed to export traces to {}: {e}", self.destination);
+        }
+    }
+
+    fn send(&self, request: String) -> Result<(), String> {
+        match (&self.destination, &self.client) {
+            (OtlpDestination::File(path), _) => OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| writeln!(file, "{request}"))
+                .map_err(|e| e.to_string()),
+            (OtlpDestination::Collector(url), Some(client)) => client
+                .post(url.clone())
+                .header(reqwest::header::CONTENT_TYPE, "application/json")
+                .body(request)
+                .send()
+                .and_then(|response| response.error_for_status())
+                .map(|_| ())
+                .map_err(|e| e.to_string()),
+            (OtlpDestination::Collector(_), None) => Err("no OTLP client".to_string()),
+        }
+    }
+
+    /// Create an `ExportTraceServiceRequest` with the OTLP/JSON encoding
+    fn request(&self, spans: Vec<ClosedSpan>) -> Value {
+        let spans: Vec<Value> = spans
+            .into_iter()
+            .map(|closed| {
+                let mut attributes: Vec<Value> = closed
+                    .span
+                    .attributes
+                    .iter()
+                    .map(|(k, v)| attribute(k, v))
+                    .collect();
+                attributes.push(attribute("code.namespace", closed.target));
+                let mut span = json!({
+                    "traceId": closed.span.trace_id,
+                    "spanId": closed.span.span_id,
+                    "name": closed.name,
+                    // SPAN_KIND_INTERNAL
+                    "kind": 1,
+                    "startTimeUnixNano": unix_nanos(closed.span.start),
+                    "endTimeUnixNano": unix_nanos(closed.end),
+                    "attributes": attributes,
+                });
+                if let Some(parent_span_id) = closed.span.parent_span_id {
+                    span["parentSpanId"] = Value::String(parent_span_id);
+                }
+                span
+            })
+            .collect();
+
+        json!({
+            "resourceSpans": [{
+                "resource": self.resource,
+                "scopeSpans": [{
+                    "scope": { "name": "ockam" },
+                    "spans": spans,
+                }]
+            }]
+        })
+    }
+}
+
+fn attribute(key: &str, value: &str) -> Value {
+    json!({ "key": key, "value": { "stringValue": value } })
+}
+
+fn unix_nanos(time: SystemTime) -> String {
+    time.duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos()
+        .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+    use std::net::{TcpListener, TcpStream};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    #[test]
+    fn parse_destination() {
+        assert_eq!(
+            OtlpDestination::from_str("http://127.0.0.1:4318").unwrap(),
+            OtlpDestination::Collector(Url::parse("http://127.0.0.1:4318/v1/traces").unwrap())
+        );
+        assert_eq!(
+            OtlpDestination::from_str("https://collector/custom/traces").unwrap(),
+            OtlpDestination::Collector(Url::parse("https://collector/custom/traces").unwrap())
+        );
+        assert_eq!(
+            OtlpDestination::from_str("/tmp/traces.json").unwrap(),
+            OtlpDestination::File(PathBuf::from("/tmp/traces.json"))
+        );
+        assert!(OtlpDestination::from_str("http://").is_err());
+        assert!(OtlpDestination::from_str("").is_err());
+    }
+
+    #[test]
+    fn export_spans_of_a_trace_to_a_file() {
+        let path = std::env::temp_dir().join(format!("traces-{}.json", random::<u64>()));
+        let layer = OtlpLayer::new(OtlpDestination::File(path.clone()), "n1");
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            let root = tracing::info_span!(
+                "handle_message",
+                trace_id = "4bf92f3577b34da6a3ce929d0e0e4736",
+                span_id = "00f067aa0ba902b7",
+                parent_span_id = "1111111111111111",
+                worker = "echoer",
+            );
+            root.in_scope(|| {
+                tracing::info_span!("child").in_scope(|| {});
+            });
+            // not part of a trace, not exported
+            tracing::info_span!("other").in_scope(|| {});
+        });
+
+        // The exporter thread exports the spans when the layer is dropped
+        let mut content = String::new();
+        for _ in 0..50 {
+            content = std::fs::read_to_string(&path).unwrap_or_default();
+            if !content.is_empty() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        let _ = std::fs::remove_file(&path);
+
+        let request: Value = serde_json::from_str(content.lines().next().unwrap()).unwrap();
+        let spans = request["resourceSpans"][0]["scopeSpans"][0]["spans"]
+            .as_array()
+            .unwrap();
+        assert_eq!(spans.len(), 2);
+
+        let child = &spans[0];
+        let root = &spans[1];
+        assert_eq!(child["name"], "child");
+        assert_eq!(child["traceId"], "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(child["parentSpanId"], "00f067aa0ba902b7");
+        assert_eq!(root["name"], "handle_message");
+        assert_eq!(root["spanId"], "00f067aa0ba902b7");
+        assert_eq!(root["parentSpanId"], "1111111111111111");
+        assert!(root["attributes"]
+            .as_array()
+            .unwrap()
+            .contains(&attribute("worker", "echoer")));
+    }
+
+    #[test]
+    fn drop_spans_when_the_queue_is_full() {
+        // the receiver is not consumed, as if the exporter was blocked by a slow collector
+        let (sender, receiver) = sync_channel(2);
+        let dropped = Arc::new(AtomicU64::new(0));
+        let layer = OtlpLayer {
+            sender,
+            dropped: dropped.clone(),
+        };
+        let subscriber = tracing_subscriber::registry().with(layer);
+
+        tracing::subscriber::with_default(subscriber, || {
+            for _ in 0..5 {
+                tracing::info_span!(
+                    "handle_message",
+                    trace_id = "4bf92f3577b34da6a3ce929d0e0e4736",
+                    span_id = "00f067aa0ba902b7",
+                )
+                .in_scope(|| {});
+            }
+        });
+
+        assert_eq!(receiver.try_iter().count(), 2);
+        assert_eq!(dropped.load(Ordering::Relaxed), 3);
+    }
+
+    #[test]
+    fn export_spans_to_a_collector() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+        let collector = thread::spawn(move || {
+            let (mut stream, _) = listener.accept().unwrap();
+            let request = read_http_request(&mut stream);
+            stream
+                .write_all(b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n")
+                .unwrap();
+            request
+        });
+
+        let exporter = Exporter::new(
+            OtlpDestination::from_str(&url).unwrap(),
+            json!({}),
+            Duration::from_secs(5),
+        );
+        exporter.send("{\"resourceSpans\":[]}".to_string()).unwrap();
+
+        let request = collector.join().unwrap();
+        assert!(request.starts_with("POST /v1/traces HTTP/1.1"));
+        assert!(request.contains("content-type: application/json"));
+        assert!(request.ends_with("{\"resourceSpans\":[]}"));
+    }
+
+    #[test]
+    fn stop_waiting_for_an_unresponsive_collector() {
+        // the connection is accepted by the listener backlog, but never answered
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}", listener.local_addr().unwrap());
+
+        let exporter = Exporter::new(
+            OtlpDestination::from_str(&url).unwrap(),
+            json!({}),
+            Duration::from_millis(500),
+        );
+        let start = std::time::Instant::now();
+        assert!(exporter.send("{}".to_string()).is_err());
+        assert!(start.elapsed() < Duration::from_secs(5));
+        drop(listener);
+    }
+
+    /// Read the headers and the body of a request with a Content-Length
+    fn read_http_request(stream: &mut TcpStream) -> String {
+        let mut request = vec![];
+        let mut buffer = [0u8; 1024];
+        loop {
+            let n = stream.read(&mut buffer).unwrap();
+            request.extend_from_slice(&buffer[..n]);
+            let text = String::from_utf8_lossy(&request).to_string();
+            if let Some(end) = text.find("\r\n\r\n") {
+                let length: usize = text
+                    .lines()
+                    .find_map(|l| l.strip_prefix("content-length: "))
+                    .and_then(|l| l.trim().parse().ok())
+                    .unwrap_or_default();
+                if request.len() >= end + 4 + length || n == 0 {
+                    return text;
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! This module exposes the node runtime metrics with the Prometheus text format
+//! on an HTTP endpoint: `GET http://<metrics address>/metrics`
+
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+use tiny_http::{Header, Method, Request, Response, Server};
+use tokio::runtime::Handle;
+
+use ockam_core::metrics::{encode_metrics, MAILBOX_DEPTH, MAILBOX_OVERFLOWS};
+use ockam_core::{Address, DenyAll, Result};
+use ockam_node::Context;
+
+use crate::error::ApiError;
+
+/// Path of the metrics endpoint
+pub const METRICS_PATH: &str = "/metrics";
+
+/// Content type of the Prometheus text format
+const METRICS_CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// HTTP server exporting the node metrics.
+/// The server is stopped when this value is dropped.
+pub struct MetricsExporter {
+    server: Arc<Server>,
+    address: SocketAddr,
+    is_stopped: Arc<AtomicBool>,
+}
+
+impl MetricsExporter {
+    /// Start an HTTP server on the given address to serve the node metrics
+    pub async fn start(ctx: &Context, address: SocketAddr) -> Result<MetricsExporter> {
+        let server = Arc::new(Server::http(address).map_err(|e| {
+            ApiError::core(format!(
+                "failed to start the metrics endpoint on {address}: {e}"
+            ))
+        })?);
+        let address = server
+            .server_addr()
+            .to_ip()
+            .ok_or_else(|| ApiError::core("the metrics endpoint should use an IP address"))?;
+
+        // this context is used to retrieve the mailbox depths of workers from the router
+        let ctx = ctx
+            .new_detached(Address::random_tagged("MetricsExporter"), DenyAll, DenyAll)
+            .await?;
+
+        let is_stopped = Arc::new(AtomicBool::new(false));
+        let handle = Handle::current();
+        {
+            let server = server.clone();
+            let is_stopped = is_stopped.clone();
+            tokio::task::spawn_blocking(move || loop {
+                match server.recv() {
+                    Ok(request) => Self::respond(&handle, &ctx, request),
+                    Err(_) if is_stopped.load(Ordering::Relaxed) => break,
+                    Err(e) => warn!("failed to receive a request on the metrics endpoint: {e}"),
+                }
+            });
+        }
+
+        info!("the node metrics are available at http://{address}{METRICS_PATH}");
+        Ok(MetricsExporter {
+            server,
+            address,
+            is_stopped,
+        })
+    }
+
+    /// Return the address of the metrics endpoint
+    pub fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Send back the node metrics for GET /metrics, and an error otherwise
+    fn respond(handle: &Handle, ctx: &Context, request: Request) {
+        let path = request.url().split('?').next().unwrap_or_default();
+        let result = if path != METRICS_PATH {
+            request.respond(Response::empty(404))
+        } else if request.method() != &Method::Get {
+            request.respond(Response::empty(405))
+        } else {
+            if let Err(e) = handle.block_on(Self::update_mailbox_statistics(ctx)) {
+                warn!("failed to retrieve the workers mailbox statistics: {e}");
+            }
+            let content_type = Header::from_str(&format!("Content-Type: {METRICS_CONTENT_TYPE}"))
+                .expect("the metrics content type header should be valid");
+            request.respond(Response::from_string(encode_metrics()).with_header(content_type))
+        };
+
+        if let Err(e) = result {
+            warn!("failed to respond to a request on the metrics endpoint: {e}");
+        }
+    }
+
+    /// The mailbox statistics are read from the router when the metrics are scraped
+    async fn update_mailbox_statistics(ctx: &Context) -> Result<()> {
+        let statistics = ctx.mailbox_statistics().await?;
+        MAILBOX_DEPTH.clear();
+        MAILBOX_OVERFLOWS.clear();
+        for s in statistics {
+            let address = s.address.to_string();
+            MAILBOX_DEPTH.with_labels(&[&address]).set(s.depth as i64);
+            if s.overflows > 0 {
+                MAILBOX_OVERFLOWS
+                    .with_labels(&[&address, &s.overflow_policy.to_string()])
+                    .inc_by(s.overflows);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Drop for MetricsExporter {
+    fn drop(&mut self) {
+        self.is_stopped.store(true, Ordering::Relaxed);
+        self.server.unblock();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_core::route;
+    use ockam_node::Context;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    use tokio::net::TcpStream;
+
+    #[ockam_macros::test]
+    async fn test_metrics_endpoint(ctx: &mut Context) -> Result<()> {
+        let exporter = MetricsExporter::start(ctx, "127.0.0.1:0".parse().unwrap()).await?;
+
+        ctx.send(route!["app"], "hello".to_string()).await?;
+        let _ = ctx.receive::<String>().await?;
+
+        let response = http_get(exporter.address(), METRICS_PATH).await;
+        assert!(response.starts_with("HTTP/1.1 200"), "{response}");
+        assert!(response.contains("text/plain; version=0.0.4"), "{response}");
+        assert!(response.contains("# TYPE ockam_router_messages_total counter"));
+        assert!(response.contains("ockam_worker_mailbox_depth{address=\"0#app\"} 0"));
+
+        let response = http_get(exporter.address(), "/other").await;
+        assert!(response.starts_with("HTTP/1.1 404"), "{response}");
+
+        ctx.stop().await
+    }
+
+    async fn http_get(address: SocketAddr, path: &str) -> String {
+        let mut stream = TcpStream::connect(address).await.unwrap();
+        stream
+            .write_all(
+                format!("GET {path} HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+                    .as_bytes(),
+            )
+            .await
+            .unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        response
+    }
+}
@@ -1,6 +1,7 @@
 mod plain_tcp;
 mod project;
 mod secure;
+mod websocket;
 
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::flow_control::FlowControlId;
@@ -10,6 +11,7 @@ use ockam_multiaddr::proto::Service;
 use ockam_multiaddr::{Match, MultiAddr, Protocol};
 use ockam_node::Context;
 use ockam_transport_tcp::{TcpConnection, TcpTransport};
+use ockam_transport_websocket::WebSocketConnection;
 
 use crate::error::ApiError;
 use crate::multiaddr_to_route;
@@ -20,6 +22,7 @@ pub(crate) use project::ProjectInstantiator;
 pub(crate) use secure::SecureChannelInstantiator;
 use std::fmt::{Debug, Formatter};
 use std::sync::Arc;
+pub(crate) use websocket::WebSocketInstantiator;
 
 #[derive(Clone)]
 pub struct Connection {
@@ -37,6 +40,8 @@ pub struct Connection {
     pub(crate) secure_channel_encryptors: Vec<Address>,
     /// A TCP worker address if used when instantiating the connection
     pub(crate) tcp_connection: Option<TcpConnection>,
+    /// A WebSocket connection if used when instantiating the connection
+    pub(crate) ws_connection: Option<WebSocketConnection>,
     /// If a flow control was created
     flow_control_id: Option<FlowControlId>,
 }
@@ -99,6 +104,7 @@ pub(crate) struct ConnectionBuilder {
     pub(crate) flow_control_id: Option<FlowControlId>,
     pub(crate) secure_channel_encryptors: Vec<Address>,
     pub(crate) tcp_connection: Option<TcpConnection>,
+    pub(crate) ws_connection: Option<WebSocketConnection>,
 }
 
 impl Debug for ConnectionBuilder {
@@ -128,6 +134,8 @@ pub struct Changes {
     pub secure_channel_encryptors: Vec<Address>,
     /// Optional, to keep track of tcp worker when created for the connection
     pub tcp_connection: Option<TcpConnection>,
+    /// Optional, to keep track of the WebSocket connection when created for the connection
+    pub ws_connection: Option<WebSocketConnection>,
 }
 
 /// Takes in a [`MultiAddr`] and instantiate it, can be implemented for any protocol.
@@ -162,6 +170,7 @@ impl ConnectionBuilder {
             secure_channel_encryptors: vec![],
             flow_control_id: None,
             tcp_connection: None,
+            ws_connection: None,
         }
     }
 
@@ -172,6 +181,7 @@ impl ConnectionBuilder {
             original_addr: self.original_multiaddr,
             secure_channel_encryptors: self.secure_channel_encryptors,
             tcp_connection: self.tcp_connection,
+            ws_connection: self.ws_connection,
             flow_control_id: self.flow_control_id,
         }
     }
@@ -213,7 +223,7 @@ impl ConnectionBuilder {
                         .append(&mut changes.secure_channel_encryptors);
 
                     if changes.tcp_connection.is_some() {
-                        if self.tcp_connection.is_some() {
+                        if self.tcp_connection.is_some() || self.ws_connection.is_some() {
                             return Err(ockam_core::Error::new(
                                 Origin::Transport,
                                 Kind::Unsupported,
@@ -223,6 +233,17 @@ impl ConnectionBuilder {
                         self.tcp_connection = changes.tcp_connection;
                     }
 
+                    if changes.ws_connection.is_some() {
+                        if self.ws_connection.is_some() || self.tcp_connection.is_some() {
+                            return Err(ockam_core::Error::new(
+                                Origin::Transport,
+                                Kind::Unsupported,
+                                "multiple transport connections created in a `MultiAddr`",
+                            ));
+                        }
+                        self.ws_connection = changes.ws_connection;
+                    }
+
                     if changes.flow_control_id.is_some() {
                         self.flow_control_id = changes.flow_control_id;
                     }
@@ -241,6 +262,7 @@ impl ConnectionBuilder {
             current_multiaddr: self.current_multiaddr,
             flow_control_id: self.flow_control_id,
             tcp_connection: self.tcp_connection,
+            ws_connection: self.ws_connection,
         })
     }
 
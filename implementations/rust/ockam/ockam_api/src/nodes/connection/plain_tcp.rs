@@ -66,6 +66,7 @@ impl Instantiator for PlainTcpInstantiator {
             flow_control_id: tcp.flow_control_id,
             secure_channel_encryptors: vec![],
             tcp_connection: Some(tcp_connection),
+            ws_connection: None,
         })
     }
 }
@@ -91,6 +91,7 @@ impl Instantiator for ProjectInstantiator {
             current_multiaddr,
             secure_channel_encryptors: vec![sc.encryptor_address().clone()],
             tcp_connection: tcp.tcp_connection,
+            ws_connection: None,
         })
     }
 }
@@ -77,6 +77,7 @@ impl Instantiator for SecureChannelInstantiator {
             flow_control_id: Some(sc.flow_control_id().clone()),
             secure_channel_encryptors: vec![sc.encryptor_address().clone()],
             tcp_connection: None,
+            ws_connection: None,
         })
     }
 }
@@ -0,0 +1,84 @@
+use crate::error::ApiError;
+use crate::nodes::connection::{Changes, ConnectionBuilder, Instantiator};
+use std::sync::Arc;
+
+use crate::nodes::NodeManager;
+use ockam_core::{async_trait, Error, Route};
+use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Service, Tcp, Ws, Wss};
+use ockam_multiaddr::{Match, MultiAddr, Protocol};
+use ockam_node::Context;
+use ockam_transport_websocket::WebSocketConnectionOptions;
+
+/// Creates the WebSocket connection for `/tcp/<port>/ws` and `/tcp/<port>/wss` addresses.
+pub(crate) struct WebSocketInstantiator {}
+
+impl WebSocketInstantiator {
+    pub(crate) fn new() -> Self {
+        Self {}
+    }
+}
+
+#[async_trait]
+impl Instantiator for WebSocketInstantiator {
+    fn matches(&self) -> Vec<Match> {
+        vec![
+            // matches any tcp address followed by a tcp protocol and a websocket protocol
+            Match::any([DnsAddr::CODE, Ip4::CODE, Ip6::CODE]),
+            Tcp::CODE.into(),
+            Match::any([Ws::CODE, Wss::CODE]),
+        ]
+    }
+
+    async fn instantiate(
+        &self,
+        _ctx: Arc<Context>,
+        node_manager: &NodeManager,
+        _transport_route: Route,
+        extracted: (MultiAddr, MultiAddr, MultiAddr),
+    ) -> Result<Changes, Error> {
+        let (before, ws_piece, after) = extracted;
+
+        let mut it = ws_piece.iter();
+        let (host, port, tls) = match (it.next(), it.next(), it.next()) {
+            (Some(host), Some(port), Some(ws)) => {
+                let host = if let Some(ip4) = host.cast::<Ip4>() {
+                    (*ip4).to_string()
+                } else if let Some(ip6) = host.cast::<Ip6>() {
+                    format!("[{}]", *ip6)
+                } else if let Some(dns) = host.cast::<DnsAddr>() {
+                    (*dns).to_string()
+                } else {
+                    return Err(ApiError::core(format!("invalid host in {ws_piece}")));
+                };
+                let port = port
+                    .cast::<Tcp>()
+                    .ok_or_else(|| ApiError::core(format!("invalid port in {ws_piece}")))?;
+                (host, *port, ws.code() == Wss::CODE)
+            }
+            _ => return Err(ApiError::core(format!("invalid address {ws_piece}"))),
+        };
+
+        let mut options = WebSocketConnectionOptions::new();
+        if tls {
+            options = options.with_tls();
+        }
+        let flow_control_id = options.flow_control_id();
+        let connection = node_manager
+            .ws_transport()
+            .await?
+            .connect(format!("{host}:{port}"), options)
+            .await?;
+
+        let mut multiaddr = MultiAddr::default();
+        multiaddr.push_back(Service::new(connection.sender_address().address()))?;
+        let current_multiaddr = ConnectionBuilder::combine(before, multiaddr, after)?;
+
+        Ok(Changes {
+            current_multiaddr,
+            flow_control_id: Some(flow_control_id),
+            secure_channel_encryptors: vec![],
+            tcp_connection: None,
+            ws_connection: Some(connection),
+        })
+    }
+}
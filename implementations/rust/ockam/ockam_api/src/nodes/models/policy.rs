@@ -1,5 +1,5 @@
 use minicbor::{Decode, Encode};
-use ockam_abac::{Action, Expr};
+use ockam_abac::{Action, Expr, PolicyDecision};
 
 #[derive(Debug, Decode, Encode)]
 #[rustfmt::skip]
@@ -39,3 +39,20 @@ impl Expression {
         &self.expr
     }
 }
+
+#[derive(Debug, Decode, Encode)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct PolicyDecisionList {
+    #[n(1)] decisions: Vec<PolicyDecision>,
+}
+
+impl PolicyDecisionList {
+    pub fn new(decisions: Vec<PolicyDecision>) -> Self {
+        PolicyDecisionList { decisions }
+    }
+
+    pub fn decisions(&self) -> &Vec<PolicyDecision> {
+        &self.decisions
+    }
+}
@@ -1,6 +1,8 @@
 //! Inlets and outlet request/response types
 
+use std::fmt::{Display, Formatter};
 use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::str::FromStr;
 use std::time::Duration;
 
 use minicbor::{Decode, Encode};
@@ -128,6 +130,14 @@ pub struct CreateOutlet {
     /// Allow the outlet to be reachable from the default secure channel, useful when we want to
     /// tighten the flow control
     #[n(4)] pub reachable_from_default_secure_channel: bool,
+    /// `host:port` targets to spread the connections over, instead of `socket_addr`.
+    /// Host names are resolved again periodically
+    #[n(5)] pub targets: Option<Vec<String>>,
+    /// Strategy used to pick the target of each connection, `round-robin` or `least-connections`
+    #[n(6)] pub load_balancing: Option<String>,
+    /// Read the connections as HTTP/1.1 requests, to route them, check the policies
+    /// of their path, and add the identity of the caller to them
+    #[n(7)] pub http: Option<HttpOutletConfig>,
 }
 
 impl CreateOutlet {
@@ -142,8 +152,96 @@ impl CreateOutlet {
             worker_addr,
             alias: alias.into(),
             reachable_from_default_secure_channel,
+            targets: None,
+            load_balancing: None,
+            http: None,
         }
     }
+
+    pub fn with_targets(mut self, targets: Vec<String>, load_balancing: Option<String>) -> Self {
+        self.targets = Some(targets);
+        self.load_balancing = load_balancing;
+        self
+    }
+
+    pub fn with_http(mut self, http: HttpOutletConfig) -> Self {
+        self.http = Some(http);
+        self
+    }
+}
+
+/// HTTP settings of an outlet
+#[derive(Clone, Debug, Default, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct HttpOutletConfig {
+    /// Routes sending some of the requests to other targets than the ones of the outlet
+    #[n(1)] pub routes: Vec<HttpRoute>,
+    /// Attributes of the caller added to the requests, in `X-Ockam-Attribute-<name>` headers
+    #[n(2)] pub attributes: Vec<String>,
+}
+
+/// Route sending the HTTP requests for a host and/or a path to some targets
+#[derive(Clone, Debug, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct HttpRoute {
+    /// Host of the requests, any host if not set
+    #[n(1)] pub host: Option<String>,
+    /// Path of the requests, including the paths below it
+    #[n(2)] pub path: String,
+    /// `host:port` targets of the requests
+    #[n(3)] pub targets: Vec<String>,
+}
+
+impl HttpRoute {
+    pub fn new(host: Option<String>, path: impl Into<String>, targets: Vec<String>) -> Self {
+        Self {
+            host,
+            path: path.into(),
+            targets,
+        }
+    }
+}
+
+impl Display for HttpRoute {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}{}={}",
+            self.host.as_deref().unwrap_or_default(),
+            self.path,
+            self.targets.join(",")
+        )
+    }
+}
+
+/// Parse a route written as `[HOST]/PATH=TARGET[,TARGET...]`
+impl FromStr for HttpRoute {
+    type Err = ApiError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (location, targets) = s
+            .split_once('=')
+            .ok_or_else(|| ApiError::message(format!("Missing targets in the HTTP route {s}")))?;
+        let (host, path) = match location.find('/') {
+            Some(0) => (None, location),
+            Some(index) => (Some(&location[..index]), &location[index..]),
+            None if !location.is_empty() => (Some(location), "/"),
+            None => (None, "/"),
+        };
+        let targets: Vec<String> = targets
+            .split(',')
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty())
+            .collect();
+        if targets.is_empty() {
+            return Err(ApiError::message(format!(
+                "Missing targets in the HTTP route {s}"
+            )));
+        }
+        Ok(Self::new(host.map(|h| h.to_string()), path, targets))
+    }
 }
 
 /// Response body when interacting with a portal endpoint
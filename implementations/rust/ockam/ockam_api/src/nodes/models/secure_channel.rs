@@ -3,7 +3,9 @@ use std::time::Duration;
 use minicbor::{Decode, Encode};
 use serde::Serialize;
 
-use ockam::identity::{Identifier, SecureChannel, DEFAULT_TIMEOUT};
+use ockam::identity::{
+    Identifier, SecureChannel, SecureChannelStatistics, TimestampInSeconds, DEFAULT_TIMEOUT,
+};
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::{route, Address, Result};
 use ockam_multiaddr::MultiAddr;
@@ -217,6 +219,7 @@ pub struct ShowSecureChannelResponse {
     #[n(2)] pub route: Option<String>,
     #[n(3)] pub authorized_identifiers: Option<Vec<String>>,
     #[n(4)] pub flow_control_id: Option<FlowControlId>,
+    #[n(5)] pub statistics: Option<SecureChannelStatisticsResponse>,
 }
 
 impl ShowSecureChannelResponse {
@@ -235,6 +238,42 @@ impl ShowSecureChannelResponse {
                 })
                 .unwrap_or(None),
             flow_control_id: info.map(|info| info.sc().flow_control_id().clone()),
+            statistics: None,
+        }
+    }
+
+    pub fn with_statistics(mut self, statistics: &SecureChannelStatistics) -> Self {
+        self.statistics = Some(statistics.into());
+        self
+    }
+}
+
+/// Usage statistics of the keys of a Secure Channel
+#[derive(Debug, Clone, Decode, Encode, Serialize)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct SecureChannelStatisticsResponse {
+    #[n(1)] pub encrypted_messages: u64,
+    #[n(2)] pub decrypted_messages: u64,
+    #[n(3)] pub encryption_key_renewals: u64,
+    #[n(4)] pub decryption_key_renewals: u64,
+    #[n(5)] pub remaining_encryption_nonces: u64,
+    #[n(6)] pub decryption_keys_exhausted: bool,
+    #[n(7)] pub rekeys: u64,
+    #[n(8)] pub last_rekey_at: Option<TimestampInSeconds>,
+}
+
+impl From<&SecureChannelStatistics> for SecureChannelStatisticsResponse {
+    fn from(statistics: &SecureChannelStatistics) -> Self {
+        Self {
+            encrypted_messages: statistics.encrypted_messages(),
+            decrypted_messages: statistics.decrypted_messages(),
+            encryption_key_renewals: statistics.encryption_key_renewals(),
+            decryption_key_renewals: statistics.decryption_key_renewals(),
+            remaining_encryption_nonces: statistics.remaining_encryption_nonces(),
+            decryption_keys_exhausted: statistics.decryption_keys_exhausted(),
+            rekeys: statistics.rekeys(),
+            last_rekey_at: statistics.last_rekey_at(),
         }
     }
 }
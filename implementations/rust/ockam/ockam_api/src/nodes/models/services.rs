@@ -3,6 +3,8 @@ use ockam_core::compat::net::SocketAddr;
 use ockam_core::Address;
 use ockam_multiaddr::MultiAddr;
 
+use crate::kafka::{FieldProtectionSecret, TopicFieldProtection};
+
 use serde::Serialize;
 
 #[derive(Debug, Clone, Decode, Encode)]
@@ -73,6 +75,7 @@ pub struct StartKafkaConsumerRequest {
     #[n(1)] pub bootstrap_server_addr: SocketAddr,
     #[n(2)] brokers_port_range: (u16, u16),
     #[n(3)] project_route: String,
+    #[n(4)] consumer_ids: Vec<String>,
 }
 
 impl StartKafkaConsumerRequest {
@@ -80,11 +83,13 @@ impl StartKafkaConsumerRequest {
         bootstrap_server_addr: SocketAddr,
         brokers_port_range: impl Into<(u16, u16)>,
         project_route: MultiAddr,
+        consumer_ids: Vec<String>,
     ) -> Self {
         Self {
             bootstrap_server_addr,
             brokers_port_range: brokers_port_range.into(),
             project_route: project_route.to_string(),
+            consumer_ids,
         }
     }
 
@@ -97,6 +102,10 @@ impl StartKafkaConsumerRequest {
     pub fn project_route(&self) -> &String {
         &self.project_route
     }
+    /// Return the identifiers of the consumers, used to name the relays of a consumer
+    pub fn consumer_ids(&self) -> &Vec<String> {
+        &self.consumer_ids
+    }
 }
 
 #[derive(Debug, Clone, Decode, Encode)]
@@ -106,6 +115,9 @@ pub struct StartKafkaProducerRequest {
     #[n(1)] pub bootstrap_server_addr: SocketAddr,
     #[n(2)] brokers_port_range: (u16, u16),
     #[n(3)] project_route: String,
+    #[n(4)] consumer_ids: Vec<String>,
+    #[n(5)] field_protections: Vec<TopicFieldProtection>,
+    #[n(6)] field_protection_secret: Option<FieldProtectionSecret>,
 }
 
 impl StartKafkaProducerRequest {
@@ -113,14 +125,25 @@ impl StartKafkaProducerRequest {
         bootstrap_server_addr: SocketAddr,
         brokers_port_range: impl Into<(u16, u16)>,
         project_route: MultiAddr,
+        consumer_ids: Vec<String>,
+        field_protections: Vec<TopicFieldProtection>,
     ) -> Self {
         Self {
             bootstrap_server_addr,
             brokers_port_range: brokers_port_range.into(),
             project_route: project_route.to_string(),
+            consumer_ids,
+            field_protections,
+            field_protection_secret: None,
         }
     }
 
+    /// Set the secret used to derive the keys encrypting the record keys and headers
+    pub fn with_field_protection_secret(mut self, secret: Option<FieldProtectionSecret>) -> Self {
+        self.field_protection_secret = secret;
+        self
+    }
+
     pub fn bootstrap_server_addr(&self) -> SocketAddr {
         self.bootstrap_server_addr
     }
@@ -130,6 +153,17 @@ impl StartKafkaProducerRequest {
     pub fn project_route(&self) -> &String {
         &self.project_route
     }
+    /// Return the identifiers of the consumers every record is encrypted for
+    pub fn consumer_ids(&self) -> &Vec<String> {
+        &self.consumer_ids
+    }
+    /// Return the fields of the records encrypted for each topic
+    pub fn field_protections(&self) -> &Vec<TopicFieldProtection> {
+        &self.field_protections
+    }
+    pub fn field_protection_secret(&self) -> Option<&FieldProtectionSecret> {
+        self.field_protection_secret.as_ref()
+    }
 }
 
 #[derive(Debug, Clone, Decode, Encode)]
@@ -139,7 +173,9 @@ pub struct StartKafkaDirectRequest {
     #[n(1)] bind_address: SocketAddr,
     #[n(2)] bootstrap_server_addr: SocketAddr,
     #[n(3)] brokers_port_range: (u16, u16),
-    #[n(4)] consumer_route: Option<String>,
+    #[n(4)] consumer_routes: Vec<String>,
+    #[n(5)] field_protections: Vec<TopicFieldProtection>,
+    #[n(6)] field_protection_secret: Option<FieldProtectionSecret>,
 }
 
 impl StartKafkaDirectRequest {
@@ -147,16 +183,25 @@ impl StartKafkaDirectRequest {
         bind_address: SocketAddr,
         bootstrap_server_addr: SocketAddr,
         brokers_port_range: impl Into<(u16, u16)>,
-        consumer_route: Option<MultiAddr>,
+        consumer_routes: Vec<MultiAddr>,
+        field_protections: Vec<TopicFieldProtection>,
     ) -> Self {
         Self {
             bind_address,
             bootstrap_server_addr,
             brokers_port_range: brokers_port_range.into(),
-            consumer_route: consumer_route.map(|a| a.to_string()),
+            consumer_routes: consumer_routes.iter().map(|a| a.to_string()).collect(),
+            field_protections,
+            field_protection_secret: None,
         }
     }
 
+    /// Set the secret used to derive the keys encrypting the record keys and headers
+    pub fn with_field_protection_secret(mut self, secret: Option<FieldProtectionSecret>) -> Self {
+        self.field_protection_secret = secret;
+        self
+    }
+
     pub fn bind_address(&self) -> SocketAddr {
         self.bind_address
     }
@@ -166,8 +211,14 @@ impl StartKafkaDirectRequest {
     pub fn brokers_port_range(&self) -> (u16, u16) {
         self.brokers_port_range
     }
-    pub fn consumer_route(&self) -> Option<String> {
-        self.consumer_route.clone()
+    pub fn consumer_routes(&self) -> &Vec<String> {
+        &self.consumer_routes
+    }
+    pub fn field_protections(&self) -> &Vec<TopicFieldProtection> {
+        &self.field_protections
+    }
+    pub fn field_protection_secret(&self) -> Option<&FieldProtectionSecret> {
+        self.field_protection_secret.as_ref()
     }
 }
 
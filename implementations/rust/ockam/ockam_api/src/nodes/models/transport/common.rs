@@ -1,5 +1,6 @@
 use minicbor::{Decode, Encode};
 use ockam_transport_tcp::TcpConnectionMode;
+use ockam_transport_websocket::WebSocketConnectionMode;
 use std::fmt::{self, Display};
 
 /// Encode which type of transport is being requested
@@ -48,6 +49,15 @@ impl From<TcpConnectionMode> for TransportMode {
     }
 }
 
+impl From<WebSocketConnectionMode> for TransportMode {
+    fn from(value: WebSocketConnectionMode) -> Self {
+        match value {
+            WebSocketConnectionMode::Outgoing => Self::Outgoing,
+            WebSocketConnectionMode::Incoming => Self::Incoming,
+        }
+    }
+}
+
 impl Display for TransportMode {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.write_str(match self {
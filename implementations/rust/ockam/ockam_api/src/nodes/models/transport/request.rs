@@ -7,11 +7,42 @@ use minicbor::{Decode, Encode};
 pub struct CreateTcpConnection {
     /// The address payload for the transport
     #[n(1)] pub addr: String,
+    /// Wrap the connection in TLS
+    #[n(2)] pub tls: bool,
+    /// Path to an additional PEM encoded CA certificate to trust
+    #[n(3)] pub ca_certificate: Option<String>,
+    /// Name used for SNI and to verify the server certificate, instead of the host of `addr`
+    #[n(4)] pub server_name: Option<String>,
+    /// Path to the PEM encoded client certificate chain
+    #[n(5)] pub client_certificate: Option<String>,
+    /// Path to the PEM encoded private key of the client certificate
+    #[n(6)] pub client_private_key: Option<String>,
 }
 
 impl CreateTcpConnection {
     pub fn new(addr: String) -> Self {
-        Self { addr }
+        Self {
+            addr,
+            tls: false,
+            ca_certificate: None,
+            server_name: None,
+            client_certificate: None,
+            client_private_key: None,
+        }
+    }
+
+    pub fn with_tls(mut self, ca_certificate: Option<String>, server_name: Option<String>) -> Self {
+        self.tls = true;
+        self.ca_certificate = ca_certificate;
+        self.server_name = server_name;
+        self
+    }
+
+    pub fn with_client_certificate(mut self, certificate: String, private_key: String) -> Self {
+        self.tls = true;
+        self.client_certificate = Some(certificate);
+        self.client_private_key = Some(private_key);
+        self
     }
 }
 
@@ -22,11 +53,80 @@ impl CreateTcpConnection {
 pub struct CreateTcpListener {
     /// The address payload for the transport
     #[n(1)] pub addr: String,
+    /// Path to the PEM encoded certificate chain, required to accept TLS connections
+    #[n(2)] pub certificate: Option<String>,
+    /// Path to the PEM encoded private key of the certificate
+    #[n(3)] pub private_key: Option<String>,
+    /// Path to the PEM encoded CA certificate which must have signed the client certificates
+    #[n(4)] pub client_ca_certificate: Option<String>,
 }
 
 impl CreateTcpListener {
     pub fn new(addr: String) -> Self {
-        Self { addr }
+        Self {
+            addr,
+            certificate: None,
+            private_key: None,
+            client_ca_certificate: None,
+        }
+    }
+
+    pub fn with_tls(
+        mut self,
+        certificate: String,
+        private_key: String,
+        client_ca_certificate: Option<String>,
+    ) -> Self {
+        self.certificate = Some(certificate);
+        self.private_key = Some(private_key);
+        self.client_ca_certificate = client_ca_certificate;
+        self
+    }
+}
+
+/// Request body when instructing a node to create a WebSocket connection
+#[derive(Debug, Clone, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateWebSocketConnection {
+    /// The `host:port` address to connect to
+    #[n(1)] pub addr: String,
+    /// Connect with `wss://` instead of `ws://`
+    #[n(2)] pub tls: bool,
+    /// Path to an additional PEM encoded CA certificate to trust
+    #[n(3)] pub ca_certificate: Option<String>,
+}
+
+impl CreateWebSocketConnection {
+    pub fn new(addr: String, tls: bool, ca_certificate: Option<String>) -> Self {
+        Self {
+            addr,
+            tls,
+            ca_certificate,
+        }
+    }
+}
+
+/// Request body when instructing a node to create a WebSocket listener
+#[derive(Debug, Clone, Decode, Encode, PartialEq, Eq)]
+#[rustfmt::skip]
+#[cbor(map)]
+pub struct CreateWebSocketListener {
+    /// The address to bind to
+    #[n(1)] pub addr: String,
+    /// Path to the PEM encoded certificate chain, required to accept `wss://` connections
+    #[n(2)] pub certificate: Option<String>,
+    /// Path to the PEM encoded private key of the certificate
+    #[n(3)] pub private_key: Option<String>,
+}
+
+impl CreateWebSocketListener {
+    pub fn new(addr: String, certificate: Option<String>, private_key: Option<String>) -> Self {
+        Self {
+            addr,
+            certificate,
+            private_key,
+        }
     }
 }
 
@@ -7,6 +7,7 @@ use ockam_core::{Error, Result};
 use ockam_multiaddr::proto::Worker;
 use ockam_multiaddr::MultiAddr;
 use ockam_transport_tcp::{TcpConnection, TcpListener, TcpListenerInfo, TcpSenderInfo};
+use ockam_transport_websocket::{WebSocketConnection, WebSocketListener};
 use std::net::SocketAddrV4;
 
 /// Response body when interacting with a transport
@@ -116,6 +117,32 @@ impl From<TcpListener> for TransportStatus {
     }
 }
 
+impl From<WebSocketConnection> for TransportStatus {
+    fn from(value: WebSocketConnection) -> Self {
+        Self {
+            tt: TransportType::WebSocket,
+            tm: value.mode().into(),
+            socket_addr: value.socket_address().to_string(),
+            worker_addr: value.sender_address().to_string(),
+            processor_address: value.receiver_address().to_string(),
+            flow_control_id: value.flow_control_id().clone(),
+        }
+    }
+}
+
+impl From<WebSocketListener> for TransportStatus {
+    fn from(value: WebSocketListener) -> Self {
+        Self {
+            tt: TransportType::WebSocket,
+            tm: TransportMode::Listen,
+            socket_addr: value.socket_address().to_string(),
+            worker_addr: "<none>".into(),
+            processor_address: value.processor_address().to_string(),
+            flow_control_id: value.flow_control_id().clone(),
+        }
+    }
+}
+
 /// Response body when interacting with a transport
 #[derive(Debug, Clone, Decode, Encode)]
 #[rustfmt::skip]
@@ -13,18 +13,20 @@ use ockam::identity::models::CredentialAndPurposeKey;
 use ockam::identity::TrustContext;
 use ockam::identity::{Credentials, CredentialsServer, Identities};
 use ockam::identity::{CredentialsServerModule, IdentityAttributesRepository};
-use ockam::identity::{Identifier, SecureChannels};
+use ockam::identity::{Identifier, IdentityKeyRotationPolicy, IdentityKeyRotator, SecureChannels};
 use ockam::{
     Address, Context, RelayService, RelayServiceOptions, Result, Routed, TcpTransport, Worker,
 };
 use ockam_abac::expr::{eq, ident, str};
-use ockam_abac::{Action, Env, Expr, Policy, Resource};
+use ockam_abac::{Action, Env, Expr, Policy, PolicyAuditRepository, PolicyAuditWriter, Resource};
 use ockam_core::api::{Method, RequestHeader, Response};
 use ockam_core::compat::{string::String, sync::Arc};
 use ockam_core::flow_control::FlowControlId;
-use ockam_core::AllowAll;
 use ockam_core::IncomingAccessControl;
+use ockam_core::{AllowAll, DenyAll};
 use ockam_multiaddr::MultiAddr;
+use ockam_transport_websocket::WebSocketTransport;
+use tokio::sync::OnceCell;
 
 use crate::bootstrapped_identities_store::PreTrustedIdentities;
 use crate::cli_state::CliState;
@@ -33,7 +35,7 @@ use crate::cloud::{AuthorityNode, ProjectNode};
 use crate::error::ApiError;
 use crate::nodes::connection::{
     Connection, ConnectionBuilder, PlainTcpInstantiator, ProjectInstantiator,
-    SecureChannelInstantiator,
+    SecureChannelInstantiator, WebSocketInstantiator,
 };
 use crate::nodes::models::portal::{OutletList, OutletStatus};
 use crate::nodes::models::transport::{TransportMode, TransportType};
@@ -60,8 +62,11 @@ pub mod relay;
 pub mod resources;
 mod secure_channel;
 mod transport;
+mod websocket;
 pub mod workers;
 
+pub use policy::{policies_path, policy_path};
+
 const TARGET: &str = "ockam_api::nodemanager::service";
 
 pub(crate) type Alias = String;
@@ -96,10 +101,13 @@ pub struct NodeManager {
     node_identifier: Identifier,
     api_transport_flow_control_id: FlowControlId,
     pub(crate) tcp_transport: TcpTransport,
+    ws_transport: OnceCell<WebSocketTransport>,
     pub(crate) secure_channels: Arc<SecureChannels>,
     trust_context: Option<TrustContext>,
     pub(crate) registry: Registry,
     pub(crate) medic_handle: MedicHandle,
+    tracing: bool,
+    policy_audit: Option<Arc<dyn PolicyAuditRepository>>,
 }
 
 impl NodeManager {
@@ -146,6 +154,14 @@ impl NodeManager {
         &self.tcp_transport
     }
 
+    /// The WebSocket transport is only started when a WebSocket listener or
+    /// connection is first requested
+    pub async fn ws_transport(&self) -> Result<&WebSocketTransport> {
+        self.ws_transport
+            .get_or_try_init(|| WebSocketTransport::create(self.tcp_transport.ctx()))
+            .await
+    }
+
     pub async fn list_outlets(&self) -> OutletList {
         OutletList::new(
             self.registry
@@ -260,6 +276,10 @@ impl NodeManager {
                 .cli_state
                 .make_policy_access_control(resource, action, env)
                 .await?;
+            let policy_access_control = match self.policy_audit() {
+                Some(audit) => policy_access_control.with_audit_repository(audit),
+                None => policy_access_control,
+            };
             Ok(Arc::new(policy_access_control))
         } else {
             debug!(
@@ -270,6 +290,11 @@ impl NodeManager {
         }
     }
 
+    /// Return the repository recording policy decisions, if the policy audit is enabled
+    pub(crate) fn policy_audit(&self) -> Option<Arc<dyn PolicyAuditRepository>> {
+        self.policy_audit.clone()
+    }
+
     pub(crate) fn trust_context(&self) -> Result<&TrustContext> {
         self.trust_context
             .as_ref()
@@ -283,6 +308,9 @@ pub struct NodeManagerGeneralOptions {
     pre_trusted_identities: Option<PreTrustedIdentities>,
     start_default_services: bool,
     persistent: bool,
+    identity_key_rotation: Option<IdentityKeyRotationPolicy>,
+    tracing: bool,
+    policy_audit: bool,
 }
 
 impl NodeManagerGeneralOptions {
@@ -299,8 +327,32 @@ impl NodeManagerGeneralOptions {
             pre_trusted_identities,
             start_default_services,
             persistent,
+            identity_key_rotation: None,
+            tracing: false,
+            policy_audit: false,
         }
     }
+
+    /// Rotate the key of the node identity according to a rotation policy
+    pub fn with_identity_key_rotation(
+        mut self,
+        identity_key_rotation: Option<IdentityKeyRotationPolicy>,
+    ) -> Self {
+        self.identity_key_rotation = identity_key_rotation;
+        self
+    }
+
+    /// Start a distributed trace for each connection accepted by the node inlets
+    pub fn with_tracing(mut self, tracing: bool) -> Self {
+        self.tracing = tracing;
+        self
+    }
+
+    /// Record the decisions taken when evaluating the policies of the node
+    pub fn with_policy_audit(mut self, policy_audit: bool) -> Self {
+        self.policy_audit = policy_audit;
+        self
+    }
 }
 
 #[derive(Clone)]
@@ -375,7 +427,7 @@ impl NodeManager {
 
         debug!("create the trust context");
         let tcp_transport = transport_options.tcp_transport;
-        let trust_context = match trust_options.trust_context {
+        let trust_context = match &trust_options.trust_context {
             None => None,
             Some(tc) => Some(
                 tc.trust_context(&tcp_transport, secure_channels.clone())
@@ -389,21 +441,92 @@ impl NodeManager {
             .await?
             .identifier();
 
+        debug!("create the revocation list refresher");
+        let revocation_list_refresher = match &trust_options.trust_context {
+            None => None,
+            Some(tc) => match tc
+                .revocation_list_refresher(
+                    &tcp_transport,
+                    secure_channels.clone(),
+                    &node_identifier,
+                )
+                .await
+            {
+                Ok(refresher) => refresher,
+                Err(e) => {
+                    warn!("cannot retrieve revocation lists from the trust context authority: {e}");
+                    None
+                }
+            },
+        };
+
+        let identity_key_rotator = match general_options.identity_key_rotation {
+            None => None,
+            Some(policy) => Some(match &trust_options.trust_context {
+                None => IdentityKeyRotator::new(
+                    secure_channels.clone(),
+                    node_identifier.clone(),
+                    policy,
+                ),
+                Some(tc) => {
+                    tc.identity_key_rotator(
+                        &tcp_transport,
+                        secure_channels.clone(),
+                        &node_identifier,
+                        policy,
+                    )
+                    .await?
+                }
+            }),
+        };
+
+        // The decisions are written in the background to avoid delaying the messages
+        let policy_audit: Option<Arc<dyn PolicyAuditRepository>> = if general_options.policy_audit {
+            Some(Arc::new(PolicyAuditWriter::start(
+                cli_state.policy_audit_repository().await?,
+            )))
+        } else {
+            None
+        };
+
         let mut s = Self {
             cli_state,
             node_name: general_options.node_name,
             node_identifier,
             api_transport_flow_control_id: transport_options.api_transport_flow_control_id,
             tcp_transport,
+            ws_transport: OnceCell::new(),
             secure_channels,
             trust_context,
             registry: Default::default(),
             medic_handle,
+            tracing: general_options.tracing,
+            policy_audit,
         };
 
         debug!("retrieve the node identifier");
         s.initialize_services(ctx, general_options.start_default_services)
             .await?;
+
+        if let Some(refresher) = revocation_list_refresher {
+            ctx.start_processor_with_access_control(
+                Address::random_tagged("RevocationListRefresher"),
+                refresher,
+                DenyAll,
+                AllowAll,
+            )
+            .await?;
+        }
+
+        if let Some(rotator) = identity_key_rotator {
+            ctx.start_processor_with_access_control(
+                Address::random_tagged("IdentityKeyRotator"),
+                rotator,
+                DenyAll,
+                AllowAll,
+            )
+            .await?;
+        }
         info!("created a node manager for the node: {}", s.node_name);
 
         Ok(s)
@@ -507,6 +630,8 @@ impl NodeManager {
                 ProjectInstantiator::new(identifier.clone(), timeout, credential.clone()),
             )
             .await?
+            .instantiate(ctx.clone(), self, WebSocketInstantiator::new())
+            .await?
             .instantiate(ctx.clone(), self, PlainTcpInstantiator::new())
             .await?
             .instantiate(
@@ -583,6 +708,30 @@ impl NodeManagerWorker {
                 encode_response(req, self.delete_tcp_listener(dec.decode()?).await)?
             }
 
+            // ==*== WebSocket Connection ==*==
+            (Get, ["node", "ws", "connection"]) => self.get_ws_connections(req).await.to_vec()?,
+            (Get, ["node", "ws", "connection", address]) => {
+                encode_response(req, self.get_ws_connection(address.to_string()).await)?
+            }
+            (Post, ["node", "ws", "connection"]) => {
+                encode_response(req, self.create_ws_connection(ctx, dec.decode()?).await)?
+            }
+            (Delete, ["node", "ws", "connection"]) => {
+                encode_response(req, self.delete_ws_connection(dec.decode()?).await)?
+            }
+
+            // ==*== WebSocket Listeners ==*==
+            (Get, ["node", "ws", "listener"]) => self.get_ws_listeners(req).await.to_vec()?,
+            (Get, ["node", "ws", "listener", address]) => {
+                encode_response(req, self.get_ws_listener(address.to_string()).await)?
+            }
+            (Post, ["node", "ws", "listener"]) => {
+                encode_response(req, self.create_ws_listener(dec.decode()?).await)?
+            }
+            (Delete, ["node", "ws", "listener"]) => {
+                encode_response(req, self.delete_ws_listener(dec.decode()?).await)?
+            }
+
             // ==*== Credential ==*==
             (Post, ["node", "credentials", "actions", "get"]) => {
                 encode_response(req, self.get_credential(ctx, dec.decode()?).await)?
@@ -736,6 +885,9 @@ impl NodeManagerWorker {
             (Delete, ["policy", resource, action]) => {
                 encode_response(req, self.delete_policy(resource, action).await)?
             }
+            (Get, ["policy_audit"]) => {
+                encode_response(req, self.list_policy_decisions(dec.decode()?).await)?
+            }
 
             // ==*== Messages ==*==
             (Post, ["v0", "message"]) => {
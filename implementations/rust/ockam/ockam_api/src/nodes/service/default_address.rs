@@ -10,6 +10,7 @@ impl DefaultAddress {
     pub const SECURE_CHANNEL_LISTENER: &'static str = "api";
     pub const DIRECT_AUTHENTICATOR: &'static str = "direct_authenticator";
     pub const CREDENTIAL_ISSUER: &'static str = "credential_issuer";
+    pub const REVOCATIONS_SERVICE: &'static str = "revocations";
     pub const ENROLLMENT_TOKEN_ISSUER: &'static str = "enrollment_token_issuer";
     pub const ENROLLMENT_TOKEN_ACCEPTOR: &'static str = "enrollment_token_acceptor";
     pub const OKTA_IDENTITY_PROVIDER: &'static str = "okta";
@@ -30,6 +31,7 @@ impl DefaultAddress {
                 | Self::SECURE_CHANNEL_LISTENER
                 | Self::DIRECT_AUTHENTICATOR
                 | Self::CREDENTIAL_ISSUER
+                | Self::REVOCATIONS_SERVICE
                 | Self::ENROLLMENT_TOKEN_ISSUER
                 | Self::ENROLLMENT_TOKEN_ACCEPTOR
                 | Self::OKTA_IDENTITY_PROVIDER
@@ -51,6 +53,7 @@ impl DefaultAddress {
             Self::SECURE_CHANNEL_LISTENER,
             Self::DIRECT_AUTHENTICATOR,
             Self::CREDENTIAL_ISSUER,
+            Self::REVOCATIONS_SERVICE,
             Self::ENROLLMENT_TOKEN_ISSUER,
             Self::ENROLLMENT_TOKEN_ACCEPTOR,
             Self::OKTA_IDENTITY_PROVIDER,
@@ -88,6 +91,9 @@ mod test {
             DefaultAddress::DIRECT_AUTHENTICATOR
         ));
         assert!(DefaultAddress::is_valid(DefaultAddress::CREDENTIAL_ISSUER));
+        assert!(DefaultAddress::is_valid(
+            DefaultAddress::REVOCATIONS_SERVICE
+        ));
         assert!(DefaultAddress::is_valid(
             DefaultAddress::ENROLLMENT_TOKEN_ISSUER
         ));
@@ -2,7 +2,7 @@ use std::net::IpAddr;
 
 use ockam::{Address, Context, Result};
 use ockam_abac::expr::{eq, ident, str};
-use ockam_abac::Policy;
+use ockam_abac::{Env, Policy};
 use ockam_core::api::{Error, Response};
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::route;
@@ -11,8 +11,9 @@ use ockam_multiaddr::MultiAddr;
 use super::{actions, resources, NodeManagerWorker};
 use crate::error::ApiError;
 use crate::kafka::{
-    ConsumerNodeAddr, KafkaInletController, KafkaPortalListener, KafkaSecureChannelControllerImpl,
-    KAFKA_OUTLET_BOOTSTRAP_ADDRESS, KAFKA_OUTLET_INTERCEPTOR_ADDRESS,
+    ConsumerNodeAddr, FieldProtectionSecret, KafkaFieldProtection, KafkaInletController,
+    KafkaPortalListener, KafkaSecureChannelControllerImpl, KafkaTopicAccessControl,
+    TopicFieldProtection, KAFKA_OUTLET_BOOTSTRAP_ADDRESS, KAFKA_OUTLET_INTERCEPTOR_ADDRESS,
 };
 use crate::kafka::{OutletManagerService, PrefixRelayService};
 use crate::nodes::models::services::{
@@ -51,11 +52,15 @@ impl NodeManagerWorker {
         body: StartServiceRequest<StartKafkaDirectRequest>,
     ) -> Result<Response<()>, Response<Error>> {
         let request = body.request();
-        let consumer_route: Option<MultiAddr> =
-            match request.consumer_route().map(|r| r.parse()).transpose() {
-                Ok(multiaddr) => multiaddr,
-                Err(e) => return Err(Response::bad_request_no_request(&e.to_string())),
-            };
+        let consumer_routes: Vec<MultiAddr> = match request
+            .consumer_routes()
+            .iter()
+            .map(|r| r.parse())
+            .collect::<Result<_, _>>()
+        {
+            Ok(multiaddrs) => multiaddrs,
+            Err(e) => return Err(Response::bad_request_no_request(&e.to_string())),
+        };
 
         match self
             .node_manager
@@ -66,7 +71,9 @@ impl NodeManagerWorker {
                 request.bind_address().port(),
                 request.brokers_port_range(),
                 *request.bootstrap_server_addr(),
-                consumer_route,
+                consumer_routes,
+                request.field_protections().clone(),
+                request.field_protection_secret().cloned(),
             )
             .await
         {
@@ -95,6 +102,9 @@ impl NodeManagerWorker {
                 request.bootstrap_server_addr().port(),
                 request.brokers_port_range(),
                 outlet_node_multiaddr,
+                request.consumer_ids().clone(),
+                vec![],
+                None,
                 KafkaServiceKind::Consumer,
             )
             .await
@@ -124,6 +134,9 @@ impl NodeManagerWorker {
                 request.bootstrap_server_addr().port(),
                 request.brokers_port_range(),
                 outlet_node_multiaddr,
+                request.consumer_ids().clone(),
+                request.field_protections().clone(),
+                request.field_protection_secret().cloned(),
                 KafkaServiceKind::Producer,
             )
             .await
@@ -170,8 +183,12 @@ impl InMemoryNode {
         server_bootstrap_port: u16,
         brokers_port_range: (u16, u16),
         bootstrap_server_addr: SocketAddr,
-        consumer_route: Option<MultiAddr>,
+        consumer_routes: Vec<MultiAddr>,
+        field_protections: Vec<TopicFieldProtection>,
+        field_protection_secret: Option<FieldProtectionSecret>,
     ) -> Result<()> {
+        let field_protection =
+            NodeManager::kafka_field_protection(field_protections, field_protection_secret)?;
         let default_secure_channel_listener_flow_control_id = context
             .flow_controls()
             .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
@@ -205,9 +222,10 @@ impl InMemoryNode {
             secure_channels = self.secure_channels.clone();
         }
 
+        let topic_access_control = self.kafka_topic_access_control(&trust_context_id).await?;
         let secure_channel_controller = KafkaSecureChannelControllerImpl::new(
             secure_channels,
-            ConsumerNodeAddr::Direct(consumer_route.clone()),
+            ConsumerNodeAddr::Direct(consumer_routes),
             trust_context_id,
         );
 
@@ -241,6 +259,8 @@ impl InMemoryNode {
             context,
             inlet_controller,
             secure_channel_controller.into_trait(),
+            field_protection,
+            Some(topic_access_control),
             local_interceptor_address.clone(),
         )
         .await?;
@@ -267,8 +287,13 @@ impl InMemoryNode {
         server_bootstrap_port: u16,
         brokers_port_range: (u16, u16),
         outlet_node_multiaddr: MultiAddr,
+        consumer_ids: Vec<String>,
+        field_protections: Vec<TopicFieldProtection>,
+        field_protection_secret: Option<FieldProtectionSecret>,
         kind: KafkaServiceKind,
     ) -> Result<()> {
+        let field_protection =
+            NodeManager::kafka_field_protection(field_protections, field_protection_secret)?;
         debug!(
             "outlet_node_multiaddr: {}",
             outlet_node_multiaddr.to_string()
@@ -299,9 +324,13 @@ impl InMemoryNode {
             }
         }
 
+        let topic_access_control = self.kafka_topic_access_control(&trust_context_id).await?;
         let secure_channel_controller = KafkaSecureChannelControllerImpl::new(
             secure_channels,
-            ConsumerNodeAddr::Relay(outlet_node_multiaddr.clone()),
+            ConsumerNodeAddr::Relay {
+                orchestrator_multiaddr: outlet_node_multiaddr.clone(),
+                consumer_ids,
+            },
             trust_context_id,
         );
 
@@ -335,6 +364,8 @@ impl InMemoryNode {
             context,
             inlet_controller,
             secure_channel_controller.into_trait(),
+            field_protection,
+            Some(topic_access_control),
             local_interceptor_address.clone(),
         )
         .await?;
@@ -351,6 +382,43 @@ impl InMemoryNode {
 }
 
 impl NodeManager {
+    /// Return the protection of the records fields. The keys and headers can only be encrypted
+    /// with a shared secret, otherwise each producer would encrypt them differently
+    fn kafka_field_protection(
+        field_protections: Vec<TopicFieldProtection>,
+        secret: Option<FieldProtectionSecret>,
+    ) -> Result<KafkaFieldProtection> {
+        let field_protection = KafkaFieldProtection::new(field_protections);
+        if field_protection.needs_data_keys() && secret.is_none() {
+            return Err(ApiError::core(
+                "a field protection secret is required to encrypt the keys or headers of the records",
+            ));
+        }
+        Ok(field_protection.with_secret(secret))
+    }
+
+    /// Create the access control evaluating the policies set on kafka topics
+    /// for the identity of this node, with the attributes of its credential
+    pub(crate) async fn kafka_topic_access_control(
+        &self,
+        trust_context_id: &str,
+    ) -> Result<KafkaTopicAccessControl> {
+        let mut env = Env::new();
+        env.put("resource.trust_context_id", str(trust_context_id));
+        let access_control = self
+            .cli_state
+            .make_kafka_topic_access_control(self.identifier(), env)
+            .await?
+            .with_trust_context(
+                self.trust_context()?.clone(),
+                self.identities().credentials(),
+            );
+        Ok(match self.policy_audit() {
+            Some(audit) => access_control.with_audit_repository(audit),
+            None => access_control,
+        })
+    }
+
     pub async fn start_kafka_outlet_service(
         &self,
         context: &Context,
@@ -1,10 +1,11 @@
 use ockam_abac::expr::{eq, ident, str};
-use ockam_abac::{Action, Policy, Resource};
+use ockam_abac::{check_policy, Action, Policy, PolicyDecision, PolicyDecisionFilter, Resource};
 use ockam_core::api::{Error, Request, Response};
 use ockam_core::{async_trait, Result};
 use ockam_node::Context;
+use tracing::warn;
 
-use crate::nodes::models::policy::{Expression, PolicyList};
+use crate::nodes::models::policy::{Expression, PolicyDecisionList, PolicyList};
 use crate::nodes::{BackgroundNode, NodeManagerWorker};
 
 use super::NodeManager;
@@ -16,8 +17,16 @@ impl NodeManagerWorker {
         action: &str,
         policy: Policy,
     ) -> Result<Response<()>, Response<Error>> {
-        let resource = Resource::new(resource);
+        let resource = unescape_resource(resource);
         let action = Action::new(action);
+        match check_policy(policy.expression()) {
+            Ok(warnings) => {
+                for w in warnings {
+                    warn!(%resource, %action, "{w}")
+                }
+            }
+            Err(e) => return Err(Response::bad_request_no_request(&e.to_string())),
+        }
         self.node_manager
             .set_policy(resource, action, policy)
             .await
@@ -30,7 +39,7 @@ impl NodeManagerWorker {
         resource: &str,
         action: &str,
     ) -> Result<Response<Policy>, Response<Error>> {
-        let resource = Resource::new(resource);
+        let resource = unescape_resource(resource);
         let action = Action::new(action);
         match self
             .node_manager
@@ -49,7 +58,7 @@ impl NodeManagerWorker {
         &self,
         resource: &str,
     ) -> Result<Response<PolicyList>, Response<Error>> {
-        let resource = Resource::new(resource);
+        let resource = unescape_resource(resource);
         match self.node_manager.get_policies_by_resource(&resource).await {
             Ok(policies) => Ok(Response::ok().body(PolicyList::new(
                 policies
@@ -61,12 +70,22 @@ impl NodeManagerWorker {
         }
     }
 
+    pub(super) async fn list_policy_decisions(
+        &self,
+        filter: PolicyDecisionFilter,
+    ) -> Result<Response<PolicyDecisionList>, Response<Error>> {
+        match self.node_manager.get_policy_decisions(&filter).await {
+            Ok(decisions) => Ok(Response::ok().body(PolicyDecisionList::new(decisions))),
+            Err(e) => Err(Response::internal_error_no_request(&e.to_string())),
+        }
+    }
+
     pub(super) async fn delete_policy(
         &self,
         resource: &str,
         action: &str,
     ) -> Result<Response<()>, Response<Error>> {
-        let resource = Resource::new(resource);
+        let resource = unescape_resource(resource);
         let action = Action::new(action);
         match self.node_manager.delete_policy(resource, action).await {
             Ok(_) => Ok(Response::ok()),
@@ -101,13 +120,35 @@ impl NodeManager {
         Ok(self.cli_state.get_policies_by_resource(resource).await?)
     }
 
+    /// Return the decisions taken when evaluating policies on this node, most recent first
+    pub async fn get_policy_decisions(
+        &self,
+        filter: &PolicyDecisionFilter,
+    ) -> Result<Vec<PolicyDecision>> {
+        Ok(self.cli_state.get_policy_decisions(filter).await?)
+    }
+
     pub async fn delete_policy(&self, resource: Resource, action: Action) -> Result<()> {
         Ok(self.cli_state.delete_policy(&resource, &action).await?)
     }
 }
 
-pub(crate) fn policy_path(r: &Resource, a: &Action) -> String {
-    format!("/policy/{r}/{a}")
+pub fn policy_path(r: &Resource, a: &Action) -> String {
+    format!("/policy/{}/{a}", escape_resource(r))
+}
+
+pub fn policies_path(r: &Resource) -> String {
+    format!("/policy/{}", escape_resource(r))
+}
+
+/// A resource name can contain a `/`, for example `kafka-topic/orders`,
+/// it is escaped to be a single segment of a request path
+fn escape_resource(r: &Resource) -> String {
+    r.as_str().replace('%', "%25").replace('/', "%2F")
+}
+
+fn unescape_resource(segment: &str) -> Resource {
+    Resource::new(&segment.replace("%2F", "/").replace("%25", "%"))
 }
 
 #[async_trait]
@@ -135,7 +176,7 @@ impl Policies for BackgroundNode {
 
         let resource = Resource::new(resource_name);
         let policies: PolicyList = self
-            .ask(ctx, Request::get(format!("/policy/{resource}")))
+            .ask(ctx, Request::get(policies_path(&resource)))
             .await?;
         if !policies.expressions().is_empty() {
             return Ok(());
@@ -152,3 +193,66 @@ impl Policies for BackgroundNode {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::http::{http_path_resource, REQUEST};
+    use ockam_abac::Env;
+
+    #[test]
+    fn test_policy_path() {
+        let resource = Resource::new("kafka-topic/orders");
+        let action = Action::new("produce");
+        assert_eq!(
+            policy_path(&resource, &action),
+            "/policy/kafka-topic%2Forders/produce"
+        );
+        assert_eq!(policies_path(&resource), "/policy/kafka-topic%2Forders");
+        assert_eq!(unescape_resource("kafka-topic%2Forders"), resource);
+
+        let resource = Resource::new("tcp-inlet");
+        assert_eq!(policies_path(&resource), "/policy/tcp-inlet");
+        assert_eq!(unescape_resource("tcp-inlet"), resource);
+
+        let resource = Resource::new("a%2F/b");
+        assert_eq!(unescape_resource(&escape_resource(&resource)), resource);
+    }
+
+    #[ockam_macros::test]
+    async fn test_add_http_method_policy(context: &mut Context) -> Result<()> {
+        let handler = crate::util::test_utils::start_manager_for_tests(context).await?;
+        let node_manager_worker = NodeManagerWorker::new(handler.node_manager.clone());
+        let caller = handler
+            .secure_channels
+            .identities()
+            .identities_creation()
+            .create_identity()
+            .await?;
+
+        // the policy is checked before being stored
+        let resource = http_path_resource("/admin");
+        let policy = Policy::new(eq([ident("action.method"), str("GET")]));
+        assert!(node_manager_worker
+            .add_policy(&escape_resource(&resource), REQUEST.as_str(), policy)
+            .await
+            .is_ok());
+
+        let access_control = handler
+            .cli_state
+            .make_http_path_access_control(Env::new())
+            .await?;
+        let segments = vec!["admin".to_string(), "users".to_string()];
+        assert!(
+            access_control
+                .is_authorized(Some(&caller), &segments, "GET")
+                .await?
+        );
+        assert!(
+            !access_control
+                .is_authorized(Some(&caller), &segments, "DELETE")
+                .await?
+        );
+        context.stop().await
+    }
+}
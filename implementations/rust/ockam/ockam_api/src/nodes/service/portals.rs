@@ -6,19 +6,22 @@ use tokio::time::timeout;
 
 use ockam::identity::Identifier;
 use ockam::{Address, Result};
-use ockam_abac::Resource;
+use ockam_abac::expr::str;
+use ockam_abac::{Env, Resource};
 use ockam_core::api::{Error, Reply, Request, RequestHeader, Response};
 use ockam_core::errcode::{Kind, Origin};
+use ockam_core::flow_control::FlowControlId;
 use ockam_core::{async_trait, route, AsyncTryClone, IncomingAccessControl, Route};
 use ockam_multiaddr::proto::Project;
 use ockam_multiaddr::{MultiAddr, Protocol};
 use ockam_node::Context;
-use ockam_transport_tcp::{TcpInletOptions, TcpOutletOptions};
+use ockam_transport_tcp::{LoadBalancing, TcpInletOptions, TcpOutletOptions};
 
 use crate::error::ApiError;
+use crate::http::{is_header_name, HttpOutletRules, HttpOutletService, HttpRouter};
 use crate::nodes::connection::Connection;
 use crate::nodes::models::portal::{
-    CreateInlet, CreateOutlet, InletList, InletStatus, OutletList, OutletStatus,
+    CreateInlet, CreateOutlet, HttpOutletConfig, InletList, InletStatus, OutletList, OutletStatus,
 };
 use crate::nodes::registry::{InletInfo, OutletInfo};
 use crate::nodes::service::default_address::DefaultAddress;
@@ -106,20 +109,51 @@ impl NodeManagerWorker {
             worker_addr,
             alias,
             reachable_from_default_secure_channel,
-            ..
+            targets,
+            load_balancing,
+            http,
         } = create_outlet;
 
-        match self
-            .node_manager
-            .create_outlet(
-                ctx,
-                socket_addr,
-                worker_addr,
-                alias,
-                reachable_from_default_secure_channel,
-            )
-            .await
+        let load_balancing = match load_balancing
+            .as_deref()
+            .map(str::parse::<LoadBalancing>)
+            .transpose()
         {
+            Ok(load_balancing) => load_balancing.unwrap_or_default(),
+            Err(e) => return Err(Response::bad_request_no_request(&e.to_string())),
+        };
+        let targets = targets.unwrap_or_else(|| vec![socket_addr.to_string()]);
+
+        let res = match http {
+            Some(http) => {
+                self.node_manager
+                    .create_http_outlet(
+                        ctx,
+                        socket_addr,
+                        targets,
+                        load_balancing,
+                        http,
+                        worker_addr,
+                        alias,
+                        reachable_from_default_secure_channel,
+                    )
+                    .await
+            }
+            None => {
+                self.node_manager
+                    .create_load_balanced_outlet(
+                        ctx,
+                        socket_addr,
+                        targets,
+                        load_balancing,
+                        worker_addr,
+                        alias,
+                        reachable_from_default_secure_channel,
+                    )
+                    .await
+            }
+        };
+        match res {
             Ok(outlet_status) => Ok(Response::ok().body(outlet_status)),
             Err(e) => Err(Response::bad_request_no_request(&format!("{e:?}"))),
         }
@@ -173,10 +207,90 @@ impl NodeManager {
         worker_addr: Address,
         alias: Option<String>,
         reachable_from_default_secure_channel: bool,
+    ) -> Result<OutletStatus> {
+        self.create_load_balanced_outlet(
+            ctx,
+            socket_addr,
+            vec![socket_addr.to_string()],
+            LoadBalancing::default(),
+            worker_addr,
+            alias,
+            reachable_from_default_secure_channel,
+        )
+        .await
+    }
+
+    /// Create an outlet spreading its connections over several `host:port` targets.
+    /// `socket_addr` is the address of the first target, reported in the outlet status
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_load_balanced_outlet(
+        &self,
+        ctx: &Context,
+        socket_addr: SocketAddr,
+        targets: Vec<String>,
+        load_balancing: LoadBalancing,
+        worker_addr: Address,
+        alias: Option<String>,
+        reachable_from_default_secure_channel: bool,
+    ) -> Result<OutletStatus> {
+        self.create_outlet_impl(
+            ctx,
+            socket_addr,
+            targets,
+            load_balancing,
+            None,
+            worker_addr,
+            alias,
+            reachable_from_default_secure_channel,
+        )
+        .await
+    }
+
+    /// Create an outlet reading its connections as HTTP/1.1 requests.
+    ///
+    /// The requests are sent to the targets of their route, or to the outlet targets
+    /// when no route matches, after checking the policies set on their path. The
+    /// identity of the caller is added to the requests as headers.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_http_outlet(
+        &self,
+        ctx: &Context,
+        socket_addr: SocketAddr,
+        targets: Vec<String>,
+        load_balancing: LoadBalancing,
+        http: HttpOutletConfig,
+        worker_addr: Address,
+        alias: Option<String>,
+        reachable_from_default_secure_channel: bool,
+    ) -> Result<OutletStatus> {
+        self.create_outlet_impl(
+            ctx,
+            socket_addr,
+            targets,
+            load_balancing,
+            Some(http),
+            worker_addr,
+            alias,
+            reachable_from_default_secure_channel,
+        )
+        .await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn create_outlet_impl(
+        &self,
+        ctx: &Context,
+        socket_addr: SocketAddr,
+        targets: Vec<String>,
+        load_balancing: LoadBalancing,
+        http: Option<HttpOutletConfig>,
+        worker_addr: Address,
+        alias: Option<String>,
+        reachable_from_default_secure_channel: bool,
     ) -> Result<OutletStatus> {
         info!(
-            "Handling request to create outlet portal at {:?} with worker {:?}",
-            socket_addr, worker_addr
+            "Handling request to create outlet portal to {:?} with worker {:?}",
+            targets, worker_addr
         );
         let resource = alias
             .as_deref()
@@ -204,31 +318,45 @@ impl NodeManager {
             )
             .await?;
 
-        let options = TcpOutletOptions::new().with_incoming_access_control(access_control);
-        let options = if self.trust_context_id().is_none() {
-            options.as_consumer(&self.api_transport_flow_control_id)
-        } else {
-            options
-        };
-
-        let options = if reachable_from_default_secure_channel {
+        let mut consumers = vec![];
+        if self.trust_context_id().is_none() {
+            consumers.push(self.api_transport_flow_control_id.clone());
+        }
+        if reachable_from_default_secure_channel {
             // Accept messages from the default secure channel listener
             if let Some(flow_control_id) = ctx
                 .flow_controls()
                 .get_flow_control_with_spawner(&DefaultAddress::SECURE_CHANNEL_LISTENER.into())
             {
-                options.as_consumer(&flow_control_id)
-            } else {
-                options
+                consumers.push(flow_control_id);
             }
-        } else {
-            options
-        };
+        }
 
-        let res = self
-            .tcp_transport
-            .create_tcp_outlet(worker_addr.clone(), socket_addr, options)
-            .await;
+        let res = match http {
+            Some(http) => {
+                self.start_http_outlet(
+                    ctx,
+                    worker_addr.clone(),
+                    targets,
+                    load_balancing,
+                    http,
+                    access_control,
+                    &consumers,
+                )
+                .await
+            }
+            None => {
+                let options = TcpOutletOptions::new()
+                    .with_incoming_access_control(access_control)
+                    .with_load_balancing(load_balancing);
+                let options = consumers
+                    .iter()
+                    .fold(options, |options, id| options.as_consumer(id));
+                self.tcp_transport
+                    .create_outlet_with_targets(worker_addr.clone(), targets, options)
+                    .await
+            }
+        };
 
         Ok(match res {
             Ok(_) => {
@@ -255,6 +383,53 @@ impl NodeManager {
         })
     }
 
+    /// Start the worker of an HTTP outlet, and the TCP outlets of its backends
+    #[allow(clippy::too_many_arguments)]
+    async fn start_http_outlet(
+        &self,
+        ctx: &Context,
+        worker_addr: Address,
+        targets: Vec<String>,
+        load_balancing: LoadBalancing,
+        http: HttpOutletConfig,
+        access_control: Arc<dyn IncomingAccessControl>,
+        consumers: &[FlowControlId],
+    ) -> Result<()> {
+        if let Some(name) = http.attributes.iter().find(|name| !is_header_name(name)) {
+            return Err(ApiError::core(format!(
+                "The attribute {name} can't be sent in a header"
+            )));
+        }
+
+        let mut env = Env::new();
+        if let Some(trust_context_id) = self.trust_context_id() {
+            env.put("resource.trust_context_id", str(trust_context_id));
+        }
+        let path_access_control = self.cli_state.make_http_path_access_control(env).await?;
+        let path_access_control = match self.policy_audit() {
+            Some(audit) => path_access_control.with_audit_repository(audit),
+            None => path_access_control,
+        };
+
+        let router = HttpRouter::create(
+            ctx,
+            &self.tcp_transport,
+            targets,
+            http.routes,
+            load_balancing,
+        )
+        .await?;
+        let rules = HttpOutletRules::new(
+            router,
+            path_access_control,
+            self.secure_channels
+                .identities()
+                .identity_attributes_repository(),
+            http.attributes,
+        );
+        HttpOutletService::create(ctx, worker_addr, rules, access_control, consumers).await
+    }
+
     pub async fn delete_outlet(&self, alias: &str) -> Result<Option<OutletInfo>> {
         info!(%alias, "Handling request to delete outlet portal");
         if let Some(deleted_outlet) = self.registry.outlets.remove(alias).await {
@@ -293,6 +468,16 @@ impl NodeManager {
 
 /// INLETS
 impl NodeManager {
+    /// Options for the inlets of this node
+    fn tcp_inlet_options(&self, access_control: Arc<dyn IncomingAccessControl>) -> TcpInletOptions {
+        let options = TcpInletOptions::new().with_incoming_access_control(access_control);
+        if self.tracing {
+            options.with_tracing()
+        } else {
+            options
+        }
+    }
+
     pub async fn create_inlet(
         &self,
         connection: Connection,
@@ -382,7 +567,7 @@ impl NodeManager {
             )
             .await?;
 
-        let options = TcpInletOptions::new().with_incoming_access_control(access_control.clone());
+        let options = self.tcp_inlet_options(access_control.clone());
         let res = self
             .tcp_transport
             .create_inlet(listen_addr.clone(), outlet_route.clone(), options)
@@ -646,6 +831,11 @@ impl InMemoryNode {
                             debug!("cannot stop tcp worker `{tcp_connection}`: {error}");
                         }
                     }
+                    if let Some(ws_connection) = previous_connection.ws_connection.as_ref() {
+                        if let Err(error) = ws_connection.stop(&ctx).await {
+                            debug!("cannot stop websocket worker `{ws_connection}`: {error}");
+                        }
+                    }
 
                     // The previous inlet worker needs to be stopped:
                     if let Err(error) = node_manager
@@ -673,7 +863,7 @@ impl InMemoryNode {
 
                     //we expect a fully normalized MultiAddr
                     let normalized_route = route![prefix_route, connection_route, suffix_route];
-                    let options = TcpInletOptions::new().with_incoming_access_control(access);
+                    let options = node_manager.tcp_inlet_options(access);
 
                     // Finally attempt to create a new inlet using the new route:
                     let new_inlet_address = node_manager
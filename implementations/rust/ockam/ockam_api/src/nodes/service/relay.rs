@@ -337,6 +337,11 @@ impl InMemoryNode {
                             debug!("cannot stop tcp worker `{tcp_connection}`: {error}");
                         }
                     }
+                    if let Some(ws_connection) = previous_connection.ws_connection.as_ref() {
+                        if let Err(error) = ws_connection.stop(&ctx).await {
+                            debug!("cannot stop websocket worker `{ws_connection}`: {error}");
+                        }
+                    }
 
                     let connection = node_manager
                         .make_connection(
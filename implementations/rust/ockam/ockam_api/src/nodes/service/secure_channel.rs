@@ -91,15 +91,18 @@ impl NodeManagerWorker {
     ) -> Result<Response<ShowSecureChannelResponse>, Response<Error>> {
         let ShowSecureChannelRequest { channel: address } = show_secure_channel;
 
-        let response =
-            self.node_manager
-                .get_secure_channel(&address)
-                .await
-                .map(|secure_channel| {
-                    Response::ok().body(ShowSecureChannelResponse::new(Some(secure_channel)))
-                })?;
+        let secure_channel = self.node_manager.get_secure_channel(&address).await?;
+        let mut response = ShowSecureChannelResponse::new(Some(secure_channel));
+        if let Some(entry) = self
+            .node_manager
+            .secure_channels
+            .secure_channel_registry()
+            .get_channel_by_encryptor_address(&address)
+        {
+            response = response.with_statistics(entry.statistics());
+        }
 
-        Ok(response)
+        Ok(Response::ok().body(response))
     }
 }
 
@@ -488,12 +491,8 @@ impl NodeManager {
 
     async fn get_secure_channels_vault(&self, vault_name: Option<String>) -> Result<Vault> {
         if let Some(vault_name) = vault_name {
-            let existing_vault = self
-                .cli_state
-                .get_named_vault(&vault_name)
-                .await?
-                .vault()
-                .await?;
+            let named_vault = self.cli_state.get_named_vault(&vault_name).await?;
+            let existing_vault = self.cli_state.make_vault(&named_vault).await?;
             Ok(existing_vault)
         } else {
             Ok(self.secure_channels.vault())
@@ -46,10 +46,9 @@ impl NodeManager {
     async fn create_tcp_connection(
         &self,
         address: String,
+        options: TcpConnectionOptions,
         ctx: &Context,
     ) -> Result<TransportStatus> {
-        let options = TcpConnectionOptions::new();
-
         // Add all Hop workers as consumers for Demo purposes
         // Production nodes should not run any Hop workers
         for hop in self.registry.hop_services.keys().await {
@@ -61,8 +60,11 @@ impl NodeManager {
         Ok(connection.into())
     }
 
-    async fn create_tcp_listener(&self, address: String) -> Result<TransportStatus> {
-        let options = TcpListenerOptions::new();
+    async fn create_tcp_listener(
+        &self,
+        address: String,
+        options: TcpListenerOptions,
+    ) -> Result<TransportStatus> {
         let listener = self.tcp_transport.listen(address, options).await?;
         Ok(listener.into())
     }
@@ -148,11 +150,40 @@ impl NodeManagerWorker {
         ctx: &Context,
         create: CreateTcpConnection,
     ) -> Result<Response<TransportStatus>, Response<Error>> {
-        let CreateTcpConnection { addr, .. } = create;
+        let CreateTcpConnection {
+            addr,
+            tls,
+            ca_certificate,
+            server_name,
+            client_certificate,
+            client_private_key,
+        } = create;
         info!("Handling request to create a new TCP connection: {addr}");
 
+        let mut options = TcpConnectionOptions::new();
+        if tls {
+            options = options.with_tls();
+        }
+        if let Some(ca_certificate) = ca_certificate {
+            options = options.with_tls_ca_certificate(ca_certificate);
+        }
+        if let Some(server_name) = server_name {
+            options = options.with_tls_server_name(server_name);
+        }
+        match (client_certificate, client_private_key) {
+            (Some(certificate), Some(private_key)) => {
+                options = options.with_tls_client_certificate(certificate, private_key);
+            }
+            (None, None) => {}
+            _ => {
+                return Err(Response::bad_request_no_request(
+                    "Both a client certificate and its private key are required",
+                ))
+            }
+        }
+
         self.node_manager
-            .create_tcp_connection(addr.to_string(), ctx)
+            .create_tcp_connection(addr.to_string(), options, ctx)
             .await
             .map(|status| Response::ok().body(status))
             .map_err(|msg| {
@@ -164,11 +195,32 @@ impl NodeManagerWorker {
         &self,
         create: CreateTcpListener,
     ) -> Result<Response<TransportStatus>, Response<Error>> {
-        let CreateTcpListener { addr, .. } = create;
+        let CreateTcpListener {
+            addr,
+            certificate,
+            private_key,
+            client_ca_certificate,
+        } = create;
         info!("Handling request to create a new tcp listener: {addr}");
 
+        let mut options = TcpListenerOptions::new();
+        match (certificate, private_key) {
+            (Some(certificate), Some(private_key)) => {
+                options = options.with_tls(certificate, private_key);
+                if let Some(client_ca_certificate) = client_ca_certificate {
+                    options = options.with_tls_client_ca_certificate(client_ca_certificate);
+                }
+            }
+            (None, None) if client_ca_certificate.is_none() => {}
+            _ => {
+                return Err(Response::bad_request_no_request(
+                    "TLS listeners require both a certificate and a private key",
+                ))
+            }
+        }
+
         self.node_manager
-            .create_tcp_listener(addr.to_string())
+            .create_tcp_listener(addr.to_string(), options)
             .await
             .map(|status| Response::ok().body(status))
             .map_err(|msg| {
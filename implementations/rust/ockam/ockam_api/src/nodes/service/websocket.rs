@@ -0,0 +1,242 @@
+use ockam::Result;
+use ockam_core::api::{Error, RequestHeader, Response};
+use ockam_core::Address;
+use ockam_node::Context;
+use ockam_transport_websocket::{WebSocketConnectionOptions, WebSocketListenerOptions};
+
+use super::{NodeManager, NodeManagerWorker};
+use crate::error::ApiError;
+use crate::nodes::models::transport::{
+    CreateWebSocketConnection, CreateWebSocketListener, DeleteTransport, TransportList,
+    TransportStatus,
+};
+
+impl NodeManager {
+    fn get_ws_connections(&self) -> TransportList {
+        let list = match self.ws_transport.get() {
+            Some(ws) => ws
+                .registry()
+                .get_all_connections()
+                .into_iter()
+                .map(TransportStatus::from)
+                .collect(),
+            None => vec![],
+        };
+        TransportList::new(list)
+    }
+
+    fn get_ws_connection(&self, address: String) -> Option<TransportStatus> {
+        let connection = self.ws_transport.get()?.find_connection(&address)?;
+        Some(connection.into())
+    }
+
+    fn get_ws_listeners(&self) -> TransportList {
+        let list = match self.ws_transport.get() {
+            Some(ws) => ws
+                .registry()
+                .get_all_listeners()
+                .into_iter()
+                .map(TransportStatus::from)
+                .collect(),
+            None => vec![],
+        };
+        TransportList::new(list)
+    }
+
+    fn get_ws_listener(&self, address: String) -> Option<TransportStatus> {
+        let listener = self.ws_transport.get()?.find_listener(&address)?;
+        Some(listener.into())
+    }
+
+    async fn create_ws_connection(
+        &self,
+        create: CreateWebSocketConnection,
+        ctx: &Context,
+    ) -> Result<TransportStatus> {
+        let mut options = WebSocketConnectionOptions::new();
+        if let Some(ca_certificate) = create.ca_certificate {
+            options = options.with_ca_certificate(ca_certificate);
+        } else if create.tls {
+            options = options.with_tls();
+        }
+
+        // Add all Hop workers as consumers for Demo purposes
+        // Production nodes should not run any Hop workers
+        for hop in self.registry.hop_services.keys().await {
+            ctx.flow_controls()
+                .add_consumer(hop.clone(), &options.flow_control_id());
+        }
+
+        let connection = self
+            .ws_transport()
+            .await?
+            .connect(create.addr, options)
+            .await?;
+        Ok(connection.into())
+    }
+
+    async fn create_ws_listener(&self, create: CreateWebSocketListener) -> Result<TransportStatus> {
+        let mut options = WebSocketListenerOptions::new();
+        match (create.certificate, create.private_key) {
+            (Some(certificate), Some(private_key)) => {
+                options = options.with_tls(certificate, private_key);
+            }
+            (None, None) => {}
+            _ => {
+                return Err(ApiError::core(
+                    "both a certificate and a private key are required to accept wss connections",
+                ))
+            }
+        }
+
+        let listener = self
+            .ws_transport()
+            .await?
+            .listen(create.addr, options)
+            .await?;
+
+        // Secure channels initiated through this listener must be accepted by the node's
+        // secure channel listeners, as they are for the node's main TCP listener
+        let flow_controls = self.tcp_transport.ctx().flow_controls();
+        for address in self.registry.secure_channel_listeners.keys().await {
+            flow_controls.add_consumer(address, listener.flow_control_id());
+        }
+
+        Ok(listener.into())
+    }
+
+    async fn delete_ws_connection(&self, address: String) -> Result<(), String> {
+        let ws = self
+            .ws_transport
+            .get()
+            .ok_or_else(|| format!("Connection {address} was not found in the registry."))?;
+        let sender_address = ws
+            .find_connection(&address)
+            .map(|connection| connection.sender_address().clone())
+            .ok_or_else(|| format!("Connection {address} was not found in the registry."))?;
+
+        ws.disconnect(sender_address.clone())
+            .await
+            .map_err(|err| format!("Unable to disconnect from {sender_address}: {err}"))
+    }
+
+    async fn delete_ws_listener(&self, address: String) -> Result<(), String> {
+        let ws = self
+            .ws_transport
+            .get()
+            .ok_or_else(|| format!("Listener {address} was not found in the registry."))?;
+        let listener_address: Address = ws
+            .find_listener(&address)
+            .map(|listener| listener.processor_address().clone())
+            .ok_or_else(|| format!("Listener {address} was not found in the registry."))?;
+
+        ws.stop_listener(&listener_address)
+            .await
+            .map_err(|err| format!("Unable to stop listener {listener_address}: {err}"))
+    }
+}
+
+impl NodeManagerWorker {
+    pub(super) async fn get_ws_connections(&self, req: &RequestHeader) -> Response<TransportList> {
+        Response::ok()
+            .with_headers(req)
+            .body(self.node_manager.get_ws_connections())
+    }
+
+    pub(super) async fn get_ws_connection(
+        &self,
+        address: String,
+    ) -> Result<Response<TransportStatus>, Response<Error>> {
+        self.node_manager
+            .get_ws_connection(address.to_string())
+            .map(|status| Response::ok().body(status))
+            .ok_or_else(|| {
+                let msg = format!("Connection {address} was not found in the registry.");
+                Response::not_found_no_request(&msg)
+            })
+    }
+
+    pub(super) async fn get_ws_listeners(&self, req: &RequestHeader) -> Response<TransportList> {
+        Response::ok()
+            .with_headers(req)
+            .body(self.node_manager.get_ws_listeners())
+    }
+
+    pub(super) async fn get_ws_listener(
+        &self,
+        address: String,
+    ) -> Result<Response<TransportStatus>, Response<Error>> {
+        self.node_manager
+            .get_ws_listener(address.to_string())
+            .map(|status| Response::ok().body(status))
+            .ok_or_else(|| {
+                let msg = format!("Listener {address} was not found in the registry.");
+                Response::not_found_no_request(&msg)
+            })
+    }
+
+    pub(super) async fn create_ws_connection(
+        &self,
+        ctx: &Context,
+        create: CreateWebSocketConnection,
+    ) -> Result<Response<TransportStatus>, Response<Error>> {
+        let addr = create.addr.clone();
+        info!("Handling request to create a new WebSocket connection: {addr}");
+
+        self.node_manager
+            .create_ws_connection(create, ctx)
+            .await
+            .map(|status| Response::ok().body(status))
+            .map_err(|msg| {
+                Response::bad_request_no_request(&format!("Unable to connect to {addr}: {msg}"))
+            })
+    }
+
+    pub(super) async fn create_ws_listener(
+        &self,
+        create: CreateWebSocketListener,
+    ) -> Result<Response<TransportStatus>, Response<Error>> {
+        let addr = create.addr.clone();
+        info!("Handling request to create a new WebSocket listener: {addr}");
+
+        self.node_manager
+            .create_ws_listener(create)
+            .await
+            .map(|status| Response::ok().body(status))
+            .map_err(|msg| {
+                Response::bad_request_no_request(&format!("Unable to listen on {addr}: {msg}"))
+            })
+    }
+
+    pub(super) async fn delete_ws_connection(
+        &self,
+        delete: DeleteTransport,
+    ) -> Result<Response<()>, Response<Error>> {
+        info!(
+            "Handling request to stop WebSocket connection: {}",
+            delete.address
+        );
+
+        self.node_manager
+            .delete_ws_connection(delete.address)
+            .await
+            .map(|status| Response::ok().body(status))
+            .map_err(|msg| Response::bad_request_no_request(&msg))
+    }
+
+    pub(super) async fn delete_ws_listener(
+        &self,
+        delete: DeleteTransport,
+    ) -> Result<Response<()>, Response<Error>> {
+        info!(
+            "Handling request to stop WebSocket listener: {}",
+            delete.address
+        );
+
+        self.node_manager
+            .delete_ws_listener(delete.address)
+            .await
+            .map(|status| Response::ok().body(status))
+            .map_err(|msg| Response::bad_request_no_request(&msg))
+    }
+}
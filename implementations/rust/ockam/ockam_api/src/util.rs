@@ -7,7 +7,7 @@ use ockam_core::errcode::{Kind, Origin};
 use ockam_core::flow_control::FlowControlId;
 use ockam_core::{Address, Error, Result, Route, TransportType, LOCAL};
 use ockam_multiaddr::proto::{
-    DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp, Worker,
+    DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp, Worker, Ws, Wss,
 };
 use ockam_multiaddr::{Code, MultiAddr, Protocol};
 use ockam_transport_tcp::{TcpConnection, TcpConnectionOptions, TCP};
@@ -365,6 +365,8 @@ pub fn local_worker(code: &Code) -> Result<bool> {
         | Ip4::CODE
         | Ip6::CODE
         | Tcp::CODE
+        | Ws::CODE
+        | Wss::CODE
         | Secure::CODE => Ok(false),
         Worker::CODE | Service::CODE => Ok(true),
 
@@ -430,9 +432,7 @@ pub mod test_utils {
             .build();
 
         let vault = cli_state
-            .get_or_create_default_named_vault()
-            .await?
-            .vault()
+            .make_vault(&cli_state.get_or_create_default_named_vault().await?)
             .await?;
         let identities = cli_state.make_identities(vault).await?;
 
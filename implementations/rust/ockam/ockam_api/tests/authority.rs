@@ -3,7 +3,7 @@ use ockam::identity::{secure_channels, AttributesEntry, Identifier, SecureChanne
 use ockam::AsyncTryClone;
 use ockam_api::authenticator::enrollment_tokens::Members;
 use ockam_api::authority_node;
-use ockam_api::authority_node::{Authority, Configuration};
+use ockam_api::authority_node::{Authority, Configuration, Revocations};
 use ockam_api::bootstrapped_identities_store::PreTrustedIdentities;
 use ockam_api::cloud::AuthorityNode;
 use ockam_api::config::lookup::InternetAddress;
@@ -196,6 +196,51 @@ async fn test_one_admin_one_member(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+#[ockam_macros::test]
+async fn admin_revokes_a_member(ctx: &mut Context) -> Result<()> {
+    use std::collections::HashMap;
+
+    let secure_channels = secure_channels().await?;
+
+    let admins = setup(ctx, secure_channels.clone(), 1).await?;
+    let admin = &admins[0];
+
+    let member = secure_channels
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+
+    admin
+        .client
+        .add_member(ctx, member.clone(), HashMap::default())
+        .await
+        .unwrap();
+
+    let revocation_list = admin.client.get_revocation_list(ctx).await.unwrap();
+    let data = revocation_list.revocation_list.get_revocation_list_data()?;
+    assert!(data.revoked_subjects.is_empty());
+    assert!(data.revoked_credentials.is_empty());
+
+    admin
+        .client
+        .revoke_subject(ctx, member.clone())
+        .await
+        .unwrap();
+
+    let revocation_list = admin.client.get_revocation_list(ctx).await.unwrap();
+    let data = revocation_list.revocation_list.get_revocation_list_data()?;
+    assert_eq!(data.revoked_subjects, vec![member.clone()]);
+
+    // The revoked member doesn't have any attributes anymore
+    let members = admin.client.list_member_ids(ctx).await.unwrap();
+    assert!(!members.contains(&member));
+
+    ctx.stop().await?;
+
+    Ok(())
+}
+
 #[ockam_macros::test]
 async fn two_admins_two_members_exist_in_one_global_scope(ctx: &mut Context) -> Result<()> {
     use std::collections::HashMap;
@@ -333,6 +378,7 @@ async fn default_configuration() -> Result<Configuration> {
         identifier: "I4dba4b2e53b2ed95967b3bab350b6c9ad9c624e5a1b2c3d4e5f6a6b5c4d3e2f1"
             .try_into()?,
         database_path: storage_path,
+        database_url: None,
         project_identifier: "123456".to_string(),
         tcp_listener_address: InternetAddress::new(&format!("127.0.0.1:{}", port)).unwrap(),
         secure_channel_listener_name: None,
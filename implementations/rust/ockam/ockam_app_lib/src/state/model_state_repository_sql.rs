@@ -6,7 +6,7 @@ use std::sync::Arc;
 use sqlx::*;
 use tracing::debug;
 
-use ockam::{FromSqlxError, SqlxDatabase, ToSqlxType, ToVoid};
+use ockam::{Boolean, FromSqlxError, Nullable, SqlxDatabase, ToSqlxType, ToVoid};
 use ockam_api::nodes::models::portal::OutletStatus;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::Error;
@@ -59,7 +59,7 @@ impl ModelStateRepository for ModelStateSqlxDatabase {
 
         // re-insert the new state
         for tcp_outlet_status in &model_state.tcp_outlets {
-            let query = query("INSERT OR REPLACE INTO tcp_outlet_status VALUES (?, ?, ?, ?)")
+            let query = query("INSERT INTO tcp_outlet_status VALUES ($1, $2, $3, $4) ON CONFLICT (alias) DO UPDATE SET socket_addr = $2, worker_addr = $3, payload = $4")
                 .bind(tcp_outlet_status.alias.to_sql())
                 .bind(tcp_outlet_status.socket_addr.to_sql())
                 .bind(tcp_outlet_status.worker_addr.to_sql())
@@ -75,7 +75,7 @@ impl ModelStateRepository for ModelStateSqlxDatabase {
 
         // re-insert the new state
         for incoming_service in &model_state.incoming_services {
-            let query = query("INSERT OR REPLACE INTO incoming_service VALUES (?, ?, ?)")
+            let query = query("INSERT INTO incoming_service VALUES ($1, $2, $3) ON CONFLICT (invitation_id) DO UPDATE SET enabled = $2, name = $3")
                 .bind(incoming_service.invitation_id.to_sql())
                 .bind(incoming_service.enabled.to_sql())
                 .bind(incoming_service.name.as_ref().map(|n| n.to_sql()));
@@ -115,7 +115,7 @@ struct TcpOutletStatusRow {
     alias: String,
     socket_addr: String,
     worker_addr: String,
-    payload: Option<String>,
+    payload: Nullable<String>,
 }
 
 impl TcpOutletStatusRow {
@@ -127,7 +127,7 @@ impl TcpOutletStatusRow {
             alias: self.alias.clone(),
             socket_addr,
             worker_addr,
-            payload: self.payload.clone(),
+            payload: self.payload.to_option(),
         })
     }
 }
@@ -136,16 +136,16 @@ impl TcpOutletStatusRow {
 #[derive(sqlx::FromRow)]
 struct PersistentIncomingServiceRow {
     invitation_id: String,
-    enabled: bool,
-    name: Option<String>,
+    enabled: Boolean,
+    name: Nullable<String>,
 }
 
 impl PersistentIncomingServiceRow {
     fn persistent_incoming_service(&self) -> Result<PersistentIncomingService> {
         Ok(PersistentIncomingService {
             invitation_id: self.invitation_id.clone(),
-            enabled: self.enabled,
-            name: self.name.clone(),
+            enabled: self.enabled.to_bool(),
+            name: self.name.to_option(),
         })
     }
 }
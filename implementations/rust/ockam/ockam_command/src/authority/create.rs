@@ -291,6 +291,7 @@ async fn start_authority_node(
     let configuration = authority_node::Configuration {
         identifier: node.identifier(),
         database_path: opts.state.database_path(),
+        database_url: opts.state.database_url(),
         project_identifier: cmd.project_identifier,
         tcp_listener_address: cmd.tcp_listener_address,
         secure_channel_listener_name: None,
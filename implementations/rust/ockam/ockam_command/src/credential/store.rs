@@ -110,12 +110,8 @@ async fn verify_issuer(
     issuer: &str,
     vault: &Option<String>,
 ) -> miette::Result<Identity> {
-    let vault = opts
-        .state
-        .get_named_vault_or_default(vault)
-        .await?
-        .vault()
-        .await?;
+    let named_vault = opts.state.get_named_vault_or_default(vault).await?;
+    let vault = opts.state.make_vault(&named_vault).await?;
     let identities = opts.state.make_identities(vault).await?;
 
     let identifier = identities
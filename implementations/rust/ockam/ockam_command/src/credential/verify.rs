@@ -96,12 +96,8 @@ pub async fn verify_credential(
             }
         };
 
-        let vault = opts
-            .state
-            .get_named_vault_or_default(vault)
-            .await?
-            .vault()
-            .await?;
+        let named_vault = opts.state.get_named_vault_or_default(vault).await?;
+        let vault = opts.state.make_vault(&named_vault).await?;
         let identities = match opts.state.make_identities(vault).await {
             Ok(i) => i,
             Err(e) => {
@@ -0,0 +1,129 @@
+use std::fmt::{Display, Formatter};
+
+use clap::Args;
+use miette::IntoDiagnostic;
+use serde::Serialize;
+use serde_json::to_string_pretty;
+
+use ockam::identity::verified_change::VerifiedChange;
+use ockam::identity::{Identifier, Identity};
+use ockam_node::Context;
+
+use crate::output::{human_readable_time, VerifyingPublicKeyDisplay};
+use crate::util::node_rpc;
+use crate::{docs, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/history/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/history/after_long_help.txt");
+
+/// Show the key rotation history of an identity
+#[derive(Clone, Debug, Args)]
+#[command(
+long_about = docs::about(LONG_ABOUT),
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct HistoryCommand {
+    /// Name of the identity. The default identity is used if no name is given
+    name: Option<String>,
+}
+
+impl HistoryCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self));
+    }
+}
+
+async fn run_impl(
+    _ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, HistoryCommand),
+) -> miette::Result<()> {
+    let identity = opts.state.get_identity_by_optional_name(&cmd.name).await?;
+    let history = IdentityHistory::from(identity);
+    let plain = history.to_string();
+
+    opts.terminal
+        .stdout()
+        .plain(&plain)
+        .json(to_string_pretty(&history).into_diagnostic()?)
+        .machine(&plain)
+        .write_line()?;
+    Ok(())
+}
+
+#[derive(Serialize)]
+struct IdentityHistory {
+    identifier: Identifier,
+    changes: Vec<IdentityChange>,
+}
+
+impl From<Identity> for IdentityHistory {
+    fn from(value: Identity) -> Self {
+        Self {
+            identifier: value.identifier().to_owned(),
+            changes: value.changes().iter().map(IdentityChange::from).collect(),
+        }
+    }
+}
+
+impl Display for IdentityHistory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Identifier: {}", self.identifier)?;
+        for (i_num, change) in self.changes.iter().enumerate() {
+            writeln!(f, "  Change[{}]:", i_num)?;
+            writeln!(f, "    change_hash:             {}", change.change_hash)?;
+            if let Some(previous_change) = &change.previous_change {
+                writeln!(f, "    previous_change:         {}", previous_change)?;
+            }
+            writeln!(
+                f,
+                "    primary_public_key:      {}",
+                change.primary_public_key
+            )?;
+            writeln!(f, "    created_at:              {}", change.created_at)?;
+            writeln!(f, "    expires_at:              {}", change.expires_at)?;
+            writeln!(
+                f,
+                "    revoke_all_purpose_keys: {}",
+                change.revoke_all_purpose_keys
+            )?;
+            if let Some(recovery_keys) = &change.recovery_keys {
+                writeln!(f, "    recovery_keys:           {}", recovery_keys)?;
+            }
+            if change.recovered {
+                writeln!(f, "    recovered:               true")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize)]
+struct IdentityChange {
+    change_hash: String,
+    previous_change: Option<String>,
+    primary_public_key: VerifyingPublicKeyDisplay,
+    created_at: String,
+    expires_at: String,
+    revoke_all_purpose_keys: bool,
+    recovery_keys: Option<String>,
+    recovered: bool,
+}
+
+impl From<&VerifiedChange> for IdentityChange {
+    fn from(value: &VerifiedChange) -> Self {
+        let data = value.data();
+        Self {
+            change_hash: hex::encode(value.change_hash()),
+            previous_change: data.previous_change.as_ref().map(hex::encode),
+            primary_public_key: VerifyingPublicKeyDisplay(value.primary_public_key().to_owned()),
+            created_at: human_readable_time(data.created_at),
+            expires_at: human_readable_time(data.expires_at),
+            revoke_all_purpose_keys: data.revoke_all_purpose_keys,
+            recovery_keys: data
+                .recovery_keys
+                .as_ref()
+                .map(|keys| format!("{} of {}", keys.threshold, keys.public_keys.len())),
+            recovered: value.is_recovery(),
+        }
+    }
+}
@@ -2,7 +2,10 @@ use clap::{Args, Subcommand};
 
 pub use create::CreateCommand;
 pub(crate) use delete::DeleteCommand;
+pub(crate) use history::HistoryCommand;
 pub(crate) use list::ListCommand;
+pub(crate) use recovery::RecoveryCommand;
+pub(crate) use rotate::RotateCommand;
 pub(crate) use show::ShowCommand;
 
 use crate::identity::default::DefaultCommand;
@@ -11,7 +14,10 @@ use crate::{docs, CommandGlobalOpts};
 mod create;
 mod default;
 mod delete;
+mod history;
 mod list;
+mod recovery;
+mod rotate;
 mod show;
 
 const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
@@ -35,6 +41,9 @@ pub enum IdentitySubcommand {
     List(ListCommand),
     Default(DefaultCommand),
     Delete(DeleteCommand),
+    Rotate(RotateCommand),
+    History(HistoryCommand),
+    Recovery(RecoveryCommand),
 }
 
 impl IdentityCommand {
@@ -45,6 +54,9 @@ impl IdentityCommand {
             IdentitySubcommand::List(c) => c.run(options),
             IdentitySubcommand::Delete(c) => c.run(options),
             IdentitySubcommand::Default(c) => c.run(options),
+            IdentitySubcommand::Rotate(c) => c.run(options),
+            IdentitySubcommand::History(c) => c.run(options),
+            IdentitySubcommand::Recovery(c) => c.run(options),
         }
     }
 }
@@ -0,0 +1,176 @@
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use clap::Args;
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+use serde_json::json;
+
+use ockam_node::Context;
+use ockam_vault::{
+    EdDSACurve25519SecretKey, SigningSecret, SoftwareVaultForSigning, VaultForSigning,
+};
+
+use crate::identity::recovery::RecoveryShare;
+use crate::terminal::OckamColor;
+use crate::util::node_rpc;
+use crate::{fmt_log, fmt_ok, CommandGlobalOpts};
+
+/// Commit an identity to a set of recovery keys and export them as share files
+#[derive(Clone, Debug, Args)]
+pub struct ExportSharesCommand {
+    /// Name of the identity. The default identity is used if no name is given
+    name: Option<String>,
+
+    /// Number of recovery keys to create, each one is exported to a different share file
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u8).range(1..))]
+    shares: u8,
+
+    /// Number of shares needed to recover the identity
+    #[arg(long, value_name = "K", value_parser = clap::value_parser!(u8).range(1..))]
+    threshold: u8,
+
+    /// Directory where the share files are written
+    #[arg(long, value_name = "DIR")]
+    output_dir: PathBuf,
+
+    /// Share file of the recovery keys currently in effect. If the identity already has recovery keys,
+    /// this argument must be repeated for as many shares as their threshold to approve their replacement
+    #[arg(long = "share", value_name = "SHARE_FILE")]
+    current_shares: Vec<PathBuf>,
+}
+
+impl ExportSharesCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self));
+    }
+}
+
+async fn run_impl(
+    _ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ExportSharesCommand),
+) -> miette::Result<()> {
+    if cmd.threshold > cmd.shares {
+        return Err(miette!(
+            "The threshold ({}) can't be greater than the number of shares ({})",
+            cmd.threshold,
+            cmd.shares
+        ));
+    }
+    let identifier = opts
+        .state
+        .get_identity_by_optional_name(&cmd.name)
+        .await?
+        .identifier()
+        .clone();
+    let mut current_secrets = vec![];
+    for path in cmd.current_shares.iter() {
+        current_secrets.push(RecoveryShare::read(path, &identifier)?);
+    }
+
+    // The share files are written before the identity commits to the recovery keys
+    // so that no recovery key can be lost
+    let vault = SoftwareVaultForSigning::create().await.into_diagnostic()?;
+    let mut public_keys = vec![];
+    let mut paths = vec![];
+    std::fs::create_dir_all(&cmd.output_dir).into_diagnostic()?;
+    for key_index in 0..cmd.shares {
+        let secret: [u8; 32] = rand::random();
+        let handle = vault
+            .import_key(SigningSecret::EdDSACurve25519(
+                EdDSACurve25519SecretKey::new(secret),
+            ))
+            .await
+            .into_diagnostic()?;
+        public_keys.push(
+            vault
+                .get_verifying_public_key(&handle)
+                .await
+                .into_diagnostic()?,
+        );
+
+        let share = RecoveryShare {
+            identifier: identifier.clone(),
+            threshold: cmd.threshold,
+            key_index,
+            secret: hex::encode(secret),
+        };
+        let path = cmd
+            .output_dir
+            .join(format!("recovery-share-{key_index}.json"));
+        if let Err(e) = write_share(&path, &share) {
+            remove_shares(&paths);
+            return Err(e);
+        }
+        paths.push(path);
+    }
+
+    let identity = match opts
+        .state
+        .set_identity_recovery_keys(&cmd.name, cmd.threshold, public_keys, current_secrets)
+        .await
+    {
+        Ok(identity) => identity,
+        Err(e) => {
+            remove_shares(&paths);
+            return Err(e.into());
+        }
+    };
+    let change_hash = hex::encode(identity.latest_change_hash().into_diagnostic()?);
+
+    let mut plain = fmt_ok!(
+        "The identity {} can now be recovered with {} of these {} shares",
+        identity
+            .identifier()
+            .to_string()
+            .color(OckamColor::PrimaryResource.color()),
+        cmd.threshold,
+        cmd.shares
+    );
+    for path in paths.iter() {
+        plain.push('\n');
+        plain.push_str(&fmt_log!("{}", path.display()));
+    }
+    opts.terminal
+        .stdout()
+        .plain(plain)
+        .json(json!({
+            "identifier": identity.identifier(),
+            "change": change_hash,
+            "threshold": cmd.threshold,
+            "shares": paths,
+        }))
+        .machine(
+            paths
+                .iter()
+                .map(|p| p.display().to_string())
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+        .write_line()?;
+    Ok(())
+}
+
+/// Write a share file which can only be read by the current user.
+/// Existing files are never overwritten since they may hold the shares of another identity
+fn write_share(path: &Path, share: &RecoveryShare) -> miette::Result<()> {
+    let mut options = OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options
+        .open(path)
+        .map_err(|e| miette!("Cannot create the share file {}: {e}", path.display()))?;
+    file.write_all(&serde_json::to_vec_pretty(share).into_diagnostic()?)
+        .into_diagnostic()
+}
+
+fn remove_shares(paths: &[PathBuf]) {
+    for path in paths {
+        let _ = std::fs::remove_file(path);
+    }
+}
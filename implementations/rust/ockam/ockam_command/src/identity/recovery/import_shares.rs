@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use colorful::Colorful;
+use miette::miette;
+use serde_json::json;
+
+use ockam::identity::Identity;
+use ockam_node::Context;
+
+use crate::identity::recovery::RecoveryShare;
+use crate::terminal::OckamColor;
+use crate::util::node_rpc;
+use crate::{fmt_log, fmt_ok, CommandGlobalOpts};
+
+/// Recover an identity with a new primary key, using its recovery share files
+#[derive(Clone, Debug, Args)]
+pub struct ImportSharesCommand {
+    /// Name of the recovered identity. If the identity is already stored, its current name must be used
+    name: String,
+
+    /// Hex-encoded change history of the identity to recover,
+    /// as given by `ockam identity show --full --encoding hex`
+    #[arg(long, value_name = "CHANGE_HISTORY")]
+    identity: String,
+
+    /// Recovery share file. This argument must be repeated for as many shares as the threshold
+    #[arg(long = "share", value_name = "SHARE_FILE", required = true)]
+    shares: Vec<PathBuf>,
+
+    /// Vault name to store the new primary key of the identity
+    #[arg(long, value_name = "VAULT_NAME")]
+    vault: Option<String>,
+}
+
+impl ImportSharesCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self));
+    }
+}
+
+async fn run_impl(
+    _ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, ImportSharesCommand),
+) -> miette::Result<()> {
+    let identity = Identity::create(cmd.identity.trim())
+        .await
+        .map_err(|e| miette!("The identity change history is invalid: {e}"))?;
+
+    let mut secrets = vec![];
+    for path in cmd.shares.iter() {
+        secrets.push(RecoveryShare::read(path, identity.identifier())?);
+    }
+
+    let named_identity = opts
+        .state
+        .recover_identity(&cmd.name, &cmd.vault, identity, secrets)
+        .await
+        .map_err(|e| miette!("The identity could not be recovered: {e}"))?;
+    let identifier = named_identity.identifier();
+
+    opts.terminal
+        .stdout()
+        .plain(format!(
+            "{}\n{}",
+            fmt_ok!(
+                "The identity {} has been recovered with a new primary key",
+                identifier
+                    .to_string()
+                    .color(OckamColor::PrimaryResource.color())
+            ),
+            fmt_log!(
+                "It is stored as {}, its previous purpose keys have been revoked",
+                cmd.name.clone().color(OckamColor::PrimaryResource.color())
+            )
+        ))
+        .json(json!({
+            "identifier": identifier,
+            "name": cmd.name,
+        }))
+        .machine(identifier.to_string())
+        .write_line()?;
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use std::path::Path;
+
+use clap::{Args, Subcommand};
+use miette::{miette, IntoDiagnostic};
+use serde::{Deserialize, Serialize};
+
+pub(crate) use export_shares::ExportSharesCommand;
+pub(crate) use import_shares::ImportSharesCommand;
+use ockam::identity::Identifier;
+use ockam_vault::{EdDSACurve25519SecretKey, SigningSecret};
+
+use crate::{docs, CommandGlobalOpts};
+
+mod export_shares;
+mod import_shares;
+
+const LONG_ABOUT: &str = include_str!("../static/recovery/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("../static/recovery/after_long_help.txt");
+
+/// Manage the recovery keys of an identity
+#[derive(Clone, Debug, Args)]
+#[command(
+arg_required_else_help = true,
+subcommand_required = true,
+long_about = docs::about(LONG_ABOUT),
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct RecoveryCommand {
+    #[command(subcommand)]
+    subcommand: RecoverySubcommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum RecoverySubcommand {
+    ExportShares(ExportSharesCommand),
+    ImportShares(ImportSharesCommand),
+}
+
+impl RecoveryCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            RecoverySubcommand::ExportShares(c) => c.run(options),
+            RecoverySubcommand::ImportShares(c) => c.run(options),
+        }
+    }
+}
+
+/// Content of a recovery share file: one of the recovery keys of an identity
+#[derive(Serialize, Deserialize)]
+struct RecoveryShare {
+    identifier: Identifier,
+    threshold: u8,
+    key_index: u8,
+    /// Hex-encoded Ed25519 secret key
+    secret: String,
+}
+
+impl RecoveryShare {
+    /// Read a share file of an identity and return the index and the secret of its recovery key
+    fn read(path: &Path, identifier: &Identifier) -> miette::Result<(u8, SigningSecret)> {
+        let share: RecoveryShare = serde_json::from_str(
+            &std::fs::read_to_string(path)
+                .map_err(|e| miette!("Cannot read the share file {}: {e}", path.display()))?,
+        )
+        .map_err(|e| miette!("The share file {} is invalid: {e}", path.display()))?;
+
+        if &share.identifier != identifier {
+            return Err(miette!(
+                "The share file {} is a share of the identity {}",
+                path.display(),
+                share.identifier
+            ));
+        }
+        let secret: [u8; 32] = hex::decode(&share.secret)
+            .into_diagnostic()?
+            .try_into()
+            .map_err(|_| miette!("The share file {} is invalid", path.display()))?;
+        Ok((
+            share.key_index,
+            SigningSecret::EdDSACurve25519(EdDSACurve25519SecretKey::new(secret)),
+        ))
+    }
+}
@@ -0,0 +1,67 @@
+use clap::Args;
+use colorful::Colorful;
+use miette::IntoDiagnostic;
+use serde_json::json;
+
+use ockam_node::Context;
+
+use crate::output::VerifyingPublicKeyDisplay;
+use crate::util::node_rpc;
+use crate::{docs, fmt_log, fmt_ok, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/rotate/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/rotate/after_long_help.txt");
+
+/// Rotate the primary key of an identity
+#[derive(Clone, Debug, Args)]
+#[command(
+long_about = docs::about(LONG_ABOUT),
+after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct RotateCommand {
+    /// Name of the identity. The default identity is used if no name is given
+    name: Option<String>,
+
+    /// Create new purpose keys and revoke the previous ones,
+    /// instead of attesting the existing purpose keys with the new key
+    #[arg(long)]
+    revoke_purpose_keys: bool,
+}
+
+impl RotateCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(run_impl, (options, self));
+    }
+}
+
+async fn run_impl(
+    _ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, RotateCommand),
+) -> miette::Result<()> {
+    let identity = opts
+        .state
+        .rotate_identity_key(&cmd.name, cmd.revoke_purpose_keys)
+        .await?;
+    let latest_change = identity.get_latest_change().into_diagnostic()?;
+    let change_hash = hex::encode(latest_change.change_hash());
+    let public_key = VerifyingPublicKeyDisplay(latest_change.primary_public_key().clone());
+
+    opts.terminal
+        .stdout()
+        .plain(format!(
+            "{}\n{}",
+            fmt_ok!(
+                "The primary key of the identity {} has been rotated",
+                identity.identifier()
+            ),
+            fmt_log!("New primary public key: {}", public_key)
+        ))
+        .json(json!({
+            "identifier": identity.identifier(),
+            "change": change_hash,
+            "primary_public_key": public_key,
+        }))
+        .machine(&change_hash)
+        .write_line()?;
+    Ok(())
+}
@@ -36,6 +36,10 @@ pub struct CreateCommand {
     /// The route to the project in ockam orchestrator, expected something like /project/<name>
     #[arg(long, default_value_t = kafka_default_project_route())]
     project_route: MultiAddr,
+    /// The identifier of this consumer, needed when several consumers read the same topics.
+    /// The producers must encrypt the records for this identifier
+    #[arg(long)]
+    consumer_id: Option<String>,
 }
 
 impl CreateCommand {
@@ -48,6 +52,8 @@ impl CreateCommand {
             bootstrap_server: self.bootstrap_server,
             brokers_port_range: self.brokers_port_range,
             project_route: self.project_route,
+            consumer_ids: self.consumer_id.into_iter().collect(),
+            field_protections: vec![],
         };
         node_rpc(rpc, (opts, arg_opts));
     }
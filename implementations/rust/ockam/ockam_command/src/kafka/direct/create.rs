@@ -11,6 +11,7 @@ use crate::{
     CommandGlobalOpts,
 };
 use clap::{command, Args};
+use ockam_api::kafka::TopicFieldProtection;
 use ockam_api::port_range::PortRange;
 use ockam_multiaddr::MultiAddr;
 
@@ -33,9 +34,18 @@ pub struct CreateCommand {
     /// bootstrap port
     #[arg(long, default_value_t = kafka_default_consumer_port_range())]
     brokers_port_range: PortRange,
-    /// The route to another kafka consumer node
-    #[arg(long)]
-    consumer_route: Option<MultiAddr>,
+    /// The route to another kafka consumer node, can be repeated to encrypt
+    /// the records for several consumer nodes
+    #[arg(long = "consumer-route", value_name = "CONSUMER_ROUTE")]
+    consumer_routes: Vec<MultiAddr>,
+    /// The fields of the records of a topic to encrypt, with the format
+    /// <TOPIC>:<FIELD>[,<FIELD>...] where a field is `value`, `key` or `header:<NAME>`.
+    /// The topic `*` applies to every other topic. Can be repeated.
+    /// Only the record values are encrypted when not set.
+    /// The keys and headers are encrypted with keys derived from the OCKAM_KAFKA_PROTECTION_SECRET
+    /// environment variable, which must be set to the same secret for all the producers
+    #[arg(long = "protect", value_name = "TOPIC:FIELDS")]
+    field_protections: Vec<TopicFieldProtection>,
 }
 
 impl CreateCommand {
@@ -47,7 +57,8 @@ impl CreateCommand {
             addr: self.addr,
             bind_address: self.bind_address,
             brokers_port_range: self.brokers_port_range,
-            consumer_route: self.consumer_route,
+            consumer_routes: self.consumer_routes,
+            field_protections: self.field_protections,
             bootstrap_server: self.bootstrap_server,
         };
         node_rpc(start, (opts, arg_opts));
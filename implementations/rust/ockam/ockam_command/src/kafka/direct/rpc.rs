@@ -4,12 +4,14 @@ use colorful::Colorful;
 use tokio::{sync::Mutex, try_join};
 
 use ockam::Context;
+use ockam_api::kafka::TopicFieldProtection;
 use ockam_api::nodes::models::services::{StartKafkaDirectRequest, StartServiceRequest};
 use ockam_api::nodes::BackgroundNode;
 use ockam_api::port_range::PortRange;
 use ockam_core::api::Request;
 use ockam_multiaddr::MultiAddr;
 
+use crate::kafka::util::field_protection_secret;
 use crate::node::NodeOpts;
 use crate::service::start::start_service_impl;
 use crate::terminal::OckamColor;
@@ -23,8 +25,9 @@ pub struct ArgOpts {
     pub addr: String,
     pub bind_address: SocketAddr,
     pub brokers_port_range: PortRange,
-    pub consumer_route: Option<MultiAddr>,
+    pub consumer_routes: Vec<MultiAddr>,
     pub bootstrap_server: SocketAddr,
+    pub field_protections: Vec<TopicFieldProtection>,
 }
 
 pub async fn start(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) -> miette::Result<()> {
@@ -35,20 +38,22 @@ pub async fn start(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) ->
         addr,
         bind_address,
         brokers_port_range,
-        consumer_route,
+        consumer_routes,
         bootstrap_server,
+        field_protections,
     } = args;
 
     opts.terminal
         .write_line(&fmt_log!("Creating {} service...\n", kafka_entity))?;
 
     display_parse_logs(&opts);
+    let field_protection_secret = field_protection_secret()?;
 
-    let consumer_route = if let Some(consumer_route) = consumer_route {
-        Some(process_nodes_multiaddr(&consumer_route, &opts.state).await?)
-    } else {
-        None
-    };
+    let mut processed_consumer_routes = Vec::with_capacity(consumer_routes.len());
+    for consumer_route in consumer_routes {
+        processed_consumer_routes
+            .push(process_nodes_multiaddr(&consumer_route, &opts.state).await?);
+    }
 
     let is_finished = Mutex::new(false);
     let send_req = async {
@@ -58,8 +63,10 @@ pub async fn start(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) ->
             bind_address.to_owned(),
             bootstrap_server,
             brokers_port_range,
-            consumer_route,
-        );
+            processed_consumer_routes,
+            field_protections,
+        )
+        .with_field_protection_secret(field_protection_secret);
         let payload = StartServiceRequest::new(payload, &addr);
         let req = Request::post(endpoint).body(payload);
         start_service_impl(&ctx, &node, &kafka_entity, req).await?;
@@ -2,6 +2,7 @@ use std::net::SocketAddr;
 
 use clap::{command, Args};
 
+use ockam_api::kafka::TopicFieldProtection;
 use ockam_api::port_range::PortRange;
 use ockam_multiaddr::MultiAddr;
 
@@ -36,6 +37,19 @@ pub struct CreateCommand {
     /// The route to the project in ockam orchestrator, expected something like /project/<name>
     #[arg(long, default_value_t = kafka_default_project_route())]
     project_route: MultiAddr,
+    /// The identifier of a consumer every record is encrypted for, can be repeated to
+    /// encrypt the records for several consumers.
+    /// The records are encrypted for the consumer without identifier when not set
+    #[arg(long = "consumer-id", value_name = "CONSUMER_ID")]
+    consumer_ids: Vec<String>,
+    /// The fields of the records of a topic to encrypt, with the format
+    /// <TOPIC>:<FIELD>[,<FIELD>...] where a field is `value`, `key` or `header:<NAME>`.
+    /// The topic `*` applies to every other topic. Can be repeated.
+    /// Only the record values are encrypted when not set.
+    /// The keys and headers are encrypted with keys derived from the OCKAM_KAFKA_PROTECTION_SECRET
+    /// environment variable, which must be set to the same secret for all the producers
+    #[arg(long = "protect", value_name = "TOPIC:FIELDS")]
+    field_protections: Vec<TopicFieldProtection>,
 }
 
 impl CreateCommand {
@@ -48,6 +62,8 @@ impl CreateCommand {
             bootstrap_server: self.bootstrap_server,
             brokers_port_range: self.brokers_port_range,
             project_route: self.project_route,
+            consumer_ids: self.consumer_ids,
+            field_protections: self.field_protections,
         };
         node_rpc(rpc, (opts, arg_opts));
     }
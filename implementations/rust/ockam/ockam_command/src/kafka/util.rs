@@ -3,11 +3,16 @@ use std::net::SocketAddr;
 use colorful::Colorful;
 use tokio::{sync::Mutex, try_join};
 
+use miette::IntoDiagnostic;
 use ockam::Context;
+use ockam_api::kafka::{
+    FieldProtectionSecret, TopicFieldProtection, OCKAM_KAFKA_PROTECTION_SECRET,
+};
 use ockam_api::nodes::models::services::{StartKafkaProducerRequest, StartServiceRequest};
 use ockam_api::nodes::BackgroundNode;
 use ockam_api::port_range::PortRange;
 use ockam_core::api::Request;
+use ockam_core::env::get_env;
 use ockam_multiaddr::MultiAddr;
 
 use crate::node::NodeOpts;
@@ -24,6 +29,8 @@ pub struct ArgOpts {
     pub bootstrap_server: SocketAddr,
     pub brokers_port_range: PortRange,
     pub project_route: MultiAddr,
+    pub consumer_ids: Vec<String>,
+    pub field_protections: Vec<TopicFieldProtection>,
 }
 
 pub async fn rpc(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) -> miette::Result<()> {
@@ -35,6 +42,8 @@ pub async fn rpc(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) -> mi
         bootstrap_server,
         brokers_port_range,
         project_route,
+        consumer_ids,
+        field_protections,
     } = args;
 
     opts.terminal
@@ -43,6 +52,7 @@ pub async fn rpc(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) -> mi
     display_parse_logs(&opts);
 
     let project_route = process_nodes_multiaddr(&project_route, &opts.state).await?;
+    let field_protection_secret = field_protection_secret()?;
 
     let is_finished = Mutex::new(false);
     let send_req = async {
@@ -52,7 +62,10 @@ pub async fn rpc(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) -> mi
             bootstrap_server.to_owned(),
             brokers_port_range,
             project_route,
-        );
+            consumer_ids,
+            field_protections,
+        )
+        .with_field_protection_secret(field_protection_secret);
         let payload = StartServiceRequest::new(payload, &addr);
         let req = Request::post(endpoint).body(payload);
         start_service_impl(&ctx, &node, &kafka_entity, req).await?;
@@ -105,3 +118,13 @@ pub async fn rpc(ctx: Context, (opts, args): (CommandGlobalOpts, ArgOpts)) -> mi
 
     Ok(())
 }
+
+/// Return the secret used to derive the keys encrypting the record keys and headers,
+/// if it is set in the environment
+pub(crate) fn field_protection_secret() -> miette::Result<Option<FieldProtectionSecret>> {
+    get_env::<String>(OCKAM_KAFKA_PROTECTION_SECRET)
+        .into_diagnostic()?
+        .map(FieldProtectionSecret::new)
+        .transpose()
+        .into_diagnostic()
+}
@@ -63,6 +63,7 @@ use util::{exitcode, exitcode::ExitCode};
 use vault::VaultCommand;
 use version::Version;
 use worker::WorkerCommand;
+use ws::{connection::WsConnectionCommand, listener::WsListenerCommand};
 
 use crate::admin::AdminCommand;
 use crate::authority::AuthorityCommand;
@@ -76,6 +77,7 @@ use crate::run::RunCommand;
 use crate::sidecar::SidecarCommand;
 use crate::subscription::SubscriptionCommand;
 pub use crate::terminal::{OckamColor, Terminal, TerminalStream};
+use ockam_api::logs::otlp::OtlpLayer;
 
 mod admin;
 mod authenticated;
@@ -121,6 +123,7 @@ pub mod util;
 mod vault;
 mod version;
 mod worker;
+mod ws;
 
 const ABOUT: &str = include_str!("./static/about.txt");
 const LONG_ABOUT: &str = include_str!("./static/long_about.txt");
@@ -314,6 +317,9 @@ pub enum OckamSubcommand {
     TcpOutlet(TcpOutletCommand),
     TcpInlet(TcpInletCommand),
 
+    WsListener(WsListenerCommand),
+    WsConnection(WsConnectionCommand),
+
     KafkaOutlet(KafkaOutletCommand),
     KafkaConsumer(KafkaConsumerCommand),
     KafkaDirect(KafkaDirectCommand),
@@ -387,6 +393,7 @@ impl OckamCommand {
                 options.global_args.no_color,
                 options.terminal.is_tty(),
                 log_path,
+                self.otlp_layer(),
             );
             tracing::debug!("{}", Version::short());
             tracing::debug!("Parsed {:?}", &self);
@@ -436,6 +443,9 @@ impl OckamCommand {
             OckamSubcommand::TcpOutlet(c) => c.run(options),
             OckamSubcommand::TcpInlet(c) => c.run(options),
 
+            OckamSubcommand::WsListener(c) => c.run(options),
+            OckamSubcommand::WsConnection(c) => c.run(options),
+
             OckamSubcommand::KafkaConsumer(c) => c.run(options),
             OckamSubcommand::KafkaProducer(c) => c.run(options),
             OckamSubcommand::KafkaDirect(c) => c.run(options),
@@ -482,6 +492,21 @@ impl OckamCommand {
         }
         None
     }
+
+    fn otlp_layer(&self) -> Option<OtlpLayer> {
+        // Only the process running the node exports its traces
+        if let OckamSubcommand::Node(c) = &self.subcommand {
+            if let NodeSubcommand::Create(c) = &c.subcommand {
+                if c.foreground {
+                    return c
+                        .otlp_exporter
+                        .clone()
+                        .map(|destination| OtlpLayer::new(destination, &c.node_name));
+                }
+            }
+        }
+        None
+    }
 }
 
 /// Display and clear any known messages from parsing.
@@ -1,4 +1,5 @@
 use ockam_api::logs::env::log_level;
+use ockam_api::logs::otlp::OtlpLayer;
 use ockam_api::logs::{LevelFilter, Logging, WorkerGuard};
 use std::path::PathBuf;
 use std::str::FromStr;
@@ -8,6 +9,7 @@ pub fn setup_logging(
     no_color: bool,
     is_tty: bool,
     log_path: Option<PathBuf>,
+    otlp: Option<OtlpLayer>,
 ) -> Option<WorkerGuard> {
     let level = {
         // Parse the the raw log level value (e.g. "info" or "-vvv").
@@ -24,10 +26,12 @@ pub fn setup_logging(
         };
         // If the parsed log level is not valid, default to info.
         let level = LevelFilter::from_str(&level_raw).unwrap_or(LevelFilter::INFO);
-        if level == LevelFilter::OFF {
-            return None;
+        match level {
+            // Traces are made of info spans, they still need to be recorded
+            LevelFilter::OFF if otlp.is_some() => LevelFilter::INFO,
+            LevelFilter::OFF => return None,
+            level => level,
         }
-        level
     };
     let color = !no_color && is_tty;
     let ockam_crates = [
@@ -40,5 +44,5 @@ pub fn setup_logging(
         "ockam_api",
         "ockam_command",
     ];
-    Logging::setup(level, color, log_path, &ockam_crates)
+    Logging::setup(level, color, log_path, &ockam_crates, otlp)
 }
@@ -1,4 +1,4 @@
-use std::{path::PathBuf, str::FromStr};
+use std::{net::SocketAddr, path::PathBuf, str::FromStr, time::Duration};
 
 use clap::Args;
 use miette::Context as _;
@@ -6,12 +6,14 @@ use miette::{miette, IntoDiagnostic};
 
 use ockam::identity::Identity;
 use ockam_api::cli_state::random_name;
+use ockam_api::logs::otlp::OtlpDestination;
 
 use crate::node::create::background::background_mode;
 use crate::node::create::foreground::foreground_mode;
 use crate::node::util::NodeManagerDefaults;
 use crate::service::config::Config;
 use crate::util::api::TrustContextOpts;
+use crate::util::duration::duration_parser;
 use crate::util::embedded_node_that_is_not_stopped;
 use crate::util::{local_cmd, node_rpc};
 use crate::{docs, CommandGlobalOpts, Result};
@@ -51,6 +53,28 @@ pub struct CreateCommand {
     )]
     pub tcp_listener_address: String,
 
+    /// Address of an HTTP endpoint exposing the node metrics with the Prometheus text format,
+    /// at the `/metrics` path. The metrics are not exported if this address is not set.
+    #[arg(display_order = 900, long, value_name = "SOCKET_ADDRESS")]
+    pub metrics_address: Option<SocketAddr>,
+
+    /// Rotate the primary key of the node identity once it is older than this duration.
+    /// The new change history is published to the trust context authority, if there is one.
+    /// The key is never rotated automatically if this duration is not set.
+    #[arg(display_order = 900, long, value_name = "DURATION", value_parser = duration_parser)]
+    pub identity_key_rotation_interval: Option<Duration>,
+
+    /// Export the node traces with OTLP/JSON, either to a file or to the OTLP/HTTP endpoint
+    /// of a collector, e.g. `http://127.0.0.1:4318`. A new trace is started for each
+    /// connection accepted by the node inlets, and propagated with the messages of the connection.
+    #[arg(display_order = 900, long, value_name = "FILE_OR_URL", value_parser = parse_otlp_destination)]
+    pub otlp_exporter: Option<OtlpDestination>,
+
+    /// Record the decisions taken when evaluating the policies of the node, so that they can
+    /// be displayed with `ockam policy audit`. The decisions are not recorded by default.
+    #[arg(display_order = 900, long)]
+    pub policy_audit: bool,
+
     /// `node create` started a child process to run this node in foreground.
     #[arg(long, hide = true)]
     pub child_process: bool,
@@ -72,15 +96,15 @@ pub struct CreateCommand {
 
     /// Name of the Vault that the node will use.
     #[arg(long = "vault", value_name = "VAULT_NAME")]
-    vault: Option<String>,
+    pub vault: Option<String>,
 
     /// Name of the Identity that the node will use
     #[arg(long = "identity", value_name = "IDENTITY_NAME")]
-    identity: Option<String>,
+    pub identity: Option<String>,
 
     /// Hex encoded Identity
     #[arg(long, value_name = "IDENTITY")]
-    authority_identity: Option<String>,
+    pub authority_identity: Option<String>,
 
     #[arg(long = "credential", value_name = "CREDENTIAL_NAME")]
     pub credential: Option<String>,
@@ -96,6 +120,10 @@ impl Default for CreateCommand {
             node_name: random_name(),
             exit_on_eof: false,
             tcp_listener_address: node_manager_defaults.tcp_listener_address,
+            metrics_address: None,
+            identity_key_rotation_interval: None,
+            otlp_exporter: None,
+            policy_audit: false,
             foreground: false,
             child_process: false,
             launch_config: None,
@@ -130,7 +158,7 @@ impl CreateCommand {
         }
     }
 
-    fn logging_to_file(&self) -> bool {
+    pub fn logging_to_file(&self) -> bool {
         // Background nodes will spawn a foreground node in a child process.
         // In that case, the child process will log to files.
         if self.child_process {
@@ -159,6 +187,17 @@ pub fn parse_launch_config(config_or_path: &str) -> Result<Config> {
     }
 }
 
+/// Parse an OTLP destination, making file paths absolute since the node
+/// may run in another directory
+pub fn parse_otlp_destination(destination: &str) -> Result<OtlpDestination> {
+    match OtlpDestination::from_str(destination)? {
+        OtlpDestination::File(path) if path.is_relative() => Ok(OtlpDestination::File(
+            std::env::current_dir().into_diagnostic()?.join(path),
+        )),
+        destination => Ok(destination),
+    }
+}
+
 pub async fn guard_node_is_not_already_running(
     opts: &CommandGlobalOpts,
     cmd: &CreateCommand,
@@ -6,8 +6,10 @@ use minicbor::{Decoder, Encode};
 use tokio::time::{sleep, Duration};
 use tracing::debug;
 
+use ockam::identity::IdentityKeyRotationPolicy;
 use ockam::{Address, AsyncTryClone, TcpListenerOptions};
 use ockam::{Context, TcpTransport};
+use ockam_api::metrics::MetricsExporter;
 use ockam_api::nodes::service::NodeManagerTrustOptions;
 use ockam_api::nodes::InMemoryNode;
 use ockam_api::{
@@ -90,7 +92,13 @@ pub(super) async fn foreground_mode(
             pre_trusted_identities,
             cmd.launch_config.is_none(),
             true,
-        ),
+        )
+        .with_identity_key_rotation(
+            cmd.identity_key_rotation_interval
+                .map(IdentityKeyRotationPolicy::new),
+        )
+        .with_tracing(cmd.otlp_exporter.is_some())
+        .with_policy_audit(cmd.policy_audit),
         NodeManagerTransportOptions::new(
             listener.flow_control_id().clone(),
             tcp.async_try_clone().await.into_diagnostic()?,
@@ -107,6 +115,15 @@ pub(super) async fn foreground_mode(
         .await
         .into_diagnostic()?;
 
+    let _metrics_exporter = match cmd.metrics_address {
+        Some(metrics_address) => Some(
+            MetricsExporter::start(&ctx, metrics_address)
+                .await
+                .into_diagnostic()?,
+        ),
+        None => None,
+    };
+
     if let Some(config) = &cmd.launch_config {
         if start_services(&ctx, config).await.is_err() {
             //TODO: Process should terminate on any error during its setup phase,
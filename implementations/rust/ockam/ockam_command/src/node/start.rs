@@ -6,6 +6,7 @@ use ockam_node::Context;
 
 use crate::node::show::print_query_status;
 use crate::node::util::spawn_node;
+use crate::node::CreateCommand;
 use crate::util::node_rpc;
 use crate::{docs, fmt_err, fmt_info, fmt_log, fmt_ok, fmt_warn, CommandGlobalOpts, OckamColor};
 
@@ -159,23 +160,13 @@ async fn run_node(
         .map(|a| a.to_string())
         .unwrap_or("no transport address".to_string());
 
-    // Restart node
-    spawn_node(
-        opts,
-        node_name,     // The selected node name
-        &None,         // Use the default identity
-        &None,         // Use the default vault
-        &node_address, // The selected node api address
-        None,          // No project information available
-        None,          // No trusted identities
-        None,          // "
-        None,          // Launch config
-        None,          // Authority Identity
-        None,          // Credential
-        None,          // Trust Context
-        true,          // Restarted nodes will log to files
-    )
-    .await?;
+    // Restart the node with the default options, restarted nodes log to files
+    let cmd = CreateCommand {
+        node_name: node_name.to_string(),
+        tcp_listener_address: node_address,
+        ..CreateCommand::default()
+    };
+    spawn_node(opts, &cmd).await?;
 
     let node = BackgroundNode::create_to_node(ctx, &opts.state, node_name).await?;
     Ok(node)
@@ -1,14 +1,13 @@
 use std::env::current_exe;
-use std::path::PathBuf;
 use std::process::{Command, Stdio};
 
 use miette::IntoDiagnostic;
 use miette::{miette, Context as _};
 use rand::random;
 
-use ockam_api::cli_state::NamedTrustContext;
 use ockam_core::env::get_env_with_default;
 
+use crate::node::CreateCommand;
 use crate::util::api::TrustContextOpts;
 use crate::CommandGlobalOpts;
 
@@ -52,23 +51,14 @@ pub async fn check_default(opts: &CommandGlobalOpts, name: &str) -> bool {
     false
 }
 
-/// A utility function to spawn a new node into foreground mode
-#[allow(clippy::too_many_arguments)]
-pub async fn spawn_node(
-    opts: &CommandGlobalOpts,
-    name: &str,
-    identity_name: &Option<String>,
-    vault_name: &Option<String>,
-    address: &str,
-    trusted_identities: Option<&String>,
-    trusted_identities_file: Option<&PathBuf>,
-    reload_from_trusted_identities_file: Option<&PathBuf>,
-    launch_config: Option<String>,
-    credential: Option<&String>,
-    trust_context: Option<&NamedTrustContext>,
-    project_name: Option<String>,
-    logging_to_file: bool,
-) -> miette::Result<()> {
+/// A utility function to spawn a new node into foreground mode, in a child process
+/// created with the options of a `node create` command
+pub async fn spawn_node(opts: &CommandGlobalOpts, cmd: &CreateCommand) -> miette::Result<()> {
+    let trust_context = match &cmd.trust_context_opts.trust_context {
+        Some(tc) => Some(opts.state.get_trust_context(tc).await?),
+        None => None,
+    };
+
     let mut args = vec![
         match opts.global_args.verbose {
             0 => "-vv".to_string(),
@@ -77,41 +67,60 @@ pub async fn spawn_node(
         "node".to_string(),
         "create".to_string(),
         "--tcp-listener-address".to_string(),
-        address.to_string(),
+        cmd.tcp_listener_address.to_string(),
         "--foreground".to_string(),
         "--child-process".to_string(),
     ];
 
-    if logging_to_file || !opts.terminal.is_tty() {
+    if cmd.logging_to_file() || !opts.terminal.is_tty() {
         args.push("--no-color".to_string());
     }
 
-    if let Some(identity_name) = identity_name {
+    if let Some(metrics_address) = cmd.metrics_address {
+        args.push("--metrics-address".to_string());
+        args.push(metrics_address.to_string());
+    }
+
+    if let Some(interval) = cmd.identity_key_rotation_interval {
+        args.push("--identity-key-rotation-interval".to_string());
+        args.push(format!("{}s", interval.as_secs()));
+    }
+
+    if let Some(otlp_exporter) = &cmd.otlp_exporter {
+        args.push("--otlp-exporter".to_string());
+        args.push(otlp_exporter.to_string());
+    }
+
+    if cmd.policy_audit {
+        args.push("--policy-audit".to_string());
+    }
+
+    if let Some(identity_name) = &cmd.identity {
         args.push("--identity".to_string());
         args.push(identity_name.to_string());
     }
 
-    if let Some(vault_name) = vault_name {
+    if let Some(vault_name) = &cmd.vault {
         args.push("--vault".to_string());
         args.push(vault_name.to_string());
     }
 
-    if let Some(l) = launch_config {
+    if let Some(config) = &cmd.launch_config {
         args.push("--launch-config".to_string());
-        args.push(l);
+        args.push(serde_json::to_string(config).into_diagnostic()?);
     }
 
-    if let Some(t) = trusted_identities {
+    if let Some(t) = &cmd.trusted_identities {
         args.push("--trusted-identities".to_string());
         args.push(t.to_string())
-    } else if let Some(t) = trusted_identities_file {
+    } else if let Some(t) = &cmd.trusted_identities_file {
         args.push("--trusted-identities-file".to_string());
         args.push(
             t.to_str()
                 .unwrap_or_else(|| panic!("unsupported path {t:?}"))
                 .to_string(),
         );
-    } else if let Some(t) = reload_from_trusted_identities_file {
+    } else if let Some(t) = &cmd.reload_from_trusted_identities_file {
         args.push("--reload-from-trusted-identities-file".to_string());
         args.push(
             t.to_str()
@@ -120,7 +129,7 @@ pub async fn spawn_node(
         );
     }
 
-    if let Some(credential) = credential {
+    if let Some(credential) = &cmd.credential {
         args.push("--credential".to_string());
         args.push(credential.to_string());
     }
@@ -130,12 +139,12 @@ pub async fn spawn_node(
         args.push(trust_context.name());
     }
 
-    if let Some(project_name) = project_name {
+    if let Some(project_name) = &cmd.trust_context_opts.project_name {
         args.push("--project".to_string());
         args.push(project_name.to_string());
     }
 
-    args.push(name.to_owned());
+    args.push(cmd.node_name.to_owned());
 
     run_ockam(args).await
 }
@@ -195,7 +195,7 @@ impl Output for ShowSecureChannelResponse {
     fn output(&self) -> Result<String> {
         let s = match &self.channel {
             Some(addr) => {
-                format!(
+                let mut s = format!(
                     "\n  Secure Channel:\n{} {}\n{} {}\n{} {}",
                     "  •         At: ".light_magenta(),
                     route_to_multiaddr(&route![addr.to_string()])
@@ -212,7 +212,36 @@ impl Output for ShowSecureChannelResponse {
                         .map(|id| id.clone().light_yellow().to_string())
                         .collect::<Vec<String>>()
                         .join("\n\t")
-                )
+                );
+                if let Some(statistics) = &self.statistics {
+                    let last_rekey_at = statistics
+                        .last_rekey_at
+                        .map(human_readable_time)
+                        .unwrap_or("never".to_string());
+                    s.push_str(&format!(
+                        "\n{} {}\n{} {}\n{} {}\n{} {}",
+                        "  •   Messages: ".light_magenta(),
+                        format!(
+                            "{} encrypted, {} decrypted",
+                            statistics.encrypted_messages, statistics.decrypted_messages
+                        )
+                        .light_yellow(),
+                        "  •   Renewals: ".light_magenta(),
+                        format!(
+                            "{} encryption keys, {} decryption keys",
+                            statistics.encryption_key_renewals, statistics.decryption_key_renewals
+                        )
+                        .light_yellow(),
+                        "  •     Rekeys: ".light_magenta(),
+                        format!("{} (last: {})", statistics.rekeys, last_rekey_at).light_yellow(),
+                        "  •  Exhausted: ".light_magenta(),
+                        statistics
+                            .decryption_keys_exhausted
+                            .to_string()
+                            .light_yellow(),
+                    ));
+                }
+                s
             }
             None => format!("{}", "Channel not found".red()),
         };
@@ -315,19 +344,12 @@ impl Output for NamedVault {
     fn output(&self) -> Result<String> {
         let mut output = String::new();
         writeln!(output, "Name: {}", self.name())?;
-        writeln!(
-            output,
-            "Type: {}",
-            match self.is_kms() {
-                true => "AWS KMS",
-                false => "OCKAM",
-            }
-        )?;
+        writeln!(output, "Type: {}", self.vault_type())?;
         Ok(output)
     }
 }
 
-fn human_readable_time(time: TimestampInSeconds) -> String {
+pub(crate) fn human_readable_time(time: TimestampInSeconds) -> String {
     use time::format_description::well_known::iso8601::*;
     use time::Error::Format;
     use time::OffsetDateTime;
@@ -0,0 +1,129 @@
+use std::fmt::Write;
+use std::time::Duration;
+
+use clap::Args;
+use colorful::Colorful;
+
+use ockam::identity::utils::now;
+use ockam::identity::Identifier;
+use ockam::Context;
+use ockam_abac::{PolicyDecision, PolicyDecisionFilter, Resource};
+use ockam_api::nodes::models::policy::PolicyDecisionList;
+use ockam_api::nodes::BackgroundNode;
+use ockam_core::api::Request;
+
+use crate::output::Output;
+use crate::terminal::OckamColor;
+use crate::util::duration::duration_parser;
+use crate::util::node_rpc;
+use crate::{CommandGlobalOpts, Result};
+
+/// Show the decisions taken when evaluating policies on a node.
+/// The decisions are only recorded by nodes created with `--policy-audit`
+#[derive(Clone, Debug, Args)]
+pub struct AuditCommand {
+    #[arg(long, display_order = 900, id = "NODE_NAME")]
+    at: Option<String>,
+
+    /// Only show the decisions taken for this resource
+    #[arg(short, long)]
+    resource: Option<Resource>,
+
+    /// Only show the decisions taken for this identity
+    #[arg(short, long)]
+    identity: Option<Identifier>,
+
+    /// Only show the decisions taken during this period, until now (e.g. 1h)
+    #[arg(long, value_name = "DURATION", value_parser = duration_parser)]
+    since: Option<Duration>,
+
+    /// Only show the decisions taken before this period, until now (e.g. 10m)
+    #[arg(long, value_name = "DURATION", value_parser = duration_parser)]
+    until: Option<Duration>,
+
+    /// Maximum number of decisions to show
+    #[arg(long, default_value_t = 100)]
+    limit: u32,
+}
+
+impl AuditCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        node_rpc(rpc, (options, self));
+    }
+
+    fn filter(&self) -> Result<PolicyDecisionFilter> {
+        let now = now()?;
+        let mut filter = PolicyDecisionFilter::default().with_limit(self.limit);
+        if let Some(resource) = &self.resource {
+            filter = filter.with_resource(resource.clone());
+        }
+        if let Some(identity) = &self.identity {
+            filter = filter.with_subject(identity.clone());
+        }
+        if let Some(since) = self.since {
+            filter = filter.with_since(now.saturating_sub(since.as_secs()).into());
+        }
+        if let Some(until) = self.until {
+            filter = filter.with_until(now.saturating_sub(until.as_secs()).into());
+        }
+        Ok(filter)
+    }
+}
+
+async fn rpc(ctx: Context, (opts, cmd): (CommandGlobalOpts, AuditCommand)) -> miette::Result<()> {
+    run_impl(&ctx, opts, cmd).await
+}
+
+async fn run_impl(ctx: &Context, opts: CommandGlobalOpts, cmd: AuditCommand) -> miette::Result<()> {
+    let node = BackgroundNode::create(ctx, &opts.state, &cmd.at).await?;
+    let req = Request::get("/policy_audit").body(cmd.filter()?);
+    let decisions: PolicyDecisionList = node.ask(ctx, req).await?;
+
+    let list = opts.terminal.build_list(
+        decisions.decisions(),
+        &format!("Policy decisions on Node {}", &node.node_name()),
+        &format!("No policy decisions on Node {}", &node.node_name()),
+    )?;
+    opts.terminal.stdout().plain(list).write_line()?;
+
+    Ok(())
+}
+
+impl Output for PolicyDecision {
+    fn output(&self) -> Result<String> {
+        let mut output = String::new();
+        let result = if self.is_authorized() {
+            "allowed".color(OckamColor::Success.color())
+        } else {
+            "denied".color(OckamColor::Failure.color())
+        };
+        writeln!(output, "Decision: {result} at {}", *self.decided_at())?;
+        writeln!(
+            output,
+            "Subject: {}",
+            self.subject()
+                .map(|s| s.to_string())
+                .unwrap_or("unknown".to_string())
+                .color(OckamColor::PrimaryResource.color())
+        )?;
+        writeln!(
+            output,
+            "Resource: {} / Action: {}",
+            self.resource()
+                .map(|r| r.to_string())
+                .unwrap_or("unknown".to_string())
+                .color(OckamColor::PrimaryResource.color()),
+            self.action()
+                .map(|a| a.to_string())
+                .unwrap_or("unknown".to_string())
+                .color(OckamColor::PrimaryResource.color())
+        )?;
+        writeln!(output, "Policy hash: {}", self.policy_hash())?;
+        write!(
+            output,
+            "Environment: {}",
+            self.environment_keys().join(", ")
+        )?;
+        Ok(output)
+    }
+}
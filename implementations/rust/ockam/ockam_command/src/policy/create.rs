@@ -1,13 +1,15 @@
 use clap::Args;
+use colorful::Colorful;
+use miette::IntoDiagnostic;
 
 use ockam::Context;
-use ockam_abac::{Action, Expr, Policy, Resource};
+use ockam_abac::{check_policy, Action, Expr, Policy, Resource};
 use ockam_api::nodes::BackgroundNode;
 use ockam_core::api::Request;
 
 use crate::policy::policy_path;
 use crate::util::node_rpc;
-use crate::CommandGlobalOpts;
+use crate::{fmt_warn, CommandGlobalOpts};
 
 #[derive(Clone, Debug, Args)]
 pub struct CreateCommand {
@@ -39,6 +41,10 @@ async fn run_impl(
     opts: CommandGlobalOpts,
     cmd: CreateCommand,
 ) -> miette::Result<()> {
+    // Reject invalid policies before sending them to the node
+    for warning in check_policy(&cmd.expression).into_diagnostic()? {
+        opts.terminal.write_line(fmt_warn!("{warning}"))?;
+    }
     let node = BackgroundNode::create(ctx, &opts.state, &cmd.at).await?;
     let bdy = Policy::new(cmd.expression);
     let req = Request::post(policy_path(&cmd.resource, &cmd.action)).body(bdy);
@@ -12,6 +12,7 @@ use ockam_api::nodes::BackgroundNode;
 use ockam_core::api::Request;
 
 use crate::output::Output;
+use crate::policy::policies_path;
 use crate::terminal::OckamColor;
 use crate::util::node_rpc;
 use crate::{CommandGlobalOpts, Result};
@@ -41,7 +42,7 @@ async fn run_impl(ctx: &Context, opts: CommandGlobalOpts, cmd: ListCommand) -> m
 
     let resource = cmd.resource;
     let get_policies = async {
-        let req = Request::get(format!("/policy/{resource}"));
+        let req = Request::get(policies_path(&resource));
         let policies: PolicyList = node.ask(ctx, req).await?;
         Ok(policies)
     };
@@ -4,15 +4,18 @@ use ockam::Context;
 use ockam_abac::expr::{eq, ident, str};
 use ockam_abac::{Action, Policy, Resource};
 use ockam_api::nodes::models::policy::PolicyList;
+pub(crate) use ockam_api::nodes::service::{policies_path, policy_path};
 use ockam_api::nodes::BackgroundNode;
 use ockam_core::api::Request;
 
+use crate::policy::audit::AuditCommand;
 use crate::policy::create::CreateCommand;
 use crate::policy::delete::DeleteCommand;
 use crate::policy::list::ListCommand;
 use crate::policy::show::ShowCommand;
 use crate::{CommandGlobalOpts, Result};
 
+mod audit;
 mod create;
 mod delete;
 mod list;
@@ -31,6 +34,7 @@ pub enum PolicySubcommand {
     Show(ShowCommand),
     Delete(DeleteCommand),
     List(ListCommand),
+    Audit(AuditCommand),
 }
 
 impl PolicyCommand {
@@ -40,14 +44,11 @@ impl PolicyCommand {
             PolicySubcommand::Show(c) => c.run(opts),
             PolicySubcommand::Delete(c) => c.run(opts),
             PolicySubcommand::List(c) => c.run(opts),
+            PolicySubcommand::Audit(c) => c.run(opts),
         }
     }
 }
 
-pub(crate) fn policy_path(r: &Resource, a: &Action) -> String {
-    format!("/policy/{r}/{a}")
-}
-
 pub(crate) async fn has_policy(
     node_name: &str,
     ctx: &Context,
@@ -55,7 +56,7 @@ pub(crate) async fn has_policy(
     resource: &Resource,
 ) -> Result<bool> {
     let node = BackgroundNode::create_to_node(ctx, &opts.state, node_name).await?;
-    let req = Request::get(format!("/policy/{resource}"));
+    let req = Request::get(policies_path(resource));
     let policies: PolicyList = node.ask(ctx, req).await?;
     Ok(!policies.expressions().is_empty())
 }
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use clap::Args;
 use colorful::Colorful;
 use indoc::formatdoc;
@@ -36,6 +38,26 @@ pub struct CreateCommand {
     /// The address to connect to
     #[arg(id = "to", short, long, value_name = "ADDRESS")]
     pub address: String,
+
+    /// Wrap the connection in TLS, verifying the server certificate
+    #[arg(long)]
+    pub tls: bool,
+
+    /// PEM encoded CA certificate to trust in addition to the platform roots, implies --tls
+    #[arg(long, value_name = "PATH")]
+    pub ca_cert: Option<PathBuf>,
+
+    /// Name used for SNI and to verify the server certificate instead of the host of the address, implies --tls
+    #[arg(long, value_name = "NAME")]
+    pub server_name: Option<String>,
+
+    /// PEM encoded client certificate chain presented to the server, implies --tls
+    #[arg(long, value_name = "PATH", requires = "key")]
+    pub cert: Option<PathBuf>,
+
+    /// PEM encoded private key of the client certificate
+    #[arg(long, value_name = "PATH", requires = "cert")]
+    pub key: Option<PathBuf>,
 }
 
 impl CreateCommand {
@@ -96,7 +118,7 @@ async fn run_impl(
     (opts, cmd): (CommandGlobalOpts, CreateCommand),
 ) -> miette::Result<()> {
     let node = BackgroundNode::create(&ctx, &opts.state, &cmd.node_opts.from).await?;
-    let request = api::create_tcp_connection(&cmd);
+    let request = api::create_tcp_connection(&cmd)?;
     let transport_status: TransportStatus = node.ask(&ctx, request).await?;
     let from = opts
         .state
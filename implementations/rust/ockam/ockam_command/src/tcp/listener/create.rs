@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use clap::Args;
 use colorful::Colorful;
 use miette::IntoDiagnostic;
@@ -9,7 +11,7 @@ use ockam_multiaddr::proto::{DnsAddr, Tcp};
 use ockam_multiaddr::MultiAddr;
 use ockam_node::Context;
 
-use crate::util::node_rpc;
+use crate::util::{absolute_path, node_rpc};
 use crate::{docs, CommandGlobalOpts};
 use crate::{fmt_log, fmt_ok};
 
@@ -25,6 +27,18 @@ pub struct CreateCommand {
 
     /// Address for this listener (eg. 127.0.0.1:7000)
     pub address: String,
+
+    /// PEM encoded certificate chain, accept only TLS connections when set
+    #[arg(long, value_name = "PATH", requires = "key")]
+    pub cert: Option<PathBuf>,
+
+    /// PEM encoded private key of the certificate
+    #[arg(long, value_name = "PATH", requires = "cert")]
+    pub key: Option<PathBuf>,
+
+    /// PEM encoded CA certificate, require clients to present a certificate signed by it
+    #[arg(long, value_name = "PATH", requires = "cert")]
+    pub client_ca_cert: Option<PathBuf>,
 }
 
 impl CreateCommand {
@@ -37,12 +51,15 @@ async fn run_impl(
     ctx: Context,
     (opts, cmd): (CommandGlobalOpts, CreateCommand),
 ) -> miette::Result<()> {
+    let mut request = CreateTcpListener::new(cmd.address);
+    if let (Some(certificate), Some(private_key)) =
+        (absolute_path(cmd.cert)?, absolute_path(cmd.key)?)
+    {
+        request = request.with_tls(certificate, private_key, absolute_path(cmd.client_ca_cert)?);
+    }
     let node = BackgroundNode::create(&ctx, &opts.state, &cmd.at).await?;
     let transport_status: TransportStatus = node
-        .ask(
-            &ctx,
-            Request::post("/node/tcp/listener").body(CreateTcpListener::new(cmd.address)),
-        )
+        .ask(&ctx, Request::post("/node/tcp/listener").body(request))
         .await?;
 
     let socket = transport_status.socket_addr().into_diagnostic()?;
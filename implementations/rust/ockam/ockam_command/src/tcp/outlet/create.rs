@@ -1,5 +1,3 @@
-use std::net::SocketAddr;
-
 use clap::Args;
 use colorful::Colorful;
 use miette::IntoDiagnostic;
@@ -9,7 +7,7 @@ use tokio::try_join;
 use ockam::Context;
 use ockam_abac::Resource;
 use ockam_api::address::extract_address_value;
-use ockam_api::nodes::models::portal::{CreateOutlet, OutletStatus};
+use ockam_api::nodes::models::portal::{CreateOutlet, HttpOutletConfig, HttpRoute, OutletStatus};
 use ockam_api::nodes::BackgroundNode;
 use ockam_core::api::Request;
 
@@ -17,7 +15,7 @@ use crate::policy::{add_default_project_policy, has_policy};
 use crate::tcp::util::alias_parser;
 use crate::terminal::OckamColor;
 use crate::util::node_rpc;
-use crate::util::parsers::socket_addr_parser;
+use crate::util::parsers::{http_route_parser, socket_addr_parser, target_parser};
 use crate::{display_parse_logs, fmt_log};
 use crate::{docs, fmt_ok, CommandGlobalOpts};
 
@@ -35,9 +33,30 @@ pub struct CreateCommand {
     #[arg(long, display_order = 901, id = "OUTLET_ADDRESS", default_value_t = default_from_addr(), value_parser = extract_address_value)]
     from: String,
 
-    /// TCP address to send raw tcp traffic.
-    #[arg(long, display_order = 902, id = "SOCKET_ADDRESS", value_parser = socket_addr_parser)]
-    to: SocketAddr,
+    /// TCP address to send raw tcp traffic. Repeat it to spread the connections over several
+    /// targets. Host names are resolved again periodically.
+    #[arg(long, display_order = 902, id = "SOCKET_ADDRESS", required = true, value_parser = target_parser)]
+    to: Vec<String>,
+
+    /// Strategy used to pick the target of each connection.
+    #[arg(long, display_order = 903, value_name = "STRATEGY", value_parser = ["round-robin", "least-connections"])]
+    load_balancing: Option<String>,
+
+    /// Read the connections as HTTP/1.1 requests. The identity of the caller is added to the
+    /// requests in the `X-Ockam-Identity` header, and the policies set on their path, with the
+    /// `http-path/<PATH>` resource and the `request` action, are checked.
+    #[arg(long, display_order = 904)]
+    http: bool,
+
+    /// Send the HTTP requests for a host and/or a path to other targets than the `--to` ones,
+    /// as `[HOST]/PATH=TARGET[,TARGET...]`. Repeat it to add several routes.
+    #[arg(long, display_order = 905, value_name = "ROUTE", requires = "http", value_parser = http_route_parser)]
+    http_route: Vec<HttpRoute>,
+
+    /// Attribute of the caller to add to the HTTP requests, in the `X-Ockam-Attribute-<NAME>`
+    /// header. Repeat it to add several attributes.
+    #[arg(long, display_order = 906, value_name = "NAME", requires = "http")]
+    http_attribute: Vec<String>,
 
     /// Assign a name to this outlet.
     #[arg(long, display_order = 900, id = "ALIAS", value_parser = alias_parser)]
@@ -58,11 +77,10 @@ pub async fn run_impl(
     ctx: Context,
     (opts, cmd): (CommandGlobalOpts, CreateCommand),
 ) -> miette::Result<()> {
+    let to = cmd.to.join(", ");
     opts.terminal.write_line(&fmt_log!(
         "Creating TCP Outlet to {}...\n",
-        &cmd.to
-            .to_string()
-            .color(OckamColor::PrimaryResource.color())
+        &to.clone().color(OckamColor::PrimaryResource.color())
     ))?;
     display_parse_logs(&opts);
 
@@ -78,7 +96,16 @@ pub async fn run_impl(
     let is_finished: Mutex<bool> = Mutex::new(false);
 
     let send_req = async {
-        let payload = CreateOutlet::new(cmd.to, cmd.from.clone().into(), cmd.alias, true);
+        // The first target is reported as the address of the outlet
+        let socket_addr = socket_addr_parser(&cmd.to[0])?;
+        let mut payload = CreateOutlet::new(socket_addr, cmd.from.clone().into(), cmd.alias, true)
+            .with_targets(cmd.to.clone(), cmd.load_balancing);
+        if cmd.http {
+            payload = payload.with_http(HttpOutletConfig {
+                routes: cmd.http_route,
+                attributes: cmd.http_attribute,
+            });
+        }
         let res = send_request(&ctx, &opts, payload, node_name.clone()).await;
         *is_finished.lock().await = true;
         res
@@ -114,9 +141,7 @@ pub async fn run_impl(
                 .to_string()
                 .color(OckamColor::PrimaryResource.color()),
             &cmd.from.color(OckamColor::PrimaryResource.color()),
-            &cmd.to
-                .to_string()
-                .color(OckamColor::PrimaryResource.color())
+            &to.color(OckamColor::PrimaryResource.color())
         ))
         .machine(machine)
         .json(json)
@@ -20,6 +20,7 @@ use ockam_core::Address;
 use ockam_multiaddr::MultiAddr;
 
 use crate::service::config::OktaIdentityProviderConfig;
+use crate::util::absolute_path;
 use crate::Result;
 
 ////////////// !== generators
@@ -37,10 +38,20 @@ pub(crate) fn list_tcp_listeners() -> Request<()> {
 /// Construct a request to create node tcp connection
 pub(crate) fn create_tcp_connection(
     cmd: &crate::tcp::connection::CreateCommand,
-) -> Request<models::transport::CreateTcpConnection> {
-    let payload = models::transport::CreateTcpConnection::new(cmd.address.clone());
+) -> miette::Result<Request<models::transport::CreateTcpConnection>> {
+    let mut payload = models::transport::CreateTcpConnection::new(cmd.address.clone());
+    let ca_certificate = absolute_path(cmd.ca_cert.clone())?;
+    if cmd.tls || ca_certificate.is_some() || cmd.server_name.is_some() {
+        payload = payload.with_tls(ca_certificate, cmd.server_name.clone());
+    }
+    if let (Some(certificate), Some(private_key)) = (
+        absolute_path(cmd.cert.clone())?,
+        absolute_path(cmd.key.clone())?,
+    ) {
+        payload = payload.with_client_certificate(certificate, private_key);
+    }
 
-    Request::post("/node/tcp/connection").body(payload)
+    Ok(Request::post("/node/tcp/connection").body(payload))
 }
 
 /// Construct a request to print a list of services for the given node
@@ -1,6 +1,6 @@
 use std::{
     net::{SocketAddr, TcpListener},
-    path::Path,
+    path::{Path, PathBuf},
 };
 
 use miette::Context as _;
@@ -149,6 +149,17 @@ pub fn print_path(p: &Path) -> String {
     p.to_str().unwrap_or("<unprintable>").to_string()
 }
 
+/// Make a path absolute before sending it to a node, since the node
+/// may not run in the same directory as the command
+pub fn absolute_path(path: Option<PathBuf>) -> miette::Result<Option<String>> {
+    path.map(|p| {
+        std::fs::canonicalize(&p)
+            .map(|p| p.to_string_lossy().to_string())
+            .map_err(|e| miette!("Cannot read {}: {e}", p.display()))
+    })
+    .transpose()
+}
+
 /// Replace the node's name with its address or leave it if it's another type of address.
 ///
 /// Example:
@@ -5,6 +5,7 @@ use miette::miette;
 
 use ockam::identity::Identifier;
 use ockam_api::config::lookup::InternetAddress;
+use ockam_api::nodes::models::portal::HttpRoute;
 use ockam_transport_tcp::resolve_peer;
 
 use crate::util::api;
@@ -26,6 +27,31 @@ pub(crate) fn socket_addr_parser(input: &str) -> Result<SocketAddr> {
         .map_err(|e| miette!("cannot parse the address {address} as a socket address: {e}"))?)
 }
 
+/// Helper function for parsing a `host:port` target from user input.
+/// The host name is kept so that it can be resolved again later. As with
+/// [`socket_addr_parser`], it is possible to just input a `port`
+pub(crate) fn target_parser(input: &str) -> Result<String> {
+    let target = if input.contains(':') {
+        input.to_string()
+    } else {
+        format!("127.0.0.1:{input}")
+    };
+    socket_addr_parser(&target)?;
+    Ok(target)
+}
+
+/// Helper function for parsing an HTTP route written as `[HOST]/PATH=TARGET[,TARGET...]`,
+/// where each target is parsed with [`target_parser`]
+pub(crate) fn http_route_parser(input: &str) -> Result<HttpRoute> {
+    let mut route = HttpRoute::from_str(input).map_err(|e| miette!("{e}"))?;
+    route.targets = route
+        .targets
+        .iter()
+        .map(|target| target_parser(target))
+        .collect::<Result<_>>()?;
+    Ok(route)
+}
+
 /// Helper fn for parsing an identifier from user input by using
 /// [`ockam_identity::Identifier::from_str()`]
 pub(crate) fn identity_identifier_parser(input: &str) -> Result<Identifier> {
@@ -1,8 +1,10 @@
+use std::path::PathBuf;
+
 use clap::Args;
 use colorful::Colorful;
 
 use ockam::Context;
-use ockam_api::cli_state::random_name;
+use ockam_api::cli_state::{random_name, Pkcs11Token};
 
 use crate::util::node_rpc;
 use crate::{docs, fmt_info, fmt_ok, CommandGlobalOpts};
@@ -20,8 +22,17 @@ pub struct CreateCommand {
     #[arg(hide_default_value = true, default_value_t = random_name())]
     name: String,
 
-    #[arg(long, default_value = "false")]
+    #[arg(long, default_value = "false", conflicts_with = "pkcs11_module")]
     aws_kms: bool,
+
+    /// Path to a PKCS#11 module. The vault keys are then stored in the token of the given slot.
+    /// The token user PIN is read from the OCKAM_PKCS11_PIN environment variable
+    #[arg(long, value_name = "PATH", requires = "slot")]
+    pkcs11_module: Option<PathBuf>,
+
+    /// Slot of the PKCS#11 token
+    #[arg(long, value_name = "SLOT", requires = "pkcs11_module")]
+    slot: Option<u64>,
 }
 
 impl CreateCommand {
@@ -44,7 +55,11 @@ async fn run_impl(
             "This is the first vault to be created in this environment. It will be set as the default vault"
         ))?;
     }
-    if cmd.aws_kms {
+    if let (Some(module), Some(slot)) = (cmd.pkcs11_module, cmd.slot) {
+        opts.state
+            .create_pkcs11_vault(&cmd.name, Pkcs11Token::new(module, slot))
+            .await?;
+    } else if cmd.aws_kms {
         opts.state.create_kms_vault(&cmd.name).await?;
     } else {
         opts.state.create_named_vault(&cmd.name).await?;
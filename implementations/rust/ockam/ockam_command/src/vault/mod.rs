@@ -2,6 +2,7 @@ mod create;
 mod default;
 mod delete;
 mod list;
+mod rotate_passphrase;
 mod show;
 mod util;
 
@@ -9,6 +10,7 @@ use crate::vault::create::CreateCommand;
 use crate::vault::default::DefaultCommand;
 use crate::vault::delete::DeleteCommand;
 use crate::vault::list::ListCommand;
+use crate::vault::rotate_passphrase::RotatePassphraseCommand;
 use crate::vault::show::ShowCommand;
 use crate::{docs, CommandGlobalOpts};
 
@@ -35,6 +37,7 @@ pub enum VaultSubcommand {
     Delete(DeleteCommand),
     List(ListCommand),
     Default(DefaultCommand),
+    RotatePassphrase(RotatePassphraseCommand),
 }
 
 impl VaultCommand {
@@ -45,6 +48,7 @@ impl VaultCommand {
             VaultSubcommand::List(cmd) => cmd.run(opts),
             VaultSubcommand::Delete(cmd) => cmd.run(opts),
             VaultSubcommand::Default(cmd) => cmd.run(opts),
+            VaultSubcommand::RotatePassphrase(cmd) => cmd.run(opts),
         }
     }
 }
@@ -0,0 +1,70 @@
+use clap::Args;
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+
+use ockam_api::cli_state::vaults::{OCKAM_VAULT_NEW_PASSPHRASE, OCKAM_VAULT_PASSPHRASE};
+use ockam_core::env::get_env;
+use ockam_node::Context;
+
+use crate::util::node_rpc;
+use crate::{docs, fmt_ok, CommandGlobalOpts};
+
+const LONG_ABOUT: &str = include_str!("./static/rotate_passphrase/long_about.txt");
+const AFTER_LONG_HELP: &str = include_str!("./static/rotate_passphrase/after_long_help.txt");
+
+/// Encrypt the keys of a vault with a new passphrase
+#[derive(Clone, Debug, Args)]
+#[command(
+    long_about = docs::about(LONG_ABOUT),
+    after_long_help = docs::after_help(AFTER_LONG_HELP)
+)]
+pub struct RotatePassphraseCommand {
+    /// Name of the vault. The default vault is used if no name is given
+    name: Option<String>,
+}
+
+impl RotatePassphraseCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self));
+    }
+}
+
+async fn run_impl(
+    _ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, RotatePassphraseCommand),
+) -> miette::Result<()> {
+    let vault = opts.state.get_named_vault_or_default(&cmd.name).await?;
+    let current_passphrase = get_env::<String>(OCKAM_VAULT_PASSPHRASE).into_diagnostic()?;
+    let new_passphrase = match get_env::<String>(OCKAM_VAULT_NEW_PASSPHRASE).into_diagnostic()? {
+        Some(passphrase) => passphrase,
+        None => {
+            if !opts.terminal.can_ask_for_user_input() {
+                return Err(miette!(
+                    "The new passphrase must be set with the {OCKAM_VAULT_NEW_PASSPHRASE} environment variable"
+                ));
+            }
+            dialoguer::Password::new()
+                .with_prompt("New passphrase")
+                .with_confirmation("Confirm the new passphrase", "The passphrases don't match")
+                .interact()
+                .into_diagnostic()?
+        }
+    };
+    if new_passphrase.is_empty() {
+        return Err(miette!("The new passphrase can not be empty"));
+    }
+
+    opts.state
+        .rotate_vault_passphrase(&vault, current_passphrase, new_passphrase)
+        .await?;
+    opts.terminal
+        .stdout()
+        .plain(fmt_ok!(
+            "The keys of the vault '{}' are now encrypted with the new passphrase",
+            vault.name()
+        ))
+        .machine(vault.name())
+        .json(serde_json::json!({ "name": vault.name() }))
+        .write_line()?;
+    Ok(())
+}
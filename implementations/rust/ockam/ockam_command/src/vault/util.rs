@@ -41,12 +41,10 @@ impl Output for VaultOutput {
             } else {
                 ""
             },
-            vault_type = match self.vault.is_kms() {
-                true => "AWS KMS",
-                false => "OCKAM",
-            }
-            .to_string()
-            .color(OckamColor::PrimaryResource.color()),
+            vault_type = self
+                .vault
+                .vault_type()
+                .color(OckamColor::PrimaryResource.color()),
         ))
     }
 
@@ -64,12 +62,10 @@ impl Output for VaultOutput {
             } else {
                 ""
             },
-            vault_type = match self.vault.is_kms() {
-                true => "AWS KMS",
-                false => "OCKAM",
-            }
-            .to_string()
-            .color(OckamColor::PrimaryResource.color()),
+            vault_type = self
+                .vault
+                .vault_type()
+                .color(OckamColor::PrimaryResource.color()),
         ))
     }
 }
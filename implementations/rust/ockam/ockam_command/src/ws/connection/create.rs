@@ -0,0 +1,85 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use indoc::formatdoc;
+use miette::{miette, IntoDiagnostic};
+use serde_json::json;
+
+use ockam_api::address::extract_address_value;
+use ockam_api::nodes::models::transport::{CreateWebSocketConnection, TransportStatus};
+use ockam_api::nodes::BackgroundNode;
+use ockam_core::api::Request;
+use ockam_node::Context;
+
+use crate::util::node_rpc;
+use crate::{docs, CommandGlobalOpts};
+
+const AFTER_LONG_HELP: &str = include_str!("./static/create/after_long_help.txt");
+
+/// Create a WebSocket connection
+#[derive(Args, Clone, Debug)]
+#[command(arg_required_else_help = true, after_long_help = docs::after_help(AFTER_LONG_HELP))]
+pub struct CreateCommand {
+    /// Node that will initiate the connection
+    #[arg(global = true, short, long, value_name = "NODE", value_parser = extract_address_value)]
+    pub from: Option<String>,
+
+    /// The address to connect to (eg. 127.0.0.1:7000)
+    #[arg(id = "to", short, long, value_name = "ADDRESS")]
+    pub address: String,
+
+    /// Connect using wss, verifying the server certificate
+    #[arg(long)]
+    pub tls: bool,
+
+    /// PEM encoded CA certificate to trust in addition to the platform roots, implies --tls
+    #[arg(long, value_name = "PATH")]
+    pub ca_cert: Option<PathBuf>,
+}
+
+impl CreateCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, CreateCommand),
+) -> miette::Result<()> {
+    // The node may not run in the same directory as the command
+    let ca_certificate = cmd
+        .ca_cert
+        .as_ref()
+        .map(|p| {
+            std::fs::canonicalize(p)
+                .map(|p| p.to_string_lossy().to_string())
+                .map_err(|e| miette!("Cannot read {}: {e}", p.display()))
+        })
+        .transpose()?;
+    let tls = cmd.tls || ca_certificate.is_some();
+
+    let node = BackgroundNode::create(&ctx, &opts.state, &cmd.from).await?;
+    let request = Request::post("/node/ws/connection").body(CreateWebSocketConnection::new(
+        cmd.address,
+        tls,
+        ca_certificate,
+    ));
+    let transport_status: TransportStatus = node.ask(&ctx, request).await?;
+    let from = opts.state.get_node_or_default(&cmd.from).await?.name();
+    let to = transport_status.socket_addr().into_diagnostic()?;
+    let scheme = if tls { "wss" } else { "ws" };
+    let plain = formatdoc! {r#"
+        WebSocket Connection:
+            From: /node/{from}
+            To: {to} (/ip4/{}/tcp/{}/{scheme})
+            Address: {}
+    "#, to.ip(), to.port(), transport_status.multiaddr().into_diagnostic()?};
+    let json = json!([{"route": transport_status.multiaddr().into_diagnostic()? }]);
+    opts.terminal
+        .stdout()
+        .plain(plain)
+        .json(json)
+        .write_line()?;
+    Ok(())
+}
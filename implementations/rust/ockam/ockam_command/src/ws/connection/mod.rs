@@ -0,0 +1,39 @@
+mod create;
+mod delete;
+mod list;
+mod show;
+
+pub(crate) use create::CreateCommand;
+pub(crate) use delete::DeleteCommand;
+pub(crate) use list::ListCommand;
+pub(crate) use show::ShowCommand;
+
+use crate::CommandGlobalOpts;
+use clap::{Args, Subcommand};
+
+/// Manage WebSocket Connections
+#[derive(Args, Clone, Debug)]
+#[command(arg_required_else_help = true)]
+pub struct WsConnectionCommand {
+    #[command(subcommand)]
+    subcommand: WsConnectionSubCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum WsConnectionSubCommand {
+    Create(CreateCommand),
+    Delete(DeleteCommand),
+    List(ListCommand),
+    Show(ShowCommand),
+}
+
+impl WsConnectionCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            WsConnectionSubCommand::Create(c) => c.run(options),
+            WsConnectionSubCommand::Delete(c) => c.run(options),
+            WsConnectionSubCommand::List(c) => c.run(options),
+            WsConnectionSubCommand::Show(c) => c.run(options),
+        }
+    }
+}
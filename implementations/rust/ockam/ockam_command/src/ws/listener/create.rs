@@ -0,0 +1,84 @@
+use std::path::PathBuf;
+
+use clap::Args;
+use colorful::Colorful;
+use miette::IntoDiagnostic;
+use ockam_api::address::extract_address_value;
+use ockam_api::nodes::models::transport::{CreateWebSocketListener, TransportStatus};
+use ockam_api::nodes::BackgroundNode;
+use ockam_core::api::Request;
+use ockam_multiaddr::proto::{DnsAddr, Tcp, Ws, Wss};
+use ockam_multiaddr::MultiAddr;
+use ockam_node::Context;
+
+use crate::util::{absolute_path, node_rpc};
+use crate::{docs, CommandGlobalOpts};
+use crate::{fmt_log, fmt_ok};
+
+const AFTER_LONG_HELP: &str = include_str!("./static/create/after_long_help.txt");
+
+/// Create a WebSocket listener
+#[derive(Args, Clone, Debug)]
+#[command(after_long_help = docs::after_help(AFTER_LONG_HELP))]
+pub struct CreateCommand {
+    /// Node at which to create the listener
+    #[arg(global = true, long, value_name = "NODE", value_parser = extract_address_value)]
+    pub at: Option<String>,
+
+    /// Address for this listener (eg. 127.0.0.1:7000)
+    pub address: String,
+
+    /// PEM encoded certificate chain, accept only wss connections when set
+    #[arg(long, value_name = "PATH", requires = "key")]
+    pub cert: Option<PathBuf>,
+
+    /// PEM encoded private key of the certificate
+    #[arg(long, value_name = "PATH", requires = "cert")]
+    pub key: Option<PathBuf>,
+}
+
+impl CreateCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self))
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, CreateCommand),
+) -> miette::Result<()> {
+    let tls = cmd.cert.is_some();
+    let request = CreateWebSocketListener::new(
+        cmd.address,
+        absolute_path(cmd.cert)?,
+        absolute_path(cmd.key)?,
+    );
+    let node = BackgroundNode::create(&ctx, &opts.state, &cmd.at).await?;
+    let transport_status: TransportStatus = node
+        .ask(&ctx, Request::post("/node/ws/listener").body(request))
+        .await?;
+
+    let socket = transport_status.socket_addr().into_diagnostic()?;
+    let port = socket.port();
+    let mut multiaddr = MultiAddr::default();
+    multiaddr
+        .push_back(DnsAddr::new("localhost"))
+        .into_diagnostic()?;
+    multiaddr.push_back(Tcp::new(port)).into_diagnostic()?;
+    if tls {
+        multiaddr.push_back(Wss).into_diagnostic()?;
+    } else {
+        multiaddr.push_back(Ws).into_diagnostic()?;
+    }
+
+    opts.terminal
+        .stdout()
+        .plain(
+            fmt_ok!("WebSocket listener created! You can send messages to it via this route:\n")
+                + &fmt_log!("{multiaddr}"),
+        )
+        .json(serde_json::json!({ "route": multiaddr.to_string() }))
+        .write_line()?;
+
+    Ok(())
+}
@@ -0,0 +1,77 @@
+use clap::Args;
+use colorful::Colorful;
+use miette::{miette, IntoDiagnostic};
+
+use ockam::Context;
+
+use ockam_api::nodes::models::transport::TransportStatus;
+use ockam_api::nodes::{models, BackgroundNode};
+use ockam_core::api::Request;
+
+use crate::util::node_rpc;
+use crate::{docs, fmt_ok, node::NodeOpts, CommandGlobalOpts};
+
+const AFTER_LONG_HELP: &str = include_str!("./static/delete/after_long_help.txt");
+
+/// Delete a WebSocket listener
+#[derive(Clone, Debug, Args)]
+#[command(after_long_help = docs::after_help(AFTER_LONG_HELP))]
+pub struct DeleteCommand {
+    #[command(flatten)]
+    node_opts: NodeOpts,
+
+    /// WebSocket Listener Worker Address or Socket Address
+    pub address: String,
+
+    /// Confirm the deletion without prompting
+    #[arg(display_order = 901, long, short)]
+    yes: bool,
+}
+
+impl DeleteCommand {
+    pub fn run(self, opts: CommandGlobalOpts) {
+        node_rpc(run_impl, (opts, self));
+    }
+}
+
+async fn run_impl(
+    ctx: Context,
+    (opts, cmd): (CommandGlobalOpts, DeleteCommand),
+) -> miette::Result<()> {
+    let node = BackgroundNode::create(&ctx, &opts.state, &cmd.node_opts.at_node).await?;
+
+    // Check if there a WebSocket listener with the provided address exists
+    let address = cmd.address;
+    node.ask_and_get_reply::<_, TransportStatus>(
+        &ctx,
+        Request::get(format!("/node/ws/listener/{address}")),
+    )
+    .await?
+    .found()
+    .into_diagnostic()?
+    .ok_or(miette!(
+        "WebSocket listener with address {address} was not found on Node {}",
+        node.node_name()
+    ))?;
+
+    // Proceed with the deletion
+    if opts.terminal.confirmed_with_flag_or_prompt(
+        cmd.yes,
+        "Are you sure you want to delete this WebSocket listener?",
+    )? {
+        let req = Request::delete("/node/ws/listener")
+            .body(models::transport::DeleteTransport::new(address.clone()));
+        node.tell(&ctx, req).await?;
+
+        opts.terminal
+            .stdout()
+            .plain(fmt_ok!(
+                "WebSocket listener with address {address} on Node {} has been deleted",
+                node.node_name()
+            ))
+            .json(serde_json::json!({"node": node.node_name() }))
+            .write_line()
+            .unwrap();
+    }
+    Ok(())
+}
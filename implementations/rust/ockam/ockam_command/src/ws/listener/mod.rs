@@ -0,0 +1,45 @@
+mod create;
+mod delete;
+mod list;
+mod show;
+
+pub(crate) use create::CreateCommand;
+pub(crate) use delete::DeleteCommand;
+pub(crate) use list::ListCommand;
+pub(crate) use show::ShowCommand;
+
+use crate::CommandGlobalOpts;
+use clap::{Args, Subcommand};
+
+/// Manage WebSocket Listeners
+#[derive(Args, Clone, Debug)]
+pub struct WsListenerCommand {
+    #[command(subcommand)]
+    subcommand: WsListenerSubCommand,
+}
+
+#[derive(Clone, Debug, Subcommand)]
+pub enum WsListenerSubCommand {
+    /// Create websocket listener on the selected node
+    Create(CreateCommand),
+
+    /// Delete websocket listener on the selected node
+    Delete(DeleteCommand),
+
+    /// List websocket listeners registered on the selected node
+    List(ListCommand),
+
+    /// Show websocket listener details
+    Show(ShowCommand),
+}
+
+impl WsListenerCommand {
+    pub fn run(self, options: CommandGlobalOpts) {
+        match self.subcommand {
+            WsListenerSubCommand::Create(c) => c.run(options),
+            WsListenerSubCommand::Delete(c) => c.run(options),
+            WsListenerSubCommand::List(c) => c.run(options),
+            WsListenerSubCommand::Show(c) => c.run(options),
+        }
+    }
+}
@@ -90,3 +90,76 @@ teardown() {
   run_success "$OCKAM" identity default "${i}"
   assert_output "${i}"
 }
+
+@test "identity - rotate key and show history" {
+  i=$(random_str)
+  run_success "$OCKAM" identity create "${i}"
+  identifier=$($OCKAM identity show "${i}")
+
+  run_success "$OCKAM" identity history "${i}"
+  assert_output --partial "Change[0]:"
+  refute_output --partial "Change[1]:"
+
+  run_success "$OCKAM" identity rotate "${i}"
+  assert_output --regexp '^[0-9a-f]{64}$'
+
+  run_success "$OCKAM" identity rotate "${i}" --revoke-purpose-keys
+  run_success "$OCKAM" identity history "${i}" --output json
+  assert_output --partial "\"revoke_all_purpose_keys\": true"
+
+  run_success "$OCKAM" identity history "${i}"
+  assert_output --partial "Change[2]:"
+  assert_output --partial "previous_change:"
+
+  # the identifier is not modified by a rotation
+  run_success "$OCKAM" identity show "${i}"
+  assert_output "${identifier}"
+}
+
+@test "identity - recover with recovery shares" {
+  i=$(random_str)
+  run_success "$OCKAM" identity create "${i}"
+  identifier=$($OCKAM identity show "${i}")
+
+  run_success "$OCKAM" identity recovery export-shares "${i}" --shares 3 --threshold 2 --output-dir "${BATS_TEST_TMPDIR}/shares"
+  assert_output --partial "recovery-share-2.json"
+  "$OCKAM" identity show "${i}" --full --encoding hex >"${BATS_TEST_TMPDIR}/identity.hex"
+
+  run_success "$OCKAM" identity history "${i}"
+  assert_output --partial "recovery_keys:           2 of 3"
+
+  # one share is not enough
+  run_failure "$OCKAM" identity recovery import-shares "${i}" --identity $(cat "${BATS_TEST_TMPDIR}/identity.hex") \
+    --share "${BATS_TEST_TMPDIR}/shares/recovery-share-0.json"
+
+  run_success "$OCKAM" identity recovery import-shares "${i}" --identity $(cat "${BATS_TEST_TMPDIR}/identity.hex") \
+    --share "${BATS_TEST_TMPDIR}/shares/recovery-share-0.json" --share "${BATS_TEST_TMPDIR}/shares/recovery-share-2.json"
+  assert_output "${identifier}"
+
+  run_success "$OCKAM" identity history "${i}"
+  assert_output --partial "recovered:               true"
+}
+
+@test "identity - replace recovery shares" {
+  i=$(random_str)
+  run_success "$OCKAM" identity create "${i}"
+
+  run_success "$OCKAM" identity recovery export-shares "${i}" --shares 3 --threshold 2 --output-dir "${BATS_TEST_TMPDIR}/shares"
+
+  # the current shares must approve their replacement
+  run_failure "$OCKAM" identity recovery export-shares "${i}" --shares 2 --threshold 1 --output-dir "${BATS_TEST_TMPDIR}/new-shares"
+  run_failure "$OCKAM" identity recovery export-shares "${i}" --shares 2 --threshold 1 --output-dir "${BATS_TEST_TMPDIR}/new-shares" \
+    --share "${BATS_TEST_TMPDIR}/shares/recovery-share-1.json"
+  run_success "$OCKAM" identity recovery export-shares "${i}" --shares 2 --threshold 1 --output-dir "${BATS_TEST_TMPDIR}/new-shares" \
+    --share "${BATS_TEST_TMPDIR}/shares/recovery-share-0.json" --share "${BATS_TEST_TMPDIR}/shares/recovery-share-1.json"
+  "$OCKAM" identity show "${i}" --full --encoding hex >"${BATS_TEST_TMPDIR}/identity.hex"
+
+  run_success "$OCKAM" identity history "${i}"
+  assert_output --partial "recovery_keys:           1 of 2"
+
+  # the replaced shares can't recover the identity anymore
+  run_failure "$OCKAM" identity recovery import-shares "${i}" --identity $(cat "${BATS_TEST_TMPDIR}/identity.hex") \
+    --share "${BATS_TEST_TMPDIR}/shares/recovery-share-0.json" --share "${BATS_TEST_TMPDIR}/shares/recovery-share-2.json"
+  run_success "$OCKAM" identity recovery import-shares "${i}" --identity $(cat "${BATS_TEST_TMPDIR}/identity.hex") \
+    --share "${BATS_TEST_TMPDIR}/new-shares/recovery-share-1.json"
+}
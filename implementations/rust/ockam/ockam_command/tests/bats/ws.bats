@@ -0,0 +1,78 @@
+#!/bin/bash
+
+# ===== SETUP
+
+setup() {
+  load load/base.bash
+  load_bats_ext
+  setup_home_dir
+}
+
+teardown() {
+  teardown_home_dir
+}
+
+# ===== TESTS
+
+@test "ws listener - CRUD" {
+  port="$(random_port)"
+  addr="127.0.0.1:$port"
+
+  run_success "$OCKAM" node create n1
+
+  # Create ws-listener and check output
+  run_success "$OCKAM" ws-listener create "$addr" --at n1
+  assert_output --regexp '/dnsaddr/localhost/tcp/[[:digit:]]+/ws'
+
+  # Check that the listener is listed
+  run_success "$OCKAM" ws-listener list --at n1
+  assert_output --partial "$addr"
+
+  # Show the listener details
+  run_success "$OCKAM" ws-listener show --at n1 "$addr"
+  assert_output --partial "$addr"
+
+  # Delete the listener
+  run_success "$OCKAM" ws-listener delete --at n1 "$addr" --yes
+
+  # Check that it's no longer listed
+  run_success "$OCKAM" ws-listener list --at n1
+  refute_output --partial "$addr"
+}
+
+@test "ws connection - CRUD" {
+  port="$(random_port)"
+  addr="127.0.0.1:$port"
+
+  run_success "$OCKAM" node create n1
+  run_success "$OCKAM" node create n2
+  run_success "$OCKAM" ws-listener create "$addr" --at n1
+
+  # Create ws-connection and check output
+  run_success "$OCKAM" ws-connection create --from n2 --to "$addr" --output json
+  assert_output --regexp '[{"route":"/worker/[[:graph:]]+"}]'
+
+  id=$($OCKAM ws-connection list --at n2 | grep -o "[0-9a-f]\{32\}" | head -1)
+
+  # Show the connection details
+  run_success "$OCKAM" ws-connection show --at n2 "$id"
+  assert_output --partial "$id"
+
+  # Delete the connection
+  run_success "$OCKAM" ws-connection delete --at n2 "$id" --yes
+
+  # Check that it's no longer listed
+  run_success "$OCKAM" ws-connection list --at n2
+  refute_output --partial "$id"
+}
+
+@test "ws - send a message through a secure channel over a ws route" {
+  port="$(random_port)"
+
+  run_success "$OCKAM" node create n1
+  run_success "$OCKAM" node create n2
+  run_success "$OCKAM" ws-listener create "127.0.0.1:$port" --at n1
+
+  run_success "$OCKAM" message send hello --from n2 --to "/dnsaddr/localhost/tcp/$port/ws/secure/api/service/echo"
+  assert_output "hello"
+}
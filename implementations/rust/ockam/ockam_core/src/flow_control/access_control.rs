@@ -70,6 +70,9 @@ impl OutgoingAccessControl for FlowControlOutgoingAccessControl {
             next,
         );
 
+        #[cfg(feature = "std")]
+        crate::metrics::FLOW_CONTROL_DENIALS.get().inc();
+
         crate::deny()
     }
 }
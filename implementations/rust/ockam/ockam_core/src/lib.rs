@@ -75,6 +75,9 @@ pub mod hex_encoding;
 /// Environmental variables
 pub mod env;
 
+#[cfg(feature = "std")]
+pub mod metrics;
+
 mod cbor;
 mod error;
 mod message;
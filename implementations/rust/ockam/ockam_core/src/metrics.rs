@@ -0,0 +1,507 @@
+//! Node runtime metrics
+//!
+//! This module defines the counters, gauges and histograms collected by an Ockam node
+//! and encodes them with the [Prometheus text format][1], which is also accepted by
+//! OpenMetrics scrapers.
+//!
+//! The metrics are process-wide statics which are updated by the router, the flow controls,
+//! the secure channels and the TCP transport. They are only exported when a node is created
+//! with a metrics address.
+//!
+//! [1]: https://prometheus.io/docs/instrumenting/exposition_formats/#text-based-format
+
+use crate::compat::collections::BTreeMap;
+use crate::compat::string::{String, ToString};
+use crate::compat::sync::Arc;
+use crate::compat::vec::Vec;
+use core::fmt::Write;
+use core::sync::atomic::{AtomicI64, AtomicU64, Ordering};
+use std::sync::Mutex;
+
+/// Number of messages sent to a worker by the router
+pub static ROUTER_MESSAGES: MetricFamily<Counter> = MetricFamily::new(
+    "ockam_router_messages_total",
+    "Number of messages routed to a local worker",
+    &[],
+    Counter::new(),
+);
+
+/// Size of the messages payloads sent to a worker by the router
+pub static ROUTER_MESSAGE_SIZE: MetricFamily<Histogram> = MetricFamily::new(
+    "ockam_router_message_size_bytes",
+    "Size of the payloads of the messages routed to a local worker",
+    &[],
+    Histogram::new(&[
+        64.0, 256.0, 1024.0, 4096.0, 16384.0, 65536.0, 262144.0, 1048576.0,
+    ]),
+);
+
+/// Number of messages waiting in the mailbox of each worker
+pub static MAILBOX_DEPTH: MetricFamily<Gauge> = MetricFamily::new(
+    "ockam_worker_mailbox_depth",
+    "Number of messages waiting to be handled by a worker",
+    &["address"],
+    Gauge::new(),
+);
+
+/// Number of messages dropped or rejected because the mailbox of a worker was full
+pub static MAILBOX_OVERFLOWS: MetricFamily<Counter> = MetricFamily::new(
+    "ockam_worker_mailbox_overflows_total",
+    "Number of messages dropped or rejected because the mailbox of a worker was full",
+    &["address", "policy"],
+    Counter::new(),
+);
+
+/// Number of secure channel handshakes, per role and result
+pub static SECURE_CHANNEL_HANDSHAKES: MetricFamily<Counter> = MetricFamily::new(
+    "ockam_secure_channel_handshakes_total",
+    "Number of secure channel handshakes, by role and result",
+    &["role", "result"],
+    Counter::new(),
+);
+
+/// Duration of the successful secure channel handshakes
+pub static SECURE_CHANNEL_HANDSHAKE_DURATION: MetricFamily<Histogram> = MetricFamily::new(
+    "ockam_secure_channel_handshake_duration_seconds",
+    "Duration of the successful secure channel handshakes",
+    &["role"],
+    Histogram::new(&[
+        0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0, 10.0,
+    ]),
+);
+
+/// Number of bytes transferred by TCP portals
+pub static PORTAL_BYTES: MetricFamily<Counter> = MetricFamily::new(
+    "ockam_portal_bytes_total",
+    "Number of bytes read from (in) or written to (out) the TCP connections of portals",
+    &["portal", "direction"],
+    Counter::new(),
+);
+
+/// Number of TCP transport connections which were opened
+pub static TCP_CONNECTIONS: MetricFamily<Counter> = MetricFamily::new(
+    "ockam_tcp_connections_total",
+    "Number of TCP transport connections opened, by mode",
+    &["mode"],
+    Counter::new(),
+);
+
+/// Number of TCP transport connections which are currently open
+pub static TCP_ACTIVE_CONNECTIONS: MetricFamily<Gauge> = MetricFamily::new(
+    "ockam_tcp_active_connections",
+    "Number of TCP transport connections currently open, by mode",
+    &["mode"],
+    Gauge::new(),
+);
+
+/// Number of messages denied by a flow control
+pub static FLOW_CONTROL_DENIALS: MetricFamily<Counter> = MetricFamily::new(
+    "ockam_flow_control_denied_messages_total",
+    "Number of messages which were not sent because of a flow control",
+    &[],
+    Counter::new(),
+);
+
+/// Encode all the node metrics with the Prometheus text format
+pub fn encode_metrics() -> String {
+    let families: [&dyn EncodeMetricFamily; 10] = [
+        &ROUTER_MESSAGES,
+        &ROUTER_MESSAGE_SIZE,
+        &MAILBOX_DEPTH,
+        &MAILBOX_OVERFLOWS,
+        &SECURE_CHANNEL_HANDSHAKES,
+        &SECURE_CHANNEL_HANDSHAKE_DURATION,
+        &PORTAL_BYTES,
+        &TCP_CONNECTIONS,
+        &TCP_ACTIVE_CONNECTIONS,
+        &FLOW_CONTROL_DENIALS,
+    ];
+
+    let mut out = String::new();
+    for family in families {
+        family.encode(&mut out);
+    }
+    out
+}
+
+/// A kind of metric which can be part of a [`MetricFamily`]
+pub trait Metric: Send + Sync + 'static {
+    /// Prometheus type of the metric
+    const TYPE: &'static str;
+
+    /// Return a new metric with the same configuration but no recorded values
+    fn empty_like(&self) -> Self;
+
+    /// Encode the metric samples, for a given set of labels
+    fn encode(&self, out: &mut String, name: &str, labels: &[(&str, &str)]);
+}
+
+/// A set of metrics of the same kind, distinguished by their label values
+///
+/// A family without label names contains a single metric, accessible with [`MetricFamily::get`].
+pub struct MetricFamily<M: Metric> {
+    name: &'static str,
+    help: &'static str,
+    label_names: &'static [&'static str],
+    unlabeled: M,
+    children: Mutex<BTreeMap<Vec<String>, Arc<M>>>,
+}
+
+impl<M: Metric> MetricFamily<M> {
+    /// Create a new family. The `metric` value is used when the family has no labels
+    /// and as a template for the metrics created for each set of label values
+    pub const fn new(
+        name: &'static str,
+        help: &'static str,
+        label_names: &'static [&'static str],
+        metric: M,
+    ) -> Self {
+        Self {
+            name,
+            help,
+            label_names,
+            unlabeled: metric,
+            children: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    /// Return the metric of a family without labels
+    pub fn get(&self) -> &M {
+        debug_assert!(self.label_names.is_empty());
+        &self.unlabeled
+    }
+
+    /// Return the metric for a given set of label values, creating it if necessary.
+    /// The values must be given in the same order as the family label names
+    pub fn with_labels(&self, label_values: &[&str]) -> Arc<M> {
+        debug_assert_eq!(label_values.len(), self.label_names.len());
+        let key: Vec<String> = label_values.iter().map(|v| v.to_string()).collect();
+        let mut children = self.lock();
+        children
+            .entry(key)
+            .or_insert_with(|| Arc::new(self.unlabeled.empty_like()))
+            .clone()
+    }
+
+    /// Remove the metric for a given set of label values
+    pub fn remove(&self, label_values: &[&str]) {
+        let key: Vec<String> = label_values.iter().map(|v| v.to_string()).collect();
+        self.lock().remove(&key);
+    }
+
+    /// Remove the metrics for all the label values
+    pub fn clear(&self) {
+        self.lock().clear();
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BTreeMap<Vec<String>, Arc<M>>> {
+        // a panic while holding the lock can not leave the map in an inconsistent state
+        self.children.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+/// Object-safe encoding of a [`MetricFamily`]
+pub trait EncodeMetricFamily: Send + Sync {
+    /// Append the family metadata and samples to `out`
+    fn encode(&self, out: &mut String);
+}
+
+impl<M: Metric> EncodeMetricFamily for MetricFamily<M> {
+    fn encode(&self, out: &mut String) {
+        let _ = writeln!(out, "# HELP {} {}", self.name, escape_help(self.help));
+        let _ = writeln!(out, "# TYPE {} {}", self.name, M::TYPE);
+
+        if self.label_names.is_empty() {
+            self.unlabeled.encode(out, self.name, &[]);
+            return;
+        }
+
+        for (label_values, metric) in self.lock().iter() {
+            let labels: Vec<(&str, &str)> = self
+                .label_names
+                .iter()
+                .copied()
+                .zip(label_values.iter().map(|v| v.as_str()))
+                .collect();
+            metric.encode(out, self.name, &labels);
+        }
+    }
+}
+
+/// A monotonically increasing value
+#[derive(Debug, Default)]
+pub struct Counter {
+    value: AtomicU64,
+}
+
+impl Counter {
+    /// Create a new counter, starting at 0
+    pub const fn new() -> Self {
+        Self {
+            value: AtomicU64::new(0),
+        }
+    }
+
+    /// Add 1 to the counter
+    pub fn inc(&self) {
+        self.inc_by(1)
+    }
+
+    /// Add a value to the counter
+    pub fn inc_by(&self, value: u64) {
+        self.value.fetch_add(value, Ordering::Relaxed);
+    }
+
+    /// Return the current value
+    pub fn get(&self) -> u64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+impl Metric for Counter {
+    const TYPE: &'static str = "counter";
+
+    fn empty_like(&self) -> Self {
+        Self::new()
+    }
+
+    fn encode(&self, out: &mut String, name: &str, labels: &[(&str, &str)]) {
+        write_sample(out, name, "", labels, None, &self.get().to_string());
+    }
+}
+
+/// A value which can go up and down
+#[derive(Debug, Default)]
+pub struct Gauge {
+    value: AtomicI64,
+}
+
+impl Gauge {
+    /// Create a new gauge, starting at 0
+    pub const fn new() -> Self {
+        Self {
+            value: AtomicI64::new(0),
+        }
+    }
+
+    /// Add 1 to the gauge
+    pub fn inc(&self) {
+        self.value.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Subtract 1 from the gauge
+    pub fn dec(&self) {
+        self.value.fetch_sub(1, Ordering::Relaxed);
+    }
+
+    /// Set the gauge value
+    pub fn set(&self, value: i64) {
+        self.value.store(value, Ordering::Relaxed);
+    }
+
+    /// Return the current value
+    pub fn get(&self) -> i64 {
+        self.value.load(Ordering::Relaxed)
+    }
+}
+
+impl Metric for Gauge {
+    const TYPE: &'static str = "gauge";
+
+    fn empty_like(&self) -> Self {
+        Self::new()
+    }
+
+    fn encode(&self, out: &mut String, name: &str, labels: &[(&str, &str)]) {
+        write_sample(out, name, "", labels, None, &self.get().to_string());
+    }
+}
+
+/// Maximum number of buckets for a [`Histogram`], not counting the `+Inf` bucket
+pub const MAX_HISTOGRAM_BUCKETS: usize = 16;
+
+/// A distribution of observed values, counted in buckets with increasing upper bounds
+#[derive(Debug)]
+pub struct Histogram {
+    bounds: &'static [f64],
+    buckets: [AtomicU64; MAX_HISTOGRAM_BUCKETS],
+    count: AtomicU64,
+    sum: AtomicU64,
+}
+
+impl Histogram {
+    /// Create a new histogram with the given (sorted) bucket upper bounds
+    pub const fn new(bounds: &'static [f64]) -> Self {
+        assert!(bounds.len() <= MAX_HISTOGRAM_BUCKETS);
+        #[allow(clippy::declare_interior_mutable_const)]
+        const ZERO: AtomicU64 = AtomicU64::new(0);
+        Self {
+            bounds,
+            buckets: [ZERO; MAX_HISTOGRAM_BUCKETS],
+            count: AtomicU64::new(0),
+            sum: AtomicU64::new(0),
+        }
+    }
+
+    /// Record a value
+    pub fn observe(&self, value: f64) {
+        if let Some(index) = self.bounds.iter().position(|bound| value <= *bound) {
+            self.buckets[index].fetch_add(1, Ordering::Relaxed);
+        }
+        self.count.fetch_add(1, Ordering::Relaxed);
+        // the sum is stored as the bits of a f64
+        let _ = self
+            .sum
+            .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |sum| {
+                Some((f64::from_bits(sum) + value).to_bits())
+            });
+    }
+
+    /// Return the number of observed values
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Return the sum of the observed values
+    pub fn sum(&self) -> f64 {
+        f64::from_bits(self.sum.load(Ordering::Relaxed))
+    }
+}
+
+impl Metric for Histogram {
+    const TYPE: &'static str = "histogram";
+
+    fn empty_like(&self) -> Self {
+        Self::new(self.bounds)
+    }
+
+    fn encode(&self, out: &mut String, name: &str, labels: &[(&str, &str)]) {
+        let mut cumulative = 0;
+        for (bound, bucket) in self.bounds.iter().zip(self.buckets.iter()) {
+            cumulative += bucket.load(Ordering::Relaxed);
+            write_sample(
+                out,
+                name,
+                "_bucket",
+                labels,
+                Some(&bound.to_string()),
+                &cumulative.to_string(),
+            );
+        }
+        let count = self.count().to_string();
+        write_sample(out, name, "_bucket", labels, Some("+Inf"), &count);
+        write_sample(out, name, "_sum", labels, None, &self.sum().to_string());
+        write_sample(out, name, "_count", labels, None, &count);
+    }
+}
+
+fn write_sample(
+    out: &mut String,
+    name: &str,
+    suffix: &str,
+    labels: &[(&str, &str)],
+    le: Option<&str>,
+    value: &str,
+) {
+    out.push_str(name);
+    out.push_str(suffix);
+    let le = le.map(|le| ("le", le));
+    let mut labels = labels.iter().copied().chain(le).peekable();
+    if labels.peek().is_some() {
+        out.push('{');
+        for (i, (label_name, label_value)) in labels.enumerate() {
+            if i > 0 {
+                out.push(',');
+            }
+            let _ = write!(
+                out,
+                "{}=\"{}\"",
+                label_name,
+                escape_label_value(label_value)
+            );
+        }
+        out.push('}');
+    }
+    out.push(' ');
+    out.push_str(value);
+    out.push('\n');
+}
+
+fn escape_help(help: &str) -> String {
+    help.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_counter_family() {
+        let family = MetricFamily::new("requests_total", "Number of requests", &[], Counter::new());
+        family.get().inc();
+        family.get().inc_by(2);
+
+        let mut out = String::new();
+        family.encode(&mut out);
+        assert_eq!(
+            out,
+            "# HELP requests_total Number of requests\n\
+             # TYPE requests_total counter\n\
+             requests_total 3\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_labeled_gauge_family() {
+        let family = MetricFamily::new("depth", "Depth", &["address"], Gauge::new());
+        family.with_labels(&["b"]).set(2);
+        family.with_labels(&["a\"1"]).inc();
+        family.with_labels(&["c"]).inc();
+        family.remove(&["c"]);
+
+        let mut out = String::new();
+        family.encode(&mut out);
+        assert_eq!(
+            out,
+            "# HELP depth Depth\n\
+             # TYPE depth gauge\n\
+             depth{address=\"a\\\"1\"} 1\n\
+             depth{address=\"b\"} 2\n"
+        );
+    }
+
+    #[test]
+    fn test_encode_histogram_family() {
+        let family = MetricFamily::new(
+            "duration_seconds",
+            "Duration",
+            &["role"],
+            Histogram::new(&[0.1, 1.0]),
+        );
+        let histogram = family.with_labels(&["initiator"]);
+        histogram.observe(0.0625);
+        histogram.observe(0.5);
+        histogram.observe(0.5);
+        histogram.observe(3.0);
+
+        let mut out = String::new();
+        family.encode(&mut out);
+        assert_eq!(
+            out,
+            "# HELP duration_seconds Duration\n\
+             # TYPE duration_seconds histogram\n\
+             duration_seconds_bucket{role=\"initiator\",le=\"0.1\"} 1\n\
+             duration_seconds_bucket{role=\"initiator\",le=\"1\"} 3\n\
+             duration_seconds_bucket{role=\"initiator\",le=\"+Inf\"} 4\n\
+             duration_seconds_sum{role=\"initiator\"} 4.0625\n\
+             duration_seconds_count{role=\"initiator\"} 4\n"
+        );
+    }
+}
@@ -6,5 +6,8 @@ pub use local_message::*;
 mod relay_message;
 pub use relay_message::*;
 
+mod trace_context;
+pub use trace_context::*;
+
 mod transport_message;
 pub use transport_message::*;
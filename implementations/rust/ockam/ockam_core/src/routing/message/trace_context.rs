@@ -0,0 +1,160 @@
+use crate::compat::rand::random;
+use crate::compat::string::String;
+use crate::errcode::{Kind, Origin};
+use crate::{Error, Result};
+use core::fmt::{self, Display, Formatter};
+use core::str::FromStr;
+use serde::{Deserialize, Serialize};
+
+/// Distributed tracing context carried along with a [`TransportMessage`](crate::TransportMessage).
+///
+/// It follows the [W3C Trace Context](https://www.w3.org/TR/trace-context/) model:
+/// a 16 bytes trace id shared by every span of a trace, the 8 bytes id of the span
+/// which sent the message, and the trace flags.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Hash, Ord, PartialOrd, Eq, PartialEq)]
+pub struct TraceContext {
+    trace_id: [u8; 16],
+    span_id: [u8; 8],
+    flags: u8,
+}
+
+impl TraceContext {
+    /// Flag set when the trace is sampled
+    pub const SAMPLED: u8 = 0x01;
+
+    /// Create a new trace context from its parts
+    pub fn new(trace_id: [u8; 16], span_id: [u8; 8], flags: u8) -> Self {
+        Self {
+            trace_id,
+            span_id,
+            flags,
+        }
+    }
+
+    /// Start a new sampled trace with random trace and span ids
+    pub fn new_root() -> Self {
+        Self::new(random(), random(), Self::SAMPLED)
+    }
+
+    /// Create the context of a span which is a child of this one:
+    /// same trace id and flags, new random span id
+    pub fn new_child(&self) -> Self {
+        Self::new(self.trace_id, random(), self.flags)
+    }
+
+    /// Id of the trace
+    pub fn trace_id(&self) -> [u8; 16] {
+        self.trace_id
+    }
+
+    /// Id of the span which emitted this context
+    pub fn span_id(&self) -> [u8; 8] {
+        self.span_id
+    }
+
+    /// Trace flags
+    pub fn flags(&self) -> u8 {
+        self.flags
+    }
+
+    /// Return true if the trace is sampled
+    pub fn is_sampled(&self) -> bool {
+        self.flags & Self::SAMPLED != 0
+    }
+
+    /// Hex encoded trace id
+    pub fn trace_id_hex(&self) -> String {
+        hex::encode(self.trace_id)
+    }
+
+    /// Hex encoded span id
+    pub fn span_id_hex(&self) -> String {
+        hex::encode(self.span_id)
+    }
+}
+
+/// Format the context as a W3C `traceparent` header value
+impl Display for TraceContext {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "00-{}-{}-{:02x}",
+            self.trace_id_hex(),
+            self.span_id_hex(),
+            self.flags
+        )
+    }
+}
+
+/// Parse a W3C `traceparent` header value
+impl FromStr for TraceContext {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::new_without_cause(Origin::Core, Kind::Invalid);
+
+        let mut parts = s.trim().split('-');
+        let (version, trace_id, span_id, flags) =
+            match (parts.next(), parts.next(), parts.next(), parts.next()) {
+                (Some(v), Some(t), Some(s), Some(f)) => (v, t, s, f),
+                _ => return Err(invalid()),
+            };
+        // Future versions may append fields, version 00 may not
+        if version.len() != 2 || version == "ff" || (version == "00" && parts.next().is_some()) {
+            return Err(invalid());
+        }
+
+        let mut context = TraceContext::new([0; 16], [0; 8], 0);
+        hex::decode_to_slice(trace_id, &mut context.trace_id).map_err(|_| invalid())?;
+        hex::decode_to_slice(span_id, &mut context.span_id).map_err(|_| invalid())?;
+        let mut flags_bytes = [0u8; 1];
+        hex::decode_to_slice(flags, &mut flags_bytes).map_err(|_| invalid())?;
+        context.flags = flags_bytes[0];
+
+        // All-zero ids are invalid
+        if context.trace_id == [0; 16] || context.span_id == [0; 8] {
+            return Err(invalid());
+        }
+
+        Ok(context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::compat::string::ToString;
+
+    #[test]
+    fn traceparent_roundtrip() {
+        let context: TraceContext = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+            .parse()
+            .unwrap();
+        assert_eq!(context.trace_id_hex(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(context.span_id_hex(), "00f067aa0ba902b7");
+        assert!(context.is_sampled());
+        assert_eq!(
+            context.to_string(),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01"
+        );
+
+        let child = context.new_child();
+        assert_eq!(child.trace_id(), context.trace_id());
+        assert_ne!(child.span_id(), context.span_id());
+    }
+
+    #[test]
+    fn invalid_traceparent() {
+        for s in [
+            "",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7",
+            "00-00000000000000000000000000000000-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-0000000000000000-01",
+            "ff-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01",
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01-extra",
+            "00-4bf92f3577b34da6a3ce929d0e0e47-00f067aa0ba902b7-01",
+        ] {
+            assert!(s.parse::<TraceContext>().is_err(), "{s}");
+        }
+    }
+}
@@ -1,6 +1,8 @@
-use crate::{compat::vec::Vec, Message, Route};
+use crate::{compat::vec::Vec, Message, Route, TraceContext};
 use core::fmt::{self, Display, Formatter};
-use serde::{Deserialize, Serialize};
+use serde::de::{self, SeqAccess, Visitor};
+use serde::ser::SerializeStruct;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 
 /// A generic transport message type.
 ///
@@ -15,7 +17,13 @@ use serde::{Deserialize, Serialize};
 ///
 /// See `ockam_transport_tcp::workers::sender::TcpSendWorker` for a usage example.
 ///
-#[derive(Serialize, Deserialize, Debug, Clone, Hash, Ord, PartialOrd, Eq, PartialEq, Message)]
+/// # Versions
+///
+/// Version 1 messages only carry the routes and the payload. Version 2
+/// messages append an optional [`TraceContext`] after the payload. Since
+/// the extra section is encoded last, nodes which only know about version 1
+/// can still decode version 2 messages, ignoring the trace context.
+#[derive(Debug, Clone, Hash, Ord, PartialOrd, Eq, PartialEq, Message)]
 pub struct TransportMessage {
     /// The transport protocol version.
     pub version: u8,
@@ -28,6 +36,8 @@ pub struct TransportMessage {
     pub return_route: Route,
     /// The message payload.
     pub payload: Vec<u8>,
+    /// Distributed tracing context, only encoded by version 2 messages.
+    pub tracing_context: Option<TraceContext>,
 }
 
 impl TransportMessage {
@@ -42,7 +52,95 @@ impl TransportMessage {
             onward_route: onward_route.into(),
             return_route: return_route.into(),
             payload,
+            tracing_context: None,
+        }
+    }
+
+    /// Attach a tracing context to this message, upgrading it to version 2
+    /// if the context is set.
+    pub fn with_tracing_context(mut self, tracing_context: Option<TraceContext>) -> Self {
+        self.set_tracing_context(tracing_context);
+        self
+    }
+
+    /// Set the tracing context of this message, upgrading it to version 2
+    /// if the context is set.
+    pub fn set_tracing_context(&mut self, tracing_context: Option<TraceContext>) {
+        if tracing_context.is_some() && self.version < 2 {
+            self.version = 2;
+        }
+        self.tracing_context = tracing_context;
+    }
+}
+
+impl Serialize for TransportMessage {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let with_metadata = self.version >= 2;
+        let len = if with_metadata { 5 } else { 4 };
+        let mut state = serializer.serialize_struct("TransportMessage", len)?;
+        state.serialize_field("version", &self.version)?;
+        state.serialize_field("onward_route", &self.onward_route)?;
+        state.serialize_field("return_route", &self.return_route)?;
+        state.serialize_field("payload", &self.payload)?;
+        if with_metadata {
+            state.serialize_field("tracing_context", &self.tracing_context)?;
+        }
+        state.end()
+    }
+}
+
+impl<'de> Deserialize<'de> for TransportMessage {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct TransportMessageVisitor;
+
+        impl<'de> Visitor<'de> for TransportMessageVisitor {
+            type Value = TransportMessage;
+
+            fn expecting(&self, f: &mut Formatter) -> fmt::Result {
+                f.write_str("struct TransportMessage")
+            }
+
+            fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                let version: u8 = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(0, &self))?;
+                let onward_route = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(1, &self))?;
+                let return_route = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(2, &self))?;
+                let payload = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(3, &self))?;
+                let tracing_context = if version >= 2 {
+                    seq.next_element()?
+                        .ok_or_else(|| de::Error::invalid_length(4, &self))?
+                } else {
+                    None
+                };
+
+                Ok(TransportMessage {
+                    version,
+                    onward_route,
+                    return_route,
+                    payload,
+                    tracing_context,
+                })
+            }
         }
+
+        deserializer.deserialize_struct(
+            "TransportMessage",
+            &[
+                "version",
+                "onward_route",
+                "return_route",
+                "payload",
+                "tracing_context",
+            ],
+            TransportMessageVisitor,
+        )
     }
 }
 
@@ -55,3 +153,44 @@ impl Display for TransportMessage {
         )
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{route, Decodable, Encodable};
+
+    #[test]
+    fn v1_encoding_is_unchanged() {
+        let msg = TransportMessage::v1(route!["a", "b"], route!["c"], vec![1, 2, 3]);
+        let encoded = msg.clone().encode().unwrap();
+        assert_eq!(TransportMessage::decode(&encoded).unwrap(), msg);
+
+        // A version 1 message followed by other data is decoded without the metadata section
+        let mut extended = encoded.clone();
+        extended.push(1);
+        assert_eq!(TransportMessage::decode(&extended).unwrap(), msg);
+    }
+
+    #[test]
+    fn v2_roundtrip() {
+        let context = TraceContext::new_root();
+        let msg = TransportMessage::v1(route!["a"], route!["c"], vec![1, 2, 3])
+            .with_tracing_context(Some(context));
+        assert_eq!(msg.version, 2);
+
+        let decoded = TransportMessage::decode(&msg.clone().encode().unwrap()).unwrap();
+        assert_eq!(decoded, msg);
+        assert_eq!(decoded.tracing_context, Some(context));
+
+        // A version 1 decoder stops after the payload
+        #[derive(serde::Deserialize)]
+        struct V1 {
+            _version: u8,
+            _onward_route: Route,
+            _return_route: Route,
+            payload: Vec<u8>,
+        }
+        let v1: V1 = serde_bare::from_slice(&msg.encode().unwrap()).unwrap();
+        assert_eq!(v1.payload, vec![1, 2, 3]);
+    }
+}
@@ -1,10 +1,10 @@
 use ockam_core::compat::sync::Arc;
 use ockam_vault::{VaultForSigning, VaultForVerifyingSignatures};
 
-use crate::models::{CredentialData, PurposeKeyAttestationData};
+use crate::models::{CredentialData, Identifier, PurposeKeyAttestationData};
 use crate::{
     CredentialsCreation, CredentialsVerification, IdentitiesCreation, IdentityAttributesRepository,
-    PurposeKeys,
+    PurposeKeys, RevocationsRepository,
 };
 
 /// Structure with both [`CredentialData`] and [`PurposeKeyAttestationData`] that we get
@@ -15,6 +15,8 @@ pub struct CredentialAndPurposeKeyData {
     pub credential_data: CredentialData,
     /// [`PurposeKeyAttestationData`]
     pub purpose_key_data: PurposeKeyAttestationData,
+    /// Trusted authority which issued the [`Credential`], directly or through a chain of delegations
+    pub authority: Identifier,
 }
 
 /// Service for managing [`Credential`]s
@@ -24,6 +26,7 @@ pub struct Credentials {
     purpose_keys: Arc<PurposeKeys>,
     identities_creation: Arc<IdentitiesCreation>,
     identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+    revocations_repository: Arc<dyn RevocationsRepository>,
 }
 
 impl Credentials {
@@ -34,6 +37,7 @@ impl Credentials {
         purpose_keys: Arc<PurposeKeys>,
         identities_creation: Arc<IdentitiesCreation>,
         identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+        revocations_repository: Arc<dyn RevocationsRepository>,
     ) -> Self {
         Self {
             credential_vault,
@@ -41,6 +45,7 @@ impl Credentials {
             purpose_keys,
             identities_creation,
             identity_attributes_repository,
+            revocations_repository,
         }
     }
 
@@ -49,6 +54,11 @@ impl Credentials {
         self.purpose_keys.clone()
     }
 
+    /// Return the revocations repository
+    pub fn revocations_repository(&self) -> Arc<dyn RevocationsRepository> {
+        self.revocations_repository.clone()
+    }
+
     /// Return [`CredentialsCreation`]
     pub fn credentials_creation(&self) -> Arc<CredentialsCreation> {
         Arc::new(CredentialsCreation::new(
@@ -64,7 +74,9 @@ impl Credentials {
         Arc::new(CredentialsVerification::new(
             self.purpose_keys.purpose_keys_verification(),
             self.verifying_vault.clone(),
+            self.identities_creation.clone(),
             self.identity_attributes_repository.clone(),
+            self.revocations_repository.clone(),
         ))
     }
 }
@@ -117,4 +129,76 @@ mod tests {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_revoke_credential() -> Result<()> {
+        let identities = identities().await?;
+        let creation = identities.identities_creation();
+
+        let issuer = creation.create_identity().await?;
+        let subject = creation.create_identity().await?;
+        let credentials = identities.credentials();
+        let verification = credentials.credentials_verification();
+
+        let subject_attributes = Attributes {
+            schema: CredentialSchemaIdentifier(1),
+            map: Default::default(),
+        };
+        let credential = credentials
+            .credentials_creation()
+            .issue_credential(
+                &issuer,
+                &subject,
+                subject_attributes,
+                Duration::from_secs(60),
+            )
+            .await?;
+
+        verification
+            .verify_credential(Some(&subject), &[issuer.clone()], &credential)
+            .await?;
+
+        // an empty revocation list doesn't revoke anything
+        let empty_list = credentials
+            .credentials_creation()
+            .issue_revocation_list(&issuer, vec![], vec![])
+            .await?;
+
+        // revoke the credential by hash
+        let credential_hash = verification.credential_hash(&credential.credential).await?;
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        let revocation_list = credentials
+            .credentials_creation()
+            .issue_revocation_list(&issuer, vec![credential_hash], vec![])
+            .await?;
+
+        // a revocation list signed by an unknown authority is rejected
+        assert!(verification
+            .receive_revocation_list(&[subject.clone()], &revocation_list)
+            .await
+            .is_err());
+
+        assert!(
+            verification
+                .receive_revocation_list(&[issuer.clone()], &revocation_list)
+                .await?
+        );
+        assert!(verification
+            .verify_credential(Some(&subject), &[issuer.clone()], &credential)
+            .await
+            .is_err());
+
+        // an older revocation list can't replace a more recent one
+        assert!(
+            !verification
+                .receive_revocation_list(&[issuer.clone()], &empty_list)
+                .await?
+        );
+        assert!(verification
+            .verify_credential(Some(&subject), &[issuer.clone()], &credential)
+            .await
+            .is_err());
+
+        Ok(())
+    }
 }
@@ -1,10 +1,19 @@
 use core::time::Duration;
 
+use ockam_core::compat::rand::RngCore;
+use ockam_core::compat::string::String;
 use ockam_core::compat::sync::Arc;
-use ockam_core::Result;
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
 use ockam_vault::{VaultForSigning, VaultForVerifyingSignatures};
 
-use crate::models::{Attributes, Credential, CredentialAndPurposeKey, CredentialData, Identifier};
+use crate::models::{
+    AttributeCommitment, AttributeDisclosure, Attributes, Credential, CredentialAndPurposeKey,
+    CredentialData, CredentialHash, Delegation, DelegationAndPurposeKey, DelegationData,
+    Identifier, RevocationList, RevocationListAndPurposeKey, RevocationListData,
+    ATTRIBUTE_SALT_LEN,
+};
 use crate::utils::{add_seconds, now};
 use crate::{IdentitiesCreation, PurposeKeyCreation};
 
@@ -41,6 +50,164 @@ impl CredentialsCreation {
         subject: &Identifier,
         subject_attributes: Attributes,
         ttl: Duration,
+    ) -> Result<CredentialAndPurposeKey> {
+        self.issue(issuer, subject, subject_attributes, None, ttl)
+            .await
+    }
+
+    /// Issue a selective-disclosure [`Credential`]
+    /// The attributes are replaced by salted commitments in the signed credential data and the
+    /// corresponding disclosures are returned with the credential. The subject can then present
+    /// only some of its attributes with [`CredentialAndPurposeKey::disclose`]
+    pub async fn issue_selective_disclosure_credential(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+        subject_attributes: Attributes,
+        ttl: Duration,
+    ) -> Result<CredentialAndPurposeKey> {
+        let mut commitments = Vec::with_capacity(subject_attributes.map.len());
+        let mut disclosures = Vec::with_capacity(subject_attributes.map.len());
+        for (key, value) in subject_attributes.map {
+            let mut salt = [0u8; ATTRIBUTE_SALT_LEN];
+            rand::thread_rng().fill_bytes(&mut salt);
+            let disclosure = AttributeDisclosure { salt, key, value };
+            let commitment = self
+                .verifying_vault
+                .sha256(&minicbor::to_vec(&disclosure)?)
+                .await?;
+            commitments.push(AttributeCommitment(commitment.0));
+            disclosures.push(disclosure);
+        }
+        // The order of the commitments must not reveal the attribute keys
+        commitments.sort_by(|c1, c2| c1.0.cmp(&c2.0));
+
+        let attributes = Attributes {
+            schema: subject_attributes.schema,
+            map: Default::default(),
+        };
+        let mut credential = self
+            .issue(issuer, subject, attributes, Some(commitments), ttl)
+            .await?;
+        credential.disclosures = Some(disclosures);
+        Ok(credential)
+    }
+
+    /// Issue a [`Credential`] on behalf of an Authority which delegated the issuance of some
+    /// attributes to the issuer. The chain of [`Delegation`]s is attached to the credential so that
+    /// it can be verified up to the Authority.
+    /// The credential doesn't outlive any of the delegations
+    pub async fn issue_delegated_credential(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+        subject_attributes: Attributes,
+        delegations: Vec<DelegationAndPurposeKey>,
+        ttl: Duration,
+    ) -> Result<CredentialAndPurposeKey> {
+        let mut delegations_data = Vec::with_capacity(delegations.len());
+        for delegation in delegations.iter() {
+            delegations_data.push(delegation.delegation.get_delegation_data()?);
+        }
+        let last_delegation = delegations_data.last().ok_or_else(|| {
+            Error::new(
+                Origin::Identity,
+                Kind::Invalid,
+                "a delegated credential needs at least one delegation",
+            )
+        })?;
+        if &last_delegation.delegate != issuer {
+            return Err(Error::new(
+                Origin::Identity,
+                Kind::Invalid,
+                format!("the last delegation was not given to the issuer {issuer}"),
+            ));
+        }
+        if let Some(key) = subject_attributes
+            .map
+            .keys()
+            .find(|key| !last_delegation.allows_attribute(key))
+        {
+            return Err(Error::new(
+                Origin::Identity,
+                Kind::Invalid,
+                format!(
+                    "the attribute {} is not part of the delegated namespaces",
+                    String::from_utf8_lossy(key)
+                ),
+            ));
+        }
+
+        let now = now()?;
+        let ttl = delegations_data
+            .iter()
+            .map(|d| Duration::from_secs(d.expires_at.0.saturating_sub(now.0)))
+            .fold(ttl, Duration::min);
+
+        let mut credential = self
+            .issue(issuer, subject, subject_attributes, None, ttl)
+            .await?;
+        credential.delegations = Some(delegations);
+        Ok(credential)
+    }
+
+    /// Issue a [`Delegation`] allowing a delegate to issue [`Credential`]s with attributes in the
+    /// given namespaces, i.e. attributes whose keys start with one of the namespaces.
+    /// The returned delegation must be added at the end of the issuer's own chain of delegations
+    /// when the issuer is not a root Authority.
+    /// The identity of the delegate must be known by the issuer, its change history is attached
+    /// to the delegation
+    pub async fn issue_delegation(
+        &self,
+        issuer: &Identifier,
+        delegate: &Identifier,
+        attribute_namespaces: Vec<Vec<u8>>,
+        ttl: Duration,
+    ) -> Result<DelegationAndPurposeKey> {
+        let issuer_purpose_key = self
+            .purpose_keys_creation
+            .get_or_create_credential_purpose_key(issuer)
+            .await?;
+        let delegate_identity = self.identities_creation.get_identity(delegate).await?;
+
+        let created_at = now()?;
+        let expires_at = add_seconds(&created_at, ttl.as_secs());
+
+        let delegation_data = DelegationData {
+            delegate: delegate.clone(),
+            attribute_namespaces: attribute_namespaces.into_iter().map(|n| n.into()).collect(),
+            created_at,
+            expires_at,
+        };
+        let delegation_data = minicbor::to_vec(delegation_data)?;
+
+        let versioned_data = Delegation::create_versioned_data(delegation_data);
+        let versioned_data = minicbor::to_vec(&versioned_data)?;
+
+        let versioned_data_hash = self.verifying_vault.sha256(&versioned_data).await?;
+
+        let signature = self
+            .credential_vault
+            .sign(issuer_purpose_key.key(), &versioned_data_hash.0)
+            .await?;
+
+        Ok(DelegationAndPurposeKey {
+            delegation: Delegation {
+                data: versioned_data,
+                signature: signature.into(),
+            },
+            purpose_key_attestation: issuer_purpose_key.attestation().clone(),
+            delegate_change_history: delegate_identity.change_history().clone(),
+        })
+    }
+
+    async fn issue(
+        &self,
+        issuer: &Identifier,
+        subject: &Identifier,
+        subject_attributes: Attributes,
+        subject_attribute_commitments: Option<Vec<AttributeCommitment>>,
+        ttl: Duration,
     ) -> Result<CredentialAndPurposeKey> {
         // TODO: Allow manual PurposeKey management
         let issuer_purpose_key = self
@@ -59,6 +226,7 @@ impl CredentialsCreation {
             subject_attributes,
             created_at,
             expires_at,
+            subject_attribute_commitments,
         };
         let credential_data = minicbor::to_vec(credential_data)?;
 
@@ -81,8 +249,48 @@ impl CredentialsCreation {
         let res = CredentialAndPurposeKey {
             credential,
             purpose_key_attestation: issuer_purpose_key.attestation().clone(),
+            disclosures: None,
+            delegations: None,
         };
 
         Ok(res)
     }
+
+    /// Issue a [`RevocationList`] listing all the credentials and subjects revoked by an issuer
+    pub async fn issue_revocation_list(
+        &self,
+        issuer: &Identifier,
+        revoked_credentials: Vec<CredentialHash>,
+        revoked_subjects: Vec<Identifier>,
+    ) -> Result<RevocationListAndPurposeKey> {
+        let issuer_purpose_key = self
+            .purpose_keys_creation
+            .get_or_create_credential_purpose_key(issuer)
+            .await?;
+
+        let revocation_list_data = RevocationListData {
+            revoked_credentials,
+            revoked_subjects,
+            created_at: now()?,
+        };
+        let revocation_list_data = minicbor::to_vec(revocation_list_data)?;
+
+        let versioned_data = RevocationList::create_versioned_data(revocation_list_data);
+        let versioned_data = minicbor::to_vec(&versioned_data)?;
+
+        let versioned_data_hash = self.verifying_vault.sha256(&versioned_data).await?;
+
+        let signature = self
+            .credential_vault
+            .sign(issuer_purpose_key.key(), &versioned_data_hash.0)
+            .await?;
+
+        Ok(RevocationListAndPurposeKey {
+            revocation_list: RevocationList {
+                data: versioned_data,
+                signature: signature.into(),
+            },
+            purpose_key_attestation: issuer_purpose_key.attestation().clone(),
+        })
+    }
 }
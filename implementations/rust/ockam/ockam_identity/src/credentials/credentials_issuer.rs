@@ -12,7 +12,10 @@ use ockam_core::compat::vec::Vec;
 use ockam_core::{Result, Routed, Worker};
 use ockam_node::Context;
 
-use crate::models::{Attributes, CredentialAndPurposeKey, CredentialSchemaIdentifier, Identifier};
+use crate::models::{
+    Attributes, CredentialAndPurposeKey, CredentialSchemaIdentifier, DelegationAndPurposeKey,
+    Identifier,
+};
 use crate::utils::AttributesBuilder;
 use crate::{Credentials, IdentityAttributesRepository, IdentitySecureChannelLocalInfo};
 
@@ -35,6 +38,7 @@ pub struct CredentialsIssuer {
     credentials: Arc<Credentials>,
     issuer: Identifier,
     subject_attributes: Attributes,
+    delegations: Option<Vec<DelegationAndPurposeKey>>,
 }
 
 impl CredentialsIssuer {
@@ -54,13 +58,31 @@ impl CredentialsIssuer {
             credentials,
             issuer: issuer.clone(),
             subject_attributes,
+            delegations: None,
         }
     }
 
+    /// Issue credentials on behalf of another authority, with the chain of delegations
+    /// leading from that authority to this issuer.
+    /// All the issued attributes, including the trust context id, must be part of the delegated namespaces
+    pub fn with_delegations(mut self, delegations: Vec<DelegationAndPurposeKey>) -> Self {
+        self.delegations = Some(delegations);
+        self
+    }
+
     async fn issue_credential(
         &self,
         subject: &Identifier,
     ) -> Result<Option<CredentialAndPurposeKey>> {
+        if self
+            .credentials
+            .revocations_repository()
+            .is_subject_revoked(&self.issuer, subject)
+            .await?
+        {
+            return Ok(None);
+        }
+
         let entry = match self
             .identity_attributes_repository
             .get_attributes(subject)
@@ -77,16 +99,30 @@ impl CredentialsIssuer {
                 .insert(key.clone().into(), value.clone().into());
         }
 
-        let credential = self
-            .credentials
-            .credentials_creation()
-            .issue_credential(
-                &self.issuer,
-                subject,
-                subject_attributes,
-                MAX_CREDENTIAL_VALIDITY,
-            )
-            .await?;
+        let credentials_creation = self.credentials.credentials_creation();
+        let credential = match &self.delegations {
+            Some(delegations) => {
+                credentials_creation
+                    .issue_delegated_credential(
+                        &self.issuer,
+                        subject,
+                        subject_attributes,
+                        delegations.clone(),
+                        MAX_CREDENTIAL_VALIDITY,
+                    )
+                    .await?
+            }
+            None => {
+                credentials_creation
+                    .issue_credential(
+                        &self.issuer,
+                        subject,
+                        subject_attributes,
+                        MAX_CREDENTIAL_VALIDITY,
+                    )
+                    .await?
+            }
+        };
 
         Ok(Some(credential))
     }
@@ -1,6 +1,7 @@
 use tracing::{debug, warn};
 
 use ockam_core::compat::collections::BTreeMap;
+use ockam_core::compat::string::String;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
@@ -8,23 +9,32 @@ use ockam_vault::VaultForVerifyingSignatures;
 
 use crate::identities::AttributesEntry;
 use crate::models::{
-    CredentialAndPurposeKey, CredentialData, Identifier, PurposePublicKey, VersionedData,
+    AttributeCommitment, AttributeDisclosure, Credential, CredentialAndPurposeKey, CredentialData,
+    CredentialHash, CredentialSignature, CredentialVerifyingKey, DelegationAndPurposeKey,
+    DelegationData, Identifier, PurposeKeyAttestation, PurposeKeyAttestationData, PurposePublicKey,
+    RevocationListAndPurposeKey, RevocationListData, VersionedData,
 };
 use crate::utils::now;
 use crate::{
-    CredentialAndPurposeKeyData, IdentityAttributesRepository, IdentityError,
-    PurposeKeyVerification, TimestampInSeconds,
+    CredentialAndPurposeKeyData, IdentitiesCreation, IdentityAttributesRepository, IdentityError,
+    PurposeKeyVerification, RevocationsRepository, TimestampInSeconds,
 };
 
 /// We allow Credentials to be created in the future related to this machine's time due to
 /// possible time dyssynchronization
 const MAX_ALLOWED_TIME_DRIFT: TimestampInSeconds = TimestampInSeconds(5);
 
+/// Maximum number of [`crate::models::Delegation`]s between a trusted Authority and the issuer
+/// of a Credential
+pub const MAX_DELEGATION_CHAIN_LENGTH: usize = 4;
+
 /// Service for managing [`Credential`]s
 pub struct CredentialsVerification {
     purpose_keys_verification: Arc<PurposeKeyVerification>,
     verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
+    identities_creation: Arc<IdentitiesCreation>,
     identities_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+    revocations_repository: Arc<dyn RevocationsRepository>,
 }
 
 impl CredentialsVerification {
@@ -32,12 +42,16 @@ impl CredentialsVerification {
     pub fn new(
         purpose_keys_verification: Arc<PurposeKeyVerification>,
         verifying_vault: Arc<dyn VaultForVerifyingSignatures>,
+        identities_creation: Arc<IdentitiesCreation>,
         identities_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+        revocations_repository: Arc<dyn RevocationsRepository>,
     ) -> Self {
         Self {
             purpose_keys_verification,
             verifying_vault,
+            identities_creation,
             identities_attributes_repository,
+            revocations_repository,
         }
     }
 }
@@ -51,59 +65,47 @@ impl CredentialsVerification {
         authorities: &[Identifier],
         credential_and_purpose_key: &CredentialAndPurposeKey,
     ) -> Result<CredentialAndPurposeKeyData> {
-        debug!("verify purpose key attestation");
-        let purpose_key_data = self
-            .purpose_keys_verification
-            .verify_purpose_key_attestation(
-                None,
-                &credential_and_purpose_key.purpose_key_attestation,
+        let (delegator, delegations) = self
+            .verify_delegations(
+                authorities,
+                credential_and_purpose_key
+                    .delegations
+                    .as_deref()
+                    .unwrap_or_default(),
             )
             .await?;
-
-        debug!("verify issuer");
-        if !authorities.contains(&purpose_key_data.subject) {
-            warn!(
-                "unknown authority on a credential: {}. Accepted authorities: {:?}",
-                purpose_key_data.subject, authorities
-            );
-            return Err(IdentityError::UnknownAuthority.into());
-        }
-
-        debug!("verify purpose key type");
-        let public_key = match purpose_key_data.public_key.clone() {
-            PurposePublicKey::SecureChannelStatic(_) => {
-                return Err(IdentityError::InvalidKeyType.into());
-            }
-
-            PurposePublicKey::CredentialSigning(public_key) => public_key,
+        let issuers = match delegations.last() {
+            Some(delegation) => vec![delegation.delegate.clone()],
+            None => authorities.to_vec(),
         };
 
-        debug!("verify signature");
-        let public_key = public_key.into();
-        let versioned_data_hash = self
-            .verifying_vault
-            .sha256(&credential_and_purpose_key.credential.data)
+        let purpose_key_data = self
+            .verify_authority_purpose_key(
+                &issuers,
+                &credential_and_purpose_key.purpose_key_attestation,
+            )
             .await?;
 
-        let signature = credential_and_purpose_key
-            .credential
-            .signature
-            .clone()
-            .into();
-
-        if !self
-            .verifying_vault
-            .verify_signature(&public_key, &versioned_data_hash.0, &signature)
+        debug!("verify signature");
+        let credential_hash = self
+            .verify_signature(
+                &purpose_key_data,
+                &credential_and_purpose_key.credential.data,
+                &credential_and_purpose_key.credential.signature,
+            )
             .await?
-        {
-            return Err(IdentityError::CredentialVerificationFailed.into());
-        }
+            .ok_or(IdentityError::CredentialVerificationFailed)?;
 
         let versioned_data: VersionedData =
             minicbor::decode(&credential_and_purpose_key.credential.data)?;
 
         let credential_data = CredentialData::get_data(&versioned_data)?;
 
+        debug!("verify disclosed attributes");
+        let credential_data = self
+            .add_disclosed_attributes(credential_data, &credential_and_purpose_key.disclosures)
+            .await?;
+
         debug!(
             "verify subject {:?}. Expected {:?}",
             credential_data.subject, expected_subject
@@ -124,6 +126,16 @@ impl CredentialsVerification {
             return Err(IdentityError::CredentialVerificationFailed.into());
         }
 
+        debug!("verify revocations");
+        // A delegated credential can be revoked by the authority and by any delegate of the chain
+        let authority = delegator.unwrap_or_else(|| purpose_key_data.subject.clone());
+        self.check_not_revoked(&authority, &credential_data, &credential_hash)
+            .await?;
+        for delegation in delegations.iter() {
+            self.check_not_revoked(&delegation.delegate, &credential_data, &credential_hash)
+                .await?;
+        }
+
         debug!("verify dates");
         if credential_data.created_at < purpose_key_data.created_at {
             // Credential validity time range should be inside the purpose key validity time range
@@ -163,16 +175,151 @@ impl CredentialsVerification {
             //     In such cases some limited tolerance may be introduced.
         }
 
+        if let Some(delegation) = delegations.last() {
+            debug!("verify delegated attributes");
+            if let Some(key) = credential_data
+                .subject_attributes
+                .map
+                .keys()
+                .find(|key| !delegation.allows_attribute(key))
+            {
+                warn!(
+                    "the attribute {} is not part of the namespaces delegated to {}",
+                    String::from_utf8_lossy(key),
+                    delegation.delegate
+                );
+                return Err(IdentityError::CredentialVerificationFailed.into());
+            }
+            if delegations
+                .iter()
+                .any(|d| credential_data.expires_at > d.expires_at)
+            {
+                // A delegated credential can't outlive its delegations
+                return Err(IdentityError::CredentialVerificationFailed.into());
+            }
+        }
+
         // FIXME: Verify if given authority is allowed to issue credentials with given Schema <-- Should be handled somewhere in the TrustContext
         // FIXME: Verify if Schema aligns with Attributes <-- Should be handled somewhere in the TrustContext
 
         Ok(CredentialAndPurposeKeyData {
             credential_data,
             purpose_key_data,
+            authority,
         })
     }
 
-    /// Receive someone's [`Credential`]: verify and put attributes from it to the storage
+    /// Return the [`CredentialHash`] identifying a [`Credential`] in a revocation list
+    pub async fn credential_hash(&self, credential: &Credential) -> Result<CredentialHash> {
+        Ok(CredentialHash(
+            self.verifying_vault.sha256(&credential.data).await?.0,
+        ))
+    }
+
+    /// Verify a [`super::super::models::RevocationList`] signed by one of the given authorities
+    pub async fn verify_revocation_list(
+        &self,
+        authorities: &[Identifier],
+        revocation_list_and_purpose_key: &RevocationListAndPurposeKey,
+    ) -> Result<(Identifier, RevocationListData)> {
+        let purpose_key_data = self
+            .verify_authority_purpose_key(
+                authorities,
+                &revocation_list_and_purpose_key.purpose_key_attestation,
+            )
+            .await?;
+
+        debug!("verify revocation list signature");
+        let revocation_list = &revocation_list_and_purpose_key.revocation_list;
+        if self
+            .verify_signature(
+                &purpose_key_data,
+                &revocation_list.data,
+                &revocation_list.signature,
+            )
+            .await?
+            .is_none()
+        {
+            return Err(IdentityError::RevocationListVerificationFailed.into());
+        }
+
+        let revocation_list_data = revocation_list.get_revocation_list_data()?;
+
+        let now = now()?;
+        if revocation_list_data.created_at > now
+            && revocation_list_data.created_at - now > MAX_ALLOWED_TIME_DRIFT
+        {
+            // A revocation list can't be created in the future
+            return Err(IdentityError::RevocationListVerificationFailed.into());
+        }
+
+        Ok((purpose_key_data.subject, revocation_list_data))
+    }
+
+    /// Receive a [`super::super::models::RevocationList`]: verify it and store it if it is more
+    /// recent than the one currently known for its authority.
+    /// The attributes attested by that authority for the revoked subjects, or with a revoked
+    /// credential, are removed so that messages coming from already established secure channels
+    /// are rejected as well.
+    /// Return false if the revocation list was not stored because it is outdated
+    pub async fn receive_revocation_list(
+        &self,
+        authorities: &[Identifier],
+        revocation_list_and_purpose_key: &RevocationListAndPurposeKey,
+    ) -> Result<bool> {
+        let (issuer, revocation_list_data) = self
+            .verify_revocation_list(authorities, revocation_list_and_purpose_key)
+            .await?;
+
+        if let Some(current) = self
+            .revocations_repository
+            .get_revocation_list(&issuer)
+            .await?
+        {
+            let current_data = current.revocation_list.get_revocation_list_data()?;
+            if current_data.created_at > revocation_list_data.created_at {
+                debug!(
+                    "ignoring a revocation list from {} older than the current one",
+                    issuer
+                );
+                return Ok(false);
+            }
+        }
+
+        self.revocations_repository
+            .store_revocation_list(
+                &issuer,
+                revocation_list_and_purpose_key,
+                &revocation_list_data,
+            )
+            .await?;
+
+        for subject in &revocation_list_data.revoked_subjects {
+            if let Some(attributes) = self
+                .identities_attributes_repository
+                .get_attributes(subject)
+                .await?
+            {
+                if attributes.attested_by().as_ref() == Some(&issuer) {
+                    debug!("removing the revoked attributes of {}", subject);
+                    self.identities_attributes_repository
+                        .delete(subject)
+                        .await?;
+                }
+            }
+        }
+        for credential_hash in &revocation_list_data.revoked_credentials {
+            self.identities_attributes_repository
+                .delete_by_credential_hash(&issuer, credential_hash)
+                .await?;
+        }
+
+        Ok(true)
+    }
+
+    /// Receive someone's [`Credential`]: verify and put attributes from it to the storage.
+    /// The attributes of a delegated credential are attested by the authority at the start of its
+    /// chain of delegations, so that they are removed by the revocation lists of that authority
     pub async fn receive_presented_credential(
         &self,
         subject: &Identifier,
@@ -187,6 +334,9 @@ impl CredentialsVerification {
             )
             .await?;
 
+        let credential_hash = self
+            .credential_hash(&credential_and_purpose_key_attestation.credential)
+            .await?;
         let map = credential_data.credential_data.subject_attributes.map;
         let map: BTreeMap<_, _> = map
             .into_iter()
@@ -200,11 +350,246 @@ impl CredentialsVerification {
                     map,
                     now()?,
                     Some(credential_data.credential_data.expires_at),
-                    Some(credential_data.purpose_key_data.subject),
-                ),
+                    Some(credential_data.authority),
+                )
+                .with_credential_hash(credential_hash),
             )
             .await?;
 
         Ok(())
     }
 }
+
+/// Private functions
+impl CredentialsVerification {
+    /// Verify that a [`PurposeKeyAttestation`] is a credential signing key of a known authority
+    async fn verify_authority_purpose_key(
+        &self,
+        authorities: &[Identifier],
+        purpose_key_attestation: &PurposeKeyAttestation,
+    ) -> Result<PurposeKeyAttestationData> {
+        debug!("verify purpose key attestation");
+        let purpose_key_data = self
+            .purpose_keys_verification
+            .verify_purpose_key_attestation(None, purpose_key_attestation)
+            .await?;
+
+        debug!("verify issuer");
+        if !authorities.contains(&purpose_key_data.subject) {
+            warn!(
+                "unknown authority on a credential: {}. Accepted authorities: {:?}",
+                purpose_key_data.subject, authorities
+            );
+            return Err(IdentityError::UnknownAuthority.into());
+        }
+
+        Ok(purpose_key_data)
+    }
+
+    /// Verify a chain of [`Delegation`]s, starting with a delegation signed by one of the
+    /// authorities. Each delegation must be signed by the delegate of the previous one and
+    /// can only restrict the namespaces of the previous delegation.
+    /// The change history of each delegate is imported, so that the next delegation or the
+    /// credential signed by that delegate can be verified.
+    /// Return the authority which signed the first delegation, if there is one, and the data of
+    /// the verified delegations
+    async fn verify_delegations(
+        &self,
+        authorities: &[Identifier],
+        delegations: &[DelegationAndPurposeKey],
+    ) -> Result<(Option<Identifier>, Vec<DelegationData>)> {
+        if delegations.len() > MAX_DELEGATION_CHAIN_LENGTH {
+            warn!(
+                "the chain of delegations is too long: {}",
+                delegations.len()
+            );
+            return Err(IdentityError::DelegationVerificationFailed.into());
+        }
+
+        let now = now()?;
+        let mut authority = None;
+        let mut delegations_data: Vec<DelegationData> = Vec::with_capacity(delegations.len());
+        for delegation in delegations {
+            let delegators = match delegations_data.last() {
+                Some(previous) => vec![previous.delegate.clone()],
+                None => authorities.to_vec(),
+            };
+            debug!("verify delegation issuer");
+            let purpose_key_data = self
+                .verify_authority_purpose_key(&delegators, &delegation.purpose_key_attestation)
+                .await?;
+
+            debug!("verify delegation signature");
+            if self
+                .verify_signature(
+                    &purpose_key_data,
+                    &delegation.delegation.data,
+                    &delegation.delegation.signature,
+                )
+                .await?
+                .is_none()
+            {
+                return Err(IdentityError::DelegationVerificationFailed.into());
+            }
+
+            let delegation_data = delegation.delegation.get_delegation_data()?;
+
+            if let Some(previous) = delegations_data.last() {
+                if !previous.includes_namespaces(&delegation_data.attribute_namespaces) {
+                    warn!(
+                        "{} delegated namespaces which were not delegated to it",
+                        previous.delegate
+                    );
+                    return Err(IdentityError::DelegationVerificationFailed.into());
+                }
+            }
+
+            if delegation_data.created_at < purpose_key_data.created_at
+                || delegation_data.expires_at > purpose_key_data.expires_at
+            {
+                // Delegation validity time range should be inside the purpose key validity time range
+                return Err(IdentityError::DelegationVerificationFailed.into());
+            }
+
+            if delegation_data.created_at > now
+                && delegation_data.created_at - now > MAX_ALLOWED_TIME_DRIFT
+            {
+                // Delegation can't be created in the future
+                return Err(IdentityError::DelegationVerificationFailed.into());
+            }
+
+            if delegation_data.expires_at < now {
+                // Delegation expired
+                return Err(IdentityError::DelegationVerificationFailed.into());
+            }
+
+            if self
+                .revocations_repository
+                .is_subject_revoked(&purpose_key_data.subject, &delegation_data.delegate)
+                .await?
+            {
+                warn!(
+                    "the delegate {} has been revoked by {}",
+                    delegation_data.delegate, purpose_key_data.subject
+                );
+                return Err(IdentityError::CredentialRevoked.into());
+            }
+
+            debug!("import the delegate {}", delegation_data.delegate);
+            self.identities_creation
+                .import_from_change_history(
+                    Some(&delegation_data.delegate),
+                    delegation.delegate_change_history.clone(),
+                )
+                .await?;
+
+            if authority.is_none() {
+                authority = Some(purpose_key_data.subject);
+            }
+            delegations_data.push(delegation_data);
+        }
+        Ok((authority, delegations_data))
+    }
+
+    /// Check that the disclosed attributes of a selective-disclosure credential match the
+    /// signed commitments and add them to the attributes of the credential
+    async fn add_disclosed_attributes(
+        &self,
+        mut credential_data: CredentialData,
+        disclosures: &Option<Vec<AttributeDisclosure>>,
+    ) -> Result<CredentialData> {
+        let disclosures = match disclosures {
+            Some(disclosures) if !disclosures.is_empty() => disclosures,
+            _ => return Ok(credential_data),
+        };
+        let commitments = match &credential_data.subject_attribute_commitments {
+            Some(commitments) => commitments.clone(),
+            // Nothing can be disclosed if the credential doesn't commit to any attribute
+            None => return Err(IdentityError::CredentialVerificationFailed.into()),
+        };
+
+        for disclosure in disclosures {
+            let commitment = AttributeCommitment(
+                self.verifying_vault
+                    .sha256(&minicbor::to_vec(disclosure)?)
+                    .await?
+                    .0,
+            );
+            if !commitments.contains(&commitment) {
+                warn!("a disclosed attribute doesn't match the credential commitments");
+                return Err(IdentityError::CredentialVerificationFailed.into());
+            }
+
+            if credential_data
+                .subject_attributes
+                .map
+                .insert(disclosure.key.clone(), disclosure.value.clone())
+                .is_some()
+            {
+                // The same attribute can't be disclosed twice
+                return Err(IdentityError::CredentialVerificationFailed.into());
+            }
+        }
+        Ok(credential_data)
+    }
+
+    /// Verify the signature of some data with a credential signing key.
+    /// Return the SHA256 hash of the data if the signature is valid
+    async fn verify_signature(
+        &self,
+        purpose_key_data: &PurposeKeyAttestationData,
+        data: &[u8],
+        signature: &CredentialSignature,
+    ) -> Result<Option<CredentialHash>> {
+        debug!("verify purpose key type");
+        let public_key: CredentialVerifyingKey = match purpose_key_data.public_key.clone() {
+            PurposePublicKey::SecureChannelStatic(_) => {
+                return Err(IdentityError::InvalidKeyType.into());
+            }
+            PurposePublicKey::CredentialSigning(public_key) => public_key,
+        };
+
+        let data_hash = self.verifying_vault.sha256(data).await?;
+        if self
+            .verifying_vault
+            .verify_signature(&public_key.into(), &data_hash.0, &signature.clone().into())
+            .await?
+        {
+            Ok(Some(CredentialHash(data_hash.0)))
+        } else {
+            Ok(None)
+        }
+    }
+
+    /// Check that neither the credential nor its subject have been revoked by the issuer
+    async fn check_not_revoked(
+        &self,
+        issuer: &Identifier,
+        credential_data: &CredentialData,
+        credential_hash: &CredentialHash,
+    ) -> Result<()> {
+        if self
+            .revocations_repository
+            .is_credential_revoked(issuer, credential_hash)
+            .await?
+        {
+            warn!(
+                "the credential {} has been revoked by {}",
+                credential_hash, issuer
+            );
+            return Err(IdentityError::CredentialRevoked.into());
+        }
+
+        if let Some(subject) = &credential_data.subject {
+            if self
+                .revocations_repository
+                .is_subject_revoked(issuer, subject)
+                .await?
+            {
+                warn!("the subject {} has been revoked by {}", subject, issuer);
+                return Err(IdentityError::CredentialRevoked.into());
+            }
+        }
+        Ok(())
+    }
+}
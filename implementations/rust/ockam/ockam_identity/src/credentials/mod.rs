@@ -8,6 +8,8 @@ mod credentials_server;
 mod credentials_server_worker;
 mod credentials_verification;
 mod one_time_code;
+mod revocation_list_refresher;
+mod storage;
 mod trust_context;
 
 pub use authority_service::*;
@@ -18,4 +20,6 @@ pub use credentials_retriever::*;
 pub use credentials_server::*;
 pub use credentials_verification::*;
 pub use one_time_code::*;
+pub use revocation_list_refresher::*;
+pub use storage::*;
 pub use trust_context::*;
@@ -0,0 +1,98 @@
+use core::time::Duration;
+use tracing::{debug, warn};
+
+use ockam_core::api::Request;
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::string::String;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, Processor, Result, Route};
+use ockam_node::{Context, DEFAULT_TIMEOUT};
+
+use crate::models::{Identifier, RevocationListAndPurposeKey};
+use crate::{SecureChannels, SecureClient};
+
+/// Default interval between two retrievals of an authority revocation list
+pub const DEFAULT_REVOCATION_LIST_REFRESH_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Processor periodically retrieving the revocation list published by an authority.
+///
+/// Every retrieved list is verified and stored locally with
+/// [`super::CredentialsVerification::receive_revocation_list`] so that revoked credentials are
+/// rejected even if the authority can't be reached when they are presented.
+pub struct RevocationListRefresher {
+    secure_channels: Arc<SecureChannels>,
+    caller: Identifier,
+    authority: Identifier,
+    authority_route: Route,
+    service_address: String,
+    refresh_interval: Duration,
+}
+
+impl RevocationListRefresher {
+    /// Create a new revocation list refresher
+    pub fn new(
+        secure_channels: Arc<SecureChannels>,
+        caller: Identifier,
+        authority: Identifier,
+        authority_route: Route,
+        service_address: impl Into<String>,
+        refresh_interval: Duration,
+    ) -> Self {
+        Self {
+            secure_channels,
+            caller,
+            authority,
+            authority_route,
+            service_address: service_address.into(),
+            refresh_interval,
+        }
+    }
+
+    /// Retrieve the current revocation list of the authority and store it if it is valid.
+    /// Return true if the locally stored revocation list was updated
+    pub async fn refresh(&self, ctx: &Context) -> Result<bool> {
+        let resolved_route = ctx
+            .resolve_transport_route(self.authority_route.clone())
+            .await?;
+        let client = SecureClient::new(
+            self.secure_channels.clone(),
+            resolved_route,
+            &self.authority,
+            &self.caller,
+            DEFAULT_TIMEOUT,
+        );
+
+        let revocation_list: RevocationListAndPurposeKey = client
+            .ask(ctx, &self.service_address, Request::get("/"))
+            .await?
+            .success()?;
+
+        self.secure_channels
+            .identities()
+            .credentials()
+            .credentials_verification()
+            .receive_revocation_list(&[self.authority.clone()], &revocation_list)
+            .await
+    }
+}
+
+#[async_trait]
+impl Processor for RevocationListRefresher {
+    type Context = Context;
+
+    async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
+        match self.refresh(ctx).await {
+            Ok(updated) => debug!(
+                "retrieved the revocation list of {} (updated: {updated})",
+                self.authority
+            ),
+            // The previously stored revocation list stays in use until the authority can be reached
+            Err(e) => warn!(
+                "cannot retrieve the revocation list of {}: {e}",
+                self.authority
+            ),
+        }
+        ctx.sleep(self.refresh_interval).await;
+        Ok(true)
+    }
+}
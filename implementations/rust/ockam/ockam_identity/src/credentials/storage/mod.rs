@@ -0,0 +1,8 @@
+pub use revocations_repository::*;
+#[cfg(feature = "storage")]
+pub use revocations_repository_sql::*;
+
+mod revocations_repository;
+
+#[cfg(feature = "storage")]
+mod revocations_repository_sql;
@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+
+use crate::models::{CredentialHash, Identifier, RevocationListAndPurposeKey, RevocationListData};
+
+/// This trait supports the persistence of credential revocations.
+///
+/// On an authority node it stores the credentials and subjects revoked by the authority.
+/// On other nodes it stores the latest verified [`RevocationListAndPurposeKey`] received from each
+/// authority, so that revoked credentials can be rejected without contacting the authority.
+#[async_trait]
+pub trait RevocationsRepository: Send + Sync + 'static {
+    /// Revoke a credential issued by the given authority
+    async fn revoke_credential(
+        &self,
+        issuer: &Identifier,
+        credential_hash: &CredentialHash,
+    ) -> Result<()>;
+
+    /// Revoke all the credentials issued by the given authority to a subject
+    async fn revoke_subject(&self, issuer: &Identifier, subject: &Identifier) -> Result<()>;
+
+    /// Return the hashes of all the credentials revoked by the given authority
+    async fn get_revoked_credentials(&self, issuer: &Identifier) -> Result<Vec<CredentialHash>>;
+
+    /// Return all the subjects revoked by the given authority
+    async fn get_revoked_subjects(&self, issuer: &Identifier) -> Result<Vec<Identifier>>;
+
+    /// Return true if the credential has been revoked by the given authority
+    async fn is_credential_revoked(
+        &self,
+        issuer: &Identifier,
+        credential_hash: &CredentialHash,
+    ) -> Result<bool>;
+
+    /// Return true if the subject has been revoked by the given authority
+    async fn is_subject_revoked(&self, issuer: &Identifier, subject: &Identifier) -> Result<bool>;
+
+    /// Store a verified revocation list for the given authority.
+    /// The revocations contained in the list replace any previous revocations for that authority
+    async fn store_revocation_list(
+        &self,
+        issuer: &Identifier,
+        revocation_list: &RevocationListAndPurposeKey,
+        revocation_list_data: &RevocationListData,
+    ) -> Result<()>;
+
+    /// Return the latest revocation list stored for the given authority
+    async fn get_revocation_list(
+        &self,
+        issuer: &Identifier,
+    ) -> Result<Option<RevocationListAndPurposeKey>>;
+}
@@ -0,0 +1,315 @@
+use core::str::FromStr;
+
+use sqlx::*;
+use tracing::debug;
+
+use ockam_core::async_trait;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+use ockam_node::database::{FromSqlxError, SqlxDatabase, SqlxType, ToSqlxType, ToVoid};
+
+use crate::models::{CredentialHash, Identifier, RevocationListAndPurposeKey, RevocationListData};
+use crate::RevocationsRepository;
+
+/// Implementation of the `RevocationsRepository` trait based on an underlying database
+/// using sqlx as its API, and Sqlite or Postgres as its driver
+#[derive(Clone)]
+pub struct RevocationsSqlxDatabase {
+    database: Arc<SqlxDatabase>,
+}
+
+impl RevocationsSqlxDatabase {
+    /// Create a new database
+    pub fn new(database: Arc<SqlxDatabase>) -> Self {
+        debug!("create a repository for revocations");
+        Self { database }
+    }
+
+    /// Create a new in-memory database
+    pub async fn create() -> Result<Arc<Self>> {
+        Ok(Arc::new(Self::new(
+            SqlxDatabase::in_memory("revocations").await?,
+        )))
+    }
+}
+
+#[async_trait]
+impl RevocationsRepository for RevocationsSqlxDatabase {
+    async fn revoke_credential(
+        &self,
+        issuer: &Identifier,
+        credential_hash: &CredentialHash,
+    ) -> Result<()> {
+        let query = query("INSERT INTO revoked_credential VALUES ($1, $2) ON CONFLICT DO NOTHING")
+            .bind(issuer.to_sql())
+            .bind(credential_hash.to_sql());
+        query.execute(&self.database.pool).await.void()
+    }
+
+    async fn revoke_subject(&self, issuer: &Identifier, subject: &Identifier) -> Result<()> {
+        let query = query("INSERT INTO revoked_subject VALUES ($1, $2) ON CONFLICT DO NOTHING")
+            .bind(issuer.to_sql())
+            .bind(subject.to_sql());
+        query.execute(&self.database.pool).await.void()
+    }
+
+    async fn get_revoked_credentials(&self, issuer: &Identifier) -> Result<Vec<CredentialHash>> {
+        let query = query_as("SELECT credential_hash FROM revoked_credential WHERE issuer=$1")
+            .bind(issuer.to_sql());
+        let rows: Vec<RevokedCredentialRow> =
+            query.fetch_all(&self.database.pool).await.into_core()?;
+        rows.iter().map(|r| r.credential_hash()).collect()
+    }
+
+    async fn get_revoked_subjects(&self, issuer: &Identifier) -> Result<Vec<Identifier>> {
+        let query =
+            query_as("SELECT subject FROM revoked_subject WHERE issuer=$1").bind(issuer.to_sql());
+        let rows: Vec<RevokedSubjectRow> =
+            query.fetch_all(&self.database.pool).await.into_core()?;
+        rows.iter().map(|r| r.subject()).collect()
+    }
+
+    async fn is_credential_revoked(
+        &self,
+        issuer: &Identifier,
+        credential_hash: &CredentialHash,
+    ) -> Result<bool> {
+        let query = query_as(
+            "SELECT credential_hash FROM revoked_credential WHERE issuer=$1 AND credential_hash=$2",
+        )
+        .bind(issuer.to_sql())
+        .bind(credential_hash.to_sql());
+        let row: Option<RevokedCredentialRow> = query
+            .fetch_optional(&self.database.pool)
+            .await
+            .into_core()?;
+        Ok(row.is_some())
+    }
+
+    async fn is_subject_revoked(&self, issuer: &Identifier, subject: &Identifier) -> Result<bool> {
+        let query = query_as("SELECT subject FROM revoked_subject WHERE issuer=$1 AND subject=$2")
+            .bind(issuer.to_sql())
+            .bind(subject.to_sql());
+        let row: Option<RevokedSubjectRow> = query
+            .fetch_optional(&self.database.pool)
+            .await
+            .into_core()?;
+        Ok(row.is_some())
+    }
+
+    async fn store_revocation_list(
+        &self,
+        issuer: &Identifier,
+        revocation_list: &RevocationListAndPurposeKey,
+        revocation_list_data: &RevocationListData,
+    ) -> Result<()> {
+        let mut transaction = self.database.begin().await.into_core()?;
+
+        query("DELETE FROM revoked_credential WHERE issuer=$1")
+            .bind(issuer.to_sql())
+            .execute(&mut *transaction)
+            .await
+            .void()?;
+        query("DELETE FROM revoked_subject WHERE issuer=$1")
+            .bind(issuer.to_sql())
+            .execute(&mut *transaction)
+            .await
+            .void()?;
+
+        for credential_hash in revocation_list_data.revoked_credentials.iter() {
+            query("INSERT INTO revoked_credential VALUES ($1, $2) ON CONFLICT DO NOTHING")
+                .bind(issuer.to_sql())
+                .bind(credential_hash.to_sql())
+                .execute(&mut *transaction)
+                .await
+                .void()?;
+        }
+        for subject in revocation_list_data.revoked_subjects.iter() {
+            query("INSERT INTO revoked_subject VALUES ($1, $2) ON CONFLICT DO NOTHING")
+                .bind(issuer.to_sql())
+                .bind(subject.to_sql())
+                .execute(&mut *transaction)
+                .await
+                .void()?;
+        }
+
+        query("INSERT INTO revocation_list VALUES ($1, $2, $3) ON CONFLICT (issuer) DO UPDATE SET created_at = $2, revocation_list = $3")
+            .bind(issuer.to_sql())
+            .bind(revocation_list_data.created_at.0.to_sql())
+            .bind(minicbor::to_vec(revocation_list)?.to_sql())
+            .execute(&mut *transaction)
+            .await
+            .void()?;
+
+        transaction.commit().await.void()
+    }
+
+    async fn get_revocation_list(
+        &self,
+        issuer: &Identifier,
+    ) -> Result<Option<RevocationListAndPurposeKey>> {
+        let query = query_as("SELECT revocation_list FROM revocation_list WHERE issuer=$1")
+            .bind(issuer.to_sql());
+        let row: Option<RevocationListRow> = query
+            .fetch_optional(&self.database.pool)
+            .await
+            .into_core()?;
+        row.map(|r| r.revocation_list()).transpose()
+    }
+}
+
+// Database serialization / deserialization
+
+impl ToSqlxType for CredentialHash {
+    fn to_sql(&self) -> SqlxType {
+        self.to_string().to_sql()
+    }
+}
+
+// Low-level representation of table rows
+#[derive(FromRow)]
+struct RevokedCredentialRow {
+    credential_hash: String,
+}
+
+impl RevokedCredentialRow {
+    fn credential_hash(&self) -> Result<CredentialHash> {
+        CredentialHash::from_str(&self.credential_hash)
+    }
+}
+
+#[derive(FromRow)]
+struct RevokedSubjectRow {
+    subject: String,
+}
+
+impl RevokedSubjectRow {
+    fn subject(&self) -> Result<Identifier> {
+        Identifier::from_str(&self.subject)
+    }
+}
+
+#[derive(FromRow)]
+struct RevocationListRow {
+    revocation_list: Vec<u8>,
+}
+
+impl RevocationListRow {
+    fn revocation_list(&self) -> Result<RevocationListAndPurposeKey> {
+        minicbor::decode(self.revocation_list.as_slice()).map_err(SqlxDatabase::map_decode_err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::identities;
+    use crate::models::{CredentialSignature, PurposeKeyAttestation, RevocationList};
+    use crate::TimestampInSeconds;
+    use ockam_node::database::with_dbs;
+    use ockam_vault::EdDSACurve25519Signature;
+
+    #[tokio::test]
+    async fn test_revocations_repository() -> Result<()> {
+        with_dbs(|db| async move {
+            let repository = create_repository(db);
+            let issuer = create_identity().await?;
+            let subject1 = create_identity().await?;
+            let subject2 = create_identity().await?;
+            let credential_hash = CredentialHash([1; 32]);
+
+            // revoke a credential and a subject
+            repository
+                .revoke_credential(&issuer, &credential_hash)
+                .await?;
+            repository.revoke_subject(&issuer, &subject1).await?;
+
+            // revoking twice is not an error
+            repository.revoke_subject(&issuer, &subject1).await?;
+
+            assert!(
+                repository
+                    .is_credential_revoked(&issuer, &credential_hash)
+                    .await?
+            );
+            assert!(repository.is_subject_revoked(&issuer, &subject1).await?);
+            assert!(!repository.is_subject_revoked(&issuer, &subject2).await?);
+
+            // revocations are scoped by issuer
+            assert!(!repository.is_subject_revoked(&subject2, &subject1).await?);
+
+            assert_eq!(
+                repository.get_revoked_credentials(&issuer).await?,
+                vec![credential_hash.clone()]
+            );
+            assert_eq!(
+                repository.get_revoked_subjects(&issuer).await?,
+                vec![subject1.clone()]
+            );
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_store_revocation_list() -> Result<()> {
+        with_dbs(|db| async move {
+            let repository = create_repository(db);
+            let issuer = create_identity().await?;
+            let subject1 = create_identity().await?;
+            let subject2 = create_identity().await?;
+
+            repository.revoke_subject(&issuer, &subject1).await?;
+
+            // a stored revocation list replaces the previous revocations
+            let revocation_list_data = RevocationListData {
+                revoked_credentials: vec![CredentialHash([2; 32])],
+                revoked_subjects: vec![subject2.clone()],
+                created_at: TimestampInSeconds(1000),
+            };
+            let revocation_list = RevocationListAndPurposeKey {
+                revocation_list: RevocationList {
+                    data: minicbor::to_vec(&revocation_list_data)?,
+                    signature: CredentialSignature::EdDSACurve25519(EdDSACurve25519Signature(
+                        [0; 64],
+                    )),
+                },
+                purpose_key_attestation: PurposeKeyAttestation {
+                    data: vec![],
+                    signature: crate::models::PurposeKeyAttestationSignature::EdDSACurve25519(
+                        EdDSACurve25519Signature([0; 64]),
+                    ),
+                },
+            };
+            repository
+                .store_revocation_list(&issuer, &revocation_list, &revocation_list_data)
+                .await?;
+
+            assert!(!repository.is_subject_revoked(&issuer, &subject1).await?);
+            assert!(repository.is_subject_revoked(&issuer, &subject2).await?);
+            assert!(
+                repository
+                    .is_credential_revoked(&issuer, &CredentialHash([2; 32]))
+                    .await?
+            );
+            assert_eq!(
+                repository.get_revocation_list(&issuer).await?,
+                Some(revocation_list)
+            );
+            Ok(())
+        })
+        .await
+    }
+
+    /// HELPERS
+    async fn create_identity() -> Result<Identifier> {
+        let identities = identities().await?;
+        identities.identities_creation().create_identity().await
+    }
+
+    fn create_repository(db: Arc<SqlxDatabase>) -> Arc<dyn RevocationsRepository> {
+        Arc::new(RevocationsSqlxDatabase::new(db))
+    }
+}
@@ -9,6 +9,8 @@ use crate::AuthorityService;
 
 /// A trust context defines which authorities are trusted to attest to which attributes, within a context.
 /// Our first implementation assumes that there is only one authority and it is trusted to attest to all attributes within this context.
+/// That authority can delegate the issuance of some attributes to other identities, in which case the delegations
+/// are presented with the credentials and verified up to the authority of the trust context.
 #[derive(Clone)]
 pub struct TrustContext {
     /// This is the ID of the trust context; which is primarily used for ABAC policies
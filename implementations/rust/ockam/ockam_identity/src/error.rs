@@ -64,6 +64,20 @@ pub enum IdentityError {
     InvalidHex,
     /// Secret Key doesn't correspond to the Identity
     WrongSecretKey,
+    /// The Credential or its Subject was revoked by the Authority
+    CredentialRevoked,
+    /// Unknown version of the RevocationList
+    UnknownRevocationListVersion,
+    /// Invalid data_type value for RevocationList
+    InvalidRevocationListDataType,
+    /// RevocationList Verification Failed
+    RevocationListVerificationFailed,
+    /// Unknown version of the Delegation
+    UnknownDelegationVersion,
+    /// Invalid data_type value for Delegation
+    InvalidDelegationDataType,
+    /// Delegation Verification Failed
+    DelegationVerificationFailed,
 }
 
 impl ockam_core::compat::error::Error for IdentityError {}
@@ -3,21 +3,24 @@ use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
 #[cfg(feature = "storage")]
 use ockam_node::database::SqlxDatabase;
+use ockam_vault::VerifyingPublicKey;
 
 #[cfg(feature = "storage")]
 use crate::identities::storage::ChangeHistorySqlxDatabase;
 #[cfg(feature = "storage")]
 use crate::identities::storage::IdentityAttributesSqlxDatabase;
-use crate::identities::{ChangeHistoryRepository, IdentitiesKeys};
-use crate::models::ChangeHistory;
+use crate::identities::{ChangeHistoryRepository, IdentitiesKeys, IdentityBuilder};
+use crate::models::{Change, ChangeHistory, RecoverySignature};
 use crate::purpose_keys::storage::PurposeKeysRepository;
 #[cfg(feature = "storage")]
 use crate::purpose_keys::storage::PurposeKeysSqlxDatabase;
 #[cfg(feature = "storage")]
 use crate::IdentitiesBuilder;
+#[cfg(feature = "storage")]
+use crate::RevocationsSqlxDatabase;
 use crate::{
     Credentials, CredentialsServer, CredentialsServerModule, Identifier, IdentitiesCreation,
-    Identity, IdentityAttributesRepository, PurposeKeys, Vault,
+    Identity, IdentityAttributesRepository, PurposeKeys, RevocationsRepository, Vault,
 };
 
 /// This struct supports all the services related to identities
@@ -27,6 +30,7 @@ pub struct Identities {
     change_history_repository: Arc<dyn ChangeHistoryRepository>,
     identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
     purpose_keys_repository: Arc<dyn PurposeKeysRepository>,
+    revocations_repository: Arc<dyn RevocationsRepository>,
 }
 
 impl Identities {
@@ -50,6 +54,11 @@ impl Identities {
         self.purpose_keys_repository.clone()
     }
 
+    /// Return the revocations repository
+    pub fn revocations_repository(&self) -> Arc<dyn RevocationsRepository> {
+        self.revocations_repository.clone()
+    }
+
     /// Get an [`Identity`] from the repository
     pub async fn get_identity(&self, identifier: &Identifier) -> Result<Identity> {
         self.identities_creation().get_identity(identifier).await
@@ -62,6 +71,106 @@ impl Identities {
             .await
     }
 
+    /// Rotate the primary key of an [`Identity`], store its new change history and attest its
+    /// purpose keys with the new key.
+    /// All the existing purpose keys are replaced if `revoke_all_purpose_keys` is true
+    pub async fn rotate_identity_key(
+        &self,
+        identifier: &Identifier,
+        revoke_all_purpose_keys: bool,
+    ) -> Result<Identity> {
+        let mut builder = self
+            .identities_creation()
+            .rotation_builder(identifier)
+            .await?;
+        if revoke_all_purpose_keys {
+            builder = builder.with_purpose_keys_revocation();
+        }
+        self.rotate_identity_key_with_builder(identifier, builder)
+            .await
+    }
+
+    /// Rotate the primary key of an [`Identity`] with a new key committing to a set of recovery
+    /// keys. If that key is lost, `threshold` of the recovery keys can authorize a new primary key.
+    /// If the identity already has recovery keys, they must approve their replacement, see
+    /// [`Self::create_recovery_keys_change`]
+    pub async fn set_recovery_keys(
+        &self,
+        identifier: &Identifier,
+        threshold: u8,
+        recovery_keys: Vec<VerifyingPublicKey>,
+    ) -> Result<Identity> {
+        let change = self
+            .create_recovery_keys_change(identifier, threshold, recovery_keys)
+            .await?;
+        self.replace_recovery_keys(identifier, change, vec![]).await
+    }
+
+    /// Create a [`Change`] rotating the primary key of an [`Identity`] with a new key committing to
+    /// a set of recovery keys. The change is signed with the current primary key. When the identity
+    /// already has recovery keys, the change must also be signed by `threshold` of them with
+    /// [`IdentitiesKeys::sign_recovery_change`] before calling [`Self::replace_recovery_keys`]
+    pub async fn create_recovery_keys_change(
+        &self,
+        identifier: &Identifier,
+        threshold: u8,
+        recovery_keys: Vec<VerifyingPublicKey>,
+    ) -> Result<Change> {
+        let identity = self.get_identity(identifier).await?;
+        let options = self
+            .identities_creation()
+            .rotation_builder(identifier)
+            .await?
+            .with_recovery_keys(threshold, recovery_keys)
+            .build_options()
+            .await?;
+        self.identities_keys()
+            .create_rotation_change(&identity, options)
+            .await
+    }
+
+    /// Rotate the primary key of an [`Identity`] with a [`Change`] created by
+    /// [`Self::create_recovery_keys_change`], signed by the recovery keys currently in effect
+    /// if there are any, store its new change history and attest its purpose keys with the new key
+    pub async fn replace_recovery_keys(
+        &self,
+        identifier: &Identifier,
+        mut change: Change,
+        recovery_signatures: Vec<RecoverySignature>,
+    ) -> Result<Identity> {
+        if !recovery_signatures.is_empty() {
+            change.recovery_signatures = Some(recovery_signatures);
+        }
+        self.identities_creation()
+            .rotate_identity_with_change(identifier, change)
+            .await?;
+
+        self.purpose_keys()
+            .purpose_keys_creation()
+            .reissue_purpose_keys(identifier)
+            .await?;
+
+        self.get_identity(identifier).await
+    }
+
+    async fn rotate_identity_key_with_builder(
+        &self,
+        identifier: &Identifier,
+        builder: IdentityBuilder,
+    ) -> Result<Identity> {
+        let options = builder.build_options().await?;
+        self.identities_creation()
+            .rotate_identity_with_options(identifier, options)
+            .await?;
+
+        self.purpose_keys()
+            .purpose_keys_creation()
+            .reissue_purpose_keys(identifier)
+            .await?;
+
+        self.get_identity(identifier).await
+    }
+
     /// Export an [`Identity`] from the repository
     pub async fn export_identity(&self, identifier: &Identifier) -> Result<Vec<u8>> {
         self.get_identity(identifier).await?.export()
@@ -102,6 +211,7 @@ impl Identities {
             self.purpose_keys(),
             self.identities_creation().clone(),
             self.identity_attributes_repository.clone(),
+            self.revocations_repository.clone(),
         ))
     }
 
@@ -118,12 +228,14 @@ impl Identities {
         change_history_repository: Arc<dyn ChangeHistoryRepository>,
         identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
         purpose_keys_repository: Arc<dyn PurposeKeysRepository>,
+        revocations_repository: Arc<dyn RevocationsRepository>,
     ) -> Identities {
         Identities {
             vault,
             change_history_repository,
             identity_attributes_repository,
             purpose_keys_repository,
+            revocations_repository,
         }
     }
 
@@ -135,6 +247,7 @@ impl Identities {
             change_history_repository: ChangeHistorySqlxDatabase::create().await?,
             identity_attributes_repository: IdentityAttributesSqlxDatabase::create().await?,
             purpose_keys_repository: PurposeKeysSqlxDatabase::create().await?,
+            revocations_repository: RevocationsSqlxDatabase::create().await?,
         })
     }
 
@@ -148,6 +261,7 @@ impl Identities {
                 database.clone(),
             )),
             purpose_keys_repository: Arc::new(PurposeKeysSqlxDatabase::new(database.clone())),
+            revocations_repository: Arc::new(RevocationsSqlxDatabase::new(database.clone())),
         }
     }
 }
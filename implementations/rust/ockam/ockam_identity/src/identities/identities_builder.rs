@@ -7,7 +7,7 @@ use ockam_vault::storage::SecretsRepository;
 
 use crate::identities::{ChangeHistoryRepository, Identities};
 use crate::purpose_keys::storage::PurposeKeysRepository;
-use crate::{IdentityAttributesRepository, Vault};
+use crate::{IdentityAttributesRepository, RevocationsRepository, Vault};
 
 /// Builder for Identities services
 #[derive(Clone)]
@@ -16,6 +16,7 @@ pub struct IdentitiesBuilder {
     pub(crate) change_history_repository: Arc<dyn ChangeHistoryRepository>,
     pub(crate) identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
     pub(crate) purpose_keys_repository: Arc<dyn PurposeKeysRepository>,
+    pub(crate) revocations_repository: Arc<dyn RevocationsRepository>,
 }
 
 /// Return a default identities
@@ -70,6 +71,15 @@ impl IdentitiesBuilder {
         self
     }
 
+    /// Set a specific repository for credential revocations
+    pub fn with_revocations_repository(
+        mut self,
+        repository: Arc<dyn RevocationsRepository>,
+    ) -> Self {
+        self.revocations_repository = repository;
+        self
+    }
+
     /// Build identities
     pub fn build(self) -> Arc<Identities> {
         Arc::new(Identities::new(
@@ -77,6 +87,7 @@ impl IdentitiesBuilder {
             self.change_history_repository,
             self.identity_attributes_repository,
             self.purpose_keys_repository,
+            self.revocations_repository,
         ))
     }
 }
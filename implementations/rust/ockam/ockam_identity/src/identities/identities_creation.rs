@@ -1,10 +1,14 @@
 use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
-use ockam_vault::{SigningSecretKeyHandle, VaultForSigning, VaultForVerifyingSignatures};
+use ockam_vault::{
+    SigningKeyType, SigningSecretKeyHandle, VaultForSigning, VaultForVerifyingSignatures,
+    VerifyingPublicKey,
+};
 
 use crate::identities::identity_builder::IdentityBuilder;
-use crate::models::{ChangeHistory, Identifier};
+use crate::models::{Change, ChangeHistory, Identifier, RecoverySignature};
 use crate::IdentityOptions;
 use crate::{ChangeHistoryRepository, IdentitiesKeys, Identity, IdentityError};
 
@@ -93,8 +97,9 @@ impl IdentitiesCreation {
     }
 
     /// Rotate an existing `Identity` and update the stored version
+    /// The new key has the same type as the current primary key of the identity
     pub async fn rotate_identity(&self, identifier: &Identifier) -> Result<()> {
-        let builder = self.identity_builder();
+        let builder = self.rotation_builder(identifier).await?;
         let options = builder.build_options().await?;
 
         self.rotate_identity_with_options(identifier, options).await
@@ -117,6 +122,56 @@ impl IdentitiesCreation {
         Ok(())
     }
 
+    /// Rotate an existing `Identity` with a [`Change`] created by
+    /// [`IdentitiesKeys::create_rotation_change`] and update the stored version
+    pub async fn rotate_identity_with_change(
+        &self,
+        identifier: &Identifier,
+        change: Change,
+    ) -> Result<()> {
+        let identity = self.get_identity(identifier).await?;
+        let identity = self
+            .identities_keys()
+            .rotate_key_with_change(identity, change)
+            .await?;
+
+        self.update_identity(&identity).await?;
+
+        Ok(())
+    }
+
+    /// Add a recovery [`Change`], created with [`IdentitiesKeys::create_recovery_change`], to an
+    /// `Identity` whose primary key was lost and update the stored version.
+    /// The change must be signed by enough recovery keys of the identity
+    pub async fn recover_identity(
+        &self,
+        identity: Identity,
+        mut change: Change,
+        recovery_signatures: Vec<RecoverySignature>,
+    ) -> Result<Identity> {
+        change.recovery_signatures = Some(recovery_signatures);
+        let identity = identity
+            .add_change(change, self.verifying_vault.clone())
+            .await?;
+
+        self.update_identity(&identity).await?;
+        Ok(identity)
+    }
+
+    /// Get an instance of [`IdentityBuilder`] generating a key with the same type as the current
+    /// primary key of an identity, in order to rotate that key
+    pub async fn rotation_builder(&self, identifier: &Identifier) -> Result<IdentityBuilder> {
+        let key_type = match self
+            .get_identity(identifier)
+            .await?
+            .get_latest_public_key()?
+        {
+            VerifyingPublicKey::EdDSACurve25519(_) => SigningKeyType::EdDSACurve25519,
+            VerifyingPublicKey::ECDSASHA256CurveP256(_) => SigningKeyType::ECDSASHA256CurveP256,
+        };
+        Ok(self.identity_builder().with_random_key(key_type))
+    }
+
     /// Import an existing Identity from its binary format
     /// Its secret is expected to exist in the Vault (either generated there, or some Vault
     /// implementations may allow importing a secret)
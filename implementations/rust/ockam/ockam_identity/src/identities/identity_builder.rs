@@ -1,8 +1,9 @@
 use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
-use ockam_vault::{SigningKeyType, SigningSecretKeyHandle};
+use ockam_vault::{SigningKeyType, SigningSecretKeyHandle, VerifyingPublicKey};
 
-use crate::models::TimestampInSeconds;
+use crate::models::{RecoveryKeys, TimestampInSeconds};
 use crate::utils::now;
 use crate::IdentityOptions;
 use crate::{Identifier, IdentitiesCreation};
@@ -30,6 +31,7 @@ pub struct IdentityBuilder {
     revoke_all_purpose_keys: bool,
     key: Key,
     ttl: Ttl,
+    recovery_keys: Option<RecoveryKeys>,
 }
 
 impl IdentityBuilder {
@@ -40,6 +42,7 @@ impl IdentityBuilder {
             revoke_all_purpose_keys: false,
             key: Key::Generate(SigningKeyType::EdDSACurve25519),
             ttl: Ttl::CreatedNowWithTtl(DEFAULT_IDENTITY_TTL),
+            recovery_keys: None,
         }
     }
 
@@ -80,6 +83,20 @@ impl IdentityBuilder {
         self
     }
 
+    /// Commit to a set of recovery keys. A new primary key can then be authorized by
+    /// `threshold` signatures made with distinct recovery keys if the current key is lost
+    pub fn with_recovery_keys(
+        mut self,
+        threshold: u8,
+        public_keys: Vec<VerifyingPublicKey>,
+    ) -> Self {
+        self.recovery_keys = Some(RecoveryKeys {
+            threshold,
+            public_keys: public_keys.into_iter().map(|k| k.into()).collect(),
+        });
+        self
+    }
+
     /// Create the corresponding [`IdentityOptions`] object
     pub async fn build_options(self) -> Result<IdentityOptions> {
         let key = match self.key {
@@ -105,8 +122,11 @@ impl IdentityBuilder {
             } => (created_at, expires_at),
         };
 
-        let options =
+        let mut options =
             IdentityOptions::new(key, self.revoke_all_purpose_keys, created_at, expires_at);
+        if let Some(recovery_keys) = self.recovery_keys {
+            options = options.with_recovery_keys(recovery_keys);
+        }
 
         Ok(options)
     }
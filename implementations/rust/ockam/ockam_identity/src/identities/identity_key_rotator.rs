@@ -0,0 +1,195 @@
+use core::cmp::min;
+use core::time::Duration;
+use tracing::{debug, info, warn};
+
+use ockam_core::compat::boxed::Box;
+use ockam_core::compat::sync::Arc;
+use ockam_core::{async_trait, Processor, Result, Route};
+use ockam_node::{Context, DEFAULT_TIMEOUT};
+
+use crate::models::{ChangeHash, Identifier, TimestampInSeconds};
+use crate::utils::now;
+use crate::{SecureChannels, SecureClient};
+
+/// Default interval between two checks of the age of an identity key
+pub const DEFAULT_KEY_ROTATION_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Policy rotating the primary key of an identity once that key is older than a given duration.
+///
+/// The age of a key is given by the `created_at` timestamp of the change introducing it, so the
+/// schedule is kept across restarts without storing any additional state.
+#[derive(Debug, Clone)]
+pub struct IdentityKeyRotationPolicy {
+    rotation_interval: Duration,
+    check_interval: Duration,
+    revoke_all_purpose_keys: bool,
+}
+
+impl IdentityKeyRotationPolicy {
+    /// Create a policy rotating the identity key every `rotation_interval`
+    pub fn new(rotation_interval: Duration) -> Self {
+        Self {
+            rotation_interval,
+            check_interval: min(rotation_interval, DEFAULT_KEY_ROTATION_CHECK_INTERVAL),
+            revoke_all_purpose_keys: false,
+        }
+    }
+
+    /// Set the interval between two checks of the age of the identity key
+    pub fn with_check_interval(mut self, check_interval: Duration) -> Self {
+        self.check_interval = check_interval;
+        self
+    }
+
+    /// Revoke all the purpose keys when rotating the identity key, instead of attesting
+    /// them again with the new key
+    pub fn with_purpose_keys_revocation(mut self) -> Self {
+        self.revoke_all_purpose_keys = true;
+        self
+    }
+
+    /// Maximum age of an identity key
+    pub fn rotation_interval(&self) -> Duration {
+        self.rotation_interval
+    }
+
+    /// Interval between two checks of the age of the identity key
+    pub fn check_interval(&self) -> Duration {
+        self.check_interval
+    }
+
+    /// Return true if a key created at `key_created_at` must be rotated at `now`
+    pub fn is_rotation_due(
+        &self,
+        key_created_at: TimestampInSeconds,
+        now: TimestampInSeconds,
+    ) -> bool {
+        now >= key_created_at + TimestampInSeconds(self.rotation_interval.as_secs())
+    }
+}
+
+/// Processor periodically rotating the primary key of an identity according to an
+/// [`IdentityKeyRotationPolicy`].
+///
+/// When the key is rotated, the new change is stored with the
+/// [`super::ChangeHistoryRepository`] and the purpose keys of the identity are attested with
+/// the new key. If an authority is configured, a secure channel is then created to the
+/// authority so that it stores the new change history of the identity.
+pub struct IdentityKeyRotator {
+    secure_channels: Arc<SecureChannels>,
+    identifier: Identifier,
+    policy: IdentityKeyRotationPolicy,
+    authority: Option<(Identifier, Route)>,
+    published_change_hash: Option<ChangeHash>,
+}
+
+impl IdentityKeyRotator {
+    /// Create a new identity key rotator
+    pub fn new(
+        secure_channels: Arc<SecureChannels>,
+        identifier: Identifier,
+        policy: IdentityKeyRotationPolicy,
+    ) -> Self {
+        Self {
+            secure_channels,
+            identifier,
+            policy,
+            authority: None,
+            published_change_hash: None,
+        }
+    }
+
+    /// Publish the change history of the identity to an authority after each rotation
+    pub fn with_authority(mut self, authority: Identifier, authority_route: Route) -> Self {
+        self.authority = Some((authority, authority_route));
+        self
+    }
+
+    /// Rotate the identity key if it is older than the rotation interval of the policy.
+    /// Return true if the key was rotated
+    pub async fn rotate_if_due(&self) -> Result<bool> {
+        let identities = self.secure_channels.identities();
+        let identity = identities.get_identity(&self.identifier).await?;
+        let key_created_at = identity.get_latest_change()?.data().created_at;
+        if !self.policy.is_rotation_due(key_created_at, now()?) {
+            return Ok(false);
+        }
+
+        identities
+            .rotate_identity_key(&self.identifier, self.policy.revoke_all_purpose_keys)
+            .await?;
+        Ok(true)
+    }
+
+    /// Publish the latest change history of the identity to the authority if it changed since
+    /// the last publication. The identity key can also be rotated by another process using the
+    /// same identity, for example with `ockam identity rotate`.
+    /// Return true if the change history was published
+    pub async fn publish_if_changed(&mut self, ctx: &Context) -> Result<bool> {
+        let identity = self
+            .secure_channels
+            .identities()
+            .get_identity(&self.identifier)
+            .await?;
+        let latest_change_hash = identity.latest_change_hash()?.clone();
+        if self.published_change_hash.as_ref() == Some(&latest_change_hash) {
+            return Ok(false);
+        }
+
+        if let Some((authority, authority_route)) = &self.authority {
+            let resolved_route = ctx.resolve_transport_route(authority_route.clone()).await?;
+            // The secure channel handshake sends the change history of the identity, which
+            // is then stored by the authority
+            SecureClient::new(
+                self.secure_channels.clone(),
+                resolved_route,
+                authority,
+                &self.identifier,
+                DEFAULT_TIMEOUT,
+            )
+            .check_secure_channel(ctx)
+            .await?;
+        }
+        self.published_change_hash = Some(latest_change_hash);
+        Ok(self.authority.is_some())
+    }
+}
+
+#[async_trait]
+impl Processor for IdentityKeyRotator {
+    type Context = Context;
+
+    async fn initialize(&mut self, _ctx: &mut Self::Context) -> Result<()> {
+        let identity = self
+            .secure_channels
+            .identities()
+            .get_identity(&self.identifier)
+            .await?;
+        self.published_change_hash = Some(identity.latest_change_hash()?.clone());
+        Ok(())
+    }
+
+    async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
+        match self.rotate_if_due().await {
+            Ok(true) => info!(
+                "rotated the primary key of the identity {}",
+                self.identifier
+            ),
+            Ok(false) => debug!("no key rotation needed for {}", self.identifier),
+            Err(e) => warn!(
+                "cannot rotate the primary key of the identity {}: {e}",
+                self.identifier
+            ),
+        }
+
+        // The publication is retried at the next check if the authority can't be reached
+        if let Err(e) = self.publish_if_changed(ctx).await {
+            warn!(
+                "cannot publish the change history of the identity {}: {e}",
+                self.identifier
+            )
+        }
+        ctx.sleep(self.policy.check_interval).await;
+        Ok(true)
+    }
+}
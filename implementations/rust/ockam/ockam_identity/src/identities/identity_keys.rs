@@ -1,5 +1,5 @@
 use crate::identity::Identity;
-use crate::models::{Change, ChangeData, ChangeHash, ChangeHistory};
+use crate::models::{Change, ChangeData, ChangeHash, ChangeHistory, RecoverySignature};
 use crate::{IdentityError, IdentityOptions};
 
 use ockam_core::compat::sync::Arc;
@@ -16,7 +16,7 @@ pub struct IdentitiesKeys {
 
 impl IdentitiesKeys {
     pub(crate) async fn create_initial_key(&self, options: IdentityOptions) -> Result<Identity> {
-        let change = self.make_change(options, None).await?;
+        let change = self.make_change(options, None, None).await?;
         let change_history = ChangeHistory(vec![change]);
 
         let identity = Identity::import_from_change_history(
@@ -49,19 +49,37 @@ impl IdentitiesKeys {
         identity: Identity,
         options: IdentityOptions,
     ) -> Result<Identity> {
-        let last_change = match identity.changes().last() {
-            Some(last_change) => last_change,
-            None => return Err(IdentityError::EmptyIdentity.into()),
-        };
+        let change = self.create_rotation_change(&identity, options).await?;
+        self.rotate_key_with_change(identity, change).await
+    }
 
-        let last_secret_key = self.get_secret_key(&identity).await?;
+    /// Create a [`Change`] replacing the primary key of an identity with the key of the options.
+    /// The change is signed with the previous key. If it replaces the recovery keys of the
+    /// identity, it must also be signed by enough of them, see [`Self::sign_recovery_change`]
+    pub async fn create_rotation_change(
+        &self,
+        identity: &Identity,
+        options: IdentityOptions,
+    ) -> Result<Change> {
+        let last_change = identity.get_latest_change()?;
+        let last_secret_key = self.get_secret_key(identity).await?;
 
-        let change = self
-            .make_change(
-                options,
-                Some((last_change.change_hash().clone(), last_secret_key.clone())),
-            )
-            .await?;
+        self.make_change(
+            options,
+            Some(last_change.change_hash().clone()),
+            Some(last_secret_key),
+        )
+        .await
+    }
+
+    /// Rotate the Identity Key with a [`Change`] created by [`Self::create_rotation_change`]
+    /// and delete the previous key
+    pub async fn rotate_key_with_change(
+        &self,
+        identity: Identity,
+        change: Change,
+    ) -> Result<Identity> {
+        let last_secret_key = self.get_secret_key(&identity).await?;
 
         let identity = identity
             .add_change(change, self.verifying_vault.clone())
@@ -82,6 +100,37 @@ impl IdentitiesKeys {
         Ok(identity)
     }
 
+    /// Create a [`Change`] replacing the lost primary key of an identity with the key of the options.
+    /// The change is not signed with the previous key, it must be authorized by signatures made
+    /// with the recovery keys of the identity, see [`Self::sign_recovery_change`].
+    /// All the purpose keys attested by previous keys are revoked
+    pub async fn create_recovery_change(
+        &self,
+        identity: &Identity,
+        mut options: IdentityOptions,
+    ) -> Result<Change> {
+        let last_change = identity.get_latest_change()?;
+        options.revoke_all_purpose_keys = true;
+        self.make_change(options, Some(last_change.change_hash().clone()), None)
+            .await
+    }
+
+    /// Sign a recovery [`Change`], or a [`Change`] replacing the recovery keys, with the
+    /// recovery key at `key_index` in the recovery keys of the identity
+    pub async fn sign_recovery_change(
+        &self,
+        change: &Change,
+        key_index: u8,
+        recovery_key: &SigningSecretKeyHandle,
+    ) -> Result<RecoverySignature> {
+        let hash = self.verifying_vault.sha256(&change.data).await?;
+        let signature = self.identity_vault.sign(recovery_key, &hash.0).await?;
+        Ok(RecoverySignature {
+            key_index,
+            signature: signature.into(),
+        })
+    }
+
     /// Return the secret key of an identity
     pub async fn get_secret_key(&self, identity: &Identity) -> Result<SigningSecretKeyHandle> {
         if let Some(last_change) = identity.changes().last() {
@@ -100,7 +149,8 @@ impl IdentitiesKeys {
     async fn make_change(
         &self,
         identity_options: IdentityOptions,
-        previous: Option<(ChangeHash, SigningSecretKeyHandle)>,
+        previous_change: Option<ChangeHash>,
+        previous_key: Option<SigningSecretKeyHandle>,
     ) -> Result<Change> {
         let secret_key = identity_options.signing_secret_key_handle;
         let public_key = self
@@ -109,11 +159,12 @@ impl IdentitiesKeys {
             .await?;
 
         let change_data = ChangeData {
-            previous_change: previous.as_ref().map(|x| x.0.clone()),
+            previous_change,
             primary_public_key: public_key.into(),
             revoke_all_purpose_keys: identity_options.revoke_all_purpose_keys,
             created_at: identity_options.created_at,
             expires_at: identity_options.expires_at,
+            recovery_keys: identity_options.recovery_keys,
         };
 
         let change_data = minicbor::to_vec(&change_data)?;
@@ -127,8 +178,9 @@ impl IdentitiesKeys {
         let self_signature = self_signature.into();
 
         // If we have previous_key passed we should sign using it
-        // If there is no previous_key - we're creating new identity, so we just generated the key
-        let previous_signature = match previous.map(|x| x.1) {
+        // If there is no previous_key - we're creating new identity, so we just generated the key,
+        // or the previous key was lost and the change must be signed with recovery keys
+        let previous_signature = match previous_key {
             Some(previous_key) => {
                 let previous_signature = self.identity_vault.sign(&previous_key, &hash.0).await?;
 
@@ -141,6 +193,7 @@ impl IdentitiesKeys {
             data: versioned_data,
             signature: self_signature,
             previous_signature,
+            recovery_signatures: None,
         };
 
         Ok(change)
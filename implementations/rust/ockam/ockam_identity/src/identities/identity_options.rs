@@ -1,3 +1,4 @@
+use crate::models::RecoveryKeys;
 use crate::TimestampInSeconds;
 use ockam_vault::SigningSecretKeyHandle;
 
@@ -7,6 +8,7 @@ pub struct IdentityOptions {
     pub(super) revoke_all_purpose_keys: bool,
     pub(super) created_at: TimestampInSeconds,
     pub(super) expires_at: TimestampInSeconds,
+    pub(super) recovery_keys: Option<RecoveryKeys>,
 }
 
 impl IdentityOptions {
@@ -22,9 +24,16 @@ impl IdentityOptions {
             revoke_all_purpose_keys,
             created_at,
             expires_at,
+            recovery_keys: None,
         }
     }
 
+    /// Commit to a set of recovery keys which can replace the new key if it is lost
+    pub fn with_recovery_keys(mut self, recovery_keys: RecoveryKeys) -> Self {
+        self.recovery_keys = Some(recovery_keys);
+        self
+    }
+
     /// New key
     pub fn signing_secret_key_handle(&self) -> &SigningSecretKeyHandle {
         &self.signing_secret_key_handle
@@ -44,4 +53,9 @@ impl IdentityOptions {
     pub fn expires_at(&self) -> TimestampInSeconds {
         self.expires_at
     }
+
+    /// Recovery keys
+    pub fn recovery_keys(&self) -> Option<&RecoveryKeys> {
+        self.recovery_keys.as_ref()
+    }
 }
@@ -3,6 +3,7 @@ mod identities;
 mod identities_builder;
 mod identities_creation;
 mod identity_builder;
+mod identity_key_rotator;
 mod identity_keys;
 mod identity_options;
 mod storage;
@@ -11,6 +12,7 @@ pub use identities::*;
 pub use identities_builder::*;
 pub use identities_creation::*;
 pub use identity_builder::*;
+pub use identity_key_rotator::*;
 pub use identity_keys::*;
 pub use identity_options::*;
 pub use storage::*;
@@ -1,4 +1,4 @@
-use crate::models::{Identifier, TimestampInSeconds};
+use crate::models::{CredentialHash, Identifier, TimestampInSeconds};
 use crate::utils::now;
 use minicbor::{Decode, Encode};
 use ockam_core::compat::borrow::ToOwned;
@@ -16,6 +16,7 @@ pub struct AttributesEntry {
     #[n(2)] added: TimestampInSeconds,
     #[n(3)] expires: Option<TimestampInSeconds>,
     #[n(4)] attested_by: Option<Identifier>,
+    #[n(5)] credential_hash: Option<CredentialHash>,
 }
 
 impl AttributesEntry {
@@ -35,9 +36,16 @@ impl AttributesEntry {
             added,
             expires,
             attested_by,
+            credential_hash: None,
         }
     }
 
+    /// Set the hash of the credential which attested these attributes
+    pub fn with_credential_hash(mut self, credential_hash: CredentialHash) -> Self {
+        self.credential_hash = Some(credential_hash);
+        self
+    }
+
     /// The entry attributes
     pub fn attrs(&self) -> &BTreeMap<Vec<u8>, Vec<u8>> {
         &self.attrs
@@ -57,6 +65,11 @@ impl AttributesEntry {
     pub fn attested_by(&self) -> Option<Identifier> {
         self.attested_by.to_owned()
     }
+
+    /// Hash of the credential which attested these attributes, if they come from a credential
+    pub fn credential_hash(&self) -> Option<&CredentialHash> {
+        self.credential_hash.as_ref()
+    }
 }
 
 impl AttributesEntry {
@@ -73,6 +86,7 @@ impl AttributesEntry {
             added: now()?,
             expires,
             attested_by,
+            credential_hash: None,
         })
     }
 }
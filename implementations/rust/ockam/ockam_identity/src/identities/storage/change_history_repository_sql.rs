@@ -1,9 +1,9 @@
 use core::str::FromStr;
 
+use sqlx::any::AnyArguments;
 use sqlx::query::Query;
-use sqlx::sqlite::SqliteArguments;
 use sqlx::*;
-use tracing::debug;
+use tracing::{debug, info};
 
 use ockam_core::async_trait;
 use ockam_core::compat::sync::Arc;
@@ -14,7 +14,7 @@ use crate::models::{ChangeHistory, Identifier};
 use crate::{ChangeHistoryRepository, Identity, IdentityError, IdentityHistoryComparison, Vault};
 
 /// Implementation of `IdentitiesRepository` trait based on an underlying database
-/// using sqlx as its API, and Sqlite as its driver
+/// using sqlx as its API, and Sqlite or Postgres as its driver
 #[derive(Clone)]
 pub struct ChangeHistorySqlxDatabase {
     database: Arc<SqlxDatabase>,
@@ -58,7 +58,20 @@ impl ChangeHistoryRepository for ChangeHistorySqlxDatabase {
                     IdentityHistoryComparison::Conflict | IdentityHistoryComparison::Older => {
                         return Err(IdentityError::ConsistencyError.into());
                     }
-                    IdentityHistoryComparison::Newer => true,
+                    IdentityHistoryComparison::Newer => {
+                        info!(
+                            "the primary key of the identity {} has been rotated",
+                            identity.identifier()
+                        );
+                        true
+                    }
+                    IdentityHistoryComparison::Recovered => {
+                        info!(
+                            "the identity {} has been recovered with its recovery keys",
+                            identity.identifier()
+                        );
+                        true
+                    }
                     IdentityHistoryComparison::Equal => false,
                 }
             }
@@ -86,11 +99,11 @@ impl ChangeHistoryRepository for ChangeHistorySqlxDatabase {
 
     async fn delete_change_history(&self, identifier: &Identifier) -> Result<()> {
         let mut transaction = self.database.begin().await.into_core()?;
-        let query1 = query("DELETE FROM identity where identifier=?").bind(identifier.to_sql());
+        let query1 = query("DELETE FROM identity where identifier=$1").bind(identifier.to_sql());
         query1.execute(&mut *transaction).await.void()?;
 
         let query2 =
-            query("DELETE FROM identity_attributes where identifier=?").bind(identifier.to_sql());
+            query("DELETE FROM identity_attributes where identifier=$1").bind(identifier.to_sql());
         query2.execute(&mut *transaction).await.void()?;
         transaction.commit().await.void()?;
         Ok(())
@@ -117,8 +130,8 @@ impl ChangeHistorySqlxDatabase {
     fn insert_query<'a>(
         identifier: &Identifier,
         change_history: &ChangeHistory,
-    ) -> Query<'a, Sqlite, SqliteArguments<'a>> {
-        query("INSERT OR REPLACE INTO identity VALUES (?, ?)")
+    ) -> Query<'a, Any, AnyArguments<'a>> {
+        query("INSERT INTO identity VALUES ($1, $2) ON CONFLICT (identifier) DO UPDATE SET change_history = $2")
             .bind(identifier.to_sql())
             .bind(change_history.to_sql())
     }
@@ -159,74 +172,81 @@ impl ChangeHistoryRow {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::{identities, Identity};
+    use crate::{identities, Identities, Identity};
+    use ockam_node::database::with_dbs;
 
     #[tokio::test]
     async fn test_identities_repository() -> Result<()> {
-        let identity1 = create_identity().await?;
-        let identity2 = create_identity().await?;
-        let repository = create_repository().await?;
-
-        // store and retrieve an identity
-        repository
-            .store_change_history(identity1.identifier(), identity1.change_history().clone())
-            .await?;
-
-        // the change history can be retrieved
-        let result = repository
-            .get_change_history(identity1.identifier())
-            .await?;
-        assert_eq!(result, Some(identity1.change_history().clone()));
-
-        // trying to retrieve a missing identity returns None
-        let result = repository
-            .get_change_history(identity2.identifier())
-            .await?;
-        assert_eq!(result, None);
-
-        // the repository can return the list of all change histories
-        repository
-            .store_change_history(identity2.identifier(), identity2.change_history().clone())
-            .await?;
-        let result = repository.get_change_histories().await?;
-        assert_eq!(
-            result,
-            vec![
-                identity1.change_history().clone(),
-                identity2.change_history().clone(),
-            ]
-        );
-        // a change history can also be deleted from the repository
-        repository
-            .delete_change_history(identity2.identifier())
-            .await?;
-        let result = repository
-            .get_change_history(identity2.identifier())
-            .await?;
-        assert_eq!(result, None);
-        Ok(())
+        with_dbs(|db| async move {
+            let identity1 = create_identity().await?;
+            let identity2 = create_identity().await?;
+            let repository = create_repository(db);
+
+            // store and retrieve an identity
+            repository
+                .store_change_history(identity1.identifier(), identity1.change_history().clone())
+                .await?;
+
+            // the change history can be retrieved
+            let result = repository
+                .get_change_history(identity1.identifier())
+                .await?;
+            assert_eq!(result, Some(identity1.change_history().clone()));
+
+            // trying to retrieve a missing identity returns None
+            let result = repository
+                .get_change_history(identity2.identifier())
+                .await?;
+            assert_eq!(result, None);
+
+            // the repository can return the list of all change histories
+            repository
+                .store_change_history(identity2.identifier(), identity2.change_history().clone())
+                .await?;
+            let result = repository.get_change_histories().await?;
+            assert_eq!(
+                result,
+                vec![
+                    identity1.change_history().clone(),
+                    identity2.change_history().clone(),
+                ]
+            );
+            // a change history can also be deleted from the repository
+            repository
+                .delete_change_history(identity2.identifier())
+                .await?;
+            let result = repository
+                .get_change_history(identity2.identifier())
+                .await?;
+            assert_eq!(result, None);
+            Ok(())
+        })
+        .await
     }
 
     #[tokio::test]
     async fn test_update_identity() -> Result<()> {
-        let identities = identities().await?;
-        let identities_creation = identities.identities_creation();
-        let identifier = identities_creation.create_identity().await?;
-
-        // rotating the identity twice
-        identities_creation.rotate_identity(&identifier).await?;
-        let rotated = identities.get_identity(&identifier).await?;
-        identities_creation.rotate_identity(&identifier).await?;
-
-        // try to update the identity with an old rotated version
-        let result = identities_creation.update_identity(&rotated).await;
-        assert!(result.is_err());
-        Ok(())
+        with_dbs(|db| async move {
+            let identities = Identities::create(db).build();
+            let identities_creation = identities.identities_creation();
+            let identifier = identities_creation.create_identity().await?;
+
+            // rotating the identity twice
+            identities_creation.rotate_identity(&identifier).await?;
+            let rotated = identities.get_identity(&identifier).await?;
+            identities_creation.rotate_identity(&identifier).await?;
+
+            // try to update the identity with an old rotated version
+            let result = identities_creation.update_identity(&rotated).await;
+            assert!(result.is_err());
+            Ok(())
+        })
+        .await
     }
 
     /// HELPERS
-    async fn create_repository() -> Result<Arc<dyn ChangeHistoryRepository>> {
-        Ok(ChangeHistorySqlxDatabase::create().await?)
+    fn create_repository(db: Arc<SqlxDatabase>) -> Arc<dyn ChangeHistoryRepository> {
+        Arc::new(ChangeHistorySqlxDatabase::new(db))
     }
 
     async fn create_identity() -> Result<Identity> {
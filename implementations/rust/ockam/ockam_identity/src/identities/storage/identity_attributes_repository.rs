@@ -1,3 +1,4 @@
+use crate::models::CredentialHash;
 use crate::{AttributesEntry, Identifier};
 use async_trait::async_trait;
 use ockam_core::compat::boxed::Box;
@@ -19,4 +20,11 @@ pub trait IdentityAttributesRepository: Send + Sync + 'static {
 
     /// Remove all attributes for a given identity identifier
     async fn delete(&self, identity: &Identifier) -> Result<()>;
+
+    /// Remove the attributes attested by an issuer with the credential having the given hash
+    async fn delete_by_credential_hash(
+        &self,
+        attested_by: &Identifier,
+        credential_hash: &CredentialHash,
+    ) -> Result<()>;
 }
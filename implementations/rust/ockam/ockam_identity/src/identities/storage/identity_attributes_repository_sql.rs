@@ -6,13 +6,13 @@ use tracing::debug;
 use ockam_core::async_trait;
 use ockam_core::compat::sync::Arc;
 use ockam_core::Result;
-use ockam_node::database::{FromSqlxError, SqlxDatabase, SqlxType, ToSqlxType, ToVoid};
+use ockam_node::database::{FromSqlxError, Nullable, SqlxDatabase, SqlxType, ToSqlxType, ToVoid};
 
-use crate::models::Identifier;
+use crate::models::{CredentialHash, Identifier};
 use crate::{AttributesEntry, IdentityAttributesRepository, TimestampInSeconds};
 
 /// Implementation of `IdentitiesRepository` trait based on an underlying database
-/// using sqlx as its API, and Sqlite as its driver
+/// using sqlx as its API, and Sqlite or Postgres as its driver
 #[derive(Clone)]
 pub struct IdentityAttributesSqlxDatabase {
     database: Arc<SqlxDatabase>,
@@ -36,7 +36,7 @@ impl IdentityAttributesSqlxDatabase {
 #[async_trait]
 impl IdentityAttributesRepository for IdentityAttributesSqlxDatabase {
     async fn get_attributes(&self, identity: &Identifier) -> Result<Option<AttributesEntry>> {
-        let query = query_as("SELECT identifier, attributes, added, expires, attested_by FROM identity_attributes WHERE identifier=$1")
+        let query = query_as("SELECT identifier, attributes, added, expires, attested_by, credential_hash FROM identity_attributes WHERE identifier=$1")
             .bind(identity.to_sql());
         let identity_attributes: Option<IdentityAttributesRow> = query
             .fetch_optional(&self.database.pool)
@@ -47,7 +47,7 @@ impl IdentityAttributesRepository for IdentityAttributesSqlxDatabase {
 
     async fn list_attributes_by_identifier(&self) -> Result<Vec<(Identifier, AttributesEntry)>> {
         let query = query_as(
-            "SELECT identifier, attributes, added, expires, attested_by FROM identity_attributes",
+            "SELECT identifier, attributes, added, expires, attested_by, credential_hash FROM identity_attributes",
         );
         let result: Vec<IdentityAttributesRow> =
             query.fetch_all(&self.database.pool).await.into_core()?;
@@ -58,18 +58,32 @@ impl IdentityAttributesRepository for IdentityAttributesSqlxDatabase {
     }
 
     async fn put_attributes(&self, subject: &Identifier, entry: AttributesEntry) -> Result<()> {
-        let query = query("INSERT OR REPLACE INTO identity_attributes VALUES (?, ?, ?, ?, ?)")
+        let query = query("INSERT INTO identity_attributes (identifier, attributes, added, expires, attested_by, credential_hash) VALUES ($1, $2, $3, $4, $5, $6) ON CONFLICT (identifier) DO UPDATE SET attributes = $2, added = $3, expires = $4, attested_by = $5, credential_hash = $6")
             .bind(subject.to_sql())
             .bind(minicbor::to_vec(entry.attrs())?.to_sql())
             .bind(entry.added().to_sql())
             .bind(entry.expires().map(|e| e.to_sql()))
-            .bind(entry.attested_by().map(|e| e.to_sql()));
+            .bind(entry.attested_by().map(|e| e.to_sql()))
+            .bind(entry.credential_hash().map(|h| h.to_string().to_sql()));
         query.execute(&self.database.pool).await.void()
     }
 
     async fn delete(&self, identity: &Identifier) -> Result<()> {
         let query =
-            query("DELETE FROM identity_attributes WHERE identifier = ?").bind(identity.to_sql());
+            query("DELETE FROM identity_attributes WHERE identifier = $1").bind(identity.to_sql());
+        query.execute(&self.database.pool).await.void()
+    }
+
+    async fn delete_by_credential_hash(
+        &self,
+        attested_by: &Identifier,
+        credential_hash: &CredentialHash,
+    ) -> Result<()> {
+        let query = query(
+            "DELETE FROM identity_attributes WHERE credential_hash = $1 AND attested_by = $2",
+        )
+        .bind(credential_hash.to_string().to_sql())
+        .bind(attested_by.to_sql());
         query.execute(&self.database.pool).await.void()
     }
 }
@@ -88,8 +102,9 @@ struct IdentityAttributesRow {
     identifier: String,
     attributes: Vec<u8>,
     added: i64,
-    expires: Option<i64>,
-    attested_by: Option<String>,
+    expires: Nullable<i64>,
+    attested_by: Nullable<String>,
+    credential_hash: Nullable<String>,
 }
 
 impl IdentityAttributesRow {
@@ -101,64 +116,90 @@ impl IdentityAttributesRow {
         let attributes =
             minicbor::decode(self.attributes.as_slice()).map_err(SqlxDatabase::map_decode_err)?;
         let added = TimestampInSeconds(self.added as u64);
-        let expires = self.expires.map(|v| TimestampInSeconds(v as u64));
+        let expires = self
+            .expires
+            .to_option()
+            .map(|v| TimestampInSeconds(v as u64));
         let attested_by = self
             .attested_by
-            .clone()
+            .to_option()
             .map(|v| Identifier::from_str(&v))
             .transpose()?;
+        let credential_hash = self
+            .credential_hash
+            .to_option()
+            .map(|v| CredentialHash::from_str(&v))
+            .transpose()?;
 
-        Ok(AttributesEntry::new(
-            attributes,
-            added,
-            expires,
-            attested_by,
-        ))
+        let entry = AttributesEntry::new(attributes, added, expires, attested_by);
+        Ok(match credential_hash {
+            Some(credential_hash) => entry.with_credential_hash(credential_hash),
+            None => entry,
+        })
     }
 }
 
 #[cfg(test)]
 mod tests {
     use ockam_core::compat::collections::BTreeMap;
+    use ockam_node::database::with_dbs;
 
     use super::*;
     use crate::identities;
 
     #[tokio::test]
     async fn test_identities_attributes_repository() -> Result<()> {
-        let repository = create_repository().await?;
-
-        // store and retrieve attributes by identity
-        let identifier1 = create_identity().await?;
-        let attributes1 = create_attributes_entry(&identifier1).await?;
-        let identifier2 = create_identity().await?;
-        let attributes2 = create_attributes_entry(&identifier2).await?;
-
-        repository
-            .put_attributes(&identifier1, attributes1.clone())
-            .await?;
-        repository
-            .put_attributes(&identifier2, attributes2.clone())
-            .await?;
-
-        let result = repository.get_attributes(&identifier1).await?;
-        assert_eq!(result, Some(attributes1.clone()));
-
-        let result = repository.list_attributes_by_identifier().await?;
-        assert_eq!(
-            result,
-            vec![
-                (identifier1.clone(), attributes1.clone()),
-                (identifier2.clone(), attributes2.clone())
-            ]
-        );
-
-        // delete attributes
-        repository.delete(&identifier1).await?;
-        let result = repository.get_attributes(&identifier1).await?;
-        assert_eq!(result, None);
-
-        Ok(())
+        with_dbs(|db| async move {
+            let repository = create_repository(db);
+
+            // store and retrieve attributes by identity
+            let identifier1 = create_identity().await?;
+            let attributes1 = create_attributes_entry(&identifier1).await?;
+            let identifier2 = create_identity().await?;
+            let attributes2 = create_attributes_entry(&identifier2)
+                .await?
+                .with_credential_hash(CredentialHash([1; 32]));
+
+            repository
+                .put_attributes(&identifier1, attributes1.clone())
+                .await?;
+            repository
+                .put_attributes(&identifier2, attributes2.clone())
+                .await?;
+
+            let result = repository.get_attributes(&identifier1).await?;
+            assert_eq!(result, Some(attributes1.clone()));
+
+            let result = repository.list_attributes_by_identifier().await?;
+            assert_eq!(
+                result,
+                vec![
+                    (identifier1.clone(), attributes1.clone()),
+                    (identifier2.clone(), attributes2.clone())
+                ]
+            );
+
+            // delete attributes
+            repository.delete(&identifier1).await?;
+            let result = repository.get_attributes(&identifier1).await?;
+            assert_eq!(result, None);
+
+            // delete attributes by credential hash, only for the issuer of that credential
+            repository
+                .delete_by_credential_hash(&identifier1, &CredentialHash([1; 32]))
+                .await?;
+            let result = repository.get_attributes(&identifier2).await?;
+            assert_eq!(result, Some(attributes2.clone()));
+
+            repository
+                .delete_by_credential_hash(&identifier2, &CredentialHash([1; 32]))
+                .await?;
+            let result = repository.get_attributes(&identifier2).await?;
+            assert_eq!(result, None);
+
+            Ok(())
+        })
+        .await
     }
 
     /// HELPERS
@@ -179,7 +220,7 @@ mod tests {
         identities.identities_creation().create_identity().await
     }
 
-    async fn create_repository() -> Result<Arc<dyn IdentityAttributesRepository>> {
-        Ok(IdentityAttributesSqlxDatabase::create().await?)
+    fn create_repository(db: Arc<SqlxDatabase>) -> Arc<dyn IdentityAttributesRepository> {
+        Arc::new(IdentityAttributesSqlxDatabase::new(db))
     }
 }
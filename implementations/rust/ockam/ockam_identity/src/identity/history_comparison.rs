@@ -10,4 +10,7 @@ pub enum IdentityHistoryComparison {
     Newer,
     /// Known identity is more recent
     Older,
+    /// Current identity forks from the known identity with a change authorized by the recovery keys
+    /// still in effect in the known identity, and replaces the changes made after the fork
+    Recovered,
 }
@@ -8,7 +8,7 @@ use ockam_core::compat::vec::Vec;
 use ockam_core::Result;
 use ockam_vault::{VaultForVerifyingSignatures, VerifyingPublicKey};
 
-use crate::models::{Change, ChangeHash, ChangeHistory, Identifier};
+use crate::models::{Change, ChangeHash, ChangeHistory, Identifier, RecoveryKeys};
 use crate::verified_change::VerifiedChange;
 use crate::IdentityHistoryComparison;
 use crate::{IdentityError, Vault};
@@ -157,6 +157,19 @@ impl Identity {
         }
     }
 
+    /// Get the [`RecoveryKeys`] currently in effect, committed to by the latest change defining them
+    pub fn recovery_keys(&self) -> Option<&RecoveryKeys> {
+        Self::recovery_keys_in_effect(&self.changes)
+    }
+
+    /// Get the [`RecoveryKeys`] in effect after a sequence of changes
+    fn recovery_keys_in_effect(changes: &[VerifiedChange]) -> Option<&RecoveryKeys> {
+        changes
+            .iter()
+            .rev()
+            .find_map(|change| change.data().recovery_keys.as_ref())
+    }
+
     /// Get latest [`VerifiedChange`]
     pub fn get_latest_change(&self) -> Result<VerifiedChange> {
         if let Some(last_change) = self.changes().last() {
@@ -181,8 +194,19 @@ impl Identity {
 
     /// Compare to a previously known state of the same `Identity`
     pub fn compare(&self, known: &Self) -> IdentityHistoryComparison {
-        for change_pair in self.changes.iter().zip(known.changes.iter()) {
+        for (index, change_pair) in self.changes.iter().zip(known.changes.iter()).enumerate() {
             if change_pair.0.change_hash() != change_pair.1.change_hash() {
+                // A recovery change takes precedence over changes signed with the previous
+                // primary key, which may have been compromised. It must be authorized by the
+                // recovery keys which are still in effect in the known history: recovery keys
+                // replaced since the fork may have been lost or compromised
+                if change_pair.0.is_recovery()
+                    && !change_pair.1.is_recovery()
+                    && Self::recovery_keys_in_effect(&self.changes[..index])
+                        == known.recovery_keys()
+                {
+                    return IdentityHistoryComparison::Recovered;
+                }
                 return IdentityHistoryComparison::Conflict;
             }
         }
@@ -1,4 +1,6 @@
-use crate::models::{Change, ChangeData, ChangeHash, ChangeSignature, VersionedData};
+use crate::models::{
+    Change, ChangeData, ChangeHash, ChangeSignature, RecoveryKeys, RecoverySignature, VersionedData,
+};
 use crate::verified_change::VerifiedChange;
 use crate::{Identity, IdentityError};
 
@@ -88,15 +90,37 @@ impl Identity {
                     // Corrupted changes sequence
                     return Err(IdentityError::IdentityVerificationFailed.into());
                 }
-            } else if change_details.change_data.previous_change.is_some() {
+            } else if change_details.change_data.previous_change.is_some()
+                || change.recovery_signatures.is_some()
+            {
                 // Should be empty
                 return Err(IdentityError::IdentityVerificationFailed.into());
             }
 
+            // A change signed with the previous key can also carry recovery signatures, when it
+            // replaces the recovery keys. Only a change without the previous signature is a recovery
+            let is_recovery =
+                change.previous_signature.is_none() && change.recovery_signatures.is_some();
+            if is_recovery && !change_details.change_data.revoke_all_purpose_keys {
+                // Purpose keys attested by a lost key can't be trusted anymore
+                return Err(IdentityError::IdentityVerificationFailed.into());
+            }
+
+            if let Some(recovery_keys) = &change_details.change_data.recovery_keys {
+                if recovery_keys.threshold == 0
+                    || recovery_keys.public_keys.len() < recovery_keys.threshold as usize
+                    || recovery_keys.public_keys.len() > u8::MAX as usize
+                {
+                    // Recovery with that set of keys is either impossible or unrestricted
+                    return Err(IdentityError::IdentityVerificationFailed.into());
+                }
+            }
+
             to_be_verified_changes.push(VerifiedChange::new(
                 change_details.change_data.clone(),
                 change_details.change_hash.clone(),
                 change_details.change_data.primary_public_key.clone().into(),
+                is_recovery,
             ));
 
             previous_change_details = Some(change_details);
@@ -115,6 +139,7 @@ impl Identity {
             return Err(IdentityError::IdentityVerificationFailed.into());
         }
 
+        let mut recovery_keys = None;
         for i in 0..to_be_verified_changes.len() {
             let last_verified_change = if i == 0 {
                 None
@@ -122,8 +147,21 @@ impl Identity {
                 Some(&to_be_verified_changes[i - 1])
             };
 
+            // Recovery keys stay in effect until a following change replaces them
+            if let Some(last_verified_change) = last_verified_change {
+                if let Some(keys) = &last_verified_change.data().recovery_keys {
+                    recovery_keys = Some(keys);
+                }
+            }
+
             let new_change = &changes[i];
-            Self::verify_change_signatures(last_verified_change, new_change, vault.clone()).await?
+            Self::verify_change_signatures(
+                last_verified_change,
+                recovery_keys,
+                new_change,
+                vault.clone(),
+            )
+            .await?
         }
         Ok(())
     }
@@ -139,30 +177,133 @@ impl Identity {
             .await
     }
 
+    /// Check that the change was signed with the primary key of the previous change
+    async fn verify_previous_signature(
+        last_verified_change: &VerifiedChange,
+        hash: [u8; 32],
+        previous_signature: &ChangeSignature,
+        vault: Arc<dyn VaultForVerifyingSignatures>,
+    ) -> Result<()> {
+        if !Self::verify_change_signature(
+            last_verified_change.primary_public_key(),
+            hash,
+            previous_signature,
+            vault,
+        )
+        .await?
+        {
+            return Err(IdentityError::IdentityVerificationFailed.into());
+        }
+
+        Ok(())
+    }
+
+    /// Check that at least `threshold` distinct recovery keys signed the change
+    async fn verify_recovery_signatures(
+        recovery_keys: &RecoveryKeys,
+        hash: [u8; 32],
+        recovery_signatures: &[RecoverySignature],
+        vault: Arc<dyn VaultForVerifyingSignatures>,
+    ) -> Result<()> {
+        let mut signers: Vec<u8> = Vec::with_capacity(recovery_signatures.len());
+        for recovery_signature in recovery_signatures {
+            if signers.contains(&recovery_signature.key_index) {
+                // Each recovery key counts only once
+                return Err(IdentityError::IdentityVerificationFailed.into());
+            }
+
+            let public_key = match recovery_keys
+                .public_keys
+                .get(recovery_signature.key_index as usize)
+            {
+                Some(public_key) => public_key.clone().into(),
+                None => return Err(IdentityError::IdentityVerificationFailed.into()),
+            };
+
+            if !Self::verify_change_signature(
+                &public_key,
+                hash,
+                &recovery_signature.signature,
+                vault.clone(),
+            )
+            .await?
+            {
+                return Err(IdentityError::IdentityVerificationFailed.into());
+            }
+            signers.push(recovery_signature.key_index);
+        }
+
+        if signers.len() < recovery_keys.threshold as usize {
+            return Err(IdentityError::IdentityVerificationFailed.into());
+        }
+
+        Ok(())
+    }
+
     /// WARNING: This function assumes all existing changes in chain are verified.
     /// WARNING: Correctness of changes sequence is not verified here.
     async fn verify_change_signatures(
         last_verified_change: Option<&VerifiedChange>,
+        recovery_keys: Option<&RecoveryKeys>,
         new_change: &Change,
         vault: Arc<dyn VaultForVerifyingSignatures>,
     ) -> Result<()> {
         let new_change_details = Self::get_change_details(new_change, vault.clone()).await?;
 
         if let Some(last_verified_change) = last_verified_change {
-            if let Some(previous_signature) = &new_change.previous_signature {
-                if !Self::verify_change_signature(
-                    last_verified_change.primary_public_key(),
-                    new_change_details.change_full_hash,
-                    previous_signature,
-                    vault.clone(),
-                )
-                .await?
-                {
+            // Recovery keys can only be replaced with the approval of the recovery keys in effect,
+            // otherwise a compromised primary key could take over the recovery of the identity
+            let replaces_recovery_keys =
+                match (recovery_keys, &new_change_details.change_data.recovery_keys) {
+                    (Some(current), Some(new)) => current != new,
+                    _ => false,
+                };
+
+            match (
+                &new_change.previous_signature,
+                &new_change.recovery_signatures,
+                recovery_keys,
+            ) {
+                (Some(previous_signature), None, _) if !replaces_recovery_keys => {
+                    Self::verify_previous_signature(
+                        last_verified_change,
+                        new_change_details.change_full_hash,
+                        previous_signature,
+                        vault.clone(),
+                    )
+                    .await?
+                }
+                (Some(previous_signature), Some(recovery_signatures), Some(recovery_keys)) => {
+                    Self::verify_previous_signature(
+                        last_verified_change,
+                        new_change_details.change_full_hash,
+                        previous_signature,
+                        vault.clone(),
+                    )
+                    .await?;
+                    Self::verify_recovery_signatures(
+                        recovery_keys,
+                        new_change_details.change_full_hash,
+                        recovery_signatures,
+                        vault.clone(),
+                    )
+                    .await?
+                }
+                (None, Some(recovery_signatures), Some(recovery_keys)) => {
+                    Self::verify_recovery_signatures(
+                        recovery_keys,
+                        new_change_details.change_full_hash,
+                        recovery_signatures,
+                        vault.clone(),
+                    )
+                    .await?
+                }
+                _ => {
+                    // Either the previous signature or recovery signatures, with recovery keys
+                    // committed to by a previous change, should be present if it's not the
+                    // first change. Both are needed to replace the recovery keys
                     return Err(IdentityError::IdentityVerificationFailed.into());
                 }
-            } else {
-                // Previous signature should be present if it's not the first change
-                return Err(IdentityError::IdentityVerificationFailed.into());
             }
         }
 
@@ -7,6 +7,7 @@ pub struct VerifiedChange {
     data: ChangeData,
     change_hash: ChangeHash,
     primary_public_key: VerifyingPublicKey,
+    is_recovery: bool,
 }
 
 impl VerifiedChange {
@@ -14,11 +15,13 @@ impl VerifiedChange {
         data: ChangeData,
         change_hash: ChangeHash,
         primary_public_key: VerifyingPublicKey,
+        is_recovery: bool,
     ) -> Self {
         Self {
             data,
             change_hash,
             primary_public_key,
+            is_recovery,
         }
     }
 
@@ -36,4 +39,10 @@ impl VerifiedChange {
     pub fn primary_public_key(&self) -> &VerifyingPublicKey {
         &self.primary_public_key
     }
+
+    /// True if that change was authorized by recovery signatures
+    /// instead of the previous primary key
+    pub fn is_recovery(&self) -> bool {
+        self.is_recovery
+    }
 }
@@ -29,6 +29,11 @@ pub struct Change {
     /// Self-signature over the data using the key
     /// from the previous [`Change`] in the [`ChangeHistory`]
     #[n(2)] pub previous_signature: Option<ChangeSignature>,
+    /// Signatures over the data using the [`RecoveryKeys`] committed to by a previous
+    /// [`Change`] in the [`ChangeHistory`].
+    /// They replace the `previous_signature` when the previous primary key was lost, and are
+    /// required in addition to the `previous_signature` when the change replaces the [`RecoveryKeys`]
+    #[n(3)] pub recovery_signatures: Option<Vec<RecoverySignature>>,
 }
 
 /// [`Change`] signature
@@ -58,6 +63,32 @@ pub struct ChangeData {
     #[n(3)] pub created_at: TimestampInSeconds,
     /// Expiration [`TimestampInSeconds`] (UTC)
     #[n(4)] pub expires_at: TimestampInSeconds,
+    /// [`RecoveryKeys`] which can authorize the next [`Change`]s instead of the primary key.
+    /// They stay in effect until another [`Change`], signed by them, commits to new [`RecoveryKeys`]
+    #[n(5)] pub recovery_keys: Option<RecoveryKeys>,
+}
+
+/// Set of k-of-n recovery keys committed to in a [`ChangeData`]
+///
+/// A [`Change`] without a `previous_signature` is valid if it is signed by at least
+/// `threshold` distinct keys of that set, see [`RecoverySignature`]
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct RecoveryKeys {
+    /// Minimum number of [`RecoverySignature`]s needed to authorize a [`Change`]
+    #[n(0)] pub threshold: u8,
+    /// Recovery Public Keys
+    #[n(1)] pub public_keys: Vec<PrimaryPublicKey>,
+}
+
+/// Signature over the data of a [`Change`] using one of the [`RecoveryKeys`]
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct RecoverySignature {
+    /// Index of the signing key in [`RecoveryKeys::public_keys`]
+    #[n(0)] pub key_index: u8,
+    /// Signature over the data of the [`Change`]
+    #[n(1)] pub signature: ChangeSignature,
 }
 
 /// [`Change`]'s public key
@@ -45,6 +45,37 @@ pub struct CredentialData {
     #[n(3)] pub created_at: TimestampInSeconds,
     /// Expiration [`TimestampInSeconds`] (UTC)
     #[n(4)] pub expires_at: TimestampInSeconds,
+    /// Commitments to the attributes that the Subject can selectively disclose, in addition to
+    /// the `subject_attributes` which are always disclosed
+    #[n(5)] pub subject_attribute_commitments: Option<Vec<AttributeCommitment>>,
+}
+
+/// AttributeCommitment length
+pub const ATTRIBUTE_COMMITMENT_LEN: usize = 32;
+
+/// Commitment to a selectively disclosed attribute of a [`Credential`]
+/// Computed as SHA256 of the CBOR serialized [`AttributeDisclosure`]
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[cbor(transparent)]
+pub struct AttributeCommitment(
+    #[cbor(n(0), with = "minicbor::bytes")] pub [u8; ATTRIBUTE_COMMITMENT_LEN],
+);
+
+/// AttributeDisclosure salt length
+pub const ATTRIBUTE_SALT_LEN: usize = 16;
+
+/// Attribute revealed by the Subject of a [`Credential`].
+/// The random salt prevents a verifier from guessing the value of an attribute which is not disclosed
+/// from its [`AttributeCommitment`]
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct AttributeDisclosure {
+    /// Random salt
+    #[cbor(n(0), with = "minicbor::bytes")] pub salt: [u8; ATTRIBUTE_SALT_LEN],
+    /// Attribute key
+    #[n(1)] pub key: ByteVec,
+    /// Attribute value
+    #[n(2)] pub value: ByteVec,
 }
 
 /// Number that determines which keys&values to expect in the [`Attributes`]
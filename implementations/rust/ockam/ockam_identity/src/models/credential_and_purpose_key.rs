@@ -6,7 +6,9 @@ use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
 
 use crate::alloc::string::ToString;
-use crate::models::{Credential, CredentialData, PurposeKeyAttestation};
+use crate::models::{
+    AttributeDisclosure, Credential, CredentialData, DelegationAndPurposeKey, PurposeKeyAttestation,
+};
 
 /// [`Credential`] and the corresponding [`PurposeKeyAttestation`] that was used to issue that
 /// [`Credential`] and will be used to verify it
@@ -18,6 +20,11 @@ pub struct CredentialAndPurposeKey {
     /// Corresponding [`PurposeKeyAttestation`] that was used to issue that
     /// [`Credential`] and will be used to verify it
     #[n(1)] pub purpose_key_attestation: PurposeKeyAttestation,
+    /// Disclosed attributes of a selective-disclosure [`Credential`]
+    #[n(2)] pub disclosures: Option<Vec<AttributeDisclosure>>,
+    /// Chain of [`super::Delegation`]s allowing the issuer to issue that [`Credential`],
+    /// starting with the one signed by a trusted Authority
+    #[n(3)] pub delegations: Option<Vec<DelegationAndPurposeKey>>,
 }
 
 impl CredentialAndPurposeKey {
@@ -47,6 +54,20 @@ impl CredentialAndPurposeKey {
     pub fn get_credential_data(&self) -> Result<CredentialData> {
         self.credential.get_credential_data()
     }
+
+    /// Return a copy of the credential which only discloses the given attributes.
+    /// The attributes which are not committed to by the credential are always disclosed
+    pub fn disclose(&self, attribute_keys: &[Vec<u8>]) -> CredentialAndPurposeKey {
+        let mut credential = self.clone();
+        if let Some(disclosures) = credential.disclosures.as_mut() {
+            disclosures.retain(|d| {
+                attribute_keys
+                    .iter()
+                    .any(|k| k.as_slice() == d.key.as_slice())
+            });
+        }
+        credential
+    }
 }
 
 #[cfg(test)]
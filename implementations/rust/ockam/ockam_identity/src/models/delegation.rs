@@ -0,0 +1,54 @@
+use minicbor::bytes::ByteVec;
+use minicbor::{Decode, Encode};
+use ockam_core::compat::vec::Vec;
+
+use crate::models::{
+    ChangeHistory, CredentialSignature, Identifier, PurposeKeyAttestation, TimestampInSeconds,
+};
+
+/// `data_type` value in [`super::VersionedData`] struct when used with [`Delegation`]
+pub const DELEGATION_DATA_TYPE: u8 = 5;
+
+/// Delegation certificate: an Authority allows another Identity to issue [`super::Credential`]s
+/// on its behalf, for a restricted set of attributes
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct Delegation {
+    /// CBOR serialized [`super::VersionedData`]
+    /// where VersionedData::data is CBOR serialized [`DelegationData`]
+    /// and VersionedData::data_type is [`DELEGATION_DATA_TYPE`]
+    #[cbor(with = "minicbor::bytes")]
+    #[n(0)] pub data: Vec<u8>,
+    /// Signature over data field using the delegating Authority's Credentials [`PurposeKeyAttestation`]
+    #[n(1)] pub signature: CredentialSignature,
+}
+
+/// Data inside a [`Delegation`]
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct DelegationData {
+    /// Identity which is allowed to issue [`super::Credential`]s
+    #[n(0)] pub delegate: Identifier,
+    /// Prefixes of the attribute keys that the delegate can attest to.
+    /// A delegate can only delegate further a subset of its own namespaces
+    #[n(1)] pub attribute_namespaces: Vec<ByteVec>,
+    /// Creation [`TimestampInSeconds`] (UTC)
+    #[n(2)] pub created_at: TimestampInSeconds,
+    /// Expiration [`TimestampInSeconds`] (UTC)
+    #[n(3)] pub expires_at: TimestampInSeconds,
+}
+
+/// [`Delegation`], the corresponding [`PurposeKeyAttestation`] that was used to sign it
+/// and the [`ChangeHistory`] of the delegate
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct DelegationAndPurposeKey {
+    /// [`Delegation`]
+    #[n(0)] pub delegation: Delegation,
+    /// Corresponding [`PurposeKeyAttestation`] that was used to sign that
+    /// [`Delegation`] and will be used to verify it
+    #[n(1)] pub purpose_key_attestation: PurposeKeyAttestation,
+    /// [`ChangeHistory`] of the delegate, used to verify the [`PurposeKeyAttestation`]
+    /// of the delegate when the verifier only knows the Authority
+    #[n(2)] pub delegate_change_history: ChangeHistory,
+}
@@ -0,0 +1,52 @@
+use minicbor::{Decode, Encode};
+use ockam_core::compat::vec::Vec;
+
+use crate::models::{CredentialSignature, Identifier, PurposeKeyAttestation, TimestampInSeconds};
+
+/// `data_type` value in [`super::VersionedData`] struct when used with [`RevocationList`]
+pub const REVOCATION_LIST_DATA_TYPE: u8 = 4;
+
+/// CredentialHash length
+pub const CREDENTIAL_HASH_LEN: usize = 32;
+
+/// Unique identifier for a [`super::Credential`]
+/// Computed as SHA256 of the [`super::Credential`] data field
+#[derive(Clone, Debug, Hash, Ord, PartialOrd, Eq, PartialEq, Encode, Decode)]
+#[cbor(transparent)]
+pub struct CredentialHash(#[cbor(n(0), with = "minicbor::bytes")] pub [u8; CREDENTIAL_HASH_LEN]);
+
+/// List of revoked [`super::Credential`]s, signed by the Authority which issued them
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct RevocationList {
+    /// CBOR serialized [`super::VersionedData`]
+    /// where VersionedData::data is CBOR serialized [`RevocationListData`]
+    /// and VersionedData::data_type is [`REVOCATION_LIST_DATA_TYPE`]
+    #[cbor(with = "minicbor::bytes")]
+    #[n(0)] pub data: Vec<u8>,
+    /// Signature over data field using the Authority's Credentials [`PurposeKeyAttestation`]
+    #[n(1)] pub signature: CredentialSignature,
+}
+
+/// Data inside a [`RevocationList`]
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct RevocationListData {
+    /// Hashes of the individual [`super::Credential`]s which must not be accepted anymore
+    #[n(0)] pub revoked_credentials: Vec<CredentialHash>,
+    /// Subjects for which no [`super::Credential`] must be accepted anymore
+    #[n(1)] pub revoked_subjects: Vec<Identifier>,
+    /// Creation [`TimestampInSeconds`] (UTC). A more recent list always supersedes an older one
+    #[n(2)] pub created_at: TimestampInSeconds,
+}
+
+/// [`RevocationList`] and the corresponding [`PurposeKeyAttestation`] that was used to sign it
+#[derive(Clone, Debug, PartialEq, Eq, Encode, Decode)]
+#[rustfmt::skip]
+pub struct RevocationListAndPurposeKey {
+    /// [`RevocationList`]
+    #[n(0)] pub revocation_list: RevocationList,
+    /// Corresponding [`PurposeKeyAttestation`] that was used to sign that
+    /// [`RevocationList`] and will be used to verify it
+    #[n(1)] pub purpose_key_attestation: PurposeKeyAttestation,
+}
@@ -0,0 +1,85 @@
+use minicbor::bytes::ByteVec;
+use ockam_core::compat::vec::Vec;
+use ockam_core::Result;
+
+use crate::models::{Delegation, DelegationData, VersionedData, DELEGATION_DATA_TYPE};
+use crate::IdentityError;
+
+impl Delegation {
+    /// Create [`VersionedData`] with corresponding version and data_type
+    pub fn create_versioned_data(data: Vec<u8>) -> VersionedData {
+        VersionedData {
+            version: 1,
+            data_type: DELEGATION_DATA_TYPE,
+            data,
+        }
+    }
+
+    /// Extract [`DelegationData`]
+    pub fn get_delegation_data(&self) -> Result<DelegationData> {
+        DelegationData::get_data(&minicbor::decode(&self.data)?)
+    }
+}
+
+impl DelegationData {
+    /// Extract [`DelegationData`] from [`VersionedData`]
+    pub fn get_data(versioned_data: &VersionedData) -> Result<Self> {
+        if versioned_data.version != 1 {
+            return Err(IdentityError::UnknownDelegationVersion.into());
+        }
+
+        if versioned_data.data_type != DELEGATION_DATA_TYPE {
+            return Err(IdentityError::InvalidDelegationDataType.into());
+        }
+
+        Ok(minicbor::decode(&versioned_data.data)?)
+    }
+
+    /// Return true if the delegate can attest to an attribute with that key
+    pub fn allows_attribute(&self, attribute_key: &[u8]) -> bool {
+        is_in_namespaces(&self.attribute_namespaces, attribute_key)
+    }
+
+    /// Return true if all the namespaces of another delegation are included in this delegation
+    pub fn includes_namespaces(&self, attribute_namespaces: &[ByteVec]) -> bool {
+        attribute_namespaces
+            .iter()
+            .all(|namespace| self.allows_attribute(namespace))
+    }
+}
+
+/// Return true if a key starts with one of the namespaces
+fn is_in_namespaces(attribute_namespaces: &[ByteVec], attribute_key: &[u8]) -> bool {
+    attribute_namespaces
+        .iter()
+        .any(|namespace| attribute_key.starts_with(namespace))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Identifier, TimestampInSeconds};
+    use core::str::FromStr;
+
+    #[test]
+    fn test_delegation_namespaces() {
+        let delegation = DelegationData {
+            delegate: Identifier::from_str(
+                "I923829d0397a06fa862be5a87b7966959b8ef99ab6455b843ca9131a747b4819",
+            )
+            .unwrap(),
+            attribute_namespaces: vec![b"team-a.".to_vec().into(), b"region".to_vec().into()],
+            created_at: TimestampInSeconds(0),
+            expires_at: TimestampInSeconds(10),
+        };
+
+        assert!(delegation.allows_attribute(b"team-a.role"));
+        assert!(delegation.allows_attribute(b"region"));
+        assert!(!delegation.allows_attribute(b"team-b.role"));
+        assert!(!delegation.allows_attribute(b"team-a"));
+
+        assert!(delegation.includes_namespaces(&[b"team-a.admins.".to_vec().into()]));
+        assert!(!delegation
+            .includes_namespaces(&[b"team-a.".to_vec().into(), b"team-".to_vec().into()]));
+    }
+}
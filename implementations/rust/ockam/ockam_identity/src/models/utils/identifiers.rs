@@ -48,7 +48,7 @@ impl From<Identifier> for String {
 
 impl From<&Identifier> for String {
     fn from(id: &Identifier) -> Self {
-        format!("{}{}", Identifier::PREFIX, hex::encode(id.0.as_ref()))
+        format!("{}{}", Identifier::PREFIX, hex::encode(id.0))
     }
 }
 
@@ -131,7 +131,7 @@ impl From<ChangeHash> for String {
 
 impl From<&ChangeHash> for String {
     fn from(change_hash: &ChangeHash) -> Self {
-        hex::encode(change_hash.0.as_ref())
+        hex::encode(change_hash.0)
     }
 }
 
@@ -1,5 +1,7 @@
 mod change_history;
 mod credentials;
+mod delegation;
 mod identifiers;
 mod purpose_key_attestation;
+mod revocation_list;
 mod timestamp;
@@ -0,0 +1,123 @@
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+
+use ockam_core::compat::{string::String, vec::Vec};
+use ockam_core::{Error, Result};
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::models::{
+    CredentialHash, RevocationList, RevocationListData, VersionedData, CREDENTIAL_HASH_LEN,
+    REVOCATION_LIST_DATA_TYPE,
+};
+use crate::IdentityError;
+
+impl RevocationList {
+    /// Create [`VersionedData`] with corresponding version and data_type
+    pub fn create_versioned_data(data: Vec<u8>) -> VersionedData {
+        VersionedData {
+            version: 1,
+            data_type: REVOCATION_LIST_DATA_TYPE,
+            data,
+        }
+    }
+
+    /// Extract [`RevocationListData`]
+    pub fn get_revocation_list_data(&self) -> Result<RevocationListData> {
+        RevocationListData::get_data(&minicbor::decode(&self.data)?)
+    }
+}
+
+impl RevocationListData {
+    /// Extract [`RevocationListData`] from [`VersionedData`]
+    pub fn get_data(versioned_data: &VersionedData) -> Result<Self> {
+        if versioned_data.version != 1 {
+            return Err(IdentityError::UnknownRevocationListVersion.into());
+        }
+
+        if versioned_data.data_type != REVOCATION_LIST_DATA_TYPE {
+            return Err(IdentityError::InvalidRevocationListDataType.into());
+        }
+
+        Ok(minicbor::decode(&versioned_data.data)?)
+    }
+}
+
+impl Display for CredentialHash {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str(&String::from(self))
+    }
+}
+
+impl From<&CredentialHash> for String {
+    fn from(credential_hash: &CredentialHash) -> Self {
+        hex::encode(credential_hash.0)
+    }
+}
+
+impl Serialize for CredentialHash {
+    fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.serialize_str(&String::from(self))
+    }
+}
+
+impl<'de> Deserialize<'de> for CredentialHash {
+    fn deserialize<D>(deserializer: D) -> core::result::Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let str: String = Deserialize::deserialize(deserializer)?;
+
+        Self::try_from(str.as_str()).map_err(de::Error::custom)
+    }
+}
+
+impl TryFrom<&str> for CredentialHash {
+    type Error = Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        let value = value.trim();
+        if let Ok(data) = hex::decode(value) {
+            data.as_slice().try_into()
+        } else {
+            Err(IdentityError::InvalidHex.into())
+        }
+    }
+}
+
+impl TryFrom<&[u8]> for CredentialHash {
+    type Error = Error;
+
+    fn try_from(value: &[u8]) -> Result<Self, Self::Error> {
+        if let Ok(value) = <[u8; CREDENTIAL_HASH_LEN]>::try_from(value) {
+            Ok(Self(value))
+        } else {
+            Err(IdentityError::InvalidHex.into())
+        }
+    }
+}
+
+impl FromStr for CredentialHash {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.try_into()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_credential_hash_hex_roundtrip() {
+        let hash = CredentialHash([7; CREDENTIAL_HASH_LEN]);
+        let decoded = CredentialHash::from_str(&hash.to_string()).unwrap();
+        assert_eq!(decoded, hash);
+
+        assert!(CredentialHash::from_str("0102").is_err());
+        assert!(CredentialHash::from_str("not hex").is_err());
+    }
+}
@@ -1,9 +1,10 @@
 use ockam_core::compat::sync::Arc;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
+use tracing::warn;
 
 use crate::models::{
-    Identifier, PurposeKeyAttestation, PurposeKeyAttestationData, PurposePublicKey,
+    Identifier, PurposeKeyAttestation, PurposeKeyAttestationData, PurposePublicKey, VersionedData,
 };
 use crate::purpose_keys::storage::PurposeKeysRepository;
 use crate::{
@@ -300,6 +301,126 @@ impl PurposeKeyCreation {
     }
 }
 
+impl PurposeKeyCreation {
+    /// Attest again the purpose keys of an identity with its latest identity key, after that
+    /// key has been rotated. Purpose keys attested with a previous identity key are not accepted
+    /// by other parties.
+    ///
+    /// If the latest change of the identity revokes all the purpose keys, new purpose keys
+    /// are created and the previous ones are deleted from the vault.
+    /// Return the number of purpose keys which have been attested
+    pub async fn reissue_purpose_keys(&self, identifier: &Identifier) -> Result<usize> {
+        let identity = self.identities_creation.get_identity(identifier).await?;
+        let revoke_all_purpose_keys = identity.get_latest_change()?.data().revoke_all_purpose_keys;
+
+        let mut reissued = 0;
+        if let Some(attestation) = self
+            .repository
+            .get_purpose_key(identifier, Purpose::SecureChannel)
+            .await?
+        {
+            self.reissue_secure_channel_purpose_key(
+                identifier,
+                &attestation,
+                revoke_all_purpose_keys,
+            )
+            .await?;
+            reissued += 1;
+        }
+
+        if let Some(attestation) = self
+            .repository
+            .get_purpose_key(identifier, Purpose::Credentials)
+            .await?
+        {
+            self.reissue_credential_purpose_key(identifier, &attestation, revoke_all_purpose_keys)
+                .await?;
+            reissued += 1;
+        }
+
+        Ok(reissued)
+    }
+
+    async fn reissue_secure_channel_purpose_key(
+        &self,
+        identifier: &Identifier,
+        attestation: &PurposeKeyAttestation,
+        revoke: bool,
+    ) -> Result<SecureChannelPurposeKey> {
+        let public_key = match Self::get_attestation_data(attestation)?.public_key {
+            PurposePublicKey::SecureChannelStatic(public_key) => public_key,
+            PurposePublicKey::CredentialSigning(_) => {
+                return Err(IdentityError::InvalidKeyType.into())
+            }
+        };
+        let vault = &self.vault.secure_channel_vault;
+        let key = vault.get_x25519_secret_key_handle(&public_key).await.ok();
+
+        match key {
+            Some(key) if !revoke => {
+                self.secure_channel_purpose_key_builder(identifier)
+                    .with_existing_key(key)
+                    .build()
+                    .await
+            }
+            _ => {
+                let purpose_key = self.create_secure_channel_purpose_key(identifier).await?;
+                if let Some(key) = key {
+                    if vault.delete_static_x25519_secret_key(key).await.is_err() {
+                        warn!(
+                            "Error deleting the revoked secure channel purpose key of {identifier}"
+                        );
+                    }
+                }
+                Ok(purpose_key)
+            }
+        }
+    }
+
+    async fn reissue_credential_purpose_key(
+        &self,
+        identifier: &Identifier,
+        attestation: &PurposeKeyAttestation,
+        revoke: bool,
+    ) -> Result<CredentialPurposeKey> {
+        let public_key = match Self::get_attestation_data(attestation)?.public_key {
+            PurposePublicKey::SecureChannelStatic(_) => {
+                return Err(IdentityError::InvalidKeyType.into())
+            }
+            PurposePublicKey::CredentialSigning(public_key) => public_key.into(),
+        };
+        let vault = &self.vault.credential_vault;
+        let key = vault.get_secret_key_handle(&public_key).await.ok();
+
+        match key {
+            Some(key) if !revoke => {
+                self.credential_purpose_key_builder(identifier)
+                    .with_existing_key(key)
+                    .build()
+                    .await
+            }
+            _ => {
+                let purpose_key = self.create_credential_purpose_key(identifier).await?;
+                if let Some(key) = key {
+                    if vault.delete_signing_secret_key(key).await.is_err() {
+                        warn!("Error deleting the revoked credential purpose key of {identifier}");
+                    }
+                }
+                Ok(purpose_key)
+            }
+        }
+    }
+
+    /// Decode the data of one of our own attestations, without verifying it since it might have
+    /// been signed with a previous identity key
+    fn get_attestation_data(
+        attestation: &PurposeKeyAttestation,
+    ) -> Result<PurposeKeyAttestationData> {
+        let versioned_data: VersionedData = minicbor::decode(&attestation.data)?;
+        PurposeKeyAttestationData::get_data(&versioned_data)
+    }
+}
+
 impl PurposeKeyCreation {
     /// Get the [`super::super::super::purpose_key::PurposeKey`]
     /// for given [`Identifier`] and [`Purpose`]
@@ -45,7 +45,7 @@ impl PurposeKeysRepository for PurposeKeysSqlxDatabase {
         purpose: Purpose,
         purpose_key_attestation: &PurposeKeyAttestation,
     ) -> Result<()> {
-        let query = query("INSERT OR REPLACE INTO purpose_key VALUES (?, ?, ?)")
+        let query = query("INSERT INTO purpose_key VALUES ($1, $2, $3) ON CONFLICT (identifier, purpose) DO UPDATE SET purpose_key_attestation = $3")
             .bind(subject.to_sql())
             .bind(purpose.to_sql())
             .bind(minicbor::to_vec(purpose_key_attestation)?.to_sql());
@@ -53,7 +53,7 @@ impl PurposeKeysRepository for PurposeKeysSqlxDatabase {
     }
 
     async fn delete_purpose_key(&self, subject: &Identifier, purpose: Purpose) -> Result<()> {
-        let query = query("DELETE FROM purpose_key WHERE identifier = ? and purpose = ?")
+        let query = query("DELETE FROM purpose_key WHERE identifier = $1 and purpose = $2")
             .bind(subject.to_sql())
             .bind(purpose.to_sql());
         query.execute(&self.database.pool).await.void()
@@ -129,51 +129,55 @@ mod tests {
     use super::*;
     use crate::identities;
     use crate::models::PurposeKeyAttestationSignature;
+    use ockam_node::database::with_dbs;
     use ockam_vault::ECDSASHA256CurveP256Signature;
 
     #[tokio::test]
     async fn test_purpose_keys_repository() -> Result<()> {
-        let repository = create_repository().await?;
-
-        // A purpose key can be stored and retrieved, given the owning identifier and purpose type
-        let identity1 = create_identity().await?;
-        let attestation1 = PurposeKeyAttestation {
-            data: vec![1, 2, 3],
-            signature: PurposeKeyAttestationSignature::ECDSASHA256CurveP256(
-                ECDSASHA256CurveP256Signature([1; 64]),
-            ),
-        };
-        repository
-            .set_purpose_key(&identity1, Purpose::Credentials, &attestation1)
-            .await?;
-
-        let result = repository
-            .get_purpose_key(&identity1, Purpose::Credentials)
-            .await?;
-        assert_eq!(result, Some(attestation1));
-
-        // the attestation can be updated
-        let attestation2 = PurposeKeyAttestation {
-            data: vec![4, 5, 6],
-            signature: PurposeKeyAttestationSignature::ECDSASHA256CurveP256(
-                ECDSASHA256CurveP256Signature([1; 64]),
-            ),
-        };
-        repository
-            .set_purpose_key(&identity1, Purpose::Credentials, &attestation2)
-            .await?;
-
-        let result = repository
-            .get_purpose_key(&identity1, Purpose::Credentials)
-            .await?;
-        assert_eq!(result, Some(attestation2));
-
-        Ok(())
+        with_dbs(|db| async move {
+            let repository = create_repository(db);
+
+            // A purpose key can be stored and retrieved, given the owning identifier and purpose type
+            let identity1 = create_identity().await?;
+            let attestation1 = PurposeKeyAttestation {
+                data: vec![1, 2, 3],
+                signature: PurposeKeyAttestationSignature::ECDSASHA256CurveP256(
+                    ECDSASHA256CurveP256Signature([1; 64]),
+                ),
+            };
+            repository
+                .set_purpose_key(&identity1, Purpose::Credentials, &attestation1)
+                .await?;
+
+            let result = repository
+                .get_purpose_key(&identity1, Purpose::Credentials)
+                .await?;
+            assert_eq!(result, Some(attestation1));
+
+            // the attestation can be updated
+            let attestation2 = PurposeKeyAttestation {
+                data: vec![4, 5, 6],
+                signature: PurposeKeyAttestationSignature::ECDSASHA256CurveP256(
+                    ECDSASHA256CurveP256Signature([1; 64]),
+                ),
+            };
+            repository
+                .set_purpose_key(&identity1, Purpose::Credentials, &attestation2)
+                .await?;
+
+            let result = repository
+                .get_purpose_key(&identity1, Purpose::Credentials)
+                .await?;
+            assert_eq!(result, Some(attestation2));
+
+            Ok(())
+        })
+        .await
     }
 
     /// HELPERS
-    async fn create_repository() -> Result<Arc<dyn PurposeKeysRepository>> {
-        Ok(PurposeKeysSqlxDatabase::create().await?)
+    fn create_repository(db: Arc<SqlxDatabase>) -> Arc<dyn PurposeKeysRepository> {
+        Arc::new(PurposeKeysSqlxDatabase::new(db))
     }
 
     async fn create_identity() -> Result<Identifier> {
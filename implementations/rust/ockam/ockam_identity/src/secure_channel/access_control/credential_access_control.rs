@@ -5,13 +5,16 @@ use ockam_core::Result;
 use ockam_core::{async_trait, RelayMessage};
 
 use crate::secure_channel::local_info::IdentitySecureChannelLocalInfo;
-use crate::IdentityAttributesRepository;
+use crate::{IdentityAttributesRepository, RevocationsRepository};
 
 /// Access control checking that message senders have a specific set of attributes
+/// and that the authority which attested those attributes has not revoked the sender
+/// or the credential carrying them
 #[derive(Clone)]
 pub struct CredentialAccessControl {
     required_attributes: Vec<(Vec<u8>, Vec<u8>)>,
     identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+    revocations_repository: Arc<dyn RevocationsRepository>,
 }
 
 impl CredentialAccessControl {
@@ -19,12 +22,24 @@ impl CredentialAccessControl {
     pub fn new(
         required_attributes: &[(Vec<u8>, Vec<u8>)],
         identity_attributes_repository: Arc<dyn IdentityAttributesRepository>,
+        revocations_repository: Arc<dyn RevocationsRepository>,
     ) -> Self {
         Self {
             required_attributes: required_attributes.to_vec(),
             identity_attributes_repository,
+            revocations_repository,
         }
     }
+
+    /// Keys of the attributes required by this access control.
+    /// They can be requested from the selective-disclosure credentials of the peers, see
+    /// [`crate::SecureChannelListenerOptions::with_requested_attributes`]
+    pub fn requested_attributes(&self) -> Vec<Vec<u8>> {
+        self.required_attributes
+            .iter()
+            .map(|(key, _)| key.clone())
+            .collect()
+    }
 }
 
 impl Debug for CredentialAccessControl {
@@ -43,15 +58,36 @@ impl IncomingAccessControl for CredentialAccessControl {
         if let Ok(msg_identity_id) =
             IdentitySecureChannelLocalInfo::find_info(relay_message.local_message())
         {
+            let their_identity_id = msg_identity_id.their_identity_id();
             let attributes = match self
                 .identity_attributes_repository
-                .get_attributes(&msg_identity_id.their_identity_id())
+                .get_attributes(&their_identity_id)
                 .await?
             {
                 Some(a) => a,
                 None => return Ok(false), // No attributes for that Identity
             };
 
+            if let Some(attested_by) = attributes.attested_by() {
+                if self
+                    .revocations_repository
+                    .is_subject_revoked(&attested_by, &their_identity_id)
+                    .await?
+                {
+                    return Ok(false); // The Identity was revoked by the Authority
+                }
+
+                if let Some(credential_hash) = attributes.credential_hash() {
+                    if self
+                        .revocations_repository
+                        .is_credential_revoked(&attested_by, credential_hash)
+                        .await?
+                    {
+                        return Ok(false); // The Credential was revoked by the Authority
+                    }
+                }
+            }
+
             for required_attribute in self.required_attributes.iter() {
                 let attr_val = match attributes.attrs().get(&required_attribute.0) {
                     Some(v) => v,
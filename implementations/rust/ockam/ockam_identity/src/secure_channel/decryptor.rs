@@ -1,4 +1,5 @@
 use core::sync::atomic::{AtomicBool, Ordering};
+use ockam_core::compat::collections::BTreeMap;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::{Any, Result, Routed, TransportMessage};
@@ -10,11 +11,14 @@ use crate::secure_channel::encryptor::{Encryptor, KEY_RENEWAL_INTERVAL};
 use crate::secure_channel::handshake::handshake_state_machine::CommonStateMachine;
 use crate::secure_channel::key_tracker::KeyTracker;
 use crate::secure_channel::nonce_tracker::NonceTracker;
+use crate::secure_channel::rekey::{EncryptorInternalMessage, Rekeying};
 use crate::secure_channel::Addresses;
+use crate::utils::now;
 use crate::{
     DecryptionRequest, DecryptionResponse, Identities, IdentityError,
     IdentitySecureChannelLocalInfo, PlaintextPayloadMessage, RefreshCredentialsMessage,
-    SecureChannelMessage, TrustContext,
+    RekeyConfirmMessage, RekeyRequestMessage, RekeyResponseMessage, SecureChannelMessage,
+    SecureChannelStatistics, TrustContext,
 };
 
 use ockam_vault::{AeadSecretKeyHandle, VaultForSecureChannels};
@@ -30,6 +34,8 @@ pub(crate) struct DecryptorHandler {
     identities: Arc<Identities>,
     trust_context: Option<TrustContext>,
     should_send_close: Arc<AtomicBool>,
+    rekeying: Rekeying,
+    statistics: Arc<SecureChannelStatistics>,
 }
 
 impl DecryptorHandler {
@@ -43,6 +49,8 @@ impl DecryptorHandler {
         vault: Arc<dyn VaultForSecureChannels>,
         their_identity_id: Identifier,
         should_send_close: Arc<AtomicBool>,
+        rekeying: Rekeying,
+        statistics: Arc<SecureChannelStatistics>,
     ) -> Self {
         Self {
             role,
@@ -52,6 +60,8 @@ impl DecryptorHandler {
             identities,
             trust_context,
             should_send_close,
+            rekeying,
+            statistics,
         }
     }
 
@@ -71,10 +81,13 @@ impl DecryptorHandler {
         let request = DecryptionRequest::decode(&msg.into_transport_message().payload)?;
 
         // Decrypt the binary
-        let decrypted_payload = self.decryptor.decrypt(&request.0).await;
+        let decrypted_payload = self.decrypt(&request.0).await;
 
         let response = match decrypted_payload {
-            Ok(payload) => DecryptionResponse::Ok(payload),
+            Ok(payload) => {
+                self.record_decryption();
+                DecryptionResponse::Ok(payload)
+            }
             Err(err) => DecryptionResponse::Err(err),
         };
 
@@ -156,6 +169,93 @@ impl DecryptorHandler {
         Ok(())
     }
 
+    async fn handle_rekey_request(
+        &mut self,
+        ctx: &mut Context,
+        msg: RekeyRequestMessage,
+    ) -> Result<()> {
+        debug!(
+            "Handling re-key request {} for {}",
+            msg.epoch, self.addresses.decryptor_remote
+        );
+
+        if self.rekeying.handle_request(msg).await? {
+            self.send_rekey_messages(ctx).await?;
+        }
+        Ok(())
+    }
+
+    async fn handle_rekey_response(
+        &mut self,
+        ctx: &mut Context,
+        msg: RekeyResponseMessage,
+    ) -> Result<()> {
+        debug!(
+            "Handling re-key response {} for {}",
+            msg.epoch, self.addresses.decryptor_remote
+        );
+
+        if let Some((switch_at, key)) = self.rekeying.handle_response(msg).await? {
+            self.decryptor.schedule_key(switch_at, key).await?;
+            self.statistics.record_rekey(now()?);
+            self.send_rekey_messages(ctx).await?;
+            info!("Re-keyed SecureChannel {}", self.addresses.decryptor_remote);
+        }
+        Ok(())
+    }
+
+    async fn handle_rekey_confirm(&mut self, msg: RekeyConfirmMessage) -> Result<()> {
+        debug!(
+            "Handling re-key confirmation {} for {}",
+            msg.epoch, self.addresses.decryptor_remote
+        );
+
+        if let Some((switch_at, key)) = self.rekeying.handle_confirm(msg) {
+            self.decryptor.schedule_key(switch_at, key).await?;
+            self.statistics.record_rekey(now()?);
+            info!("Re-keyed SecureChannel {}", self.addresses.decryptor_remote);
+        }
+        Ok(())
+    }
+
+    /// Ask the encryptor to send the re-key messages prepared by the decryptor
+    async fn send_rekey_messages(&self, ctx: &Context) -> Result<()> {
+        ctx.send_from_address(
+            self.addresses.encryptor_internal.clone(),
+            EncryptorInternalMessage::SendRekeyMessages,
+            self.addresses.decryptor_remote.clone(),
+        )
+        .await
+    }
+
+    /// Decrypt a message from the other side.
+    /// If the other side switched to the key of a re-key that we answered, before we received
+    /// its confirmation, that re-key is now confirmed
+    async fn decrypt(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        let unconfirmed_key = self.rekeying.unconfirmed_key();
+        let (plaintext, used_unconfirmed_key) = self
+            .decryptor
+            .decrypt_with_unconfirmed_key(payload, unconfirmed_key.as_ref())
+            .await?;
+        if let Some(key) = unconfirmed_key.filter(|_| used_unconfirmed_key) {
+            if let Some(epoch) = self.rekeying.handle_implicit_confirm(&key) {
+                self.statistics.record_rekey(now()?);
+                info!(
+                    "Re-keyed SecureChannel {}, the confirmation of the re-key {} was lost",
+                    self.addresses.decryptor_remote, epoch
+                );
+            }
+        }
+        Ok(plaintext)
+    }
+
+    fn record_decryption(&self) {
+        self.statistics.record_decryption(
+            self.decryptor.number_of_rekeys(),
+            self.decryptor.keys_exhausted(),
+        );
+    }
+
     pub(crate) async fn handle_decrypt(
         &mut self,
         ctx: &mut Context,
@@ -171,7 +271,8 @@ impl DecryptorHandler {
         let payload = Vec::<u8>::decode(&payload)?;
 
         // Decrypt the binary
-        let decrypted_payload = self.decryptor.decrypt(&payload).await?;
+        let decrypted_payload = self.decrypt(&payload).await?;
+        self.record_decryption();
 
         let msg: SecureChannelMessage = minicbor::decode(&decrypted_payload)?;
 
@@ -181,6 +282,11 @@ impl DecryptorHandler {
                 self.handle_refresh_credentials(ctx, msg).await?
             }
             SecureChannelMessage::Close => self.handle_close(ctx).await?,
+            SecureChannelMessage::RekeyRequest(msg) => self.handle_rekey_request(ctx, msg).await?,
+            SecureChannelMessage::RekeyResponse(msg) => {
+                self.handle_rekey_response(ctx, msg).await?
+            }
+            SecureChannelMessage::RekeyConfirm(msg) => self.handle_rekey_confirm(msg).await?,
         };
 
         Ok(())
@@ -196,6 +302,8 @@ pub(crate) struct Decryptor {
     vault: Arc<dyn VaultForSecureChannels>,
     key_tracker: KeyTracker,
     nonce_tracker: NonceTracker,
+    /// Keys obtained with an explicit re-key, indexed by the nonce starting from which they must be used
+    scheduled_keys: BTreeMap<u64, AeadSecretKeyHandle>,
 }
 
 impl Decryptor {
@@ -204,9 +312,41 @@ impl Decryptor {
             vault,
             key_tracker: KeyTracker::new(key, KEY_RENEWAL_INTERVAL),
             nonce_tracker: NonceTracker::new(),
+            scheduled_keys: BTreeMap::new(),
         }
     }
 
+    /// Use a new key, obtained with an explicit re-key, for the messages starting at a given nonce.
+    /// That nonce must be the start of a key renewal interval
+    pub(crate) async fn schedule_key(
+        &mut self,
+        switch_at: u64,
+        key: AeadSecretKeyHandle,
+    ) -> Result<()> {
+        if switch_at % KEY_RENEWAL_INTERVAL != 0
+            || switch_at < self.key_tracker.next_interval_start()
+        {
+            warn!("cannot use a new key starting at nonce {}", switch_at);
+            self.vault.delete_aead_secret_key(key).await?;
+            return Err(IdentityError::InvalidNonce.into());
+        }
+
+        if let Some(previous_key) = self.scheduled_keys.insert(switch_at, key) {
+            self.vault.delete_aead_secret_key(previous_key).await?;
+        }
+        Ok(())
+    }
+
+    /// Number of key renewals which happened so far
+    pub(crate) fn number_of_rekeys(&self) -> u64 {
+        self.key_tracker.number_of_rekeys()
+    }
+
+    /// True if no more messages can be decrypted
+    pub(crate) fn keys_exhausted(&self) -> bool {
+        self.key_tracker.max_rekeys_reached()
+    }
+
     /// Restore 12-byte nonce needed for AES GCM from 8 byte that we use for noise
     fn convert_nonce_from_small(b: &[u8]) -> Result<(u64, [u8; 12])> {
         let bytes: [u8; 8] = b.try_into().map_err(|_| IdentityError::InvalidNonce)?;
@@ -216,7 +356,21 @@ impl Decryptor {
         Ok((nonce, Encryptor::convert_nonce_from_u64(nonce).1))
     }
 
+    /// Decrypt a message, without any key of an unconfirmed re-key
+    #[cfg(test)]
     pub async fn decrypt(&mut self, payload: &[u8]) -> Result<Vec<u8>> {
+        Ok(self.decrypt_with_unconfirmed_key(payload, None).await?.0)
+    }
+
+    /// Decrypt a message. The first message of a new key renewal interval which can not be
+    /// decrypted with the renewed key is decrypted with the `unconfirmed_key` of an explicit
+    /// re-key, if the other side switched to it before we received its confirmation.
+    /// Return the plaintext and true if the unconfirmed key is now the current key
+    pub(crate) async fn decrypt_with_unconfirmed_key(
+        &mut self,
+        payload: &[u8],
+        unconfirmed_key: Option<&AeadSecretKeyHandle>,
+    ) -> Result<(Vec<u8>, bool)> {
         if payload.len() < 8 {
             return Err(IdentityError::InvalidNonce.into());
         }
@@ -226,26 +380,49 @@ impl Decryptor {
 
         // get the key corresponding to the current nonce and
         // rekey if necessary
-        let key = if let Some(key) = self.key_tracker.get_key(nonce)? {
-            key
-        } else {
-            Encryptor::rekey(&self.vault, &self.key_tracker.current_key).await?
-        };
+        // use the key obtained with an explicit re-key if there is one for the next interval
+        let next_interval_start = self.key_tracker.next_interval_start();
+        let (mut key, is_new_key, is_renewed_key) =
+            if let Some(key) = self.key_tracker.get_key(nonce)? {
+                (key, false, false)
+            } else if let Some(key) = self.scheduled_keys.get(&next_interval_start) {
+                (key.clone(), true, false)
+            } else {
+                let key = Encryptor::rekey(&self.vault, &self.key_tracker.current_key).await?;
+                (key, true, true)
+            };
 
         // to improve protection against connection disruption attacks, we want to validate the
         // message with a decryption _before_ committing to the new state
-        let result = self
+        let mut result = self
             .vault
             .aead_decrypt(&key, &payload[8..], &nonce_buffer, &[])
             .await;
 
+        let mut used_unconfirmed_key = false;
+        if let (Err(_), true, Some(unconfirmed_key)) = (&result, is_renewed_key, unconfirmed_key) {
+            let unconfirmed_result = self
+                .vault
+                .aead_decrypt(unconfirmed_key, &payload[8..], &nonce_buffer, &[])
+                .await;
+            if unconfirmed_result.is_ok() {
+                self.vault.delete_aead_secret_key(key).await?;
+                key = unconfirmed_key.clone();
+                result = unconfirmed_result;
+                used_unconfirmed_key = true;
+            }
+        }
+
         if result.is_ok() {
             self.nonce_tracker = nonce_tracker;
+            if is_new_key {
+                self.scheduled_keys.remove(&next_interval_start);
+            }
             if let Some(key_to_delete) = self.key_tracker.update_key(key)? {
                 self.vault.delete_aead_secret_key(key_to_delete).await?;
             }
         }
-        result
+        Ok((result?, used_unconfirmed_key))
     }
 
     /// Remove the channel keys on shutdown
@@ -256,6 +433,11 @@ impl Decryptor {
         if let Some(previous_key) = self.key_tracker.previous_key.clone() {
             self.vault.delete_aead_secret_key(previous_key).await?;
         };
+        for scheduled_key in self.scheduled_keys.values() {
+            self.vault
+                .delete_aead_secret_key(scheduled_key.clone())
+                .await?;
+        }
         Ok(())
     }
 }
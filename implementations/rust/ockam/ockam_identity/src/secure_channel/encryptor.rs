@@ -1,3 +1,4 @@
+use ockam_core::compat::collections::BTreeMap;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::errcode::{Kind, Origin};
@@ -10,6 +11,8 @@ pub(crate) struct Encryptor {
     key: AeadSecretKeyHandle,
     nonce: u64,
     vault: Arc<dyn VaultForSecureChannels>,
+    /// Keys obtained with an explicit re-key, indexed by the nonce starting from which they must be used
+    scheduled_keys: BTreeMap<u64, AeadSecretKeyHandle>,
 }
 
 // To simplify the implementation we use the same constant for the size of the message
@@ -55,7 +58,10 @@ impl Encryptor {
         self.nonce += 1;
 
         if current_nonce > 0 && current_nonce % KEY_RENEWAL_INTERVAL == 0 {
-            let new_key = Self::rekey(&self.vault, &self.key).await?;
+            let new_key = match self.scheduled_keys.remove(&current_nonce) {
+                Some(key) => key,
+                None => Self::rekey(&self.vault, &self.key).await?,
+            };
             let old_key = core::mem::replace(&mut self.key, new_key);
             self.vault.delete_aead_secret_key(old_key).await?;
         }
@@ -74,15 +80,43 @@ impl Encryptor {
         Ok(res)
     }
 
+    /// Use a new key, obtained with an explicit re-key, starting at the next key renewal.
+    /// Return the nonce of the first message encrypted with that key
+    pub(crate) async fn schedule_key(&mut self, key: AeadSecretKeyHandle) -> Result<u64> {
+        let switch_at = (self.nonce / KEY_RENEWAL_INTERVAL + 1)
+            .checked_mul(KEY_RENEWAL_INTERVAL)
+            .ok_or(IdentityError::NonceOverflow)?;
+
+        if let Some(previous_key) = self.scheduled_keys.insert(switch_at, key) {
+            self.vault.delete_aead_secret_key(previous_key).await?;
+        }
+        Ok(switch_at)
+    }
+
+    /// Nonce of the next encrypted message
+    pub(crate) fn nonce(&self) -> u64 {
+        self.nonce
+    }
+
     pub fn new(
         key: AeadSecretKeyHandle,
         nonce: u64,
         vault: Arc<dyn VaultForSecureChannels>,
     ) -> Self {
-        Self { key, nonce, vault }
+        Self {
+            key,
+            nonce,
+            vault,
+            scheduled_keys: BTreeMap::new(),
+        }
     }
 
     pub(crate) async fn shutdown(&self) -> Result<()> {
+        for scheduled_key in self.scheduled_keys.values() {
+            self.vault
+                .delete_aead_secret_key(scheduled_key.clone())
+                .await?;
+        }
         if !self.vault.delete_aead_secret_key(self.key.clone()).await? {
             Err(Error::new(
                 Origin::Ockam,
@@ -16,10 +16,14 @@ use crate::models::{CredentialData, VersionedData};
 use crate::secure_channel::addresses::Addresses;
 use crate::secure_channel::api::{EncryptionRequest, EncryptionResponse};
 use crate::secure_channel::encryptor::Encryptor;
+use crate::secure_channel::rekey::{
+    EncryptorInternalMessage, OutgoingRekeyMessage, RekeyTriggers, Rekeying,
+};
 use crate::utils::now;
 use crate::{
     ChangeHistoryRepository, Identifier, IdentityError, PlaintextPayloadMessage,
-    RefreshCredentialsMessage, SecureChannelMessage, TimestampInSeconds, TrustContext,
+    RefreshCredentialsMessage, RekeyConfirmMessage, RekeyResponseMessage, SecureChannelMessage,
+    SecureChannelStatistics, TimestampInSeconds, TrustContext,
 };
 
 pub(crate) struct EncryptorWorker {
@@ -38,11 +42,20 @@ pub(crate) struct EncryptorWorker {
     /// The time interval before the credential expiration when we'll ask the credential retriever
     /// for a new one
     refresh_credential_time_gap: Duration,
-    credential_refresh_event: Option<DelayedEvent<()>>,
+    credential_refresh_event: Option<DelayedEvent<EncryptorInternalMessage>>,
     // TODO: Should be CredentialsRetriever
     trust_context: Option<TrustContext>,
+    /// Attributes requested by the other party from our selective-disclosure credentials
+    their_requested_attributes: Option<Vec<Vec<u8>>>,
 
     should_send_close: Arc<AtomicBool>,
+
+    rekeying: Rekeying,
+    rekey_triggers: RekeyTriggers,
+    /// Number of messages encrypted since the last re-key was started
+    messages_since_rekey: u64,
+    rekey_event: Option<DelayedEvent<EncryptorInternalMessage>>,
+    statistics: Arc<SecureChannelStatistics>,
 }
 
 impl EncryptorWorker {
@@ -58,7 +71,11 @@ impl EncryptorWorker {
         min_credential_refresh_interval: Duration,
         refresh_credential_time_gap: Duration,
         trust_context: Option<TrustContext>,
+        their_requested_attributes: Option<Vec<Vec<u8>>>,
         should_send_close: Arc<AtomicBool>,
+        rekeying: Rekeying,
+        rekey_triggers: RekeyTriggers,
+        statistics: Arc<SecureChannelStatistics>,
     ) -> Self {
         Self {
             role,
@@ -72,14 +89,23 @@ impl EncryptorWorker {
             refresh_credential_time_gap,
             credential_refresh_event: None,
             trust_context,
+            their_requested_attributes,
             should_send_close,
+            rekeying,
+            rekey_triggers,
+            messages_since_rekey: 0,
+            rekey_event: None,
+            statistics,
         }
     }
 
     /// Encrypt the message
     async fn encrypt(&mut self, ctx: &Context, msg: SecureChannelMessage) -> Result<Vec<u8>> {
         match self.encryptor.encrypt(&minicbor::to_vec(&msg)?).await {
-            Ok(encrypted_payload) => Ok(encrypted_payload),
+            Ok(encrypted_payload) => {
+                self.statistics.record_encryption(self.encryptor.nonce());
+                Ok(encrypted_payload)
+            }
             // If encryption failed, that means we have some internal error,
             // and we may be in an invalid state, it's better to stop the Worker
             Err(err) => {
@@ -110,7 +136,10 @@ impl EncryptorWorker {
 
         // Encrypt the message
         let response = match self.encryptor.encrypt(&request.0).await {
-            Ok(encrypted_payload) => EncryptionResponse::Ok(encrypted_payload),
+            Ok(encrypted_payload) => {
+                self.statistics.record_encryption(self.encryptor.nonce());
+                EncryptionResponse::Ok(encrypted_payload)
+            }
             // If encryption failed, that means we have some internal error,
             // and we may be in an invalid state, it's better to stop the Worker
             Err(err) => {
@@ -129,6 +158,8 @@ impl EncryptorWorker {
 
         if should_stop {
             ctx.stop_worker(self.addresses.encryptor.clone()).await?;
+        } else {
+            self.count_message_for_rekey(ctx).await?;
         }
 
         Ok(())
@@ -167,6 +198,101 @@ impl EncryptorWorker {
         )
         .await?;
 
+        self.count_message_for_rekey(ctx).await
+    }
+
+    /// Encrypt a message and send it to the decryptor on the other side
+    async fn send_message(&mut self, ctx: &Context, msg: SecureChannelMessage) -> Result<()> {
+        let msg = self.encrypt(ctx, msg).await?;
+
+        ctx.send_from_address(
+            self.remote_route.clone(),
+            msg,
+            self.addresses.encryptor.clone(),
+        )
+        .await
+    }
+
+    /// Start a re-key once the configured number of messages has been encrypted
+    async fn count_message_for_rekey(&mut self, ctx: &Context) -> Result<()> {
+        self.messages_since_rekey += 1;
+        match self.rekey_triggers.message_count {
+            Some(message_count) if self.messages_since_rekey >= message_count => {
+                self.start_rekey(ctx).await
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Send a re-key request to the other side, unless a re-key is already in progress
+    async fn start_rekey(&mut self, ctx: &Context) -> Result<()> {
+        self.messages_since_rekey = 0;
+        if let Some(request) = self.rekeying.start().await? {
+            info!(
+                "Starting re-key {} for {}",
+                request.epoch, self.addresses.encryptor
+            );
+            self.send_message(ctx, SecureChannelMessage::RekeyRequest(request))
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Send the re-key messages prepared by the decryptor and start using the new encryption key
+    async fn send_rekey_messages(&mut self, ctx: &Context) -> Result<()> {
+        for message in self.rekeying.take_outgoing() {
+            let msg = match message {
+                OutgoingRekeyMessage::Response {
+                    epoch,
+                    public_key,
+                    encryption_key,
+                } => {
+                    let switch_at = self.encryptor.schedule_key(encryption_key).await?;
+                    SecureChannelMessage::RekeyResponse(RekeyResponseMessage {
+                        epoch,
+                        public_key,
+                        switch_at,
+                    })
+                }
+                OutgoingRekeyMessage::Confirm {
+                    epoch,
+                    encryption_key,
+                } => {
+                    let switch_at = self.encryptor.schedule_key(encryption_key).await?;
+                    SecureChannelMessage::RekeyConfirm(RekeyConfirmMessage { epoch, switch_at })
+                }
+            };
+            self.send_message(ctx, msg).await?;
+        }
+        Ok(())
+    }
+
+    /// Schedule a DelayedEvent triggering the next periodic re-key
+    async fn schedule_rekey(&mut self, ctx: &Context) -> Result<()> {
+        let interval = if let Some(interval) = self.rekey_triggers.interval {
+            interval
+        } else {
+            return Ok(());
+        };
+
+        // Cancel the old event
+        self.rekey_event = None;
+
+        debug!(
+            "Scheduling re-key for {} in {} seconds",
+            self.addresses.encryptor,
+            interval.as_secs()
+        );
+        let mut rekey_event = DelayedEvent::create(
+            ctx,
+            self.addresses.encryptor_internal.clone(),
+            EncryptorInternalMessage::Rekey,
+        )
+        .await?;
+        rekey_event.schedule(interval).await?;
+
+        self.rekey_event = Some(rekey_event);
+
         Ok(())
     }
 
@@ -226,6 +352,11 @@ impl EncryptorWorker {
         let data = CredentialData::get_data(&versioned_data)?;
         self.min_credential_expiration = Some(data.expires_at);
 
+        let credential = match &self.their_requested_attributes {
+            Some(requested_attributes) => credential.disclose(requested_attributes),
+            None => credential,
+        };
+
         let msg = RefreshCredentialsMessage {
             change_history,
             credentials: vec![credential],
@@ -310,8 +441,12 @@ impl EncryptorWorker {
             self.addresses.encryptor,
             duration.as_secs()
         );
-        let mut credential_refresh_event =
-            DelayedEvent::create(ctx, self.addresses.encryptor_internal.clone(), ()).await?;
+        let mut credential_refresh_event = DelayedEvent::create(
+            ctx,
+            self.addresses.encryptor_internal.clone(),
+            EncryptorInternalMessage::RefreshCredentials,
+        )
+        .await?;
         credential_refresh_event.schedule(duration).await?;
 
         self.credential_refresh_event = Some(credential_refresh_event);
@@ -326,7 +461,8 @@ impl Worker for EncryptorWorker {
     type Context = Context;
 
     async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
-        self.schedule_credentials_refresh(ctx, false).await
+        self.schedule_credentials_refresh(ctx, false).await?;
+        self.schedule_rekey(ctx).await
     }
 
     async fn handle_message(
@@ -341,7 +477,19 @@ impl Worker for EncryptorWorker {
         } else if msg_addr == self.addresses.encryptor_api {
             self.handle_encrypt_api(ctx, msg).await?;
         } else if msg_addr == self.addresses.encryptor_internal {
-            self.handle_refresh_credentials(ctx).await?;
+            let msg = EncryptorInternalMessage::decode(&msg.into_transport_message().payload)?;
+            match msg {
+                EncryptorInternalMessage::RefreshCredentials => {
+                    self.handle_refresh_credentials(ctx).await?
+                }
+                EncryptorInternalMessage::Rekey => {
+                    self.start_rekey(ctx).await?;
+                    self.schedule_rekey(ctx).await?
+                }
+                EncryptorInternalMessage::SendRekeyMessages => {
+                    self.send_rekey_messages(ctx).await?
+                }
+            }
         } else {
             return Err(IdentityError::UnknownChannelMsgDestination.into());
         }
@@ -356,6 +504,7 @@ impl Worker for EncryptorWorker {
         if self.should_send_close.load(Ordering::Relaxed) {
             let _ = self.send_close_channel(context).await;
         }
+        self.rekeying.shutdown().await?;
         self.encryptor.shutdown().await
     }
 }
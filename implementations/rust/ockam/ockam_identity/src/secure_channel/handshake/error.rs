@@ -16,6 +16,8 @@ pub enum XXError {
     ExceededMaxMessageLen,
     /// Invalid internal state.
     InvalidInternalState,
+    /// A hybrid key exchange is required but the other party does not support it.
+    HybridKeyExchangeRequired,
 }
 
 impl StdError for XXError {}
@@ -29,6 +31,12 @@ impl fmt::Display for XXError {
                 write!(f, "exceeded maximum allowed message length for noise")
             }
             Self::InvalidInternalState => write!(f, "invalid internal state"),
+            Self::HybridKeyExchangeRequired => {
+                write!(
+                    f,
+                    "a hybrid key exchange is required but not supported by the other party"
+                )
+            }
         }
     }
 }
@@ -41,6 +49,7 @@ impl From<XXError> for Error {
             XXError::MessageLenMismatch => Kind::Misuse,
             XXError::ExceededMaxMessageLen => Kind::Invalid,
             XXError::InvalidInternalState => Kind::Internal,
+            XXError::HybridKeyExchangeRequired => Kind::Unsupported,
         };
 
         Error::new(Origin::KeyExchange, kind, err)
@@ -4,8 +4,9 @@ use ockam_core::compat::vec::Vec;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
 use ockam_vault::{
-    AeadSecretKeyHandle, HKDFNumberOfOutputs, SecretBufferHandle, VaultForSecureChannels,
-    X25519PublicKey, X25519SecretKeyHandle, X25519_PUBLIC_KEY_LENGTH,
+    AeadSecretKeyHandle, HKDFNumberOfOutputs, MlKemCiphertext, MlKemPublicKey,
+    MlKemSecretKeyHandle, SecretBufferHandle, VaultForSecureChannels, X25519PublicKey,
+    X25519SecretKeyHandle, X25519_PUBLIC_KEY_LENGTH,
 };
 use sha2::{Digest, Sha256};
 use Status::*;
@@ -215,6 +216,56 @@ impl Handshake {
         Ok(payload)
     }
 
+    /// Generate an ephemeral ML-KEM key, used by the initiator for a hybrid key exchange,
+    /// and return its public key
+    pub(super) async fn generate_ml_kem_key(&mut self) -> Result<MlKemPublicKey> {
+        let key = self.vault.generate_ephemeral_ml_kem_secret_key().await?;
+        let public_key = self.vault.get_ml_kem_public_key(&key).await?;
+        self.state.ml_kem_key = Some(key);
+        Ok(public_key)
+    }
+
+    /// Encapsulate a secret for the initiator ML-KEM public key and return the ciphertext.
+    /// The secret is mixed in the chaining key with `mix_ml_kem_secret` once message 2 has been encoded
+    pub(super) async fn encapsulate_ml_kem_secret(
+        &mut self,
+        public_key: &MlKemPublicKey,
+    ) -> Result<MlKemCiphertext> {
+        let (secret, ciphertext) = self.vault.ml_kem_encapsulate(public_key).await?;
+        self.state.ml_kem_secret = Some(secret);
+        Ok(ciphertext)
+    }
+
+    /// Decapsulate the secret sent by the responder in message 2
+    pub(super) async fn decapsulate_ml_kem_secret(
+        &mut self,
+        ciphertext: &MlKemCiphertext,
+    ) -> Result<()> {
+        let key = self
+            .state
+            .ml_kem_key
+            .take()
+            .ok_or(XXError::InvalidInternalState)?;
+        let secret = self.vault.ml_kem_decapsulate(&key, ciphertext).await;
+        self.vault.delete_ephemeral_ml_kem_secret_key(key).await?;
+        self.state.ml_kem_secret = Some(secret?);
+        Ok(())
+    }
+
+    /// ck, k = HKDF(ck, ss, 2) where ss is the secret shared with ML-KEM
+    /// This is done by both parties right after message 2, so that the keys used to encrypt
+    /// message 3 and the transport messages depend on both the X25519 and the ML-KEM secrets
+    pub(super) async fn mix_ml_kem_secret(&mut self) -> Result<()> {
+        let mut state = self.state.clone();
+        let secret = state
+            .ml_kem_secret
+            .take()
+            .ok_or(XXError::InvalidInternalState)?;
+        self.hkdf(&mut state, secret).await?;
+        self.state = state;
+        Ok(())
+    }
+
     /// Set the final state of the state machine by creating the encryption / decryption keys
     /// and return the other party identity
     pub(super) async fn set_final_state(&mut self, role: Role) -> Result<()> {
@@ -374,6 +425,14 @@ impl Handshake {
             .delete_ephemeral_x25519_secret_key(self.state.take_e()?)
             .await?;
 
+        // the ML-KEM key is still there if the responder did not support the hybrid key exchange
+        if let Some(ml_kem_key) = self.state.ml_kem_key.take() {
+            _ = self
+                .vault
+                .delete_ephemeral_ml_kem_secret_key(ml_kem_key)
+                .await?;
+        }
+
         Ok(())
     }
 }
@@ -469,6 +528,8 @@ pub(super) struct HandshakeState {
     n: u64,
     h: [u8; SHA256_SIZE],
     ck: Option<SecretBufferHandle>,
+    ml_kem_key: Option<MlKemSecretKeyHandle>,
+    ml_kem_secret: Option<SecretBufferHandle>,
     pub(super) status: Status,
 }
 
@@ -487,6 +548,8 @@ impl HandshakeState {
             n: 0,
             h: [0u8; SHA256_SIZE],
             ck: None,
+            ml_kem_key: None,
+            ml_kem_secret: None,
             status: Initial,
         }
     }
@@ -1,3 +1,4 @@
+use minicbor::bytes::ByteVec;
 use minicbor::{Decode, Encode};
 use tracing::{debug, warn};
 
@@ -6,13 +7,14 @@ use ockam_core::compat::string::ToString;
 use ockam_core::compat::sync::Arc;
 use ockam_core::compat::vec::Vec;
 use ockam_core::{async_trait, Result};
-use ockam_vault::{AeadSecretKeyHandle, X25519PublicKey};
+use ockam_vault::{AeadSecretKeyHandle, MlKemCiphertext, MlKemPublicKey, X25519PublicKey};
 
 use crate::models::{
     ChangeHistory, CredentialAndPurposeKey, PurposeKeyAttestation, PurposePublicKey,
 };
 use crate::{
-    Identifier, Identities, IdentityError, SecureChannelTrustInfo, TrustContext, TrustPolicy,
+    Identifier, Identities, IdentityError, KeyExchangePolicy, SecureChannelTrustInfo, TrustContext,
+    TrustPolicy,
 };
 
 /// Interface for a state machine in a key exchange protocol
@@ -60,6 +62,8 @@ pub(super) struct HandshakeKeys {
 pub(super) struct HandshakeResults {
     pub(super) handshake_keys: HandshakeKeys,
     pub(super) their_identifier: Identifier,
+    pub(super) is_hybrid_key_exchange: bool,
+    pub(super) their_requested_attributes: Option<Vec<Vec<u8>>>,
 }
 
 /// This struct implements functions common to both initiator and the responder state machines
@@ -70,10 +74,17 @@ pub(crate) struct CommonStateMachine {
     pub(super) credentials: Vec<CredentialAndPurposeKey>,
     pub(super) trust_policy: Arc<dyn TrustPolicy>,
     pub(super) trust_context: Option<TrustContext>,
+    pub(super) key_exchange_policy: KeyExchangePolicy,
+    pub(super) is_hybrid_key_exchange: bool,
+    /// Attributes requested from the selective-disclosure credentials of the other party
+    pub(super) requested_attributes: Option<Vec<Vec<u8>>>,
+    /// Attributes requested by the other party from our selective-disclosure credentials
+    pub(super) their_requested_attributes: Option<Vec<Vec<u8>>>,
     their_identifier: Option<Identifier>,
 }
 
 impl CommonStateMachine {
+    #[allow(clippy::too_many_arguments)]
     pub(super) fn new(
         identities: Arc<Identities>,
         identifier: Identifier,
@@ -81,6 +92,8 @@ impl CommonStateMachine {
         credentials: Vec<CredentialAndPurposeKey>,
         trust_policy: Arc<dyn TrustPolicy>,
         trust_context: Option<TrustContext>,
+        key_exchange_policy: KeyExchangePolicy,
+        requested_attributes: Option<Vec<Vec<u8>>>,
     ) -> Self {
         Self {
             identities,
@@ -89,26 +102,37 @@ impl CommonStateMachine {
             credentials,
             trust_policy,
             trust_context,
+            key_exchange_policy,
+            is_hybrid_key_exchange: false,
+            requested_attributes,
+            their_requested_attributes: None,
             their_identifier: None,
         }
     }
 
-    /// Prepare a payload containing the identity of the current party and serialize it.
+    /// Prepare a payload containing the identity of the current party.
     /// That payload contains:
     ///
     ///  - the current Identity Change History
     ///  - the current Secure Channel Purpose Key Attestation
     ///  - the Identity Credentials and corresponding Credentials Purpose Key Attestations
+    ///  - the attributes requested from the credentials of the other party, if any
     ///
-    pub(super) async fn make_identity_payload(&self) -> Result<Vec<u8>> {
+    pub(super) async fn make_identity_payload(&self) -> Result<IdentityAndCredentials> {
         // prepare the payload that will be sent either in message 2 or message 3
         let change_history = self.identities.get_change_history(&self.identifier).await?;
-        let payload = IdentityAndCredentials {
+        Ok(IdentityAndCredentials {
             change_history,
             purpose_key_attestation: self.purpose_key_attestation.clone(),
             credentials: self.credentials.clone(),
-        };
-        Ok(minicbor::to_vec(payload)?)
+            ml_kem_ciphertext: None,
+            requested_attributes: self.requested_attributes.as_ref().map(|attributes| {
+                attributes
+                    .iter()
+                    .map(|key| ByteVec::from(key.clone()))
+                    .collect()
+            }),
+        })
     }
 
     /// Verify the identity sent by the other party: the Purpose Key and the credentials must be valid
@@ -119,6 +143,10 @@ impl CommonStateMachine {
         peer: IdentityAndCredentials,
         peer_public_key: X25519PublicKey,
     ) -> Result<()> {
+        self.their_requested_attributes = peer
+            .requested_attributes
+            .map(|attributes| attributes.into_iter().map(Vec::from).collect());
+
         let identifier = Self::process_identity_payload_static(
             self.identities.clone(),
             Some(self.trust_policy.clone()),
@@ -146,6 +174,8 @@ impl CommonStateMachine {
             (Some(their_identifier), Some(handshake_keys)) => Some(HandshakeResults {
                 their_identifier,
                 handshake_keys,
+                is_hybrid_key_exchange: self.is_hybrid_key_exchange,
+                their_requested_attributes: self.their_requested_attributes.clone(),
             }),
             _ => None,
         }
@@ -273,4 +303,22 @@ pub(super) struct IdentityAndCredentials {
     /// Credentials associated to the identity along with corresponding Credentials Purpose Keys
     /// to verify those Credentials
     #[n(2)] pub(super) credentials: Vec<CredentialAndPurposeKey>,
+    /// ML-KEM ciphertext sent by the responder in message 2 when the key exchange is hybrid.
+    /// Since this payload is encrypted, the ciphertext cannot be removed by an attacker
+    #[n(3)] pub(super) ml_kem_ciphertext: Option<MlKemCiphertext>,
+    /// Attributes that the responder requests in message 2 from the selective-disclosure
+    /// credentials of the initiator. All the attributes are disclosed if there is no request
+    #[n(4)] pub(super) requested_attributes: Option<Vec<ByteVec>>,
+}
+
+/// This internal structure is used as the payload of message 1 when the initiator
+/// proposes a hybrid key exchange. That payload is empty for an X25519 key exchange.
+///
+/// The payload is not encrypted but it is part of the handshake hash, so that the responder
+/// and the initiator end up with different keys if it is removed by an attacker
+#[derive(Debug, Clone, Encode, Decode)]
+#[rustfmt::skip]
+pub(super) struct HybridKeyExchangeProposal {
+    /// Ephemeral ML-KEM public key of the initiator
+    #[n(0)] pub(super) ml_kem_public_key: MlKemPublicKey,
 }
@@ -25,10 +25,12 @@ use crate::secure_channel::handshake::handshake_state_machine::{
 };
 use crate::secure_channel::handshake::initiator_state_machine::InitiatorStateMachine;
 use crate::secure_channel::handshake::responder_state_machine::ResponderStateMachine;
+use crate::secure_channel::rekey::{RekeyTriggers, Rekeying};
 use crate::secure_channel::{Addresses, Role};
 use crate::{
-    ChangeHistoryRepository, IdentityError, SecureChannelPurposeKey, SecureChannelRegistryEntry,
-    SecureChannels, TimestampInSeconds, TrustContext, TrustPolicy,
+    ChangeHistoryRepository, IdentityError, KeyExchangePolicy, SecureChannelPurposeKey,
+    SecureChannelRegistryEntry, SecureChannelStatistics, SecureChannels, TimestampInSeconds,
+    TrustContext, TrustPolicy,
 };
 
 /// This struct implements a Worker receiving and sending messages
@@ -48,6 +50,9 @@ pub(crate) struct HandshakeWorker {
     trust_context: Option<TrustContext>,
     change_history_repository: Arc<dyn ChangeHistoryRepository>,
     should_send_close: Arc<AtomicBool>,
+    rekey_triggers: RekeyTriggers,
+    #[cfg(feature = "std")]
+    started_at: std::time::Instant,
 }
 
 #[ockam_core::worker]
@@ -58,7 +63,8 @@ impl Worker for HandshakeWorker {
     /// Initialize the state machine with an `Initialize` event
     /// Depending on the state machine role there might be a message to send to the other party
     async fn initialize(&mut self, context: &mut Self::Context) -> Result<()> {
-        match self.state_machine.on_event(Initialize).await? {
+        let action = self.state_machine.on_event(Initialize).await;
+        match self.record_handshake_failure(action)? {
             SendMessage(message) => {
                 debug!(
                     "remote route {:?}, decryptor remote {:?}",
@@ -105,13 +111,13 @@ impl Worker for HandshakeWorker {
         };
 
         let transport_message = message.into_transport_message();
-        if let SendMessage(message) = self
+        let action = self
             .state_machine
             .on_event(ReceivedMessage(Vec::<u8>::decode(
                 &transport_message.payload,
             )?))
-            .await?
-        {
+            .await;
+        if let SendMessage(message) = self.record_handshake_failure(action)? {
             // set the remote route by taking the most up to date message return route
             // In the case of the initiator the first return route mentions the secure channel listener
             // address so we need to wait for the return route corresponding to the remote handshake worker
@@ -131,6 +137,7 @@ impl Worker for HandshakeWorker {
         if let Some(final_state) = self.state_machine.get_handshake_results() {
             // start the encryptor worker and return the decryptor
             self.decryptor_handler = Some(self.finalize(context, final_state).await?);
+            self.record_handshake_success();
             if let Some(callback_sender) = self.callback_sender.take() {
                 callback_sender.send(())?;
             }
@@ -167,6 +174,9 @@ impl HandshakeWorker {
         credentials: Vec<CredentialAndPurposeKey>,
         min_credential_refresh_interval: Duration,
         refresh_credential_time_gap: Duration,
+        rekey_triggers: RekeyTriggers,
+        key_exchange_policy: KeyExchangePolicy,
+        requested_attributes: Option<Vec<Vec<u8>>>,
         trust_context: Option<TrustContext>,
         remote_route: Option<Route>,
         timeout: Option<Duration>,
@@ -194,6 +204,7 @@ impl HandshakeWorker {
                     credentials,
                     trust_policy,
                     trust_context.clone(),
+                    key_exchange_policy,
                 )
                 .await?,
             )
@@ -207,6 +218,8 @@ impl HandshakeWorker {
                     credentials,
                     trust_policy,
                     trust_context.clone(),
+                    key_exchange_policy,
+                    requested_attributes,
                 )
                 .await?,
             )
@@ -234,6 +247,9 @@ impl HandshakeWorker {
             trust_context,
             change_history_repository: identities.change_history_repository(),
             should_send_close: Arc::new(AtomicBool::new(true)),
+            rekey_triggers,
+            #[cfg(feature = "std")]
+            started_at: std::time::Instant::now(),
         };
 
         WorkerBuilder::new(worker)
@@ -277,6 +293,34 @@ impl HandshakeWorker {
         })
     }
 
+    /// Count a failed handshake if the state machine could not process an event
+    fn record_handshake_failure(&self, action: Result<Action>) -> Result<Action> {
+        #[cfg(feature = "std")]
+        if action.is_err() {
+            ockam_core::metrics::SECURE_CHANNEL_HANDSHAKES
+                .with_labels(&[self.role.str(), "failed"])
+                .inc();
+        }
+        action
+    }
+
+    /// Count a successful handshake and record its duration
+    fn record_handshake_success(&self) {
+        #[cfg(feature = "std")]
+        {
+            use ockam_core::metrics::{
+                SECURE_CHANNEL_HANDSHAKES, SECURE_CHANNEL_HANDSHAKE_DURATION,
+            };
+
+            SECURE_CHANNEL_HANDSHAKES
+                .with_labels(&[self.role.str(), "succeeded"])
+                .inc();
+            SECURE_CHANNEL_HANDSHAKE_DURATION
+                .with_labels(&[self.role.str()])
+                .observe(self.started_at.elapsed().as_secs_f64());
+        }
+    }
+
     /// Create mailboxes and access rights for the workers involved in the secure channel creation
     pub(crate) fn create_mailboxes(
         addresses: &Addresses,
@@ -313,6 +357,10 @@ impl HandshakeWorker {
         context: &Context,
         handshake_results: HandshakeResults,
     ) -> Result<DecryptorHandler> {
+        let vault = self.secure_channels.identities.vault().secure_channel_vault;
+        let rekeying = Rekeying::new(vault, self.role.is_initiator());
+        let statistics = Arc::new(SecureChannelStatistics::default());
+
         // create a decryptor to delegate the processing of all messages after the handshake
         let decryptor = DecryptorHandler::new(
             self.secure_channels.identities.clone(),
@@ -323,6 +371,8 @@ impl HandshakeWorker {
             self.secure_channels.identities.vault().secure_channel_vault,
             handshake_results.their_identifier.clone(),
             self.should_send_close.clone(),
+            rekeying.clone(),
+            statistics.clone(),
         );
 
         // create a separate encryptor worker which will be started independently
@@ -342,7 +392,11 @@ impl HandshakeWorker {
                 self.min_credential_refresh_interval,
                 self.refresh_credential_time_gap,
                 self.trust_context.clone(),
+                handshake_results.their_requested_attributes.clone(),
                 self.should_send_close.clone(),
+                rekeying,
+                self.rekey_triggers,
+                statistics.clone(),
             );
 
             let next_hop = self.remote_route()?.next()?.clone();
@@ -394,6 +448,8 @@ impl HandshakeWorker {
             self.identifier.clone(),
             handshake_results.their_identifier,
             their_decryptor_address,
+            handshake_results.is_hybrid_key_exchange,
+            statistics,
         );
 
         self.secure_channels
@@ -4,7 +4,7 @@ use ockam_core::compat::sync::Arc;
 use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
-use ockam_vault::{VaultForSecureChannels, X25519PublicKey};
+use ockam_vault::{MlKemCiphertext, MlKemPublicKey, VaultForSecureChannels, X25519PublicKey};
 use Action::*;
 use Event::*;
 use Role::*;
@@ -14,10 +14,12 @@ use crate::models::{CredentialAndPurposeKey, Identifier};
 use crate::secure_channel::handshake::error::XXError;
 use crate::secure_channel::handshake::handshake::Handshake;
 use crate::secure_channel::handshake::handshake_state_machine::{
-    Action, CommonStateMachine, Event, HandshakeKeys, HandshakeResults, IdentityAndCredentials,
-    StateMachine, Status,
+    Action, CommonStateMachine, Event, HandshakeKeys, HandshakeResults, HybridKeyExchangeProposal,
+    IdentityAndCredentials, StateMachine, Status,
+};
+use crate::{
+    Identities, KeyExchangePolicy, Role, SecureChannelPurposeKey, TrustContext, TrustPolicy,
 };
-use crate::{Identities, Role, SecureChannelPurposeKey, TrustContext, TrustPolicy};
 
 /// Implementation of a state machine for the key exchange on the initiator side
 #[async_trait]
@@ -28,7 +30,8 @@ impl StateMachine for InitiatorStateMachine {
             // Initialize the handshake and send message 1
             (Initial, Initialize) => {
                 self.initialize_handshake().await?;
-                let message1 = self.encode_message1(&[]).await?;
+                let message1_payload = self.make_message1_payload().await?;
+                let message1 = self.encode_message1(&message1_payload).await?;
 
                 // Send message 1 and wait for message 2
                 self.handshake.state.status = WaitingForMessage2;
@@ -39,16 +42,27 @@ impl StateMachine for InitiatorStateMachine {
                 let message2_payload = self.decode_message2(&message).await?;
                 let their_identity_payload: IdentityAndCredentials =
                     minicbor::decode(&message2_payload)?;
+                self.finish_hybrid_key_exchange(&their_identity_payload.ml_kem_ciphertext)
+                    .await?;
                 self.process_identity_payload(
                     their_identity_payload,
                     self.handshake.state.rs()?.clone(),
                 )
                 .await?;
-                let identity_payload = self
+                let mut identity_payload = self
                     .identity_payload
                     .take()
                     .ok_or(XXError::InvalidInternalState)?;
-                let message3 = self.encode_message3(&identity_payload).await?;
+                if let Some(requested_attributes) = &self.common.their_requested_attributes {
+                    identity_payload.credentials = identity_payload
+                        .credentials
+                        .iter()
+                        .map(|credential| credential.disclose(requested_attributes))
+                        .collect();
+                }
+                let message3 = self
+                    .encode_message3(&minicbor::to_vec(identity_payload)?)
+                    .await?;
                 self.set_final_state(Initiator).await?;
                 Ok(SendMessage(message3))
             }
@@ -73,8 +87,8 @@ impl StateMachine for InitiatorStateMachine {
 pub(super) struct InitiatorStateMachine {
     pub(super) common: CommonStateMachine,
     pub(super) handshake: Handshake,
-    /// this serialized payload contains an identity, its credentials and a signature of its static key
-    pub(super) identity_payload: Option<Vec<u8>>,
+    /// this payload contains an identity, its credentials and a signature of its static key
+    pub(super) identity_payload: Option<IdentityAndCredentials>,
 }
 
 impl InitiatorStateMachine {
@@ -89,6 +103,9 @@ impl InitiatorStateMachine {
             #[call(initialize)]
             async fn initialize_handshake(&mut self) -> Result<()>;
             async fn encode_message1(&mut self, payload: &[u8]) -> Result<Vec<u8>>;
+            async fn generate_ml_kem_key(&mut self) -> Result<MlKemPublicKey>;
+            async fn decapsulate_ml_kem_secret(&mut self, ciphertext: &MlKemCiphertext) -> Result<()>;
+            async fn mix_ml_kem_secret(&mut self) -> Result<()>;
             async fn decode_message2(&mut self, message: &[u8]) -> Result<Vec<u8>>;
             async fn encode_message3(&mut self, payload: &[u8]) -> Result<Vec<u8>>;
             async fn set_final_state(&mut self, role: Role) -> Result<()>;
@@ -98,6 +115,42 @@ impl InitiatorStateMachine {
 }
 
 impl InitiatorStateMachine {
+    /// Propose a hybrid key exchange in message 1 if the key exchange policy allows it
+    async fn make_message1_payload(&mut self) -> Result<Vec<u8>> {
+        if !self.common.key_exchange_policy.is_hybrid() {
+            return Ok(Vec::new());
+        }
+        let proposal = HybridKeyExchangeProposal {
+            ml_kem_public_key: self.generate_ml_kem_key().await?,
+        };
+        Ok(minicbor::to_vec(proposal)?)
+    }
+
+    /// If the responder accepted the hybrid key exchange, mix the ML-KEM secret in the
+    /// handshake keys. Otherwise check that the key exchange policy allows an X25519 key exchange
+    async fn finish_hybrid_key_exchange(
+        &mut self,
+        ml_kem_ciphertext: &Option<MlKemCiphertext>,
+    ) -> Result<()> {
+        match ml_kem_ciphertext {
+            Some(ciphertext) if self.common.key_exchange_policy.is_hybrid() => {
+                self.decapsulate_ml_kem_secret(ciphertext).await?;
+                self.mix_ml_kem_secret().await?;
+                self.common.is_hybrid_key_exchange = true;
+                Ok(())
+            }
+            // we never proposed a hybrid key exchange
+            Some(_) => Err(XXError::InvalidInternalState.into()),
+            None if self.common.key_exchange_policy.is_hybrid_required() => {
+                Err(XXError::HybridKeyExchangeRequired.into())
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+impl InitiatorStateMachine {
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         vault: Arc<dyn VaultForSecureChannels>,
         identities: Arc<Identities>,
@@ -106,6 +159,7 @@ impl InitiatorStateMachine {
         credentials: Vec<CredentialAndPurposeKey>,
         trust_policy: Arc<dyn TrustPolicy>,
         trust_context: Option<TrustContext>,
+        key_exchange_policy: KeyExchangePolicy,
     ) -> Result<InitiatorStateMachine> {
         let common = CommonStateMachine::new(
             identities,
@@ -114,6 +168,8 @@ impl InitiatorStateMachine {
             credentials,
             trust_policy,
             trust_context,
+            key_exchange_policy,
+            None,
         );
         let identity_payload = common.make_identity_payload().await?;
 
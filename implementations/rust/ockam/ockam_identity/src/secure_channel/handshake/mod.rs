@@ -1,4 +1,4 @@
-mod error;
+pub(crate) mod error;
 
 // This directive makes sure that we only run the handshake protocol if it has been compiled
 // on a little endian system since it is not supporting a big endian one at the moment
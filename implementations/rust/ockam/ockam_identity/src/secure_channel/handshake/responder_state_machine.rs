@@ -4,7 +4,7 @@ use ockam_core::compat::sync::Arc;
 use ockam_core::compat::{boxed::Box, vec::Vec};
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::{Error, Result};
-use ockam_vault::{VaultForSecureChannels, X25519PublicKey};
+use ockam_vault::{MlKemCiphertext, MlKemPublicKey, VaultForSecureChannels, X25519PublicKey};
 use Action::*;
 use Event::*;
 use Role::*;
@@ -14,10 +14,12 @@ use crate::models::{CredentialAndPurposeKey, Identifier};
 use crate::secure_channel::handshake::error::XXError;
 use crate::secure_channel::handshake::handshake::Handshake;
 use crate::secure_channel::handshake::handshake_state_machine::{
-    Action, CommonStateMachine, Event, HandshakeKeys, HandshakeResults, IdentityAndCredentials,
-    StateMachine, Status,
+    Action, CommonStateMachine, Event, HandshakeKeys, HandshakeResults, HybridKeyExchangeProposal,
+    IdentityAndCredentials, StateMachine, Status,
+};
+use crate::{
+    Identities, KeyExchangePolicy, Role, SecureChannelPurposeKey, TrustContext, TrustPolicy,
 };
-use crate::{Identities, Role, SecureChannelPurposeKey, TrustContext, TrustPolicy};
 
 /// Implementation of a state machine for the key exchange on the responder side
 #[async_trait]
@@ -33,12 +35,20 @@ impl StateMachine for ResponderStateMachine {
             }
             // Process message 1 and send message 2
             (WaitingForMessage1, ReceivedMessage(message)) => {
-                self.decode_message1(&message).await?;
-                let identity_payload = self
+                let message1_payload = self.decode_message1(&message).await?;
+                let mut identity_payload = self
                     .identity_payload
                     .take()
                     .ok_or(XXError::InvalidInternalState)?;
-                let message2 = self.encode_message2(&identity_payload).await?;
+                identity_payload.ml_kem_ciphertext =
+                    self.accept_hybrid_key_exchange(&message1_payload).await?;
+                let message2 = self
+                    .encode_message2(&minicbor::to_vec(&identity_payload)?)
+                    .await?;
+                if identity_payload.ml_kem_ciphertext.is_some() {
+                    self.mix_ml_kem_secret().await?;
+                    self.common.is_hybrid_key_exchange = true;
+                }
 
                 self.handshake.state.status = WaitingForMessage3;
                 Ok(SendMessage(message2))
@@ -76,8 +86,8 @@ impl StateMachine for ResponderStateMachine {
 pub struct ResponderStateMachine {
     common: CommonStateMachine,
     handshake: Handshake,
-    /// this serialized payload contains an identity, its credentials and a signature of its static key
-    identity_payload: Option<Vec<u8>>,
+    /// this payload contains an identity, its credentials and a signature of its static key
+    identity_payload: Option<IdentityAndCredentials>,
 }
 
 impl ResponderStateMachine {
@@ -93,6 +103,8 @@ impl ResponderStateMachine {
             async fn initialize_handshake(&mut self) -> Result<()>;
             async fn decode_message1(&mut self, message: &[u8]) -> Result<Vec<u8>>;
             async fn encode_message2(&mut self, payload: &[u8]) -> Result<Vec<u8>>;
+            async fn encapsulate_ml_kem_secret(&mut self, public_key: &MlKemPublicKey) -> Result<MlKemCiphertext>;
+            async fn mix_ml_kem_secret(&mut self) -> Result<()>;
             async fn decode_message3(&mut self, message: &[u8]) -> Result<Vec<u8>>;
             async fn set_final_state(&mut self, role: Role) -> Result<()>;
             fn get_handshake_keys(&self) -> Option<HandshakeKeys>;
@@ -101,6 +113,34 @@ impl ResponderStateMachine {
 }
 
 impl ResponderStateMachine {
+    /// If the initiator proposed a hybrid key exchange in message 1 and if our key exchange
+    /// policy allows it, encapsulate a secret for the initiator. The resulting ciphertext is
+    /// sent in message 2.
+    ///
+    /// Otherwise check that the key exchange policy allows an X25519 key exchange
+    async fn accept_hybrid_key_exchange(
+        &mut self,
+        message1_payload: &[u8],
+    ) -> Result<Option<MlKemCiphertext>> {
+        let policy = self.common.key_exchange_policy;
+        if message1_payload.is_empty() || !policy.is_hybrid() {
+            return if policy.is_hybrid_required() {
+                Err(XXError::HybridKeyExchangeRequired.into())
+            } else {
+                Ok(None)
+            };
+        }
+
+        let proposal: HybridKeyExchangeProposal = minicbor::decode(message1_payload)?;
+        let ciphertext = self
+            .encapsulate_ml_kem_secret(&proposal.ml_kem_public_key)
+            .await?;
+        Ok(Some(ciphertext))
+    }
+}
+
+impl ResponderStateMachine {
+    #[allow(clippy::too_many_arguments)]
     pub async fn new(
         vault: Arc<dyn VaultForSecureChannels>,
         identities: Arc<Identities>,
@@ -109,6 +149,8 @@ impl ResponderStateMachine {
         credentials: Vec<CredentialAndPurposeKey>,
         trust_policy: Arc<dyn TrustPolicy>,
         trust_context: Option<TrustContext>,
+        key_exchange_policy: KeyExchangePolicy,
+        requested_attributes: Option<Vec<Vec<u8>>>,
     ) -> Result<ResponderStateMachine> {
         let common = CommonStateMachine::new(
             identities,
@@ -117,6 +159,8 @@ impl ResponderStateMachine {
             credentials,
             trust_policy,
             trust_context,
+            key_exchange_policy,
+            requested_attributes,
         );
         let identity_payload = common.make_identity_payload().await?;
 
@@ -26,6 +26,21 @@ impl KeyTracker {
 }
 
 impl KeyTracker {
+    /// Number of key renewals which happened so far
+    pub(crate) fn number_of_rekeys(&self) -> u64 {
+        self.number_of_rekeys
+    }
+
+    /// True if no more key renewals are possible
+    pub(crate) fn max_rekeys_reached(&self) -> bool {
+        self.max_rekeys_reached
+    }
+
+    /// First nonce of the interval following the current one
+    pub(crate) fn next_interval_start(&self) -> u64 {
+        (self.number_of_rekeys + 1).saturating_mul(self.renewal_interval)
+    }
+
     /// The rekeying algorithm specifies a series of intervals of size self.renewal_interval
     /// where each interval corresponds to a set of contiguous nonces using the same key.
     ///
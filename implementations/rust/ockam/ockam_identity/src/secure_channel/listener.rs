@@ -96,6 +96,9 @@ impl Worker for SecureChannelListenerWorker {
             credentials,
             self.options.min_credential_refresh_interval,
             self.options.refresh_credential_time_gap,
+            self.options.rekey_triggers,
+            self.options.key_exchange_policy,
+            self.options.requested_attributes.clone(),
             self.options.trust_context.clone(),
             None,
             None,
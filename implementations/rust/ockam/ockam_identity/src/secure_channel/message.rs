@@ -2,6 +2,7 @@ use crate::models::{ChangeHistory, CredentialAndPurposeKey};
 use minicbor::{Decode, Encode};
 use ockam_core::compat::vec::Vec;
 use ockam_core::Route;
+use ockam_vault::X25519PublicKey;
 
 /// Secure Channel Message format.
 #[derive(Debug, Encode, Decode, Clone)]
@@ -13,6 +14,12 @@ pub enum SecureChannelMessage {
     #[n(1)] RefreshCredentials(#[n(0)] RefreshCredentialsMessage),
     /// Close the channel.
     #[n(2)] Close,
+    /// Start an explicit re-key of the channel.
+    #[n(3)] RekeyRequest(#[n(0)] RekeyRequestMessage),
+    /// Answer to a re-key request.
+    #[n(4)] RekeyResponse(#[n(0)] RekeyResponseMessage),
+    /// Finish an explicit re-key of the channel.
+    #[n(5)] RekeyConfirm(#[n(0)] RekeyConfirmMessage),
 }
 
 /// Secure Channel Message format.
@@ -38,3 +45,35 @@ pub struct RefreshCredentialsMessage {
     /// to verify those Credentials
     #[n(1)] pub credentials: Vec<CredentialAndPurposeKey>,
 }
+
+/// Secure Channel Message format.
+#[derive(Debug, Encode, Decode, Clone)]
+#[rustfmt::skip]
+pub struct RekeyRequestMessage {
+    /// Number of the re-key, starting at 1 for the first re-key of the channel
+    #[n(0)] pub epoch: u64,
+    /// Fresh ephemeral key of the party requesting the re-key
+    #[n(1)] pub public_key: X25519PublicKey,
+}
+
+/// Secure Channel Message format.
+#[derive(Debug, Encode, Decode, Clone)]
+#[rustfmt::skip]
+pub struct RekeyResponseMessage {
+    /// Number of the re-key
+    #[n(0)] pub epoch: u64,
+    /// Fresh ephemeral key of the party answering the re-key request
+    #[n(1)] pub public_key: X25519PublicKey,
+    /// Nonce starting from which messages are encrypted with the new key
+    #[n(2)] pub switch_at: u64,
+}
+
+/// Secure Channel Message format.
+#[derive(Debug, Encode, Decode, Clone)]
+#[rustfmt::skip]
+pub struct RekeyConfirmMessage {
+    /// Number of the re-key
+    #[n(0)] pub epoch: u64,
+    /// Nonce starting from which messages are encrypted with the new key
+    #[n(1)] pub switch_at: u64,
+}
@@ -13,7 +13,9 @@ mod message;
 mod nonce_tracker;
 mod options;
 mod registry;
+mod rekey;
 mod role;
+mod statistics;
 
 /// List of trust policies to setup ABAC controls
 pub mod trust_policy;
@@ -28,12 +30,14 @@ pub use message::*;
 pub use options::*;
 pub use registry::*;
 pub(crate) use role::*;
+pub use statistics::*;
 pub use trust_policy::*;
 
 #[cfg(test)]
 mod tests {
     use crate::secure_channel::{decryptor::Decryptor, encryptor::Encryptor};
     use ockam_core::compat::rand::RngCore;
+    use ockam_core::compat::sync::Arc;
     use ockam_core::Result;
     use ockam_vault::{SoftwareVaultForSecureChannels, VaultForSecureChannels};
     use rand::seq::SliceRandom;
@@ -134,7 +138,139 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_encrypt_decrypt_with_scheduled_key() -> Result<()> {
+        let (mut encryptor, mut decryptor, vault1, vault2) =
+            create_encryptor_decryptor_with_vaults().await?;
+
+        let mut key = [0u8; 32];
+        thread_rng().fill_bytes(&mut key);
+        let key_on_v1 = vault1.import_secret_buffer(key.to_vec()).await?;
+        let key_on_v1 = vault1.convert_secret_buffer_to_aead_key(key_on_v1).await?;
+        let key_on_v2 = vault2.import_secret_buffer(key.to_vec()).await?;
+        let key_on_v2 = vault2.convert_secret_buffer_to_aead_key(key_on_v2).await?;
+
+        for n in 0..40 {
+            let msg = vec![n];
+            assert_eq!(
+                msg,
+                decryptor.decrypt(&encryptor.encrypt(&msg).await?).await?
+            );
+        }
+
+        // the new key is used at the start of the next key renewal interval
+        let switch_at = encryptor.schedule_key(key_on_v1).await?;
+        assert_eq!(switch_at, 64);
+
+        // the decryptor doesn't know about the new key yet
+        let mut ciphertexts = vec![];
+        for n in 40..70 {
+            ciphertexts.push((vec![n], encryptor.encrypt(&[n]).await?));
+        }
+        for (msg, ciphertext) in &ciphertexts[..24] {
+            assert_eq!(msg, &decryptor.decrypt(ciphertext).await?);
+        }
+        assert!(decryptor.decrypt(&ciphertexts[24].1).await.is_err());
+
+        decryptor.schedule_key(switch_at, key_on_v2).await?;
+        for (msg, ciphertext) in &ciphertexts[24..] {
+            assert_eq!(msg, &decryptor.decrypt(ciphertext).await?);
+        }
+
+        // the keys derived from the new key are used afterwards
+        for n in 70..200 {
+            let msg = vec![n];
+            assert_eq!(
+                msg,
+                decryptor.decrypt(&encryptor.encrypt(&msg).await?).await?
+            );
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_encrypt_decrypt_with_unconfirmed_key() -> Result<()> {
+        let (mut encryptor, mut decryptor, vault1, vault2) =
+            create_encryptor_decryptor_with_vaults().await?;
+
+        let mut key = [0u8; 32];
+        thread_rng().fill_bytes(&mut key);
+        let key_on_v1 = vault1.import_secret_buffer(key.to_vec()).await?;
+        let key_on_v1 = vault1.convert_secret_buffer_to_aead_key(key_on_v1).await?;
+        let key_on_v2 = vault2.import_secret_buffer(key.to_vec()).await?;
+        let key_on_v2 = vault2.convert_secret_buffer_to_aead_key(key_on_v2).await?;
+
+        // the decryptor knows the new key but not the nonce from which it is used,
+        // since the confirmation of the re-key was lost
+        let switch_at = encryptor.schedule_key(key_on_v1).await?;
+        assert_eq!(switch_at, 32);
+
+        let mut ciphertexts = vec![];
+        for n in 0..70 {
+            ciphertexts.push((vec![n], encryptor.encrypt(&[n]).await?));
+        }
+        for (msg, ciphertext) in &ciphertexts[..31] {
+            let (plaintext, used_unconfirmed_key) = decryptor
+                .decrypt_with_unconfirmed_key(ciphertext, Some(&key_on_v2))
+                .await?;
+            assert_eq!(msg, &plaintext);
+            assert!(!used_unconfirmed_key);
+        }
+
+        // the first message encrypted with the new key switches the decryptor to that key
+        let (plaintext, used_unconfirmed_key) = decryptor
+            .decrypt_with_unconfirmed_key(&ciphertexts[32].1, Some(&key_on_v2))
+            .await?;
+        assert_eq!(ciphertexts[32].0, plaintext);
+        assert!(used_unconfirmed_key);
+
+        // the messages of the previous interval are still decrypted with the previous key
+        assert_eq!(
+            ciphertexts[31].0,
+            decryptor.decrypt(&ciphertexts[31].1).await?
+        );
+        // and the keys derived from the new key are used afterwards
+        for (msg, ciphertext) in &ciphertexts[33..] {
+            assert_eq!(msg, &decryptor.decrypt(ciphertext).await?);
+        }
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unconfirmed_key_not_used_by_the_other_side() -> Result<()> {
+        let (mut encryptor, mut decryptor, _, vault2) =
+            create_encryptor_decryptor_with_vaults().await?;
+        let unconfirmed_key = vault2.import_secret_buffer(vec![1; 32]).await?;
+        let unconfirmed_key = vault2
+            .convert_secret_buffer_to_aead_key(unconfirmed_key)
+            .await?;
+
+        // the other side keeps renewing its current key
+        for n in 0..100 {
+            let msg = vec![n];
+            let (plaintext, used_unconfirmed_key) = decryptor
+                .decrypt_with_unconfirmed_key(
+                    &encryptor.encrypt(&msg).await?,
+                    Some(&unconfirmed_key),
+                )
+                .await?;
+            assert_eq!(msg, plaintext);
+            assert!(!used_unconfirmed_key);
+        }
+        Ok(())
+    }
+
     async fn create_encryptor_decryptor() -> Result<(Encryptor, Decryptor)> {
+        let (encryptor, decryptor, _, _) = create_encryptor_decryptor_with_vaults().await?;
+        Ok((encryptor, decryptor))
+    }
+
+    async fn create_encryptor_decryptor_with_vaults() -> Result<(
+        Encryptor,
+        Decryptor,
+        Arc<dyn VaultForSecureChannels>,
+        Arc<dyn VaultForSecureChannels>,
+    )> {
         let vault1 = SoftwareVaultForSecureChannels::create().await?;
         let vault2 = SoftwareVaultForSecureChannels::create().await?;
 
@@ -149,8 +285,10 @@ mod tests {
         let key_on_v2 = vault2.convert_secret_buffer_to_aead_key(key_on_v2).await?;
 
         Ok((
-            Encryptor::new(key_on_v1, 0, vault1),
-            Decryptor::new(key_on_v2, vault2),
+            Encryptor::new(key_on_v1, 0, vault1.clone()),
+            Decryptor::new(key_on_v2, vault2.clone()),
+            vault1,
+            vault2,
         ))
     }
 }
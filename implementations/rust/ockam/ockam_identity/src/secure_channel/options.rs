@@ -4,6 +4,7 @@ use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl,
 use ockam_core::{Address, OutgoingAccessControl, Result};
 
 use crate::models::CredentialAndPurposeKey;
+use crate::secure_channel::rekey::RekeyTriggers;
 use crate::secure_channel::Addresses;
 use crate::{TrustContext, TrustEveryonePolicy, TrustPolicy};
 
@@ -21,6 +22,33 @@ pub const DEFAULT_MIN_REFRESH_CREDENTIAL_INTERVAL: Duration = Duration::from_sec
 /// This is the default timeout for creating a secure channel
 pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
 
+/// Key exchange performed during the handshake of a secure channel
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum KeyExchangePolicy {
+    /// Only use X25519
+    #[default]
+    Classic,
+    /// Combine X25519 with ML-KEM-768 if the other party supports it, so that recorded traffic
+    /// cannot be decrypted later with a quantum computer. Fall back to X25519 otherwise
+    PreferHybrid,
+    /// Combine X25519 with ML-KEM-768 and fail the handshake if the other party does not
+    /// support it
+    RequireHybrid,
+}
+
+impl KeyExchangePolicy {
+    /// Return true if a hybrid key exchange must be attempted
+    pub fn is_hybrid(&self) -> bool {
+        !matches!(self, KeyExchangePolicy::Classic)
+    }
+
+    /// Return true if the handshake must fail when the other party does not support
+    /// a hybrid key exchange
+    pub fn is_hybrid_required(&self) -> bool {
+        matches!(self, KeyExchangePolicy::RequireHybrid)
+    }
+}
+
 /// Trust options for a Secure Channel
 pub struct SecureChannelOptions {
     pub(crate) flow_control_id: FlowControlId,
@@ -30,6 +58,8 @@ pub struct SecureChannelOptions {
     pub(crate) timeout: Duration,
     pub(crate) min_credential_refresh_interval: Duration,
     pub(crate) credential_refresh_time_gap: Duration,
+    pub(crate) rekey_triggers: RekeyTriggers,
+    pub(crate) key_exchange_policy: KeyExchangePolicy,
 }
 
 impl fmt::Debug for SecureChannelOptions {
@@ -54,6 +84,8 @@ impl SecureChannelOptions {
             timeout: DEFAULT_TIMEOUT,
             min_credential_refresh_interval: DEFAULT_MIN_REFRESH_CREDENTIAL_INTERVAL,
             credential_refresh_time_gap: DEFAULT_REFRESH_CREDENTIAL_TIME_GAP,
+            rekey_triggers: RekeyTriggers::default(),
+            key_exchange_policy: KeyExchangePolicy::default(),
         }
     }
 
@@ -109,6 +141,28 @@ impl SecureChannelOptions {
         self.min_credential_refresh_interval = min_credential_refresh_interval;
         self
     }
+
+    /// Explicitly re-key the channel periodically.
+    /// A re-key renews the ephemeral keys of both parties without closing the channel.
+    /// Both parties must support explicit re-keys
+    pub fn with_rekey_interval(mut self, rekey_interval: Duration) -> Self {
+        self.rekey_triggers.interval = Some(rekey_interval);
+        self
+    }
+
+    /// Explicitly re-key the channel each time a given number of messages has been encrypted
+    /// by this side of the channel.
+    /// Both parties must support explicit re-keys
+    pub fn with_rekey_message_count(mut self, rekey_message_count: u64) -> Self {
+        self.rekey_triggers.message_count = Some(rekey_message_count);
+        self
+    }
+
+    /// Set the key exchange performed during the handshake
+    pub fn with_key_exchange_policy(mut self, key_exchange_policy: KeyExchangePolicy) -> Self {
+        self.key_exchange_policy = key_exchange_policy;
+        self
+    }
 }
 
 impl SecureChannelOptions {
@@ -161,6 +215,9 @@ pub struct SecureChannelListenerOptions {
     pub(crate) credentials: Vec<CredentialAndPurposeKey>,
     pub(crate) min_credential_refresh_interval: Duration,
     pub(crate) refresh_credential_time_gap: Duration,
+    pub(crate) rekey_triggers: RekeyTriggers,
+    pub(crate) key_exchange_policy: KeyExchangePolicy,
+    pub(crate) requested_attributes: Option<Vec<Vec<u8>>>,
 }
 
 impl fmt::Debug for SecureChannelListenerOptions {
@@ -183,6 +240,9 @@ impl SecureChannelListenerOptions {
             credentials: vec![],
             min_credential_refresh_interval: DEFAULT_MIN_REFRESH_CREDENTIAL_INTERVAL,
             refresh_credential_time_gap: DEFAULT_REFRESH_CREDENTIAL_TIME_GAP,
+            rekey_triggers: RekeyTriggers::default(),
+            key_exchange_policy: KeyExchangePolicy::default(),
+            requested_attributes: None,
         }
     }
 
@@ -241,6 +301,39 @@ impl SecureChannelListenerOptions {
         self.min_credential_refresh_interval = min_credential_refresh_interval;
         self
     }
+
+    /// Explicitly re-key the channel periodically.
+    /// A re-key renews the ephemeral keys of both parties without closing the channel.
+    /// Both parties must support explicit re-keys
+    pub fn with_rekey_interval(mut self, rekey_interval: Duration) -> Self {
+        self.rekey_triggers.interval = Some(rekey_interval);
+        self
+    }
+
+    /// Explicitly re-key the channel each time a given number of messages has been encrypted
+    /// by this side of the channel.
+    /// Both parties must support explicit re-keys
+    pub fn with_rekey_message_count(mut self, rekey_message_count: u64) -> Self {
+        self.rekey_triggers.message_count = Some(rekey_message_count);
+        self
+    }
+
+    /// Set the key exchange performed during the handshake
+    pub fn with_key_exchange_policy(mut self, key_exchange_policy: KeyExchangePolicy) -> Self {
+        self.key_exchange_policy = key_exchange_policy;
+        self
+    }
+
+    /// Request some attributes from the selective-disclosure credentials of the initiators,
+    /// for example the attributes required by a [`crate::CredentialAccessControl`].
+    /// Initiators then only disclose those attributes. They disclose all their attributes if
+    /// no attribute is requested
+    pub fn with_requested_attributes(mut self, attribute_keys: Vec<Vec<u8>>) -> Self {
+        self.requested_attributes
+            .get_or_insert_with(Vec::new)
+            .extend(attribute_keys);
+        self
+    }
 }
 
 impl SecureChannelListenerOptions {
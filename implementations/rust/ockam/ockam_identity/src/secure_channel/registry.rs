@@ -4,7 +4,7 @@ use ockam_core::compat::vec::Vec;
 use ockam_core::{Address, Result};
 
 use crate::models::Identifier;
-use crate::IdentityError;
+use crate::{IdentityError, SecureChannelStatistics};
 
 /// Known information about particular SecureChannel
 #[derive(Clone, Debug)]
@@ -17,6 +17,8 @@ pub struct SecureChannelRegistryEntry {
     my_id: Identifier,
     their_id: Identifier,
     their_decryptor_address: Address,
+    is_hybrid_key_exchange: bool,
+    statistics: Arc<SecureChannelStatistics>,
 }
 
 impl SecureChannelRegistryEntry {
@@ -31,6 +33,8 @@ impl SecureChannelRegistryEntry {
         my_id: Identifier,
         their_id: Identifier,
         their_decryptor_address: Address,
+        is_hybrid_key_exchange: bool,
+        statistics: Arc<SecureChannelStatistics>,
     ) -> Self {
         Self {
             encryptor_messaging_address,
@@ -41,6 +45,8 @@ impl SecureChannelRegistryEntry {
             my_id,
             their_id,
             their_decryptor_address,
+            is_hybrid_key_exchange,
+            statistics,
         }
     }
 
@@ -83,6 +89,16 @@ impl SecureChannelRegistryEntry {
     pub fn their_decryptor_address(&self) -> Address {
         self.their_decryptor_address.clone()
     }
+
+    /// True if the channel keys were established with a hybrid X25519 + ML-KEM key exchange
+    pub fn is_hybrid_key_exchange(&self) -> bool {
+        self.is_hybrid_key_exchange
+    }
+
+    /// Usage statistics of the channel keys
+    pub fn statistics(&self) -> &SecureChannelStatistics {
+        &self.statistics
+    }
 }
 
 /// Registry of all known Secure Channels
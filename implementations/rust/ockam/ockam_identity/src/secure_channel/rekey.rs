@@ -0,0 +1,640 @@
+use core::time::Duration;
+use ockam_core::compat::collections::VecDeque;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::compat::vec::Vec;
+use ockam_core::{Message, Result};
+use ockam_vault::{
+    AeadSecretKeyHandle, HKDFNumberOfOutputs, SecretBufferHandle, VaultForSecureChannels,
+    X25519PublicKey, X25519SecretKeyHandle,
+};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::{debug, warn};
+
+use crate::models::TimestampInSeconds;
+use crate::secure_channel::handshake::error::XXError;
+use crate::utils::now;
+use crate::{RekeyConfirmMessage, RekeyRequestMessage, RekeyResponseMessage};
+
+/// Label used to derive the keys produced by an explicit re-key
+const REKEY_PROTOCOL_NAME: &[u8] = b"OCKAM_REKEY_25519_SHA256";
+
+/// Time after which a re-key request which was not answered, because the request or the
+/// response was lost, is abandoned so that a new re-key can be started
+const PENDING_REKEY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Conditions triggering an explicit re-key of a secure channel.
+/// When no condition is set the channel is never explicitly re-keyed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct RekeyTriggers {
+    /// Re-key the channel periodically
+    pub(crate) interval: Option<Duration>,
+    /// Re-key the channel after a given number of encrypted messages
+    pub(crate) message_count: Option<u64>,
+}
+
+/// Messages sent to the internal address of the `EncryptorWorker`
+#[derive(Serialize, Deserialize, Message, Clone, Debug)]
+pub(crate) enum EncryptorInternalMessage {
+    /// Get a new credential and present it to the other side
+    RefreshCredentials,
+    /// Start an explicit re-key
+    Rekey,
+    /// Send the re-key messages prepared by the decryptor
+    SendRekeyMessages,
+}
+
+/// Re-key message which must be encrypted and sent by the encryptor,
+/// together with the key which must be used for encryption once that message is sent
+pub(crate) enum OutgoingRekeyMessage {
+    Response {
+        epoch: u64,
+        public_key: X25519PublicKey,
+        encryption_key: AeadSecretKeyHandle,
+    },
+    Confirm {
+        epoch: u64,
+        encryption_key: AeadSecretKeyHandle,
+    },
+}
+
+/// A re-key request we sent and for which we wait for a response
+struct PendingRequest {
+    epoch: u64,
+    secret_key: X25519SecretKeyHandle,
+    public_key: X25519PublicKey,
+    started_at: TimestampInSeconds,
+}
+
+/// A re-key request we answered and for which we wait for a confirmation.
+/// The decryption key is only known once the key exchange has been computed
+struct PendingConfirmation {
+    epoch: u64,
+    decryption_key: Option<AeadSecretKeyHandle>,
+}
+
+#[derive(Default)]
+struct RekeyState {
+    /// Number of the last re-key accepted on this channel
+    epoch: u64,
+    pending_request: Option<PendingRequest>,
+    pending_confirmation: Option<PendingConfirmation>,
+    outgoing: VecDeque<OutgoingRekeyMessage>,
+}
+
+impl RekeyState {
+    /// Abandon our re-key request if it was sent at least `timeout` ago.
+    /// Return the secret key of that request, which must be deleted.
+    ///
+    /// An unconfirmed re-key is never abandoned after a timeout: if only the confirmation was lost
+    /// the other side already uses its new key, which is then detected by the decryptor
+    fn expire(
+        &mut self,
+        now: TimestampInSeconds,
+        timeout: Duration,
+    ) -> Option<X25519SecretKeyHandle> {
+        let pending = self.pending_request.take()?;
+        if now.saturating_sub(*pending.started_at) >= timeout.as_secs() {
+            warn!("abandoning the re-key request {}", pending.epoch);
+            Some(pending.secret_key)
+        } else {
+            self.pending_request = Some(pending);
+            None
+        }
+    }
+}
+
+/// Explicit re-key of a secure channel.
+///
+/// An explicit re-key performs a new X25519 key exchange between the two parties, authenticated
+/// by the existing channel keys, and replaces the encryption and decryption keys in both directions:
+///
+///  1. the requester sends a fresh ephemeral public key
+///  2. the responder sends its own fresh ephemeral public key and starts using its new encryption key
+///     at the next key renewal interval
+///  3. the requester confirms the re-key and starts using its new encryption key
+///     at the next key renewal interval
+///
+/// The state is shared between the encryptor and the decryptor of one side of the channel.
+/// If both sides request a re-key at the same time, the request sent by the channel initiator wins.
+///
+/// A re-key request which is not answered after [`PENDING_REKEY_TIMEOUT`] is abandoned by the
+/// next re-key request, which uses the same epoch. When the responder receives a request for the
+/// epoch it is waiting a confirmation for, its response was lost and its re-key is replaced.
+///
+/// When the confirmation is lost, the requester already uses its new key. The responder keeps
+/// the decryption key of the unconfirmed re-key and the decryptor tries it when the other side
+/// starts a new key renewal interval. The first message decrypted with that key confirms the re-key.
+#[derive(Clone)]
+pub(crate) struct Rekeying {
+    vault: Arc<dyn VaultForSecureChannels>,
+    is_initiator: bool,
+    pending_timeout: Duration,
+    state: Arc<Mutex<RekeyState>>,
+}
+
+impl Rekeying {
+    pub(crate) fn new(vault: Arc<dyn VaultForSecureChannels>, is_initiator: bool) -> Self {
+        Self {
+            vault,
+            is_initiator,
+            pending_timeout: PENDING_REKEY_TIMEOUT,
+            state: Default::default(),
+        }
+    }
+
+    /// Create a re-key request, unless a re-key is already in progress
+    pub(crate) async fn start(&self) -> Result<Option<RekeyRequestMessage>> {
+        let secret_key = self.vault.generate_ephemeral_x25519_secret_key().await?;
+        let public_key = self.vault.get_x25519_public_key(&secret_key).await?;
+        let started_at = now()?;
+
+        let (request, expired_key) = {
+            let mut state = self.state.lock().unwrap();
+            let expired_key = state.expire(started_at, self.pending_timeout);
+            let request = if state.pending_request.is_some() || state.pending_confirmation.is_some()
+            {
+                None
+            } else {
+                let epoch = state.epoch + 1;
+                state.pending_request = Some(PendingRequest {
+                    epoch,
+                    secret_key: secret_key.clone(),
+                    public_key: public_key.clone(),
+                    started_at,
+                });
+                Some(RekeyRequestMessage { epoch, public_key })
+            };
+            (request, expired_key)
+        };
+        self.delete_keys(expired_key, None).await?;
+
+        if request.is_none() {
+            debug!("a re-key is already in progress");
+            self.vault
+                .delete_ephemeral_x25519_secret_key(secret_key)
+                .await?;
+        }
+        Ok(request)
+    }
+
+    /// Handle a re-key request from the other side.
+    /// Return true if a response must be sent by the encryptor.
+    ///
+    /// A request for the epoch of the re-key waiting for a confirmation replaces that re-key,
+    /// since the other side sends it again when it did not receive our response. Otherwise a
+    /// request is ignored until the current re-key is confirmed.
+    pub(crate) async fn handle_request(&self, request: RekeyRequestMessage) -> Result<bool> {
+        let started_at = now()?;
+        let (accepted, expired_key, dropped_request, replaced_key) = {
+            let mut state = self.state.lock().unwrap();
+            let expired_key = state.expire(started_at, self.pending_timeout);
+            let replaced_key = match state.pending_confirmation.take() {
+                Some(pending) if pending.epoch == request.epoch => {
+                    warn!(
+                        "the re-key response {} was lost, answering the request again",
+                        request.epoch
+                    );
+                    state.epoch = request.epoch - 1;
+                    pending.decryption_key
+                }
+                pending => {
+                    state.pending_confirmation = pending;
+                    None
+                }
+            };
+            if request.epoch <= state.epoch || state.pending_confirmation.is_some() {
+                warn!(
+                    "ignoring the re-key request {}, the current re-key is {}",
+                    request.epoch, state.epoch
+                );
+                (false, expired_key, None, replaced_key)
+            } else if state.pending_request.is_some() && self.is_initiator {
+                debug!("ignoring the re-key request from the responder, ours takes precedence");
+                (false, expired_key, None, replaced_key)
+            } else {
+                state.epoch = request.epoch;
+                state.pending_confirmation = Some(PendingConfirmation {
+                    epoch: request.epoch,
+                    decryption_key: None,
+                });
+                (
+                    true,
+                    expired_key,
+                    state.pending_request.take(),
+                    replaced_key,
+                )
+            }
+        };
+        self.delete_keys(expired_key, replaced_key).await?;
+        if !accepted {
+            return Ok(false);
+        }
+        if let Some(dropped_request) = dropped_request {
+            self.vault
+                .delete_ephemeral_x25519_secret_key(dropped_request.secret_key)
+                .await?;
+        }
+
+        let secret_key = self.vault.generate_ephemeral_x25519_secret_key().await?;
+        let public_key = self.vault.get_x25519_public_key(&secret_key).await?;
+        let keys = self
+            .derive_keys(
+                request.epoch,
+                &secret_key,
+                &request.public_key,
+                &request.public_key,
+                &public_key,
+            )
+            .await;
+        self.vault
+            .delete_ephemeral_x25519_secret_key(secret_key)
+            .await?;
+        let (requester_key, responder_key) = keys?;
+
+        let mut state = self.state.lock().unwrap();
+        state.pending_confirmation = Some(PendingConfirmation {
+            epoch: request.epoch,
+            decryption_key: Some(requester_key),
+        });
+        state.outgoing.push_back(OutgoingRekeyMessage::Response {
+            epoch: request.epoch,
+            public_key,
+            encryption_key: responder_key,
+        });
+        Ok(true)
+    }
+
+    /// Handle the response to our re-key request.
+    /// Return the nonce from which the other side uses its new key, and the corresponding
+    /// decryption key. In that case a confirmation must be sent by the encryptor
+    pub(crate) async fn handle_response(
+        &self,
+        response: RekeyResponseMessage,
+    ) -> Result<Option<(u64, AeadSecretKeyHandle)>> {
+        let pending_request = {
+            let mut state = self.state.lock().unwrap();
+            match state.pending_request.take() {
+                Some(pending) if pending.epoch == response.epoch => pending,
+                pending => {
+                    state.pending_request = pending;
+                    warn!("ignoring an unexpected re-key response {}", response.epoch);
+                    return Ok(None);
+                }
+            }
+        };
+
+        let keys = self
+            .derive_keys(
+                response.epoch,
+                &pending_request.secret_key,
+                &response.public_key,
+                &pending_request.public_key,
+                &response.public_key,
+            )
+            .await;
+        self.vault
+            .delete_ephemeral_x25519_secret_key(pending_request.secret_key)
+            .await?;
+        let (requester_key, responder_key) = keys?;
+
+        let mut state = self.state.lock().unwrap();
+        state.epoch = response.epoch;
+        state.outgoing.push_back(OutgoingRekeyMessage::Confirm {
+            epoch: response.epoch,
+            encryption_key: requester_key,
+        });
+        Ok(Some((response.switch_at, responder_key)))
+    }
+
+    /// Handle the confirmation of a re-key request we answered.
+    /// Return the nonce from which the other side uses its new key, and the corresponding
+    /// decryption key
+    pub(crate) fn handle_confirm(
+        &self,
+        confirm: RekeyConfirmMessage,
+    ) -> Option<(u64, AeadSecretKeyHandle)> {
+        let mut state = self.state.lock().unwrap();
+        match state.pending_confirmation.take() {
+            Some(PendingConfirmation {
+                epoch,
+                decryption_key: Some(decryption_key),
+                ..
+            }) if epoch == confirm.epoch => Some((confirm.switch_at, decryption_key)),
+            pending => {
+                state.pending_confirmation = pending;
+                warn!(
+                    "ignoring an unexpected re-key confirmation {}",
+                    confirm.epoch
+                );
+                None
+            }
+        }
+    }
+
+    /// Return the decryption key of the re-key waiting for a confirmation, if it is known.
+    /// The other side might already use it if the confirmation was lost
+    pub(crate) fn unconfirmed_key(&self) -> Option<AeadSecretKeyHandle> {
+        let state = self.state.lock().unwrap();
+        state
+            .pending_confirmation
+            .as_ref()
+            .and_then(|p| p.decryption_key.clone())
+    }
+
+    /// Handle a message decrypted with the key returned by [`Rekeying::unconfirmed_key`].
+    /// The other side received our response, so that re-key is confirmed.
+    /// The key is now owned by the decryptor. Return the epoch of the confirmed re-key
+    pub(crate) fn handle_implicit_confirm(&self, key: &AeadSecretKeyHandle) -> Option<u64> {
+        let mut state = self.state.lock().unwrap();
+        match state.pending_confirmation.take() {
+            Some(PendingConfirmation {
+                epoch,
+                decryption_key: Some(decryption_key),
+            }) if &decryption_key == key => Some(epoch),
+            pending => {
+                state.pending_confirmation = pending;
+                None
+            }
+        }
+    }
+
+    /// Return the re-key messages which must be sent to the other side
+    pub(crate) fn take_outgoing(&self) -> Vec<OutgoingRekeyMessage> {
+        self.state.lock().unwrap().outgoing.drain(..).collect()
+    }
+
+    /// Remove the keys of a re-key in progress
+    pub(crate) async fn shutdown(&self) -> Result<()> {
+        let (secret_key, mut keys) = {
+            let mut state = self.state.lock().unwrap();
+            let secret_key = state.pending_request.take().map(|p| p.secret_key);
+            let mut keys: Vec<AeadSecretKeyHandle> = state
+                .outgoing
+                .drain(..)
+                .map(|message| match message {
+                    OutgoingRekeyMessage::Response { encryption_key, .. } => encryption_key,
+                    OutgoingRekeyMessage::Confirm { encryption_key, .. } => encryption_key,
+                })
+                .collect();
+            keys.extend(
+                state
+                    .pending_confirmation
+                    .take()
+                    .and_then(|p| p.decryption_key),
+            );
+            (secret_key, keys)
+        };
+        if let Some(secret_key) = secret_key {
+            self.vault
+                .delete_ephemeral_x25519_secret_key(secret_key)
+                .await?;
+        }
+        for key in keys.drain(..) {
+            self.vault.delete_aead_secret_key(key).await?;
+        }
+        Ok(())
+    }
+
+    /// Delete the keys of an abandoned re-key
+    async fn delete_keys(
+        &self,
+        secret_key: Option<X25519SecretKeyHandle>,
+        decryption_key: Option<AeadSecretKeyHandle>,
+    ) -> Result<()> {
+        if let Some(secret_key) = secret_key {
+            self.vault
+                .delete_ephemeral_x25519_secret_key(secret_key)
+                .await?;
+        }
+        if let Some(decryption_key) = decryption_key {
+            self.vault.delete_aead_secret_key(decryption_key).await?;
+        }
+        Ok(())
+    }
+
+    /// Derive the keys used by the requester and by the responder of a re-key:
+    ///  k_requester, k_responder = HKDF(H(label || epoch || e_requester || e_responder), DH(e, re))
+    async fn derive_keys(
+        &self,
+        epoch: u64,
+        secret_key: &X25519SecretKeyHandle,
+        their_public_key: &X25519PublicKey,
+        requester_public_key: &X25519PublicKey,
+        responder_public_key: &X25519PublicKey,
+    ) -> Result<(AeadSecretKeyHandle, AeadSecretKeyHandle)> {
+        let mut salt = Sha256::new();
+        salt.update(REKEY_PROTOCOL_NAME);
+        salt.update(epoch.to_be_bytes());
+        salt.update(requester_public_key.0);
+        salt.update(responder_public_key.0);
+        let salt = self
+            .vault
+            .import_secret_buffer(salt.finalize().to_vec())
+            .await?;
+
+        let dh = self.vault.x25519_ecdh(secret_key, their_public_key).await?;
+        let hkdf_output = self
+            .vault
+            .hkdf(&salt, Some(&dh), HKDFNumberOfOutputs::Two)
+            .await;
+        self.vault.delete_secret_buffer(dh).await?;
+        self.vault.delete_secret_buffer(salt).await?;
+
+        let [k1, k2]: [SecretBufferHandle; 2] = hkdf_output?
+            .0
+             .0
+            .try_into()
+            .map_err(|_| XXError::InternalVaultError)?;
+        let k1 = self.vault.convert_secret_buffer_to_aead_key(k1).await?;
+        let k2 = self.vault.convert_secret_buffer_to_aead_key(k2).await?;
+        Ok((k1, k2))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ockam_vault::SoftwareVaultForSecureChannels;
+
+    #[tokio::test]
+    async fn test_rekey_exchange() -> Result<()> {
+        let vault1 = SoftwareVaultForSecureChannels::create().await?;
+        let vault2 = SoftwareVaultForSecureChannels::create().await?;
+        let initiator = Rekeying::new(vault1.clone(), true);
+        let responder = Rekeying::new(vault2.clone(), false);
+
+        let request = initiator.start().await?.unwrap();
+        assert_eq!(request.epoch, 1);
+        assert!(
+            initiator.start().await?.is_none(),
+            "a re-key is already in progress"
+        );
+
+        assert!(responder.handle_request(request).await?);
+        let (response_epoch, public_key, responder_key) = match responder.take_outgoing().pop() {
+            Some(OutgoingRekeyMessage::Response {
+                epoch,
+                public_key,
+                encryption_key,
+            }) => (epoch, public_key, encryption_key),
+            _ => panic!("a response must be sent"),
+        };
+
+        let response = RekeyResponseMessage {
+            epoch: response_epoch,
+            public_key,
+            switch_at: 32,
+        };
+        let (switch_at, initiator_decryption_key) =
+            initiator.handle_response(response).await?.unwrap();
+        assert_eq!(switch_at, 32);
+        let (confirm_epoch, initiator_key) = match initiator.take_outgoing().pop() {
+            Some(OutgoingRekeyMessage::Confirm {
+                epoch,
+                encryption_key,
+            }) => (epoch, encryption_key),
+            _ => panic!("a confirmation must be sent"),
+        };
+
+        let confirm = RekeyConfirmMessage {
+            epoch: confirm_epoch,
+            switch_at: 64,
+        };
+        let (switch_at, responder_decryption_key) = responder.handle_confirm(confirm).unwrap();
+        assert_eq!(switch_at, 64);
+
+        // both sides agree on the keys used in each direction
+        let nonce = [0u8; 12];
+        let ciphertext = vault1
+            .aead_encrypt(&initiator_key, b"hello", &nonce, &[])
+            .await?;
+        let plaintext = vault2
+            .aead_decrypt(&responder_decryption_key, &ciphertext, &nonce, &[])
+            .await?;
+        assert_eq!(plaintext, b"hello");
+
+        let ciphertext = vault2
+            .aead_encrypt(&responder_key, b"hello", &nonce, &[])
+            .await?;
+        let plaintext = vault1
+            .aead_decrypt(&initiator_decryption_key, &ciphertext, &nonce, &[])
+            .await?;
+        assert_eq!(plaintext, b"hello");
+
+        // no ephemeral key is left once the re-key is done
+        assert_eq!(vault1.number_of_ephemeral_x25519_secrets(), 0);
+        assert_eq!(vault2.number_of_ephemeral_x25519_secrets(), 0);
+        assert_eq!(vault1.number_of_ephemeral_buffer_secrets(), 0);
+        assert_eq!(vault2.number_of_ephemeral_buffer_secrets(), 0);
+
+        // a new re-key can be started
+        assert_eq!(responder.start().await?.unwrap().epoch, 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_simultaneous_rekey_requests() -> Result<()> {
+        let initiator = Rekeying::new(SoftwareVaultForSecureChannels::create().await?, true);
+        let responder = Rekeying::new(SoftwareVaultForSecureChannels::create().await?, false);
+
+        let initiator_request = initiator.start().await?.unwrap();
+        let responder_request = responder.start().await?.unwrap();
+
+        // the request of the channel initiator takes precedence
+        assert!(!initiator.handle_request(responder_request).await?);
+        assert!(responder.handle_request(initiator_request).await?);
+        Ok(())
+    }
+
+    /// Answer a re-key request, and return the response which must be sent to the requester
+    async fn respond(
+        responder: &Rekeying,
+        request: RekeyRequestMessage,
+    ) -> Result<RekeyResponseMessage> {
+        assert!(responder.handle_request(request).await?);
+        match responder.take_outgoing().pop() {
+            Some(OutgoingRekeyMessage::Response {
+                epoch, public_key, ..
+            }) => Ok(RekeyResponseMessage {
+                epoch,
+                public_key,
+                switch_at: 32,
+            }),
+            _ => panic!("a response must be sent"),
+        }
+    }
+
+    /// Handle the response to a re-key request, and return the confirmation which must be sent
+    /// to the responder
+    async fn confirm(
+        requester: &Rekeying,
+        response: RekeyResponseMessage,
+    ) -> Result<RekeyConfirmMessage> {
+        assert!(requester.handle_response(response).await?.is_some());
+        match requester.take_outgoing().pop() {
+            Some(OutgoingRekeyMessage::Confirm { epoch, .. }) => Ok(RekeyConfirmMessage {
+                epoch,
+                switch_at: 64,
+            }),
+            _ => panic!("a confirmation must be sent"),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_rekey_after_lost_confirmation() -> Result<()> {
+        let initiator = Rekeying::new(SoftwareVaultForSecureChannels::create().await?, true);
+        let responder = Rekeying::new(SoftwareVaultForSecureChannels::create().await?, false);
+        let responder = Rekeying {
+            pending_timeout: Duration::ZERO,
+            ..responder
+        };
+
+        // the confirmation of the first re-key is lost
+        let response = respond(&responder, initiator.start().await?.unwrap()).await?;
+        confirm(&initiator, response).await?;
+
+        // the unconfirmed key is kept, even after a timeout, since the initiator uses it
+        let request = initiator.start().await?.unwrap();
+        assert_eq!(request.epoch, 2);
+        assert!(!responder.handle_request(request.clone()).await?);
+        assert!(responder.start().await?.is_none());
+        let unconfirmed_key = responder.unconfirmed_key().unwrap();
+
+        // the re-key is confirmed once a message is decrypted with that key
+        assert_eq!(responder.handle_implicit_confirm(&unconfirmed_key), Some(1));
+        assert!(responder.unconfirmed_key().is_none());
+
+        let response = respond(&responder, request).await?;
+        assert_eq!(response.epoch, 2);
+        let confirm = confirm(&initiator, response).await?;
+        assert!(responder.handle_confirm(confirm).is_some());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_rekey_after_lost_response() -> Result<()> {
+        let vault = SoftwareVaultForSecureChannels::create().await?;
+        let initiator = Rekeying::new(SoftwareVaultForSecureChannels::create().await?, true);
+        let responder = Rekeying::new(vault.clone(), false);
+        let initiator = Rekeying {
+            pending_timeout: Duration::ZERO,
+            ..initiator
+        };
+
+        // the response to the first request is lost, the request is sent again once it expires
+        respond(&responder, initiator.start().await?.unwrap()).await?;
+        let request = initiator.start().await?.unwrap();
+        assert_eq!(request.epoch, 1);
+
+        // the responder answers it again, and the decryption key of its first response
+        // is replaced by the new one
+        let aead_secrets = vault.number_of_ephemeral_aead_secrets();
+        let response = respond(&responder, request).await?;
+        assert_eq!(vault.number_of_ephemeral_aead_secrets(), aead_secrets + 1);
+        let confirm = confirm(&initiator, response).await?;
+        assert!(responder.handle_confirm(confirm).is_some());
+        assert_eq!(initiator.start().await?.unwrap().epoch, 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,91 @@
+use core::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+
+use crate::secure_channel::encryptor::KEY_RENEWAL_INTERVAL;
+use crate::TimestampInSeconds;
+
+/// Counters describing the usage of the keys of one side of a secure channel.
+///
+/// They can be used to monitor long-lived channels and detect channels which are close
+/// to exhausting their keys.
+#[derive(Debug, Default)]
+pub struct SecureChannelStatistics {
+    encrypted_messages: AtomicU64,
+    decrypted_messages: AtomicU64,
+    next_encryption_nonce: AtomicU64,
+    decryption_key_renewals: AtomicU64,
+    decryption_keys_exhausted: AtomicBool,
+    rekeys: AtomicU64,
+    last_rekey_at: AtomicU64,
+}
+
+impl SecureChannelStatistics {
+    /// Number of messages encrypted by this side of the channel
+    pub fn encrypted_messages(&self) -> u64 {
+        self.encrypted_messages.load(Ordering::Relaxed)
+    }
+
+    /// Number of messages successfully decrypted by this side of the channel
+    pub fn decrypted_messages(&self) -> u64 {
+        self.decrypted_messages.load(Ordering::Relaxed)
+    }
+
+    /// Number of times the encryption key was renewed, either by deriving it from the previous
+    /// key or by installing a key obtained with an explicit re-key
+    pub fn encryption_key_renewals(&self) -> u64 {
+        self.next_encryption_nonce().saturating_sub(1) / KEY_RENEWAL_INTERVAL
+    }
+
+    /// Number of times the decryption key was renewed
+    pub fn decryption_key_renewals(&self) -> u64 {
+        self.decryption_key_renewals.load(Ordering::Relaxed)
+    }
+
+    /// Number of nonces which can still be used for encryption before the channel
+    /// must be closed
+    pub fn remaining_encryption_nonces(&self) -> u64 {
+        u64::MAX - self.next_encryption_nonce()
+    }
+
+    /// True if the decryption keys are exhausted and no more messages can be decrypted
+    pub fn decryption_keys_exhausted(&self) -> bool {
+        self.decryption_keys_exhausted.load(Ordering::Relaxed)
+    }
+
+    /// Number of explicit re-keys completed on this channel
+    pub fn rekeys(&self) -> u64 {
+        self.rekeys.load(Ordering::Relaxed)
+    }
+
+    /// Time of the last explicit re-key, if any
+    pub fn last_rekey_at(&self) -> Option<TimestampInSeconds> {
+        match self.last_rekey_at.load(Ordering::Relaxed) {
+            0 => None,
+            last_rekey_at => Some(last_rekey_at.into()),
+        }
+    }
+
+    fn next_encryption_nonce(&self) -> u64 {
+        self.next_encryption_nonce.load(Ordering::Relaxed)
+    }
+}
+
+impl SecureChannelStatistics {
+    pub(crate) fn record_encryption(&self, next_nonce: u64) {
+        self.encrypted_messages.fetch_add(1, Ordering::Relaxed);
+        self.next_encryption_nonce
+            .store(next_nonce, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_decryption(&self, key_renewals: u64, keys_exhausted: bool) {
+        self.decrypted_messages.fetch_add(1, Ordering::Relaxed);
+        self.decryption_key_renewals
+            .store(key_renewals, Ordering::Relaxed);
+        self.decryption_keys_exhausted
+            .store(keys_exhausted, Ordering::Relaxed);
+    }
+
+    pub(crate) fn record_rekey(&self, now: TimestampInSeconds) {
+        self.rekeys.fetch_add(1, Ordering::Relaxed);
+        self.last_rekey_at.store(*now, Ordering::Relaxed);
+    }
+}
@@ -152,6 +152,9 @@ impl SecureChannels {
             credentials,
             options.min_credential_refresh_interval,
             options.credential_refresh_time_gap,
+            options.rekey_triggers,
+            options.key_exchange_policy,
+            None,
             options.trust_context,
             Some(route),
             Some(options.timeout),
@@ -7,7 +7,7 @@ use crate::identities::{ChangeHistoryRepository, Identities};
 use crate::secure_channel::SecureChannelRegistry;
 use crate::secure_channels::SecureChannels;
 use crate::storage::PurposeKeysRepository;
-use crate::{IdentitiesBuilder, IdentityAttributesRepository, Vault};
+use crate::{IdentitiesBuilder, IdentityAttributesRepository, RevocationsRepository, Vault};
 
 /// This struct supports all the services related to secure channels
 #[derive(Clone)]
@@ -69,6 +69,17 @@ impl SecureChannelsBuilder {
         self
     }
 
+    /// Set a specific credential revocations repository
+    pub fn with_revocations_repository(
+        mut self,
+        repository: Arc<dyn RevocationsRepository>,
+    ) -> Self {
+        self.identities_builder = self
+            .identities_builder
+            .with_revocations_repository(repository);
+        self
+    }
+
     /// Set a specific identities
     pub fn with_identities(mut self, identities: Arc<Identities>) -> Self {
         self.identities_builder = self
@@ -76,7 +87,8 @@ impl SecureChannelsBuilder {
             .with_change_history_repository(identities.change_history_repository())
             .with_identity_attributes_repository(identities.identity_attributes_repository())
             .with_vault(identities.vault())
-            .with_purpose_keys_repository(identities.purpose_keys_repository());
+            .with_purpose_keys_repository(identities.purpose_keys_repository())
+            .with_revocations_repository(identities.revocations_repository());
         self
     }
 
@@ -8,9 +8,9 @@ use ockam_identity::secure_channels::secure_channels;
 use ockam_identity::utils::AttributesBuilder;
 use ockam_identity::{
     AuthorityService, DecryptionResponse, EncryptionRequest, EncryptionResponse,
-    IdentityAccessControlBuilder, IdentitySecureChannelLocalInfo, SecureChannelListenerOptions,
-    SecureChannelOptions, SecureChannels, TrustContext, TrustEveryonePolicy, TrustIdentifierPolicy,
-    Vault,
+    IdentityAccessControlBuilder, IdentitySecureChannelLocalInfo, KeyExchangePolicy,
+    SecureChannelListenerOptions, SecureChannelOptions, SecureChannels, TrustContext,
+    TrustEveryonePolicy, TrustIdentifierPolicy, Vault,
 };
 use ockam_node::{Context, MessageReceiveOptions, WorkerBuilder};
 use ockam_vault::{
@@ -340,6 +340,286 @@ async fn test_channel_send_multiple_messages_both_directions(ctx: &mut Context)
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn test_channel_explicit_rekey(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities_creation = secure_channels.identities().identities_creation();
+
+    let alice = identities_creation.create_identity().await?;
+    let bob = identities_creation.create_identity().await?;
+
+    let bob_options = SecureChannelListenerOptions::new().with_rekey_message_count(10);
+    let sc_listener_flow_control_id = bob_options.spawner_flow_control_id();
+    secure_channels
+        .create_secure_channel_listener(ctx, &bob, "bob_listener", bob_options)
+        .await?;
+
+    let alice_options = SecureChannelOptions::new().with_rekey_message_count(25);
+    let sc_flow_control_id = alice_options.producer_flow_control_id();
+    let alice_channel = secure_channels
+        .create_secure_channel(ctx, &alice, route!["bob_listener"], alice_options)
+        .await?;
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    child_ctx
+        .flow_controls()
+        .add_consumer(child_ctx.address(), &sc_listener_flow_control_id);
+    child_ctx
+        .flow_controls()
+        .add_consumer(child_ctx.address(), &sc_flow_control_id);
+
+    for n in 0..200 {
+        let payload = format!("Hello, Bob! {}", n);
+        child_ctx
+            .send(
+                route![alice_channel.clone(), child_ctx.address()],
+                payload.clone(),
+            )
+            .await?;
+
+        let message = child_ctx.receive::<String>().await?;
+        assert_eq!(&payload, message.as_body());
+
+        let payload = format!("Hello, Alice! {}", n);
+        child_ctx
+            .send(message.return_route(), payload.clone())
+            .await?;
+
+        let message = child_ctx.receive::<String>().await?;
+        assert_eq!(&payload, message.as_body());
+    }
+
+    let alice_entry = secure_channels
+        .secure_channel_registry()
+        .get_channel_by_encryptor_address(alice_channel.encryptor_address())
+        .unwrap();
+    let bob_entry = secure_channels
+        .secure_channel_registry()
+        .get_channel_list()
+        .into_iter()
+        .find(|entry| !entry.is_initiator())
+        .unwrap();
+
+    for entry in [alice_entry, bob_entry] {
+        let statistics = entry.statistics();
+        assert!(statistics.rekeys() > 1);
+        assert!(statistics.last_rekey_at().is_some());
+        assert!(statistics.encrypted_messages() > 200);
+        assert!(statistics.decrypted_messages() > 200);
+        assert!(statistics.encryption_key_renewals() > 0);
+        assert!(statistics.decryption_key_renewals() > 0);
+        assert!(!statistics.decryption_keys_exhausted());
+    }
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn test_channel_hybrid_key_exchange(ctx: &mut Context) -> Result<()> {
+    let alice_sc_vault = SoftwareVaultForSecureChannels::create().await?;
+    let bob_sc_vault = SoftwareVaultForSecureChannels::create().await?;
+
+    let is_hybrid = create_channel_with_key_exchange_policies(
+        ctx,
+        (alice_sc_vault.clone(), KeyExchangePolicy::RequireHybrid),
+        (bob_sc_vault.clone(), KeyExchangePolicy::PreferHybrid),
+    )
+    .await?;
+    assert!(is_hybrid);
+
+    // the ML-KEM keys and secrets are deleted after the handshake
+    for vault in [alice_sc_vault, bob_sc_vault] {
+        assert_eq!(vault.number_of_ephemeral_ml_kem_secrets(), 0);
+        assert_eq!(vault.number_of_ephemeral_buffer_secrets(), 0);
+        assert_eq!(vault.number_of_ephemeral_x25519_secrets(), 0);
+    }
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn test_channel_hybrid_initiator__classic_responder(ctx: &mut Context) -> Result<()> {
+    let alice_sc_vault = SoftwareVaultForSecureChannels::create().await?;
+    let is_hybrid = create_channel_with_key_exchange_policies(
+        ctx,
+        (alice_sc_vault.clone(), KeyExchangePolicy::PreferHybrid),
+        (
+            SoftwareVaultForSecureChannels::create().await?,
+            KeyExchangePolicy::Classic,
+        ),
+    )
+    .await?;
+    assert!(!is_hybrid);
+
+    // the unused ML-KEM key of the initiator is deleted
+    assert_eq!(alice_sc_vault.number_of_ephemeral_ml_kem_secrets(), 0);
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn test_channel_classic_initiator__hybrid_responder(ctx: &mut Context) -> Result<()> {
+    let is_hybrid = create_channel_with_key_exchange_policies(
+        ctx,
+        (
+            SoftwareVaultForSecureChannels::create().await?,
+            KeyExchangePolicy::Classic,
+        ),
+        (
+            SoftwareVaultForSecureChannels::create().await?,
+            KeyExchangePolicy::PreferHybrid,
+        ),
+    )
+    .await?;
+    assert!(!is_hybrid);
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn test_channel_required_hybrid_initiator__classic_responder__should_fail(
+    ctx: &mut Context,
+) -> Result<()> {
+    let result = create_channel_with_key_exchange_policies(
+        ctx,
+        (
+            SoftwareVaultForSecureChannels::create().await?,
+            KeyExchangePolicy::RequireHybrid,
+        ),
+        (
+            SoftwareVaultForSecureChannels::create().await?,
+            KeyExchangePolicy::Classic,
+        ),
+    )
+    .await;
+    assert!(result.is_err());
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn test_channel_classic_initiator__required_hybrid_responder__should_fail(
+    ctx: &mut Context,
+) -> Result<()> {
+    let result = create_channel_with_key_exchange_policies(
+        ctx,
+        (
+            SoftwareVaultForSecureChannels::create().await?,
+            KeyExchangePolicy::Classic,
+        ),
+        (
+            SoftwareVaultForSecureChannels::create().await?,
+            KeyExchangePolicy::RequireHybrid,
+        ),
+    )
+    .await;
+    assert!(result.is_err());
+
+    ctx.stop().await
+}
+
+/// Create a secure channel between alice and bob, using a given vault and key exchange policy
+/// for each of them, and exchange messages.
+/// Return true if the key exchange was hybrid
+async fn create_channel_with_key_exchange_policies(
+    ctx: &mut Context,
+    (alice_sc_vault, alice_policy): (Arc<SoftwareVaultForSecureChannels>, KeyExchangePolicy),
+    (bob_sc_vault, bob_policy): (Arc<SoftwareVaultForSecureChannels>, KeyExchangePolicy),
+) -> Result<bool> {
+    let mut alice_vault = Vault::create().await?;
+    alice_vault.secure_channel_vault = alice_sc_vault;
+    let mut bob_vault = Vault::create().await?;
+    bob_vault.secure_channel_vault = bob_sc_vault;
+
+    let secure_channels_alice = SecureChannels::builder()
+        .await?
+        .with_vault(alice_vault)
+        .build();
+    let secure_channels_bob = SecureChannels::builder()
+        .await?
+        .with_vault(bob_vault)
+        .build();
+
+    let alice = secure_channels_alice
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+    let bob = secure_channels_bob
+        .identities()
+        .identities_creation()
+        .create_identity()
+        .await?;
+
+    let bob_options = SecureChannelListenerOptions::new().with_key_exchange_policy(bob_policy);
+    let sc_listener_flow_control_id = bob_options.spawner_flow_control_id();
+    secure_channels_bob
+        .create_secure_channel_listener(ctx, &bob, "bob_listener", bob_options)
+        .await?;
+
+    let alice_options = SecureChannelOptions::new()
+        .with_key_exchange_policy(alice_policy)
+        .with_timeout(Duration::from_millis(500));
+    let alice_channel = secure_channels_alice
+        .create_secure_channel(ctx, &alice, route!["bob_listener"], alice_options)
+        .await?;
+
+    let mut child_ctx = ctx
+        .new_detached_with_mailboxes(Mailboxes::main(
+            "child",
+            Arc::new(AllowAll),
+            Arc::new(AllowAll),
+        ))
+        .await?;
+    child_ctx
+        .flow_controls()
+        .add_consumer(child_ctx.address(), &sc_listener_flow_control_id);
+    child_ctx
+        .flow_controls()
+        .add_consumer(child_ctx.address(), alice_channel.flow_control_id());
+
+    child_ctx
+        .send(
+            route![alice_channel.clone(), child_ctx.address()],
+            "Hello, Bob!".to_string(),
+        )
+        .await?;
+    let message = child_ctx.receive::<String>().await?;
+    assert_eq!("Hello, Bob!", message.as_body());
+
+    child_ctx
+        .send(message.return_route(), "Hello, Alice!".to_string())
+        .await?;
+    let message = child_ctx.receive::<String>().await?;
+    assert_eq!("Hello, Alice!", message.as_body());
+
+    let alice_entry = secure_channels_alice
+        .secure_channel_registry()
+        .get_channel_by_encryptor_address(alice_channel.encryptor_address())
+        .unwrap();
+    let bob_entry = secure_channels_bob
+        .secure_channel_registry()
+        .get_channel_list()
+        .pop()
+        .unwrap();
+    assert_eq!(
+        alice_entry.is_hybrid_key_exchange(),
+        bob_entry.is_hybrid_key_exchange()
+    );
+
+    Ok(alice_entry.is_hybrid_key_exchange())
+}
+
 #[ockam_macros::test]
 async fn test_channel_registry(ctx: &mut Context) -> Result<()> {
     let secure_channels = secure_channels().await?;
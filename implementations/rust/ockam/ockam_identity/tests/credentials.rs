@@ -1,6 +1,7 @@
 use std::sync::atomic::{AtomicI8, Ordering};
 use std::time::Duration;
 
+use minicbor::bytes::ByteVec;
 use ockam_core::compat::sync::Arc;
 use ockam_core::{async_trait, Any, DenyAll};
 use ockam_core::{route, Result, Routed, Worker};
@@ -8,7 +9,7 @@ use ockam_identity::models::CredentialSchemaIdentifier;
 use ockam_identity::secure_channels::secure_channels;
 use ockam_identity::utils::AttributesBuilder;
 use ockam_identity::{
-    AuthorityService, CredentialAccessControl, CredentialsMemoryRetriever,
+    identities, AuthorityService, CredentialAccessControl, CredentialsMemoryRetriever,
     SecureChannelListenerOptions, SecureChannelOptions, TrustContext, TrustIdentifierPolicy,
 };
 use ockam_node::{Context, WorkerBuilder};
@@ -274,8 +275,11 @@ async fn access_control(ctx: &mut Context) -> Result<()> {
     };
 
     let required_attributes = vec![(b"is_superuser".to_vec(), b"true".to_vec())];
-    let access_control =
-        CredentialAccessControl::new(&required_attributes, identity_attributes_repository.clone());
+    let access_control = CredentialAccessControl::new(
+        &required_attributes,
+        identity_attributes_repository.clone(),
+        identities.revocations_repository(),
+    );
 
     ctx.flow_controls()
         .add_consumer("counter", listener.flow_control_id());
@@ -310,6 +314,646 @@ async fn access_control(ctx: &mut Context) -> Result<()> {
     ctx.stop().await
 }
 
+#[ockam_macros::test]
+async fn access_control_with_revoked_subject(ctx: &mut Context) -> Result<()> {
+    access_control_with_revocation(ctx, true).await
+}
+
+#[ockam_macros::test]
+async fn access_control_with_revoked_credential(ctx: &mut Context) -> Result<()> {
+    access_control_with_revocation(ctx, false).await
+}
+
+/// Check that a revocation, of the subject or of its credential, stops the messages
+/// of an established secure channel
+async fn access_control_with_revocation(ctx: &mut Context, revoke_subject: bool) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities = secure_channels.identities();
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+    let credentials_service = identities.credentials_server();
+
+    let authority = identities_creation.create_identity().await?;
+    let server = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            &server,
+            "listener",
+            SecureChannelListenerOptions::new(),
+        )
+        .await?;
+
+    let trust_context = TrustContext::new(
+        "test_trust_context_id".to_string(),
+        Some(AuthorityService::new(
+            credentials.clone(),
+            authority.clone(),
+            None,
+        )),
+    );
+
+    ctx.flow_controls()
+        .add_consumer("credential_exchange", listener.flow_control_id());
+    credentials_service
+        .start(
+            ctx,
+            trust_context,
+            server.clone(),
+            "credential_exchange".into(),
+            false,
+        )
+        .await?;
+
+    let channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            &client,
+            route!["listener"],
+            SecureChannelOptions::new()
+                .with_trust_policy(TrustIdentifierPolicy::new(server.clone())),
+        )
+        .await?;
+
+    let credential = credentials
+        .credentials_creation()
+        .issue_credential(
+            &authority,
+            &client,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("is_superuser", "true")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+
+    let counter = Arc::new(AtomicI8::new(0));
+    let worker = CountingWorker {
+        msgs_count: counter.clone(),
+    };
+    let required_attributes = vec![(b"is_superuser".to_vec(), b"true".to_vec())];
+    let access_control = CredentialAccessControl::new(
+        &required_attributes,
+        identities.identity_attributes_repository(),
+        identities.revocations_repository(),
+    );
+
+    ctx.flow_controls()
+        .add_consumer("counter", listener.flow_control_id());
+    WorkerBuilder::new(worker)
+        .with_address("counter")
+        .with_incoming_access_control(access_control)
+        .with_outgoing_access_control(DenyAll)
+        .start(ctx)
+        .await?;
+
+    credentials_service
+        .present_credential(
+            ctx,
+            route![channel.clone(), "credential_exchange"],
+            credential.clone(),
+        )
+        .await?;
+
+    ctx.send(route![channel.clone(), "counter"], "Hello".to_string())
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(counter.load(Ordering::Relaxed), 1);
+
+    // the authority revokes the client, or its credential, and the server receives the new
+    // revocation list
+    let revocation_list = if revoke_subject {
+        credentials
+            .credentials_creation()
+            .issue_revocation_list(&authority, vec![], vec![client.clone()])
+            .await?
+    } else {
+        let credential_hash = credentials
+            .credentials_verification()
+            .credential_hash(&credential.credential)
+            .await?;
+        credentials
+            .credentials_creation()
+            .issue_revocation_list(&authority, vec![credential_hash], vec![])
+            .await?
+    };
+    let updated = credentials
+        .credentials_verification()
+        .receive_revocation_list(&[authority.clone()], &revocation_list)
+        .await?;
+    assert!(updated);
+
+    // messages from the existing secure channel are now rejected
+    ctx.send(route![channel.clone(), "counter"], "Hello".to_string())
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(counter.load(Ordering::Relaxed), 1);
+
+    // and the credential can't be presented again
+    let res = credentials_service
+        .present_credential(
+            ctx,
+            route![channel.clone(), "credential_exchange"],
+            credential,
+        )
+        .await;
+    assert!(res.is_err());
+
+    ctx.send(route![channel, "counter"], "Hello".to_string())
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(counter.load(Ordering::Relaxed), 1);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn selective_disclosure(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities = secure_channels.identities();
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+
+    let authority = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let credential = credentials
+        .credentials_creation()
+        .issue_selective_disclosure_credential(
+            &authority,
+            &client,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("name", "alice")
+                .with_attribute("role", "admin")
+                .with_attribute("email", "alice@example.com")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+
+    // the signed credential data only contains commitments to the attributes
+    let credential_data = credential.get_credential_data()?;
+    assert!(credential_data.subject_attributes.map.is_empty());
+    assert_eq!(
+        credential_data.subject_attribute_commitments.unwrap().len(),
+        3
+    );
+
+    // only the disclosed attributes are verified
+    let disclosed = credential.disclose(&[b"role".to_vec()]);
+    let verified = credentials
+        .credentials_verification()
+        .verify_credential(Some(&client), &[authority.clone()], &disclosed)
+        .await?;
+    let attributes = verified.credential_data.subject_attributes.map;
+    assert_eq!(attributes.len(), 1);
+    assert_eq!(
+        attributes
+            .get(&ByteVec::from(b"role".to_vec()))
+            .unwrap()
+            .as_slice(),
+        b"admin"
+    );
+
+    // a disclosure which doesn't match its commitment is rejected
+    let mut tampered = disclosed.clone();
+    tampered.disclosures.as_mut().unwrap()[0].value = b"superuser".to_vec().into();
+    assert!(credentials
+        .credentials_verification()
+        .verify_credential(Some(&client), &[authority.clone()], &tampered)
+        .await
+        .is_err());
+
+    // as well as the same attribute disclosed twice
+    let mut duplicated = disclosed.clone();
+    let disclosure = duplicated.disclosures.as_ref().unwrap()[0].clone();
+    duplicated.disclosures.as_mut().unwrap().push(disclosure);
+    assert!(credentials
+        .credentials_verification()
+        .verify_credential(Some(&client), &[authority.clone()], &duplicated)
+        .await
+        .is_err());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn access_control_with_requested_attributes(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities = secure_channels.identities();
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+
+    let authority = identities_creation.create_identity().await?;
+    let server = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let trust_context = TrustContext::new(
+        "test_trust_context_id".to_string(),
+        Some(AuthorityService::new(
+            credentials.clone(),
+            authority.clone(),
+            None,
+        )),
+    );
+
+    let required_attributes = vec![(b"role".to_vec(), b"admin".to_vec())];
+    let access_control = CredentialAccessControl::new(
+        &required_attributes,
+        identities.identity_attributes_repository(),
+        identities.revocations_repository(),
+    );
+
+    // the listener only asks for the attributes needed by the access control
+    let listener = secure_channels
+        .create_secure_channel_listener(
+            ctx,
+            &server,
+            "listener",
+            SecureChannelListenerOptions::new()
+                .with_trust_context(trust_context.clone())
+                .with_requested_attributes(access_control.requested_attributes()),
+        )
+        .await?;
+
+    let counter = Arc::new(AtomicI8::new(0));
+    let worker = CountingWorker {
+        msgs_count: counter.clone(),
+    };
+    ctx.flow_controls()
+        .add_consumer("counter", listener.flow_control_id());
+    WorkerBuilder::new(worker)
+        .with_address("counter")
+        .with_incoming_access_control(access_control)
+        .with_outgoing_access_control(DenyAll)
+        .start(ctx)
+        .await?;
+
+    let credential = credentials
+        .credentials_creation()
+        .issue_selective_disclosure_credential(
+            &authority,
+            &client,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("role", "admin")
+                .with_attribute("email", "alice@example.com")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+
+    let channel = secure_channels
+        .create_secure_channel(
+            ctx,
+            &client,
+            route!["listener"],
+            SecureChannelOptions::new()
+                .with_trust_context(trust_context)
+                .with_credential(credential),
+        )
+        .await?;
+
+    ctx.send(route![channel, "counter"], "Hello".to_string())
+        .await?;
+    ctx.sleep(Duration::from_millis(100)).await;
+    assert_eq!(counter.load(Ordering::Relaxed), 1);
+
+    // the attributes which were not requested are not disclosed to the server
+    let attributes = identities
+        .identity_attributes_repository()
+        .get_attributes(&client)
+        .await?
+        .unwrap();
+    assert_eq!(
+        attributes
+            .attrs()
+            .get("role".as_bytes())
+            .unwrap()
+            .as_slice(),
+        b"admin"
+    );
+    assert!(attributes.attrs().get("email".as_bytes()).is_none());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn delegated_credentials(ctx: &mut Context) -> Result<()> {
+    let secure_channels = secure_channels().await?;
+    let identities = secure_channels.identities();
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+    let credentials_creation = credentials.credentials_creation();
+    let credentials_verification = credentials.credentials_verification();
+
+    let authority = identities_creation.create_identity().await?;
+    let team_issuer = identities_creation.create_identity().await?;
+    let ops_issuer = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let team_delegation = credentials_creation
+        .issue_delegation(
+            &authority,
+            &team_issuer,
+            vec![b"team-a.".to_vec()],
+            Duration::from_secs(120),
+        )
+        .await?;
+
+    // the team issuer can issue attributes in its namespace
+    let credential = credentials_creation
+        .issue_delegated_credential(
+            &team_issuer,
+            &client,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("team-a.role", "admin")
+                .build(),
+            vec![team_delegation.clone()],
+            Duration::from_secs(3600),
+        )
+        .await?;
+    let verified = credentials_verification
+        .verify_credential(Some(&client), &[authority.clone()], &credential)
+        .await?;
+    assert_eq!(verified.purpose_key_data.subject, team_issuer);
+    // the credential doesn't outlive its delegation
+    assert_eq!(
+        verified.credential_data.expires_at,
+        team_delegation.delegation.get_delegation_data()?.expires_at
+    );
+
+    // the credential is not valid without its delegation
+    let mut without_delegation = credential.clone();
+    without_delegation.delegations = None;
+    assert!(credentials_verification
+        .verify_credential(Some(&client), &[authority.clone()], &without_delegation)
+        .await
+        .is_err());
+
+    // attributes outside of the delegated namespace can't be issued...
+    assert!(credentials_creation
+        .issue_delegated_credential(
+            &team_issuer,
+            &client,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("team-b.role", "admin")
+                .build(),
+            vec![team_delegation.clone()],
+            Duration::from_secs(60),
+        )
+        .await
+        .is_err());
+
+    // ...nor verified if the delegation is attached to a regular credential
+    let mut outside_namespace = credentials_creation
+        .issue_credential(
+            &team_issuer,
+            &client,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("team-b.role", "admin")
+                .build(),
+            Duration::from_secs(60),
+        )
+        .await?;
+    outside_namespace.delegations = Some(vec![team_delegation.clone()]);
+    assert!(credentials_verification
+        .verify_credential(Some(&client), &[authority.clone()], &outside_namespace)
+        .await
+        .is_err());
+
+    // the team issuer can delegate a part of its namespace
+    let ops_delegation = credentials_creation
+        .issue_delegation(
+            &team_issuer,
+            &ops_issuer,
+            vec![b"team-a.ops.".to_vec()],
+            Duration::from_secs(60),
+        )
+        .await?;
+    let ops_credential = credentials_creation
+        .issue_delegated_credential(
+            &ops_issuer,
+            &client,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("team-a.ops.on_call", "true")
+                .build(),
+            vec![team_delegation.clone(), ops_delegation],
+            Duration::from_secs(60),
+        )
+        .await?;
+    credentials_verification
+        .verify_credential(Some(&client), &[authority.clone()], &ops_credential)
+        .await?;
+
+    // but not more than its namespace
+    let broader_delegation = credentials_creation
+        .issue_delegation(
+            &team_issuer,
+            &ops_issuer,
+            vec![b"team-".to_vec()],
+            Duration::from_secs(60),
+        )
+        .await?;
+    let broader_credential = credentials_creation
+        .issue_delegated_credential(
+            &ops_issuer,
+            &client,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("team-b.role", "admin")
+                .build(),
+            vec![team_delegation.clone(), broader_delegation],
+            Duration::from_secs(60),
+        )
+        .await?;
+    assert!(credentials_verification
+        .verify_credential(Some(&client), &[authority.clone()], &broader_credential)
+        .await
+        .is_err());
+
+    // a chain which doesn't start with a trusted authority is rejected
+    assert!(credentials_verification
+        .verify_credential(Some(&client), &[team_issuer.clone()], &credential)
+        .await
+        .is_err());
+
+    // when the authority revokes the team issuer, its credentials are rejected
+    let revocation_list = credentials_creation
+        .issue_revocation_list(&authority, vec![], vec![team_issuer.clone()])
+        .await?;
+    credentials_verification
+        .receive_revocation_list(&[authority.clone()], &revocation_list)
+        .await?;
+    assert!(credentials_verification
+        .verify_credential(Some(&client), &[authority.clone()], &credential)
+        .await
+        .is_err());
+    assert!(credentials_verification
+        .verify_credential(Some(&client), &[authority.clone()], &ops_credential)
+        .await
+        .is_err());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn delegated_credentials_verified_by_another_node(ctx: &mut Context) -> Result<()> {
+    let issuer_identities = identities().await?;
+    let identities_creation = issuer_identities.identities_creation();
+    let credentials_creation = issuer_identities.credentials().credentials_creation();
+
+    let authority = identities_creation.create_identity().await?;
+    let team_issuer = identities_creation.create_identity().await?;
+    let ops_issuer = identities_creation.create_identity().await?;
+    let client = identities_creation.create_identity().await?;
+
+    let team_delegation = credentials_creation
+        .issue_delegation(
+            &authority,
+            &team_issuer,
+            vec![b"team-a.".to_vec()],
+            Duration::from_secs(120),
+        )
+        .await?;
+    let ops_delegation = credentials_creation
+        .issue_delegation(
+            &team_issuer,
+            &ops_issuer,
+            vec![b"team-a.ops.".to_vec()],
+            Duration::from_secs(60),
+        )
+        .await?;
+    let credential = credentials_creation
+        .issue_delegated_credential(
+            &ops_issuer,
+            &client,
+            AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                .with_attribute("team-a.ops.on_call", "true")
+                .build(),
+            vec![team_delegation, ops_delegation],
+            Duration::from_secs(30),
+        )
+        .await?;
+
+    // the verifier only knows the authority
+    let verifier_identities = identities().await?;
+    verifier_identities
+        .identities_creation()
+        .import(
+            Some(&authority),
+            &issuer_identities.export_identity(&authority).await?,
+        )
+        .await?;
+    let verified = verifier_identities
+        .credentials()
+        .credentials_verification()
+        .verify_credential(Some(&client), &[authority.clone()], &credential)
+        .await?;
+    assert_eq!(verified.purpose_key_data.subject, ops_issuer);
+
+    // the change history of a delegate must be the one of the delegated identity
+    let mut credential = credential.clone();
+    let delegations = credential.delegations.as_mut().unwrap();
+    delegations[1].delegate_change_history = delegations[0].delegate_change_history.clone();
+    let other_verifier_identities = identities().await?;
+    other_verifier_identities
+        .identities_creation()
+        .import(
+            Some(&authority),
+            &issuer_identities.export_identity(&authority).await?,
+        )
+        .await?;
+    assert!(other_verifier_identities
+        .credentials()
+        .credentials_verification()
+        .verify_credential(Some(&client), &[authority.clone()], &credential)
+        .await
+        .is_err());
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn delegated_credentials_revoked_by_the_authority(ctx: &mut Context) -> Result<()> {
+    let identities = identities().await?;
+    let identities_creation = identities.identities_creation();
+    let credentials = identities.credentials();
+    let credentials_creation = credentials.credentials_creation();
+    let credentials_verification = credentials.credentials_verification();
+
+    let authority = identities_creation.create_identity().await?;
+    let team_issuer = identities_creation.create_identity().await?;
+    let client1 = identities_creation.create_identity().await?;
+    let client2 = identities_creation.create_identity().await?;
+
+    let team_delegation = credentials_creation
+        .issue_delegation(
+            &authority,
+            &team_issuer,
+            vec![b"team-a.".to_vec()],
+            Duration::from_secs(120),
+        )
+        .await?;
+    let mut delegated_credentials = vec![];
+    for client in [&client1, &client2] {
+        let credential = credentials_creation
+            .issue_delegated_credential(
+                &team_issuer,
+                client,
+                AttributesBuilder::with_schema(CredentialSchemaIdentifier(0))
+                    .with_attribute("team-a.role", "admin")
+                    .build(),
+                vec![team_delegation.clone()],
+                Duration::from_secs(60),
+            )
+            .await?;
+        let verified = credentials_verification
+            .verify_credential(Some(client), &[authority.clone()], &credential)
+            .await?;
+        assert_eq!(verified.authority, authority);
+
+        // the stored attributes are attested by the authority of the delegation chain
+        credentials_verification
+            .receive_presented_credential(client, &[authority.clone()], &credential)
+            .await?;
+        let attributes = identities
+            .identity_attributes_repository()
+            .get_attributes(client)
+            .await?
+            .unwrap();
+        assert_eq!(attributes.attested_by(), Some(authority.clone()));
+        delegated_credentials.push(credential);
+    }
+
+    // the authority revokes the credential of the first client and the second client
+    let credential_hash = credentials_verification
+        .credential_hash(&delegated_credentials[0].credential)
+        .await?;
+    let revocation_list = credentials_creation
+        .issue_revocation_list(&authority, vec![credential_hash], vec![client2.clone()])
+        .await?;
+    assert!(
+        credentials_verification
+            .receive_revocation_list(&[authority.clone()], &revocation_list)
+            .await?
+    );
+
+    for (client, credential) in [&client1, &client2].iter().zip(delegated_credentials) {
+        assert!(credentials_verification
+            .verify_credential(Some(client), &[authority.clone()], &credential)
+            .await
+            .is_err());
+        assert!(identities
+            .identity_attributes_repository()
+            .get_attributes(client)
+            .await?
+            .is_none());
+    }
+
+    ctx.stop().await
+}
+
 struct CountingWorker {
     msgs_count: Arc<AtomicI8>,
 }
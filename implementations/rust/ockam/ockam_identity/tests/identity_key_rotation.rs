@@ -0,0 +1,127 @@
+use core::time::Duration;
+
+use ockam_core::Result;
+use ockam_identity::models::TimestampInSeconds;
+use ockam_identity::{identities, secure_channels, IdentityKeyRotationPolicy, IdentityKeyRotator};
+use ockam_vault::{SigningKeyType, VerifyingPublicKey};
+
+#[tokio::test]
+async fn rotate_identity_key_keeps_the_key_type() -> Result<()> {
+    let identities = identities().await?;
+    let identities_creation = identities.identities_creation();
+
+    let identifier = identities_creation
+        .identity_builder()
+        .with_random_key(SigningKeyType::ECDSASHA256CurveP256)
+        .build()
+        .await?;
+    let before = identities.get_identity(&identifier).await?;
+
+    let after = identities.rotate_identity_key(&identifier, false).await?;
+
+    assert_eq!(after.identifier(), &identifier);
+    assert_eq!(after.changes().len(), 2);
+    assert_ne!(
+        before.get_latest_public_key()?,
+        after.get_latest_public_key()?
+    );
+    assert!(matches!(
+        after.get_latest_public_key()?,
+        VerifyingPublicKey::ECDSASHA256CurveP256(_)
+    ));
+
+    // the new change is persisted
+    let stored = identities.get_identity(&identifier).await?;
+    assert_eq!(stored.latest_change_hash()?, after.latest_change_hash()?);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rotate_identity_key_attests_purpose_keys_again() -> Result<()> {
+    let identities = identities().await?;
+    let identities_creation = identities.identities_creation();
+    let purpose_keys_creation = identities.purpose_keys().purpose_keys_creation();
+
+    let identifier = identities_creation.create_identity().await?;
+    let secure_channel_key = purpose_keys_creation
+        .create_secure_channel_purpose_key(&identifier)
+        .await?;
+    let credential_key = purpose_keys_creation
+        .create_credential_purpose_key(&identifier)
+        .await?;
+
+    let identity = identities.rotate_identity_key(&identifier, false).await?;
+
+    // the same purpose keys are now attested with the new identity key
+    let rotated_secure_channel_key = purpose_keys_creation
+        .get_secure_channel_purpose_key(&identifier)
+        .await?;
+    assert_eq!(
+        rotated_secure_channel_key.public_key(),
+        secure_channel_key.public_key()
+    );
+    assert_eq!(
+        &rotated_secure_channel_key.data().subject_latest_change_hash,
+        identity.latest_change_hash()?
+    );
+    let rotated_credential_key = purpose_keys_creation
+        .get_credential_purpose_key(&identifier)
+        .await?;
+    assert_eq!(
+        rotated_credential_key.public_key(),
+        credential_key.public_key()
+    );
+
+    // when the purpose keys are revoked, new ones are created
+    identities.rotate_identity_key(&identifier, true).await?;
+
+    let revoked_secure_channel_key = purpose_keys_creation
+        .get_secure_channel_purpose_key(&identifier)
+        .await?;
+    assert_ne!(
+        revoked_secure_channel_key.public_key(),
+        secure_channel_key.public_key()
+    );
+    let revoked_credential_key = purpose_keys_creation
+        .get_credential_purpose_key(&identifier)
+        .await?;
+    assert_ne!(
+        revoked_credential_key.public_key(),
+        credential_key.public_key()
+    );
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rotate_identity_key_when_due() -> Result<()> {
+    let policy = IdentityKeyRotationPolicy::new(Duration::from_secs(3600));
+    assert_eq!(policy.check_interval(), Duration::from_secs(60));
+    assert!(!policy.is_rotation_due(TimestampInSeconds(1000), TimestampInSeconds(4599)));
+    assert!(policy.is_rotation_due(TimestampInSeconds(1000), TimestampInSeconds(4600)));
+
+    let secure_channels = secure_channels().await?;
+    let identities = secure_channels.identities();
+    let identifier = identities.identities_creation().create_identity().await?;
+
+    let rotator = IdentityKeyRotator::new(secure_channels.clone(), identifier.clone(), policy);
+    assert!(!rotator.rotate_if_due().await?);
+    assert_eq!(
+        identities.get_identity(&identifier).await?.changes().len(),
+        1
+    );
+
+    let rotator = IdentityKeyRotator::new(
+        secure_channels.clone(),
+        identifier.clone(),
+        IdentityKeyRotationPolicy::new(Duration::from_secs(0)),
+    );
+    assert!(rotator.rotate_if_due().await?);
+    assert_eq!(
+        identities.get_identity(&identifier).await?.changes().len(),
+        2
+    );
+
+    Ok(())
+}
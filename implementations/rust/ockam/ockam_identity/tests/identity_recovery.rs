@@ -0,0 +1,338 @@
+use ockam_core::compat::sync::Arc;
+use ockam_core::Result;
+use ockam_identity::models::{Change, RecoverySignature};
+use ockam_identity::{identities, Identities, IdentitiesKeys, Identity, IdentityHistoryComparison};
+use ockam_vault::{
+    SigningKeyType, SigningSecretKeyHandle, SoftwareVaultForSigning,
+    SoftwareVaultForVerifyingSignatures, VaultForSigning, VerifyingPublicKey,
+};
+
+/// Recovery keys, kept outside of the vault of the identity
+struct RecoveryKeys {
+    vault: Arc<SoftwareVaultForSigning>,
+    keys: Vec<SigningSecretKeyHandle>,
+}
+
+impl RecoveryKeys {
+    async fn create(n: usize) -> Result<Self> {
+        let vault = SoftwareVaultForSigning::create().await?;
+        let mut keys = vec![];
+        for _ in 0..n {
+            keys.push(
+                vault
+                    .generate_signing_secret_key(SigningKeyType::EdDSACurve25519)
+                    .await?,
+            );
+        }
+        Ok(Self { vault, keys })
+    }
+
+    async fn public_keys(&self) -> Result<Vec<VerifyingPublicKey>> {
+        let mut public_keys = vec![];
+        for key in self.keys.iter() {
+            public_keys.push(self.vault.get_verifying_public_key(key).await?);
+        }
+        Ok(public_keys)
+    }
+
+    async fn sign(&self, change: &Change, key_indexes: &[u8]) -> Result<Vec<RecoverySignature>> {
+        let identities_keys = IdentitiesKeys::new(
+            self.vault.clone(),
+            SoftwareVaultForVerifyingSignatures::create(),
+        );
+        let mut signatures = vec![];
+        for key_index in key_indexes {
+            signatures.push(
+                identities_keys
+                    .sign_recovery_change(change, *key_index, &self.keys[*key_index as usize])
+                    .await?,
+            );
+        }
+        Ok(signatures)
+    }
+}
+
+/// Create a recovery change for an identity on a node which doesn't have its primary key
+async fn create_recovery_change(identities: &Identities, identity: &Identity) -> Result<Change> {
+    let options = identities
+        .identities_creation()
+        .identity_builder()
+        .build_options()
+        .await?;
+    identities
+        .identities_keys()
+        .create_recovery_change(identity, options)
+        .await
+}
+
+#[tokio::test]
+async fn recover_identity_with_threshold_of_recovery_keys() -> Result<()> {
+    let identities1 = identities().await?;
+    let identifier = identities1.identities_creation().create_identity().await?;
+    let recovery_keys = RecoveryKeys::create(3).await?;
+
+    let identity = identities1
+        .set_recovery_keys(&identifier, 2, recovery_keys.public_keys().await?)
+        .await?;
+    assert_eq!(identity.recovery_keys().unwrap().threshold, 2);
+    assert_eq!(identity.recovery_keys().unwrap().public_keys.len(), 3);
+
+    // the recovery keys are kept by later changes
+    let identity = identities1.rotate_identity_key(&identifier, false).await?;
+    assert!(identity.recovery_keys().is_some());
+
+    // the primary key is lost, the identity is recovered on another node
+    let identities2 = identities().await?;
+    let change = create_recovery_change(&identities2, &identity).await?;
+    let signatures = recovery_keys.sign(&change, &[0, 2]).await?;
+    let recovered = identities2
+        .identities_creation()
+        .recover_identity(identity.clone(), change, signatures)
+        .await?;
+
+    assert_eq!(recovered.identifier(), &identifier);
+    let latest_change = recovered.get_latest_change()?;
+    assert!(latest_change.is_recovery());
+    assert!(latest_change.data().revoke_all_purpose_keys);
+    assert!(identities2
+        .identities_keys()
+        .get_secret_key(&recovered)
+        .await
+        .is_ok());
+
+    // the recovered identity can be exported and imported again
+    let imported = Identity::import(
+        Some(&identifier),
+        &recovered.export()?,
+        SoftwareVaultForVerifyingSignatures::create(),
+    )
+    .await?;
+    assert_eq!(imported, recovered);
+
+    // other nodes accept the recovered identity
+    identities1
+        .identities_creation()
+        .update_identity(&recovered)
+        .await?;
+    assert_eq!(identities1.get_identity(&identifier).await?, recovered);
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn recovery_requires_enough_distinct_recovery_signatures() -> Result<()> {
+    let identities1 = identities().await?;
+    let identifier = identities1.identities_creation().create_identity().await?;
+    let recovery_keys = RecoveryKeys::create(3).await?;
+    let other_keys = RecoveryKeys::create(3).await?;
+
+    let identities2 = identities().await?;
+
+    // without recovery keys an identity can't be recovered
+    let identity = identities1.get_identity(&identifier).await?;
+    let change = create_recovery_change(&identities2, &identity).await?;
+    let signatures = other_keys.sign(&change, &[0, 1]).await?;
+    assert!(identities2
+        .identities_creation()
+        .recover_identity(identity, change, signatures)
+        .await
+        .is_err());
+
+    let identity = identities1
+        .set_recovery_keys(&identifier, 2, recovery_keys.public_keys().await?)
+        .await?;
+
+    // not enough signatures
+    let change = create_recovery_change(&identities2, &identity).await?;
+    let signatures = recovery_keys.sign(&change, &[1]).await?;
+    assert!(identities2
+        .identities_creation()
+        .recover_identity(identity.clone(), change, signatures)
+        .await
+        .is_err());
+
+    // the same key signing twice
+    let change = create_recovery_change(&identities2, &identity).await?;
+    let signatures = recovery_keys.sign(&change, &[1, 1]).await?;
+    assert!(identities2
+        .identities_creation()
+        .recover_identity(identity.clone(), change, signatures)
+        .await
+        .is_err());
+
+    // signatures made with keys which are not recovery keys
+    let change = create_recovery_change(&identities2, &identity).await?;
+    let signatures = other_keys.sign(&change, &[0, 1]).await?;
+    assert!(identities2
+        .identities_creation()
+        .recover_identity(identity.clone(), change, signatures)
+        .await
+        .is_err());
+
+    // recovery keys can't be committed to if they can never reach the threshold
+    assert!(identities1
+        .set_recovery_keys(&identifier, 4, recovery_keys.public_keys().await?)
+        .await
+        .is_err());
+    assert!(identities1
+        .set_recovery_keys(&identifier, 0, recovery_keys.public_keys().await?)
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn recovered_identity_takes_precedence_over_a_compromised_key() -> Result<()> {
+    let identities1 = identities().await?;
+    let identifier = identities1.identities_creation().create_identity().await?;
+    let recovery_keys = RecoveryKeys::create(1).await?;
+    let identity = identities1
+        .set_recovery_keys(&identifier, 1, recovery_keys.public_keys().await?)
+        .await?;
+
+    // an attacker holding the primary key rotates it
+    let compromised = identities1.rotate_identity_key(&identifier, false).await?;
+
+    // the owner recovers the identity from the last change they trust
+    let identities2 = identities().await?;
+    let change = create_recovery_change(&identities2, &identity).await?;
+    let signatures = recovery_keys.sign(&change, &[0]).await?;
+    let recovered = identities2
+        .identities_creation()
+        .recover_identity(identity.clone(), change, signatures)
+        .await?;
+
+    assert_eq!(
+        recovered.compare(&compromised),
+        IdentityHistoryComparison::Recovered
+    );
+    assert_eq!(
+        compromised.compare(&recovered),
+        IdentityHistoryComparison::Conflict
+    );
+    assert_eq!(
+        recovered.compare(&identity),
+        IdentityHistoryComparison::Newer
+    );
+
+    // the recovered history replaces the compromised one...
+    identities1
+        .identities_creation()
+        .update_identity(&recovered)
+        .await?;
+    assert_eq!(identities1.get_identity(&identifier).await?, recovered);
+
+    // ...and the compromised history is rejected afterwards
+    assert!(identities1
+        .identities_creation()
+        .update_identity(&compromised)
+        .await
+        .is_err());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn replacing_recovery_keys_requires_their_signatures() -> Result<()> {
+    let identities = identities().await?;
+    let identifier = identities.identities_creation().create_identity().await?;
+    let old_keys = RecoveryKeys::create(3).await?;
+    let new_keys = RecoveryKeys::create(2).await?;
+
+    identities
+        .set_recovery_keys(&identifier, 2, old_keys.public_keys().await?)
+        .await?;
+
+    // the primary key alone can't replace the recovery keys
+    assert!(identities
+        .set_recovery_keys(&identifier, 1, new_keys.public_keys().await?)
+        .await
+        .is_err());
+
+    // nor can the primary key with too few recovery signatures
+    let change = identities
+        .create_recovery_keys_change(&identifier, 1, new_keys.public_keys().await?)
+        .await?;
+    let signatures = old_keys.sign(&change, &[0]).await?;
+    assert!(identities
+        .replace_recovery_keys(&identifier, change, signatures)
+        .await
+        .is_err());
+
+    // the recovery keys in effect approve their replacement
+    let change = identities
+        .create_recovery_keys_change(&identifier, 1, new_keys.public_keys().await?)
+        .await?;
+    let signatures = old_keys.sign(&change, &[0, 1]).await?;
+    let identity = identities
+        .replace_recovery_keys(&identifier, change, signatures)
+        .await?;
+    assert_eq!(identity.recovery_keys().unwrap().threshold, 1);
+    assert_eq!(
+        identity.recovery_keys().unwrap().public_keys.len(),
+        new_keys.keys.len()
+    );
+    // this is a rotation with the primary key, not a recovery
+    assert!(!identity.get_latest_change()?.is_recovery());
+
+    Ok(())
+}
+
+#[tokio::test]
+async fn rotated_out_recovery_keys_cannot_recover_the_identity() -> Result<()> {
+    let identities1 = identities().await?;
+    let identifier = identities1.identities_creation().create_identity().await?;
+    let old_keys = RecoveryKeys::create(1).await?;
+    let new_keys = RecoveryKeys::create(1).await?;
+
+    let with_old_keys = identities1
+        .set_recovery_keys(&identifier, 1, old_keys.public_keys().await?)
+        .await?;
+    let change = identities1
+        .create_recovery_keys_change(&identifier, 1, new_keys.public_keys().await?)
+        .await?;
+    let signatures = old_keys.sign(&change, &[0]).await?;
+    let known = identities1
+        .replace_recovery_keys(&identifier, change, signatures)
+        .await?;
+
+    // whoever holds the replaced recovery key forks the history where that key was in effect
+    let identities2 = identities().await?;
+    let change = create_recovery_change(&identities2, &with_old_keys).await?;
+    let signatures = old_keys.sign(&change, &[0]).await?;
+    let forked = identities2
+        .identities_creation()
+        .recover_identity(with_old_keys, change, signatures)
+        .await?;
+
+    // the fork doesn't replace the history which moved to the new recovery keys
+    assert_eq!(forked.compare(&known), IdentityHistoryComparison::Conflict);
+    assert!(identities1
+        .identities_creation()
+        .update_identity(&forked)
+        .await
+        .is_err());
+    assert_eq!(identities1.get_identity(&identifier).await?, known);
+
+    // the replaced recovery key can't recover the latest history either
+    let identities3 = identities().await?;
+    let change = create_recovery_change(&identities3, &known).await?;
+    let signatures = old_keys.sign(&change, &[0]).await?;
+    assert!(identities3
+        .identities_creation()
+        .recover_identity(known.clone(), change, signatures)
+        .await
+        .is_err());
+
+    // the current recovery key still can
+    let change = create_recovery_change(&identities3, &known).await?;
+    let signatures = new_keys.sign(&change, &[0]).await?;
+    let recovered = identities3
+        .identities_creation()
+        .recover_identity(known.clone(), change, signatures)
+        .await?;
+    assert_eq!(recovered.compare(&known), IdentityHistoryComparison::Newer);
+
+    Ok(())
+}
@@ -1,5 +1,5 @@
 use super::{Buffer, Checked, Code, Codec, Protocol};
-use crate::proto::{DnsAddr, Node, Project, Secure, Service, Space, Tcp, Worker};
+use crate::proto::{DnsAddr, Node, Project, Secure, Service, Space, Tcp, Worker, Ws, Wss};
 use crate::{Error, ProtoValue};
 use core::fmt;
 use unsigned_varint::decode;
@@ -9,9 +9,13 @@ pub struct StdCodec;
 impl Codec for StdCodec {
     fn split_str<'a>(
         &self,
-        _prefix: &str,
+        prefix: &str,
         input: &'a str,
     ) -> Result<(Checked<&'a str>, &'a str), Error> {
+        // Protocols without a value leave the whole input to the next protocol
+        if prefix == Ws::PREFIX || prefix == Wss::PREFIX {
+            return Ok((Checked(""), input));
+        }
         if let Some(p) = input.find('/') {
             let (x, y) = input.split_at(p);
             Ok((Checked(x), y))
@@ -49,6 +53,7 @@ impl Codec for StdCodec {
                 let (x, y) = input.split_at(2);
                 Ok((Checked(x), y))
             }
+            Ws::CODE | Wss::CODE => Ok((Checked(&[]), input)),
             c @ Worker::CODE
             | c @ DnsAddr::CODE
             | c @ Service::CODE
@@ -81,6 +86,8 @@ impl Codec for StdCodec {
             Project::CODE => Project::read_bytes(input).is_ok(),
             Space::CODE => Space::read_bytes(input).is_ok(),
             Secure::CODE => Secure::read_bytes(input).is_ok(),
+            Ws::CODE => Ws::read_bytes(input).is_ok(),
+            Wss::CODE => Wss::read_bytes(input).is_ok(),
             _ => false,
         }
     }
@@ -99,6 +106,8 @@ impl Codec for StdCodec {
             Project::CODE => Project::read_bytes(val.data())?.write_bytes(buf),
             Space::CODE => Space::read_bytes(val.data())?.write_bytes(buf),
             Secure::CODE => Secure::read_bytes(val.data())?.write_bytes(buf),
+            Ws::CODE => Ws::read_bytes(val.data())?.write_bytes(buf),
+            Wss::CODE => Wss::read_bytes(val.data())?.write_bytes(buf),
             code => return Err(Error::unregistered(code)),
         }
         Ok(())
@@ -153,6 +162,14 @@ impl Codec for StdCodec {
                 Secure::read_str(value)?.write_bytes(buf);
                 Ok(())
             }
+            Ws::PREFIX => {
+                Ws::read_str(value)?.write_bytes(buf);
+                Ok(())
+            }
+            Wss::PREFIX => {
+                Wss::read_str(value)?.write_bytes(buf);
+                Ok(())
+            }
             _ => Err(Error::unregistered_prefix(prefix)),
         }
     }
@@ -206,6 +223,14 @@ impl Codec for StdCodec {
                 Secure::read_bytes(value)?.write_str(f)?;
                 Ok(())
             }
+            Ws::CODE => {
+                Ws::read_bytes(value)?.write_str(f)?;
+                Ok(())
+            }
+            Wss::CODE => {
+                Wss::read_bytes(value)?.write_str(f)?;
+                Ok(())
+            }
             _ => Err(Error::unregistered(code)),
         }
     }
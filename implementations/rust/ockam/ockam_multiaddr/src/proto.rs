@@ -210,3 +210,56 @@ gen_str_proto!(Node, 72526, "node");
 gen_str_proto!(Project, 82526, "project");
 gen_str_proto!(Space, 92526, "space");
 gen_str_proto!(Secure, 99526, "secure");
+
+macro_rules! gen_unit_proto {
+    ($(#[$m:meta])* $t:ident, $c:literal, $p:literal) => {
+        $(#[$m])*
+        #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+        pub struct $t;
+
+        impl Protocol<'_> for $t {
+            const CODE: Code = Code::new($c);
+            const PREFIX: &'static str = $p;
+
+            fn read_str(input: Checked<&str>) -> Result<Self, Error> {
+                if input.is_empty() {
+                    Ok($t)
+                } else {
+                    Err(Error::message(concat!($p, " does not take a value")))
+                }
+            }
+
+            fn read_bytes(input: Checked<&[u8]>) -> Result<Self, Error> {
+                if input.is_empty() {
+                    Ok($t)
+                } else {
+                    Err(Error::message(concat!($p, " does not take a value")))
+                }
+            }
+
+            fn write_str(&self, f: &mut fmt::Formatter) -> Result<(), Error> {
+                write!(f, "/{}", Self::PREFIX)?;
+                Ok(())
+            }
+
+            fn write_bytes(&self, buf: &mut dyn Buffer) {
+                let mut b = encode::u32_buffer();
+                let uvi = encode::u32(Self::CODE.into(), &mut b);
+                buf.extend_with(uvi)
+            }
+        }
+    };
+}
+
+gen_unit_proto!(
+    /// WebSocket over the preceding TCP address, e.g. `/dnsaddr/localhost/tcp/4000/ws`.
+    Ws,
+    477,
+    "ws"
+);
+gen_unit_proto!(
+    /// WebSocket secured with TLS over the preceding TCP address.
+    Wss,
+    478,
+    "wss"
+);
@@ -1,6 +1,6 @@
 use super::{Code, Codec, Protocol};
 use crate::codec::StdCodec;
-use crate::proto::{DnsAddr, Node, Project, Secure, Service, Space, Tcp, Worker};
+use crate::proto::{DnsAddr, Node, Project, Secure, Service, Space, Tcp, Worker, Ws, Wss};
 use alloc::collections::btree_map::BTreeMap;
 use alloc::sync::Arc;
 use core::fmt;
@@ -38,6 +38,10 @@ impl Default for Registry {
         r.register(Space::CODE, Space::PREFIX, std_codec.clone());
         #[allow(clippy::redundant_clone)]
         r.register(Secure::CODE, Secure::PREFIX, std_codec.clone());
+        #[allow(clippy::redundant_clone)]
+        r.register(Ws::CODE, Ws::PREFIX, std_codec.clone());
+        #[allow(clippy::redundant_clone)]
+        r.register(Wss::CODE, Wss::PREFIX, std_codec.clone());
         #[cfg(feature = "std")]
         r.register(
             crate::proto::Ip4::CODE,
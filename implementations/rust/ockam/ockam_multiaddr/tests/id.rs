@@ -1,5 +1,7 @@
 use core::fmt;
-use ockam_multiaddr::proto::{DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp};
+use ockam_multiaddr::proto::{
+    DnsAddr, Ip4, Ip6, Node, Project, Secure, Service, Space, Tcp, Ws, Wss,
+};
 use ockam_multiaddr::{Code, Match, MultiAddr, Protocol};
 use quickcheck::{quickcheck, Arbitrary, Gen};
 use rand::distributions::{Alphanumeric, DistString};
@@ -132,6 +134,14 @@ quickcheck! {
                         addr.push_back(Space::new("space")).unwrap();
                         prot.push_back(Space::CODE);
                     }
+                    Ws::CODE => {
+                        addr.push_back(Ws).unwrap();
+                        prot.push_back(Ws::CODE);
+                    }
+                    Wss::CODE => {
+                        addr.push_back(Wss).unwrap();
+                        prot.push_back(Wss::CODE);
+                    }
                     _ => unreachable!()
                 }
             }
@@ -153,6 +163,8 @@ const PROTOS: &[Code] = &[
     Node::CODE,
     Project::CODE,
     Space::CODE,
+    Ws::CODE,
+    Wss::CODE,
 ];
 
 impl Arbitrary for Addr {
@@ -169,6 +181,8 @@ impl Arbitrary for Addr {
                 Project::CODE => a.push_back(Project::new(gen_string())).unwrap(),
                 Space::CODE => a.push_back(Space::new(gen_string())).unwrap(),
                 Node::CODE => a.push_back(Node::new(gen_string())).unwrap(),
+                Ws::CODE => a.push_back(Ws).unwrap(),
+                Wss::CODE => a.push_back(Wss).unwrap(),
                 _ => unreachable!(),
             }
         }
@@ -1,11 +1,17 @@
+use crate::{MailboxOptions, MailboxReceiver, MailboxSender};
+use ockam_core::Address;
+
 /// Sender used to send payload messages
-pub type MessageSender<T> = crate::tokio::sync::mpsc::Sender<T>;
+pub type MessageSender<T> = MailboxSender<T>;
 /// Receiver used to receive payload messages
-pub type MessageReceiver<T> = crate::tokio::sync::mpsc::Receiver<T>;
-
-/// Create message channel
-pub fn message_channel<T>() -> (MessageSender<T>, MessageReceiver<T>) {
-    crate::tokio::sync::mpsc::channel(16)
+pub type MessageReceiver<T> = MailboxReceiver<T>;
+
+/// Create the message channel of a worker
+pub fn message_channel<T>(
+    address: Address,
+    options: MailboxOptions,
+) -> (MessageSender<T>, MessageReceiver<T>) {
+    crate::mailbox::mailbox_channel(address, options)
 }
 
 /// Router sender
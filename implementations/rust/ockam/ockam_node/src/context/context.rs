@@ -1,13 +1,15 @@
-use crate::channel_types::{SmallReceiver, SmallSender};
+use crate::channel_types::{MessageReceiver, SmallSender};
 use crate::tokio::runtime::Handle;
-use crate::{error::*, AsyncDropSender, NodeMessage};
+use crate::{error::*, AsyncDropSender, MailboxStatistics, NodeMessage};
 use core::sync::atomic::AtomicUsize;
 use ockam_core::compat::collections::HashMap;
 use ockam_core::compat::sync::{Arc, RwLock};
 use ockam_core::compat::time::Duration;
 use ockam_core::compat::{string::String, vec::Vec};
 use ockam_core::flow_control::FlowControls;
-use ockam_core::{async_trait, Address, Mailboxes, RelayMessage, Result, TransportType};
+use ockam_core::{
+    async_trait, Address, Mailboxes, RelayMessage, Result, TraceContext, TransportType,
+};
 
 #[cfg(feature = "std")]
 use core::fmt::{Debug, Formatter};
@@ -21,12 +23,14 @@ pub struct Context {
     pub(super) mailboxes: Mailboxes,
     pub(super) sender: SmallSender<NodeMessage>,
     pub(super) rt: Handle,
-    pub(super) receiver: SmallReceiver<RelayMessage>,
+    pub(super) receiver: MessageReceiver<RelayMessage>,
     pub(super) async_drop_sender: Option<AsyncDropSender>,
     pub(super) mailbox_count: Arc<AtomicUsize>,
     /// List of transports used to resolve external addresses to local workers in routes
     pub(super) transports: Arc<RwLock<HashMap<TransportType, Arc<dyn Transport>>>>,
     pub(super) flow_controls: FlowControls,
+    /// Trace context of the message currently handled, attached to sent messages
+    pub(super) tracing_context: Option<TraceContext>,
 }
 
 /// This trait can be used to integrate transports into a node
@@ -78,6 +82,20 @@ impl Context {
         &self.mailboxes
     }
 
+    /// Return the distributed trace context attached to messages sent
+    /// from this context
+    pub fn tracing_context(&self) -> Option<TraceContext> {
+        self.tracing_context
+    }
+
+    /// Set the distributed trace context attached to messages sent from
+    /// this context. It is set by the node while a worker handles a message
+    /// which carries a trace context, but processors can set it to start
+    /// a new trace
+    pub fn set_tracing_context(&mut self, tracing_context: Option<TraceContext>) {
+        self.tracing_context = tracing_context;
+    }
+
     /// Shared [`FlowControls`] instance
     pub fn flow_controls(&self) -> &FlowControls {
         &self.flow_controls
@@ -128,6 +146,32 @@ impl Context {
             .take_workers()
     }
 
+    /// Return the number of messages waiting in the mailbox of each running worker
+    pub async fn mailbox_depths(&self) -> Result<Vec<(Address, usize)>> {
+        Ok(self
+            .mailbox_statistics()
+            .await?
+            .into_iter()
+            .map(|s| (s.address, s.depth))
+            .collect())
+    }
+
+    /// Return the mailbox statistics of each running worker and processor
+    pub async fn mailbox_statistics(&self) -> Result<Vec<MailboxStatistics>> {
+        let (msg, mut reply_rx) = NodeMessage::mailbox_statistics();
+
+        self.sender
+            .send(msg)
+            .await
+            .map_err(NodeError::from_send_err)?;
+
+        reply_rx
+            .recv()
+            .await
+            .ok_or_else(|| NodeError::NodeState(NodeReason::Unknown).internal())??
+            .take_mailbox_statistics()
+    }
+
     /// Send a shutdown acknowledgement to the router
     pub(crate) async fn send_stop_ack(&self) -> Result<()> {
         self.sender
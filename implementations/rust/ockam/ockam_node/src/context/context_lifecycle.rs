@@ -13,7 +13,7 @@ use ockam_transport_core::Transport;
 use crate::async_drop::AsyncDrop;
 use crate::channel_types::{message_channel, small_channel, SmallReceiver, SmallSender};
 use crate::tokio::{self, runtime::Handle};
-use crate::{debugger, Context};
+use crate::{debugger, Context, MailboxOptions};
 use crate::{error::*, relay::CtrlSignal, router::SenderPair, NodeMessage};
 
 /// A special type of `Context` that has no worker relay and inherits
@@ -62,8 +62,10 @@ impl Context {
         async_drop_sender: Option<AsyncDropSender>,
         transports: Arc<RwLock<HashMap<TransportType, Arc<dyn Transport>>>>,
         flow_controls: &FlowControls,
+        mailbox_options: MailboxOptions,
     ) -> (Self, SenderPair, SmallReceiver<CtrlSignal>) {
-        let (mailbox_tx, receiver) = message_channel();
+        let (mailbox_tx, receiver) =
+            message_channel(mailboxes.main_address().clone(), mailbox_options);
         let (ctrl_tx, ctrl_rx) = small_channel();
         (
             Self {
@@ -75,6 +77,7 @@ impl Context {
                 mailbox_count: Arc::new(0.into()),
                 transports,
                 flow_controls: flow_controls.clone(),
+                tracing_context: None,
             },
             SenderPair {
                 msgs: mailbox_tx,
@@ -87,6 +90,7 @@ impl Context {
     pub(crate) fn copy_with_mailboxes(
         &self,
         mailboxes: Mailboxes,
+        mailbox_options: MailboxOptions,
     ) -> (Context, SenderPair, SmallReceiver<CtrlSignal>) {
         Context::new(
             self.runtime().clone(),
@@ -95,6 +99,7 @@ impl Context {
             None,
             self.transports.clone(),
             &self.flow_controls,
+            mailbox_options,
         )
     }
 
@@ -110,6 +115,7 @@ impl Context {
             Some(drop_sender),
             self.transports.clone(),
             &self.flow_controls,
+            MailboxOptions::default(),
         )
     }
 
@@ -165,7 +171,9 @@ impl Context {
 
         // Create a new context and get access to the mailbox senders
         let addresses = mailboxes.addresses();
-        let (ctx, sender, _) = self.copy_with_mailboxes_detached(mailboxes, drop_sender);
+        let (mut ctx, sender, _) = self.copy_with_mailboxes_detached(mailboxes, drop_sender);
+        // Messages sent from a detached context belong to the same trace
+        ctx.tracing_context = self.tracing_context;
 
         // Create a "detached relay" and register it with the router
         let (msg, mut rx) =
@@ -195,7 +203,7 @@ mod tests {
 
         // after a copy with new mailboxes the list of transports should be intact
         let mailboxes = Mailboxes::new(Mailbox::deny_all("address"), vec![]);
-        let (copy, _, _) = ctx.copy_with_mailboxes(mailboxes.clone());
+        let (copy, _, _) = ctx.copy_with_mailboxes(mailboxes.clone(), MailboxOptions::default());
         assert!(copy.is_transport_registered(transport.transport_type()));
 
         // after a detached copy with new mailboxes the list of transports should be intact
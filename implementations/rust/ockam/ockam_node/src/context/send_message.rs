@@ -252,7 +252,8 @@ impl Context {
 
         // Pack the payload into a TransportMessage
         let payload = msg.encode().map_err(|_| NodeError::Data.internal())?;
-        let transport_msg = TransportMessage::v1(route, route![sending_address.clone()], payload);
+        let transport_msg = TransportMessage::v1(route, route![sending_address.clone()], payload)
+            .with_tracing_context(self.tracing_context);
 
         // Pack transport message into a LocalMessage wrapper
         let local_msg = LocalMessage::new(transport_msg, local_info);
@@ -271,11 +272,14 @@ impl Context {
             return Ok(());
         }
 
+        #[cfg(feature = "std")]
+        let payload_size = relay_msg.local_message().transport().payload.len();
+
         // Send the packed user message with associated route
-        sender
-            .send(relay_msg)
-            .await
-            .map_err(NodeError::from_send_err)?;
+        sender.send(relay_msg).await?;
+
+        #[cfg(feature = "std")]
+        record_routed_message(payload_size);
 
         Ok(())
     }
@@ -310,9 +314,16 @@ impl Context {
     /// [`TransportMessage`]: ockam_core::TransportMessage
     pub async fn forward_from_address(
         &self,
-        local_msg: LocalMessage,
+        mut local_msg: LocalMessage,
         sending_address: Address,
     ) -> Result<()> {
+        // Messages forwarded without a trace context join the trace being handled
+        if local_msg.transport().tracing_context.is_none() && self.tracing_context.is_some() {
+            local_msg
+                .transport_mut()
+                .set_tracing_context(self.tracing_context);
+        }
+
         // Check if the sender address exists
         if !self.mailboxes.contains(&sending_address) {
             return Err(Error::new_without_cause(Origin::Node, Kind::Invalid));
@@ -356,12 +367,24 @@ impl Context {
             return Ok(());
         }
 
+        #[cfg(feature = "std")]
+        let payload_size = relay_msg.local_message().transport().payload.len();
+
         // Forward the message
-        sender
-            .send(relay_msg)
-            .await
-            .map_err(NodeError::from_send_err)?;
+        sender.send(relay_msg).await?;
+
+        #[cfg(feature = "std")]
+        record_routed_message(payload_size);
 
         Ok(())
     }
 }
+
+/// Update the router metrics after a message has been sent to a worker
+#[cfg(feature = "std")]
+fn record_routed_message(payload_size: usize) {
+    use ockam_core::metrics::{ROUTER_MESSAGES, ROUTER_MESSAGE_SIZE};
+
+    ROUTER_MESSAGES.get().inc();
+    ROUTER_MESSAGE_SIZE.get().observe(payload_size as f64);
+}
@@ -54,6 +54,7 @@ mod context;
 mod delayed;
 mod error;
 mod executor;
+mod mailbox;
 mod messages;
 mod node;
 mod parser;
@@ -69,6 +70,7 @@ pub use context::*;
 pub use delayed::*;
 pub use error::*;
 pub use executor::*;
+pub use mailbox::*;
 pub use messages::*;
 pub use processor_builder::ProcessorBuilder;
 pub use storage::*;
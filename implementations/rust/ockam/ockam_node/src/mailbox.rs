@@ -0,0 +1,436 @@
+//! Bounded worker mailboxes
+//!
+//! Every worker and processor receives its messages through a bounded
+//! queue. The capacity of the queue and the behaviour of the senders
+//! when it is full are configured with [`MailboxOptions`] when the
+//! worker is started.
+
+use crate::error::{NodeError, NodeReason, WorkerReason};
+use core::fmt;
+use core::task::{Poll, Waker};
+use futures::future::poll_fn;
+use ockam_core::compat::collections::VecDeque;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Address, Error, Result};
+
+/// Default number of messages which can be queued in a mailbox
+pub const DEFAULT_MAILBOX_CAPACITY: usize = 16;
+
+/// What happens to a message sent to a worker whose mailbox is full
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OverflowPolicy {
+    /// Wait until the worker has handled a message (default)
+    #[default]
+    Block,
+    /// Discard the oldest queued message to make room for the new one
+    DropOldest,
+    /// Discard the new message
+    DropNewest,
+    /// Return an error to the sender
+    Reject,
+}
+
+impl fmt::Display for OverflowPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Block => "block",
+            Self::DropOldest => "drop-oldest",
+            Self::DropNewest => "drop-newest",
+            Self::Reject => "reject",
+        })
+    }
+}
+
+/// Capacity and overflow policy of the mailbox of a worker or processor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MailboxOptions {
+    capacity: usize,
+    overflow_policy: OverflowPolicy,
+}
+
+impl Default for MailboxOptions {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MailboxOptions {
+    /// Mailbox of [`DEFAULT_MAILBOX_CAPACITY`] messages which blocks the senders when full
+    pub fn new() -> Self {
+        Self {
+            capacity: DEFAULT_MAILBOX_CAPACITY,
+            overflow_policy: OverflowPolicy::Block,
+        }
+    }
+
+    /// Set the maximum number of queued messages. A capacity of 0 is treated as 1
+    pub fn with_capacity(mut self, capacity: usize) -> Self {
+        self.capacity = capacity.max(1);
+        self
+    }
+
+    /// Set the behaviour of the senders when the mailbox is full
+    pub fn with_overflow_policy(mut self, overflow_policy: OverflowPolicy) -> Self {
+        self.overflow_policy = overflow_policy;
+        self
+    }
+
+    /// Maximum number of queued messages
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Behaviour of the senders when the mailbox is full
+    pub fn overflow_policy(&self) -> OverflowPolicy {
+        self.overflow_policy
+    }
+}
+
+/// Statistics of the mailbox of a running worker or processor, as seen by the router
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MailboxStatistics {
+    /// Primary address of the worker
+    pub address: Address,
+    /// Maximum number of queued messages
+    pub capacity: usize,
+    /// Behaviour of the senders when the mailbox is full
+    pub overflow_policy: OverflowPolicy,
+    /// Number of messages currently waiting to be handled
+    pub depth: usize,
+    /// Number of messages queued since the worker was started
+    pub delivered: u64,
+    /// Number of messages dropped or rejected because the mailbox was full
+    pub overflows: u64,
+}
+
+/// Create a mailbox channel for the worker with the given primary address
+pub(crate) fn mailbox_channel<T>(
+    address: Address,
+    options: MailboxOptions,
+) -> (MailboxSender<T>, MailboxReceiver<T>) {
+    let shared = Arc::new(Shared {
+        address,
+        options,
+        state: Mutex::new(State {
+            queue: VecDeque::with_capacity(options.capacity),
+            receiver_waker: None,
+            blocked_senders: Vec::new(),
+            senders: 1,
+            closed: false,
+            delivered: 0,
+            overflows: 0,
+        }),
+    });
+    (
+        MailboxSender {
+            shared: shared.clone(),
+        },
+        MailboxReceiver { shared },
+    )
+}
+
+struct Shared<T> {
+    address: Address,
+    options: MailboxOptions,
+    state: Mutex<State<T>>,
+}
+
+struct State<T> {
+    queue: VecDeque<T>,
+    receiver_waker: Option<Waker>,
+    blocked_senders: Vec<Waker>,
+    senders: usize,
+    closed: bool,
+    delivered: u64,
+    overflows: u64,
+}
+
+impl<T> State<T> {
+    fn push(&mut self, msg: T) -> Option<Waker> {
+        self.queue.push_back(msg);
+        self.delivered += 1;
+        self.receiver_waker.take()
+    }
+}
+
+/// Sending half of a worker mailbox
+pub struct MailboxSender<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> fmt::Debug for MailboxSender<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MailboxSender")
+            .field("address", &self.shared.address)
+            .field("options", &self.shared.options)
+            .finish()
+    }
+}
+
+impl<T> Clone for MailboxSender<T> {
+    fn clone(&self) -> Self {
+        self.shared.state.lock().unwrap().senders += 1;
+        Self {
+            shared: self.shared.clone(),
+        }
+    }
+}
+
+impl<T> Drop for MailboxSender<T> {
+    fn drop(&mut self) {
+        let waker = {
+            let mut state = self.shared.state.lock().unwrap();
+            state.senders -= 1;
+            if state.senders == 0 {
+                state.receiver_waker.take()
+            } else {
+                None
+            }
+        };
+        // The receiver is woken up to notice that the mailbox is closed
+        if let Some(waker) = waker {
+            waker.wake()
+        }
+    }
+}
+
+impl<T> MailboxSender<T> {
+    /// Queue a message, applying the overflow policy if the mailbox is full
+    pub async fn send(&self, msg: T) -> Result<()> {
+        let capacity = self.shared.options.capacity;
+        let mut msg = Some(msg);
+        poll_fn(|cx| {
+            let mut state = self.shared.state.lock().unwrap();
+            if state.closed {
+                return Poll::Ready(Err(self.closed_error()));
+            }
+
+            let waker = if state.queue.len() < capacity {
+                state.push(msg.take().expect("a message is only sent once"))
+            } else {
+                match self.shared.options.overflow_policy {
+                    OverflowPolicy::Block => {
+                        if !state
+                            .blocked_senders
+                            .iter()
+                            .any(|w| w.will_wake(cx.waker()))
+                        {
+                            state.blocked_senders.push(cx.waker().clone());
+                        }
+                        return Poll::Pending;
+                    }
+                    OverflowPolicy::DropOldest => {
+                        state.overflows += 1;
+                        debug!(
+                            "The mailbox of {} is full, dropping its oldest message",
+                            self.shared.address
+                        );
+                        drop(state.queue.pop_front());
+                        state.push(msg.take().expect("a message is only sent once"))
+                    }
+                    OverflowPolicy::DropNewest => {
+                        state.overflows += 1;
+                        debug!(
+                            "The mailbox of {} is full, dropping the new message",
+                            self.shared.address
+                        );
+                        None
+                    }
+                    OverflowPolicy::Reject => {
+                        state.overflows += 1;
+                        return Poll::Ready(Err(self.full_error()));
+                    }
+                }
+            };
+            drop(state);
+
+            if let Some(waker) = waker {
+                waker.wake()
+            }
+            Poll::Ready(Ok(()))
+        })
+        .await
+    }
+
+    /// Number of messages waiting in the mailbox
+    pub fn len(&self) -> usize {
+        self.shared.state.lock().unwrap().queue.len()
+    }
+
+    /// Return true if no messages are waiting in the mailbox
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Capacity and overflow policy of the mailbox
+    pub fn options(&self) -> MailboxOptions {
+        self.shared.options
+    }
+
+    /// Current statistics of the mailbox, reported for the given address
+    pub(crate) fn statistics(&self, address: Address) -> MailboxStatistics {
+        let state = self.shared.state.lock().unwrap();
+        MailboxStatistics {
+            address,
+            capacity: self.shared.options.capacity,
+            overflow_policy: self.shared.options.overflow_policy,
+            depth: state.queue.len(),
+            delivered: state.delivered,
+            overflows: state.overflows,
+        }
+    }
+
+    fn closed_error(&self) -> Error {
+        Error::new(
+            Origin::Node,
+            Kind::Internal,
+            NodeError::NodeState(NodeReason::Unknown),
+        )
+        .context("SendError", "the mailbox is closed")
+        .context("Address", &self.shared.address)
+    }
+
+    fn full_error(&self) -> Error {
+        Error::new(
+            Origin::Node,
+            Kind::ResourceExhausted,
+            NodeError::WorkerState(WorkerReason::MailboxFull),
+        )
+        .context("Address", &self.shared.address)
+    }
+}
+
+/// Receiving half of a worker mailbox
+pub struct MailboxReceiver<T> {
+    shared: Arc<Shared<T>>,
+}
+
+impl<T> fmt::Debug for MailboxReceiver<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("MailboxReceiver")
+            .field("address", &self.shared.address)
+            .field("options", &self.shared.options)
+            .finish()
+    }
+}
+
+impl<T> Drop for MailboxReceiver<T> {
+    fn drop(&mut self) {
+        let (queue, blocked_senders) = {
+            let mut state = self.shared.state.lock().unwrap();
+            state.closed = true;
+            (
+                core::mem::take(&mut state.queue),
+                core::mem::take(&mut state.blocked_senders),
+            )
+        };
+        // Pending messages are dropped outside of the lock
+        drop(queue);
+        blocked_senders.into_iter().for_each(Waker::wake);
+    }
+}
+
+impl<T> MailboxReceiver<T> {
+    /// Wait for the next message. Return `None` once all the senders
+    /// are dropped and the mailbox is empty
+    pub async fn recv(&mut self) -> Option<T> {
+        poll_fn(|cx| {
+            let mut state = self.shared.state.lock().unwrap();
+            match state.queue.pop_front() {
+                Some(msg) => {
+                    let blocked_senders = core::mem::take(&mut state.blocked_senders);
+                    drop(state);
+                    // Senders re-register themselves if the mailbox is full again
+                    blocked_senders.into_iter().for_each(Waker::wake);
+                    Poll::Ready(Some(msg))
+                }
+                None if state.senders == 0 => Poll::Ready(None),
+                None => {
+                    state.receiver_waker = Some(cx.waker().clone());
+                    Poll::Pending
+                }
+            }
+        })
+        .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tokio;
+    use core::time::Duration;
+
+    fn channel(
+        capacity: usize,
+        policy: OverflowPolicy,
+    ) -> (MailboxSender<u8>, MailboxReceiver<u8>) {
+        mailbox_channel(
+            "worker".into(),
+            MailboxOptions::new()
+                .with_capacity(capacity)
+                .with_overflow_policy(policy),
+        )
+    }
+
+    #[tokio::test]
+    async fn drop_oldest_keeps_the_latest_messages() {
+        let (tx, mut rx) = channel(2, OverflowPolicy::DropOldest);
+        for i in 0..4 {
+            tx.send(i).await.unwrap();
+        }
+        assert_eq!(rx.recv().await, Some(2));
+        assert_eq!(rx.recv().await, Some(3));
+
+        let statistics = tx.statistics("worker".into());
+        assert_eq!(statistics.delivered, 4);
+        assert_eq!(statistics.overflows, 2);
+        assert_eq!(statistics.depth, 0);
+    }
+
+    #[tokio::test]
+    async fn drop_newest_keeps_the_first_messages() {
+        let (tx, mut rx) = channel(2, OverflowPolicy::DropNewest);
+        for i in 0..4 {
+            tx.send(i).await.unwrap();
+        }
+        drop(tx);
+        assert_eq!(rx.recv().await, Some(0));
+        assert_eq!(rx.recv().await, Some(1));
+        assert_eq!(rx.recv().await, None);
+    }
+
+    #[tokio::test]
+    async fn reject_returns_an_error_to_the_sender() {
+        let (tx, mut rx) = channel(1, OverflowPolicy::Reject);
+        tx.send(0).await.unwrap();
+        let error = tx.send(1).await.unwrap_err();
+        assert_eq!(error.code().kind, Kind::ResourceExhausted);
+
+        assert_eq!(rx.recv().await, Some(0));
+        tx.send(2).await.unwrap();
+        assert_eq!(rx.recv().await, Some(2));
+    }
+
+    #[tokio::test]
+    async fn block_waits_for_a_free_slot() {
+        let (tx, mut rx) = channel(1, OverflowPolicy::Block);
+        tx.send(0).await.unwrap();
+
+        let blocked = tokio::spawn({
+            let tx = tx.clone();
+            async move { tx.send(1).await }
+        });
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!blocked.is_finished());
+
+        assert_eq!(rx.recv().await, Some(0));
+        blocked.await.unwrap().unwrap();
+        assert_eq!(rx.recv().await, Some(1));
+
+        drop(rx);
+        assert!(tx.send(2).await.is_err());
+    }
+}
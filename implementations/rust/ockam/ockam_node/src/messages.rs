@@ -2,6 +2,7 @@ use crate::channel_types::{small_channel, MessageSender, SmallReceiver, SmallSen
 use crate::{
     error::{NodeError, NodeReason, RouterReason, WorkerReason},
     router::SenderPair,
+    MailboxStatistics,
 };
 use core::{fmt, sync::atomic::AtomicUsize};
 use ockam_core::compat::{string::String, sync::Arc, vec::Vec};
@@ -25,6 +26,8 @@ pub enum NodeMessage {
     },
     /// Return a list of all worker addresses
     ListWorkers(SmallSender<NodeReplyResult>),
+    /// Return the mailbox statistics of each worker
+    MailboxStatistics(SmallSender<NodeReplyResult>),
     /// Add an existing address to a cluster
     SetCluster(Address, String, SmallSender<NodeReplyResult>),
     /// Stop an existing worker
@@ -54,6 +57,7 @@ impl fmt::Display for NodeMessage {
         match self {
             NodeMessage::StartWorker { .. } => write!(f, "StartWorker"),
             NodeMessage::ListWorkers(_) => write!(f, "ListWorkers"),
+            NodeMessage::MailboxStatistics(_) => write!(f, "MailboxStatistics"),
             NodeMessage::SetCluster(_, _, _) => write!(f, "SetCluster"),
             NodeMessage::StopWorker(_, _, _) => write!(f, "StopWorker"),
             NodeMessage::StartProcessor(_, _, _) => write!(f, "StartProcessor"),
@@ -118,6 +122,12 @@ impl NodeMessage {
         (Self::ListWorkers(tx), rx)
     }
 
+    /// Create a mailbox statistics message and reply receiver
+    pub fn mailbox_statistics() -> (Self, SmallReceiver<NodeReplyResult>) {
+        let (tx, rx) = small_channel();
+        (Self::MailboxStatistics(tx), rx)
+    }
+
     /// Create a set cluster message and reply receiver
     pub fn set_cluster(addr: Address, label: String) -> (Self, SmallReceiver<NodeReplyResult>) {
         let (tx, rx) = small_channel();
@@ -164,6 +174,8 @@ pub enum RouterReply {
     Ok,
     /// A list of worker addresses
     Workers(Vec<Address>),
+    /// The mailbox statistics of each worker
+    MailboxStatistics(Vec<MailboxStatistics>),
     /// Message sender to a specific worker
     Sender {
         /// The address a message is being sent to
@@ -259,6 +271,11 @@ impl RouterReply {
         Ok(Self::Workers(v))
     }
 
+    /// Return [RouterReply::MailboxStatistics] for the given statistics
+    pub fn mailbox_statistics(v: Vec<MailboxStatistics>) -> NodeReplyResult {
+        Ok(Self::MailboxStatistics(v))
+    }
+
     /// Return [RouterReply::Sender] for the given information
     pub fn sender(addr: Address, sender: MessageSender<RelayMessage>) -> NodeReplyResult {
         Ok(RouterReply::Sender { addr, sender })
@@ -280,6 +297,14 @@ impl RouterReply {
         }
     }
 
+    /// Consume the wrapper and return [RouterReply::MailboxStatistics]
+    pub fn take_mailbox_statistics(self) -> Result<Vec<MailboxStatistics>> {
+        match self {
+            Self::MailboxStatistics(s) => Ok(s),
+            _ => Err(NodeError::NodeState(NodeReason::Unknown).internal()),
+        }
+    }
+
     /// Consume the wrapper and return [RouterReply::State]
     pub fn take_state(self) -> Result<bool> {
         match self {
@@ -65,6 +65,7 @@ impl NodeBuilder {
             None,
             Default::default(),
             &flow_controls,
+            Default::default(),
         );
 
         debugger::log_inherit_context("NODE", &ctx, &ctx);
@@ -1,6 +1,6 @@
 use crate::debugger;
 use crate::error::{NodeError, NodeReason};
-use crate::{relay::ProcessorRelay, Context, NodeMessage};
+use crate::{relay::ProcessorRelay, Context, MailboxOptions, NodeMessage};
 use ockam_core::compat::sync::Arc;
 use ockam_core::{
     errcode::{Kind, Origin},
@@ -40,6 +40,7 @@ where
             outgoing_ac: Arc::new(DenyAll),
             processor: self.processor,
             address: address.into(),
+            mailbox_options: MailboxOptions::default(),
         }
     }
 
@@ -48,6 +49,7 @@ where
         ProcessorBuilderMultipleAddresses {
             mailboxes,
             processor: self.processor,
+            mailbox_options: MailboxOptions::default(),
         }
     }
 }
@@ -58,15 +60,28 @@ where
 {
     mailboxes: Mailboxes,
     processor: P,
+    mailbox_options: MailboxOptions,
 }
 
 impl<P> ProcessorBuilderMultipleAddresses<P>
 where
     P: Processor<Context = Context>,
 {
+    /// Set the capacity and overflow policy of the processor mailbox
+    pub fn with_mailbox_options(mut self, mailbox_options: MailboxOptions) -> Self {
+        self.mailbox_options = mailbox_options;
+        self
+    }
+
     /// Consume this builder and start a new Ockam [`Processor`] from the given context
     pub async fn start(self, context: &Context) -> Result<()> {
-        start(context, self.mailboxes, self.processor).await
+        start(
+            context,
+            self.mailboxes,
+            self.mailbox_options,
+            self.processor,
+        )
+        .await
     }
 }
 
@@ -78,6 +93,7 @@ where
     outgoing_ac: Arc<dyn OutgoingAccessControl>,
     address: Address,
     processor: P,
+    mailbox_options: MailboxOptions,
 }
 
 impl<P> ProcessorBuilderOneAddress<P>
@@ -89,6 +105,7 @@ where
         start(
             context,
             Mailboxes::main(self.address, self.incoming_ac, self.outgoing_ac),
+            self.mailbox_options,
             self.processor,
         )
         .await
@@ -134,10 +151,21 @@ where
         self.outgoing_ac = outgoing_access_control.clone();
         self
     }
+
+    /// Set the capacity and overflow policy of the processor mailbox
+    pub fn with_mailbox_options(mut self, mailbox_options: MailboxOptions) -> Self {
+        self.mailbox_options = mailbox_options;
+        self
+    }
 }
 
 /// Consume this builder and start a new Ockam [`Processor`] from the given context
-pub async fn start<P>(context: &Context, mailboxes: Mailboxes, processor: P) -> Result<()>
+pub async fn start<P>(
+    context: &Context,
+    mailboxes: Mailboxes,
+    mailbox_options: MailboxOptions,
+    processor: P,
+) -> Result<()>
 where
     P: Processor<Context = Context>,
 {
@@ -151,7 +179,7 @@ where
     let main_address = mailboxes.main_address().clone();
 
     // Pass it to the context
-    let (ctx, sender, ctrl_rx) = context.copy_with_mailboxes(mailboxes);
+    let (ctx, sender, ctrl_rx) = context.copy_with_mailboxes(mailboxes, mailbox_options);
 
     debugger::log_inherit_context("PROCESSOR", context, &ctx);
 
@@ -3,6 +3,7 @@ use crate::relay::CtrlSignal;
 use crate::tokio::runtime::Handle;
 use crate::{parser, Context};
 use ockam_core::{Message, RelayMessage, Result, Routed, Worker};
+use tracing::Instrument;
 
 /// Worker relay machinery
 ///
@@ -71,7 +72,34 @@ where
 
         // Call the worker handle function - pass errors up
         let routed = Self::wrap_direct_message(relay_msg)?;
-        self.worker.handle_message(&mut self.ctx, routed).await?;
+        let tracing_context = routed.local_message().transport().tracing_context;
+        match tracing_context {
+            Some(parent) => {
+                // Handle the message in a child span of the sender's span, and
+                // propagate it to the messages sent by the worker
+                let context = parent.new_child();
+                let span = if context.is_sampled() {
+                    info_span!(
+                        "handle_message",
+                        worker = %self.ctx.address(),
+                        trace_id = %context.trace_id_hex(),
+                        span_id = %context.span_id_hex(),
+                        parent_span_id = %parent.span_id_hex(),
+                    )
+                } else {
+                    tracing::Span::none()
+                };
+                self.ctx.set_tracing_context(Some(context));
+                let result = self
+                    .worker
+                    .handle_message(&mut self.ctx, routed)
+                    .instrument(span)
+                    .await;
+                self.ctx.set_tracing_context(None);
+                result?;
+            }
+            None => self.worker.handle_message(&mut self.ctx, routed).await?,
+        }
 
         // Signal to the outer loop that we would like to run again
         Ok(true)
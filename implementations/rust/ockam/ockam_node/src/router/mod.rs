@@ -249,6 +249,13 @@ impl Router {
                 .await
                 .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?,
 
+            MailboxStatistics(sender) => sender
+                .send(RouterReply::mailbox_statistics(
+                    self.map.mailbox_statistics(),
+                ))
+                .await
+                .map_err(|_| NodeError::NodeState(NodeReason::Unknown).internal())?,
+
             SetCluster(addr, label, reply) => {
                 debug!("Setting cluster on address {}", addr);
                 let msg = self.map.set_cluster(label, addr);
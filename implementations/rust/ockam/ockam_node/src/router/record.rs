@@ -2,7 +2,7 @@ use crate::channel_types::{MessageSender, SmallSender};
 use crate::relay::CtrlSignal;
 use crate::{
     error::{NodeError, NodeReason},
-    NodeReplyResult, RouterReply,
+    MailboxStatistics, NodeReplyResult, RouterReply,
 };
 use core::sync::atomic::{AtomicUsize, Ordering};
 use ockam_core::{
@@ -100,6 +100,14 @@ impl InternalMap {
     pub(super) fn get_primary_address(&self, alias_address: &Address) -> Option<&Address> {
         self.alias_map.get(alias_address)
     }
+
+    /// Return the mailbox statistics of each worker which can still receive messages
+    pub(super) fn mailbox_statistics(&self) -> Vec<MailboxStatistics> {
+        self.address_records_map
+            .iter()
+            .filter_map(|(address, record)| record.mailbox_statistics(address))
+            .collect()
+    }
 }
 
 impl InternalMap {
@@ -262,6 +270,13 @@ impl AddressRecord {
         self.sender.clone().expect("No such sender!")
     }
 
+    /// Return the statistics of the worker mailbox, if it is still open
+    pub fn mailbox_statistics(&self, address: &Address) -> Option<MailboxStatistics> {
+        self.sender
+            .as_ref()
+            .map(|sender| sender.statistics(address.clone()))
+    }
+
     pub fn drop_sender(&mut self) {
         self.sender = None;
     }
@@ -353,7 +368,7 @@ pub enum ReadyState {
 #[cfg(test)]
 mod test {
     use super::*;
-    use crate::channel_types::small_channel;
+    use crate::channel_types::{message_channel, small_channel};
     use crate::router::record::InternalMap;
 
     #[test]
@@ -387,7 +402,7 @@ mod test {
 
     /// HELPERS
     fn create_address_record(primary: &str) -> AddressRecord {
-        let (tx1, _) = small_channel();
+        let (tx1, _) = message_channel(primary.into(), Default::default());
         let (tx2, _) = small_channel();
         AddressRecord::new(
             vec![primary.into()],
@@ -0,0 +1,20 @@
+-------------------
+-- POLICY DECISIONS
+-------------------
+
+-- This table stores an audit trail of the decisions taken when evaluating policies
+CREATE TABLE policy_decision
+(
+    decided_at       BIGINT  NOT NULL, -- UNIX timestamp in seconds: when the policy was evaluated
+    subject          TEXT,             -- optional identifier of the identity which sent the message
+    resource         TEXT,             -- optional resource name
+    action           TEXT,             -- optional action name
+    policy_hash      TEXT    NOT NULL, -- hex-encoded SHA256 hash of the encoded policy expression
+    is_authorized    BIGINT  NOT NULL, -- boolean indicating if access was granted (1 means true)
+    environment_keys TEXT    NOT NULL, -- comma-separated list of the names bound in the evaluation environment
+    rowid            BIGSERIAL         -- insertion order of the decisions, as given by the implicit rowid column in Sqlite
+);
+
+CREATE INDEX policy_decision_decided_at_index ON policy_decision (decided_at);
+CREATE INDEX policy_decision_resource_index ON policy_decision (resource);
+CREATE INDEX policy_decision_subject_index ON policy_decision (subject);
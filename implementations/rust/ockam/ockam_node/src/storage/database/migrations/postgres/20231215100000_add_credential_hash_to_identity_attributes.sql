@@ -0,0 +1,7 @@
+-----------------------------
+-- IDENTITY ATTRIBUTES
+-----------------------------
+
+-- Attributes received with a credential keep the hash of that credential, so that they
+-- can be removed when the credential is revoked
+ALTER TABLE identity_attributes ADD COLUMN credential_hash TEXT; -- Hex-encoded SHA256 hash of the credential data
@@ -0,0 +1,29 @@
+---------------
+-- REVOCATIONS
+---------------
+
+-- This table stores the hashes of credentials revoked by a given authority
+CREATE TABLE revoked_credential
+(
+    issuer          TEXT NOT NULL, -- Identifier of the authority which revoked the credential
+    credential_hash TEXT NOT NULL  -- Hex-encoded SHA256 hash of the credential data
+);
+
+CREATE UNIQUE INDEX revoked_credential_index ON revoked_credential (issuer, credential_hash);
+
+-- This table stores the subjects whose credentials have all been revoked by a given authority
+CREATE TABLE revoked_subject
+(
+    issuer  TEXT NOT NULL, -- Identifier of the authority which revoked the subject
+    subject TEXT NOT NULL  -- Identifier of the revoked subject
+);
+
+CREATE UNIQUE INDEX revoked_subject_index ON revoked_subject (issuer, subject);
+
+-- This table stores the latest signed revocation list received from a given authority
+CREATE TABLE revocation_list
+(
+    issuer          TEXT PRIMARY KEY, -- Identifier of the authority which signed the revocation list
+    created_at      INTEGER NOT NULL, -- UNIX timestamp in seconds: when the revocation list was created
+    revocation_list BLOB    NOT NULL  -- Encoded revocation list: revocation list data, signature and purpose key attestation
+);
@@ -0,0 +1,15 @@
+-------------------------
+-- SECRETS WRAPPING KEY
+-------------------------
+
+-- This table describes the key used to encrypt the vault secrets, when they are encrypted at rest.
+-- It contains at most one row. When it is empty the secrets are stored in clear.
+CREATE TABLE secrets_wrapping_key
+(
+    key_source      TEXT    NOT NULL, -- 'passphrase' if the key is derived from a passphrase, 'provider' if it comes from a key provider
+    salt            BLOB    NOT NULL, -- salt used to derive the key from a passphrase, empty otherwise
+    memory_cost     INTEGER,          -- Argon2id memory cost, in KiB, used to derive the key from a passphrase
+    time_cost       INTEGER,          -- Argon2id number of passes used to derive the key from a passphrase
+    parallelism     INTEGER,          -- Argon2id number of lanes used to derive the key from a passphrase
+    key_check       BLOB    NOT NULL  -- encryption of a known value with the key, used to check that a key is correct
+);
@@ -0,0 +1,8 @@
+-------------------------
+-- PKCS#11 VAULTS
+-------------------------
+
+-- A KMS vault can store its keys in a token accessed via a PKCS#11 module.
+-- Those columns are NULL for other vaults.
+ALTER TABLE vault ADD COLUMN pkcs11_module TEXT;    -- Path to the PKCS#11 module
+ALTER TABLE vault ADD COLUMN pkcs11_slot   INTEGER; -- Slot of the token
@@ -0,0 +1,6 @@
+-----------------------------
+-- IDENTITY ATTRIBUTES
+-----------------------------
+
+-- Index the credential hash of the attributes, to remove them when their credential is revoked
+CREATE INDEX identity_attributes_credential_hash_index ON identity_attributes (credential_hash);
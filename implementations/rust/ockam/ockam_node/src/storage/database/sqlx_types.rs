@@ -2,14 +2,16 @@ use std::net::SocketAddr;
 use std::path::PathBuf;
 
 use ockam_core::Address;
+use sqlx::any::AnyValueRef;
 use sqlx::database::HasArguments;
 use sqlx::encode::IsNull;
-use sqlx::{Database, Encode, Sqlite, Type};
+use sqlx::error::BoxDynError;
+use sqlx::{Any, Database, Decode, Encode, Type, TypeInfo, ValueRef};
 use time::OffsetDateTime;
 
 /// This enum represents the set of types that we currently support in our database
-/// Since we support only Sqlite at the moment, those types are close to what is supported by Sqlite:
-/// https://www.sqlite.org/datatype3.html
+/// Those types are close to what is supported by Sqlite: https://www.sqlite.org/datatype3.html
+/// and are mapped to BIGINT, BYTEA, TEXT and DOUBLE PRECISION for Postgres
 ///
 /// The purpose of this type is to ease the serialization of data types in Ockam into data types in
 /// our database. For example, if we describe how to translate an `Identifier` into some `Text` then
@@ -29,36 +31,114 @@ pub enum SqlxType {
     Real(f64),
 }
 
-/// The SqlxType implements the Type<Sqlite> trait from sqlx to allow its values to be serialized
-/// to an Sqlite database
-impl Type<Sqlite> for SqlxType {
-    fn type_info() -> <Sqlite as Database>::TypeInfo {
-        <Vec<u8> as Type<Sqlite>>::type_info()
+/// The SqlxType implements the Type<Any> trait from sqlx to allow its values to be serialized
+/// to any of the supported databases
+impl Type<Any> for SqlxType {
+    fn type_info() -> <Any as Database>::TypeInfo {
+        <Vec<u8> as Type<Any>>::type_info()
     }
 }
 
-/// The SqlType implements the Encode<Sqlite> trait from sqlx to allow its values to be serialized
-/// to an Sqlite database. There is a 1 to 1 mapping with the database native types
-impl Encode<'_, Sqlite> for SqlxType {
-    fn encode_by_ref(&self, buf: &mut <Sqlite as HasArguments>::ArgumentBuffer) -> IsNull {
+/// The SqlType implements the Encode<Any> trait from sqlx to allow its values to be serialized
+/// to any of the supported databases. There is a 1 to 1 mapping with the database native types
+impl Encode<'_, Any> for SqlxType {
+    fn encode_by_ref(&self, buf: &mut <Any as HasArguments>::ArgumentBuffer) -> IsNull {
         match self {
-            SqlxType::Text(v) => <String as Encode<'_, Sqlite>>::encode_by_ref(v, buf),
-            SqlxType::Blob(v) => <Vec<u8> as Encode<'_, Sqlite>>::encode_by_ref(v, buf),
-            SqlxType::Integer(v) => <i64 as Encode<'_, Sqlite>>::encode_by_ref(v, buf),
-            SqlxType::Real(v) => <f64 as Encode<'_, Sqlite>>::encode_by_ref(v, buf),
+            SqlxType::Text(v) => <String as Encode<'_, Any>>::encode_by_ref(v, buf),
+            SqlxType::Blob(v) => <Vec<u8> as Encode<'_, Any>>::encode_by_ref(v, buf),
+            SqlxType::Integer(v) => <i64 as Encode<'_, Any>>::encode_by_ref(v, buf),
+            SqlxType::Real(v) => <f64 as Encode<'_, Any>>::encode_by_ref(v, buf),
         }
     }
 
-    fn produces(&self) -> Option<<Sqlite as Database>::TypeInfo> {
+    fn produces(&self) -> Option<<Any as Database>::TypeInfo> {
         Some(match self {
-            SqlxType::Text(_) => <String as Type<Sqlite>>::type_info(),
-            SqlxType::Blob(_) => <Vec<u8> as Type<Sqlite>>::type_info(),
-            SqlxType::Integer(_) => <i64 as Type<Sqlite>>::type_info(),
-            SqlxType::Real(_) => <f64 as Type<Sqlite>>::type_info(),
+            SqlxType::Text(_) => <String as Type<Any>>::type_info(),
+            SqlxType::Blob(_) => <Vec<u8> as Type<Any>>::type_info(),
+            SqlxType::Integer(_) => <i64 as Type<Any>>::type_info(),
+            SqlxType::Real(_) => <f64 as Type<Any>>::type_info(),
         })
     }
 }
 
+/// Booleans are stored as integers (see the `ToSqlxType` instance for `bool`)
+/// and the Any driver cannot decode an integer as a `bool`.
+/// This type can be used instead of `bool` in a row type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Boolean(bool);
+
+impl Boolean {
+    /// Create a new Boolean value
+    pub fn new(value: bool) -> Self {
+        Boolean(value)
+    }
+
+    /// Return the boolean value
+    pub fn to_bool(&self) -> bool {
+        self.0
+    }
+}
+
+impl Type<Any> for Boolean {
+    fn type_info() -> <Any as Database>::TypeInfo {
+        <i64 as Type<Any>>::type_info()
+    }
+
+    fn compatible(ty: &<Any as Database>::TypeInfo) -> bool {
+        <i64 as Type<Any>>::compatible(ty)
+    }
+}
+
+impl Decode<'_, Any> for Boolean {
+    fn decode(value: AnyValueRef<'_>) -> Result<Self, BoxDynError> {
+        Ok(Boolean(<i64 as Decode<'_, Any>>::decode(value)? != 0))
+    }
+}
+
+/// The Any driver never reports a value as being NULL, so an optional column can not be
+/// decoded as an `Option<T>`. This type can be used instead of `Option<T>` in a row type.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Nullable<T>(Option<T>);
+
+impl<T> Nullable<T> {
+    /// Create a new Nullable value
+    pub fn new(value: Option<T>) -> Self {
+        Nullable(value)
+    }
+
+    /// Return the optional value
+    pub fn to_option(&self) -> Option<T>
+    where
+        T: Clone,
+    {
+        self.0.clone()
+    }
+}
+
+fn is_null(ty: &<Any as Database>::TypeInfo) -> bool {
+    ty.name() == "NULL"
+}
+
+impl<T: Type<Any>> Type<Any> for Nullable<T> {
+    fn type_info() -> <Any as Database>::TypeInfo {
+        T::type_info()
+    }
+
+    fn compatible(ty: &<Any as Database>::TypeInfo) -> bool {
+        is_null(ty) || T::compatible(ty)
+    }
+}
+
+impl<'r, T: Decode<'r, Any>> Decode<'r, Any> for Nullable<T> {
+    fn decode(value: AnyValueRef<'r>) -> Result<Self, BoxDynError> {
+        if is_null(&value.type_info()) {
+            Ok(Nullable(None))
+        } else {
+            Ok(Nullable(Some(T::decode(value)?)))
+        }
+    }
+}
+
 /// This trait can be implemented by any type that can be converted to a database type
 /// Typically an `Identifier` (to a `Text`), a `TimestampInSeconds` (to an `Integer`) etc...
 ///
@@ -134,6 +214,12 @@ impl ToSqlxType for u8 {
     }
 }
 
+impl ToSqlxType for i64 {
+    fn to_sql(&self) -> SqlxType {
+        SqlxType::Integer(*self)
+    }
+}
+
 impl ToSqlxType for i32 {
     fn to_sql(&self) -> SqlxType {
         SqlxType::Integer(*self as i64)
@@ -1,6 +1,6 @@
 use crate::debugger;
 use crate::error::{NodeError, NodeReason};
-use crate::{relay::WorkerRelay, Context, NodeMessage};
+use crate::{relay::WorkerRelay, Context, MailboxOptions, NodeMessage};
 use ockam_core::compat::sync::Arc;
 use ockam_core::{
     errcode::{Kind, Origin},
@@ -40,6 +40,7 @@ where
             outgoing_ac: Arc::new(AllowAll),
             worker: self.worker,
             address: address.into(),
+            mailbox_options: MailboxOptions::default(),
         }
     }
 
@@ -48,6 +49,7 @@ where
         WorkerBuilderMultipleAddresses {
             mailboxes,
             worker: self.worker,
+            mailbox_options: MailboxOptions::default(),
         }
     }
 }
@@ -58,15 +60,22 @@ where
 {
     mailboxes: Mailboxes,
     worker: W,
+    mailbox_options: MailboxOptions,
 }
 
 impl<W> WorkerBuilderMultipleAddresses<W>
 where
     W: Worker<Context = Context>,
 {
+    /// Set the capacity and overflow policy of the worker mailbox
+    pub fn with_mailbox_options(mut self, mailbox_options: MailboxOptions) -> Self {
+        self.mailbox_options = mailbox_options;
+        self
+    }
+
     /// Consume this builder and start a new Ockam [`Worker`] from the given context
     pub async fn start(self, context: &Context) -> Result<()> {
-        start(context, self.mailboxes, self.worker).await
+        start(context, self.mailboxes, self.mailbox_options, self.worker).await
     }
 }
 
@@ -78,6 +87,7 @@ where
     outgoing_ac: Arc<dyn OutgoingAccessControl>,
     address: Address,
     worker: W,
+    mailbox_options: MailboxOptions,
 }
 
 impl<W> WorkerBuilderOneAddress<W>
@@ -89,6 +99,7 @@ where
         start(
             context,
             Mailboxes::main(self.address, self.incoming_ac, self.outgoing_ac),
+            self.mailbox_options,
             self.worker,
         )
         .await
@@ -134,10 +145,21 @@ where
         self.outgoing_ac = outgoing_access_control.clone();
         self
     }
+
+    /// Set the capacity and overflow policy of the worker mailbox
+    pub fn with_mailbox_options(mut self, mailbox_options: MailboxOptions) -> Self {
+        self.mailbox_options = mailbox_options;
+        self
+    }
 }
 
 /// Consume this builder and start a new Ockam [`Worker`] from the given context
-async fn start<W>(context: &Context, mailboxes: Mailboxes, worker: W) -> Result<()>
+async fn start<W>(
+    context: &Context,
+    mailboxes: Mailboxes,
+    mailbox_options: MailboxOptions,
+    worker: W,
+) -> Result<()>
 where
     W: Worker<Context = Context>,
 {
@@ -151,7 +173,7 @@ where
     let addresses = mailboxes.addresses();
 
     // Pass it to the context
-    let (ctx, sender, ctrl_rx) = context.copy_with_mailboxes(mailboxes);
+    let (ctx, sender, ctrl_rx) = context.copy_with_mailboxes(mailboxes, mailbox_options);
 
     debugger::log_inherit_context("WORKER", context, &ctx);
 
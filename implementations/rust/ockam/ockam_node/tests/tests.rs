@@ -5,10 +5,13 @@ use ockam_core::compat::{
     string::{String, ToString},
     sync::Arc,
 };
+use ockam_core::errcode::Kind;
 use ockam_core::{async_trait, Address, AllowAll, Any, Decodable, DenyAll, Message, LOCAL};
-use ockam_core::{route, Processor, Result, Routed, Worker};
+use ockam_core::{route, Processor, Result, Routed, TraceContext, Worker};
 use ockam_node::compat::futures::FutureExt;
-use ockam_node::{Context, MessageReceiveOptions, NodeBuilder};
+use ockam_node::{
+    Context, MailboxOptions, MessageReceiveOptions, NodeBuilder, OverflowPolicy, WorkerBuilder,
+};
 use serde::{Deserialize, Serialize};
 use std::sync::atomic::{AtomicI8, AtomicU32};
 use std::time::{SystemTime, UNIX_EPOCH};
@@ -560,3 +563,122 @@ async fn starting_worker_with_dup_address_should_fail(ctx: &mut Context) -> Resu
         .is_err());
     ctx.stop().await
 }
+
+#[ockam_macros::test]
+async fn trace_context_is_propagated_by_workers(ctx: &mut Context) -> Result<()> {
+    ctx.start_worker("echoer", DummyWorker).await?;
+
+    // Messages sent without a trace context don't get one
+    let mut child_ctx = ctx.new_detached("client", AllowAll, AllowAll).await?;
+    child_ctx
+        .send(route!["echoer"], "Hello".to_string())
+        .await?;
+    let reply = child_ctx.receive::<String>().await?;
+    assert_eq!(reply.local_message().transport().tracing_context, None);
+
+    // The worker replies within the trace of the received message, from a child span
+    let root = TraceContext::new_root();
+    child_ctx.set_tracing_context(Some(root));
+    child_ctx
+        .send(route!["echoer"], "Hello".to_string())
+        .await?;
+    let reply = child_ctx.receive::<String>().await?;
+    let received = reply
+        .local_message()
+        .transport()
+        .tracing_context
+        .expect("the reply should have a trace context");
+    assert_eq!(received.trace_id(), root.trace_id());
+    assert_ne!(received.span_id(), root.span_id());
+    assert_eq!(reply.body(), "Hello");
+
+    ctx.stop().await
+}
+
+/// Collects the received messages, once its initialization delay has elapsed
+struct SlowStartWorker {
+    received: Arc<std::sync::Mutex<Vec<String>>>,
+}
+
+#[async_trait]
+impl Worker for SlowStartWorker {
+    type Message = String;
+    type Context = Context;
+
+    async fn initialize(&mut self, _ctx: &mut Context) -> Result<()> {
+        sleep(Duration::from_millis(300)).await;
+        Ok(())
+    }
+
+    async fn handle_message(&mut self, _ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        self.received.lock().unwrap().push(msg.body());
+        Ok(())
+    }
+}
+
+#[ockam_macros::test]
+async fn full_mailbox_drops_the_oldest_messages(ctx: &mut Context) -> Result<()> {
+    let received = Arc::new(std::sync::Mutex::new(Vec::new()));
+    WorkerBuilder::new(SlowStartWorker {
+        received: received.clone(),
+    })
+    .with_address("slow")
+    .with_mailbox_options(
+        MailboxOptions::new()
+            .with_capacity(2)
+            .with_overflow_policy(OverflowPolicy::DropOldest),
+    )
+    .start(ctx)
+    .await?;
+
+    for i in 0..4 {
+        ctx.send(route!["slow"], i.to_string()).await?;
+    }
+
+    let statistics = ctx
+        .mailbox_statistics()
+        .await?
+        .into_iter()
+        .find(|s| s.address == "slow".into())
+        .expect("the worker should have mailbox statistics");
+    assert_eq!(statistics.capacity, 2);
+    assert_eq!(statistics.overflow_policy, OverflowPolicy::DropOldest);
+    assert_eq!(statistics.delivered, 4);
+    assert_eq!(statistics.overflows, 2);
+
+    sleep(Duration::from_millis(500)).await;
+    assert_eq!(*received.lock().unwrap(), vec!["2", "3"]);
+
+    ctx.stop().await
+}
+
+#[ockam_macros::test]
+async fn full_mailbox_rejects_messages(ctx: &mut Context) -> Result<()> {
+    WorkerBuilder::new(SlowStartWorker {
+        received: Default::default(),
+    })
+    .with_address("slow")
+    .with_mailbox_options(
+        MailboxOptions::new()
+            .with_capacity(1)
+            .with_overflow_policy(OverflowPolicy::Reject),
+    )
+    .start(ctx)
+    .await?;
+
+    ctx.send(route!["slow"], "first".to_string()).await?;
+    let error = ctx
+        .send(route!["slow"], "second".to_string())
+        .await
+        .unwrap_err();
+    assert_eq!(error.code().kind, Kind::ResourceExhausted);
+
+    // A worker with the default mailbox is not affected
+    ctx.start_worker("echoer", DummyWorker).await?;
+    let reply: String = ctx
+        .send_and_receive(route!["echoer"], "Hello".to_string())
+        .await?;
+    assert_eq!(reply, "Hello");
+
+    ctx.stop().await
+}
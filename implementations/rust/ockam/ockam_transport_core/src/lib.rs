@@ -16,5 +16,8 @@
 mod error;
 mod transport;
 
+#[cfg(feature = "tls")]
+pub mod tls;
+
 pub use error::TransportError;
 pub use transport::*;
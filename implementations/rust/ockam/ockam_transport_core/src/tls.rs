@@ -0,0 +1,197 @@
+//! TLS configuration shared by the transports supporting TLS connections.
+
+use core::time::Duration;
+use std::fs::File;
+use std::io::BufReader;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{Error, Result};
+use tokio_rustls::rustls::server::AllowAnyAuthenticatedClient;
+use tokio_rustls::rustls::{
+    Certificate, ClientConfig, PrivateKey, RootCertStore, ServerConfig, ServerName,
+};
+use tokio_rustls::{TlsAcceptor, TlsConnector};
+use tracing::warn;
+
+/// Maximum duration of the TLS handshake of an incoming connection
+pub const TLS_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Paths to a PEM encoded certificate chain and its private key
+#[derive(Clone, Debug)]
+pub struct TlsCertificate {
+    certificate: PathBuf,
+    private_key: PathBuf,
+}
+
+impl TlsCertificate {
+    /// Create a new certificate from the paths of its PEM files
+    pub fn new(certificate: impl Into<PathBuf>, private_key: impl Into<PathBuf>) -> Self {
+        Self {
+            certificate: certificate.into(),
+            private_key: private_key.into(),
+        }
+    }
+
+    /// Path to the PEM encoded certificate chain
+    pub fn certificate(&self) -> &Path {
+        &self.certificate
+    }
+
+    /// Path to the PEM encoded private key
+    pub fn private_key(&self) -> &Path {
+        &self.private_key
+    }
+}
+
+/// Create a TLS acceptor serving the given certificate and, if a client CA
+/// certificate is set, requiring the clients to present a certificate signed by it
+pub fn create_acceptor(
+    certificate: &TlsCertificate,
+    client_ca_certificate: Option<&Path>,
+) -> Result<TlsAcceptor> {
+    let (certs, key) = read_certificate(certificate)?;
+
+    let builder = ServerConfig::builder().with_safe_defaults();
+    let builder = match client_ca_certificate {
+        Some(path) => {
+            let mut roots = RootCertStore::empty();
+            add_ca_certificates(&mut roots, path)?;
+            builder.with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots).boxed())
+        }
+        None => builder.with_no_client_auth(),
+    };
+    let config = builder.with_single_cert(certs, key).map_err(tls_error)?;
+
+    Ok(TlsAcceptor::from(Arc::new(config)))
+}
+
+/// Create a TLS connector trusting the platform roots and, optionally, the CA
+/// certificate(s) stored at the given path. The client certificate, if any, is
+/// presented to the servers requesting one
+pub fn create_connector(
+    ca_certificate: Option<&Path>,
+    client_certificate: Option<&TlsCertificate>,
+) -> Result<TlsConnector> {
+    let mut roots = RootCertStore::empty();
+    match rustls_native_certs::load_native_certs() {
+        Ok(certs) => {
+            let certs = certs.into_iter().map(|c| c.0).collect::<Vec<_>>();
+            roots.add_parsable_certificates(&certs);
+        }
+        Err(e) => warn!("Could not load the platform certificates: {}", e),
+    }
+    if let Some(path) = ca_certificate {
+        add_ca_certificates(&mut roots, path)?;
+    }
+
+    let builder = ClientConfig::builder()
+        .with_safe_defaults()
+        .with_root_certificates(roots);
+    let config = match client_certificate {
+        Some(client_certificate) => {
+            let (certs, key) = read_certificate(client_certificate)?;
+            builder
+                .with_client_auth_cert(certs, key)
+                .map_err(tls_error)?
+        }
+        None => builder.with_no_client_auth(),
+    };
+
+    Ok(TlsConnector::from(Arc::new(config)))
+}
+
+/// Return the name that the server certificate must be valid for
+pub fn server_name(host: &str) -> Result<ServerName> {
+    ServerName::try_from(host).map_err(tls_error)
+}
+
+/// Return the host part of a `host:port` address, without the brackets of IPv6 addresses
+pub fn host(peer: &str) -> &str {
+    let host = peer.rsplit_once(':').map(|(host, _)| host).unwrap_or(peer);
+    host.trim_start_matches('[').trim_end_matches(']')
+}
+
+/// Create an error for an invalid TLS configuration or a failed TLS handshake
+pub fn tls_error(e: impl core::fmt::Display) -> Error {
+    Error::new(Origin::Transport, Kind::Invalid, e.to_string())
+}
+
+fn read_certificate(certificate: &TlsCertificate) -> Result<(Vec<Certificate>, PrivateKey)> {
+    let certs = read_pem_items(&certificate.certificate)?
+        .into_iter()
+        .filter_map(|item| match item {
+            rustls_pemfile::Item::X509Certificate(der) => Some(Certificate(der)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    if certs.is_empty() {
+        return Err(tls_error(format!(
+            "no certificate found in {}",
+            certificate.certificate.display()
+        )));
+    }
+
+    let key = read_pem_items(&certificate.private_key)?
+        .into_iter()
+        .find_map(|item| match item {
+            rustls_pemfile::Item::PKCS8Key(der)
+            | rustls_pemfile::Item::RSAKey(der)
+            | rustls_pemfile::Item::ECKey(der) => Some(PrivateKey(der)),
+            _ => None,
+        })
+        .ok_or_else(|| {
+            tls_error(format!(
+                "no private key found in {}",
+                certificate.private_key.display()
+            ))
+        })?;
+
+    Ok((certs, key))
+}
+
+fn add_ca_certificates(roots: &mut RootCertStore, path: &Path) -> Result<()> {
+    let certs = read_pem_items(path)?
+        .into_iter()
+        .filter_map(|item| match item {
+            rustls_pemfile::Item::X509Certificate(der) => Some(der),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    let (added, _) = roots.add_parsable_certificates(&certs);
+    if added == 0 {
+        return Err(tls_error(format!(
+            "no CA certificate found in {}",
+            path.display()
+        )));
+    }
+    Ok(())
+}
+
+fn read_pem_items(path: &Path) -> Result<Vec<rustls_pemfile::Item>> {
+    let file = File::open(path)
+        .map_err(|e| tls_error(format!("cannot open {}: {}", path.display(), e)))?;
+    rustls_pemfile::read_all(&mut BufReader::new(file))
+        .map_err(|e| tls_error(format!("cannot read {}: {}", path.display(), e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn host_of_peer_address() {
+        assert_eq!(host("example.com:443"), "example.com");
+        assert_eq!(host("127.0.0.1:4000"), "127.0.0.1");
+        assert_eq!(host("[::1]:4000"), "::1");
+        assert_eq!(host("localhost"), "localhost");
+    }
+
+    #[test]
+    fn missing_certificate_is_rejected() {
+        let certificate = TlsCertificate::new("does/not/exist.pem", "does/not/exist.key");
+        assert!(create_acceptor(&certificate, None).is_err());
+        assert!(create_connector(None, Some(&certificate)).is_err());
+    }
+}
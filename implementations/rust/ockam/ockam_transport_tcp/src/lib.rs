@@ -21,6 +21,7 @@ extern crate alloc;
 mod options;
 mod portal;
 mod registry;
+mod tls;
 mod transport;
 
 use ockam_core::TransportType;
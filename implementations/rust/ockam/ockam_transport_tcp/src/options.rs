@@ -1,7 +1,10 @@
+use crate::tls::{TlsClientOptions, TlsServerOptions};
 use crate::workers::Addresses;
 use ockam_core::compat::sync::Arc;
 use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl, FlowControls};
 use ockam_core::{Address, AllowAll, IncomingAccessControl, OutgoingAccessControl};
+use ockam_transport_core::tls::TlsCertificate;
+use std::path::PathBuf;
 
 pub(crate) struct TcpConnectionAccessControl {
     pub sender_incoming_access_control: Arc<dyn IncomingAccessControl>,
@@ -13,6 +16,7 @@ pub(crate) struct TcpConnectionAccessControl {
 pub struct TcpConnectionOptions {
     pub(super) consumer: Vec<FlowControlId>,
     pub(crate) flow_control_id: FlowControlId,
+    pub(crate) tls: Option<TlsClientOptions>,
 }
 
 impl TcpConnectionOptions {
@@ -22,6 +26,7 @@ impl TcpConnectionOptions {
         Self {
             consumer: vec![],
             flow_control_id: FlowControls::generate_flow_control_id(),
+            tls: None,
         }
     }
 
@@ -36,6 +41,38 @@ impl TcpConnectionOptions {
     pub fn flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
     }
+
+    /// Wrap the connection in TLS, verifying the server certificate against the platform roots
+    pub fn with_tls(mut self) -> Self {
+        self.tls.get_or_insert_with(Default::default);
+        self
+    }
+
+    /// Wrap the connection in TLS and also trust the PEM encoded CA certificate(s) at `path`
+    pub fn with_tls_ca_certificate(mut self, path: impl Into<PathBuf>) -> Self {
+        self.tls.get_or_insert_with(Default::default).ca_certificate = Some(path.into());
+        self
+    }
+
+    /// Wrap the connection in TLS and use `server_name` for SNI and certificate verification
+    /// instead of the host of the peer address
+    pub fn with_tls_server_name(mut self, server_name: impl Into<String>) -> Self {
+        self.tls.get_or_insert_with(Default::default).server_name = Some(server_name.into());
+        self
+    }
+
+    /// Wrap the connection in TLS and authenticate with the PEM encoded client
+    /// certificate chain and private key
+    pub fn with_tls_client_certificate(
+        mut self,
+        certificate: impl Into<PathBuf>,
+        private_key: impl Into<PathBuf>,
+    ) -> Self {
+        self.tls
+            .get_or_insert_with(Default::default)
+            .client_certificate = Some(TlsCertificate::new(certificate, private_key));
+        self
+    }
 }
 
 impl TcpConnectionOptions {
@@ -71,6 +108,8 @@ impl TcpConnectionOptions {
 #[derive(Debug)]
 pub struct TcpListenerOptions {
     pub(crate) flow_control_id: FlowControlId,
+    tls_certificate: Option<TlsCertificate>,
+    tls_client_ca_certificate: Option<PathBuf>,
 }
 
 impl TcpListenerOptions {
@@ -81,6 +120,8 @@ impl TcpListenerOptions {
     pub fn new() -> Self {
         Self {
             flow_control_id: FlowControls::generate_flow_control_id(),
+            tls_certificate: None,
+            tls_client_ca_certificate: None,
         }
     }
 
@@ -88,9 +129,35 @@ impl TcpListenerOptions {
     pub fn spawner_flow_control_id(&self) -> FlowControlId {
         self.flow_control_id.clone()
     }
+
+    /// Accept TLS connections only, serving the PEM encoded certificate chain and private key
+    pub fn with_tls(
+        mut self,
+        certificate: impl Into<PathBuf>,
+        private_key: impl Into<PathBuf>,
+    ) -> Self {
+        self.tls_certificate = Some(TlsCertificate::new(certificate, private_key));
+        self
+    }
+
+    /// Require TLS clients to present a certificate signed by the PEM encoded CA certificate(s)
+    /// at `path`. Only effective together with [`TcpListenerOptions::with_tls`]
+    pub fn with_tls_client_ca_certificate(mut self, path: impl Into<PathBuf>) -> Self {
+        self.tls_client_ca_certificate = Some(path.into());
+        self
+    }
 }
 
 impl TcpListenerOptions {
+    pub(crate) fn tls(&self) -> Option<TlsServerOptions> {
+        self.tls_certificate
+            .clone()
+            .map(|certificate| TlsServerOptions {
+                certificate,
+                client_ca_certificate: self.tls_client_ca_certificate.clone(),
+            })
+    }
+
     pub(crate) fn setup_flow_control_for_listener(
         &self,
         flow_controls: &FlowControls,
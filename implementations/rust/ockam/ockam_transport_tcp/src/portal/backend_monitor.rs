@@ -0,0 +1,134 @@
+use crate::portal::backends::{resolve_target, OutletBackends};
+use core::time::Duration;
+use ockam_core::{async_trait, Address, Processor, Result};
+use ockam_node::Context;
+use std::time::Instant;
+use tokio::net::TcpStream;
+use tokio::task::JoinSet;
+use tokio::time::{sleep, timeout};
+use tracing::{debug, warn};
+
+/// Maximum duration of a health check connection attempt
+const HEALTH_CHECK_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// A processor re-resolving the targets of a TCP outlet and checking the
+/// health of its backends
+///
+/// It is started and stopped together with the
+/// [`TcpOutletListenWorker`](crate::portal::TcpOutletListenWorker).
+pub(crate) struct TcpOutletBackendMonitor {
+    backends: OutletBackends,
+    dns_refresh_interval: Option<Duration>,
+    health_check_interval: Option<Duration>,
+    last_resolution: Instant,
+    last_health_check: Instant,
+}
+
+impl TcpOutletBackendMonitor {
+    /// Start the monitor if the backends of the outlet can change or need to be checked
+    pub(crate) async fn start(
+        ctx: &Context,
+        backends: OutletBackends,
+        dns_refresh_interval: Option<Duration>,
+        health_check_interval: Option<Duration>,
+    ) -> Result<Option<Address>> {
+        // Fixed addresses never need to be resolved again, and a single backend
+        // is always used, whatever its health
+        let dns_refresh_interval = dns_refresh_interval.filter(|_| backends.has_host_names());
+        let health_check_interval = health_check_interval
+            .filter(|_| dns_refresh_interval.is_some() || backends.addresses().len() > 1);
+        if dns_refresh_interval.is_none() && health_check_interval.is_none() {
+            return Ok(None);
+        }
+
+        let address = Address::random_tagged("TcpOutletBackendMonitor");
+        let now = Instant::now();
+        let processor = Self {
+            backends,
+            dns_refresh_interval,
+            health_check_interval,
+            last_resolution: now,
+            last_health_check: now,
+        };
+        ctx.start_processor(address.clone(), processor).await?;
+
+        Ok(Some(address))
+    }
+
+    async fn refresh_addresses(&self) {
+        for target in self.backends.targets() {
+            match resolve_target(&target).await {
+                Ok(resolved) => self.backends.update_addresses(&target, resolved),
+                Err(e) => {
+                    // Keep the previous addresses of that target
+                    warn!("Could not resolve the outlet target {}: {}", target, e);
+                }
+            }
+        }
+    }
+
+    /// Check all the backends at once, so that unreachable backends
+    /// don't delay the checks of the other ones
+    async fn check_health(&self) {
+        let mut checks = JoinSet::new();
+        for address in self.backends.addresses() {
+            checks.spawn(async move {
+                let healthy = matches!(
+                    timeout(HEALTH_CHECK_TIMEOUT, TcpStream::connect(address)).await,
+                    Ok(Ok(_))
+                );
+                (address, healthy)
+            });
+        }
+        while let Some(result) = checks.join_next().await {
+            if let Ok((address, healthy)) = result {
+                debug!("Health check of backend {}: {}", address, healthy);
+                self.backends.set_healthy(&address, healthy);
+            }
+        }
+    }
+
+    /// Duration until the next resolution or health check
+    fn next_deadline(&self) -> Duration {
+        let remaining = |last: Instant, interval: Option<Duration>| {
+            interval.map(|i| i.saturating_sub(last.elapsed()))
+        };
+        [
+            remaining(self.last_resolution, self.dns_refresh_interval),
+            remaining(self.last_health_check, self.health_check_interval),
+        ]
+        .into_iter()
+        .flatten()
+        .min()
+        .unwrap_or_default()
+    }
+}
+
+#[async_trait]
+impl Processor for TcpOutletBackendMonitor {
+    type Context = Context;
+
+    async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        ctx.set_cluster(crate::CLUSTER_NAME).await
+    }
+
+    async fn process(&mut self, _ctx: &mut Context) -> Result<bool> {
+        sleep(self.next_deadline()).await;
+
+        if let Some(interval) = self.dns_refresh_interval {
+            if self.last_resolution.elapsed() >= interval {
+                self.refresh_addresses().await;
+                self.last_resolution = Instant::now();
+            }
+        }
+
+        if let Some(interval) = self.health_check_interval {
+            if self.last_health_check.elapsed() >= interval {
+                self.check_health().await;
+                self.last_health_check = Instant::now();
+            }
+        }
+
+        Ok(true)
+    }
+}
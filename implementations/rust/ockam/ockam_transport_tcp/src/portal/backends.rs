@@ -0,0 +1,357 @@
+use crate::transport::common::parse_socket_addr;
+use crate::LoadBalancing;
+use ockam_core::compat::net::SocketAddr;
+use ockam_core::compat::sync::{Arc, Mutex};
+use ockam_core::Result;
+use ockam_transport_core::TransportError;
+use tracing::{debug, warn};
+
+/// Backends of a TCP outlet, resolved from its `host:port` targets
+///
+/// The backends are shared between the outlet listener, which picks a
+/// backend for every new portal, the portal workers, which keep track of
+/// their connection, and the monitor, which re-resolves the targets and
+/// checks the health of the backends.
+#[derive(Clone)]
+pub(crate) struct OutletBackends {
+    inner: Arc<Mutex<Inner>>,
+}
+
+struct Inner {
+    targets: Vec<Target>,
+    backends: Vec<Backend>,
+    load_balancing: LoadBalancing,
+    // Without health checks a backend could never become healthy again
+    health_checks: bool,
+    next: usize,
+}
+
+/// A `host:port` target with the addresses of its last successful resolution
+struct Target {
+    name: String,
+    addresses: Vec<SocketAddr>,
+}
+
+#[derive(Debug)]
+struct Backend {
+    address: SocketAddr,
+    healthy: bool,
+    connections: usize,
+}
+
+impl Backend {
+    fn new(address: SocketAddr) -> Self {
+        Self {
+            address,
+            healthy: true,
+            connections: 0,
+        }
+    }
+}
+
+impl OutletBackends {
+    /// Resolve the targets, failing if none of them can be resolved.
+    ///
+    /// The targets which cannot be resolved yet are skipped, their addresses are
+    /// added when they are resolved again.
+    pub(crate) async fn resolve(
+        targets: Vec<String>,
+        load_balancing: LoadBalancing,
+        health_checks: bool,
+    ) -> Result<Self> {
+        let mut resolved_targets = vec![];
+        for target in targets {
+            let addresses = match resolve_target(&target).await {
+                Ok(addresses) => addresses,
+                Err(e) => {
+                    warn!("Could not resolve the outlet target {}: {}", target, e);
+                    vec![]
+                }
+            };
+            resolved_targets.push(Target {
+                name: target,
+                addresses,
+            });
+        }
+        let addresses = all_addresses(&resolved_targets);
+        if addresses.is_empty() {
+            return Err(TransportError::InvalidAddress.into());
+        }
+
+        Ok(Self {
+            inner: Arc::new(Mutex::new(Inner {
+                targets: resolved_targets,
+                backends: addresses.into_iter().map(Backend::new).collect(),
+                load_balancing,
+                health_checks,
+                next: 0,
+            })),
+        })
+    }
+
+    /// Targets of the outlet, as they were given
+    pub(crate) fn targets(&self) -> Vec<String> {
+        let inner = self.inner.lock().unwrap();
+        inner.targets.iter().map(|t| t.name.clone()).collect()
+    }
+
+    /// Currently resolved backend addresses
+    pub(crate) fn addresses(&self) -> Vec<SocketAddr> {
+        let inner = self.inner.lock().unwrap();
+        inner.backends.iter().map(|b| b.address).collect()
+    }
+
+    /// Return true if the targets contain host names which can resolve to different addresses over time
+    pub(crate) fn has_host_names(&self) -> bool {
+        self.targets().iter().any(|t| parse_socket_addr(t).is_err())
+    }
+
+    /// Pick the backend of a new connection.
+    ///
+    /// Unhealthy backends are skipped unless no backend is healthy, in which
+    /// case they are all tried in turn.
+    pub(crate) fn select(&self) -> Result<BackendConnection> {
+        let mut inner = self.inner.lock().unwrap();
+        let len = inner.backends.len();
+        if len == 0 {
+            return Err(TransportError::InvalidAddress.into());
+        }
+
+        let all_unhealthy = inner.backends.iter().all(|b| !b.healthy);
+        // Rotate the starting point so that ties are spread over the backends
+        let start = inner.next % len;
+        let candidates = (0..len)
+            .map(|i| (start + i) % len)
+            .filter(|&i| all_unhealthy || inner.backends[i].healthy);
+
+        let index = match inner.load_balancing {
+            LoadBalancing::RoundRobin => candidates.take(1).next(),
+            LoadBalancing::LeastConnections => {
+                candidates.min_by_key(|&i| inner.backends[i].connections)
+            }
+        }
+        .unwrap_or(start);
+        inner.next = index + 1;
+
+        let backend = &mut inner.backends[index];
+        backend.connections += 1;
+        debug!(
+            "Selected backend {} with {} connection(s)",
+            backend.address, backend.connections
+        );
+
+        Ok(BackendConnection {
+            backends: self.clone(),
+            address: backend.address,
+        })
+    }
+
+    /// Replace the addresses of a target and update the backends accordingly,
+    /// keeping the state of the ones which remain
+    pub(crate) fn update_addresses(&self, target: &str, addresses: Vec<SocketAddr>) {
+        let mut inner = self.inner.lock().unwrap();
+        for t in inner.targets.iter_mut().filter(|t| t.name == target) {
+            t.addresses = addresses.clone();
+        }
+        let addresses = all_addresses(&inner.targets);
+        let mut previous = core::mem::take(&mut inner.backends);
+        inner.backends = addresses
+            .into_iter()
+            .map(
+                |address| match previous.iter().position(|b| b.address == address) {
+                    Some(i) => previous.swap_remove(i),
+                    None => {
+                        debug!("Adding backend {}", address);
+                        Backend::new(address)
+                    }
+                },
+            )
+            .collect();
+        for removed in previous {
+            debug!("Removing backend {}", removed.address);
+        }
+    }
+
+    /// Record the result of a connection attempt to a backend
+    pub(crate) fn set_healthy(&self, address: &SocketAddr, healthy: bool) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(backend) = inner.backends.iter_mut().find(|b| &b.address == address) {
+            if backend.healthy != healthy {
+                if healthy {
+                    debug!("Backend {} is healthy again", address);
+                } else {
+                    warn!("Backend {} is unhealthy", address);
+                }
+            }
+            backend.healthy = healthy;
+        }
+    }
+
+    fn release(&self, address: &SocketAddr) {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(backend) = inner.backends.iter_mut().find(|b| &b.address == address) {
+            backend.connections = backend.connections.saturating_sub(1);
+        }
+    }
+}
+
+/// A connection of an outlet to one of its backends.
+///
+/// The connection is counted for load balancing until this value is dropped.
+pub(crate) struct BackendConnection {
+    backends: OutletBackends,
+    address: SocketAddr,
+}
+
+impl BackendConnection {
+    pub(crate) fn address(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Report that the backend could not be reached
+    pub(crate) fn mark_unhealthy(&self) {
+        if self.backends.inner.lock().unwrap().health_checks {
+            self.backends.set_healthy(&self.address, false);
+        }
+    }
+}
+
+impl Drop for BackendConnection {
+    fn drop(&mut self) {
+        self.backends.release(&self.address);
+    }
+}
+
+/// Addresses of all the targets, without duplicates
+fn all_addresses(targets: &[Target]) -> Vec<SocketAddr> {
+    let mut addresses: Vec<SocketAddr> = targets
+        .iter()
+        .flat_map(|t| t.addresses.iter().cloned())
+        .collect();
+    addresses.sort();
+    addresses.dedup();
+    addresses
+}
+
+/// Resolve a `host:port` target to all of its addresses, preferring IPv4 addresses
+pub(crate) async fn resolve_target(target: &str) -> Result<Vec<SocketAddr>> {
+    if let Ok(address) = parse_socket_addr(target) {
+        return Ok(vec![address]);
+    }
+
+    let addresses: Vec<SocketAddr> = tokio::net::lookup_host(target)
+        .await
+        .map_err(|_| TransportError::InvalidAddress)?
+        .collect();
+    let ipv4: Vec<SocketAddr> = addresses.iter().filter(|a| a.is_ipv4()).cloned().collect();
+    let mut addresses = if ipv4.is_empty() { addresses } else { ipv4 };
+    addresses.sort();
+    Ok(addresses)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(port: u16) -> SocketAddr {
+        SocketAddr::from(([127, 0, 0, 1], port))
+    }
+
+    async fn backends(ports: &[u16], load_balancing: LoadBalancing) -> OutletBackends {
+        let targets = ports.iter().map(|p| addr(*p).to_string()).collect();
+        OutletBackends::resolve(targets, load_balancing, true)
+            .await
+            .unwrap()
+    }
+
+    #[tokio::test]
+    async fn round_robin_skips_unhealthy_backends() {
+        let backends = backends(&[1, 2, 3], LoadBalancing::RoundRobin).await;
+
+        let selected: Vec<u16> = (0..4)
+            .map(|_| backends.select().unwrap().address().port())
+            .collect();
+        assert_eq!(selected, vec![1, 2, 3, 1]);
+
+        backends.set_healthy(&addr(2), false);
+        let selected: Vec<u16> = (0..4)
+            .map(|_| backends.select().unwrap().address().port())
+            .collect();
+        assert_eq!(selected, vec![3, 1, 3, 1]);
+
+        // When no backend is healthy they are all tried
+        backends.set_healthy(&addr(1), false);
+        backends.set_healthy(&addr(3), false);
+        let selected: Vec<u16> = (0..3)
+            .map(|_| backends.select().unwrap().address().port())
+            .collect();
+        assert_eq!(selected, vec![2, 3, 1]);
+    }
+
+    #[tokio::test]
+    async fn least_connections_picks_the_least_loaded_backend() {
+        let backends = backends(&[1, 2], LoadBalancing::LeastConnections).await;
+
+        let first = backends.select().unwrap();
+        let second = backends.select().unwrap();
+        assert_eq!(first.address().port(), 1);
+        assert_eq!(second.address().port(), 2);
+
+        // Closing the first connection makes its backend the least loaded
+        drop(first);
+        let third = backends.select().unwrap();
+        assert_eq!(third.address().port(), 1);
+        drop(second);
+        assert_eq!(backends.select().unwrap().address().port(), 2);
+        assert_eq!(backends.select().unwrap().address().port(), 2);
+    }
+
+    #[tokio::test]
+    async fn updated_addresses_keep_their_state() {
+        let backends = backends(&[1, 2], LoadBalancing::RoundRobin).await;
+        backends.set_healthy(&addr(2), false);
+
+        backends.update_addresses(&addr(1).to_string(), vec![addr(3)]);
+        assert_eq!(backends.addresses(), vec![addr(2), addr(3)]);
+
+        let selected: Vec<u16> = (0..2)
+            .map(|_| backends.select().unwrap().address().port())
+            .collect();
+        assert_eq!(selected, vec![3, 3]);
+    }
+
+    #[tokio::test]
+    async fn addresses_of_several_targets_are_deduplicated() {
+        let backends = backends(&[2, 1, 2], LoadBalancing::RoundRobin).await;
+        assert_eq!(backends.addresses(), vec![addr(1), addr(2)]);
+
+        // the addresses of the other targets remain
+        backends.update_addresses(&addr(2).to_string(), vec![addr(3), addr(1)]);
+        assert_eq!(backends.addresses(), vec![addr(1), addr(3)]);
+    }
+
+    #[tokio::test]
+    async fn unresolvable_targets_are_skipped() {
+        let backends = OutletBackends::resolve(
+            vec!["not an address".into(), addr(1).to_string()],
+            Default::default(),
+            true,
+        )
+        .await
+        .unwrap();
+        assert_eq!(backends.addresses(), vec![addr(1)]);
+        assert_eq!(backends.targets().len(), 2);
+
+        backends.update_addresses("not an address", vec![addr(2)]);
+        assert_eq!(backends.addresses(), vec![addr(1), addr(2)]);
+    }
+
+    #[tokio::test]
+    async fn unresolvable_targets_are_rejected() {
+        let res = OutletBackends::resolve(vec![], LoadBalancing::RoundRobin, true).await;
+        assert!(res.is_err());
+        let res =
+            OutletBackends::resolve(vec!["not an address".into()], Default::default(), true).await;
+        assert!(res.is_err());
+    }
+}
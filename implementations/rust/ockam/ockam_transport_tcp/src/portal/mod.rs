@@ -1,4 +1,6 @@
 mod addresses;
+mod backend_monitor;
+mod backends;
 mod inlet_listener;
 pub mod options;
 mod outlet_listener;
@@ -6,6 +8,8 @@ mod portal_message;
 mod portal_receiver;
 mod portal_worker;
 
+pub(crate) use backend_monitor::*;
+pub(crate) use backends::*;
 pub(crate) use inlet_listener::*;
 pub(crate) use outlet_listener::*;
 pub use portal_message::*;
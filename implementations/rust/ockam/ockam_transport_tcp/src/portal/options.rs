@@ -1,12 +1,24 @@
 use crate::portal::addresses::Addresses;
+use core::fmt::{Display, Formatter};
+use core::str::FromStr;
+use core::time::Duration;
+use ockam_core::compat::string::String;
 use ockam_core::compat::sync::Arc;
+use ockam_core::errcode::{Kind, Origin};
 use ockam_core::flow_control::{FlowControlId, FlowControls};
-use ockam_core::{Address, AllowAll, IncomingAccessControl};
+use ockam_core::{Address, AllowAll, Error, IncomingAccessControl};
+
+/// Default interval between two resolutions of the host names of an outlet's targets
+pub const DEFAULT_DNS_REFRESH_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Default interval between two health checks of an outlet's backends
+pub const DEFAULT_HEALTH_CHECK_INTERVAL: Duration = Duration::from_secs(10);
 
 /// Trust Options for an Inlet
 #[derive(Debug)]
 pub struct TcpInletOptions {
     pub(super) incoming_access_control: Arc<dyn IncomingAccessControl>,
+    pub(super) tracing: bool,
 }
 
 impl TcpInletOptions {
@@ -14,9 +26,17 @@ impl TcpInletOptions {
     pub fn new() -> Self {
         Self {
             incoming_access_control: Arc::new(AllowAll),
+            tracing: false,
         }
     }
 
+    /// Start a new distributed trace for every accepted connection. The trace
+    /// context is propagated with the portal messages up to the outlet
+    pub fn with_tracing(mut self) -> Self {
+        self.tracing = true;
+        self
+    }
+
     /// Set Incoming Access Control
     pub fn with_incoming_access_control_impl(
         mut self,
@@ -57,11 +77,49 @@ impl Default for TcpInletOptions {
     }
 }
 
+/// Strategy used by an outlet to pick the backend of each new connection
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum LoadBalancing {
+    /// Use each backend in turn
+    #[default]
+    RoundRobin,
+    /// Use the backend with the fewest open connections
+    LeastConnections,
+}
+
+impl Display for LoadBalancing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            LoadBalancing::RoundRobin => write!(f, "round-robin"),
+            LoadBalancing::LeastConnections => write!(f, "least-connections"),
+        }
+    }
+}
+
+impl FromStr for LoadBalancing {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "round-robin" => Ok(LoadBalancing::RoundRobin),
+            "least-connections" => Ok(LoadBalancing::LeastConnections),
+            _ => Err(Error::new(
+                Origin::Transport,
+                Kind::Invalid,
+                String::from("expected round-robin or least-connections"),
+            )),
+        }
+    }
+}
+
 /// Trust Options for an Outlet
 #[derive(Debug)]
 pub struct TcpOutletOptions {
     pub(super) consumer: Vec<FlowControlId>,
     pub(super) incoming_access_control: Arc<dyn IncomingAccessControl>,
+    pub(crate) load_balancing: LoadBalancing,
+    pub(crate) dns_refresh_interval: Option<Duration>,
+    pub(crate) health_check_interval: Option<Duration>,
 }
 
 impl TcpOutletOptions {
@@ -70,9 +128,33 @@ impl TcpOutletOptions {
         Self {
             consumer: vec![],
             incoming_access_control: Arc::new(AllowAll),
+            load_balancing: LoadBalancing::default(),
+            dns_refresh_interval: Some(DEFAULT_DNS_REFRESH_INTERVAL),
+            health_check_interval: Some(DEFAULT_HEALTH_CHECK_INTERVAL),
         }
     }
 
+    /// Set the strategy used to spread the connections over the backends
+    pub fn with_load_balancing(mut self, load_balancing: LoadBalancing) -> Self {
+        self.load_balancing = load_balancing;
+        self
+    }
+
+    /// Set the interval between two resolutions of the target host names, `None` to resolve
+    /// them only once when the outlet is created
+    pub fn with_dns_refresh_interval(mut self, interval: Option<Duration>) -> Self {
+        self.dns_refresh_interval = interval;
+        self
+    }
+
+    /// Set the interval between two health checks of the backends, `None` to disable them.
+    /// A backend is unhealthy when a TCP connection cannot be established with it, and it
+    /// is not used for new connections while another backend is healthy
+    pub fn with_health_check_interval(mut self, interval: Option<Duration>) -> Self {
+        self.health_check_interval = interval;
+        self
+    }
+
     /// Set Incoming Access Control
     pub fn with_incoming_access_control_impl(
         mut self,
@@ -1,9 +1,9 @@
 use crate::portal::addresses::{Addresses, PortalType};
+use crate::portal::{OutletBackends, TcpOutletBackendMonitor};
 use crate::{portal::TcpPortalWorker, PortalMessage, TcpOutletOptions, TcpRegistry};
 use ockam_core::{async_trait, Address, DenyAll, Result, Routed, Worker};
 use ockam_node::{Context, WorkerBuilder};
 use ockam_transport_core::TransportError;
-use std::net::SocketAddr;
 use tracing::debug;
 
 /// A TCP Portal Outlet listen worker
@@ -13,16 +13,23 @@ use tracing::debug;
 /// [`TcpTransport::create_outlet`](crate::TcpTransport::create_outlet).
 pub(crate) struct TcpOutletListenWorker {
     registry: TcpRegistry,
-    peer: SocketAddr,
+    backends: OutletBackends,
+    monitor: Option<Address>,
     options: TcpOutletOptions,
 }
 
 impl TcpOutletListenWorker {
     /// Create a new `TcpOutletListenWorker`
-    fn new(registry: TcpRegistry, peer: SocketAddr, options: TcpOutletOptions) -> Self {
+    fn new(
+        registry: TcpRegistry,
+        backends: OutletBackends,
+        monitor: Option<Address>,
+        options: TcpOutletOptions,
+    ) -> Self {
         Self {
             registry,
-            peer,
+            backends,
+            monitor,
             options,
         }
     }
@@ -31,14 +38,28 @@ impl TcpOutletListenWorker {
         ctx: &Context,
         registry: TcpRegistry,
         address: Address,
-        peer: SocketAddr,
+        targets: Vec<String>,
         options: TcpOutletOptions,
     ) -> Result<()> {
+        let backends = OutletBackends::resolve(
+            targets,
+            options.load_balancing,
+            options.health_check_interval.is_some(),
+        )
+        .await?;
         let access_control = options.incoming_access_control.clone();
 
         options.setup_flow_control_for_outlet_listener(ctx.flow_controls(), &address);
 
-        let worker = Self::new(registry, peer, options);
+        let monitor = TcpOutletBackendMonitor::start(
+            ctx,
+            backends.clone(),
+            options.dns_refresh_interval,
+            options.health_check_interval,
+        )
+        .await?;
+
+        let worker = Self::new(registry, backends, monitor, options);
         WorkerBuilder::new(worker)
             .with_address(address)
             .with_incoming_access_control_arc(access_control)
@@ -63,6 +84,9 @@ impl Worker for TcpOutletListenWorker {
 
     async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
         self.registry.remove_outlet_listener_worker(&ctx.address());
+        if let Some(monitor) = self.monitor.take() {
+            let _ = ctx.stop_processor(monitor).await;
+        }
 
         Ok(())
     }
@@ -80,6 +104,7 @@ impl Worker for TcpOutletListenWorker {
             return Err(TransportError::Protocol.into());
         }
 
+        let backend = self.backends.select()?;
         let addresses = Addresses::generate(PortalType::Outlet);
 
         self.options
@@ -88,7 +113,7 @@ impl Worker for TcpOutletListenWorker {
         TcpPortalWorker::start_new_outlet(
             ctx,
             self.registry.clone(),
-            self.peer,
+            backend,
             return_route.clone(),
             addresses.clone(),
             self.options.incoming_access_control.clone(),
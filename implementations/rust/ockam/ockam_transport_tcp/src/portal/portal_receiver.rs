@@ -1,7 +1,8 @@
 use crate::portal::portal_message::MAX_PAYLOAD_SIZE;
 use crate::{PortalInternalMessage, PortalMessage, TcpRegistry};
-use ockam_core::compat::vec::Vec;
-use ockam_core::{async_trait, Encodable, LocalMessage, Route, TransportMessage};
+use ockam_core::compat::{sync::Arc, vec::Vec};
+use ockam_core::metrics::Counter;
+use ockam_core::{async_trait, Encodable, LocalMessage, Route, TraceContext, TransportMessage};
 use ockam_core::{route, Address, Processor, Result};
 use ockam_node::Context;
 use tokio::{io::AsyncReadExt, net::tcp::OwnedReadHalf};
@@ -18,6 +19,8 @@ pub(crate) struct TcpPortalRecvProcessor {
     read_half: OwnedReadHalf,
     sender_address: Address,
     onward_route: Route,
+    bytes_in: Arc<Counter>,
+    tracing_context: Option<TraceContext>,
 }
 
 impl TcpPortalRecvProcessor {
@@ -27,6 +30,8 @@ impl TcpPortalRecvProcessor {
         read_half: OwnedReadHalf,
         sender_address: Address,
         onward_route: Route,
+        bytes_in: Arc<Counter>,
+        tracing_context: Option<TraceContext>,
     ) -> Self {
         Self {
             registry,
@@ -34,6 +39,8 @@ impl TcpPortalRecvProcessor {
             read_half,
             sender_address,
             onward_route,
+            bytes_in,
+            tracing_context,
         }
     }
 }
@@ -44,6 +51,8 @@ impl Processor for TcpPortalRecvProcessor {
 
     async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
         self.registry.add_portal_receiver_processor(&ctx.address());
+        // Data read from the connection belongs to the trace of the connection
+        ctx.set_tracing_context(self.tracing_context);
 
         Ok(())
     }
@@ -91,6 +100,8 @@ impl Processor for TcpPortalRecvProcessor {
             return Ok(false);
         }
 
+        self.bytes_in.inc_by(self.buf.len() as u64);
+
         // Loop just in case buf was extended (should not happen though)
         for chunk in self.buf.chunks(MAX_PAYLOAD_SIZE) {
             let msg = TransportMessage::v1(
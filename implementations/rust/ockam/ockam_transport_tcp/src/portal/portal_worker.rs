@@ -1,10 +1,12 @@
 use crate::portal::addresses::{Addresses, PortalType};
+use crate::portal::BackendConnection;
 use crate::{portal::TcpPortalRecvProcessor, PortalInternalMessage, PortalMessage, TcpRegistry};
 use core::time::Duration;
 use ockam_core::compat::{boxed::Box, net::SocketAddr, sync::Arc};
+use ockam_core::metrics::{Counter, PORTAL_BYTES};
 use ockam_core::{
     async_trait, AllowAll, AllowOnwardAddresses, AllowSourceAddress, Decodable, DenyAll,
-    IncomingAccessControl, Mailbox, Mailboxes,
+    IncomingAccessControl, Mailbox, Mailboxes, TraceContext,
 };
 use ockam_core::{Any, Result, Route, Routed, Worker};
 use ockam_node::{Context, ProcessorBuilder, WorkerBuilder};
@@ -12,7 +14,7 @@ use ockam_transport_core::TransportError;
 use tokio::io::AsyncWriteExt;
 use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
-use tracing::{debug, info, trace, warn};
+use tracing::{debug, info, info_span, trace, warn, Span};
 
 /// Enumerate all `TcpPortalWorker` states
 ///
@@ -40,14 +42,19 @@ pub(crate) struct TcpPortalWorker {
     write_half: Option<OwnedWriteHalf>,
     read_half: Option<OwnedReadHalf>,
     peer: SocketAddr,
+    backend: Option<BackendConnection>,
     addresses: Addresses,
     remote_route: Option<Route>,
     is_disconnecting: bool,
     portal_type: PortalType,
+    bytes_out: Arc<Counter>,
+    tracing_context: Option<TraceContext>,
+    connection_span: Option<Span>,
 }
 
 impl TcpPortalWorker {
     /// Start a new `TcpPortalWorker` of type [`TypeName::Inlet`]
+    #[allow(clippy::too_many_arguments)]
     pub(super) async fn start_new_inlet(
         ctx: &Context,
         registry: TcpRegistry,
@@ -56,16 +63,34 @@ impl TcpPortalWorker {
         ping_route: Route,
         addresses: Addresses,
         access_control: Arc<dyn IncomingAccessControl>,
+        tracing: bool,
     ) -> Result<()> {
+        // Every connection accepted by the inlet starts a new trace
+        let (tracing_context, connection_span) = if tracing {
+            let context = TraceContext::new_root();
+            let span = info_span!(
+                "tcp_inlet_connection",
+                peer = %peer,
+                trace_id = %context.trace_id_hex(),
+                span_id = %context.span_id_hex(),
+            );
+            (Some(context), Some(span))
+        } else {
+            (None, None)
+        };
+
         Self::start(
             ctx,
             registry,
             peer,
+            None,
             State::SendPing { ping_route },
             Some(stream),
             addresses,
             PortalType::Inlet,
             access_control,
+            tracing_context,
+            connection_span,
         )
         .await
     }
@@ -74,20 +99,26 @@ impl TcpPortalWorker {
     pub(super) async fn start_new_outlet(
         ctx: &Context,
         registry: TcpRegistry,
-        peer: SocketAddr,
+        backend: BackendConnection,
         pong_route: Route,
         addresses: Addresses,
         access_control: Arc<dyn IncomingAccessControl>,
     ) -> Result<()> {
+        // The outlet joins the trace of the inlet's ping, if any
+        let tracing_context = ctx.tracing_context();
+
         Self::start(
             ctx,
             registry,
-            peer,
+            backend.address(),
+            Some(backend),
             State::SendPong { pong_route },
             None,
             addresses,
             PortalType::Outlet,
             access_control,
+            tracing_context,
+            None,
         )
         .await
     }
@@ -98,11 +129,14 @@ impl TcpPortalWorker {
         ctx: &Context,
         registry: TcpRegistry,
         peer: SocketAddr,
+        backend: Option<BackendConnection>,
         state: State,
         stream: Option<TcpStream>,
         addresses: Addresses,
         portal_type: PortalType,
         access_control: Arc<dyn IncomingAccessControl>,
+        tracing_context: Option<TraceContext>,
+        connection_span: Option<Span>,
     ) -> Result<()> {
         info!(
             "Creating new {:?} at internal: {}, remote: {}",
@@ -125,10 +159,14 @@ impl TcpPortalWorker {
             write_half: tx,
             read_half: rx,
             peer,
+            backend,
             addresses: addresses.clone(),
             remote_route: None,
             is_disconnecting: false,
+            bytes_out: PORTAL_BYTES.with_labels(&[portal_type.str(), "out"]),
             portal_type,
+            tracing_context,
+            connection_span,
         };
 
         let internal_mailbox = Mailbox::new(
@@ -173,6 +211,8 @@ impl TcpPortalWorker {
                 rx,
                 self.addresses.internal.clone(),
                 onward_route,
+                PORTAL_BYTES.with_labels(&[self.portal_type.str(), "in"]),
+                ctx.tracing_context(),
             );
 
             ProcessorBuilder::new(receiver)
@@ -296,9 +336,15 @@ impl TcpPortalWorker {
         .await?;
 
         if self.write_half.is_none() {
-            let stream = TcpStream::connect(self.peer)
-                .await
-                .map_err(TransportError::from)?;
+            let stream = match TcpStream::connect(self.peer).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    if let Some(backend) = &self.backend {
+                        backend.mark_unhealthy();
+                    }
+                    return Err(TransportError::from(e).into());
+                }
+            };
             let (rx, tx) = stream.into_split();
             self.write_half = Some(tx);
             self.read_half = Some(rx);
@@ -324,6 +370,9 @@ impl Worker for TcpPortalWorker {
     type Message = Any;
 
     async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        // The ping or pong is sent within the trace of the connection
+        ctx.set_tracing_context(self.tracing_context);
+
         let state = self.clone_state();
 
         match state {
@@ -345,6 +394,8 @@ impl Worker for TcpPortalWorker {
 
     async fn shutdown(&mut self, _ctx: &mut Self::Context) -> Result<()> {
         self.registry.remove_portal_worker(&self.addresses.remote);
+        // Closing the span ends the connection trace
+        drop(self.connection_span.take());
 
         Ok(())
     }
@@ -424,7 +475,7 @@ impl Worker for TcpPortalWorker {
                         PortalMessage::Payload(payload) => {
                             if let Some(tx) = &mut self.write_half {
                                 match tx.write_all(&payload).await {
-                                    Ok(()) => {}
+                                    Ok(()) => self.bytes_out.inc_by(payload.len() as u64),
                                     Err(err) => {
                                         warn!(
                                             "Failed to send message to peer {} with error: {}",
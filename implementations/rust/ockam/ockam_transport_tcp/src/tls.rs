@@ -0,0 +1,68 @@
+use std::path::PathBuf;
+
+use ockam_core::Result;
+use ockam_transport_core::tls::{self, TlsCertificate};
+use tokio::net::TcpStream;
+use tokio_rustls::TlsAcceptor;
+
+use crate::workers::{split_stream, TcpReadHalf, TcpWriteHalf};
+
+/// TLS settings of an outgoing TCP connection
+#[derive(Clone, Debug, Default)]
+pub(crate) struct TlsClientOptions {
+    pub(crate) ca_certificate: Option<PathBuf>,
+    pub(crate) server_name: Option<String>,
+    pub(crate) client_certificate: Option<TlsCertificate>,
+}
+
+/// TLS settings of a TCP listener
+#[derive(Clone, Debug)]
+pub(crate) struct TlsServerOptions {
+    pub(crate) certificate: TlsCertificate,
+    pub(crate) client_ca_certificate: Option<PathBuf>,
+}
+
+impl TlsClientOptions {
+    /// Run the TLS handshake over a connected stream.
+    ///
+    /// The server name defaults to the host part of the `host:port` peer address.
+    pub(crate) async fn connect(
+        &self,
+        stream: TcpStream,
+        peer: &str,
+    ) -> Result<(TcpReadHalf, TcpWriteHalf)> {
+        let connector = tls::create_connector(
+            self.ca_certificate.as_deref(),
+            self.client_certificate.as_ref(),
+        )?;
+        let host = match &self.server_name {
+            Some(server_name) => server_name.as_str(),
+            None => tls::host(peer),
+        };
+        let server_name = tls::server_name(host)?;
+
+        let stream = connector
+            .connect(server_name, stream)
+            .await
+            .map_err(|e| tls::tls_error(format!("TLS handshake with {peer} failed: {e}")))?;
+        Ok(split_stream(stream))
+    }
+}
+
+impl TlsServerOptions {
+    /// Create a TLS acceptor serving the certificate of the listener and,
+    /// if a client CA certificate is set, requiring the clients to present a
+    /// certificate signed by it
+    pub(crate) fn create_acceptor(&self) -> Result<TlsAcceptor> {
+        tls::create_acceptor(&self.certificate, self.client_ca_certificate.as_deref())
+    }
+}
+
+/// Run the server side of the TLS handshake over an accepted stream
+pub(crate) async fn accept(
+    acceptor: &TlsAcceptor,
+    stream: TcpStream,
+) -> Result<(TcpReadHalf, TcpWriteHalf)> {
+    let stream = acceptor.accept(stream).await.map_err(tls::tls_error)?;
+    Ok(split_stream(stream))
+}
@@ -150,7 +150,7 @@ pub fn resolve_peer(peer: String) -> Result<SocketAddr> {
     Err(TransportError::InvalidAddress.into())
 }
 
-pub(super) fn parse_socket_addr(s: &str) -> Result<SocketAddr> {
+pub(crate) fn parse_socket_addr(s: &str) -> Result<SocketAddr> {
     Ok(s.parse().map_err(|_| TransportError::InvalidAddress)?)
 }
 
@@ -1,11 +1,14 @@
 use crate::transport::common::{resolve_peer, TcpConnection};
-use crate::workers::{Addresses, TcpRecvProcessor, TcpSendWorker};
+use crate::workers::{split_tcp_stream, Addresses, TcpRecvProcessor, TcpSendWorker};
 use crate::{TcpConnectionMode, TcpConnectionOptions, TcpTransport};
 use ockam_core::{Address, Result};
 
 impl TcpTransport {
     /// Establish an outgoing TCP connection.
     ///
+    /// The connection is wrapped in TLS when it is requested by the `options`,
+    /// see [`TcpConnectionOptions::with_tls`].
+    ///
     /// ```rust
     /// use ockam_transport_tcp::{TcpConnectionOptions, TcpListenerOptions, TcpTransport};
     /// # use ockam_node::Context;
@@ -21,10 +24,15 @@ impl TcpTransport {
         peer: impl Into<String>,
         options: TcpConnectionOptions,
     ) -> Result<TcpConnection> {
+        let peer = peer.into();
         // Resolve peer address
-        let socket = resolve_peer(peer.into())?;
+        let socket = resolve_peer(peer.clone())?;
 
-        let (read_half, write_half) = TcpSendWorker::connect(socket).await?;
+        let stream = TcpSendWorker::connect(socket).await?;
+        let (read_half, write_half) = match &options.tls {
+            Some(tls) => tls.connect(stream, &peer).await?,
+            None => split_tcp_stream(stream),
+        };
 
         let mode = TcpConnectionMode::Outgoing;
         let addresses = Addresses::generate(mode);
@@ -1,5 +1,5 @@
 use crate::portal::TcpInletListenProcessor;
-use crate::transport::common::{parse_socket_addr, resolve_peer};
+use crate::transport::common::parse_socket_addr;
 use crate::{portal::TcpOutletListenWorker, TcpInletOptions, TcpOutletOptions, TcpTransport};
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::{Address, Result, Route};
@@ -82,18 +82,8 @@ impl TcpTransport {
         peer: impl Into<String>,
         options: TcpOutletOptions,
     ) -> Result<()> {
-        // Resolve peer address
-        let peer_addr = resolve_peer(peer.into())?;
-        TcpOutletListenWorker::start(
-            &self.ctx,
-            self.registry.clone(),
-            address.into(),
-            peer_addr,
-            options,
-        )
-        .await?;
-
-        Ok(())
+        self.create_outlet_with_targets(address, vec![peer.into()], options)
+            .await
     }
 
     /// Create Tcp Outlet Listener at address, that connects to peer using Tcp
@@ -103,10 +93,44 @@ impl TcpTransport {
         peer: SocketAddr,
         options: TcpOutletOptions,
     ) -> Result<()> {
-        TcpOutletListenWorker::start(&self.ctx, self.registry.clone(), address, peer, options)
-            .await?;
+        self.create_outlet_with_targets(address, vec![peer.to_string()], options)
+            .await
+    }
 
-        Ok(())
+    /// Create Tcp Outlet Listener at address, spreading the connections over several
+    /// `host:port` targets according to the [`LoadBalancing`](crate::LoadBalancing) strategy of
+    /// the options.
+    ///
+    /// Each target can resolve to several addresses, which are all used as backends. Host names
+    /// are resolved again periodically, and backends refusing connections are skipped until they
+    /// pass a health check again, see [`TcpOutletOptions`].
+    ///
+    /// ```rust
+    /// use ockam_transport_tcp::{LoadBalancing, TcpOutletOptions, TcpTransport};
+    /// # use ockam_node::Context;
+    /// # use ockam_core::{AllowAll, Result};
+    /// # async fn test(ctx: Context) -> Result<()> {
+    /// let tcp = TcpTransport::create(&ctx).await?;
+    /// let targets = vec!["localhost:9000".to_string(), "localhost:9001".to_string()];
+    /// let options = TcpOutletOptions::new().with_load_balancing(LoadBalancing::LeastConnections);
+    /// tcp.create_outlet_with_targets("outlet", targets, options).await?;
+    /// # tcp.stop_outlet("outlet").await?;
+    /// # Ok(()) }
+    /// ```
+    pub async fn create_outlet_with_targets(
+        &self,
+        address: impl Into<Address>,
+        targets: Vec<String>,
+        options: TcpOutletOptions,
+    ) -> Result<()> {
+        TcpOutletListenWorker::start(
+            &self.ctx,
+            self.registry.clone(),
+            address.into(),
+            targets,
+            options,
+        )
+        .await
     }
 
     /// Stop outlet at addr
@@ -1,22 +1,37 @@
-use crate::workers::{Addresses, TcpRecvProcessor};
+use crate::tls;
+use crate::workers::{split_tcp_stream, Addresses, TcpReadHalf, TcpRecvProcessor, TcpWriteHalf};
 use crate::{TcpConnectionMode, TcpListenerInfo, TcpListenerOptions, TcpRegistry, TcpSendWorker};
+use ockam_core::compat::sync::Arc;
 use ockam_core::{async_trait, compat::net::SocketAddr};
-use ockam_core::{Address, Processor, Result};
+use ockam_core::{Address, DenyAll, Processor, Result};
 use ockam_node::Context;
+use ockam_transport_core::tls::{tls_error, TLS_HANDSHAKE_TIMEOUT};
 use ockam_transport_core::TransportError;
-use tokio::net::TcpListener;
-use tracing::debug;
+use tokio::net::{TcpListener, TcpStream};
+use tokio::time::timeout;
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, warn};
 
 /// A TCP Listen processor
 ///
 /// TCP listen processors are created by `TcpTransport`
 /// after a call is made to
 /// [`TcpTransport::listen`](crate::TcpTransport::listen).
+///
+/// The TLS handshakes of the incoming connections run in a separate task so
+/// that a slow or silent peer does not block the other connections.
 pub(crate) struct TcpListenProcessor {
-    registry: TcpRegistry,
     inner: TcpListener,
     socket_address: SocketAddr,
-    options: TcpListenerOptions,
+    handler: ConnectionHandler,
+}
+
+/// State shared with the tasks accepting incoming TLS connections
+#[derive(Clone)]
+struct ConnectionHandler {
+    registry: TcpRegistry,
+    options: Arc<TcpListenerOptions>,
+    tls_acceptor: Option<TlsAcceptor>,
 }
 
 impl TcpListenProcessor {
@@ -26,6 +41,12 @@ impl TcpListenProcessor {
         addr: SocketAddr,
         options: TcpListenerOptions,
     ) -> Result<(SocketAddr, Address)> {
+        // Fail early if the TLS certificate or key cannot be loaded
+        let tls_acceptor = match options.tls() {
+            Some(tls) => Some(tls.create_acceptor()?),
+            None => None,
+        };
+
         debug!("Binding TcpListener to {}", addr);
         let inner = TcpListener::bind(addr)
             .await
@@ -36,10 +57,13 @@ impl TcpListenProcessor {
         options.setup_flow_control_for_listener(ctx.flow_controls(), &address);
 
         let processor = Self {
-            registry,
             inner,
             socket_address: saddr,
-            options,
+            handler: ConnectionHandler {
+                registry,
+                options: Arc::new(options),
+                tls_acceptor,
+            },
         };
 
         ctx.start_processor(address.clone(), processor).await?;
@@ -48,35 +72,31 @@ impl TcpListenProcessor {
     }
 }
 
-#[async_trait]
-impl Processor for TcpListenProcessor {
-    type Context = Context;
-
-    async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
-        ctx.set_cluster(crate::CLUSTER_NAME).await?;
-
-        self.registry.add_listener_processor(TcpListenerInfo::new(
-            ctx.address(),
-            self.socket_address,
-            self.options.flow_control_id.clone(),
-        ));
-
-        Ok(())
-    }
-
-    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
-        self.registry.remove_listener_processor(&ctx.address());
-
-        Ok(())
+impl ConnectionHandler {
+    /// Run the TLS handshake with a new peer and start the workers of its connection
+    async fn accept_tls(
+        self,
+        ctx: Context,
+        acceptor: TlsAcceptor,
+        stream: TcpStream,
+        peer: SocketAddr,
+    ) -> Result<()> {
+        let (read_half, write_half) =
+            timeout(TLS_HANDSHAKE_TIMEOUT, tls::accept(&acceptor, stream))
+                .await
+                .map_err(|_| tls_error("TLS handshake timed out"))??;
+
+        self.start_workers(&ctx, read_half, write_half, peer).await
     }
 
-    async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
-        debug!("Waiting for incoming TCP connection...");
-
-        // Wait for an incoming connection
-        let (stream, peer) = self.inner.accept().await.map_err(TransportError::from)?;
-        debug!("TCP connection accepted");
-
+    /// Start the sender worker and receiver processor of an accepted connection
+    async fn start_workers(
+        &self,
+        ctx: &Context,
+        read_half: TcpReadHalf,
+        write_half: TcpWriteHalf,
+        peer: SocketAddr,
+    ) -> Result<()> {
         let mode = TcpConnectionMode::Incoming;
         let addresses = Addresses::generate(mode);
 
@@ -87,8 +107,6 @@ impl Processor for TcpListenProcessor {
             .options
             .create_access_control(ctx.flow_controls(), receiver_flow_control_id.clone());
 
-        let (read_half, write_half) = stream.into_split();
-
         // Worker to receive messages from the Node and send them over the wire
         TcpSendWorker::start(
             ctx,
@@ -113,7 +131,68 @@ impl Processor for TcpListenProcessor {
             &receiver_flow_control_id,
             access_control.receiver_outgoing_access_control,
         )
-        .await?;
+        .await
+    }
+}
+
+#[async_trait]
+impl Processor for TcpListenProcessor {
+    type Context = Context;
+
+    async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
+        ctx.set_cluster(crate::CLUSTER_NAME).await?;
+
+        self.handler
+            .registry
+            .add_listener_processor(TcpListenerInfo::new(
+                ctx.address(),
+                self.socket_address,
+                self.handler.options.flow_control_id.clone(),
+            ));
+
+        Ok(())
+    }
+
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.handler
+            .registry
+            .remove_listener_processor(&ctx.address());
+
+        Ok(())
+    }
+
+    async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
+        debug!("Waiting for incoming TCP connection...");
+
+        // Wait for an incoming connection
+        let (stream, peer) = self.inner.accept().await.map_err(TransportError::from)?;
+        debug!("TCP connection accepted");
+
+        let acceptor = match &self.handler.tls_acceptor {
+            Some(acceptor) => acceptor.clone(),
+            None => {
+                let (read_half, write_half) = split_tcp_stream(stream);
+                self.handler
+                    .start_workers(ctx, read_half, write_half, peer)
+                    .await?;
+                return Ok(true);
+            }
+        };
+
+        // A failed TLS handshake only affects that peer, keep on listening
+        let handler = self.handler.clone();
+        let ctx = ctx
+            .new_detached(
+                Address::random_tagged("TcpListenProcessor.accept"),
+                DenyAll,
+                DenyAll,
+            )
+            .await?;
+        tokio::spawn(async move {
+            if let Err(e) = handler.accept_tls(ctx, acceptor, stream, peer).await {
+                warn!("TLS handshake with {} failed: {}", peer, e);
+            }
+        });
 
         Ok(true)
     }
@@ -1,4 +1,4 @@
-use crate::workers::Addresses;
+use crate::workers::{Addresses, TcpReadHalf};
 use crate::{TcpConnectionMode, TcpReceiverInfo, TcpRegistry, TcpSendWorkerMsg};
 use ockam_core::compat::net::SocketAddr;
 use ockam_core::compat::sync::Arc;
@@ -9,7 +9,7 @@ use ockam_core::{
 use ockam_core::{Decodable, LocalMessage, Processor, Result, TransportMessage};
 use ockam_node::{Context, ProcessorBuilder};
 use ockam_transport_core::TransportError;
-use tokio::{io::AsyncReadExt, net::tcp::OwnedReadHalf};
+use tokio::io::AsyncReadExt;
 use tracing::{error, info, trace};
 
 /// A TCP receiving message processor
@@ -22,7 +22,7 @@ use tracing::{error, info, trace};
 /// the node message system.
 pub(crate) struct TcpRecvProcessor {
     registry: TcpRegistry,
-    read_half: OwnedReadHalf,
+    read_half: TcpReadHalf,
     socket_address: SocketAddr,
     addresses: Addresses,
     mode: TcpConnectionMode,
@@ -33,7 +33,7 @@ impl TcpRecvProcessor {
     /// Create a new `TcpRecvProcessor`
     fn new(
         registry: TcpRegistry,
-        read_half: OwnedReadHalf,
+        read_half: TcpReadHalf,
         socket_address: SocketAddr,
         addresses: Addresses,
         mode: TcpConnectionMode,
@@ -53,7 +53,7 @@ impl TcpRecvProcessor {
     pub async fn start(
         ctx: &Context,
         registry: TcpRegistry,
-        read_half: OwnedReadHalf,
+        read_half: TcpReadHalf,
         addresses: &Addresses,
         socket_address: SocketAddr,
         mode: TcpConnectionMode,
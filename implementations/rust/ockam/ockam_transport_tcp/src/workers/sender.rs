@@ -1,8 +1,9 @@
-use crate::workers::Addresses;
+use crate::workers::{Addresses, TcpWriteHalf};
 use crate::{TcpConnectionMode, TcpRegistry, TcpSenderInfo};
 use cfg_if::cfg_if;
 use core::time::Duration;
 use ockam_core::flow_control::FlowControlId;
+use ockam_core::metrics::{TCP_ACTIVE_CONNECTIONS, TCP_CONNECTIONS};
 use ockam_core::{
     async_trait,
     compat::{net::SocketAddr, sync::Arc},
@@ -17,7 +18,6 @@ use ockam_transport_core::TransportError;
 use serde::{Deserialize, Serialize};
 use socket2::{SockRef, TcpKeepalive};
 use tokio::io::AsyncWriteExt;
-use tokio::net::tcp::{OwnedReadHalf, OwnedWriteHalf};
 use tokio::net::TcpStream;
 use tracing::{debug, info, trace, warn};
 
@@ -36,7 +36,7 @@ pub(crate) enum TcpSendWorkerMsg {
 /// to dispatch to a remote peer.
 pub(crate) struct TcpSendWorker {
     registry: TcpRegistry,
-    write_half: OwnedWriteHalf,
+    write_half: TcpWriteHalf,
     socket_address: SocketAddr,
     addresses: Addresses,
     mode: TcpConnectionMode,
@@ -48,7 +48,7 @@ impl TcpSendWorker {
     /// Create a new `TcpSendWorker`
     fn new(
         registry: TcpRegistry,
-        write_half: OwnedWriteHalf,
+        write_half: TcpWriteHalf,
         socket_address: SocketAddr,
         addresses: Addresses,
         mode: TcpConnectionMode,
@@ -73,7 +73,7 @@ impl TcpSendWorker {
     pub(crate) async fn start(
         ctx: &Context,
         registry: TcpRegistry,
-        write_half: OwnedWriteHalf,
+        write_half: TcpWriteHalf,
         addresses: &Addresses,
         socket_address: SocketAddr,
         mode: TcpConnectionMode,
@@ -119,9 +119,7 @@ impl TcpSendWorker {
         Ok(())
     }
 
-    pub(crate) async fn connect(
-        socket_address: SocketAddr,
-    ) -> Result<(OwnedReadHalf, OwnedWriteHalf)> {
+    pub(crate) async fn connect(socket_address: SocketAddr) -> Result<TcpStream> {
         debug!(addr = %socket_address, "Connecting");
         let connection = match TcpStream::connect(socket_address).await {
             Ok(c) => {
@@ -147,7 +145,7 @@ impl TcpSendWorker {
         let socket = SockRef::from(&connection);
         socket.set_tcp_keepalive(&keepalive).unwrap();
 
-        Ok(connection.into_split())
+        Ok(connection)
     }
 }
 
@@ -167,12 +165,19 @@ impl Worker for TcpSendWorker {
             self.receiver_flow_control_id.clone(),
         ));
 
+        let mode = self.mode.to_string();
+        TCP_CONNECTIONS.with_labels(&[&mode]).inc();
+        TCP_ACTIVE_CONNECTIONS.with_labels(&[&mode]).inc();
+
         Ok(())
     }
 
     async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
         self.registry
             .remove_sender_worker(self.addresses.sender_address());
+        TCP_ACTIVE_CONNECTIONS
+            .with_labels(&[&self.mode.to_string()])
+            .dec();
 
         if self.rx_should_be_stopped {
             let _ = ctx
@@ -216,7 +221,12 @@ impl Worker for TcpSendWorker {
             // Create a message buffer with prepended length
             let msg = prepare_message(msg)?;
 
-            if self.write_half.write_all(msg.as_slice()).await.is_err() {
+            // Flush explicitly since a TLS stream buffers the written records
+            let written = match self.write_half.write_all(msg.as_slice()).await {
+                Ok(()) => self.write_half.flush().await,
+                Err(e) => Err(e),
+            };
+            if written.is_err() {
                 warn!("Failed to send message to peer {}", self.socket_address);
                 self.stop(ctx).await?;
 
@@ -238,3 +238,101 @@ async fn portal__tcp_connection_with_invalid_message_flow__should_not_succeed(
 
     Ok(())
 }
+
+/// Accept `count` portal connections and echo a payload on each of them
+fn spawn_echo_backend(listener: TcpListener, count: usize) -> tokio::task::JoinHandle<()> {
+    tokio::spawn(async move {
+        let mut echoed = 0;
+        while echoed < count {
+            let (mut stream, _) = listener.accept().await.unwrap();
+            let mut payload = [0u8; LENGTH];
+            // Health checks close their connection without sending anything
+            if stream.read_exact(&mut payload).await.is_ok() {
+                write_binary(&mut stream, payload).await;
+                echoed += 1;
+            }
+        }
+    })
+}
+
+async fn echo_through(inlet_addr: &str) {
+    let payload = generate_binary();
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    write_binary(&mut stream, payload).await;
+    read_assert_binary(&mut stream, payload).await;
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__outlet_with_several_targets__should_use_all_of_them(
+    ctx: &mut Context,
+) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+
+    let listener1 = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let listener2 = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    // Host names are resolved too
+    let targets = vec![
+        listener1.local_addr().unwrap().to_string(),
+        format!("localhost:{}", listener2.local_addr().unwrap().port()),
+    ];
+    tcp.create_outlet_with_targets("outlet", targets, TcpOutletOptions::new())
+        .await?;
+    let (inlet_addr, _) = tcp
+        .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+        .await?;
+    let inlet_addr = inlet_addr.to_string();
+
+    // Connections are spread round-robin, each backend gets two of them
+    let backend1 = spawn_echo_backend(listener1, 2);
+    let backend2 = spawn_echo_backend(listener2, 2);
+    for _ in 0..4 {
+        echo_through(&inlet_addr).await;
+    }
+    assert!(backend1.await.is_ok());
+    assert!(backend2.await.is_ok());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test(timeout = 5000)]
+async fn portal__outlet_with_unhealthy_target__should_use_healthy_ones(
+    ctx: &mut Context,
+) -> Result<()> {
+    let tcp = TcpTransport::create(ctx).await?;
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let closed_address = {
+        let closed = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        closed.local_addr().unwrap().to_string()
+    };
+    let targets = vec![closed_address, listener.local_addr().unwrap().to_string()];
+    let options =
+        TcpOutletOptions::new().with_health_check_interval(Some(Duration::from_millis(100)));
+    tcp.create_outlet_with_targets("outlet", targets, options)
+        .await?;
+    let (inlet_addr, _) = tcp
+        .create_inlet("127.0.0.1:0", route!["outlet"], TcpInletOptions::new())
+        .await?;
+    let inlet_addr = inlet_addr.to_string();
+
+    // Wait for the closed target to fail its health check
+    tokio::time::sleep(Duration::from_millis(500)).await;
+
+    let backend = spawn_echo_backend(listener, 3);
+    for _ in 0..3 {
+        echo_through(&inlet_addr).await;
+    }
+    assert!(backend.await.is_ok());
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
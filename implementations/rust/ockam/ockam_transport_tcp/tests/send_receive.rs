@@ -1,5 +1,5 @@
 use ockam_core::compat::rand::{self, Rng};
-use ockam_core::{route, Result, Routed, Worker};
+use ockam_core::{route, AllowAll, Result, Routed, TraceContext, Worker};
 use ockam_node::Context;
 use ockam_transport_tcp::{TcpConnectionOptions, TcpListenerOptions, TcpTransport};
 
@@ -52,3 +52,40 @@ async fn send_receive(ctx: &mut Context) -> Result<()> {
 
     Ok(())
 }
+
+#[ockam_macros::test]
+async fn send_receive_with_trace_context(ctx: &mut Context) -> Result<()> {
+    let options = TcpListenerOptions::new();
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+
+    let connection = transport
+        .connect(listener.socket_string(), TcpConnectionOptions::new())
+        .await?;
+    let addr = connection.sender_address().clone();
+    ctx.flow_controls()
+        .add_consumer("client", connection.flow_control_id());
+
+    let mut child_ctx = ctx.new_detached("client", AllowAll, AllowAll).await?;
+    let root = TraceContext::new_root();
+    child_ctx.set_tracing_context(Some(root));
+    child_ctx
+        .send(route![addr, "echoer"], "Hello".to_string())
+        .await?;
+
+    // The trace context crossed the connection twice
+    let reply = child_ctx.receive::<String>().await?;
+    let received = reply.local_message().transport().tracing_context.unwrap();
+    assert_eq!(received.trace_id(), root.trace_id());
+    assert_eq!(reply.body(), "Hello");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
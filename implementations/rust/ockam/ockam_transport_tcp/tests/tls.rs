@@ -0,0 +1,216 @@
+use core::time::Duration;
+use std::path::PathBuf;
+
+use ockam_core::{route, Result, Routed, Worker};
+use ockam_node::{Context, MessageSendReceiveOptions};
+use ockam_transport_tcp::{TcpConnectionOptions, TcpListenerOptions, TcpTransport};
+
+pub struct Echoer;
+
+#[ockam_core::worker]
+impl Worker for Echoer {
+    type Message = String;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<String>) -> Result<()> {
+        ctx.send(msg.return_route(), msg.body()).await
+    }
+}
+
+fn fixture(name: &str) -> PathBuf {
+    PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests")
+        .join("tls")
+        .join(name)
+}
+
+fn server_options() -> TcpListenerOptions {
+    TcpListenerOptions::new().with_tls(fixture("server.pem"), fixture("server.key"))
+}
+
+async fn start_echoer(ctx: &Context, options: &TcpListenerOptions) -> Result<()> {
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    ctx.start_worker("echoer", Echoer).await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tls__send_receive__should_work(ctx: &mut Context) -> Result<()> {
+    let options = server_options();
+    start_echoer(ctx, &options).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+
+    // The certificate of the server is valid for "localhost"
+    let peer = format!("localhost:{}", listener.socket_address().port());
+    let connection = transport
+        .connect(
+            peer,
+            TcpConnectionOptions::new().with_tls_ca_certificate(fixture("ca.pem")),
+        )
+        .await?;
+
+    let msg = "Hello over TLS".to_string();
+    let reply: String = ctx
+        .send_and_receive(route![connection.clone(), "echoer"], msg.clone())
+        .await?;
+    assert_eq!(reply, msg);
+
+    // An IP address is also part of the certificate
+    let connection = transport
+        .connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new().with_tls_ca_certificate(fixture("ca.pem")),
+        )
+        .await?;
+    let reply: String = ctx
+        .send_and_receive(route![connection, "echoer"], msg.clone())
+        .await?;
+    assert_eq!(reply, msg);
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tls__silent_peer__should_not_block_the_listener(ctx: &mut Context) -> Result<()> {
+    let options = server_options();
+    start_echoer(ctx, &options).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+
+    // This peer opens a TCP connection but never starts the TLS handshake
+    let _silent_peer = tokio::net::TcpStream::connect(listener.socket_address())
+        .await
+        .unwrap();
+
+    let connection = transport
+        .connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new().with_tls_ca_certificate(fixture("ca.pem")),
+        )
+        .await?;
+    let msg = "Hello over TLS".to_string();
+    let reply: String = ctx
+        .send_and_receive(route![connection, "echoer"], msg.clone())
+        .await?;
+    assert_eq!(reply, msg);
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tls__server_name__should_be_used_for_verification(ctx: &mut Context) -> Result<()> {
+    let options = server_options();
+    start_echoer(ctx, &options).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+
+    let connection = transport
+        .connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new()
+                .with_tls_ca_certificate(fixture("ca.pem"))
+                .with_tls_server_name("ockam.test"),
+        )
+        .await?;
+    let reply: String = ctx
+        .send_and_receive(route![connection, "echoer"], "Hello".to_string())
+        .await?;
+    assert_eq!(reply, "Hello");
+
+    let res = transport
+        .connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new()
+                .with_tls_ca_certificate(fixture("ca.pem"))
+                .with_tls_server_name("unknown.test"),
+        )
+        .await;
+    assert!(res.is_err());
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tls__untrusted_server__should_fail(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let listener = transport.listen("127.0.0.1:0", server_options()).await?;
+
+    // The CA of the server is not part of the platform roots
+    let res = transport
+        .connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new().with_tls(),
+        )
+        .await;
+    assert!(res.is_err());
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tls__client_certificate__should_be_required(ctx: &mut Context) -> Result<()> {
+    let options = server_options().with_tls_client_ca_certificate(fixture("ca.pem"));
+    start_echoer(ctx, &options).await?;
+
+    let transport = TcpTransport::create(ctx).await?;
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+
+    let connection = transport
+        .connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new()
+                .with_tls_ca_certificate(fixture("ca.pem"))
+                .with_tls_client_certificate(fixture("client.pem"), fixture("client.key")),
+        )
+        .await?;
+    let reply: String = ctx
+        .send_and_receive(route![connection, "echoer"], "Hello".to_string())
+        .await?;
+    assert_eq!(reply, "Hello");
+
+    // With TLS 1.3 the server rejects the missing certificate after the client
+    // has completed its side of the handshake, so the first message never arrives
+    let connection = transport
+        .connect(
+            listener.socket_string(),
+            TcpConnectionOptions::new().with_tls_ca_certificate(fixture("ca.pem")),
+        )
+        .await;
+    if let Ok(connection) = connection {
+        let reply: Result<String> = ctx
+            .send_and_receive_extended(
+                route![connection, "echoer"],
+                "Hello".to_string(),
+                MessageSendReceiveOptions::new().with_timeout(Duration::from_secs(1)),
+            )
+            .await
+            .map(|m: Routed<String>| m.body());
+        assert!(reply.is_err());
+    }
+
+    ctx.stop().await
+}
+
+#[allow(non_snake_case)]
+#[ockam_macros::test]
+async fn tls__missing_certificate__should_fail_to_listen(ctx: &mut Context) -> Result<()> {
+    let transport = TcpTransport::create(ctx).await?;
+    let res = transport
+        .listen(
+            "127.0.0.1:0",
+            TcpListenerOptions::new().with_tls(fixture("missing.pem"), fixture("server.key")),
+        )
+        .await;
+    assert!(res.is_err());
+
+    ctx.stop().await
+}
@@ -10,11 +10,13 @@
 use ockam_core::TransportType;
 
 pub use hole_puncher::{PunchError, UdpHolePuncher};
+pub use reliable_channel::{ReliableChannelError, UdpReliableChannel};
 pub use rendezvous_service::UdpRendezvousService;
 pub use transport::UdpTransport;
 pub use transport::UdpTransportExtension;
 
 mod hole_puncher;
+mod reliable_channel;
 mod rendezvous_service;
 mod router;
 mod transport;
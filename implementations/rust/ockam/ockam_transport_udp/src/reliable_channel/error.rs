@@ -0,0 +1,45 @@
+use ockam_core::{
+    errcode::{Kind, Origin},
+    Error,
+};
+
+/// UDP reliable channel errors
+#[derive(Clone, Copy, Debug)]
+pub enum ReliableChannelError {
+    /// A message could not be decoded
+    InvalidMessage,
+
+    /// A message has more segments than the send buffer can hold
+    SendBufferFull,
+
+    /// Segments have been retransmitted too many times without being acknowledged
+    PeerUnreachable,
+
+    /// The channel has been closed
+    ChannelClosed,
+
+    /// Internal error, possibly a bug
+    Internal,
+}
+
+impl ockam_core::compat::error::Error for ReliableChannelError {}
+impl core::fmt::Display for ReliableChannelError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        core::fmt::Debug::fmt(self, f)
+    }
+}
+
+impl From<ReliableChannelError> for Error {
+    #[track_caller]
+    fn from(err: ReliableChannelError) -> Self {
+        use ReliableChannelError::*;
+        let kind = match err {
+            InvalidMessage => Kind::Invalid,
+            SendBufferFull => Kind::ResourceExhausted,
+            PeerUnreachable => Kind::Timeout,
+            ChannelClosed => Kind::Cancelled,
+            Internal => Kind::Internal,
+        };
+        Error::new(Origin::Transport, kind, err)
+    }
+}
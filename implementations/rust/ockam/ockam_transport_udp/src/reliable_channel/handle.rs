@@ -0,0 +1,111 @@
+use crate::reliable_channel::worker::UdpReliableChannelWorker;
+use ockam_core::flow_control::{FlowControlId, FlowControls};
+use ockam_core::{Address, Result, Route};
+use ockam_node::Context;
+
+/// High level management interface for UDP reliable channels
+///
+/// UDP datagrams can be lost, duplicated or re-ordered. A reliable channel provides a reliable,
+/// ordered delivery of messages on top of a UDP route, for example a route going through a
+/// [`UdpHolePuncher`](crate::UdpHolePuncher). This makes it possible to run protocols
+/// which expect a reliable transport, like TCP portals, directly between two NATed nodes.
+///
+/// Messages are split into numbered segments which are acknowledged by the peer and
+/// retransmitted until they are. The peer puts the segments back in order, reassembles them
+/// and forwards the messages to the rest of their onward route. A side of the channel stops
+/// when its segments are not acknowledged anymore, after about 10 seconds.
+///
+/// The segments which are not acknowledged yet are kept in a bounded send buffer. When it is full,
+/// local senders are blocked until the peer acknowledges some segments, so that a TCP portal
+/// stops reading from its connection instead of losing data.
+///
+/// A channel is made of two workers, one on each node, each one with a well-known name and
+/// a route to the other one.
+///
+/// Messages received from the peer are only delivered to the consumers of the channel
+/// [`FlowControlId`].
+///
+/// # Example
+///
+/// ```rust
+/// # use {ockam_node::Context, ockam_core::{Result, route}};
+/// # use ockam::{TcpInletOptions, TcpTransport};
+/// # async fn test(ctx: &mut Context, tcp: TcpTransport) -> Result<()> {
+/// use ockam_transport_udp::{UdpHolePuncher, UdpReliableChannel, UdpTransport, UDP};
+///
+/// UdpTransport::create(ctx).await?;
+///
+/// // Create a NAT hole from us 'alice' to them 'bob'
+/// let rendezvous_route = route![(UDP, "192.168.1.10:4000"), "zurg"];
+/// let mut puncher = UdpHolePuncher::create(ctx, "alice", "bob", rendezvous_route).await?;
+/// puncher.wait_for_hole_open().await?;
+///
+/// // Create our side of a reliable channel with 'bob', who creates a channel
+/// // named "bob_channel" with the route `route![bob_puncher.address(), "alice_channel"]`
+/// let channel = UdpReliableChannel::create(
+///     ctx,
+///     "alice_channel",
+///     route![puncher.address(), "bob_channel"],
+/// )
+/// .await?;
+///
+/// // Create a TCP inlet for an outlet named "outlet" on the other node.
+/// // That outlet must be a consumer of the flow control id of the other side of the channel
+/// tcp.create_inlet(
+///     "127.0.0.1:5000",
+///     route![channel.address(), "outlet"],
+///     TcpInletOptions::new(),
+/// )
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+///
+pub struct UdpReliableChannel {
+    main_addr: Address,
+    local_addr: Address,
+    flow_control_id: FlowControlId,
+}
+
+impl UdpReliableChannel {
+    /// Create one side of a reliable channel
+    ///
+    /// - `name` is the address receiving messages from the other side
+    /// - `peer_route` is the route to the other side of the channel
+    pub async fn create(
+        ctx: &Context,
+        name: impl Into<Address>,
+        peer_route: impl Into<Route>,
+    ) -> Result<UdpReliableChannel> {
+        let main_addr = name.into();
+        let flow_control_id = FlowControls::generate_flow_control_id();
+        let local_addr = UdpReliableChannelWorker::create(
+            ctx,
+            main_addr.clone(),
+            peer_route.into(),
+            &flow_control_id,
+        )
+        .await?;
+
+        Ok(Self {
+            main_addr,
+            local_addr,
+            flow_control_id,
+        })
+    }
+
+    /// Address used to send messages to the other side of the channel
+    pub fn address(&self) -> Address {
+        self.local_addr.clone()
+    }
+
+    /// Flow control id of the messages received from the other side of the channel
+    pub fn flow_control_id(&self) -> &FlowControlId {
+        &self.flow_control_id
+    }
+
+    /// Stop this side of the channel
+    pub async fn stop(&self, ctx: &Context) -> Result<()> {
+        ctx.stop_worker(self.main_addr.clone()).await
+    }
+}
@@ -0,0 +1,23 @@
+use ockam_core::Message;
+use serde::{Deserialize, Serialize};
+
+/// Internal message type for UDP reliable channels
+#[derive(Serialize, Deserialize, Debug, Message, Clone)]
+pub(crate) enum ReliableMessage {
+    /// A segment of an encoded transport message.
+    /// Segments are numbered with consecutive sequence numbers within a session and
+    /// the last segment of a transport message is flagged with `is_last`
+    Data {
+        session: u64,
+        seq: u64,
+        is_last: bool,
+        segment: Vec<u8>,
+    },
+    /// Acknowledge all the segments of a session with a sequence number lower than `next_seq`
+    Ack { session: u64, next_seq: u64 },
+    /// Internal timer event used to retransmit unacknowledged segments
+    Retransmit,
+    /// Internal message with an encoded transport message to send to the peer,
+    /// once there is room for its segments in the send buffer
+    Send { message: Vec<u8> },
+}
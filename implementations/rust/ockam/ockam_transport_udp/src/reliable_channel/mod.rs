@@ -0,0 +1,9 @@
+pub use error::ReliableChannelError;
+pub use handle::UdpReliableChannel;
+
+mod error;
+mod handle;
+mod message;
+mod sender;
+mod window;
+mod worker;
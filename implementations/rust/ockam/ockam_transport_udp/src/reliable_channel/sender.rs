@@ -0,0 +1,86 @@
+use crate::reliable_channel::message::ReliableMessage;
+use crate::ReliableChannelError;
+use ockam_core::{route, Address, Any, Encodable, Result, Routed, Worker};
+use ockam_node::Context;
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+use tracing::{trace, warn};
+
+/// [`Worker`] receiving the messages sent by local entities through a UDP reliable channel
+///
+/// See documentation for [`UdpReliableChannel`](crate::UdpReliableChannel).
+///
+/// Each message is passed to the [`UdpReliableChannelWorker`](super::worker::UdpReliableChannelWorker)
+/// once its segments fit in the send buffer, which is tracked with one permit per segment.
+/// While the buffer is full this worker waits for the peer to acknowledge some segments,
+/// its mailbox fills up and its senders are blocked. For a TCP portal, this stops reading
+/// from the TCP connection until the peer has caught up.
+pub(crate) struct UdpReliableChannelSender {
+    /// Address of the worker sending the segments to the peer
+    main_addr: Address,
+    /// Maximum size of a segment
+    segment_size: usize,
+    /// Maximum number of segments in the send buffer
+    send_buffer_size: usize,
+    /// One permit for each segment which can still be added to the send buffer
+    send_buffer: Arc<Semaphore>,
+}
+
+impl UdpReliableChannelSender {
+    pub(crate) fn new(
+        main_addr: Address,
+        segment_size: usize,
+        send_buffer_size: usize,
+        send_buffer: Arc<Semaphore>,
+    ) -> Self {
+        Self {
+            main_addr,
+            segment_size,
+            send_buffer_size,
+            send_buffer,
+        }
+    }
+}
+
+#[ockam_core::worker]
+impl Worker for UdpReliableChannelSender {
+    type Message = Any;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        let mut msg = msg.into_transport_message();
+        trace!("Local => ReliableChannel: {:?}", msg);
+
+        // Remove our address, the peer delivers the message to the rest of the route
+        msg.onward_route.step()?;
+        let message = msg.encode()?;
+
+        // an empty message is still sent as one (empty) segment
+        let segments = message.len().div_ceil(self.segment_size).max(1);
+        let permits = match u32::try_from(segments) {
+            Ok(permits) if permits as usize <= self.send_buffer_size => permits,
+            _ => {
+                warn!(
+                    "Dropping a message of {} bytes, larger than the send buffer of the reliable channel {}",
+                    message.len(),
+                    self.main_addr
+                );
+                return Err(ReliableChannelError::SendBufferFull.into());
+            }
+        };
+
+        // The permits are given back by the main worker when the segments are acknowledged.
+        // The semaphore is closed when the channel is closed
+        self.send_buffer
+            .acquire_many(permits)
+            .await
+            .map_err(|_| ReliableChannelError::ChannelClosed)?
+            .forget();
+
+        ctx.send(
+            route![self.main_addr.clone()],
+            ReliableMessage::Send { message },
+        )
+        .await
+    }
+}
@@ -0,0 +1,359 @@
+use crate::ReliableChannelError;
+use std::collections::{BTreeMap, VecDeque};
+use std::time::{Duration, Instant};
+use tracing::warn;
+
+/// A numbered part of a message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(super) struct Segment {
+    pub(super) seq: u64,
+    pub(super) is_last: bool,
+    pub(super) data: Vec<u8>,
+}
+
+/// A segment sent to the peer and not acknowledged yet
+struct InFlight {
+    segment: Segment,
+    sent_at: Instant,
+    retransmissions: u32,
+}
+
+/// Sending side of a reliable channel
+///
+/// Messages are split into segments with consecutive sequence numbers.
+/// At most `capacity` segments can be waiting for an acknowledgement, the other
+/// segments are kept pending until some room is made in the window.
+/// At most `max_pending` segments can be kept pending, and a segment can only be
+/// retransmitted `max_retransmissions` times.
+pub(super) struct SendWindow {
+    capacity: usize,
+    segment_size: usize,
+    max_pending: usize,
+    max_retransmissions: u32,
+    next_seq: u64,
+    in_flight: VecDeque<InFlight>,
+    pending: VecDeque<Segment>,
+}
+
+impl SendWindow {
+    pub(super) fn new(
+        capacity: usize,
+        segment_size: usize,
+        max_pending: usize,
+        max_retransmissions: u32,
+    ) -> Self {
+        Self {
+            capacity,
+            segment_size,
+            max_pending,
+            max_retransmissions,
+            next_seq: 0,
+            in_flight: VecDeque::new(),
+            pending: VecDeque::new(),
+        }
+    }
+
+    /// Split a message into segments and return the segments which can be sent now.
+    /// The message is rejected if its segments cannot all be kept pending
+    pub(super) fn push(
+        &mut self,
+        message: &[u8],
+        now: Instant,
+    ) -> Result<Vec<Segment>, ReliableChannelError> {
+        let mut chunks: Vec<&[u8]> = message.chunks(self.segment_size).collect();
+        // an empty message is still sent as one (empty) segment
+        if chunks.is_empty() {
+            chunks.push(&[]);
+        }
+        if self.pending.len() + chunks.len() > self.max_pending {
+            return Err(ReliableChannelError::SendBufferFull);
+        }
+        let last = chunks.len() - 1;
+        for (i, chunk) in chunks.into_iter().enumerate() {
+            self.pending.push_back(Segment {
+                seq: self.next_seq,
+                is_last: i == last,
+                data: chunk.to_vec(),
+            });
+            self.next_seq += 1;
+        }
+        Ok(self.send_pending(now))
+    }
+
+    /// Remove the segments acknowledged by the peer and return the
+    /// pending segments which can now be sent
+    pub(super) fn ack(&mut self, next_seq: u64, now: Instant) -> Vec<Segment> {
+        while let Some(in_flight) = self.in_flight.front() {
+            if in_flight.segment.seq >= next_seq {
+                break;
+            }
+            self.in_flight.pop_front();
+        }
+        self.send_pending(now)
+    }
+
+    /// Return the segments which were sent more than `timeout` ago without being
+    /// acknowledged. They are considered as sent again at `now`.
+    /// An error is returned when a segment has already been retransmitted too many times
+    pub(super) fn expired(
+        &mut self,
+        now: Instant,
+        timeout: Duration,
+    ) -> Result<Vec<Segment>, ReliableChannelError> {
+        let mut expired = vec![];
+        for in_flight in self.in_flight.iter_mut() {
+            if now.duration_since(in_flight.sent_at) < timeout {
+                continue;
+            }
+            if in_flight.retransmissions >= self.max_retransmissions {
+                return Err(ReliableChannelError::PeerUnreachable);
+            }
+            in_flight.sent_at = now;
+            in_flight.retransmissions += 1;
+            expired.push(in_flight.segment.clone());
+        }
+        Ok(expired)
+    }
+
+    /// Return true if some segments have not been acknowledged yet
+    pub(super) fn has_in_flight(&self) -> bool {
+        !self.in_flight.is_empty()
+    }
+
+    /// Number of segments which are pending or not acknowledged yet
+    pub(super) fn len(&self) -> usize {
+        self.in_flight.len() + self.pending.len()
+    }
+
+    fn send_pending(&mut self, now: Instant) -> Vec<Segment> {
+        let mut sent = vec![];
+        while self.in_flight.len() < self.capacity {
+            match self.pending.pop_front() {
+                Some(segment) => {
+                    self.in_flight.push_back(InFlight {
+                        segment: segment.clone(),
+                        sent_at: now,
+                        retransmissions: 0,
+                    });
+                    sent.push(segment);
+                }
+                None => break,
+            }
+        }
+        sent
+    }
+}
+
+/// Receiving side of a reliable channel
+///
+/// Segments are put back in order and reassembled into messages.
+/// Segments too far ahead of the next expected segment are dropped and will be retransmitted.
+/// Messages larger than `max_message_size` are dropped.
+pub(super) struct ReceiveWindow {
+    capacity: u64,
+    max_message_size: usize,
+    next_seq: u64,
+    out_of_order: BTreeMap<u64, Segment>,
+    message: Vec<u8>,
+    /// True when the segments of the current message are dropped
+    discarding: bool,
+}
+
+impl ReceiveWindow {
+    pub(super) fn new(capacity: usize, max_message_size: usize) -> Self {
+        Self {
+            capacity: capacity as u64,
+            max_message_size,
+            next_seq: 0,
+            out_of_order: BTreeMap::new(),
+            message: vec![],
+            discarding: false,
+        }
+    }
+
+    /// Sequence number of the next expected segment.
+    /// All the segments before that number have been received
+    pub(super) fn next_seq(&self) -> u64 {
+        self.next_seq
+    }
+
+    /// Accept a segment and return the messages which are now complete, in order
+    pub(super) fn receive(&mut self, segment: Segment) -> Vec<Vec<u8>> {
+        if segment.seq < self.next_seq || segment.seq >= self.next_seq + self.capacity {
+            // duplicate or out of the window
+            return vec![];
+        }
+        self.out_of_order.insert(segment.seq, segment);
+
+        let mut messages = vec![];
+        while let Some(segment) = self.out_of_order.remove(&self.next_seq) {
+            self.next_seq += 1;
+            if !self.discarding {
+                if self.message.len() + segment.data.len() > self.max_message_size {
+                    warn!(
+                        "Dropping a message larger than {} bytes",
+                        self.max_message_size
+                    );
+                    self.message = vec![];
+                    self.discarding = true;
+                } else {
+                    self.message.extend_from_slice(&segment.data);
+                }
+            }
+            if segment.is_last {
+                if self.discarding {
+                    self.discarding = false;
+                } else {
+                    messages.push(core::mem::take(&mut self.message));
+                }
+            }
+        }
+        messages
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_are_segmented_and_reassembled() {
+        let now = Instant::now();
+        let mut sender = SendWindow::new(10, 4, 100, 10);
+        let mut receiver = ReceiveWindow::new(10, 100);
+
+        let mut segments = sender.push(b"hello world", now).unwrap();
+        segments.extend(sender.push(b"", now).unwrap());
+        segments.extend(sender.push(b"abcd", now).unwrap());
+        assert_eq!(segments.len(), 5);
+
+        let messages: Vec<Vec<u8>> = segments
+            .into_iter()
+            .flat_map(|s| receiver.receive(s))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![b"hello world".to_vec(), b"".to_vec(), b"abcd".to_vec()]
+        );
+        assert_eq!(receiver.next_seq(), 5);
+    }
+
+    #[test]
+    fn test_segments_are_reordered_and_deduplicated() {
+        let now = Instant::now();
+        let mut sender = SendWindow::new(10, 2, 100, 10);
+        let mut receiver = ReceiveWindow::new(10, 100);
+
+        let segments = sender.push(b"abcdef", now).unwrap();
+        assert!(receiver.receive(segments[2].clone()).is_empty());
+        assert!(receiver.receive(segments[1].clone()).is_empty());
+        assert!(receiver.receive(segments[1].clone()).is_empty());
+        assert_eq!(receiver.next_seq(), 0);
+
+        assert_eq!(
+            receiver.receive(segments[0].clone()),
+            vec![b"abcdef".to_vec()]
+        );
+        assert!(receiver.receive(segments[0].clone()).is_empty());
+        assert_eq!(receiver.next_seq(), 3);
+    }
+
+    #[test]
+    fn test_segments_outside_of_the_receive_window_are_dropped() {
+        let now = Instant::now();
+        let mut sender = SendWindow::new(10, 1, 100, 10);
+        let mut receiver = ReceiveWindow::new(2, 100);
+
+        let segments = sender.push(b"abc", now).unwrap();
+        assert!(receiver.receive(segments[2].clone()).is_empty());
+        assert!(receiver.receive(segments[0].clone()).is_empty());
+        assert!(receiver.receive(segments[1].clone()).is_empty());
+        assert_eq!(receiver.next_seq(), 2);
+
+        // the dropped segment is retransmitted
+        assert_eq!(receiver.receive(segments[2].clone()), vec![b"abc".to_vec()]);
+    }
+
+    #[test]
+    fn test_send_window_is_limited_and_segments_are_retransmitted() {
+        let now = Instant::now();
+        let timeout = Duration::from_millis(200);
+        let mut sender = SendWindow::new(2, 1, 100, 10);
+
+        let sent = sender.push(b"abcd", now).unwrap();
+        assert_eq!(sent.iter().map(|s| s.seq).collect::<Vec<_>>(), vec![0, 1]);
+        assert!(sender.expired(now, timeout).unwrap().is_empty());
+
+        let later = now + timeout;
+        let expired = sender.expired(later, timeout).unwrap();
+        assert_eq!(
+            expired.iter().map(|s| s.seq).collect::<Vec<_>>(),
+            vec![0, 1]
+        );
+        assert!(sender.expired(later, timeout).unwrap().is_empty());
+
+        // acknowledging the first segment makes room for the third one
+        let sent = sender.ack(1, later);
+        assert_eq!(sent.iter().map(|s| s.seq).collect::<Vec<_>>(), vec![2]);
+
+        let sent = sender.ack(3, later);
+        assert_eq!(sent.iter().map(|s| s.seq).collect::<Vec<_>>(), vec![3]);
+        assert!(sender.has_in_flight());
+
+        assert!(sender.ack(4, later).is_empty());
+        assert!(!sender.has_in_flight());
+    }
+
+    #[test]
+    fn test_segments_are_not_retransmitted_forever() {
+        let mut now = Instant::now();
+        let timeout = Duration::from_millis(200);
+        let mut sender = SendWindow::new(2, 1, 100, 3);
+
+        sender.push(b"a", now).unwrap();
+        for _ in 0..3 {
+            now += timeout;
+            assert_eq!(sender.expired(now, timeout).unwrap().len(), 1);
+        }
+        now += timeout;
+        assert!(matches!(
+            sender.expired(now, timeout),
+            Err(ReliableChannelError::PeerUnreachable)
+        ));
+    }
+
+    #[test]
+    fn test_pending_segments_are_limited() {
+        let now = Instant::now();
+        let mut sender = SendWindow::new(2, 1, 4, 10);
+
+        // 2 segments are in flight and 2 are pending
+        assert_eq!(sender.push(b"abcd", now).unwrap().len(), 2);
+        assert!(matches!(
+            sender.push(b"efg", now),
+            Err(ReliableChannelError::SendBufferFull)
+        ));
+        assert!(sender.push(b"ef", now).unwrap().is_empty());
+
+        // acknowledged segments make room for new ones
+        assert_eq!(sender.ack(2, now).len(), 2);
+        assert!(sender.push(b"gh", now).unwrap().is_empty());
+        assert!(sender.push(b"i", now).is_err());
+    }
+
+    #[test]
+    fn test_messages_larger_than_the_maximum_size_are_dropped() {
+        let now = Instant::now();
+        let mut sender = SendWindow::new(10, 2, 100, 10);
+        let mut receiver = ReceiveWindow::new(10, 4);
+
+        let mut segments = sender.push(b"abcdef", now).unwrap();
+        segments.extend(sender.push(b"ghij", now).unwrap());
+
+        let messages: Vec<Vec<u8>> = segments
+            .into_iter()
+            .flat_map(|s| receiver.receive(s))
+            .collect();
+        assert_eq!(messages, vec![b"ghij".to_vec()]);
+    }
+}
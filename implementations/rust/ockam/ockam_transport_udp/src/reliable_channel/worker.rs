@@ -0,0 +1,311 @@
+use crate::reliable_channel::message::ReliableMessage;
+use crate::reliable_channel::sender::UdpReliableChannelSender;
+use crate::reliable_channel::window::{ReceiveWindow, Segment, SendWindow};
+use crate::ReliableChannelError;
+use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl};
+use ockam_core::{
+    Address, AllowAll, AllowOnwardAddress, Any, Decodable, DenyAll, LocalMessage, Mailbox,
+    Mailboxes, Result, Route, Routed, TransportMessage, Worker,
+};
+use ockam_node::{Context, DelayedEvent, WorkerBuilder};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tracing::{debug, trace, warn};
+
+/// Maximum size of the data sent in one datagram. A smaller size than the UDP maximum
+/// avoids IP fragmentation, which is often not supported by NATs
+const SEGMENT_SIZE: usize = 1200;
+
+/// Maximum number of segments sent and not acknowledged yet
+const WINDOW_SIZE: usize = 256;
+
+/// Maximum number of segments in the send buffer, waiting for some room in the window
+/// or for an acknowledgement. Local senders wait until their message fits
+const MAX_PENDING_SEGMENTS: usize = 4096;
+
+/// Maximum size of a message reassembled from the segments received from the peer
+const MAX_MESSAGE_SIZE: usize = MAX_PENDING_SEGMENTS * SEGMENT_SIZE;
+
+/// Time after which a segment which has not been acknowledged is sent again
+const RETRANSMIT_AFTER: Duration = Duration::from_millis(200);
+
+/// Number of retransmissions of a segment after which the peer is considered unreachable
+/// and the channel is closed, which is about 10 seconds without any acknowledgement
+const MAX_RETRANSMISSIONS: u32 = 50;
+
+/// [`Worker`] for one side of a UDP reliable channel
+///
+/// See documentation for [`UdpReliableChannel`](crate::UdpReliableChannel).
+///
+/// # 'Main' Mailbox
+///
+/// Sends segments and acknowledgements to the remote peer's [`UdpReliableChannelWorker`]
+/// and receives them from it.
+///
+/// Also receives the retransmission timer events, and the messages of local entities from
+/// the [`UdpReliableChannelSender`]. These messages are split into segments, which are sent
+/// to the peer until they are acknowledged.
+///
+/// # 'Local' Mailbox
+///
+/// Messages reassembled from the segments received by the 'main' mailbox
+/// are forwarded to local entities from the 'local' mailbox.
+/// Their replies are sent back through the [`UdpReliableChannelSender`].
+pub(crate) struct UdpReliableChannelWorker {
+    /// Address of main mailbox
+    main_addr: Address,
+    /// Address of local mailbox
+    local_addr: Address,
+    /// Address of the worker receiving the messages of local entities
+    sender_addr: Address,
+    /// One permit for each segment which can still be added to the send buffer.
+    /// The permits of the segments are given back when they are acknowledged
+    send_buffer: Arc<Semaphore>,
+    /// Route to peer node's reliable channel worker
+    peer_route: Route,
+    /// Random identifier of the segments sent by this worker.
+    /// It lets the peer detect that this side has been restarted
+    session: u64,
+    /// Session of the segments received from the peer
+    peer_session: Option<u64>,
+    send_window: SendWindow,
+    receive_window: ReceiveWindow,
+    /// For generating internal retransmission events
+    retransmit: DelayedEvent<ReliableMessage>,
+    retransmit_scheduled: bool,
+}
+
+impl UdpReliableChannelWorker {
+    pub(crate) async fn create(
+        ctx: &Context,
+        main_addr: Address,
+        peer_route: Route,
+        flow_control_id: &FlowControlId,
+    ) -> Result<Address> {
+        let local_addr = Address::random_tagged("UdpReliableChannel.local");
+        let sender_addr = Address::random_tagged("UdpReliableChannel.sender");
+        let send_buffer = Arc::new(Semaphore::new(MAX_PENDING_SEGMENTS));
+
+        let retransmit =
+            DelayedEvent::create(ctx, main_addr.clone(), ReliableMessage::Retransmit).await?;
+
+        let main_mailbox = Mailbox::new(
+            main_addr.clone(),
+            Arc::new(AllowAll), // FIXME: @ac
+            Arc::new(AllowAll), // FIXME: @ac
+        );
+
+        // Messages received from the peer are only delivered to the consumers of the flow control.
+        // The sender address is the address of the channel for local entities, they can find
+        // that flow control from it
+        ctx.flow_controls().add_producer(
+            local_addr.clone(),
+            flow_control_id,
+            None,
+            vec![sender_addr.clone()],
+        );
+        let local_mailbox = Mailbox::new(
+            local_addr.clone(),
+            Arc::new(DenyAll),
+            Arc::new(FlowControlOutgoingAccessControl::new(
+                ctx.flow_controls(),
+                flow_control_id.clone(),
+                None,
+            )),
+        );
+
+        let worker = Self {
+            main_addr: main_addr.clone(),
+            local_addr,
+            sender_addr: sender_addr.clone(),
+            send_buffer: send_buffer.clone(),
+            peer_route,
+            session: rand::random(),
+            peer_session: None,
+            send_window: SendWindow::new(
+                WINDOW_SIZE,
+                SEGMENT_SIZE,
+                MAX_PENDING_SEGMENTS,
+                MAX_RETRANSMISSIONS,
+            ),
+            receive_window: ReceiveWindow::new(WINDOW_SIZE, MAX_MESSAGE_SIZE),
+            retransmit,
+            retransmit_scheduled: false,
+        };
+        WorkerBuilder::new(worker)
+            .with_mailboxes(Mailboxes::new(main_mailbox, vec![local_mailbox]))
+            .start(ctx)
+            .await?;
+
+        let sender = UdpReliableChannelSender::new(
+            main_addr.clone(),
+            SEGMENT_SIZE,
+            MAX_PENDING_SEGMENTS,
+            send_buffer,
+        );
+        WorkerBuilder::new(sender)
+            .with_address(sender_addr.clone())
+            .with_outgoing_access_control(AllowOnwardAddress(main_addr))
+            .start(ctx)
+            .await?;
+
+        Ok(sender_addr)
+    }
+
+    /// Send segments to the peer and make sure that they are retransmitted if they are lost
+    async fn send_segments(&mut self, ctx: &Context, segments: Vec<Segment>) -> Result<()> {
+        for segment in segments {
+            let msg = ReliableMessage::Data {
+                session: self.session,
+                seq: segment.seq,
+                is_last: segment.is_last,
+                segment: segment.data,
+            };
+            // UDP is unreliable anyway, a failed send is handled as a lost segment
+            if let Err(e) = ctx.send(self.peer_route.clone(), msg).await {
+                warn!("Failed to send a segment to {}: {}", self.peer_route, e);
+            }
+        }
+
+        if self.send_window.has_in_flight() && !self.retransmit_scheduled {
+            self.retransmit.schedule(RETRANSMIT_AFTER).await?;
+            self.retransmit_scheduled = true;
+        }
+        Ok(())
+    }
+
+    /// Handle an encoded message from local entities, for which the sender reserved
+    /// some room in the send buffer
+    async fn handle_local(&mut self, ctx: &Context, message: Vec<u8>) -> Result<()> {
+        let segments = self.send_window.push(&message, Instant::now())?;
+        self.send_segments(ctx, segments).await
+    }
+
+    /// Handle messages from peer
+    async fn handle_peer(&mut self, ctx: &Context, msg: ReliableMessage) -> Result<()> {
+        match msg {
+            ReliableMessage::Data {
+                session,
+                seq,
+                is_last,
+                segment,
+            } => {
+                if self.peer_session != Some(session) {
+                    debug!("New session for the reliable channel {}", self.main_addr);
+                    self.peer_session = Some(session);
+                    self.receive_window = ReceiveWindow::new(WINDOW_SIZE, MAX_MESSAGE_SIZE);
+                }
+
+                let messages = self.receive_window.receive(Segment {
+                    seq,
+                    is_last,
+                    data: segment,
+                });
+
+                let ack = ReliableMessage::Ack {
+                    session,
+                    next_seq: self.receive_window.next_seq(),
+                };
+                if let Err(e) = ctx.send(self.peer_route.clone(), ack).await {
+                    warn!("Failed to send an ack to {}: {}", self.peer_route, e);
+                }
+
+                for message in messages {
+                    self.deliver(ctx, &message).await?;
+                }
+            }
+            ReliableMessage::Ack { session, next_seq } => {
+                if session == self.session {
+                    let buffered = self.send_window.len();
+                    let segments = self.send_window.ack(next_seq, Instant::now());
+                    // the acknowledged segments make room for new messages
+                    self.send_buffer
+                        .add_permits(buffered - self.send_window.len());
+                    self.send_segments(ctx, segments).await?;
+                }
+            }
+            ReliableMessage::Retransmit | ReliableMessage::Send { .. } => {
+                return Err(ReliableChannelError::Internal.into())
+            }
+        }
+        Ok(())
+    }
+
+    /// Forward a reassembled message to a local entity
+    async fn deliver(&self, ctx: &Context, message: &[u8]) -> Result<()> {
+        let mut msg =
+            TransportMessage::decode(message).map_err(|_| ReliableChannelError::InvalidMessage)?;
+        // Replies are sent back through this channel
+        msg.return_route.modify().prepend(self.sender_addr.clone());
+
+        trace!("ReliableChannel => Local: {:?}", msg);
+        ctx.forward_from_address(LocalMessage::new(msg, vec![]), self.local_addr.clone())
+            .await
+    }
+
+    /// Handle retransmission events.
+    /// The channel is closed when the peer does not acknowledge the segments anymore
+    async fn handle_retransmit(&mut self, ctx: &Context) -> Result<()> {
+        self.retransmit_scheduled = false;
+        let segments = match self.send_window.expired(Instant::now(), RETRANSMIT_AFTER) {
+            Ok(segments) => segments,
+            Err(_) => {
+                warn!(
+                    "Closing the reliable channel {}, the peer {} is unreachable",
+                    self.main_addr, self.peer_route
+                );
+                return ctx.stop_worker(self.main_addr.clone()).await;
+            }
+        };
+        if !segments.is_empty() {
+            trace!(
+                "Retransmitting {} segments to {}",
+                segments.len(),
+                self.peer_route
+            );
+        }
+        self.send_segments(ctx, segments).await
+    }
+}
+
+#[ockam_core::worker]
+impl Worker for UdpReliableChannelWorker {
+    type Message = Any;
+    type Context = Context;
+
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.retransmit.cancel();
+        // Local senders waiting for some room in the send buffer get an error
+        self.send_buffer.close();
+        ctx.stop_worker(self.sender_addr.clone()).await
+    }
+
+    async fn handle_message(
+        &mut self,
+        ctx: &mut Context,
+        msg: Routed<Self::Message>,
+    ) -> Result<()> {
+        match msg.msg_addr() {
+            // 'main' mailbox
+            addr if addr == self.main_addr => {
+                if msg.sender()? == self.retransmit.address() {
+                    self.handle_retransmit(ctx).await
+                } else if msg.sender()? == self.sender_addr {
+                    match ReliableMessage::decode(msg.payload()) {
+                        Ok(ReliableMessage::Send { message }) => {
+                            self.handle_local(ctx, message).await
+                        }
+                        _ => Err(ReliableChannelError::Internal.into()),
+                    }
+                } else {
+                    let msg = ReliableMessage::decode(msg.payload())
+                        .map_err(|_| ReliableChannelError::InvalidMessage)?;
+                    self.handle_peer(ctx, msg).await
+                }
+            }
+
+            _ => Err(ReliableChannelError::Internal.into()),
+        }
+    }
+}
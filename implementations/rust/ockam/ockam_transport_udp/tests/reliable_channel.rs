@@ -0,0 +1,166 @@
+use ockam::{TcpInletOptions, TcpOutletOptions, TcpTransport};
+use ockam_core::compat::rand::{self, Rng};
+use ockam_core::{route, Any, Result, Routed, Worker};
+use ockam_node::Context;
+use ockam_transport_udp::{UdpReliableChannel, UdpTransport, UDP};
+use std::time::Duration;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+
+mod utils;
+
+/// A TCP portal should work over a UDP reliable channel,
+/// even if some UDP datagrams are lost
+#[ockam_macros::test(timeout = 30000)]
+async fn portal_over_reliable_channel(ctx: &mut Context) -> Result<()> {
+    echo_through_portal_over_lossy_channel(ctx, 100_000, Duration::from_secs(10)).await?;
+    ctx.stop().await
+}
+
+/// Sending more data than the send buffer of the channel can hold should slow down the
+/// portal instead of losing some data
+#[ockam_macros::test(timeout = 120000)]
+async fn portal_over_reliable_channel_with_more_data_than_the_send_buffer(
+    ctx: &mut Context,
+) -> Result<()> {
+    // the send buffer holds 4096 segments of 1200 bytes
+    echo_through_portal_over_lossy_channel(ctx, 6_000_000, Duration::from_secs(90)).await?;
+    ctx.stop().await
+}
+
+/// Send some random data to a TCP echo server through a portal over a lossy reliable channel
+/// and check that the same data is received back
+async fn echo_through_portal_over_lossy_channel(
+    ctx: &mut Context,
+    data_len: usize,
+    timeout: Duration,
+) -> Result<()> {
+    let bind_addr = utils::available_local_ports(1)
+        .await?
+        .first()
+        .unwrap()
+        .to_string();
+
+    let udp = UdpTransport::create(ctx).await?;
+    udp.listen(bind_addr.clone()).await?;
+    ctx.start_worker("lossy", Lossy { count: 0 }).await?;
+
+    // Both sides of the channel are on the same node but every message
+    // between them goes through UDP and loses some datagrams on the way
+    let alice = UdpReliableChannel::create(
+        ctx,
+        "alice_channel",
+        route![(UDP, bind_addr.clone()), "lossy", "bob_channel"],
+    )
+    .await?;
+    let bob = UdpReliableChannel::create(
+        ctx,
+        "bob_channel",
+        route![(UDP, bind_addr), "lossy", "alice_channel"],
+    )
+    .await?;
+
+    // A TCP echo server behind the outlet
+    let echo_server = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let echo_addr = echo_server.local_addr().unwrap();
+    tokio::spawn(async move {
+        let (mut stream, _) = echo_server.accept().await.unwrap();
+        let (mut reader, mut writer) = stream.split();
+        tokio::io::copy(&mut reader, &mut writer).await.unwrap();
+    });
+
+    let tcp = TcpTransport::create(ctx).await?;
+    tcp.create_outlet(
+        "outlet",
+        echo_addr.to_string(),
+        TcpOutletOptions::new().as_consumer(bob.flow_control_id()),
+    )
+    .await?;
+    let (inlet_addr, _) = tcp
+        .create_inlet(
+            "127.0.0.1:0",
+            route![alice.address(), "outlet"],
+            TcpInletOptions::new(),
+        )
+        .await?;
+
+    let data: Vec<u8> = rand::thread_rng()
+        .sample_iter(&rand::distributions::Standard)
+        .take(data_len)
+        .collect();
+
+    let mut stream = TcpStream::connect(inlet_addr).await.unwrap();
+    let (mut reader, mut writer) = stream.split();
+    let received = tokio::time::timeout(timeout, async {
+        let write = writer.write_all(&data);
+        let mut received = vec![0; data.len()];
+        let read = reader.read_exact(&mut received);
+        let (write, read) = tokio::join!(write, read);
+        write.unwrap();
+        read.unwrap();
+        received
+    })
+    .await
+    .unwrap();
+    assert!(received == data, "The data should be received in order");
+    Ok(())
+}
+
+/// A channel should be closed when its peer never acknowledges the segments
+#[ockam_macros::test(timeout = 30000)]
+async fn reliable_channel_to_unreachable_peer_is_closed(ctx: &mut Context) -> Result<()> {
+    let bind_addr = utils::available_local_ports(1)
+        .await?
+        .first()
+        .unwrap()
+        .to_string();
+
+    let udp = UdpTransport::create(ctx).await?;
+    udp.listen(bind_addr.clone()).await?;
+
+    // there is no worker at the end of the route
+    let alice = UdpReliableChannel::create(
+        ctx,
+        "alice_channel",
+        route![(UDP, bind_addr), "bob_channel"],
+    )
+    .await?;
+    ctx.send(route![alice.address(), "echo"], "hello".to_string())
+        .await?;
+
+    tokio::time::timeout(Duration::from_secs(20), async {
+        while ctx
+            .list_workers()
+            .await
+            .unwrap()
+            .contains(&"alice_channel".into())
+        {
+            tokio::time::sleep(Duration::from_millis(500)).await;
+        }
+    })
+    .await
+    .expect("the channel should be closed");
+
+    ctx.stop().await
+}
+
+/// Drop one message out of 5
+struct Lossy {
+    count: usize,
+}
+
+#[ockam_core::worker]
+impl Worker for Lossy {
+    type Message = Any;
+    type Context = Context;
+
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+        self.count += 1;
+        if self.count % 5 == 0 {
+            return Ok(());
+        }
+        let mut msg = msg.into_local_message();
+        msg.transport_mut().onward_route.step()?;
+        ctx.forward(msg).await
+    }
+}
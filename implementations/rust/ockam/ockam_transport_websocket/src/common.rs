@@ -0,0 +1,161 @@
+use core::fmt;
+use core::fmt::Formatter;
+use std::net::SocketAddr;
+
+use ockam_core::flow_control::FlowControlId;
+use ockam_core::{Address, Result};
+use ockam_node::Context;
+
+/// Direction of a WebSocket connection
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum WebSocketConnectionMode {
+    /// Connection was initiated by this node
+    Outgoing,
+    /// Connection was accepted by one of this node's listeners
+    Incoming,
+}
+
+impl fmt::Display for WebSocketConnectionMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Outgoing => write!(f, "outgoing"),
+            Self::Incoming => write!(f, "incoming"),
+        }
+    }
+}
+
+/// Result of [`WebSocketTransport::connect`](crate::WebSocketTransport::connect) call,
+/// also used to describe connections accepted by a listener.
+#[derive(Clone, Debug)]
+pub struct WebSocketConnection {
+    sender_address: Address,
+    receiver_address: Address,
+    socket_address: SocketAddr,
+    mode: WebSocketConnectionMode,
+    flow_control_id: FlowControlId,
+    tls: bool,
+}
+
+impl fmt::Display for WebSocketConnection {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Socket: {}, Worker: {}, Processor: {}, FlowId: {}, TLS: {}",
+            self.socket_address,
+            self.sender_address,
+            self.receiver_address,
+            self.flow_control_id,
+            self.tls
+        )
+    }
+}
+
+impl From<WebSocketConnection> for Address {
+    fn from(value: WebSocketConnection) -> Self {
+        value.sender_address
+    }
+}
+
+impl WebSocketConnection {
+    /// Constructor
+    pub fn new(
+        sender_address: Address,
+        receiver_address: Address,
+        socket_address: SocketAddr,
+        mode: WebSocketConnectionMode,
+        flow_control_id: FlowControlId,
+        tls: bool,
+    ) -> Self {
+        Self {
+            sender_address,
+            receiver_address,
+            socket_address,
+            mode,
+            flow_control_id,
+            tls,
+        }
+    }
+    /// Stops the [`WebSocketConnection`], this method must be called to avoid
+    /// leakage of the connection.
+    /// Simply dropping this object won't close the connection
+    pub async fn stop(&self, context: &Context) -> Result<()> {
+        context.stop_worker(self.sender_address.clone()).await
+    }
+    /// Sender worker [`Address`] that can be used in a route to send messages
+    /// to the other side of the WebSocket connection
+    pub fn sender_address(&self) -> &Address {
+        &self.sender_address
+    }
+    /// Receiver processor [`Address`]
+    pub fn receiver_address(&self) -> &Address {
+        &self.receiver_address
+    }
+    /// Corresponding [`SocketAddr`]
+    pub fn socket_address(&self) -> &SocketAddr {
+        &self.socket_address
+    }
+    /// Generated fresh random [`FlowControlId`]
+    pub fn flow_control_id(&self) -> &FlowControlId {
+        &self.flow_control_id
+    }
+    /// Corresponding [`WebSocketConnectionMode`]
+    pub fn mode(&self) -> WebSocketConnectionMode {
+        self.mode
+    }
+    /// Whether the connection is secured with TLS (`wss://`)
+    pub fn is_tls(&self) -> bool {
+        self.tls
+    }
+}
+
+/// Result of [`WebSocketTransport::listen`](crate::WebSocketTransport::listen) call.
+#[derive(Clone, Debug)]
+pub struct WebSocketListener {
+    processor_address: Address,
+    socket_address: SocketAddr,
+    flow_control_id: FlowControlId,
+    tls: bool,
+}
+
+impl fmt::Display for WebSocketListener {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Socket: {}, Processor: {}, FlowId: {}, TLS: {}",
+            self.socket_address, self.processor_address, self.flow_control_id, self.tls
+        )
+    }
+}
+
+impl WebSocketListener {
+    /// Constructor
+    pub fn new(
+        processor_address: Address,
+        socket_address: SocketAddr,
+        flow_control_id: FlowControlId,
+        tls: bool,
+    ) -> Self {
+        Self {
+            processor_address,
+            socket_address,
+            flow_control_id,
+            tls,
+        }
+    }
+    /// Corresponding Worker [`Address`] that can be used to stop the Listener
+    pub fn processor_address(&self) -> &Address {
+        &self.processor_address
+    }
+    /// Corresponding [`SocketAddr`]
+    pub fn socket_address(&self) -> &SocketAddr {
+        &self.socket_address
+    }
+    /// Spawner [`FlowControlId`] of the connections accepted by this listener
+    pub fn flow_control_id(&self) -> &FlowControlId {
+        &self.flow_control_id
+    }
+    /// Whether the listener only accepts TLS (`wss://`) connections
+    pub fn is_tls(&self) -> bool {
+        self.tls
+    }
+}
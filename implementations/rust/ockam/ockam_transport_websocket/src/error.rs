@@ -15,6 +15,8 @@ pub(crate) enum WebSocketError {
     Http,
     /// TLS error.
     Tls,
+    /// The handshake did not complete in time.
+    HandshakeTimeout,
 }
 impl ockam_core::compat::error::Error for WebSocketError {}
 impl core::fmt::Display for WebSocketError {
@@ -23,6 +25,7 @@ impl core::fmt::Display for WebSocketError {
             Self::Transport(t) => write!(f, "ockam transport error {t}"),
             Self::Http => write!(f, "http protocol error"),
             Self::Tls => write!(f, "tls protocol error"),
+            Self::HandshakeTimeout => write!(f, "handshake timed out"),
         }
     }
 }
@@ -34,6 +37,7 @@ impl From<WebSocketError> for Error {
         let kind = match err {
             Transport(_) => Kind::Io,
             Http | Tls => Kind::Protocol,
+            HandshakeTimeout => Kind::Timeout,
         };
 
         Error::new(Origin::Transport, kind, err)
@@ -23,14 +23,18 @@
 //!
 //! // Now we can write the main function that will run the previous worker. In this case, our worker will be listening for new connections on port 8000 until the process is manually killed.
 //!
-//! use ockam_transport_websocket::WebSocketTransport;
+//! use ockam_transport_websocket::{WebSocketListenerOptions, WebSocketTransport};
 //! use ockam_node::NodeBuilder;
 //! use ockam_macros::node;
 //!
 //! #[ockam_macros::node(crate = "ockam_node")]
 //! async fn main(mut ctx: Context) -> Result<()> {//!
 //!     let ws = WebSocketTransport::create(&ctx).await?;
-//!     ws.listen("localhost:8000").await?; // Listen on port 8000
+//!     let options = WebSocketListenerOptions::new();
+//!
+//!     // Allow the connections accepted by the listener to reach "my_worker"
+//!     ctx.flow_controls().add_consumer("my_worker", &options.spawner_flow_control_id());
+//!     ws.listen("127.0.0.1:8000", options).await?; // Listen on port 8000
 //!
 //!     // Start a worker, of type MyWorker, at address "my_worker"
 //!     ctx.start_worker("my_worker", MyWorker).await?;
@@ -43,18 +47,20 @@
 //! Finally, we can write another node that connects to the node that is hosting the `MyWorker` worker, and we are ready to send and receive messages between them.
 //!
 //! ```rust,no_run
-//! use ockam_transport_websocket::{WebSocketTransport, WS};
+//! use ockam_transport_websocket::{WebSocketConnectionOptions, WebSocketTransport};
 //! use ockam_core::{route, Result};
 //! use ockam_node::Context;
 //! use ockam_macros::node;
 //!
 //! #[ockam_macros::node(crate = "ockam_node")]
 //! async fn main(mut ctx: Context) -> Result<()> {
-//!     use ockam_node::MessageReceiveOptions;
-//! let ws = WebSocketTransport::create(&ctx).await?;
+//!     let ws = WebSocketTransport::create(&ctx).await?;
+//!
+//!     // Connect to the server, use `WebSocketConnectionOptions::new().with_tls()` for `wss://`.
+//!     let connection = ws.connect("127.0.0.1:8000", WebSocketConnectionOptions::new()).await?;
 //!
 //!     // Define the route to the server's worker.
-//!     let r = route![(WS, "localhost:8000"), "my_worker"];
+//!     let r = route![connection, "my_worker"];
 //!
 //!     // Now you can send messages to the worker.
 //!     ctx.send(r, "Hello Ockam!".to_string()).await?;
@@ -89,11 +95,16 @@ use std::net::SocketAddr;
 
 use ockam_core::{Result, TransportType};
 use ockam_transport_core::TransportError;
-pub use transport::*;
 
-use crate::router::{WebSocketRouter, WebSocketRouterHandle};
+pub use common::*;
+pub use options::{WebSocketConnectionOptions, WebSocketListenerOptions, WebSocketTlsCertificate};
+pub use registry::*;
+pub use transport::*;
 
+mod common;
 mod error;
+mod options;
+mod registry;
 mod router;
 mod transport;
 mod workers;
@@ -0,0 +1,179 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use ockam_core::flow_control::{FlowControlId, FlowControlOutgoingAccessControl, FlowControls};
+use ockam_core::{Address, AllowAll, IncomingAccessControl, OutgoingAccessControl};
+use ockam_transport_core::tls::TlsCertificate;
+
+use crate::workers::Addresses;
+
+pub(crate) struct WebSocketConnectionAccessControl {
+    pub sender_incoming_access_control: Arc<dyn IncomingAccessControl>,
+    pub receiver_outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+}
+
+/// Trust and TLS options for an outgoing WebSocket connection
+#[derive(Debug)]
+pub struct WebSocketConnectionOptions {
+    pub(crate) consumer: Vec<FlowControlId>,
+    pub(crate) flow_control_id: FlowControlId,
+    pub(crate) tls: bool,
+    pub(crate) ca_certificate: Option<PathBuf>,
+}
+
+impl WebSocketConnectionOptions {
+    #[allow(clippy::new_without_default)]
+    /// Mark this WebSocket Receiver as a Producer with a random [`FlowControlId`]
+    pub fn new() -> Self {
+        Self {
+            consumer: vec![],
+            flow_control_id: FlowControls::generate_flow_control_id(),
+            tls: false,
+            ca_certificate: None,
+        }
+    }
+
+    /// Mark that this Connection is a Consumer for to the given [`FlowControlId`]
+    pub fn as_consumer(mut self, id: &FlowControlId) -> Self {
+        self.consumer.push(id.clone());
+
+        self
+    }
+
+    /// Connect using TLS (`wss://`), verifying the server certificate against
+    /// the platform trust store
+    pub fn with_tls(mut self) -> Self {
+        self.tls = true;
+
+        self
+    }
+
+    /// Connect using TLS (`wss://`), additionally trusting the PEM encoded
+    /// CA certificate(s) stored at the given path
+    pub fn with_ca_certificate(mut self, path: impl AsRef<Path>) -> Self {
+        self.tls = true;
+        self.ca_certificate = Some(path.as_ref().to_path_buf());
+
+        self
+    }
+
+    /// Getter for freshly generated [`FlowControlId`]
+    pub fn flow_control_id(&self) -> FlowControlId {
+        self.flow_control_id.clone()
+    }
+}
+
+impl WebSocketConnectionOptions {
+    pub(crate) fn setup_flow_control(&self, flow_controls: &FlowControls, addresses: &Addresses) {
+        flow_controls.add_producer(
+            addresses.receiver_address().clone(),
+            &self.flow_control_id,
+            None,
+            vec![addresses.sender_address().clone()],
+        );
+
+        for id in &self.consumer {
+            flow_controls.add_consumer(addresses.sender_address().clone(), id);
+        }
+    }
+
+    pub(crate) fn create_access_control(
+        &self,
+        flow_controls: &FlowControls,
+    ) -> WebSocketConnectionAccessControl {
+        WebSocketConnectionAccessControl {
+            sender_incoming_access_control: Arc::new(AllowAll),
+            receiver_outgoing_access_control: Arc::new(FlowControlOutgoingAccessControl::new(
+                flow_controls,
+                self.flow_control_id.clone(),
+                None,
+            )),
+        }
+    }
+}
+
+/// Trust and TLS options for a WebSocket listener
+#[derive(Debug)]
+pub struct WebSocketListenerOptions {
+    pub(crate) flow_control_id: FlowControlId,
+    pub(crate) tls: Option<WebSocketTlsCertificate>,
+}
+
+/// Paths to the PEM encoded certificate chain and private key used by a
+/// WebSocket listener to accept `wss://` connections
+pub type WebSocketTlsCertificate = TlsCertificate;
+
+impl WebSocketListenerOptions {
+    /// Mark this WebSocket Listener as a Spawner with given [`FlowControlId`].
+    /// NOTE: Spawned connections get fresh random [`FlowControlId`], however they are still marked
+    /// with Spawner's [`FlowControlId`]
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self {
+            flow_control_id: FlowControls::generate_flow_control_id(),
+            tls: None,
+        }
+    }
+
+    /// Accept `wss://` connections only, using the given PEM encoded
+    /// certificate chain and private key
+    pub fn with_tls(
+        mut self,
+        certificate: impl AsRef<Path>,
+        private_key: impl AsRef<Path>,
+    ) -> Self {
+        self.tls = Some(WebSocketTlsCertificate::new(
+            certificate.as_ref(),
+            private_key.as_ref(),
+        ));
+
+        self
+    }
+
+    /// Getter for freshly generated [`FlowControlId`]
+    pub fn spawner_flow_control_id(&self) -> FlowControlId {
+        self.flow_control_id.clone()
+    }
+}
+
+impl WebSocketListenerOptions {
+    pub(crate) fn setup_flow_control_for_listener(
+        &self,
+        flow_controls: &FlowControls,
+        address: &Address,
+    ) {
+        flow_controls.add_spawner(address.clone(), &self.flow_control_id);
+    }
+
+    pub(crate) fn setup_flow_control_for_connection(
+        &self,
+        flow_controls: &FlowControls,
+        addresses: &Addresses,
+    ) -> FlowControlId {
+        let flow_control_id = FlowControls::generate_flow_control_id();
+
+        flow_controls.add_producer(
+            addresses.receiver_address().clone(),
+            &flow_control_id,
+            Some(&self.flow_control_id),
+            vec![addresses.sender_address().clone()],
+        );
+
+        flow_control_id
+    }
+
+    pub(crate) fn create_access_control(
+        &self,
+        flow_controls: &FlowControls,
+        flow_control_id: FlowControlId,
+    ) -> WebSocketConnectionAccessControl {
+        WebSocketConnectionAccessControl {
+            sender_incoming_access_control: Arc::new(AllowAll),
+            receiver_outgoing_access_control: Arc::new(FlowControlOutgoingAccessControl::new(
+                flow_controls,
+                flow_control_id,
+                Some(self.flow_control_id.clone()),
+            )),
+        }
+    }
+}
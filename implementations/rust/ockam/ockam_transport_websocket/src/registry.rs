@@ -0,0 +1,53 @@
+use std::sync::{Arc, RwLock};
+
+use ockam_core::Address;
+
+use crate::{WebSocketConnection, WebSocketListener};
+
+/// Registry of all active listeners and connections of a WebSocket transport
+#[derive(Default, Clone)]
+pub struct WebSocketRegistry {
+    registry: Arc<RwLock<InternalRegistry>>,
+}
+
+#[derive(Default)]
+struct InternalRegistry {
+    listeners: Vec<WebSocketListener>,
+    connections: Vec<WebSocketConnection>,
+}
+
+impl WebSocketRegistry {
+    /// Return all active listeners
+    pub fn get_all_listeners(&self) -> Vec<WebSocketListener> {
+        self.registry.read().unwrap().listeners.clone()
+    }
+
+    /// Return all active connections, both outgoing and incoming
+    pub fn get_all_connections(&self) -> Vec<WebSocketConnection> {
+        self.registry.read().unwrap().connections.clone()
+    }
+
+    pub(crate) fn add_listener(&self, listener: WebSocketListener) {
+        self.registry.write().unwrap().listeners.push(listener);
+    }
+
+    pub(crate) fn remove_listener(&self, address: &Address) {
+        self.registry
+            .write()
+            .unwrap()
+            .listeners
+            .retain(|x| x.processor_address() != address);
+    }
+
+    pub(crate) fn add_connection(&self, connection: WebSocketConnection) {
+        self.registry.write().unwrap().connections.push(connection);
+    }
+
+    pub(crate) fn remove_connection(&self, sender_address: &Address) {
+        self.registry
+            .write()
+            .unwrap()
+            .connections
+            .retain(|x| x.sender_address() != sender_address);
+    }
+}
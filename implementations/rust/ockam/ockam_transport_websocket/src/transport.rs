@@ -1,11 +1,20 @@
-use core::fmt;
-use std::net::SocketAddr;
-use std::str::FromStr;
+use std::net::{SocketAddr, ToSocketAddrs};
 
-use ockam_core::{async_trait, Address, Result};
+use tokio::net::TcpStream;
+
+use ockam_core::{async_trait, Address, AsyncTryClone, Result};
 use ockam_node::{Context, HasContext};
+use ockam_transport_core::{tls, TransportError};
 
-use crate::{parse_socket_addr, WebSocketRouter, WebSocketRouterHandle, WS};
+use crate::error::WebSocketError;
+use crate::router::WebSocketRouter;
+use crate::workers::{
+    Addresses, TransportStream, WebSocketListenProcessor, WebSocketSendWorker, WebSocketStream,
+};
+use crate::{
+    parse_socket_addr, WebSocketConnection, WebSocketConnectionMode, WebSocketConnectionOptions,
+    WebSocketListener, WebSocketListenerOptions, WebSocketRegistry,
+};
 
 /// High level management interface for WebSocket transports.
 ///
@@ -23,30 +32,33 @@ use crate::{parse_socket_addr, WebSocketRouter, WebSocketRouterHandle, WS};
 /// establishing a connection upon arrival of an initial message.
 ///
 /// ```rust
-/// use ockam_transport_websocket::WebSocketTransport;
+/// use ockam_transport_websocket::{WebSocketConnectionOptions, WebSocketListenerOptions, WebSocketTransport};
 /// # use ockam_core::Result;
 /// # use ockam_node::Context;
 /// # async fn test(ctx: Context) -> Result<()> {
 /// let ws = WebSocketTransport::create(&ctx).await?;
-/// ws.listen("127.0.0.1:8000").await?; // Listen on port 8000
-/// ws.connect("127.0.0.1:5000").await?; // And connect to port 5000
+/// ws.listen("127.0.0.1:8000", WebSocketListenerOptions::new()).await?; // Listen on port 8000
+/// ws.connect("127.0.0.1:5000", WebSocketConnectionOptions::new()).await?; // And connect to port 5000
 /// # Ok(()) }
 /// ```
 ///
 /// The same `WebSocketTransport` can also bind to multiple ports.
 ///
 /// ```rust
-/// use ockam_transport_websocket::WebSocketTransport;
+/// use ockam_transport_websocket::{WebSocketListenerOptions, WebSocketTransport};
 /// # use ockam_core::{Address, Result};
 /// # use ockam_node::Context;
 /// # async fn test(ctx: Context) -> Result<()> {
 /// let ws = WebSocketTransport::create(&ctx).await?;
-/// ws.listen("127.0.0.1:8000").await?; // Listen on port 8000
-/// ws.listen("127.0.0.1:9000").await?; // Listen on port 9000
+/// ws.listen("127.0.0.1:8000", WebSocketListenerOptions::new()).await?; // Listen on port 8000
+/// ws.listen("127.0.0.1:9000", WebSocketListenerOptions::new()).await?; // Listen on port 9000
 /// # Ok(()) }
 /// ```
+#[derive(AsyncTryClone)]
+#[async_try_clone(crate = "ockam_core")]
 pub struct WebSocketTransport {
-    router_handle: WebSocketRouterHandle,
+    ctx: Context,
+    registry: WebSocketRegistry,
 }
 
 impl WebSocketTransport {
@@ -61,44 +73,139 @@ impl WebSocketTransport {
     /// # Ok(()) }
     /// ```
     pub async fn create(ctx: &Context) -> Result<WebSocketTransport> {
-        let router_handle = WebSocketRouter::register(ctx).await?;
-        Ok(Self { router_handle })
+        let registry = WebSocketRegistry::default();
+        WebSocketRouter::register(ctx, registry.clone()).await?;
+        Ok(Self {
+            ctx: ctx.async_try_clone().await?,
+            registry,
+        })
+    }
+
+    /// Registry of all active listeners and connections
+    pub fn registry(&self) -> &WebSocketRegistry {
+        &self.registry
     }
 
     /// Establish an outgoing WebSocket connection on an existing transport.
     ///
     /// ```rust
-    /// use ockam_transport_websocket::WebSocketTransport;
+    /// use ockam_transport_websocket::{WebSocketConnectionOptions, WebSocketListenerOptions, WebSocketTransport};
     /// # use ockam_node::Context;
     /// # use ockam_core::Result;
     /// # async fn test(ctx: Context) -> Result<()> {
     /// let ws = WebSocketTransport::create(&ctx).await?;
-    /// ws.listen("127.0.0.1:8000").await?; // Listen on port 8000
-    /// ws.connect("127.0.0.1:5000").await?; // and connect to port 5000
+    /// ws.listen("127.0.0.1:8000", WebSocketListenerOptions::new()).await?; // Listen on port 8000
+    /// let connection = ws.connect("127.0.0.1:5000", WebSocketConnectionOptions::new()).await?; // and connect to port 5000
     /// # Ok(()) }
     /// ```
-    pub async fn connect<S: AsRef<str>>(&self, peer: S) -> Result<()> {
-        self.router_handle.connect(peer).await
+    pub async fn connect(
+        &self,
+        peer: impl AsRef<str>,
+        options: WebSocketConnectionOptions,
+    ) -> Result<WebSocketConnection> {
+        let (socket_address, stream) = connect_stream(peer.as_ref(), &options).await?;
+
+        let mode = WebSocketConnectionMode::Outgoing;
+        let addresses = Addresses::generate(mode);
+        options.setup_flow_control(self.ctx.flow_controls(), &addresses);
+        let access_control = options.create_access_control(self.ctx.flow_controls());
+        let connection = WebSocketConnection::new(
+            addresses.sender_address().clone(),
+            addresses.receiver_address().clone(),
+            socket_address,
+            mode,
+            options.flow_control_id(),
+            options.tls,
+        );
+
+        WebSocketSendWorker::start(
+            &self.ctx,
+            self.registry.clone(),
+            stream,
+            &addresses,
+            connection.clone(),
+            access_control,
+        )
+        .await?;
+
+        Ok(connection)
+    }
+
+    /// Interrupt an active WebSocket connection given its sender `Address`
+    pub async fn disconnect(&self, address: impl Into<Address>) -> Result<()> {
+        self.ctx.stop_worker(address.into()).await
     }
 
     /// Start listening to incoming connections on an existing transport.
     ///
-    /// Returns the local address that this transport is bound to.
+    /// Returns the listener, including the local address that this transport is bound to.
     ///
     /// This can be useful, for example, when binding to port 0 to figure out
     /// which port was actually bound.
     ///
     /// ```rust
-    /// use ockam_transport_websocket::WebSocketTransport;
+    /// use ockam_transport_websocket::{WebSocketListenerOptions, WebSocketTransport};
     /// # use ockam_node::Context;
     /// # use ockam_core::Result;
     /// # async fn test(ctx: Context) -> Result<()> {
     /// let ws = WebSocketTransport::create(&ctx).await?;
-    /// ws.listen("127.0.0.1:8000").await?;
+    /// ws.listen("127.0.0.1:8000", WebSocketListenerOptions::new()).await?;
     /// # Ok(()) }
-    pub async fn listen<S: AsRef<str>>(&self, bind_addr: S) -> Result<SocketAddr> {
+    pub async fn listen(
+        &self,
+        bind_addr: impl AsRef<str>,
+        options: WebSocketListenerOptions,
+    ) -> Result<WebSocketListener> {
+        let flow_control_id = options.spawner_flow_control_id();
+        let tls = options.tls.is_some();
         let bind_addr = parse_socket_addr(bind_addr)?;
-        self.router_handle.bind(bind_addr).await
+        let (socket_address, address) =
+            WebSocketListenProcessor::start(&self.ctx, self.registry.clone(), bind_addr, options)
+                .await?;
+
+        Ok(WebSocketListener::new(
+            address,
+            socket_address,
+            flow_control_id,
+            tls,
+        ))
+    }
+
+    /// Interrupt an active WebSocket listener given its `Address`
+    pub async fn stop_listener(&self, address: &Address) -> Result<()> {
+        self.ctx.stop_processor(address.clone()).await
+    }
+
+    /// Search for a connection given its sender or receiver address, or its socket address
+    pub fn find_connection(&self, address: &str) -> Option<WebSocketConnection> {
+        let connections = self.registry.get_all_connections();
+        match address.parse::<SocketAddr>() {
+            Ok(socket_address) => connections
+                .into_iter()
+                .find(|c| c.socket_address() == &socket_address),
+            Err(_) => {
+                let address: Address = address.into();
+                connections
+                    .into_iter()
+                    .find(|c| c.sender_address() == &address || c.receiver_address() == &address)
+            }
+        }
+    }
+
+    /// Search for a listener given its processor address or its socket address
+    pub fn find_listener(&self, address: &str) -> Option<WebSocketListener> {
+        let listeners = self.registry.get_all_listeners();
+        match address.parse::<SocketAddr>() {
+            Ok(socket_address) => listeners
+                .into_iter()
+                .find(|l| l.socket_address() == &socket_address),
+            Err(_) => {
+                let address: Address = address.into();
+                listeners
+                    .into_iter()
+                    .find(|l| l.processor_address() == &address)
+            }
+        }
     }
 }
 
@@ -114,50 +221,50 @@ pub trait WebSocketTransportExtension: HasContext {
 
 impl<A: HasContext> WebSocketTransportExtension for A {}
 
-#[derive(Clone)]
-pub(crate) struct WebSocketAddress {
-    protocol: String,
-    socket_addr: SocketAddr,
-}
-
-impl From<WebSocketAddress> for Address {
-    fn from(other: WebSocketAddress) -> Self {
-        format!("{}#{}", WS, other.socket_addr).into()
-    }
-}
+/// Open a TCP connection to the given `host:port` peer and perform the
+/// TLS (if requested) and WebSocket handshakes over it.
+pub(crate) async fn connect_stream(
+    peer: &str,
+    options: &WebSocketConnectionOptions,
+) -> Result<(SocketAddr, WebSocketStream<TransportStream>)> {
+    let socket_address = resolve_peer(peer)?;
+    let tcp_stream = TcpStream::connect(socket_address)
+        .await
+        .map_err(TransportError::from)?;
 
-impl From<SocketAddr> for WebSocketAddress {
-    fn from(socket_addr: SocketAddr) -> Self {
-        Self {
-            protocol: "ws".to_string(),
-            socket_addr,
-        }
-    }
-}
+    let (scheme, stream) = if options.tls {
+        let connector = tls::create_connector(options.ca_certificate.as_deref(), None)?;
+        let server_name = tls::server_name(tls::host(peer))?;
+        let tls_stream = connector
+            .connect(server_name, tcp_stream)
+            .await
+            .map_err(|_| WebSocketError::Tls)?;
+        ("wss", TransportStream::Tls(Box::new(tls_stream.into())))
+    } else {
+        ("ws", TransportStream::Plain(tcp_stream))
+    };
 
-impl From<WebSocketAddress> for SocketAddr {
-    fn from(other: WebSocketAddress) -> Self {
-        other.socket_addr
-    }
-}
+    let (ws_stream, _) = tokio_tungstenite::client_async(format!("{scheme}://{peer}/"), stream)
+        .await
+        .map_err(WebSocketError::from)?;
 
-impl From<&WebSocketAddress> for String {
-    fn from(other: &WebSocketAddress) -> Self {
-        other.to_string()
-    }
+    Ok((socket_address, ws_stream))
 }
 
-impl FromStr for WebSocketAddress {
-    type Err = ockam_core::Error;
-
-    fn from_str(s: &str) -> Result<Self> {
-        let socket_addr = parse_socket_addr(s)?;
-        Ok(WebSocketAddress::from(socket_addr))
+/// Resolve the given `host:port` peer to a [`SocketAddr`], preferring IPv4
+fn resolve_peer(peer: &str) -> Result<SocketAddr> {
+    if let Ok(p) = parse_socket_addr(peer) {
+        return Ok(p);
     }
-}
 
-impl fmt::Display for WebSocketAddress {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "{}://{}", &self.protocol, &self.socket_addr)
-    }
+    let addrs: Vec<SocketAddr> = peer
+        .to_socket_addrs()
+        .map_err(|_| TransportError::InvalidAddress)?
+        .collect();
+    addrs
+        .iter()
+        .find(|x| x.is_ipv4())
+        .or_else(|| addrs.first())
+        .copied()
+        .ok_or_else(|| TransportError::InvalidAddress.into())
 }
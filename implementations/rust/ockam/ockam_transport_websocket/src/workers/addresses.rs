@@ -0,0 +1,34 @@
+use ockam_core::Address;
+
+use crate::WebSocketConnectionMode;
+
+#[derive(Clone, Debug)]
+pub(crate) struct Addresses {
+    /// Used to receive messages from other workers which are then serialized and sent over the wire
+    sender_address: Address,
+    /// Sender internal address used to schedule heartbeats
+    sender_internal_address: Address,
+    /// Receiver Processor Address
+    receiver_address: Address,
+}
+
+impl Addresses {
+    pub(crate) fn generate(mode: WebSocketConnectionMode) -> Self {
+        Self {
+            sender_address: Address::random_tagged(&format!("WebSocketSendWorker_tx_addr_{mode}")),
+            sender_internal_address: Address::random_tagged(&format!(
+                "WebSocketSendWorker_int_addr_{mode}"
+            )),
+            receiver_address: Address::random_tagged(&format!("WebSocketRecvProcessor_{mode}")),
+        }
+    }
+    pub(crate) fn sender_address(&self) -> &Address {
+        &self.sender_address
+    }
+    pub(crate) fn sender_internal_address(&self) -> &Address {
+        &self.sender_internal_address
+    }
+    pub(crate) fn receiver_address(&self) -> &Address {
+        &self.receiver_address
+    }
+}
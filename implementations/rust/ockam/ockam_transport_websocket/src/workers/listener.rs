@@ -1,46 +1,151 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
 
 use tokio::net::TcpListener;
+use tokio::time::timeout;
+use tokio_rustls::TlsAcceptor;
 
-use ockam_core::{async_trait, Address, AllowAll, Processor, Result};
+use ockam_core::{async_trait, Address, DenyAll, Processor, Result};
 use ockam_node::Context;
-use ockam_transport_core::TransportError;
+use ockam_transport_core::{tls, TransportError};
 
-use crate::{error::WebSocketError, workers::WorkerPair, WebSocketRouterHandle};
+use crate::error::WebSocketError;
+use crate::workers::{Addresses, TransportStream, WebSocketSendWorker, WebSocketStream};
+use crate::{
+    WebSocketConnection, WebSocketConnectionMode, WebSocketListener, WebSocketListenerOptions,
+    WebSocketRegistry,
+};
+
+/// Maximum duration of the TLS and WebSocket handshakes of an incoming connection
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
 
 /// A worker that runs in the background as a `Processor` waiting for incoming
 /// clients' connections.
 ///
-/// When a new connection is established, a new `WorkerPair` is spawned and
-/// registered by the router.
+/// When a new connection is established, its handshakes run in a separate task
+/// so that a slow or silent peer does not block the other connections. Once they
+/// succeed, a new pair of sender worker and receiver processor is spawned for it.
 pub(crate) struct WebSocketListenProcessor {
     inner: TcpListener,
-    router_handle: WebSocketRouterHandle,
+    socket_address: SocketAddr,
+    handler: ConnectionHandler,
+}
+
+/// State shared with the tasks accepting incoming connections
+#[derive(Clone)]
+struct ConnectionHandler {
+    registry: WebSocketRegistry,
+    acceptor: Option<TlsAcceptor>,
+    options: Arc<WebSocketListenerOptions>,
 }
 
 impl WebSocketListenProcessor {
     /// Create and start a new instance bound to the given `addr`.
     pub(crate) async fn start(
         ctx: &Context,
-        router_handle: WebSocketRouterHandle,
+        registry: WebSocketRegistry,
         addr: SocketAddr,
-    ) -> Result<SocketAddr> {
+        options: WebSocketListenerOptions,
+    ) -> Result<(SocketAddr, Address)> {
         debug!("Binding WebSocketListener to {}", addr);
+        // Load the certificate before binding so that a misconfiguration
+        // is reported to the caller
+        let acceptor = match &options.tls {
+            Some(certificate) => Some(tls::create_acceptor(certificate, None)?),
+            None => None,
+        };
         let inner = TcpListener::bind(addr)
             .await
             .map_err(TransportError::from)?;
-        let saddr = inner.local_addr().map_err(TransportError::from)?;
+        let socket_address = inner.local_addr().map_err(TransportError::from)?;
+
+        let address = Address::random_tagged("WebSocketListenProcessor");
+        options.setup_flow_control_for_listener(ctx.flow_controls(), &address);
+        registry.add_listener(WebSocketListener::new(
+            address.clone(),
+            socket_address,
+            options.spawner_flow_control_id(),
+            acceptor.is_some(),
+        ));
+
         let processor = Self {
             inner,
-            router_handle,
+            socket_address,
+            handler: ConnectionHandler {
+                registry,
+                acceptor,
+                options: Arc::new(options),
+            },
         };
-        let waddr = Address::random_tagged("WebSocketListenProcessor");
-        ctx.start_processor_with_access_control(
-            waddr, processor, AllowAll, // FIXME: @ac
-            AllowAll, // FIXME: @ac
+        ctx.start_processor_with_access_control(address.clone(), processor, DenyAll, DenyAll)
+            .await?;
+
+        Ok((socket_address, address))
+    }
+}
+
+impl ConnectionHandler {
+    /// Perform the handshakes with a new peer and start the workers of its connection
+    async fn accept(
+        self,
+        ctx: Context,
+        tcp_stream: tokio::net::TcpStream,
+        peer: SocketAddr,
+    ) -> Result<()> {
+        let ws_stream = timeout(HANDSHAKE_TIMEOUT, self.handshake(tcp_stream))
+            .await
+            .map_err(|_| WebSocketError::HandshakeTimeout)??;
+        debug!("WebSocket connection accepted from {}", peer);
+
+        let mode = WebSocketConnectionMode::Incoming;
+        let addresses = Addresses::generate(mode);
+        let flow_control_id = self
+            .options
+            .setup_flow_control_for_connection(ctx.flow_controls(), &addresses);
+        let access_control = self
+            .options
+            .create_access_control(ctx.flow_controls(), flow_control_id.clone());
+        let connection = WebSocketConnection::new(
+            addresses.sender_address().clone(),
+            addresses.receiver_address().clone(),
+            peer,
+            mode,
+            flow_control_id,
+            self.acceptor.is_some(),
+        );
+
+        // Spawn a connection worker pair for it
+        WebSocketSendWorker::start(
+            &ctx,
+            self.registry,
+            ws_stream,
+            &addresses,
+            connection,
+            access_control,
         )
-        .await?;
-        Ok(saddr)
+        .await
+    }
+
+    /// Perform the TLS (if configured) and WebSocket handshakes.
+    async fn handshake(
+        &self,
+        tcp_stream: tokio::net::TcpStream,
+    ) -> Result<WebSocketStream<TransportStream>> {
+        let stream = match &self.acceptor {
+            Some(acceptor) => {
+                let tls_stream = acceptor
+                    .accept(tcp_stream)
+                    .await
+                    .map_err(|_| WebSocketError::Tls)?;
+                TransportStream::Tls(Box::new(tls_stream.into()))
+            }
+            None => TransportStream::Plain(tcp_stream),
+        };
+
+        Ok(tokio_tungstenite::accept_async(stream)
+            .await
+            .map_err(WebSocketError::from)?)
     }
 }
 
@@ -52,22 +157,36 @@ impl Processor for WebSocketListenProcessor {
         ctx.set_cluster(crate::CLUSTER_NAME).await
     }
 
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.handler.registry.remove_listener(&ctx.address());
+
+        Ok(())
+    }
+
     async fn process(&mut self, ctx: &mut Self::Context) -> Result<bool> {
         debug!("Waiting for incoming TCP connection...");
 
         // Wait for an incoming connection
         let (tcp_stream, peer) = self.inner.accept().await.map_err(TransportError::from)?;
-        let ws_stream = tokio_tungstenite::accept_async(tcp_stream)
-            .await
-            .map_err(WebSocketError::from)?;
-        debug!("TCP connection accepted");
-
-        // Spawn a connection worker for it
-        let pair = WorkerPair::from_server(ctx, ws_stream, peer, vec![]).await?;
 
-        // Register the connection with the local TcpRouter
-        self.router_handle.register(&pair).await?;
-        debug!("TCP connection registered");
+        // A failed handshake only affects that peer, keep on listening
+        let handler = self.handler.clone();
+        let socket_address = self.socket_address;
+        let ctx = ctx
+            .new_detached(
+                Address::random_tagged("WebSocketListenProcessor.accept"),
+                DenyAll,
+                DenyAll,
+            )
+            .await?;
+        tokio::spawn(async move {
+            if let Err(e) = handler.accept(ctx, tcp_stream, peer).await {
+                warn!(
+                    "WebSocket handshake with {} on {} failed: {}",
+                    peer, socket_address, e
+                );
+            }
+        });
 
         Ok(true)
     }
@@ -1,48 +1,60 @@
 use std::net::SocketAddr;
+use std::sync::Arc;
 
 use futures_util::stream::SplitStream;
 use futures_util::StreamExt;
-use tokio_tungstenite::WebSocketStream;
 
-use crate::WebSocketAddress;
 use ockam_core::{
-    async_trait, Address, Decodable, LocalMessage, Processor, Result, TransportMessage,
+    async_trait, Decodable, DenyAll, LocalMessage, Mailbox, Mailboxes, OutgoingAccessControl,
+    Processor, Result, TransportMessage,
 };
-use ockam_node::Context;
+use ockam_node::{Context, ProcessorBuilder};
 use ockam_transport_core::TransportError;
 
-use crate::workers::AsyncStream;
+use crate::workers::{Addresses, TransportStream, WebSocketStream};
 
-/// A WebSocket receiving message worker.
+/// A WebSocket receiving message processor.
 ///
-/// This half of the worker is created when spawning a new connection
-/// worker pair, and listens for messages received to the WebSocket stream
-/// from the remote peer.
-pub(crate) struct WebSocketRecvProcessor<S>
-where
-    S: AsyncStream,
-{
-    ws_stream: SplitStream<WebSocketStream<S>>,
-    peer_addr: Address,
+/// This half of the connection is created together with its
+/// `WebSocketSendWorker`, and listens for messages received to the
+/// WebSocket stream from the remote peer.
+pub(crate) struct WebSocketRecvProcessor {
+    ws_stream: SplitStream<WebSocketStream<TransportStream>>,
+    addresses: Addresses,
+    peer: SocketAddr,
 }
 
-impl<S> WebSocketRecvProcessor<S>
-where
-    S: AsyncStream,
-{
-    pub(crate) fn new(ws_stream: SplitStream<WebSocketStream<S>>, peer: SocketAddr) -> Self {
-        Self {
+impl WebSocketRecvProcessor {
+    /// Create and start a new receiver for the given half of a WebSocket stream.
+    pub(crate) async fn start(
+        ctx: &Context,
+        ws_stream: SplitStream<WebSocketStream<TransportStream>>,
+        addresses: &Addresses,
+        peer: SocketAddr,
+        receiver_outgoing_access_control: Arc<dyn OutgoingAccessControl>,
+    ) -> Result<()> {
+        let receiver = Self {
             ws_stream,
-            peer_addr: WebSocketAddress::from(peer).into(),
-        }
+            addresses: addresses.clone(),
+            peer,
+        };
+
+        let mailbox = Mailbox::new(
+            addresses.receiver_address().clone(),
+            Arc::new(DenyAll),
+            receiver_outgoing_access_control,
+        );
+        ProcessorBuilder::new(receiver)
+            .with_mailboxes(Mailboxes::new(mailbox, vec![]))
+            .start(ctx)
+            .await?;
+
+        Ok(())
     }
 }
 
 #[async_trait]
-impl<S> Processor for WebSocketRecvProcessor<S>
-where
-    S: AsyncStream,
-{
+impl Processor for WebSocketRecvProcessor {
     type Context = Context;
 
     async fn initialize(&mut self, ctx: &mut Context) -> Result<()> {
@@ -53,27 +65,37 @@ where
     /// any available, and forward it to the next hop in the route.
     async fn process(&mut self, ctx: &mut Context) -> Result<bool> {
         // Get next message from the stream or abort if the stream is
-        // either closed or exhausted.
+        // either closed or exhausted. In both cases the sender is stopped
+        // as well so that the connection is released.
         let ws_msg = match self.ws_stream.next().await {
-            Some(res) => match res {
-                Ok(ws_msg) => ws_msg,
-                Err(_e) => {
-                    info!(
-                        "Connection to peer '{}' was closed; dropping stream",
-                        self.peer_addr
-                    );
-                    return Ok(false);
-                }
-            },
+            Some(Ok(ws_msg)) => ws_msg,
+            Some(Err(_e)) => {
+                info!(
+                    "Connection to peer '{}' was closed; dropping stream",
+                    self.peer
+                );
+                let _ = ctx
+                    .stop_worker(self.addresses.sender_address().clone())
+                    .await;
+                return Ok(false);
+            }
             None => {
                 info!(
                     "Stream connected to peer '{}' is exhausted; dropping stream",
-                    self.peer_addr
+                    self.peer
                 );
+                let _ = ctx
+                    .stop_worker(self.addresses.sender_address().clone())
+                    .await;
                 return Ok(false);
             }
         };
 
+        // Control frames are handled by tungstenite itself
+        if !(ws_msg.is_binary() || ws_msg.is_text()) {
+            return Ok(true);
+        }
+
         // Extract message payload
         let encoded_msg = ws_msg.into_data();
 
@@ -83,12 +105,15 @@ where
 
         // Heartbeat message
         if msg.onward_route.next().is_err() {
-            trace!("Got heartbeat message from: {}", self.peer_addr);
+            trace!("Got heartbeat message from: {}", self.peer);
+            return Ok(true);
         }
 
-        // Insert the peer address into the return route so that
+        // Insert the sender address into the return route so that
         // reply routing can be properly resolved
-        msg.return_route.modify().prepend(self.peer_addr.clone());
+        msg.return_route
+            .modify()
+            .prepend(self.addresses.sender_address().clone());
 
         // Some verbose logging we may want to remove
         trace!("Message onward route: {}", msg.onward_route);
@@ -1,223 +1,157 @@
-use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 
-use futures_util::stream::{SplitSink, SplitStream};
+use futures_util::stream::SplitSink;
 use futures_util::{SinkExt, StreamExt};
 use tokio_tungstenite::tungstenite::protocol::Message as WebSocketMessage;
 
-use crate::error::WebSocketError;
 use ockam_core::{
-    async_trait, route, Address, AllowAll, Any, Decodable, Encodable, LocalMessage, Mailbox,
-    Mailboxes, Result, Routed, TransportMessage, Worker,
+    async_trait, route, AllowAll, Any, DenyAll, Encodable, Mailbox, Mailboxes, Result, Routed,
+    TransportMessage, Worker,
 };
 use ockam_node::{Context, DelayedEvent, WorkerBuilder};
-use ockam_transport_core::TransportError;
 
-use crate::workers::{
-    AsyncStream, TcpClientStream, TcpServerStream, WebSocketRecvProcessor, WebSocketStream,
-};
-use crate::WebSocketAddress;
+use crate::options::WebSocketConnectionAccessControl;
+use crate::workers::{Addresses, TransportStream, WebSocketRecvProcessor, WebSocketStream};
+use crate::{WebSocketConnection, WebSocketRegistry};
 
-/// Transmit and receive peers of a WebSocket connection.
-#[derive(Debug)]
-pub(crate) struct WorkerPair {
-    hostnames: Vec<String>,
-    peer: Address,
-    tx_addr: Address,
+/// A WebSocket sending message worker.
+///
+/// This half of the connection is created together with its
+/// `WebSocketRecvProcessor`, and listens for messages from the node
+/// message system to dispatch to a remote peer.
+pub(crate) struct WebSocketSendWorker {
+    registry: WebSocketRegistry,
+    ws_sink: SplitSink<WebSocketStream<TransportStream>, WebSocketMessage>,
+    connection: WebSocketConnection,
+    addresses: Addresses,
+    heartbeat: DelayedEvent<Vec<u8>>,
+    heartbeat_interval: Option<Duration>,
 }
 
-impl WorkerPair {
-    pub(crate) fn hostnames(&self) -> &[String] {
-        &self.hostnames
-    }
-    pub(crate) fn peer(&self) -> Address {
-        self.peer.clone()
-    }
-    pub(crate) fn tx_addr(&self) -> Address {
-        self.tx_addr.clone()
-    }
-
-    /// Spawn instances of `WebSocketSendWorker` and `WebSocketRecvProcessor` and
-    /// returns a `WorkerPair` instance that will be registered by the `WebSocketRouter`.
-    ///
-    /// The WebSocket stream is created when the `WebSocketSendWorker` is initialized.
-    pub(crate) async fn from_client(
+impl WebSocketSendWorker {
+    /// Split the given stream and start both the sender worker and the
+    /// receiver processor of the connection. The connection is registered
+    /// until the sender worker stops.
+    pub(crate) async fn start(
         ctx: &Context,
-        peer: SocketAddr,
-        hostnames: Vec<String>,
-    ) -> Result<WorkerPair> {
-        trace!("Creating new WS worker pair");
-
-        let internal_addr = Address::random_tagged("WebSocketSender.internal.from_client");
-        let sender = WebSocketSendWorker::<TcpClientStream>::new(
-            peer,
-            internal_addr.clone(),
-            DelayedEvent::create(ctx, internal_addr.clone(), vec![]).await?,
-        );
-
-        let tx_addr = Address::random_tagged("WebSocketSender.tx_addr.from_client");
+        registry: WebSocketRegistry,
+        stream: WebSocketStream<TransportStream>,
+        addresses: &Addresses,
+        connection: WebSocketConnection,
+        access_control: WebSocketConnectionAccessControl,
+    ) -> Result<()> {
+        let (ws_sink, ws_stream) = stream.split();
+        let peer = *connection.socket_address();
+
+        let sender = Self {
+            registry: registry.clone(),
+            ws_sink,
+            connection: connection.clone(),
+            addresses: addresses.clone(),
+            heartbeat: DelayedEvent::create(
+                ctx,
+                addresses.sender_internal_address().clone(),
+                vec![],
+            )
+            .await?,
+            heartbeat_interval: None,
+        };
 
         let mailboxes = Mailboxes::new(
             Mailbox::new(
-                tx_addr.clone(),
-                Arc::new(AllowAll), // FIXME: @ac
-                Arc::new(AllowAll), // FIXME: @ac
+                addresses.sender_address().clone(),
+                access_control.sender_incoming_access_control,
+                Arc::new(DenyAll),
             ),
             vec![Mailbox::new(
-                internal_addr,
-                Arc::new(AllowAll), // FIXME: @ac
-                Arc::new(AllowAll), // FIXME: @ac
+                addresses.sender_internal_address().clone(),
+                Arc::new(AllowAll),
+                Arc::new(DenyAll),
             )],
         );
         WorkerBuilder::new(sender)
             .with_mailboxes(mailboxes)
             .start(ctx)
             .await?;
+        registry.add_connection(connection);
 
-        // Return a handle to the worker pair
-        Ok(WorkerPair {
-            hostnames,
-            peer: WebSocketAddress::from(peer).into(),
-            tx_addr,
-        })
-    }
-
-    /// Spawn instances of `WebSocketSendWorker` and `WebSocketRecvProcessor` and
-    /// returns a `WorkerPair` instance that will be registered by the `WebSocketRouter`.
-    pub(crate) async fn from_server(
-        ctx: &Context,
-        stream: WebSocketStream<TcpServerStream>,
-        peer: SocketAddr,
-        hostnames: Vec<String>,
-    ) -> Result<WorkerPair> {
-        trace!("Creating new WS worker pair");
-
-        let internal_addr = Address::random_tagged("WebSocketSender.internal.from_server");
-        let sender = WebSocketSendWorker::<TcpServerStream>::new(
-            stream,
+        WebSocketRecvProcessor::start(
+            ctx,
+            ws_stream,
+            addresses,
             peer,
-            internal_addr.clone(),
-            DelayedEvent::create(ctx, internal_addr.clone(), vec![]).await?,
-        );
+            access_control.receiver_outgoing_access_control,
+        )
+        .await
+    }
 
-        let tx_addr = Address::random_tagged("WebSocketSender.tx_addr.from_server");
-        let mailboxes = Mailboxes::new(
-            Mailbox::new(
-                tx_addr.clone(),
-                Arc::new(AllowAll), // FIXME: @ac
-                Arc::new(AllowAll), // FIXME: @ac
-            ),
-            vec![Mailbox::new(
-                internal_addr,
-                Arc::new(AllowAll), // FIXME: @ac
-                Arc::new(AllowAll), // FIXME: @ac
-            )],
-        );
-        WorkerBuilder::new(sender)
-            .with_mailboxes(mailboxes)
-            .start(ctx)
-            .await?;
+    async fn schedule_heartbeat(&mut self) -> Result<()> {
+        let heartbeat_interval = match &self.heartbeat_interval {
+            Some(hi) => *hi,
+            None => return Ok(()),
+        };
 
-        // Return a handle to the worker pair
-        Ok(WorkerPair {
-            hostnames,
-            peer: WebSocketAddress::from(peer).into(),
-            tx_addr,
-        })
+        self.heartbeat.schedule(heartbeat_interval).await
     }
 }
 
-/// A WebSocket sending message worker.
-///
-/// This half of the worker is created when spawning a new connection
-/// worker pair, and listens for messages from the node message system
-/// to dispatch to a remote peer.
-pub(crate) struct WebSocketSendWorker<S>
-where
-    S: AsyncStream,
-{
-    ws_stream: Option<SplitStream<WebSocketStream<S>>>,
-    ws_sink: Option<SplitSink<WebSocketStream<S>, WebSocketMessage>>,
-    peer: SocketAddr,
-    internal_addr: Address,
-    heartbeat: DelayedEvent<Vec<u8>>,
-    heartbeat_interval: Option<Duration>,
-}
-
-impl<S> WebSocketSendWorker<S>
-where
-    S: AsyncStream,
-{
-    async fn handle_initialize(&mut self, ctx: &mut Context) -> Result<()> {
-        if let Some(ws_stream) = self.ws_stream.take() {
-            let rx_addr = Address::random_tagged("WebSocketSendWorker.rx_addr");
-            let receiver = WebSocketRecvProcessor::new(ws_stream, self.peer);
-            ctx.start_processor_with_access_control(
-                rx_addr.clone(),
-                receiver,
-                AllowAll, // FIXME: @ac
-                AllowAll, // FIXME: @ac
-            )
-            .await?;
-        } else {
-            return Err(TransportError::GenericIo.into());
-        }
+#[async_trait]
+impl Worker for WebSocketSendWorker {
+    type Message = Any;
+    type Context = Context;
 
+    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
         ctx.set_cluster(crate::CLUSTER_NAME).await?;
-        self.schedule_heartbeat().await?;
-        Ok(())
+        self.schedule_heartbeat().await
     }
 
-    async fn schedule_heartbeat(&mut self) -> Result<()> {
-        let heartbeat_interval = match &self.heartbeat_interval {
-            Some(hi) => *hi,
-            None => return Ok(()),
-        };
+    async fn shutdown(&mut self, ctx: &mut Self::Context) -> Result<()> {
+        self.registry
+            .remove_connection(self.addresses.sender_address());
+        let _ = self.ws_sink.close().await;
+        let _ = ctx
+            .stop_processor(self.addresses.receiver_address().clone())
+            .await;
 
-        self.heartbeat.schedule(heartbeat_interval).await
+        Ok(())
     }
 
-    /// Receive messages from the `WebSocketRouter` to send
-    /// across the `WebSocketStream` to the next remote peer.
-    async fn handle_msg(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
+    /// Receive messages from other workers to send
+    /// across the WebSocket stream to the remote peer.
+    async fn handle_message(&mut self, ctx: &mut Context, msg: Routed<Any>) -> Result<()> {
         self.heartbeat.cancel();
+        let peer = *self.connection.socket_address();
 
-        let ws_sink = if let Some(ws_sink) = &mut self.ws_sink {
-            ws_sink
-        } else {
-            return Err(TransportError::PeerNotFound.into());
-        };
-
-        let recipient = msg.msg_addr();
-        if recipient == self.internal_addr {
+        if msg.msg_addr() == *self.addresses.sender_internal_address() {
             let msg = TransportMessage::v1(route![], route![], vec![]);
             // Sending empty heartbeat
-            if ws_sink
+            if self
+                .ws_sink
                 .send(WebSocketMessage::from(msg.encode()?))
                 .await
                 .is_err()
             {
-                warn!("Failed to send heartbeat to peer {}", self.peer);
+                warn!("Failed to send heartbeat to peer {}", peer);
                 ctx.stop_worker(ctx.address()).await?;
 
                 return Ok(());
             }
-            debug!("Sent heartbeat to peer {}", self.peer);
+            debug!("Sent heartbeat to peer {}", peer);
         } else {
-            let mut msg = LocalMessage::decode(msg.payload())?.into_transport_message();
+            let mut msg = msg.into_transport_message();
 
             // Remove our own address from the route so the other end
             // knows what to do with the incoming message
             msg.onward_route.step()?;
 
             let msg = WebSocketMessage::from(msg.encode()?);
-            if ws_sink.send(msg).await.is_err() {
-                warn!("Failed to send message to peer {}", self.peer);
+            if self.ws_sink.send(msg).await.is_err() {
+                warn!("Failed to send message to peer {}", peer);
                 ctx.stop_worker(ctx.address()).await?;
                 return Ok(());
             }
-            debug!("Sent message to peer {}", self.peer);
+            debug!("Sent message to peer {}", peer);
         }
 
         self.schedule_heartbeat().await?;
@@ -225,87 +159,3 @@ where
         Ok(())
     }
 }
-
-impl WebSocketSendWorker<TcpServerStream> {
-    fn new(
-        stream: WebSocketStream<TcpServerStream>,
-        peer: SocketAddr,
-        internal_addr: Address,
-        heartbeat: DelayedEvent<Vec<u8>>,
-    ) -> Self {
-        let (ws_sink, ws_stream) = stream.split();
-        Self {
-            ws_sink: Some(ws_sink),
-            ws_stream: Some(ws_stream),
-            peer,
-            internal_addr,
-            heartbeat,
-            heartbeat_interval: None,
-        }
-    }
-}
-
-impl WebSocketSendWorker<TcpClientStream> {
-    fn new(peer: SocketAddr, internal_addr: Address, heartbeat: DelayedEvent<Vec<u8>>) -> Self {
-        Self {
-            ws_stream: None,
-            ws_sink: None,
-            peer,
-            internal_addr,
-            heartbeat,
-            heartbeat_interval: None,
-        }
-    }
-
-    async fn initialize_stream(&mut self) -> Result<()> {
-        if self.ws_stream.is_none() {
-            let peer = WebSocketAddress::from(self.peer).to_string();
-            let (stream, _) = tokio_tungstenite::connect_async(peer)
-                .await
-                .map_err(WebSocketError::from)?;
-            let (ws_sink, ws_stream) = stream.split();
-            self.ws_sink = Some(ws_sink);
-            self.ws_stream = Some(ws_stream);
-        }
-        Ok(())
-    }
-}
-
-#[async_trait]
-impl Worker for WebSocketSendWorker<TcpServerStream> {
-    type Message = Any;
-    type Context = Context;
-
-    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
-        self.handle_initialize(ctx).await?;
-        Ok(())
-    }
-
-    async fn handle_message(
-        &mut self,
-        ctx: &mut Context,
-        msg: Routed<Self::Message>,
-    ) -> Result<()> {
-        self.handle_msg(ctx, msg).await
-    }
-}
-
-#[async_trait]
-impl Worker for WebSocketSendWorker<TcpClientStream> {
-    type Message = Any;
-    type Context = Context;
-
-    async fn initialize(&mut self, ctx: &mut Self::Context) -> Result<()> {
-        self.initialize_stream().await?;
-        self.handle_initialize(ctx).await?;
-        Ok(())
-    }
-
-    async fn handle_message(
-        &mut self,
-        ctx: &mut Context,
-        msg: Routed<Self::Message>,
-    ) -> Result<()> {
-        self.handle_msg(ctx, msg).await
-    }
-}
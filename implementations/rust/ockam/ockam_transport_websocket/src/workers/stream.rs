@@ -1,22 +1,56 @@
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpStream;
+
 /// Type alias for `tokio_tungstenite::WebSocketStream`.
 pub(crate) type WebSocketStream<S> = tokio_tungstenite::WebSocketStream<S>;
 
-/// Stream created when a server accepts a new connection.
-pub(crate) type TcpServerStream = tokio::net::TcpStream;
-
-/// Stream created when a client connects to a server.
-pub(crate) type TcpClientStream = tokio_tungstenite::MaybeTlsStream<TcpServerStream>;
+/// Stream underlying a WebSocket connection, either plain TCP (`ws://`)
+/// or TCP wrapped in TLS (`wss://`).
+pub(crate) enum TransportStream {
+    Plain(TcpStream),
+    Tls(Box<tokio_rustls::TlsStream<TcpStream>>),
+}
 
-/// Trait alias to define an AsyncStream returned
-/// when creating or accepting WebSocket connections.
-///
-/// This is used to reduce the complexity of the definition
-/// of the structs that use WebSocket streams.
-pub(crate) trait AsyncStream:
-    tokio::io::AsyncRead + tokio::io::AsyncWrite + Unpin + Send + 'static
-{
+impl AsyncRead for TransportStream {
+    fn poll_read(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(s) => Pin::new(s).poll_read(cx, buf),
+            Self::Tls(s) => Pin::new(s.as_mut()).poll_read(cx, buf),
+        }
+    }
 }
 
-impl AsyncStream for TcpClientStream {}
+impl AsyncWrite for TransportStream {
+    fn poll_write(
+        self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        match self.get_mut() {
+            Self::Plain(s) => Pin::new(s).poll_write(cx, buf),
+            Self::Tls(s) => Pin::new(s.as_mut()).poll_write(cx, buf),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(s) => Pin::new(s).poll_flush(cx),
+            Self::Tls(s) => Pin::new(s.as_mut()).poll_flush(cx),
+        }
+    }
 
-impl AsyncStream for TcpServerStream {}
+    fn poll_shutdown(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        match self.get_mut() {
+            Self::Plain(s) => Pin::new(s).poll_shutdown(cx),
+            Self::Tls(s) => Pin::new(s.as_mut()).poll_shutdown(cx),
+        }
+    }
+}
@@ -1,26 +1,40 @@
 use ockam_core::compat::rand::{self, Rng};
 use ockam_core::{route, Result, Routed, Worker};
 use ockam_node::Context;
-use ockam_transport_websocket::{WebSocketTransport, WS};
+use ockam_transport_websocket::{
+    WebSocketConnectionOptions, WebSocketListenerOptions, WebSocketTransport, WS,
+};
 
-#[ignore]
 #[ockam_macros::test]
 async fn send_receive(ctx: &mut Context) -> Result<()> {
     let transport = WebSocketTransport::create(ctx).await?;
-    let listener_address = transport.listen("127.0.0.1:0").await?;
+    let options = WebSocketListenerOptions::new();
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    let listener = transport.listen("127.0.0.1:0", options).await?;
     ctx.start_worker("echoer", Echoer).await?;
 
     // Sender
     {
-        let msg: String = rand::thread_rng()
-            .sample_iter(&rand::distributions::Alphanumeric)
-            .take(256)
-            .map(char::from)
-            .collect();
-        let r = route![(WS, listener_address.to_string()), "echoer"];
+        let msg = random_message();
+        let connection = transport
+            .connect(
+                listener.socket_address().to_string(),
+                WebSocketConnectionOptions::new(),
+            )
+            .await?;
+        let r = route![connection.clone(), "echoer"];
         let reply = ctx.send_and_receive::<String>(r, msg.clone()).await?;
 
         assert_eq!(reply, msg, "Should receive the same message");
+        assert_eq!(transport.registry().get_all_listeners().len(), 1);
+        assert!(transport
+            .find_connection(&connection.sender_address().to_string())
+            .is_some());
+
+        transport
+            .disconnect(connection.sender_address().clone())
+            .await?;
     };
 
     if let Err(e) = ctx.stop().await {
@@ -30,6 +44,75 @@ async fn send_receive(ctx: &mut Context) -> Result<()> {
     Ok(())
 }
 
+#[ockam_macros::test]
+async fn send_receive_through_router(ctx: &mut Context) -> Result<()> {
+    let transport = WebSocketTransport::create(ctx).await?;
+    let options = WebSocketListenerOptions::new();
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+    ctx.start_worker("echoer", Echoer).await?;
+
+    let msg = random_message();
+    let r = route![(WS, listener.socket_address().to_string()), "echoer"];
+    let reply = ctx.send_and_receive::<String>(r, msg.clone()).await?;
+
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn silent_peer_does_not_block_the_listener(ctx: &mut Context) -> Result<()> {
+    let transport = WebSocketTransport::create(ctx).await?;
+    let options = WebSocketListenerOptions::new();
+    ctx.flow_controls()
+        .add_consumer("echoer", &options.spawner_flow_control_id());
+    let listener = transport.listen("127.0.0.1:0", options).await?;
+    ctx.start_worker("echoer", Echoer).await?;
+
+    // This peer opens a TCP connection but never starts the WebSocket handshake
+    let _silent_peer = tokio::net::TcpStream::connect(listener.socket_address())
+        .await
+        .unwrap();
+
+    let msg = random_message();
+    let r = route![(WS, listener.socket_address().to_string()), "echoer"];
+    let reply = ctx.send_and_receive::<String>(r, msg.clone()).await?;
+    assert_eq!(reply, msg, "Should receive the same message");
+
+    if let Err(e) = ctx.stop().await {
+        println!("Unclean stop: {}", e)
+    }
+
+    Ok(())
+}
+
+#[ockam_macros::test]
+async fn listen_with_missing_certificate(ctx: &mut Context) -> Result<()> {
+    let transport = WebSocketTransport::create(ctx).await?;
+    let options =
+        WebSocketListenerOptions::new().with_tls("does/not/exist.pem", "does/not/exist.key");
+    let res = transport.listen("127.0.0.1:0", options).await;
+
+    assert!(res.is_err());
+    assert!(transport.registry().get_all_listeners().is_empty());
+
+    ctx.stop().await
+}
+
+fn random_message() -> String {
+    rand::thread_rng()
+        .sample_iter(&rand::distributions::Alphanumeric)
+        .take(256)
+        .map(char::from)
+        .collect()
+}
+
 pub struct Echoer;
 
 #[ockam_core::worker]
@@ -33,6 +33,10 @@ pub enum VaultError {
     InvalidSha256Len,
     /// Invalid Signature Size
     InvalidSignatureSize,
+    /// The secrets are encrypted but no wrapping key was provided
+    MissingWrappingKey,
+    /// The wrapping key can not decrypt the secrets
+    InvalidWrappingKey,
 }
 
 impl ockam_core::compat::error::Error for VaultError {}
@@ -54,6 +58,13 @@ impl core::fmt::Display for VaultError {
             Self::KeyNotFound => write!(f, "key not found"),
             Self::InvalidSha256Len => write!(f, "invalid sha256 len"),
             Self::InvalidSignatureSize => write!(f, "invalid signature len"),
+            Self::MissingWrappingKey => write!(
+                f,
+                "the vault secrets are encrypted, a wrapping key is required to open the vault"
+            ),
+            Self::InvalidWrappingKey => {
+                write!(f, "the wrapping key can not decrypt the vault secrets")
+            }
         }
     }
 }
@@ -65,6 +76,7 @@ impl From<VaultError> for Error {
         let kind = match err {
             InvalidPublicKey | InvalidKeyType | InvalidHkdfOutputType => Kind::Misuse,
             UnknownEcdhKeyType => Kind::NotFound,
+            MissingWrappingKey | InvalidWrappingKey => Kind::Unsupported,
             _ => Kind::Invalid,
         };
 
@@ -0,0 +1,217 @@
+//! ML-KEM-768 key encapsulation mechanism, as defined in [FIPS 203][1].
+//!
+//! The algorithms are provided by the RustCrypto [`ml-kem`][2] crate. This module only converts
+//! the keys and ciphertexts from and to their encoded form and checks the encapsulation keys
+//! received from a peer. It is tested against the ML-KEM-768 vectors of the NIST [ACVP server][3].
+//!
+//! [1]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.203.pdf
+//! [2]: https://crates.io/crates/ml-kem
+//! [3]: https://github.com/usnistgov/ACVP-Server
+
+use ::ml_kem::kem::{Decapsulate, DecapsulationKey, EncapsulationKey};
+use ::ml_kem::{EncapsulateDeterministic, EncodedSizeUser, KemCore, MlKem768, MlKem768Params};
+
+use crate::{
+    VaultError, ML_KEM_768_CIPHERTEXT_LENGTH, ML_KEM_768_PUBLIC_KEY_LENGTH,
+    ML_KEM_768_SECRET_KEY_LENGTH, ML_KEM_SHARED_SECRET_LENGTH,
+};
+use ockam_core::Result;
+
+/// Generate an ML-KEM-768 key pair from the 2 random seeds d and z (ML-KEM.KeyGen_internal).
+/// Return the encapsulation key and the decapsulation key
+pub(crate) fn generate_key_pair(
+    d: &[u8; 32],
+    z: &[u8; 32],
+) -> (
+    [u8; ML_KEM_768_PUBLIC_KEY_LENGTH],
+    [u8; ML_KEM_768_SECRET_KEY_LENGTH],
+) {
+    let (decapsulation_key, encapsulation_key) =
+        MlKem768::generate_deterministic(&(*d).into(), &(*z).into());
+
+    (
+        encapsulation_key.as_bytes().into(),
+        decapsulation_key.as_bytes().into(),
+    )
+}
+
+/// Return the encapsulation key which is part of a decapsulation key
+pub(crate) fn encapsulation_key(
+    decapsulation_key: &[u8; ML_KEM_768_SECRET_KEY_LENGTH],
+) -> [u8; ML_KEM_768_PUBLIC_KEY_LENGTH] {
+    DecapsulationKey::<MlKem768Params>::from_bytes(decapsulation_key.into())
+        .encapsulation_key()
+        .as_bytes()
+        .into()
+}
+
+/// Encapsulate a shared secret for the owner of an encapsulation key, using the random
+/// message m (ML-KEM.Encaps_internal). Return the shared secret and the ciphertext
+pub(crate) fn encapsulate(
+    encapsulation_key: &[u8; ML_KEM_768_PUBLIC_KEY_LENGTH],
+    m: &[u8; 32],
+) -> Result<(
+    [u8; ML_KEM_SHARED_SECRET_LENGTH],
+    [u8; ML_KEM_768_CIPHERTEXT_LENGTH],
+)> {
+    let key = EncapsulationKey::<MlKem768Params>::from_bytes(encapsulation_key.into());
+
+    // modulus check: decoding reduces the coefficients modulo q, so the key must be encoded
+    // again unchanged if all its coefficients were reduced
+    if key.as_bytes().as_slice() != encapsulation_key.as_slice() {
+        return Err(VaultError::InvalidPublicKey.into());
+    }
+
+    let (ciphertext, shared_secret) = key
+        .encapsulate_deterministic(&(*m).into())
+        .map_err(|_| VaultError::InvalidPublicKey)?;
+
+    Ok((shared_secret.into(), ciphertext.into()))
+}
+
+/// Decapsulate a shared secret (ML-KEM.Decaps_internal).
+/// An invalid ciphertext is implicitly rejected by returning a pseudo-random secret
+/// which will not match the secret of the sender
+pub(crate) fn decapsulate(
+    decapsulation_key: &[u8; ML_KEM_768_SECRET_KEY_LENGTH],
+    ciphertext: &[u8; ML_KEM_768_CIPHERTEXT_LENGTH],
+) -> [u8; ML_KEM_SHARED_SECRET_LENGTH] {
+    let key = DecapsulationKey::<MlKem768Params>::from_bytes(decapsulation_key.into());
+
+    // the decapsulation of an ML-KEM key never fails
+    key.decapsulate(ciphertext.into())
+        .expect("ML-KEM decapsulation is infallible")
+        .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha3::digest::{ExtendableOutput, Update, XofReader};
+    use sha3::Shake256;
+
+    /// A test vector of a .rsp file: the `name = value` lines of a block separated by empty lines
+    struct Vector<'a>(&'a str);
+
+    impl Vector<'_> {
+        fn get(&self, name: &str) -> Option<&str> {
+            let prefix = format!("{name} = ");
+            self.0.lines().find_map(|l| l.strip_prefix(prefix.as_str()))
+        }
+
+        fn bytes<const L: usize>(&self, name: &str) -> [u8; L] {
+            hex::decode(self.get(name).unwrap())
+                .unwrap()
+                .try_into()
+                .unwrap()
+        }
+    }
+
+    /// Return the blocks of a .rsp file which contain a test case
+    fn test_cases(vectors: &str) -> Vec<Vector> {
+        vectors
+            .split("\n\n")
+            .map(Vector)
+            .filter(|v| v.get("tcId").is_some())
+            .collect()
+    }
+
+    /// Check the key generation against the NIST ACVP vectors of ml_kem_768_keygen.rsp
+    #[test]
+    fn test_key_generation_known_answers() {
+        let test_cases = test_cases(include_str!("ml_kem_768_keygen.rsp"));
+        assert_eq!(test_cases.len(), 25);
+        for test_case in test_cases {
+            let tc_id = test_case.get("tcId").unwrap();
+            let (ek, dk) = generate_key_pair(&test_case.bytes("d"), &test_case.bytes("z"));
+            assert_eq!(ek, test_case.bytes("ek"), "tcId {tc_id}");
+            assert_eq!(dk, test_case.bytes("dk"), "tcId {tc_id}");
+            assert_eq!(encapsulation_key(&dk), ek, "tcId {tc_id}");
+        }
+    }
+
+    /// Check the encapsulation against the NIST ACVP vectors of ml_kem_768_encap_decap.rsp
+    #[test]
+    fn test_encapsulation_known_answers() -> Result<()> {
+        let vectors = include_str!("ml_kem_768_encap_decap.rsp");
+        let (encapsulation, _) = vectors.split_once("[decapsulation]").unwrap();
+        let test_cases = test_cases(encapsulation);
+        assert_eq!(test_cases.len(), 25);
+        for test_case in test_cases {
+            let tc_id = test_case.get("tcId").unwrap();
+            let ek = test_case.bytes("ek");
+            let dk = test_case.bytes("dk");
+            let (k, c) = encapsulate(&ek, &test_case.bytes("m"))?;
+            assert_eq!(k, test_case.bytes("k"), "tcId {tc_id}");
+            assert_eq!(c, test_case.bytes("c"), "tcId {tc_id}");
+            assert_eq!(decapsulate(&dk, &c), k, "tcId {tc_id}");
+        }
+        Ok(())
+    }
+
+    /// Check the decapsulation of valid and modified ciphertexts against the NIST ACVP vectors
+    /// of ml_kem_768_encap_decap.rsp
+    #[test]
+    fn test_decapsulation_known_answers() {
+        let vectors = include_str!("ml_kem_768_encap_decap.rsp");
+        let (_, decapsulation) = vectors.split_once("[decapsulation]").unwrap();
+        let key_pair = decapsulation.split("\n\n").map(Vector).nth(1).unwrap();
+        let ek: [u8; ML_KEM_768_PUBLIC_KEY_LENGTH] = key_pair.bytes("ek");
+        let dk = key_pair.bytes("dk");
+        assert_eq!(encapsulation_key(&dk), ek);
+
+        let test_cases = test_cases(decapsulation);
+        assert_eq!(test_cases.len(), 10);
+        let mut modified = 0;
+        for test_case in test_cases {
+            let tc_id = test_case.get("tcId").unwrap();
+            match test_case.get("reason").unwrap() {
+                "modify ciphertext" => modified += 1,
+                reason => assert_eq!(reason, "no modification"),
+            }
+            let k = decapsulate(&dk, &test_case.bytes("c"));
+            assert_eq!(k, test_case.bytes("k"), "tcId {tc_id}");
+        }
+        assert_eq!(modified, 5);
+    }
+
+    #[test]
+    fn test_encapsulation_round_trip() -> Result<()> {
+        let (ek, dk) = generate_key_pair(&[1; 32], &[2; 32]);
+        let (shared_secret, ciphertext) = encapsulate(&ek, &[3; 32])?;
+        assert_eq!(decapsulate(&dk, &ciphertext), shared_secret);
+
+        // a different key pair decapsulates another secret
+        let (_, other_dk) = generate_key_pair(&[4; 32], &[5; 32]);
+        assert_ne!(decapsulate(&other_dk, &ciphertext), shared_secret);
+        Ok(())
+    }
+
+    #[test]
+    fn test_implicit_rejection() -> Result<()> {
+        let (ek, dk) = generate_key_pair(&[1; 32], &[2; 32]);
+        let (shared_secret, mut ciphertext) = encapsulate(&ek, &[3; 32])?;
+        ciphertext[0] ^= 1;
+
+        // the rejection secret is J(z || c)
+        let mut expected = [0u8; 32];
+        let mut shake = Shake256::default();
+        shake.update(&[2; 32]);
+        shake.update(&ciphertext);
+        shake.finalize_xof().read(&mut expected);
+
+        let rejected = decapsulate(&dk, &ciphertext);
+        assert_ne!(rejected, shared_secret);
+        assert_eq!(rejected, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_encapsulation_key() {
+        let (mut ek, _) = generate_key_pair(&[1; 32], &[2; 32]);
+        // the first coefficient is now 4095 which is not reduced modulo q
+        ek[0] = 0xff;
+        ek[1] |= 0x0f;
+        assert!(encapsulate(&ek, &[3; 32]).is_err());
+    }
+}
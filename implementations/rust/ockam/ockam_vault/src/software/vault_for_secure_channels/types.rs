@@ -3,6 +3,8 @@ use zeroize::{Zeroize, ZeroizeOnDrop};
 
 use ockam_core::compat::vec::Vec;
 
+use crate::ML_KEM_768_SECRET_KEY_LENGTH;
+
 /// X25519 private key length.
 pub const X25519_SECRET_KEY_LENGTH: usize = 32;
 
@@ -21,6 +23,21 @@ impl X25519SecretKey {
     }
 }
 
+/// ML-KEM-768 Secret Key.
+#[derive(Eq, PartialEq, Clone, Zeroize, ZeroizeOnDrop)]
+pub struct MlKemSecretKey([u8; ML_KEM_768_SECRET_KEY_LENGTH]);
+
+impl MlKemSecretKey {
+    /// Constructor.
+    pub fn new(key: [u8; ML_KEM_768_SECRET_KEY_LENGTH]) -> Self {
+        Self(key)
+    }
+
+    pub(crate) fn key(&self) -> &[u8; ML_KEM_768_SECRET_KEY_LENGTH] {
+        &self.0
+    }
+}
+
 /// Buffer with sensitive data, like HKDF output.
 #[derive(Eq, PartialEq, Clone, Zeroize, ZeroizeOnDrop)]
 pub struct BufferSecret(Vec<u8>);
@@ -14,18 +14,20 @@ use crate::storage::SecretsSqlxDatabase;
 
 use crate::{
     AeadSecret, AeadSecretKeyHandle, BufferSecret, HKDFNumberOfOutputs, HandleToSecret, HashOutput,
-    HkdfOutput, SecretBufferHandle, SoftwareVaultForVerifyingSignatures, VaultError,
-    VaultForSecureChannels, X25519PublicKey, X25519SecretKey, X25519SecretKeyHandle,
-    AEAD_SECRET_LENGTH,
+    HkdfOutput, MlKemCiphertext, MlKemPublicKey, MlKemSecretKey, MlKemSecretKeyHandle,
+    SecretBufferHandle, SoftwareVaultForVerifyingSignatures, VaultError, VaultForSecureChannels,
+    X25519PublicKey, X25519SecretKey, X25519SecretKeyHandle, AEAD_SECRET_LENGTH,
 };
 
 use super::aes::make_aes;
+use super::ml_kem;
 
 /// [`SecureChannelVault`] implementation using software
 pub struct SoftwareVaultForSecureChannels {
     ephemeral_buffer_secrets: Arc<RwLock<BTreeMap<SecretBufferHandle, BufferSecret>>>,
     ephemeral_aead_secrets: Arc<RwLock<BTreeMap<AeadSecretKeyHandle, AeadSecret>>>,
     ephemeral_x25519_secrets: Arc<RwLock<BTreeMap<X25519SecretKeyHandle, X25519SecretKey>>>,
+    ephemeral_ml_kem_secrets: Arc<RwLock<BTreeMap<MlKemSecretKeyHandle, MlKemSecretKey>>>,
     static_x25519_secrets: Arc<dyn SecretsRepository>,
 }
 
@@ -36,6 +38,7 @@ impl SoftwareVaultForSecureChannels {
             ephemeral_buffer_secrets: Default::default(),
             ephemeral_aead_secrets: Default::default(),
             ephemeral_x25519_secrets: Default::default(),
+            ephemeral_ml_kem_secrets: Default::default(),
             static_x25519_secrets: repository,
         }
     }
@@ -99,6 +102,11 @@ impl SoftwareVaultForSecureChannels {
         self.ephemeral_x25519_secrets.read().unwrap().len()
     }
 
+    /// Return the total number of ephemeral ML-KEM secrets present in the Vault
+    pub fn number_of_ephemeral_ml_kem_secrets(&self) -> usize {
+        self.ephemeral_ml_kem_secrets.read().unwrap().len()
+    }
+
     /// Return the total number of ephemeral buffer secrets present in the Vault
     pub fn number_of_ephemeral_buffer_secrets(&self) -> usize {
         self.ephemeral_buffer_secrets.read().unwrap().len()
@@ -190,6 +198,19 @@ impl SoftwareVaultForSecureChannels {
             .ok_or(VaultError::KeyNotFound.into())
     }
 
+    fn generate_random_seed() -> [u8; 32] {
+        let mut seed = [0u8; 32];
+        thread_rng().fill_bytes(&mut seed);
+        seed
+    }
+
+    fn get_ml_kem_secret(&self, handle: &MlKemSecretKeyHandle) -> Result<MlKemSecretKey> {
+        match self.ephemeral_ml_kem_secrets.read().unwrap().get(handle) {
+            Some(secret) => Ok(secret.clone()),
+            None => Err(VaultError::KeyNotFound.into()),
+        }
+    }
+
     async fn get_buffer_secret(&self, handle: &SecretBufferHandle) -> Result<BufferSecret> {
         match self.ephemeral_buffer_secrets.read().unwrap().get(handle) {
             Some(secret) => Ok(secret.clone()),
@@ -402,4 +423,60 @@ impl VaultForSecureChannels for SoftwareVaultForSecureChannels {
             .remove(&secret_key_handle)
             .is_some())
     }
+
+    async fn generate_ephemeral_ml_kem_secret_key(&self) -> Result<MlKemSecretKeyHandle> {
+        let (_, secret) =
+            ml_kem::generate_key_pair(&Self::generate_random_seed(), &Self::generate_random_seed());
+        let handle = MlKemSecretKeyHandle(Self::generate_random_handle());
+
+        self.ephemeral_ml_kem_secrets
+            .write()
+            .unwrap()
+            .insert(handle.clone(), MlKemSecretKey::new(secret));
+
+        Ok(handle)
+    }
+
+    async fn delete_ephemeral_ml_kem_secret_key(
+        &self,
+        secret_key_handle: MlKemSecretKeyHandle,
+    ) -> Result<bool> {
+        Ok(self
+            .ephemeral_ml_kem_secrets
+            .write()
+            .unwrap()
+            .remove(&secret_key_handle)
+            .is_some())
+    }
+
+    async fn get_ml_kem_public_key(
+        &self,
+        secret_key_handle: &MlKemSecretKeyHandle,
+    ) -> Result<MlKemPublicKey> {
+        let secret = self.get_ml_kem_secret(secret_key_handle)?;
+
+        Ok(MlKemPublicKey(ml_kem::encapsulation_key(secret.key())))
+    }
+
+    async fn ml_kem_encapsulate(
+        &self,
+        peer_public_key: &MlKemPublicKey,
+    ) -> Result<(SecretBufferHandle, MlKemCiphertext)> {
+        let (shared_secret, ciphertext) =
+            ml_kem::encapsulate(&peer_public_key.0, &Self::generate_random_seed())?;
+        let handle = self.import_buffer_secret_impl(BufferSecret::new(shared_secret.to_vec()));
+
+        Ok((handle, MlKemCiphertext(ciphertext)))
+    }
+
+    async fn ml_kem_decapsulate(
+        &self,
+        secret_key_handle: &MlKemSecretKeyHandle,
+        ciphertext: &MlKemCiphertext,
+    ) -> Result<SecretBufferHandle> {
+        let secret = self.get_ml_kem_secret(secret_key_handle)?;
+        let shared_secret = ml_kem::decapsulate(secret.key(), &ciphertext.0);
+
+        Ok(self.import_buffer_secret_impl(BufferSecret::new(shared_secret.to_vec())))
+    }
 }
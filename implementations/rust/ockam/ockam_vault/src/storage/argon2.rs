@@ -0,0 +1,161 @@
+//! Argon2id password hashing function, as defined in [RFC 9106][1].
+//!
+//! It is used to derive the key wrapping the vault secrets from a passphrase.
+//! The hash itself is computed by the `argon2` crate.
+//!
+//! [1]: https://www.rfc-editor.org/rfc/rfc9106
+
+use argon2::{Algorithm, Argon2, AssociatedData, ParamsBuilder, Version};
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::Result;
+
+/// Cost parameters of the Argon2id function
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Argon2Params {
+    /// Memory size, in KiB
+    pub memory_cost: u32,
+    /// Number of passes over the memory
+    pub time_cost: u32,
+    /// Number of lanes
+    pub parallelism: u32,
+}
+
+impl Default for Argon2Params {
+    /// The second recommended option of RFC 9106 would use 64 MiB of memory.
+    /// These values follow the current OWASP recommendation for Argon2id instead,
+    /// which keeps the opening of a vault fast on small machines.
+    fn default() -> Self {
+        Self {
+            memory_cost: 19 * 1024,
+            time_cost: 2,
+            parallelism: 1,
+        }
+    }
+}
+
+impl Argon2Params {
+    /// Return true if the parameters can be used to compute a hash
+    pub fn is_valid(&self) -> bool {
+        self.time_cost >= 1
+            && (1..(1 << 24)).contains(&self.parallelism)
+            && self.memory_cost >= 8 * self.parallelism
+    }
+}
+
+/// Compute the Argon2id hash of a password and write it to `output`.
+/// The `secret` and `associated_data` inputs are optional and can be left empty.
+pub(crate) fn argon2id(
+    params: &Argon2Params,
+    password: &[u8],
+    salt: &[u8],
+    secret: &[u8],
+    associated_data: &[u8],
+    output: &mut [u8],
+) -> Result<()> {
+    if !params.is_valid() {
+        return Err(argon2_error("invalid Argon2 parameters"));
+    }
+    let params = ParamsBuilder::new()
+        .m_cost(params.memory_cost)
+        .t_cost(params.time_cost)
+        .p_cost(params.parallelism)
+        .data(AssociatedData::new(associated_data).map_err(argon2_error)?)
+        .output_len(output.len())
+        .build()
+        .map_err(argon2_error)?;
+    let argon2 = if secret.is_empty() {
+        Argon2::new(Algorithm::Argon2id, Version::V0x13, params)
+    } else {
+        Argon2::new_with_secret(secret, Algorithm::Argon2id, Version::V0x13, params)
+            .map_err(argon2_error)?
+    };
+    argon2
+        .hash_password_into(password, salt, output)
+        .map_err(argon2_error)
+}
+
+fn argon2_error(e: impl core::fmt::Display) -> ockam_core::Error {
+    ockam_core::Error::new(Origin::Vault, Kind::Invalid, format!("{e}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_argon2id() {
+        // test vector from RFC 9106, section 5.3
+        let params = Argon2Params {
+            memory_cost: 32,
+            time_cost: 3,
+            parallelism: 4,
+        };
+        let mut output = [0u8; 32];
+        argon2id(
+            &params,
+            &[0x01; 32],
+            &[0x02; 16],
+            &[0x03; 8],
+            &[0x04; 12],
+            &mut output,
+        )
+        .unwrap();
+        assert_eq!(
+            hex::encode(output),
+            "0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659"
+        );
+    }
+
+    #[test]
+    fn test_argon2id_several_address_blocks_and_long_output() {
+        // the segments are longer than one block of addresses
+        let params = Argon2Params {
+            memory_cost: 2048,
+            time_cost: 2,
+            parallelism: 2,
+        };
+        let mut output = [0u8; 100];
+        argon2id(&params, b"password", b"somesalt", &[], &[], &mut output).unwrap();
+        assert_eq!(
+            hex::encode(output),
+            "873643194e1dbd199f37122655ffb719cc6bf5c1c494081f9142d39a5c2a36c2ddd9cc76ffe9a1025b106453037ff90600fcd6ef8763d0d29923ab308edbcc1328e9e5a801cb03085a9697623f18873bc2ef8e5bb3bc6583f52c6587e933fb519f684223"
+        );
+    }
+
+    #[test]
+    fn test_argon2id_invalid_parameters() {
+        let params = Argon2Params {
+            memory_cost: 8,
+            time_cost: 1,
+            parallelism: 2,
+        };
+        let mut output = [0u8; 32];
+        assert!(argon2id(&params, b"password", b"somesalt", &[], &[], &mut output).is_err());
+    }
+
+    #[test]
+    fn test_argon2id_is_deterministic_and_depends_on_all_inputs() {
+        let params = Argon2Params {
+            memory_cost: 64,
+            time_cost: 1,
+            parallelism: 1,
+        };
+        let hash = |password: &[u8], salt: &[u8]| {
+            let mut output = [0u8; 32];
+            argon2id(&params, password, salt, &[], &[], &mut output).unwrap();
+            output
+        };
+        assert_eq!(
+            hash(b"password", b"somesalt"),
+            hash(b"password", b"somesalt")
+        );
+        assert_ne!(
+            hash(b"password", b"somesalt"),
+            hash(b"passwore", b"somesalt")
+        );
+        assert_ne!(
+            hash(b"password", b"somesalt"),
+            hash(b"password", b"somesalu")
+        );
+    }
+}
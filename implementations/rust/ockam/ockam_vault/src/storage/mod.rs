@@ -1,7 +1,16 @@
 mod secrets_repository;
 #[cfg(feature = "storage")]
 mod secrets_repository_sql;
+#[cfg(feature = "storage")]
+mod secrets_wrapping_key;
+
+#[cfg(feature = "storage")]
+mod argon2;
 
+#[cfg(feature = "storage")]
+pub use argon2::Argon2Params;
 pub use secrets_repository::*;
 #[cfg(feature = "storage")]
 pub use secrets_repository_sql::*;
+#[cfg(feature = "storage")]
+pub use secrets_wrapping_key::*;
@@ -1,31 +1,90 @@
+use sqlx::any::AnyArguments;
 use sqlx::*;
 use tracing::debug;
+use zeroize::Zeroizing;
 
 use ockam_core::async_trait;
-use ockam_core::compat::sync::Arc;
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::compat::sync::{Arc, RwLock};
 use ockam_core::compat::vec::Vec;
 use ockam_core::errcode::{Kind, Origin};
 use ockam_core::Result;
-use ockam_node::database::{FromSqlxError, SqlxDatabase, SqlxType, ToSqlxType, ToVoid};
+use ockam_node::database::{
+    DatabaseType, FromSqlxError, Nullable, SqlxDatabase, SqlxType, ToSqlxType, ToVoid,
+};
 
 use crate::storage::secrets_repository::SecretsRepository;
+use crate::storage::{Argon2Params, WrappingKey, WrappingKeySource};
 
 use crate::{
     ECDSASHA256CurveP256SecretKey, EdDSACurve25519SecretKey, HandleToSecret, SigningSecret,
-    SigningSecretKeyHandle, X25519SecretKey, X25519SecretKeyHandle,
+    SigningSecretKeyHandle, VaultError, X25519SecretKey, X25519SecretKeyHandle,
 };
 
 /// Implementation of a secrets repository using a SQL database
+///
+/// The secrets can be encrypted at rest with a wrapping key, see [`SecretsSqlxDatabase::open_with_wrapping_key`].
+/// In that case the repository can not be used without that key.
 #[derive(Clone)]
 pub struct SecretsSqlxDatabase {
     database: Arc<SqlxDatabase>,
+    /// Wrapping key of the secrets, once it is known.
+    /// The absence of a wrapping key is never cached since another process
+    /// can encrypt the secrets at any time
+    wrapping_key: Arc<RwLock<Option<KnownWrappingKey>>>,
+}
+
+/// A wrapping key known by a repository, with the source it was obtained from.
+/// The key check of the database row is kept in order to detect that another process
+/// rotated the wrapping key
+#[derive(Clone)]
+struct KnownWrappingKey {
+    key: WrappingKey,
+    key_check: Vec<u8>,
+    source: WrappingKeySource,
 }
 
 impl SecretsSqlxDatabase {
-    /// Create a new database for policies keys
+    /// Create a new database for secrets stored in clear.
+    /// Accessing the secrets fails if they have been encrypted with a wrapping key
     pub fn new(database: Arc<SqlxDatabase>) -> Self {
         debug!("create a repository for secrets");
-        Self { database }
+        Self {
+            database,
+            wrapping_key: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Create a new database for secrets encrypted with a wrapping key.
+    ///
+    /// If the secrets are not encrypted yet, they are encrypted with that key.
+    /// Otherwise the key must be the key which was used to encrypt them.
+    pub async fn open_with_wrapping_key(
+        database: Arc<SqlxDatabase>,
+        source: WrappingKeySource,
+    ) -> Result<Self> {
+        let repository = Self::new(database);
+        match repository.get_wrapping_key_row().await? {
+            Some(row) => {
+                repository.reload_wrapping_key(&row, source).await?;
+            }
+            None => repository.replace_wrapping_key(None, source).await?,
+        };
+        Ok(repository)
+    }
+
+    /// Encrypt all the secrets with a new wrapping key.
+    ///
+    /// If the secrets are currently encrypted, this repository must have been opened
+    /// with their current wrapping key.
+    pub async fn rotate_wrapping_key(&self, source: WrappingKeySource) -> Result<()> {
+        let current = self.wrapping_key().await?;
+        self.replace_wrapping_key(current.as_ref(), source).await
+    }
+
+    /// Return true if the secrets stored in a database are encrypted with a wrapping key
+    pub async fn has_wrapping_key(database: Arc<SqlxDatabase>) -> Result<bool> {
+        Ok(Self::new(database).get_wrapping_key_row().await?.is_some())
     }
 
     /// Create a new in-memory database for policies
@@ -39,6 +98,201 @@ impl SecretsSqlxDatabase {
 const ED_DSA_CURVE_25519: &str = "EdDSACurve25519";
 const EC_DSA_SHA256_CURVE_P256: &str = "ECDSASHA256CurveP256";
 
+const PASSPHRASE_KEY_SOURCE: &str = "passphrase";
+const PROVIDER_KEY_SOURCE: &str = "provider";
+
+/// Length of the salt used to derive a wrapping key from a passphrase
+const SALT_LENGTH: usize = 16;
+
+/// Value encrypted with the wrapping key in order to check that a given key is correct
+const KEY_CHECK_VALUE: &[u8] = b"ockam secrets wrapping key check";
+
+const SELECT_WRAPPING_KEY: &str = "SELECT key_source, salt, memory_cost, time_cost, parallelism, key_check FROM secrets_wrapping_key";
+
+/// Functions used to encrypt the secrets with a wrapping key
+impl SecretsSqlxDatabase {
+    /// Return the wrapping key used to encrypt the secrets, if they are encrypted.
+    /// Return an error if the secrets are encrypted with a key that this repository doesn't know
+    async fn wrapping_key(&self) -> Result<Option<WrappingKey>> {
+        let row = self.get_wrapping_key_row().await?;
+        self.current_wrapping_key(row.as_ref()).await
+    }
+
+    /// Return the wrapping key to use for storing a secret in a transaction.
+    /// The wrapping key row is read again in that transaction, so that a secret is never
+    /// stored in clear, or with a previous key, if the secrets have just been encrypted
+    /// or their key rotated by another process
+    async fn wrapping_key_for_update(
+        &self,
+        transaction: &mut Transaction<'_, Any>,
+    ) -> Result<Option<WrappingKey>> {
+        let row: Option<WrappingKeyRow> = query_as(SELECT_WRAPPING_KEY)
+            .fetch_optional(&mut **transaction)
+            .await
+            .into_core()?;
+        self.current_wrapping_key(row.as_ref()).await
+    }
+
+    /// Return the wrapping key described by the current wrapping key row.
+    ///
+    /// If another process rotated the wrapping key, the key is obtained again from the source
+    /// of the known key. This fails if that source doesn't give the new key, for example when
+    /// the key is now derived from another passphrase.
+    async fn current_wrapping_key(
+        &self,
+        row: Option<&WrappingKeyRow>,
+    ) -> Result<Option<WrappingKey>> {
+        let known_key = self.wrapping_key.read().unwrap().clone();
+        match (row, known_key) {
+            (None, None) => Ok(None),
+            (None, Some(_)) => Err(VaultError::InvalidWrappingKey.into()),
+            (Some(_), None) => Err(VaultError::MissingWrappingKey.into()),
+            (Some(row), Some(known_key)) if row.key_check == known_key.key_check => {
+                Ok(Some(known_key.key))
+            }
+            (Some(row), Some(known_key)) => {
+                debug!("the secrets wrapping key has been rotated by another process");
+                let key = self.reload_wrapping_key(row, known_key.source).await?;
+                Ok(Some(key))
+            }
+        }
+    }
+
+    /// Obtain the wrapping key described by a row from its source and keep it
+    async fn reload_wrapping_key(
+        &self,
+        row: &WrappingKeyRow,
+        source: WrappingKeySource,
+    ) -> Result<WrappingKey> {
+        let key = row.wrapping_key(&source).await?;
+        *self.wrapping_key.write().unwrap() = Some(KnownWrappingKey {
+            key: key.clone(),
+            key_check: row.key_check.clone(),
+            source,
+        });
+        Ok(key)
+    }
+
+    async fn get_wrapping_key_row(&self) -> Result<Option<WrappingKeyRow>> {
+        let query = query_as(SELECT_WRAPPING_KEY);
+        query.fetch_optional(&self.database.pool).await.into_core()
+    }
+
+    /// Encrypt all the secrets with the key given by `source`, in one transaction.
+    /// The secrets are currently encrypted with the `current` key, or stored in clear.
+    async fn replace_wrapping_key(
+        &self,
+        current: Option<&WrappingKey>,
+        source: WrappingKeySource,
+    ) -> Result<()> {
+        let (key, row) = WrappingKeyRow::create(&source).await?;
+        let mut transaction = self.database.begin().await.into_core()?;
+
+        // The secrets might have been encrypted, or their key rotated, by another process
+        let current_row: Option<WrappingKeyRow> = query_as(SELECT_WRAPPING_KEY)
+            .fetch_optional(&mut *transaction)
+            .await
+            .into_core()?;
+        match (&current_row, current) {
+            (None, None) => (),
+            (Some(current_row), Some(current)) => current_row.check(current)?,
+            (Some(_), None) => return Err(VaultError::MissingWrappingKey.into()),
+            (None, Some(_)) => return Err(VaultError::InvalidWrappingKey.into()),
+        }
+
+        let signing_secrets: Vec<SigningSecretRow> =
+            query_as("SELECT handle, secret_type, secret FROM signing_secret")
+                .fetch_all(&mut *transaction)
+                .await
+                .into_core()?;
+        for row in signing_secrets {
+            let secret = unwrap_secret(current, &row.secret, &row.handle)?;
+            query("UPDATE signing_secret SET secret = $1 WHERE handle = $2")
+                .bind(key.wrap(&secret, &row.handle)?.to_sql())
+                .bind(row.handle.to_sql())
+                .execute(&mut *transaction)
+                .await
+                .void()?;
+        }
+
+        let x25519_secrets: Vec<X25519SecretRow> =
+            query_as("SELECT handle, secret FROM x25519_secret")
+                .fetch_all(&mut *transaction)
+                .await
+                .into_core()?;
+        for row in x25519_secrets {
+            let secret = unwrap_secret(current, &row.secret, &row.handle)?;
+            query("UPDATE x25519_secret SET secret = $1 WHERE handle = $2")
+                .bind(key.wrap(&secret, &row.handle)?.to_sql())
+                .bind(row.handle.to_sql())
+                .execute(&mut *transaction)
+                .await
+                .void()?;
+        }
+
+        query("DELETE FROM secrets_wrapping_key")
+            .execute(&mut *transaction)
+            .await
+            .void()?;
+        row.insert().execute(&mut *transaction).await.void()?;
+        transaction.commit().await.void()?;
+
+        *self.wrapping_key.write().unwrap() = Some(KnownWrappingKey {
+            key,
+            key_check: row.key_check.clone(),
+            source,
+        });
+        self.remove_previous_secrets().await
+    }
+
+    /// Remove the previous values of the secrets from the database files.
+    ///
+    /// The secrets are updated in place, so their previous values can remain in the
+    /// free pages of the database and in its write-ahead log. With Sqlite they are
+    /// removed by rebuilding the database and truncating the write-ahead log.
+    ///
+    /// With Postgres the tables are rewritten but the storage that they used is only
+    /// released to the file system.
+    async fn remove_previous_secrets(&self) -> Result<()> {
+        match self.database.database_type {
+            DatabaseType::Sqlite => {
+                query("VACUUM").execute(&self.database.pool).await.void()?;
+                query("PRAGMA wal_checkpoint(TRUNCATE)")
+                    .execute(&self.database.pool)
+                    .await
+                    .void()
+            }
+            DatabaseType::Postgres => query("VACUUM FULL signing_secret, x25519_secret")
+                .execute(&self.database.pool)
+                .await
+                .void(),
+        }
+    }
+}
+
+/// Encrypt a secret if there is a wrapping key. The handle is used as associated data
+fn wrap_secret(key: Option<&WrappingKey>, secret: &[u8], handle: &[u8]) -> Result<Vec<u8>> {
+    match key {
+        Some(key) => key.wrap(secret, handle),
+        None => Ok(secret.to_vec()),
+    }
+}
+
+/// Decrypt a secret if there is a wrapping key
+fn unwrap_secret(
+    key: Option<&WrappingKey>,
+    secret: &[u8],
+    handle: &[u8],
+) -> Result<Zeroizing<Vec<u8>>> {
+    match key {
+        Some(key) => Ok(Zeroizing::new(
+            key.unwrap(secret, handle)
+                .map_err(|_| VaultError::InvalidWrappingKey)?,
+        )),
+        None => Ok(Zeroizing::new(secret.to_vec())),
+    }
+}
+
 #[async_trait]
 impl SecretsRepository for SecretsSqlxDatabase {
     async fn store_signing_secret(
@@ -51,27 +305,40 @@ impl SecretsRepository for SecretsSqlxDatabase {
             SigningSecretKeyHandle::ECDSASHA256CurveP256(_) => EC_DSA_SHA256_CURVE_P256.into(),
         };
 
-        let query = query("INSERT OR REPLACE INTO signing_secret VALUES (?, ?, ?)")
+        let mut transaction = self.database.begin().await.into_core()?;
+        let key = self.wrapping_key_for_update(&mut transaction).await?;
+        let secret = match &secret {
+            SigningSecret::EdDSACurve25519(k) => {
+                wrap_secret(key.as_ref(), k.key(), handle.handle().value())?
+            }
+            SigningSecret::ECDSASHA256CurveP256(k) => {
+                wrap_secret(key.as_ref(), k.key(), handle.handle().value())?
+            }
+        };
+
+        let query = query("INSERT INTO signing_secret VALUES ($1, $2, $3) ON CONFLICT (handle) DO UPDATE SET secret_type = $2, secret = $3")
             .bind(handle.to_sql())
             .bind(secret_type.to_sql())
             .bind(secret.to_sql());
-        query.execute(&self.database.pool).await.void()
+        query.execute(&mut *transaction).await.void()?;
+        transaction.commit().await.void()
     }
 
     async fn delete_signing_secret(
         &self,
         handle: &SigningSecretKeyHandle,
     ) -> Result<Option<SigningSecret>> {
+        let key = self.wrapping_key().await?;
         let mut transaction = self.database.begin().await.into_core()?;
         let query1 =
-            query_as("SELECT handle, secret_type, secret FROM signing_secret WHERE handle=?")
+            query_as("SELECT handle, secret_type, secret FROM signing_secret WHERE handle=$1")
                 .bind(handle.to_sql());
         let row: Option<SigningSecretRow> =
             query1.fetch_optional(&mut *transaction).await.into_core()?;
-        let secret = row.map(|r| r.signing_secret()).transpose()?;
+        let secret = row.map(|r| r.signing_secret(key.as_ref())).transpose()?;
 
         let result = if let Some(secret) = secret {
-            let query = query("DELETE FROM signing_secret WHERE handle = ?").bind(handle.to_sql());
+            let query = query("DELETE FROM signing_secret WHERE handle = $1").bind(handle.to_sql());
             query.execute(&mut *transaction).await.void()?;
             Some(secret)
         } else {
@@ -85,17 +352,19 @@ impl SecretsRepository for SecretsSqlxDatabase {
         &self,
         handle: &SigningSecretKeyHandle,
     ) -> Result<Option<SigningSecret>> {
+        let key = self.wrapping_key().await?;
         let query =
-            query_as("SELECT handle, secret_type, secret FROM signing_secret WHERE handle=?")
+            query_as("SELECT handle, secret_type, secret FROM signing_secret WHERE handle=$1")
                 .bind(handle.to_sql());
         let row: Option<SigningSecretRow> = query
             .fetch_optional(&self.database.pool)
             .await
             .into_core()?;
-        Ok(row.map(|r| r.signing_secret()).transpose()?)
+        Ok(row.map(|r| r.signing_secret(key.as_ref())).transpose()?)
     }
 
     async fn get_signing_secret_handles(&self) -> Result<Vec<SigningSecretKeyHandle>> {
+        self.wrapping_key().await?;
         let query = query_as("SELECT handle, secret_type, secret FROM signing_secret");
         let rows: Vec<SigningSecretRow> = query.fetch_all(&self.database.pool).await.into_core()?;
         Ok(rows
@@ -109,25 +378,30 @@ impl SecretsRepository for SecretsSqlxDatabase {
         handle: &X25519SecretKeyHandle,
         secret: X25519SecretKey,
     ) -> Result<()> {
-        let query = query("INSERT OR REPLACE INTO x25519_secret VALUES (?, ?)")
+        let mut transaction = self.database.begin().await.into_core()?;
+        let key = self.wrapping_key_for_update(&mut transaction).await?;
+        let secret = wrap_secret(key.as_ref(), secret.key(), handle.0.value())?;
+        let query = query("INSERT INTO x25519_secret VALUES ($1, $2) ON CONFLICT (handle) DO UPDATE SET secret = $2")
             .bind(handle.to_sql())
             .bind(secret.to_sql());
-        query.execute(&self.database.pool).await.void()
+        query.execute(&mut *transaction).await.void()?;
+        transaction.commit().await.void()
     }
 
     async fn delete_x25519_secret(
         &self,
         handle: &X25519SecretKeyHandle,
     ) -> Result<Option<X25519SecretKey>> {
+        let key = self.wrapping_key().await?;
         let mut transaction = self.database.begin().await.into_core()?;
-        let query1 = query_as("SELECT handle, secret FROM x25519_secret WHERE handle=?")
+        let query1 = query_as("SELECT handle, secret FROM x25519_secret WHERE handle=$1")
             .bind(handle.to_sql());
         let row: Option<X25519SecretRow> =
             query1.fetch_optional(&mut *transaction).await.into_core()?;
-        let secret = row.map(|r| r.x25519_secret()).transpose()?;
+        let secret = row.map(|r| r.x25519_secret(key.as_ref())).transpose()?;
 
         let result = if let Some(secret) = secret {
-            let query = query("DELETE FROM x25519_secret WHERE handle = ?").bind(handle.to_sql());
+            let query = query("DELETE FROM x25519_secret WHERE handle = $1").bind(handle.to_sql());
             query.execute(&mut *transaction).await.void()?;
             Some(secret)
         } else {
@@ -141,16 +415,18 @@ impl SecretsRepository for SecretsSqlxDatabase {
         &self,
         handle: &X25519SecretKeyHandle,
     ) -> Result<Option<X25519SecretKey>> {
-        let query = query_as("SELECT handle, secret FROM x25519_secret WHERE handle=?")
+        let key = self.wrapping_key().await?;
+        let query = query_as("SELECT handle, secret FROM x25519_secret WHERE handle=$1")
             .bind(handle.to_sql());
         let row: Option<X25519SecretRow> = query
             .fetch_optional(&self.database.pool)
             .await
             .into_core()?;
-        Ok(row.map(|r| r.x25519_secret()).transpose()?)
+        Ok(row.map(|r| r.x25519_secret(key.as_ref())).transpose()?)
     }
 
     async fn get_x25519_secret_handles(&self) -> Result<Vec<X25519SecretKeyHandle>> {
+        self.wrapping_key().await?;
         let query = query_as("SELECT handle, secret FROM x25519_secret");
         let rows: Vec<X25519SecretRow> = query.fetch_all(&self.database.pool).await.into_core()?;
         Ok(rows
@@ -201,8 +477,9 @@ struct SigningSecretRow {
 }
 
 impl SigningSecretRow {
-    fn signing_secret(&self) -> Result<SigningSecret> {
-        let secret: [u8; 32] = self.secret.clone().try_into().map_err(|_| {
+    fn signing_secret(&self, key: Option<&WrappingKey>) -> Result<SigningSecret> {
+        let secret = unwrap_secret(key, &self.secret, &self.handle)?;
+        let secret: [u8; 32] = secret.as_slice().try_into().map_err(|_| {
             ockam_core::Error::new(
                 Origin::Api,
                 Kind::Serialization,
@@ -248,8 +525,9 @@ struct X25519SecretRow {
 }
 
 impl X25519SecretRow {
-    fn x25519_secret(&self) -> Result<X25519SecretKey> {
-        let secret: [u8; 32] = self.secret.clone().try_into().map_err(|_| {
+    fn x25519_secret(&self, key: Option<&WrappingKey>) -> Result<X25519SecretKey> {
+        let secret = unwrap_secret(key, &self.secret, &self.handle)?;
+        let secret: [u8; 32] = secret.as_slice().try_into().map_err(|_| {
             ockam_core::Error::new(
                 Origin::Api,
                 Kind::Serialization,
@@ -266,76 +544,472 @@ impl X25519SecretRow {
     }
 }
 
+/// Description of the key used to encrypt the secrets
+#[derive(FromRow)]
+struct WrappingKeyRow {
+    key_source: String,
+    salt: Vec<u8>,
+    memory_cost: Nullable<i64>,
+    time_cost: Nullable<i64>,
+    parallelism: Nullable<i64>,
+    key_check: Vec<u8>,
+}
+
+impl WrappingKeyRow {
+    /// Create a new wrapping key and its description.
+    /// A new salt is generated when the key is derived from a passphrase
+    async fn create(source: &WrappingKeySource) -> Result<(WrappingKey, WrappingKeyRow)> {
+        let (key, key_source, salt, params) = match source {
+            WrappingKeySource::Passphrase(passphrase) => {
+                let mut salt = vec![0u8; SALT_LENGTH];
+                thread_rng().fill_bytes(&mut salt);
+                let params = Argon2Params::default();
+                let key = WrappingKey::derive_from_passphrase(passphrase, &salt, &params).await?;
+                (key, PASSPHRASE_KEY_SOURCE, salt, Some(params))
+            }
+            WrappingKeySource::Provider(provider) => (
+                provider.wrapping_key().await?,
+                PROVIDER_KEY_SOURCE,
+                vec![],
+                None,
+            ),
+        };
+        let row = WrappingKeyRow {
+            key_source: key_source.into(),
+            salt,
+            memory_cost: Nullable::new(params.map(|p| p.memory_cost as i64)),
+            time_cost: Nullable::new(params.map(|p| p.time_cost as i64)),
+            parallelism: Nullable::new(params.map(|p| p.parallelism as i64)),
+            key_check: key.wrap(KEY_CHECK_VALUE, &[])?,
+        };
+        Ok((key, row))
+    }
+
+    /// Return the wrapping key described by this row, given by the source
+    async fn wrapping_key(&self, source: &WrappingKeySource) -> Result<WrappingKey> {
+        let key = match (self.key_source.as_str(), source) {
+            (PASSPHRASE_KEY_SOURCE, WrappingKeySource::Passphrase(passphrase)) => {
+                WrappingKey::derive_from_passphrase(passphrase, &self.salt, &self.argon2_params()?)
+                    .await?
+            }
+            (PROVIDER_KEY_SOURCE, WrappingKeySource::Provider(provider)) => {
+                provider.wrapping_key().await?
+            }
+            (key_source, _) => {
+                return Err(ockam_core::Error::new(
+                    Origin::Vault,
+                    Kind::Invalid,
+                    format!("the vault secrets are encrypted with a key given by a {key_source}"),
+                ))
+            }
+        };
+        self.check(&key)?;
+        Ok(key)
+    }
+
+    /// Check that a key is the wrapping key described by this row
+    fn check(&self, key: &WrappingKey) -> Result<()> {
+        match key.unwrap(&self.key_check, &[]) {
+            Ok(value) if value == KEY_CHECK_VALUE => Ok(()),
+            _ => Err(VaultError::InvalidWrappingKey.into()),
+        }
+    }
+
+    fn argon2_params(&self) -> Result<Argon2Params> {
+        let params = match (
+            self.memory_cost.to_option(),
+            self.time_cost.to_option(),
+            self.parallelism.to_option(),
+        ) {
+            (Some(memory_cost), Some(time_cost), Some(parallelism)) => Argon2Params {
+                memory_cost: memory_cost as u32,
+                time_cost: time_cost as u32,
+                parallelism: parallelism as u32,
+            },
+            _ => Argon2Params::default(),
+        };
+        if params.is_valid() {
+            Ok(params)
+        } else {
+            Err(ockam_core::Error::new(
+                Origin::Vault,
+                Kind::Serialization,
+                "invalid Argon2 parameters for the secrets wrapping key",
+            ))
+        }
+    }
+
+    fn insert(&self) -> query::Query<'_, Any, AnyArguments<'_>> {
+        query("INSERT INTO secrets_wrapping_key VALUES ($1, $2, $3, $4, $5, $6)")
+            .bind(self.key_source.to_sql())
+            .bind(self.salt.to_sql())
+            .bind(self.memory_cost.to_option().map(|c| c.to_sql()))
+            .bind(self.time_cost.to_option().map(|c| c.to_sql()))
+            .bind(self.parallelism.to_option().map(|p| p.to_sql()))
+            .bind(self.key_check.to_sql())
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
+    use ockam_node::database::with_dbs;
 
     #[tokio::test]
     async fn test_signing_secrets_repository() -> Result<()> {
-        let repository = create_repository().await?;
+        with_dbs(|db| async move {
+            let repository = create_repository(db);
 
-        let handle1 =
-            SigningSecretKeyHandle::ECDSASHA256CurveP256(HandleToSecret::new(vec![1, 2, 3]));
-        let secret1 =
-            SigningSecret::ECDSASHA256CurveP256(ECDSASHA256CurveP256SecretKey::new([1; 32]));
+            let handle1 =
+                SigningSecretKeyHandle::ECDSASHA256CurveP256(HandleToSecret::new(vec![1, 2, 3]));
+            let secret1 =
+                SigningSecret::ECDSASHA256CurveP256(ECDSASHA256CurveP256SecretKey::new([1; 32]));
 
-        let handle2 = SigningSecretKeyHandle::EdDSACurve25519(HandleToSecret::new(vec![4, 5, 6]));
-        let secret2 = SigningSecret::EdDSACurve25519(EdDSACurve25519SecretKey::new([1; 32]));
+            let handle2 =
+                SigningSecretKeyHandle::EdDSACurve25519(HandleToSecret::new(vec![4, 5, 6]));
+            let secret2 = SigningSecret::EdDSACurve25519(EdDSACurve25519SecretKey::new([1; 32]));
 
-        repository
-            .store_signing_secret(&handle1, secret1.clone())
-            .await?;
-        repository
-            .store_signing_secret(&handle2, secret2.clone())
-            .await?;
+            repository
+                .store_signing_secret(&handle1, secret1.clone())
+                .await?;
+            repository
+                .store_signing_secret(&handle2, secret2.clone())
+                .await?;
 
-        let result = repository.get_signing_secret(&handle1).await?;
-        assert!(result == Some(secret1));
+            let result = repository.get_signing_secret(&handle1).await?;
+            assert!(result == Some(secret1));
 
-        let result = repository.get_signing_secret_handles().await?;
-        assert_eq!(result, vec![handle1.clone(), handle2]);
+            let result = repository.get_signing_secret_handles().await?;
+            assert_eq!(result, vec![handle1.clone(), handle2]);
 
-        repository.delete_signing_secret(&handle1).await?;
+            repository.delete_signing_secret(&handle1).await?;
 
-        let result = repository.get_signing_secret(&handle1).await?;
-        assert!(result.is_none());
+            let result = repository.get_signing_secret(&handle1).await?;
+            assert!(result.is_none());
 
-        Ok(())
+            Ok(())
+        })
+        .await
     }
 
     #[tokio::test]
     async fn test_x25519_secrets_repository() -> Result<()> {
-        let repository = create_repository().await?;
+        with_dbs(|db| async move {
+            let repository = create_repository(db);
 
-        let handle1 = X25519SecretKeyHandle(HandleToSecret::new(vec![1, 2, 3]));
-        let secret1 = X25519SecretKey::new([1; 32]);
+            let handle1 = X25519SecretKeyHandle(HandleToSecret::new(vec![1, 2, 3]));
+            let secret1 = X25519SecretKey::new([1; 32]);
 
-        let handle2 = X25519SecretKeyHandle(HandleToSecret::new(vec![4, 5, 6]));
-        let secret2 = X25519SecretKey::new([1; 32]);
+            let handle2 = X25519SecretKeyHandle(HandleToSecret::new(vec![4, 5, 6]));
+            let secret2 = X25519SecretKey::new([1; 32]);
 
-        repository
-            .store_x25519_secret(&handle1, secret1.clone())
+            repository
+                .store_x25519_secret(&handle1, secret1.clone())
+                .await?;
+            repository
+                .store_x25519_secret(&handle2, secret2.clone())
+                .await?;
+
+            let result = repository.get_x25519_secret(&handle1).await?;
+            assert!(result == Some(secret1));
+
+            let result = repository.get_x25519_secret_handles().await?;
+            assert_eq!(result, vec![handle1.clone(), handle2]);
+
+            repository.delete_x25519_secret(&handle1).await?;
+
+            let result = repository.get_x25519_secret(&handle1).await?;
+            assert!(result.is_none());
+
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_secrets_encrypted_with_a_passphrase() -> Result<()> {
+        with_dbs(|db| async move {
+            // store a secret in clear and then encrypt the secrets
+            let handle1 = X25519SecretKeyHandle(HandleToSecret::new(vec![1, 2, 3]));
+            let secret1 = X25519SecretKey::new([1; 32]);
+            let repository = SecretsSqlxDatabase::new(db.clone());
+            repository
+                .store_x25519_secret(&handle1, secret1.clone())
+                .await?;
+            assert!(!SecretsSqlxDatabase::has_wrapping_key(db.clone()).await?);
+
+            let passphrase = WrappingKeySource::passphrase("my passphrase");
+            let repository =
+                SecretsSqlxDatabase::open_with_wrapping_key(db.clone(), passphrase.clone()).await?;
+            assert!(SecretsSqlxDatabase::has_wrapping_key(db.clone()).await?);
+
+            // new secrets are encrypted too
+            let handle2 =
+                SigningSecretKeyHandle::EdDSACurve25519(HandleToSecret::new(vec![4, 5, 6]));
+            let secret2 = SigningSecret::EdDSACurve25519(EdDSACurve25519SecretKey::new([2; 32]));
+            repository
+                .store_signing_secret(&handle2, secret2.clone())
+                .await?;
+            assert_eq!(get_stored_secrets(&db).await?.len(), 2);
+            for stored in get_stored_secrets(&db).await? {
+                assert_ne!(stored, vec![1; 32]);
+                assert_ne!(stored, vec![2; 32]);
+            }
+
+            // the secrets can be read with the same passphrase
+            let repository =
+                SecretsSqlxDatabase::open_with_wrapping_key(db.clone(), passphrase).await?;
+            assert!(repository.get_x25519_secret(&handle1).await? == Some(secret1));
+            assert!(repository.get_signing_secret(&handle2).await? == Some(secret2));
+
+            // the secrets can not be read without the passphrase or with a wrong one
+            let repository = SecretsSqlxDatabase::new(db.clone());
+            assert!(repository.get_x25519_secret(&handle1).await.is_err());
+            assert!(repository.get_x25519_secret_handles().await.is_err());
+            let result = SecretsSqlxDatabase::open_with_wrapping_key(
+                db.clone(),
+                WrappingKeySource::passphrase("wrong passphrase"),
+            )
+            .await;
+            assert!(result.is_err());
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_rotate_wrapping_key() -> Result<()> {
+        with_dbs(|db| async move {
+            let handle = X25519SecretKeyHandle(HandleToSecret::new(vec![1, 2, 3]));
+            let secret = X25519SecretKey::new([1; 32]);
+            let passphrase = WrappingKeySource::passphrase("my passphrase");
+            let repository =
+                SecretsSqlxDatabase::open_with_wrapping_key(db.clone(), passphrase.clone()).await?;
+            repository
+                .store_x25519_secret(&handle, secret.clone())
+                .await?;
+            let stored_before_rotation = get_stored_secrets(&db).await?;
+
+            // rotate the key to a key returned by a provider
+            let provider = WrappingKeySource::provider(Arc::new(StaticWrappingKeyProvider));
+            repository.rotate_wrapping_key(provider.clone()).await?;
+            assert_ne!(get_stored_secrets(&db).await?, stored_before_rotation);
+            assert!(repository.get_x25519_secret(&handle).await? == Some(secret.clone()));
+
+            // the previous key can not be used anymore
+            assert!(
+                SecretsSqlxDatabase::open_with_wrapping_key(db.clone(), passphrase)
+                    .await
+                    .is_err()
+            );
+            let repository =
+                SecretsSqlxDatabase::open_with_wrapping_key(db.clone(), provider).await?;
+            assert!(repository.get_x25519_secret(&handle).await? == Some(secret));
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_secrets_encrypted_by_another_repository() -> Result<()> {
+        with_dbs(|db| async move {
+            let handle1 = X25519SecretKeyHandle(HandleToSecret::new(vec![1, 2, 3]));
+            let secret1 = X25519SecretKey::new([1; 32]);
+            let clear_repository = SecretsSqlxDatabase::new(db.clone());
+            clear_repository
+                .store_x25519_secret(&handle1, secret1.clone())
+                .await?;
+
+            // another process encrypts the secrets
+            let provider = WrappingKeySource::provider(Arc::new(StaticWrappingKeyProvider));
+            let repository =
+                SecretsSqlxDatabase::open_with_wrapping_key(db.clone(), provider).await?;
+
+            // the secrets can not be stored in clear anymore
+            let handle2 = X25519SecretKeyHandle(HandleToSecret::new(vec![4, 5, 6]));
+            let secret2 = X25519SecretKey::new([2; 32]);
+            assert!(clear_repository
+                .store_x25519_secret(&handle2, secret2)
+                .await
+                .is_err());
+            assert!(clear_repository.get_x25519_secret(&handle1).await.is_err());
+            assert!(clear_repository
+                .rotate_wrapping_key(WrappingKeySource::passphrase("my passphrase"))
+                .await
+                .is_err());
+
+            // the stored secrets can still be rotated
+            repository
+                .rotate_wrapping_key(WrappingKeySource::passphrase("my passphrase"))
+                .await?;
+            assert!(repository.get_x25519_secret(&handle1).await? == Some(secret1));
+            assert_eq!(repository.get_x25519_secret_handles().await?.len(), 1);
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_passphrase_rotated_by_another_repository() -> Result<()> {
+        with_dbs(|db| async move {
+            let handle1 = X25519SecretKeyHandle(HandleToSecret::new(vec![1, 2, 3]));
+            let secret1 = X25519SecretKey::new([1; 32]);
+            let passphrase = WrappingKeySource::passphrase("my passphrase");
+            let repository =
+                SecretsSqlxDatabase::open_with_wrapping_key(db.clone(), passphrase.clone()).await?;
+            repository
+                .store_x25519_secret(&handle1, secret1.clone())
+                .await?;
+
+            // another process rotates the passphrase
+            let new_passphrase = WrappingKeySource::passphrase("my new passphrase");
+            SecretsSqlxDatabase::open_with_wrapping_key(db.clone(), passphrase)
+                .await?
+                .rotate_wrapping_key(new_passphrase.clone())
+                .await?;
+
+            // the previous key is not used anymore to store or read secrets
+            let handle2 = X25519SecretKeyHandle(HandleToSecret::new(vec![4, 5, 6]));
+            let secret2 = X25519SecretKey::new([2; 32]);
+            assert!(repository
+                .store_x25519_secret(&handle2, secret2)
+                .await
+                .is_err());
+            assert!(repository.get_x25519_secret(&handle1).await.is_err());
+            assert!(repository.get_x25519_secret_handles().await.is_err());
+
+            // all the secrets can be read with the new passphrase
+            let repository =
+                SecretsSqlxDatabase::open_with_wrapping_key(db.clone(), new_passphrase).await?;
+            assert!(repository.get_x25519_secret(&handle1).await? == Some(secret1));
+            assert_eq!(repository.get_x25519_secret_handles().await?.len(), 1);
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_provider_key_rotated_by_another_repository() -> Result<()> {
+        with_dbs(|db| async move {
+            let handle1 = X25519SecretKeyHandle(HandleToSecret::new(vec![1, 2, 3]));
+            let secret1 = X25519SecretKey::new([1; 32]);
+            let provider = Arc::new(RotatingWrappingKeyProvider::default());
+            let repository = SecretsSqlxDatabase::open_with_wrapping_key(
+                db.clone(),
+                WrappingKeySource::provider(provider.clone()),
+            )
+            .await?;
+            repository
+                .store_x25519_secret(&handle1, secret1.clone())
+                .await?;
+
+            // the provider key is rotated and another process encrypts the secrets with it
+            let other_repository = SecretsSqlxDatabase::open_with_wrapping_key(
+                db.clone(),
+                WrappingKeySource::provider(Arc::new(RotatingWrappingKeyProvider::default())),
+            )
             .await?;
+            provider.rotate();
+            other_repository
+                .rotate_wrapping_key(WrappingKeySource::provider(provider.clone()))
+                .await?;
+
+            // the new key is obtained again from the provider
+            let handle2 = X25519SecretKeyHandle(HandleToSecret::new(vec![4, 5, 6]));
+            let secret2 = X25519SecretKey::new([2; 32]);
+            repository
+                .store_x25519_secret(&handle2, secret2.clone())
+                .await?;
+            assert!(repository.get_x25519_secret(&handle1).await? == Some(secret1));
+            assert!(other_repository.get_x25519_secret(&handle2).await? == Some(secret2));
+            Ok(())
+        })
+        .await
+    }
+
+    #[tokio::test]
+    async fn test_no_secret_in_clear_remains_after_encryption() -> Result<()> {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("secrets.sqlite3");
+        let db = Arc::new(SqlxDatabase::create(&path).await?);
+        // the previous values of the secrets remain in the write-ahead log until it is truncated
+        query("PRAGMA journal_mode=WAL")
+            .execute(&db.pool)
+            .await
+            .void()?;
+
+        let secret: [u8; 32] = *b"a known secret of 32 bytes long!";
+        let handle = X25519SecretKeyHandle(HandleToSecret::new(vec![1, 2, 3]));
+        let repository = SecretsSqlxDatabase::new(db.clone());
         repository
-            .store_x25519_secret(&handle2, secret2.clone())
+            .store_x25519_secret(&handle, X25519SecretKey::new(secret))
             .await?;
+        assert!(database_files_contain(&path, &secret));
 
-        let result = repository.get_x25519_secret(&handle1).await?;
-        assert!(result == Some(secret1));
+        let provider = WrappingKeySource::provider(Arc::new(StaticWrappingKeyProvider));
+        let repository = SecretsSqlxDatabase::open_with_wrapping_key(db.clone(), provider).await?;
+        assert!(!database_files_contain(&path, &secret));
+        assert!(repository.get_x25519_secret(&handle).await? == Some(X25519SecretKey::new(secret)));
+        Ok(())
+    }
 
-        let result = repository.get_x25519_secret_handles().await?;
-        assert_eq!(result, vec![handle1.clone(), handle2]);
+    /// HELPERS
+    fn create_repository(db: Arc<SqlxDatabase>) -> Arc<dyn SecretsRepository> {
+        Arc::new(SecretsSqlxDatabase::new(db))
+    }
 
-        repository.delete_x25519_secret(&handle1).await?;
+    /// Return the secrets as they are stored in the database
+    async fn get_stored_secrets(db: &SqlxDatabase) -> Result<Vec<Vec<u8>>> {
+        let mut secrets: Vec<Vec<u8>> = query_scalar("SELECT secret FROM x25519_secret")
+            .fetch_all(&db.pool)
+            .await
+            .into_core()?;
+        let signing_secrets: Vec<Vec<u8>> = query_scalar("SELECT secret FROM signing_secret")
+            .fetch_all(&db.pool)
+            .await
+            .into_core()?;
+        secrets.extend(signing_secrets);
+        Ok(secrets)
+    }
+
+    /// Return true if the database file or its write-ahead log contain some bytes
+    fn database_files_contain(path: &std::path::Path, bytes: &[u8]) -> bool {
+        let mut wal_path = path.as_os_str().to_owned();
+        wal_path.push("-wal");
+        [path.as_os_str().to_owned(), wal_path].iter().any(|p| {
+            std::fs::read(p)
+                .map(|content| content.windows(bytes.len()).any(|w| w == bytes))
+                .unwrap_or(false)
+        })
+    }
 
-        let result = repository.get_x25519_secret(&handle1).await?;
-        assert!(result.is_none());
+    struct StaticWrappingKeyProvider;
 
-        Ok(())
+    #[async_trait]
+    impl crate::storage::WrappingKeyProvider for StaticWrappingKeyProvider {
+        async fn wrapping_key(&self) -> Result<WrappingKey> {
+            Ok(WrappingKey::new([7; 32]))
+        }
     }
 
-    /// HELPERS
-    async fn create_repository() -> Result<Arc<dyn SecretsRepository>> {
-        Ok(SecretsSqlxDatabase::create().await?)
+    /// Provider of a key which changes when it is rotated
+    #[derive(Default)]
+    struct RotatingWrappingKeyProvider {
+        version: std::sync::atomic::AtomicU8,
+    }
+
+    impl RotatingWrappingKeyProvider {
+        fn rotate(&self) {
+            self.version
+                .fetch_add(1, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    #[async_trait]
+    impl crate::storage::WrappingKeyProvider for RotatingWrappingKeyProvider {
+        async fn wrapping_key(&self) -> Result<WrappingKey> {
+            let version = self.version.load(std::sync::atomic::Ordering::SeqCst);
+            Ok(WrappingKey::new([version; 32]))
+        }
     }
 }
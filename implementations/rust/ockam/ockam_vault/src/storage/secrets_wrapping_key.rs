@@ -0,0 +1,175 @@
+use aes_gcm::aead::{Aead, NewAead, Payload};
+use aes_gcm::Aes256Gcm;
+use core::fmt::{Debug, Formatter};
+use zeroize::{Zeroize, ZeroizeOnDrop, Zeroizing};
+
+use ockam_core::compat::rand::{thread_rng, RngCore};
+use ockam_core::compat::string::String;
+use ockam_core::compat::sync::Arc;
+use ockam_core::compat::vec::Vec;
+use ockam_core::errcode::{Kind, Origin};
+use ockam_core::{async_trait, Result};
+use ockam_node::tokio::task::spawn_blocking;
+
+use crate::storage::argon2::{argon2id, Argon2Params};
+use crate::{VaultError, AES_NONCE_LENGTH};
+
+/// Length of a wrapping key
+pub const WRAPPING_KEY_LENGTH: usize = 32;
+
+/// Key used to encrypt the secrets stored in a vault, also known as a key-encryption key.
+///
+/// Each secret is encrypted with AES-256-GCM and a random nonce, and its handle is used as
+/// associated data so that an encrypted secret can not be swapped with another one.
+#[derive(Clone, Zeroize, ZeroizeOnDrop)]
+pub struct WrappingKey([u8; WRAPPING_KEY_LENGTH]);
+
+impl WrappingKey {
+    /// Create a wrapping key from its bytes
+    pub fn new(key: [u8; WRAPPING_KEY_LENGTH]) -> Self {
+        Self(key)
+    }
+
+    /// Derive a wrapping key from a passphrase with Argon2id.
+    ///
+    /// The derivation is deliberately slow and uses a lot of memory, so it runs on a
+    /// thread dedicated to blocking tasks instead of blocking the current executor thread
+    pub async fn derive_from_passphrase(
+        passphrase: &str,
+        salt: &[u8],
+        params: &Argon2Params,
+    ) -> Result<Self> {
+        let passphrase = Zeroizing::new(passphrase.to_string());
+        let salt = salt.to_vec();
+        let params = *params;
+        spawn_blocking(move || {
+            let mut key = Self([0u8; WRAPPING_KEY_LENGTH]);
+            argon2id(&params, passphrase.as_bytes(), &salt, &[], &[], &mut key.0)?;
+            Ok(key)
+        })
+        .await
+        .map_err(|e| ockam_core::Error::new(Origin::Vault, Kind::Internal, e))?
+    }
+
+    /// Encrypt a secret. The result contains the nonce followed by the ciphertext
+    pub(crate) fn wrap(&self, secret: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+        let mut nonce = [0u8; AES_NONCE_LENGTH];
+        thread_rng().fill_bytes(&mut nonce);
+        let ciphertext = Aes256Gcm::new((&self.0).into())
+            .encrypt((&nonce).into(), Payload { msg: secret, aad })
+            .map_err(|_| VaultError::AeadAesGcmEncrypt)?;
+
+        let mut wrapped = nonce.to_vec();
+        wrapped.extend_from_slice(&ciphertext);
+        Ok(wrapped)
+    }
+
+    /// Decrypt a secret encrypted with the `wrap` function
+    pub(crate) fn unwrap(&self, wrapped: &[u8], aad: &[u8]) -> Result<Vec<u8>> {
+        if wrapped.len() < AES_NONCE_LENGTH {
+            return Err(VaultError::AeadAesGcmDecrypt.into());
+        }
+        let (nonce, ciphertext) = wrapped.split_at(AES_NONCE_LENGTH);
+        Ok(Aes256Gcm::new((&self.0).into())
+            .decrypt(
+                nonce.into(),
+                Payload {
+                    msg: ciphertext,
+                    aad,
+                },
+            )
+            .map_err(|_| VaultError::AeadAesGcmDecrypt)?)
+    }
+}
+
+impl Debug for WrappingKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        f.write_str("WrappingKey(...)")
+    }
+}
+
+/// A provider of wrapping keys, for example backed by an OS keychain or a cloud KMS
+#[async_trait]
+pub trait WrappingKeyProvider: Send + Sync + 'static {
+    /// Return the key used to encrypt the vault secrets
+    async fn wrapping_key(&self) -> Result<WrappingKey>;
+}
+
+/// This enum specifies how the key encrypting the vault secrets is obtained
+#[derive(Clone)]
+pub enum WrappingKeySource {
+    /// The key is derived from a passphrase with Argon2id.
+    /// The salt and the Argon2id parameters are stored in the database
+    Passphrase(String),
+    /// The key is returned by a provider
+    Provider(Arc<dyn WrappingKeyProvider>),
+}
+
+impl WrappingKeySource {
+    /// Create a source for a key derived from a passphrase
+    pub fn passphrase(passphrase: impl Into<String>) -> Self {
+        Self::Passphrase(passphrase.into())
+    }
+
+    /// Create a source for a key returned by a provider
+    pub fn provider(provider: Arc<dyn WrappingKeyProvider>) -> Self {
+        Self::Provider(provider)
+    }
+}
+
+impl Debug for WrappingKeySource {
+    fn fmt(&self, f: &mut Formatter<'_>) -> core::fmt::Result {
+        match self {
+            WrappingKeySource::Passphrase(_) => f.write_str("Passphrase(...)"),
+            WrappingKeySource::Provider(_) => f.write_str("Provider"),
+        }
+    }
+}
+
+impl Drop for WrappingKeySource {
+    fn drop(&mut self) {
+        if let WrappingKeySource::Passphrase(passphrase) = self {
+            passphrase.zeroize()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_wrap_unwrap() -> Result<()> {
+        let key = WrappingKey::new([1; WRAPPING_KEY_LENGTH]);
+        let wrapped = key.wrap(&[2; 32], b"handle")?;
+        assert_eq!(wrapped.len(), AES_NONCE_LENGTH + 32 + 16);
+        assert_eq!(key.unwrap(&wrapped, b"handle")?, vec![2; 32]);
+
+        // the same secret is wrapped differently each time
+        assert_ne!(key.wrap(&[2; 32], b"handle")?, wrapped);
+
+        // the secret can not be unwrapped with another key or for another handle
+        let other_key = WrappingKey::new([3; WRAPPING_KEY_LENGTH]);
+        assert!(other_key.unwrap(&wrapped, b"handle").is_err());
+        assert!(key.unwrap(&wrapped, b"other handle").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_derive_from_passphrase() -> Result<()> {
+        let params = Argon2Params {
+            memory_cost: 64,
+            time_cost: 1,
+            parallelism: 1,
+        };
+        let key1 =
+            WrappingKey::derive_from_passphrase("passphrase", b"salt-salt-salt", &params).await?;
+        let key2 =
+            WrappingKey::derive_from_passphrase("passphrase", b"salt-salt-salt", &params).await?;
+        let key3 =
+            WrappingKey::derive_from_passphrase("passphrase", b"other-salt-salt", &params).await?;
+        assert_eq!(key1.0, key2.0);
+        assert_ne!(key1.0, key3.0);
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use crate::{
-    AeadSecretKeyHandle, HashOutput, HkdfOutput, SecretBufferHandle, X25519PublicKey,
-    X25519SecretKeyHandle,
+    AeadSecretKeyHandle, HashOutput, HkdfOutput, MlKemCiphertext, MlKemPublicKey,
+    MlKemSecretKeyHandle, SecretBufferHandle, X25519PublicKey, X25519SecretKeyHandle,
 };
 
 use ockam_core::compat::vec::Vec;
@@ -102,4 +102,36 @@ pub trait VaultForSecureChannels: Send + Sync + 'static {
 
     /// Delete AEAD Key.
     async fn delete_aead_secret_key(&self, secret_key_handle: AeadSecretKeyHandle) -> Result<bool>;
+
+    /// Generate a fresh ephemeral (not persisted) ML-KEM-768 Key.
+    async fn generate_ephemeral_ml_kem_secret_key(&self) -> Result<MlKemSecretKeyHandle>;
+
+    /// Delete ephemeral ML-KEM-768 Key.
+    async fn delete_ephemeral_ml_kem_secret_key(
+        &self,
+        secret_key_handle: MlKemSecretKeyHandle,
+    ) -> Result<bool>;
+
+    /// Get [`MlKemPublicKey`] of the corresponding ML-KEM-768 Secret Key given its Handle.
+    async fn get_ml_kem_public_key(
+        &self,
+        secret_key_handle: &MlKemSecretKeyHandle,
+    ) -> Result<MlKemPublicKey>;
+
+    /// Perform ML-KEM-768 encapsulation of a fresh shared secret for the owner of a public key.
+    /// Return the shared secret and the ciphertext which must be sent to the peer.
+    /// [1]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.203.pdf
+    async fn ml_kem_encapsulate(
+        &self,
+        peer_public_key: &MlKemPublicKey,
+    ) -> Result<(SecretBufferHandle, MlKemCiphertext)>;
+
+    /// Perform ML-KEM-768 decapsulation of a shared secret.
+    /// An invalid ciphertext results in a random shared secret, unknown to the peer.
+    /// [1]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.203.pdf
+    async fn ml_kem_decapsulate(
+        &self,
+        secret_key_handle: &MlKemSecretKeyHandle,
+        ciphertext: &MlKemCiphertext,
+    ) -> Result<SecretBufferHandle>;
 }
@@ -0,0 +1,33 @@
+use minicbor::{Decode, Encode};
+
+/// ML-KEM-768 encapsulation (public) key length.
+pub const ML_KEM_768_PUBLIC_KEY_LENGTH: usize = 1184;
+
+/// ML-KEM-768 decapsulation (secret) key length.
+pub const ML_KEM_768_SECRET_KEY_LENGTH: usize = 2400;
+
+/// ML-KEM-768 ciphertext length.
+pub const ML_KEM_768_CIPHERTEXT_LENGTH: usize = 1088;
+
+/// ML-KEM shared secret length.
+pub const ML_KEM_SHARED_SECRET_LENGTH: usize = 32;
+
+/// ML-KEM-768 encapsulation key, used to establish a shared secret resistant to
+/// quantum computers.
+///
+/// - ML-KEM as defined [here][1].
+///
+/// [1]: https://nvlpubs.nist.gov/nistpubs/FIPS/NIST.FIPS.203.pdf
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+#[cbor(transparent)]
+pub struct MlKemPublicKey(
+    #[cbor(n(0), with = "minicbor::bytes")] pub [u8; ML_KEM_768_PUBLIC_KEY_LENGTH],
+);
+
+/// ML-KEM-768 ciphertext, encapsulating a shared secret for the owner of
+/// a [`MlKemPublicKey`].
+#[derive(Encode, Decode, Clone, Debug, PartialEq, Eq)]
+#[cbor(transparent)]
+pub struct MlKemCiphertext(
+    #[cbor(n(0), with = "minicbor::bytes")] pub [u8; ML_KEM_768_CIPHERTEXT_LENGTH],
+);
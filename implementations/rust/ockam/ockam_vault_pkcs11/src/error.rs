@@ -0,0 +1,32 @@
+use cryptoki::object::AttributeType;
+use ockam_core::errcode::{Kind, Origin};
+use thiserror::Error;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("pkcs#11 module {module} could not be loaded: {error}")]
+    Load { module: String, error: String },
+    #[error("pkcs#11 call failed: {0}")]
+    Pkcs11(#[from] cryptoki::error::Error),
+    #[error("the pkcs#11 module did not return the attribute {0}")]
+    MissingAttribute(AttributeType),
+    #[error("key type is not supported")]
+    UnsupportedKeyType,
+    #[error("ec point is incorrect")]
+    InvalidPublicKey,
+    #[error("signature is incorrect")]
+    InvalidSignature,
+    #[error("key was not found")]
+    KeyNotFound,
+    #[error("the pkcs#11 session lock is poisoned")]
+    SessionLock,
+    #[error("a pkcs#11 call could not be completed: {0}")]
+    Task(String),
+}
+
+impl From<Error> for ockam_core::Error {
+    fn from(e: Error) -> Self {
+        ockam_core::Error::new(Origin::Other, Kind::Io, e)
+    }
+}
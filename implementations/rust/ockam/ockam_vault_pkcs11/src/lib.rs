@@ -0,0 +1,20 @@
+//! PKCS#11 implementation of the ockam_vault::VaultForSigning trait
+//!
+//! Keys are generated and used inside a token (an HSM, a YubiHSM, SoftHSM, ...)
+//! accessed via the PKCS#11 module provided by its vendor.
+#![deny(unsafe_code)]
+#![warn(
+    missing_docs,
+    trivial_casts,
+    trivial_numeric_casts,
+    unused_import_braces,
+    unused_qualifications
+)]
+
+mod error;
+mod pkcs11_client;
+mod pkcs11_signing_vault;
+
+pub use error::*;
+pub use pkcs11_client::*;
+pub use pkcs11_signing_vault::*;
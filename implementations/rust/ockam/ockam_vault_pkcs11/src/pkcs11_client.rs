@@ -0,0 +1,295 @@
+use crate::error::Error;
+use cryptoki::context::{CInitializeArgs, Pkcs11};
+use cryptoki::error::{Error as CryptokiError, RvError};
+use cryptoki::mechanism::Mechanism;
+use cryptoki::object::{Attribute, AttributeType, KeyType, ObjectClass, ObjectHandle};
+use cryptoki::session::{Session, UserType};
+use cryptoki::slot::Slot;
+use cryptoki::types::AuthPin;
+use ockam_core::Result;
+use ockam_vault::{
+    ECDSASHA256CurveP256PublicKey, ECDSASHA256CurveP256Signature, HandleToSecret, Signature,
+    SigningSecretKeyHandle, VerifyingPublicKey, ECDSA_SHA256_CURVEP256_PUBLIC_KEY_LENGTH,
+    ECDSA_SHA256_CURVEP256_SIGNATURE_LENGTH,
+};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, MutexGuard, OnceLock};
+use tracing as log;
+
+/// DER encoding of the OID of the P-256 curve (prime256v1), used as CKA_EC_PARAMS
+const P256_EC_PARAMS: [u8; 10] = [0x06, 0x08, 0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+
+/// Number of random bytes used to create the CKA_ID of a new key
+const KEY_ID_LENGTH: usize = 16;
+
+/// Configuration of a PKCS#11 vault
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pkcs11Config {
+    module: PathBuf,
+    slot: u64,
+    pin: Option<String>,
+}
+
+impl Pkcs11Config {
+    /// Create a configuration for a token in a given slot of a PKCS#11 module.
+    /// `module` is the path to the shared library implementing the PKCS#11 interface
+    pub fn new(module: impl AsRef<Path>, slot: u64) -> Self {
+        Self {
+            module: module.as_ref().to_path_buf(),
+            slot,
+            pin: None,
+        }
+    }
+
+    /// Set the user PIN used to log in to the token
+    pub fn with_pin(mut self, pin: impl Into<String>) -> Self {
+        self.pin = Some(pin.into());
+        self
+    }
+
+    /// Return the path to the PKCS#11 module
+    pub fn module(&self) -> &Path {
+        self.module.as_path()
+    }
+
+    /// Return the slot of the token
+    pub fn slot(&self) -> u64 {
+        self.slot
+    }
+}
+
+/// Client for a token accessed via a PKCS#11 module
+///
+/// All the operations are executed with the same session, and the session is locked
+/// during each operation since PKCS#11 sessions must not be used concurrently.
+/// The operations are blocking calls to the module, they must not be executed
+/// directly on an async runtime.
+///
+/// Keys are identified by their CKA_ID attribute. The private and the public key of a key pair
+/// share the same CKA_ID.
+pub struct Pkcs11Client {
+    session: Mutex<Session>,
+}
+
+impl Pkcs11Client {
+    /// Load the PKCS#11 module, open a session on the configured slot and log in
+    /// if a PIN is provided
+    pub fn new(config: &Pkcs11Config) -> Result<Self> {
+        let pkcs11 = module(config.module())?;
+        let slot = Slot::try_from(config.slot).map_err(Error::from)?;
+        let session = pkcs11.open_rw_session(slot).map_err(Error::from)?;
+
+        if let Some(pin) = &config.pin {
+            match session.login(UserType::User, Some(&AuthPin::new(pin.clone()))) {
+                // the session may belong to an application which already logged in
+                Ok(()) | Err(CryptokiError::Pkcs11(RvError::UserAlreadyLoggedIn, _)) => (),
+                Err(e) => return Err(Error::from(e).into()),
+            }
+        }
+        log::debug!(module = %config.module.display(), slot = %config.slot, "opened a pkcs#11 session");
+        Ok(Self {
+            session: Mutex::new(session),
+        })
+    }
+
+    /// Return the handles of all the EC private keys of the token
+    pub fn list_keys(&self) -> Result<Vec<SigningSecretKeyHandle>> {
+        let session = self.lock()?;
+        let template = [
+            Attribute::Class(ObjectClass::PRIVATE_KEY),
+            Attribute::KeyType(KeyType::EC),
+        ];
+        let mut keys = vec![];
+        for object in session.find_objects(&template).map_err(Error::from)? {
+            match get_attribute(&session, object, AttributeType::Id)? {
+                Some(Attribute::Id(id)) => keys.push(to_handle(id)),
+                _ => log::warn!("skipping a private key without an id"),
+            }
+        }
+        Ok(keys)
+    }
+
+    /// Generate a new P-256 key pair on the token and return the handle of its private key
+    pub fn create_key(&self) -> Result<SigningSecretKeyHandle> {
+        let session = self.lock()?;
+
+        // the id is printable so that it can be passed as a key id on the command line
+        let id = hex::encode(rand::random::<[u8; KEY_ID_LENGTH]>()).into_bytes();
+        let label = b"ockam".to_vec();
+        let public_template = [
+            Attribute::EcParams(P256_EC_PARAMS.to_vec()),
+            Attribute::Id(id.clone()),
+            Attribute::Label(label.clone()),
+            Attribute::Token(true),
+            Attribute::Verify(true),
+        ];
+        let private_template = [
+            Attribute::Id(id.clone()),
+            Attribute::Label(label),
+            Attribute::Token(true),
+            Attribute::Private(true),
+            Attribute::Sensitive(true),
+            Attribute::Extractable(false),
+            Attribute::Sign(true),
+        ];
+        session
+            .generate_key_pair(
+                &Mechanism::EccKeyPairGen,
+                &public_template,
+                &private_template,
+            )
+            .map_err(Error::from)?;
+        log::debug!(key = %String::from_utf8_lossy(&id), "generated a key pair");
+        Ok(to_handle(id))
+    }
+
+    /// Export the public key of a key pair
+    pub fn public_key(&self, key: &SigningSecretKeyHandle) -> Result<VerifyingPublicKey> {
+        let session = self.lock()?;
+        let public_key =
+            find_key(&session, ObjectClass::PUBLIC_KEY, key)?.ok_or(Error::KeyNotFound)?;
+        let ec_point = match get_attribute(&session, public_key, AttributeType::EcPoint)? {
+            Some(Attribute::EcPoint(ec_point)) => ec_point,
+            _ => return Err(Error::MissingAttribute(AttributeType::EcPoint).into()),
+        };
+        Ok(VerifyingPublicKey::ECDSASHA256CurveP256(
+            ECDSASHA256CurveP256PublicKey(parse_ec_point(&ec_point)?),
+        ))
+    }
+
+    /// Sign a message with a private key. The message is hashed with SHA-256 before being signed
+    pub fn sign(&self, key: &SigningSecretKeyHandle, message: &[u8]) -> Result<Signature> {
+        let session = self.lock()?;
+        let private_key =
+            find_key(&session, ObjectClass::PRIVATE_KEY, key)?.ok_or(Error::KeyNotFound)?;
+        let digest = Sha256::digest(message);
+        let signature = session
+            .sign(&Mechanism::Ecdsa, private_key, &digest)
+            .map_err(Error::from)?;
+        // the signature is the concatenation of r and s
+        let signature: [u8; ECDSA_SHA256_CURVEP256_SIGNATURE_LENGTH] =
+            signature.try_into().map_err(|_| Error::InvalidSignature)?;
+        Ok(Signature::ECDSASHA256CurveP256(
+            ECDSASHA256CurveP256Signature(signature),
+        ))
+    }
+
+    /// Delete a key pair. Return false if the private key could not be found
+    pub fn delete_key(&self, key: &SigningSecretKeyHandle) -> Result<bool> {
+        let session = self.lock()?;
+        let private_key = find_key(&session, ObjectClass::PRIVATE_KEY, key)?;
+        let public_key = find_key(&session, ObjectClass::PUBLIC_KEY, key)?;
+        for object in private_key.iter().chain(public_key.iter()) {
+            session.destroy_object(*object).map_err(Error::from)?;
+        }
+        Ok(private_key.is_some())
+    }
+
+    fn lock(&self) -> Result<MutexGuard<'_, Session>> {
+        Ok(self.session.lock().map_err(|_| Error::SessionLock)?)
+    }
+}
+
+/// Return the context of a PKCS#11 module, loading and initializing it the first time.
+///
+/// The contexts are kept for the lifetime of the process: a module is finalized when its
+/// context is dropped, which would close the sessions of all the other clients of that module.
+fn module(path: &Path) -> Result<Pkcs11> {
+    static MODULES: OnceLock<Mutex<HashMap<PathBuf, Pkcs11>>> = OnceLock::new();
+    let mut modules = MODULES
+        .get_or_init(Default::default)
+        .lock()
+        .map_err(|_| Error::SessionLock)?;
+    if let Some(pkcs11) = modules.get(path) {
+        return Ok(pkcs11.clone());
+    }
+
+    let pkcs11 = Pkcs11::new(path).map_err(|e| Error::Load {
+        module: path.display().to_string(),
+        error: e.to_string(),
+    })?;
+    match pkcs11.initialize(CInitializeArgs::OsThreads) {
+        // the module can also be used by another library of this process
+        Ok(()) | Err(CryptokiError::Pkcs11(RvError::CryptokiAlreadyInitialized, _)) => (),
+        Err(e) => return Err(Error::from(e).into()),
+    }
+    modules.insert(path.to_path_buf(), pkcs11.clone());
+    Ok(pkcs11)
+}
+
+/// Find the private or public key object with the CKA_ID given by the key handle
+fn find_key(
+    session: &Session,
+    class: ObjectClass,
+    key: &SigningSecretKeyHandle,
+) -> Result<Option<ObjectHandle>> {
+    let id = match key {
+        SigningSecretKeyHandle::ECDSASHA256CurveP256(handle) => handle.value(),
+        SigningSecretKeyHandle::EdDSACurve25519(_) => return Err(Error::UnsupportedKeyType.into()),
+    };
+    let template = [Attribute::Class(class), Attribute::Id(id.clone())];
+    Ok(session
+        .find_objects(&template)
+        .map_err(Error::from)?
+        .first()
+        .copied())
+}
+
+/// Return the value of an attribute, or None if the object does not have that attribute
+fn get_attribute(
+    session: &Session,
+    object: ObjectHandle,
+    attribute_type: AttributeType,
+) -> Result<Option<Attribute>> {
+    Ok(session
+        .get_attributes(object, &[attribute_type])
+        .map_err(Error::from)?
+        .pop())
+}
+
+fn to_handle(id: Vec<u8>) -> SigningSecretKeyHandle {
+    SigningSecretKeyHandle::ECDSASHA256CurveP256(HandleToSecret::new(id))
+}
+
+/// CKA_EC_POINT is a DER-encoded OCTET STRING containing the uncompressed point.
+/// Some modules return the raw point instead, so both encodings are accepted
+fn parse_ec_point(ec_point: &[u8]) -> Result<[u8; ECDSA_SHA256_CURVEP256_PUBLIC_KEY_LENGTH]> {
+    let point = match ec_point {
+        [0x04, 0x41, point @ ..] if point.len() == ECDSA_SHA256_CURVEP256_PUBLIC_KEY_LENGTH => {
+            point
+        }
+        point => point,
+    };
+    p256::PublicKey::from_sec1_bytes(point).map_err(|_| Error::InvalidPublicKey)?;
+    Ok(point.try_into().map_err(|_| Error::InvalidPublicKey)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ec_point() {
+        let point = p256::SecretKey::from_slice(&[1; 32])
+            .unwrap()
+            .public_key()
+            .to_sec1_bytes()
+            .to_vec();
+
+        let mut der = vec![0x04, 0x41];
+        der.extend_from_slice(&point);
+        assert_eq!(parse_ec_point(&der).unwrap().to_vec(), point);
+        assert_eq!(parse_ec_point(&point).unwrap().to_vec(), point);
+
+        assert!(parse_ec_point(&der[..40]).is_err());
+        assert!(parse_ec_point(&[0x04; 65]).is_err());
+    }
+
+    #[test]
+    fn test_load_missing_module() {
+        let config = Pkcs11Config::new("/does/not/exist/libpkcs11.so", 0);
+        assert!(Pkcs11Client::new(&config).is_err());
+    }
+}
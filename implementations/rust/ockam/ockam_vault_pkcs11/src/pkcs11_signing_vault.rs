@@ -0,0 +1,185 @@
+use crate::error::Error;
+use crate::pkcs11_client::{Pkcs11Client, Pkcs11Config};
+use ockam_core::compat::sync::{Arc, RwLock};
+use ockam_core::{async_trait, Result};
+use ockam_vault::{
+    Signature, SigningKeyType, SigningSecretKeyHandle, VaultError, VaultForSigning,
+    VerifyingPublicKey,
+};
+use tracing::error;
+
+struct Pkcs11KeyPair {
+    key: SigningSecretKeyHandle,
+    public_key: VerifyingPublicKey,
+}
+
+/// Security module implementation using a token accessed via a PKCS#11 module (an HSM for example)
+///
+/// Only ECDSA P-256 keys are supported. The private keys never leave the token.
+pub struct Pkcs11SigningVault {
+    client: Arc<Pkcs11Client>,
+    // Store mapping from PublicKey to key id in memory
+    // This is fetched at the Vault initialization
+    // and is updated locally during add/delete operations
+    // WARNING: The assumption is that there is no concurrent access to the same keys from
+    // different places.
+    keys: Arc<RwLock<Vec<Pkcs11KeyPair>>>,
+}
+
+impl Pkcs11SigningVault {
+    /// Create a new PKCS#11 security module
+    pub async fn create(config: Pkcs11Config) -> Result<Self> {
+        let (client, key_pairs) = blocking(move || {
+            let client = Pkcs11Client::new(&config)?;
+
+            let mut key_pairs: Vec<Pkcs11KeyPair> = vec![];
+            // Fetch list of all keys, then fetch the public key for each key
+            for key in client.list_keys()? {
+                match client.public_key(&key) {
+                    Ok(public_key) => key_pairs.push(Pkcs11KeyPair { key, public_key }),
+                    // The token can contain keys on other curves or private keys without a
+                    // corresponding public key object. Those keys are skipped
+                    Err(err) => error!("Error exporting public key: {err}"),
+                }
+            }
+            Ok((client, key_pairs))
+        })
+        .await?;
+
+        Ok(Self {
+            client: Arc::new(client),
+            keys: Arc::new(RwLock::new(key_pairs)),
+        })
+    }
+
+    /// Return list of all keys
+    pub fn keys(&self) -> Vec<SigningSecretKeyHandle> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .map(|x| x.key.clone())
+            .collect()
+    }
+
+    /// Return number of keys
+    pub async fn number_of_keys(&self) -> Result<usize> {
+        Ok(self.keys.read().unwrap().len())
+    }
+
+    /// Execute a call to the PKCS#11 module on a thread where blocking is allowed
+    async fn with_client<T, F>(&self, f: F) -> Result<T>
+    where
+        F: FnOnce(&Pkcs11Client) -> Result<T> + Send + 'static,
+        T: Send + 'static,
+    {
+        let client = self.client.clone();
+        blocking(move || f(&client)).await
+    }
+}
+
+/// The PKCS#11 calls block the current thread until the token answers,
+/// which can take a while for a network HSM
+async fn blocking<T, F>(f: F) -> Result<T>
+where
+    F: FnOnce() -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(|e| Error::Task(e.to_string()))?
+}
+
+#[async_trait]
+impl VaultForSigning for Pkcs11SigningVault {
+    async fn sign(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+        data: &[u8],
+    ) -> Result<Signature> {
+        let key = signing_secret_key_handle.clone();
+        let data = data.to_vec();
+        self.with_client(move |client| client.sign(&key, &data))
+            .await
+    }
+
+    async fn generate_signing_secret_key(
+        &self,
+        signing_key_type: SigningKeyType,
+    ) -> Result<SigningSecretKeyHandle> {
+        if signing_key_type != SigningKeyType::ECDSASHA256CurveP256 {
+            return Err(VaultError::InvalidKeyType.into());
+        }
+
+        let (key, public_key) = self
+            .with_client(|client| {
+                let key = client.create_key()?;
+                let public_key = client.public_key(&key)?;
+                Ok((key, public_key))
+            })
+            .await?;
+
+        self.keys.write().unwrap().push(Pkcs11KeyPair {
+            key: key.clone(),
+            public_key,
+        });
+
+        Ok(key)
+    }
+
+    async fn get_verifying_public_key(
+        &self,
+        signing_secret_key_handle: &SigningSecretKeyHandle,
+    ) -> Result<VerifyingPublicKey> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .find_map(|x| {
+                if &x.key == signing_secret_key_handle {
+                    Some(x.public_key.clone())
+                } else {
+                    None
+                }
+            })
+            .ok_or(Error::KeyNotFound.into())
+    }
+
+    async fn get_secret_key_handle(
+        &self,
+        verifying_public_key: &VerifyingPublicKey,
+    ) -> Result<SigningSecretKeyHandle> {
+        self.keys
+            .read()
+            .unwrap()
+            .iter()
+            .find_map(|x| {
+                if &x.public_key == verifying_public_key {
+                    Some(x.key.clone())
+                } else {
+                    None
+                }
+            })
+            .ok_or(Error::KeyNotFound.into())
+    }
+
+    async fn delete_signing_secret_key(
+        &self,
+        signing_secret_key_handle: SigningSecretKeyHandle,
+    ) -> Result<bool> {
+        let key = signing_secret_key_handle.clone();
+        if self
+            .with_client(move |client| client.delete_key(&key))
+            .await?
+        {
+            self.keys
+                .write()
+                .unwrap()
+                .retain(|x| x.key != signing_secret_key_handle);
+
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+}
@@ -0,0 +1,78 @@
+use ockam_core::Result;
+use ockam_vault::{
+    SigningKeyType, SoftwareVaultForVerifyingSignatures, VaultForSigning,
+    VaultForVerifyingSignatures,
+};
+use ockam_vault_pkcs11::{Pkcs11Config, Pkcs11SigningVault};
+
+/// These tests need to be executed with a PKCS#11 token, for example a SoftHSM token,
+/// and the following environment variables
+/// OCKAM_PKCS11_TEST_MODULE: path to the PKCS#11 module
+/// OCKAM_PKCS11_TEST_SLOT: slot of the token
+/// OCKAM_PKCS11_TEST_PIN: user PIN of the token
+
+#[tokio::test]
+#[ignore]
+async fn test_sign_verify() -> Result<()> {
+    let signing_vault = Pkcs11SigningVault::create(config()).await?;
+    let handle = signing_vault
+        .generate_signing_secret_key(SigningKeyType::ECDSASHA256CurveP256)
+        .await?;
+    let message = b"hello world";
+    let signature = signing_vault.sign(&handle, message.as_slice()).await?;
+    let public_key = signing_vault.get_verifying_public_key(&handle).await?;
+
+    let verifier = SoftwareVaultForVerifyingSignatures::new();
+    assert!(
+        verifier
+            .verify_signature(&public_key, message, &signature)
+            .await?
+    );
+
+    signing_vault.delete_signing_secret_key(handle).await?;
+
+    Ok(())
+}
+
+#[tokio::test]
+#[ignore]
+async fn test_keys_management() -> Result<()> {
+    let signing_vault = Pkcs11SigningVault::create(config()).await?;
+
+    let number_of_keys1 = signing_vault.number_of_keys().await?;
+
+    let handle = signing_vault
+        .generate_signing_secret_key(SigningKeyType::ECDSASHA256CurveP256)
+        .await?;
+
+    let number_of_keys2 = signing_vault.number_of_keys().await?;
+    assert_eq!(number_of_keys1 + 1, number_of_keys2);
+
+    let public_key = signing_vault.get_verifying_public_key(&handle).await?;
+
+    let handle2 = signing_vault.get_secret_key_handle(&public_key).await?;
+    assert_eq!(handle, handle2);
+
+    // the key is found again when the vault is re-created
+    let signing_vault2 = Pkcs11SigningVault::create(config()).await?;
+    assert_eq!(
+        signing_vault2.get_verifying_public_key(&handle).await?,
+        public_key
+    );
+
+    signing_vault.delete_signing_secret_key(handle).await?;
+    let number_of_keys3 = signing_vault.number_of_keys().await?;
+    assert_eq!(number_of_keys2, number_of_keys3 + 1);
+
+    Ok(())
+}
+
+fn config() -> Pkcs11Config {
+    let module = std::env::var("OCKAM_PKCS11_TEST_MODULE").expect("OCKAM_PKCS11_TEST_MODULE");
+    let slot = std::env::var("OCKAM_PKCS11_TEST_SLOT")
+        .expect("OCKAM_PKCS11_TEST_SLOT")
+        .parse()
+        .expect("the slot should be a number");
+    let pin = std::env::var("OCKAM_PKCS11_TEST_PIN").expect("OCKAM_PKCS11_TEST_PIN");
+    Pkcs11Config::new(module, slot).with_pin(pin)
+}